@@ -0,0 +1,416 @@
+//! Grouping rows by some columns and summarizing others, like an Excel pivot table (`--pivot`),
+//! and footer rows summarizing whole columns (`--totals`)
+
+use crate::workbook::{CellValue, column_selection, compare_cells, find_column};
+use anyhow::{Result, bail};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// How a value column's cells are combined within each group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,
+    Average,
+    Count,
+    Min,
+    Max,
+}
+
+impl Aggregation {
+    /// "sum", "avg" (or "average", "mean"), "count", "min", or "max"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sum" => Some(Aggregation::Sum),
+            "avg" | "average" | "mean" => Some(Aggregation::Average),
+            "count" => Some(Aggregation::Count),
+            "min" => Some(Aggregation::Min),
+            "max" => Some(Aggregation::Max),
+            _ => None,
+        }
+    }
+
+    /// Short name, as written after a value column ("Amount:avg")
+    pub fn name(self) -> &'static str {
+        match self {
+            Aggregation::Sum => "sum",
+            Aggregation::Average => "avg",
+            Aggregation::Count => "count",
+            Aggregation::Min => "min",
+            Aggregation::Max => "max",
+        }
+    }
+
+    /// Capitalized name, as Excel labels it: "Sum", "Average", ...
+    pub fn label(self) -> &'static str {
+        match self {
+            Aggregation::Sum => "Sum",
+            Aggregation::Average => "Average",
+            Aggregation::Count => "Count",
+            Aggregation::Min => "Min",
+            Aggregation::Max => "Max",
+        }
+    }
+
+    /// Header of the result column, as Excel writes it: "Sum of Amount"
+    pub fn header(self, column: &str) -> String {
+        format!("{} of {column}", self.label())
+    }
+
+    /// Combine one group's cells. Count counts non-empty cells; the others use only the
+    /// numbers and give an empty cell when there are none. Whole numbers stay integers
+    /// for sum, min, and max.
+    fn apply(self, cells: &[&CellValue]) -> CellValue {
+        if self == Aggregation::Count {
+            let filled = cells.iter().filter(|cell| !is_blank(cell)).count();
+            return CellValue::Int(filled as i64);
+        }
+        let ints: Option<Vec<i64>> = cells
+            .iter()
+            .filter(|cell| cell.is_numeric())
+            .map(|cell| match cell {
+                CellValue::Int(i) => Some(*i),
+                _ => None,
+            })
+            .collect();
+        let numbers: Vec<f64> = cells
+            .iter()
+            .filter_map(|cell| match cell {
+                CellValue::Int(i) => Some(*i as f64),
+                CellValue::Float(f) => Some(*f),
+                _ => None,
+            })
+            .collect();
+        if numbers.is_empty() {
+            return CellValue::Empty;
+        }
+
+        match (self, ints) {
+            (Aggregation::Sum, Some(ints)) => ints
+                .iter()
+                .try_fold(0i64, |total, &i| total.checked_add(i))
+                .map(CellValue::Int)
+                .unwrap_or_else(|| CellValue::Float(numbers.iter().sum())),
+            (Aggregation::Min, Some(ints)) => CellValue::Int(ints.into_iter().min().unwrap_or(0)),
+            (Aggregation::Max, Some(ints)) => CellValue::Int(ints.into_iter().max().unwrap_or(0)),
+            (Aggregation::Sum, None) => CellValue::Float(numbers.iter().sum()),
+            (Aggregation::Min, None) => {
+                CellValue::Float(numbers.iter().copied().fold(f64::INFINITY, f64::min))
+            }
+            (Aggregation::Max, None) => {
+                CellValue::Float(numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+            }
+            _ => CellValue::Float(numbers.iter().sum::<f64>() / numbers.len() as f64),
+        }
+    }
+}
+
+fn is_blank(cell: &CellValue) -> bool {
+    match cell {
+        CellValue::Empty => true,
+        CellValue::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+/// Splits a `--values` entry into its column and aggregation: "Amount:avg", or "Amount"
+/// for a sum. Letter ranges keep their colon ("E:G" sums columns E to G).
+pub fn value_spec(spec: &str) -> (&str, Aggregation) {
+    match spec.trim().rsplit_once(':') {
+        Some((column, name)) => match Aggregation::parse(name) {
+            Some(aggregation) => (column.trim(), aggregation),
+            None => (spec.trim(), Aggregation::Sum),
+        },
+        None => (spec.trim(), Aggregation::Sum),
+    }
+}
+
+/// Groups `rows` by the `group_by` columns and aggregates each `values` entry
+/// ("Amount:sum", see `value_spec`) per group, returning the new headers and rows.
+/// Groups come out in sort order; without `values` each group's rows are counted, and
+/// without `group_by` there's a single row of totals. Columns are picked as with
+/// `--columns`; `first_col` is the sheet column of `headers[0]`.
+pub fn pivot(
+    headers: &[String],
+    first_col: usize,
+    rows: &[Vec<CellValue>],
+    group_by: &[String],
+    values: &[String],
+) -> Result<(Vec<String>, Vec<Vec<CellValue>>)> {
+    if group_by.is_empty() && values.is_empty() {
+        bail!(
+            "A pivot needs columns to group by (--rows), columns to aggregate (--values), or both"
+        );
+    }
+    let keys = column_selection(headers, first_col, group_by)?;
+    let mut measures = Vec::new();
+    for spec in values {
+        let (column, aggregation) = value_spec(spec);
+        for idx in column_selection(headers, first_col, &[column.to_string()])? {
+            measures.push((idx, aggregation));
+        }
+    }
+    Ok(pivot_columns(headers, rows, &keys, &measures))
+}
+
+/// How often each value of `column` occurs (`--count-by`): rows of value, count, and
+/// percentage of all rows, most frequent first and ties in sort order
+pub fn value_counts(
+    headers: &[String],
+    first_col: usize,
+    rows: &[Vec<CellValue>],
+    column: &str,
+) -> Result<(Vec<String>, Vec<Vec<CellValue>>)> {
+    let idx = find_column(headers, first_col, column)?;
+    let (mut headers, mut counts) = pivot_columns(headers, rows, &[idx], &[]);
+    let count = |row: &[CellValue]| match row.get(1) {
+        Some(CellValue::Int(n)) => *n,
+        _ => 0,
+    };
+    counts.sort_by_key(|row| std::cmp::Reverse(count(row)));
+    headers.push("Percent".to_string());
+    for row in &mut counts {
+        let percent = count(row) as f64 * 100.0 / rows.len().max(1) as f64;
+        row.push(CellValue::Float((percent * 100.0).round() / 100.0));
+    }
+    Ok((headers, counts))
+}
+
+/// Footer rows for `--totals`, one per aggregation, over the numeric columns of `rows`:
+/// those with at least one number and nothing but numbers and blanks. Other columns stay
+/// empty, except that a non-numeric first column holds the aggregation's label ("Sum").
+pub fn totals(
+    rows: &[Vec<CellValue>],
+    width: usize,
+    aggregations: &[Aggregation],
+) -> Vec<Vec<CellValue>> {
+    let empty = CellValue::Empty;
+    let columns: Vec<Vec<&CellValue>> = (0..width)
+        .map(|col| {
+            rows.iter()
+                .map(|row| row.get(col).unwrap_or(&empty))
+                .collect()
+        })
+        .collect();
+    let numeric: Vec<bool> = columns
+        .iter()
+        .map(|cells| {
+            cells.iter().any(|cell| cell.is_numeric())
+                && cells.iter().all(|cell| is_blank(cell) || cell.is_numeric())
+        })
+        .collect();
+    aggregations
+        .iter()
+        .map(|&aggregation| {
+            columns
+                .iter()
+                .zip(&numeric)
+                .enumerate()
+                .map(|(col, (cells, &numeric))| match (numeric, col) {
+                    (true, _) => aggregation.apply(cells),
+                    (false, 0) => CellValue::String(aggregation.label().to_string()),
+                    (false, _) => CellValue::Empty,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `pivot` with the columns already resolved: group by the `keys` columns and aggregate
+/// each (column, aggregation) of `measures`
+pub fn pivot_columns(
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    keys: &[usize],
+    measures: &[(usize, Aggregation)],
+) -> (Vec<String>, Vec<Vec<CellValue>>) {
+    // Groups in first-seen order, each with its key cells and row indices
+    let mut groups: Vec<(Vec<CellValue>, Vec<usize>)> = Vec::new();
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        let key: Vec<CellValue> = keys
+            .iter()
+            .map(|&k| row.get(k).cloned().unwrap_or(CellValue::Empty))
+            .collect();
+        match index.entry(key.iter().map(CellValue::to_raw_string).collect()) {
+            Entry::Occupied(entry) => groups[*entry.get()].1.push(i),
+            Entry::Vacant(entry) => {
+                entry.insert(groups.len());
+                groups.push((key, vec![i]));
+            }
+        }
+    }
+    if keys.is_empty() && groups.is_empty() {
+        groups.push((Vec::new(), Vec::new()));
+    }
+    groups.sort_by(|(a, _), (b, _)| {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| compare_cells(Some(x), Some(y), false))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let header = |idx: usize| headers.get(idx).map(String::as_str).unwrap_or_default();
+    let mut pivot_headers: Vec<String> = keys.iter().map(|&k| header(k).to_string()).collect();
+    if measures.is_empty() {
+        pivot_headers.push("Count".to_string());
+    }
+    pivot_headers.extend(
+        measures
+            .iter()
+            .map(|&(idx, aggregation)| aggregation.header(header(idx))),
+    );
+    let empty = CellValue::Empty;
+    let pivot_rows = groups
+        .into_iter()
+        .map(|(mut key, members)| {
+            if measures.is_empty() {
+                key.push(CellValue::Int(members.len() as i64));
+            }
+            for &(idx, aggregation) in measures {
+                let cells: Vec<&CellValue> = members
+                    .iter()
+                    .map(|&i| rows[i].get(idx).unwrap_or(&empty))
+                    .collect();
+                key.push(aggregation.apply(&cells));
+            }
+            key
+        })
+        .collect();
+    (pivot_headers, pivot_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> CellValue {
+        CellValue::String(value.to_string())
+    }
+
+    #[test]
+    fn test_pivot() {
+        let headers: Vec<String> = ["Region", "Product", "Amount"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let rows = vec![
+            vec![s("West"), s("Pens"), CellValue::Int(10)],
+            vec![s("East"), s("Pens"), CellValue::Float(2.5)],
+            vec![s("West"), s("Ink"), CellValue::Int(30)],
+            vec![s("East"), s("Ink"), s("n/a")],
+        ];
+        let spec =
+            |items: &[&str]| -> Vec<String> { items.iter().map(|i| i.to_string()).collect() };
+
+        let (h, r) = pivot(
+            &headers,
+            0,
+            &rows,
+            &spec(&["Region"]),
+            &spec(&["Amount:sum", "C:count", "Amount:max"]),
+        )
+        .unwrap();
+        assert_eq!(
+            h,
+            [
+                "Region",
+                "Sum of Amount",
+                "Count of Amount",
+                "Max of Amount"
+            ]
+        );
+        assert_eq!(
+            r,
+            vec![
+                vec![
+                    s("East"),
+                    CellValue::Float(2.5),
+                    CellValue::Int(2),
+                    CellValue::Float(2.5)
+                ],
+                vec![
+                    s("West"),
+                    CellValue::Int(40),
+                    CellValue::Int(2),
+                    CellValue::Int(30)
+                ],
+            ]
+        );
+
+        // Without values, rows are counted; without grouping, there's one total row
+        let (h, r) = pivot(&headers, 0, &rows, &spec(&["product"]), &[]).unwrap();
+        assert_eq!(h, ["Product", "Count"]);
+        assert_eq!(r[0], vec![s("Ink"), CellValue::Int(2)]);
+        let (_, r) = pivot(&headers, 0, &rows, &[], &spec(&["Amount:avg"])).unwrap();
+        assert_eq!(r, vec![vec![CellValue::Float(42.5 / 3.0)]]);
+
+        assert!(pivot(&headers, 0, &rows, &[], &[]).is_err());
+        assert!(pivot(&headers, 0, &rows, &spec(&["Missing"]), &[]).is_err());
+    }
+
+    #[test]
+    fn test_value_counts() {
+        let headers = vec!["Status".to_string()];
+        let rows: Vec<Vec<CellValue>> = ["open", "closed", "open", "", "closed", "open"]
+            .iter()
+            .map(|status| vec![s(status)])
+            .collect();
+        let (h, r) = value_counts(&headers, 0, &rows, "status").unwrap();
+        assert_eq!(h, ["Status", "Count", "Percent"]);
+        assert_eq!(
+            r,
+            vec![
+                vec![s("open"), CellValue::Int(3), CellValue::Float(50.0)],
+                vec![s("closed"), CellValue::Int(2), CellValue::Float(33.33)],
+                vec![s(""), CellValue::Int(1), CellValue::Float(16.67)],
+            ]
+        );
+        assert!(value_counts(&headers, 0, &rows, "A:B").is_err());
+    }
+
+    #[test]
+    fn test_totals() {
+        let rows = vec![
+            vec![s("West"), CellValue::Int(10), s("a"), CellValue::Float(1.5)],
+            vec![
+                s("East"),
+                CellValue::Empty,
+                CellValue::Int(3),
+                CellValue::Int(2),
+            ],
+        ];
+        assert_eq!(
+            totals(&rows, 4, &[Aggregation::Sum, Aggregation::Max]),
+            vec![
+                vec![
+                    s("Sum"),
+                    CellValue::Int(10),
+                    CellValue::Empty,
+                    CellValue::Float(3.5)
+                ],
+                vec![
+                    s("Max"),
+                    CellValue::Int(10),
+                    CellValue::Empty,
+                    CellValue::Float(2.0)
+                ],
+            ]
+        );
+        // A numeric first column is totaled too, leaving no room for the label
+        let numbers = vec![vec![CellValue::Int(1)], vec![CellValue::Int(2)]];
+        assert_eq!(
+            totals(&numbers, 1, &[Aggregation::Average]),
+            vec![vec![CellValue::Float(1.5)]]
+        );
+    }
+
+    #[test]
+    fn test_value_spec() {
+        assert_eq!(value_spec("Amount:AVG"), ("Amount", Aggregation::Average));
+        assert_eq!(value_spec(" Amount "), ("Amount", Aggregation::Sum));
+        assert_eq!(value_spec("E:G"), ("E:G", Aggregation::Sum));
+        assert_eq!(value_spec("E:G:min"), ("E:G", Aggregation::Min));
+    }
+}
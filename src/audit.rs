@@ -0,0 +1,220 @@
+//! Formula audit (`--audit-formulas`): the formulas a review of a model should look at,
+//! those showing errors, reaching into other sheets or workbooks, calling volatile
+//! functions, or hiding numbers that belong in input cells
+
+use crate::formula::formula_parts;
+use crate::workbook::{CellValue, Workbook, cell_reference};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Functions Excel recalculates on every change in the workbook, whatever their inputs
+const VOLATILE: [&str; 9] = [
+    "NOW",
+    "TODAY",
+    "RAND",
+    "RANDBETWEEN",
+    "RANDARRAY",
+    "OFFSET",
+    "INDIRECT",
+    "CELL",
+    "INFO",
+];
+
+/// What an audit flags a formula for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Finding {
+    /// Its cached result is an error, or it holds an error literal like `#REF!`
+    Error,
+    /// It references another sheet of the workbook
+    OtherSheet,
+    /// It references another workbook
+    ExternalLink,
+    /// It calls a volatile function
+    Volatile,
+    /// It holds a number other than 0 and 1
+    Constant,
+}
+
+impl Finding {
+    /// Name in the text report, the same as in JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            Finding::Error => "error",
+            Finding::OtherSheet => "other_sheet",
+            Finding::ExternalLink => "external_link",
+            Finding::Volatile => "volatile",
+            Finding::Constant => "constant",
+        }
+    }
+}
+
+/// A formula flagged for one reason; a formula can be flagged for several
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub finding: Finding,
+    /// Where the formula is, as `Sheet!A1`
+    pub cell: String,
+    /// The formula, starting with `=`
+    pub formula: String,
+    /// The errors, sheets, workbooks, functions, or numbers found, comma-separated
+    pub detail: String,
+}
+
+/// The findings of a workbook's formulas, sheet by sheet in cell order
+#[derive(Debug, Default, Serialize)]
+pub struct FormulaAudit {
+    /// Formula cells examined
+    pub formulas: usize,
+    /// Formulas that couldn't be read (structured table references, array constants), so
+    /// only their results were checked for errors
+    pub unread: usize,
+    pub entries: Vec<AuditEntry>,
+}
+
+/// Audit every formula of every sheet
+pub fn audit_formulas(workbook: &mut Workbook) -> Result<FormulaAudit> {
+    let mut audit = FormulaAudit::default();
+    for sheet in workbook.sheet_names() {
+        let formulas = workbook.sheet_formulas(&sheet)?;
+        if formulas.is_empty() {
+            continue;
+        }
+        let values: HashMap<(usize, usize), CellValue> = workbook
+            .sheet_values(&sheet)?
+            .into_iter()
+            .map(|(row, col, value)| ((row, col), value))
+            .collect();
+        for (row, col, formula) in formulas {
+            let cell = cell_reference(&sheet, row, col);
+            let value = values.get(&(row, col));
+            audit.formulas += 1;
+            match audit_formula(&sheet, &cell, &formula, value) {
+                Some(entries) => audit.entries.extend(entries),
+                None => {
+                    audit.unread += 1;
+                    audit
+                        .entries
+                        .extend(error_entry(&cell, &formula, value, &[]));
+                }
+            }
+        }
+    }
+    Ok(audit)
+}
+
+/// The findings of one formula on `sheet` whose cached result is `value`; None if the
+/// formula can't be read
+pub fn audit_formula(
+    sheet: &str,
+    cell: &str,
+    formula: &str,
+    value: Option<&CellValue>,
+) -> Option<Vec<AuditEntry>> {
+    let parts = formula_parts(formula)?;
+    let entry = |finding, detail: Vec<String>| {
+        (!detail.is_empty()).then(|| AuditEntry {
+            finding,
+            cell: cell.to_string(),
+            formula: format!("={formula}"),
+            detail: detail.join(", "),
+        })
+    };
+    let (external, sheets): (Vec<String>, Vec<String>) = parts
+        .sheets
+        .into_iter()
+        .filter(|name| !name.eq_ignore_ascii_case(sheet))
+        .partition(|name| name.contains('['));
+    let volatile = parts
+        .functions
+        .into_iter()
+        .filter(|name| VOLATILE.contains(&name.as_str()))
+        .collect();
+    let constants = parts
+        .numbers
+        .into_iter()
+        .filter(|number| number != "0" && number != "1")
+        .collect();
+    Some(
+        [
+            error_entry(cell, formula, value, &parts.errors),
+            entry(Finding::OtherSheet, sheets),
+            entry(Finding::ExternalLink, external),
+            entry(Finding::Volatile, volatile),
+            entry(Finding::Constant, constants),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+    )
+}
+
+/// An error finding if the cached result is an error or the formula holds error literals
+fn error_entry(
+    cell: &str,
+    formula: &str,
+    value: Option<&CellValue>,
+    literals: &[String],
+) -> Option<AuditEntry> {
+    // The result as Excel shows it (`#DIV/0!`), then the literals as written
+    let mut errors = Vec::new();
+    if let Some(error @ CellValue::Error(_)) = value {
+        errors.push(error.to_raw_string());
+    }
+    errors.extend(
+        literals
+            .iter()
+            .map(|literal| format!("{literal} in formula")),
+    );
+    (!errors.is_empty()).then(|| AuditEntry {
+        finding: Finding::Error,
+        cell: cell.to_string(),
+        formula: format!("={formula}"),
+        detail: errors.join(", "),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_formula() {
+        let findings = |formula: &str, value: Option<&CellValue>| -> Vec<(Finding, String)> {
+            audit_formula("Model", "Model!C2", formula, value)
+                .unwrap()
+                .into_iter()
+                .map(|entry| (entry.finding, entry.detail))
+                .collect()
+        };
+        assert_eq!(
+            findings("B2*1.07+Rates!A1+Model!A1", None),
+            [
+                (Finding::OtherSheet, "Rates".to_string()),
+                (Finding::Constant, "1.07".to_string())
+            ]
+        );
+        assert_eq!(
+            findings("IF(TODAY()>A1,[1]Prices!B2,0)", None),
+            [
+                (Finding::ExternalLink, "[1]Prices".to_string()),
+                (Finding::Volatile, "TODAY".to_string())
+            ]
+        );
+        let error = CellValue::Error("#DIV/0!".to_string());
+        assert_eq!(
+            findings("A1/B1", Some(&error)),
+            [(Finding::Error, "#DIV/0!".to_string())]
+        );
+        assert_eq!(
+            findings("SUM(#REF!)", None),
+            [(Finding::Error, "#REF! in formula".to_string())]
+        );
+        assert!(findings("SUM(A1:A9)*1", None).is_empty());
+        assert_eq!(
+            audit_formula("Model", "Model!C2", "Table1[Amount]", None),
+            None
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Telling whether the terminal has a light or dark background, so `theme = "auto"` can
+//! start with a readable theme
+
+use std::time::Duration;
+
+/// Longest wait for the terminal to answer the background query
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// The terminal's background, asked with an OSC 11 query (the terminal must be in raw mode)
+/// or else read from COLORFGBG; None when neither tells
+pub fn detect() -> Option<Background> {
+    query_terminal().or_else(|| from_colorfgbg(&std::env::var("COLORFGBG").ok()?))
+}
+
+/// COLORFGBG is "fg;bg" (or "fg;default;bg") in ANSI color numbers, as set by rxvt,
+/// Konsole, and others: white (7) and the bright colors but dark gray (9-15) are light
+fn from_colorfgbg(value: &str) -> Option<Background> {
+    let bg: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+    match bg {
+        7 | 9..=15 => Some(Background::Light),
+        0..=6 | 8 => Some(Background::Dark),
+        _ => None,
+    }
+}
+
+/// The background from an OSC 11 reply, `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` ended by BEL or
+/// `ESC \`, with 1 to 4 hex digits per channel
+fn from_osc11_reply(reply: &str) -> Option<Background> {
+    let rgb = reply.split("]11;rgb:").nth(1)?;
+    let mut channels = rgb.split('/').map(|channel| {
+        let hex: String = channel
+            .chars()
+            .take_while(|c| c.is_ascii_hexdigit())
+            .collect();
+        let max = 16u32.checked_pow(hex.len() as u32)?.checked_sub(1)?;
+        let value = u32::from_str_radix(&hex, 16).ok()?;
+        (max > 0).then(|| value as f64 / max as f64)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Ask the terminal for its background color. The query is followed by a device
+/// attributes request, which every terminal answers, so terminals that ignore OSC 11
+/// don't hold up startup until the timeout.
+#[cfg(unix)]
+fn query_terminal() -> Option<Background> {
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").ok()?;
+    tty.flush().ok()?;
+
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 256];
+    // Read until the device attributes reply (ESC [ ? ... c) ends the answers
+    while !answered(&reply) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        let mut fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fd` is one valid pollfd for an open file descriptor
+        let ready = unsafe { libc::poll(&mut fd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            break;
+        }
+        match tty.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => reply.extend_from_slice(&buf[..n]),
+        }
+    }
+    from_osc11_reply(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+fn query_terminal() -> Option<Background> {
+    None
+}
+
+/// Whether the reply holds the device attributes answer that follows the OSC 11 one
+#[cfg(unix)]
+fn answered(reply: &[u8]) -> bool {
+    reply
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_detection() {
+        assert_eq!(from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("0;default;7"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("15;8"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("default"), None);
+
+        assert_eq!(
+            from_osc11_reply("\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62;22c"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            from_osc11_reply("\x1b]11;rgb:1e1e/1e1e/2e2e\x07"),
+            Some(Background::Dark)
+        );
+        assert_eq!(
+            from_osc11_reply("\x1b]11;rgb:fd/f6/e3\x1b\\"),
+            Some(Background::Light)
+        );
+        assert_eq!(from_osc11_reply("\x1b[?62;22c"), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Comparing sheet contents against a CSV baseline
+
+/// A cell whose value differs from the baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// 0-based position in the compared grid (row 0 is the header row)
+    pub row: usize,
+    pub col: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Parses CSV text (RFC 4180 quoting, LF or CRLF line endings) into rows of fields
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    crate::delimited::parse(text, ',')
+}
+
+/// Compares two grids cell by cell; missing cells count as empty.
+/// Values that both parse as numbers match when within `tolerance`.
+pub fn compare(actual: &[Vec<String>], expected: &[Vec<String>], tolerance: f64) -> Vec<Mismatch> {
+    let cell = |grid: &[Vec<String>], row: usize, col: usize| -> String {
+        grid.get(row)
+            .and_then(|r| r.get(col))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let height = actual.len().max(expected.len());
+    let mut mismatches = Vec::new();
+
+    for row in 0..height {
+        let width = [actual.get(row), expected.get(row)]
+            .iter()
+            .map(|r| r.map_or(0, |r| r.len()))
+            .max()
+            .unwrap_or(0);
+        for col in 0..width {
+            let a = cell(actual, row, col);
+            let e = cell(expected, row, col);
+            if !values_match(&a, &e, tolerance) {
+                mismatches.push(Mismatch {
+                    row,
+                    col,
+                    expected: e,
+                    actual: a,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+fn values_match(actual: &str, expected: &str, tolerance: f64) -> bool {
+    if actual == expected {
+        return true;
+    }
+    match (actual.trim().parse::<f64>(), expected.trim().parse::<f64>()) {
+        (Ok(a), Ok(e)) => (a - e).abs() <= tolerance,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|r| r.iter().map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("Name,Note\r\nAlice,\"Hello, \"\"world\"\"\"\nBob,\"two\nlines\"\n");
+        assert_eq!(
+            rows,
+            grid(&[
+                &["Name", "Note"],
+                &["Alice", "Hello, \"world\""],
+                &["Bob", "two\nlines"],
+            ])
+        );
+        assert_eq!(parse_csv("a,b"), grid(&[&["a", "b"]]));
+        assert_eq!(parse_csv("a,\n"), grid(&[&["a", ""]]));
+    }
+
+    #[test]
+    fn test_compare_with_tolerance() {
+        let expected = grid(&[&["Item", "Total"], &["a", "10.00"], &["b", "2.5"]]);
+        let actual = grid(&[&["Item", "Total"], &["a", "10.004"], &["b", "2.6"], &["c"]]);
+
+        let mismatches = compare(&actual, &expected, 0.01);
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch {
+                    row: 2,
+                    col: 1,
+                    expected: "2.5".to_string(),
+                    actual: "2.6".to_string(),
+                },
+                Mismatch {
+                    row: 3,
+                    col: 0,
+                    expected: String::new(),
+                    actual: "c".to_string(),
+                },
+            ]
+        );
+        assert_eq!(compare(&actual[..3], &expected, 0.0).len(), 2);
+        assert!(compare(&expected, &expected, 0.0).is_empty());
+    }
+}
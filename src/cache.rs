@@ -0,0 +1,495 @@
+//! On-disk cache of parsed sheets, so reopening a large workbook skips parsing its XML.
+//! Entries are keyed by a hash of the file's contents plus the sheet name, stored in a
+//! compact binary format, and evicted oldest-first once the cache outgrows its size limit.
+//! Off until [`enable`] is called.
+
+use calamine::{Cell, CellErrorType, CellType, Data, ExcelDateTime, ExcelDateTimeType, Range};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Bumped whenever the entry layout changes, so stale entries read as misses
+const VERSION: u32 = 1;
+const MAGIC: &[u8; 4] = b"XLKC";
+/// Sheets smaller than this parse faster than a cache lookup pays off
+const MIN_CELLS: usize = 50_000;
+
+struct Settings {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+/// Cache sheets in `dir`, keeping it under `max_bytes`. Only the first call has an effect.
+pub fn enable(dir: PathBuf, max_bytes: u64) {
+    let _ = SETTINGS.set(Settings { dir, max_bytes });
+}
+
+/// `~/.cache/xleak` (or the platform's cache directory)
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("xleak"))
+}
+
+/// What part of a sheet an entry holds
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Part {
+    Values,
+    Formulas,
+}
+
+/// Hash of a file's contents identifying its entries; None when caching is off
+pub(crate) fn file_key(path: &Path) -> Option<String> {
+    SETTINGS.get()?;
+    let mut file = File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).ok()?;
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Whether parsing a sheet this size is slow enough to be worth an entry
+pub(crate) fn worth_caching<T: CellType>(range: &Range<T>) -> bool {
+    let (rows, cols) = range.get_size();
+    worth_caching_area(rows * cols)
+}
+
+/// Like [`worth_caching`] for a sheet whose cells span `area` cells
+pub(crate) fn worth_caching_area(area: usize) -> bool {
+    area >= MIN_CELLS
+}
+
+/// Whether a sheet has an entry (which may still turn out unreadable)
+pub(crate) fn contains(key: &str, sheet: &str, part: Part) -> bool {
+    SETTINGS
+        .get()
+        .is_some_and(|settings| entry_path(&settings.dir, key, sheet, part).exists())
+}
+
+/// The cached range for a sheet, if there is a readable entry
+pub(crate) fn load<T: Entry>(key: &str, sheet: &str, part: Part) -> Option<Range<T>> {
+    load_with(key, sheet, part, read_range)
+}
+
+/// The cached non-empty cells of a sheet, at their absolute positions, without laying
+/// them out as a range
+pub(crate) fn load_cells<T: Entry>(key: &str, sheet: &str, part: Part) -> Option<Vec<Cell<T>>> {
+    load_with(key, sheet, part, read_cells)
+}
+
+fn load_with<R>(
+    key: &str,
+    sheet: &str,
+    part: Part,
+    read: impl FnOnce(&mut BufReader<File>) -> io::Result<R>,
+) -> Option<R> {
+    let settings = SETTINGS.get()?;
+    let path = entry_path(&settings.dir, key, sheet, part);
+    let mut input = BufReader::new(File::open(&path).ok()?);
+    match read(&mut input) {
+        Ok(entry) => {
+            // Mark as recently used, so eviction keeps it
+            let _ = input.get_ref().set_modified(SystemTime::now());
+            Some(entry)
+        }
+        Err(_) => {
+            let _ = fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+/// Save a sheet's range, then evict old entries over the size limit. Failures are
+/// ignored: the cache only ever saves work.
+pub(crate) fn store<T: Entry>(key: &str, sheet: &str, part: Part, range: &Range<T>) {
+    store_with(key, sheet, part, |out| write_range(out, range));
+}
+
+/// Like [`store`] for a sheet's non-empty cells, at their absolute positions; the entry
+/// is the same as for the range they make up
+pub(crate) fn store_cells<T: Entry>(key: &str, sheet: &str, part: Part, cells: &[Cell<T>]) {
+    store_with(key, sheet, part, |out| write_cells(out, cells));
+}
+
+fn store_with(
+    key: &str,
+    sheet: &str,
+    part: Part,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) {
+    let Some(settings) = SETTINGS.get() else {
+        return;
+    };
+    let path = entry_path(&settings.dir, key, sheet, part);
+    if fs::create_dir_all(&settings.dir).is_err() {
+        return;
+    }
+    // Write next to the entry and rename, so readers never see half an entry
+    let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = File::create(&partial).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        write(&mut out)?;
+        out.flush()
+    });
+    if written.and_then(|_| fs::rename(&partial, &path)).is_err() {
+        let _ = fs::remove_file(&partial);
+        return;
+    }
+    evict(&settings.dir, settings.max_bytes);
+}
+
+fn entry_path(dir: &Path, key: &str, sheet: &str, part: Part) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    hasher.update([0]);
+    hasher.update(sheet);
+    let name = format!("{:x}", hasher.finalize());
+    let part = match part {
+        Part::Values => "values",
+        Part::Formulas => "formulas",
+    };
+    dir.join(format!("{}-{part}.bin", &name[..32]))
+}
+
+/// Delete the least recently used entries until the cache fits in `max_bytes`
+fn evict(dir: &Path, max_bytes: u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "bin"))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    entries.sort();
+    for (_, len, path) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+/// Top-left and bottom-right corners of a range
+type Bounds = ((u32, u32), (u32, u32));
+
+fn write_range<T: Entry>(out: &mut impl Write, range: &Range<T>) -> io::Result<()> {
+    let cells: Vec<_> = range
+        .used_cells()
+        .map(|(row, col, cell)| ((row as u32, col as u32), cell))
+        .collect();
+    write_entry(out, range.start().zip(range.end()), &cells)
+}
+
+fn write_cells<T: Entry>(out: &mut impl Write, cells: &[Cell<T>]) -> io::Result<()> {
+    let positions = cells.iter().map(Cell::get_position);
+    let start = positions
+        .clone()
+        .reduce(|(r, c), (row, col)| (r.min(row), c.min(col)));
+    let end = positions.reduce(|(r, c), (row, col)| (r.max(row), c.max(col)));
+    let Some((start, end)) = start.zip(end) else {
+        return write_entry::<T>(out, None, &[]);
+    };
+    let cells: Vec<_> = cells
+        .iter()
+        .map(|cell| {
+            let (row, col) = cell.get_position();
+            ((row - start.0, col - start.1), cell.get_value())
+        })
+        .collect();
+    write_entry(out, Some((start, end)), &cells)
+}
+
+/// An entry for a range with these corners (None if empty) holding `cells`, by position
+/// relative to the start
+fn write_entry<T: Entry>(
+    out: &mut impl Write,
+    bounds: Option<Bounds>,
+    cells: &[((u32, u32), &T)],
+) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    let Some((start, end)) = bounds else {
+        return out.write_all(&[0]);
+    };
+    out.write_all(&[1])?;
+    for n in [start.0, start.1, end.0, end.1] {
+        out.write_all(&n.to_le_bytes())?;
+    }
+    // Only non-empty cells
+    out.write_all(&(cells.len() as u64).to_le_bytes())?;
+    for ((row, col), cell) in cells {
+        out.write_all(&row.to_le_bytes())?;
+        out.write_all(&col.to_le_bytes())?;
+        cell.write(out)?;
+    }
+    Ok(())
+}
+
+fn read_range<T: Entry>(input: &mut impl Read) -> io::Result<Range<T>> {
+    let Some((start, end)) = read_bounds(input)? else {
+        return Ok(Range::empty());
+    };
+    let mut range = Range::new(start, end);
+    read_entry_cells(input, (start, end), |position, cell| {
+        range.set_value(position, cell)
+    })?;
+    Ok(range)
+}
+
+fn read_cells<T: Entry>(input: &mut impl Read) -> io::Result<Vec<Cell<T>>> {
+    let mut cells = Vec::new();
+    if let Some(bounds) = read_bounds(input)? {
+        read_entry_cells(input, bounds, |position, cell| {
+            cells.push(Cell::new(position, cell))
+        })?;
+    }
+    Ok(cells)
+}
+
+/// Checks an entry's header and reads its range's corners; None if the range is empty
+fn read_bounds(input: &mut impl Read) -> io::Result<Option<Bounds>> {
+    let mut magic = [0; 4];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC || read_u32(input)? != VERSION {
+        return Err(invalid("not a current cache entry"));
+    }
+    if read_u8(input)? == 0 {
+        return Ok(None);
+    }
+    let start = (read_u32(input)?, read_u32(input)?);
+    let end = (read_u32(input)?, read_u32(input)?);
+    if start.0 > end.0 || start.1 > end.1 {
+        return Err(invalid("bad range"));
+    }
+    Ok(Some((start, end)))
+}
+
+/// Reads the cells following an entry's corners, handing each to `add` at its absolute
+/// position
+fn read_entry_cells<T: Entry>(
+    input: &mut impl Read,
+    (start, end): Bounds,
+    mut add: impl FnMut((u32, u32), T),
+) -> io::Result<()> {
+    for _ in 0..read_u64(input)? {
+        let row = start.0 + read_u32(input)?;
+        let col = start.1 + read_u32(input)?;
+        if row > end.0 || col > end.1 {
+            return Err(invalid("cell outside the range"));
+        }
+        add((row, col), T::read(input)?);
+    }
+    Ok(())
+}
+
+/// A cell type that can be written to and read back from an entry
+pub(crate) trait Entry: CellType + Sized {
+    fn write(&self, out: &mut impl Write) -> io::Result<()>;
+    fn read(input: &mut impl Read) -> io::Result<Self>;
+}
+
+impl Entry for String {
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        write_str(out, self)
+    }
+
+    fn read(input: &mut impl Read) -> io::Result<Self> {
+        read_str(input)
+    }
+}
+
+const ERRORS: [CellErrorType; 8] = [
+    CellErrorType::Div0,
+    CellErrorType::NA,
+    CellErrorType::Name,
+    CellErrorType::Null,
+    CellErrorType::Num,
+    CellErrorType::Ref,
+    CellErrorType::Value,
+    CellErrorType::GettingData,
+];
+
+impl Entry for Data {
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Data::Empty => out.write_all(&[0]),
+            Data::Int(n) => {
+                out.write_all(&[1])?;
+                out.write_all(&n.to_le_bytes())
+            }
+            Data::Float(f) => {
+                out.write_all(&[2])?;
+                out.write_all(&f.to_le_bytes())
+            }
+            Data::String(s) => {
+                out.write_all(&[3])?;
+                write_str(out, s)
+            }
+            Data::Bool(b) => out.write_all(&[4, *b as u8]),
+            Data::DateTime(date) => {
+                // calamine doesn't expose the date1904 flag; compare against a rebuilt value
+                let serial = date.as_f64();
+                let kind = if date.is_duration() {
+                    ExcelDateTimeType::TimeDelta
+                } else {
+                    ExcelDateTimeType::DateTime
+                };
+                let is_1904 = *date == ExcelDateTime::new(serial, kind, true);
+                let flags = date.is_duration() as u8 | (is_1904 as u8) << 1;
+                out.write_all(&[5, flags])?;
+                out.write_all(&serial.to_le_bytes())
+            }
+            Data::DateTimeIso(s) => {
+                out.write_all(&[6])?;
+                write_str(out, s)
+            }
+            Data::DurationIso(s) => {
+                out.write_all(&[7])?;
+                write_str(out, s)
+            }
+            Data::Error(e) => {
+                let code = ERRORS.iter().position(|known| known == e).unwrap_or(0);
+                out.write_all(&[8, code as u8])
+            }
+        }
+    }
+
+    fn read(input: &mut impl Read) -> io::Result<Self> {
+        Ok(match read_u8(input)? {
+            0 => Data::Empty,
+            1 => Data::Int(i64::from_le_bytes(read_bytes(input)?)),
+            2 => Data::Float(f64::from_le_bytes(read_bytes(input)?)),
+            3 => Data::String(read_str(input)?),
+            4 => Data::Bool(read_u8(input)? != 0),
+            5 => {
+                let flags = read_u8(input)?;
+                let serial = f64::from_le_bytes(read_bytes(input)?);
+                let kind = if flags & 1 != 0 {
+                    ExcelDateTimeType::TimeDelta
+                } else {
+                    ExcelDateTimeType::DateTime
+                };
+                Data::DateTime(ExcelDateTime::new(serial, kind, flags & 2 != 0))
+            }
+            6 => Data::DateTimeIso(read_str(input)?),
+            7 => Data::DurationIso(read_str(input)?),
+            8 => {
+                let code = read_u8(input)? as usize;
+                Data::Error(
+                    ERRORS
+                        .get(code)
+                        .ok_or_else(|| invalid("bad error"))?
+                        .clone(),
+                )
+            }
+            _ => return Err(invalid("bad cell type")),
+        })
+    }
+}
+
+fn write_str(out: &mut impl Write, s: &str) -> io::Result<()> {
+    out.write_all(&(s.len() as u32).to_le_bytes())?;
+    out.write_all(s.as_bytes())
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(input)? as usize;
+    let mut bytes = vec![0; len];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid("bad string"))
+}
+
+fn read_bytes<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u8(input: &mut impl Read) -> io::Result<u8> {
+    Ok(read_bytes::<1>(input)?[0])
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(input)?))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(input)?))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut range = Range::new((2, 1), (4, 3));
+        let cells = [
+            Data::Int(-7),
+            Data::Float(1.5),
+            Data::String("héllo".to_string()),
+            Data::Bool(true),
+            Data::DateTime(ExcelDateTime::new(
+                45000.25,
+                ExcelDateTimeType::DateTime,
+                true,
+            )),
+            Data::DateTime(ExcelDateTime::new(1.5, ExcelDateTimeType::TimeDelta, false)),
+            Data::DateTimeIso("2024-01-02T03:04:05".to_string()),
+            Data::Error(CellErrorType::Ref),
+        ];
+        for (i, cell) in cells.iter().enumerate() {
+            range.set_value((2 + i as u32 / 3, 1 + i as u32 % 3), cell.clone());
+        }
+
+        let mut bytes = Vec::new();
+        write_range(&mut bytes, &range).unwrap();
+        let read: Range<Data> = read_range(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.start(), range.start());
+        assert_eq!(read.end(), range.end());
+        assert!(read.cells().zip(range.cells()).all(|(a, b)| a == b));
+
+        // Cells written on their own make the same entry as their range
+        let cells: Vec<_> = range
+            .used_cells()
+            .map(|(row, col, cell)| Cell::new((2 + row as u32, 1 + col as u32), cell.clone()))
+            .collect();
+        let mut from_cells = Vec::new();
+        write_cells(&mut from_cells, &cells).unwrap();
+        assert_eq!(from_cells, bytes);
+        let read: Vec<Cell<Data>> = read_cells(&mut bytes.as_slice()).unwrap();
+        let positions = |cells: &[Cell<Data>]| -> Vec<_> {
+            cells
+                .iter()
+                .map(|cell| (cell.get_position(), cell.get_value().clone()))
+                .collect()
+        };
+        assert_eq!(positions(&read), positions(&cells));
+
+        let mut bytes = Vec::new();
+        write_range(&mut bytes, &Range::<String>::empty()).unwrap();
+        assert!(
+            read_range::<String>(&mut bytes.as_slice())
+                .unwrap()
+                .is_empty()
+        );
+
+        // Truncated and foreign entries are misses, not panics
+        bytes.truncate(6);
+        assert!(read_range::<Data>(&mut bytes.as_slice()).is_err());
+        assert!(read_range::<Data>(&mut b"PK\x03\x04".as_slice()).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Bar and line charts of one column against another, in the terminal (`--chart`) and as
+//! a TUI popup
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    widgets::{Axis, Chart, Dataset, GraphType, Widget},
+};
+use xleak::display;
+use xleak::locale::NumberLocale;
+use xleak::workbook::CellValue;
+
+/// Widest label in front of a bar
+const MAX_LABEL_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    Bar,
+    Line,
+}
+
+impl ChartKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "bar" => Some(ChartKind::Bar),
+            "line" => Some(ChartKind::Line),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartKind::Bar => "bar",
+            ChartKind::Line => "line",
+        }
+    }
+}
+
+/// One column's numbers with a label for each, from another column or the row number;
+/// rows without a number are left out
+pub struct Series {
+    pub x_name: String,
+    pub y_name: String,
+    pub points: Vec<(String, CellValue)>,
+    /// Number format of the labels and values
+    locale: NumberLocale,
+}
+
+impl Series {
+    /// Column `y` against column `x`, or against row numbers without one, shown in `locale`
+    pub fn new(
+        headers: &[String],
+        rows: &[Vec<CellValue>],
+        x: Option<usize>,
+        y: usize,
+        locale: &NumberLocale,
+    ) -> Self {
+        let points = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| {
+                let value = row.get(y).filter(|cell| number(cell).is_some())?;
+                let label = match x {
+                    Some(x) => row
+                        .get(x)
+                        .map(|cell| cell.to_display_string(locale))
+                        .unwrap_or_default(),
+                    None => (i + 1).to_string(),
+                };
+                Some((label, value.clone()))
+            })
+            .collect();
+        let header = |col: usize| headers.get(col).cloned().unwrap_or_default();
+        Self {
+            x_name: x.map(header).unwrap_or_else(|| "Row".to_string()),
+            y_name: header(y),
+            points,
+            locale: locale.clone(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("{} by {}", self.y_name, self.x_name)
+    }
+}
+
+fn number(cell: &CellValue) -> Option<f64> {
+    match cell {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) if f.is_finite() => Some(*f),
+        _ => None,
+    }
+}
+
+/// A bar chart laid on its side: a line per point with its label, bar, and value,
+/// fitted to `width` columns
+pub fn bar_lines(series: &Series, width: usize) -> Vec<String> {
+    let Some(scale) = display::bar_scale(series.points.iter().map(|(_, value)| value)) else {
+        return Vec::new();
+    };
+    let label_width = series
+        .points
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_LABEL_WIDTH);
+    let value_width = series
+        .points
+        .iter()
+        .map(|(_, value)| value.to_display_string(&series.locale).chars().count())
+        .max()
+        .unwrap_or(0);
+    let bar_width = width.saturating_sub(label_width + value_width + 2).max(10);
+    series
+        .points
+        .iter()
+        .map(|(label, cell)| {
+            let label: String = label.chars().take(label_width).collect();
+            let value = cell.to_display_string(&series.locale);
+            format!(
+                "{label:<label_width$} {} {value}",
+                display::bar(cell, scale, bar_width)
+            )
+        })
+        .collect()
+}
+
+/// Draw a line chart of the series, with the first, middle, and last labels on the x axis
+pub fn draw_line_chart(series: &Series, area: Rect, buf: &mut Buffer) {
+    let data: Vec<(f64, f64)> = series
+        .points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, value))| Some((i as f64, number(value)?)))
+        .collect();
+    if data.is_empty() {
+        return;
+    }
+    let lo = data.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let hi = data.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    // A flat line still needs some height to be drawn in
+    let (lo, hi) = if hi > lo {
+        (lo, hi)
+    } else {
+        (lo - 1.0, hi + 1.0)
+    };
+    let last = data.len().saturating_sub(1);
+    let x_labels: Vec<String> = [0, last / 2, last]
+        .iter()
+        .map(|&i| {
+            series
+                .points
+                .get(i)
+                .map(|p| p.0.clone())
+                .unwrap_or_default()
+        })
+        .collect();
+    let y_labels: Vec<String> = [lo, (lo + hi) / 2.0, hi]
+        .iter()
+        .map(|&n| value_label(n, &series.locale))
+        .collect();
+
+    let datasets = vec![
+        Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&data),
+    ];
+    Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .title(series.x_name.clone())
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, last.max(1) as f64])
+                .labels(x_labels),
+        )
+        .y_axis(
+            Axis::default()
+                .title(series.y_name.clone())
+                .style(Style::default().fg(Color::Gray))
+                .bounds([lo, hi])
+                .labels(y_labels),
+        )
+        .render(area, buf);
+}
+
+/// An axis label in the display number format
+fn value_label(n: f64, locale: &NumberLocale) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        CellValue::Int(n as i64).to_display_string(locale)
+    } else {
+        CellValue::Float(n).to_display_string(locale)
+    }
+}
+
+/// The chart as plain text lines, `width` columns wide; a line chart is `height` lines tall
+pub fn render_text(kind: ChartKind, series: &Series, width: u16, height: u16) -> Vec<String> {
+    match kind {
+        ChartKind::Bar => bar_lines(series, width as usize),
+        ChartKind::Line => {
+            let area = Rect::new(0, 0, width, height);
+            let mut buf = Buffer::empty(area);
+            draw_line_chart(series, area, &mut buf);
+            (0..height)
+                .map(|y| {
+                    let line: String = (0..width)
+                        .filter_map(|x| buf.cell((x, y)).map(|cell| cell.symbol()))
+                        .collect();
+                    line.trim_end().to_string()
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charts() {
+        let headers = vec!["Month".to_string(), "Revenue".to_string()];
+        let rows = vec![
+            vec![CellValue::String("Jan".to_string()), CellValue::Int(100)],
+            vec![CellValue::String("Feb".to_string()), CellValue::Empty],
+            vec![CellValue::String("Mar".to_string()), CellValue::Int(50)],
+        ];
+        let locale = NumberLocale::default();
+        let series = Series::new(&headers, &rows, Some(0), 1, &locale);
+        assert_eq!(series.title(), "Revenue by Month");
+        assert_eq!(series.points.len(), 2);
+
+        assert_eq!(
+            bar_lines(&series, 18),
+            ["Jan ██████████ 100", "Mar █████      50"]
+        );
+        let by_row = Series::new(&headers, &rows, None, 1, &locale);
+        assert_eq!(by_row.points[1].0, "3");
+
+        let text = render_text(ChartKind::Line, &series, 40, 10);
+        assert_eq!(text.len(), 10);
+        assert!(text.iter().any(|line| line.contains("Jan")));
+        assert!(text.iter().any(|line| line.contains("100")));
+    }
+}
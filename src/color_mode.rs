@@ -0,0 +1,179 @@
+//! How many colors the terminal can show (`--color-mode`): true color, the xterm
+//! 256-color palette, the 16 ANSI colors, or none. RGB colors (themes, heatmaps, workbook
+//! colors) are mapped to the nearest color the terminal has, so they still make sense
+//! over screen, mosh, and other terminals without true color.
+
+use anyhow::{Result, bail};
+use std::sync::OnceLock;
+
+static MODE: OnceLock<ColorMode> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Truecolor,
+    Ansi256,
+    Ansi16,
+    None,
+}
+
+/// A color the terminal can show: an RGB one, or an index into its palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermColor {
+    Rgb(u8, u8, u8),
+    Indexed(u8),
+}
+
+/// xterm's default colors for the 16 ANSI indexes
+const ANSI: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6x6x6 color cube at indexes 16-231
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorMode {
+    /// "auto" (guessed from the environment), "truecolor", "256", "16", or "none"
+    pub fn parse(name: &str) -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Ok(match name.trim().to_ascii_lowercase().as_str() {
+            // NO_COLOR (https://no-color.org) turns colors off unless a mode is asked for
+            "auto" if var("NO_COLOR").is_some() => ColorMode::None,
+            "auto" => Self::detect(var("COLORTERM").as_deref(), var("TERM").as_deref()),
+            "truecolor" | "24bit" => ColorMode::Truecolor,
+            "256" => ColorMode::Ansi256,
+            "16" => ColorMode::Ansi16,
+            "none" => ColorMode::None,
+            _ => bail!("Invalid color mode '{name}'. Use auto, truecolor, 256, 16, or none"),
+        })
+    }
+
+    /// True color when COLORTERM says so, otherwise what TERM names: "dumb" has no colors,
+    /// "*-256color" has 256, and others (xterm, screen, linux) are held to 16. Without a
+    /// TERM (Windows consoles) true color is assumed.
+    fn detect(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorMode::Truecolor;
+        }
+        match term {
+            None | Some("") => ColorMode::Truecolor,
+            Some("dumb") => ColorMode::None,
+            Some(term) if term.contains("256color") => ColorMode::Ansi256,
+            Some(_) => ColorMode::Ansi16,
+        }
+    }
+
+    /// What an RGB color becomes in this mode; None when colors are off
+    pub fn rgb(self, rgb: (u8, u8, u8)) -> Option<TermColor> {
+        match self {
+            ColorMode::Truecolor => Some(TermColor::Rgb(rgb.0, rgb.1, rgb.2)),
+            ColorMode::Ansi256 => Some(TermColor::Indexed(nearest_256(rgb))),
+            ColorMode::Ansi16 => Some(TermColor::Indexed(nearest_16(rgb))),
+            ColorMode::None => None,
+        }
+    }
+
+    /// What a palette color becomes in this mode: the 256-color ones past the first 16
+    /// are mapped down in 16-color mode; None when colors are off
+    pub fn indexed(self, index: u8) -> Option<TermColor> {
+        match self {
+            ColorMode::None => None,
+            ColorMode::Ansi16 if index > 15 => {
+                Some(TermColor::Indexed(nearest_16(palette_rgb(index))))
+            }
+            _ => Some(TermColor::Indexed(index)),
+        }
+    }
+}
+
+/// Use `mode` for terminal tables and the TUI. Only the first call has an effect.
+pub fn set_color_mode(mode: ColorMode) {
+    let _ = MODE.set(mode);
+}
+
+/// The mode set with [`set_color_mode`], true color until then
+pub fn color_mode() -> ColorMode {
+    MODE.get().copied().unwrap_or(ColorMode::Truecolor)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).unsigned_abs();
+    d(a.0, b.0).pow(2) + d(a.1, b.1).pow(2) + d(a.2, b.2).pow(2)
+}
+
+/// The RGB value of a 256-color palette index (xterm's defaults for the first 16)
+pub fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// The closest color of the 256-color palette's cube and gray ramp (indexes 16-255;
+/// the first 16 vary between terminals)
+pub fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&index| distance(rgb, palette_rgb(index)))
+        .unwrap_or(16)
+}
+
+/// The closest of the 16 ANSI colors
+pub fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16)
+        .min_by_key(|&index| distance(rgb, ANSI[index as usize]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_modes() {
+        assert_eq!(ColorMode::parse("256").unwrap(), ColorMode::Ansi256);
+        assert!(ColorMode::parse("8").is_err());
+        assert_eq!(
+            ColorMode::detect(Some("truecolor"), Some("screen")),
+            ColorMode::Truecolor
+        );
+        assert_eq!(
+            ColorMode::detect(None, Some("xterm-256color")),
+            ColorMode::Ansi256
+        );
+        assert_eq!(ColorMode::detect(None, Some("screen")), ColorMode::Ansi16);
+        assert_eq!(ColorMode::detect(None, Some("dumb")), ColorMode::None);
+
+        assert_eq!(nearest_256((255, 0, 0)), 196);
+        assert_eq!(nearest_256((25, 25, 28)), 234);
+        assert_eq!(palette_rgb(196), (255, 0, 0));
+        assert_eq!(nearest_16((215, 48, 39)), 1);
+        assert_eq!(nearest_16((25, 25, 28)), 0);
+        assert_eq!(ColorMode::Ansi16.indexed(196), Some(TermColor::Indexed(9)));
+        assert_eq!(ColorMode::None.rgb((1, 2, 3)), None);
+    }
+}
@@ -0,0 +1,468 @@
+//! Conditional formatting recorded in a worksheet: color scales, data bars, and cell-value
+//! rules, evaluated against the sheet's values to approximate how Excel shows it
+
+use crate::ooxml::{Package, XmlElement, parse_cell_ref, parse_range_ref};
+use crate::styles::{self, CellStyle, Palette, Rgb};
+use crate::workbook::CellValue;
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// 0-based absolute (first, last) corners of a range
+pub type CellRange = ((u32, u32), (u32, u32));
+
+/// Where a color scale stop or data bar end sits among the range's numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalePoint {
+    Min,
+    Max,
+    Number(f64),
+    Percent(f64),
+    Percentile(f64),
+}
+
+/// How a `cellIs` rule compares a cell with its operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Between,
+    NotBetween,
+}
+
+/// A literal from a rule's `formula`: a number or a quoted string
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatRule {
+    /// Backgrounds blended between two or three stops
+    ColorScale(Vec<(ScalePoint, Rgb)>),
+    /// A bar across the cell, as long as the number's place between `min` and `max`
+    DataBar {
+        min: ScalePoint,
+        max: ScalePoint,
+        color: Rgb,
+    },
+    /// A style for cells whose value passes a comparison
+    CellIs {
+        comparison: Comparison,
+        operands: Vec<Operand>,
+        style: CellStyle,
+    },
+}
+
+/// One rule and the cells it covers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalFormat {
+    pub ranges: Vec<CellRange>,
+    /// Lower runs first and wins where rules clash
+    pub priority: u32,
+    pub rule: FormatRule,
+}
+
+/// The combined look of one cell under all its rules
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CellFormat {
+    pub style: CellStyle,
+    /// Share of the cell a data bar fills (0.0 to 1.0), and its color
+    pub bar: Option<(f64, Rgb)>,
+}
+
+/// Reads a sheet's conditional formatting rules with their styles, by priority; rules of
+/// kinds not listed above (formulas, top 10, text contains, ...) are skipped
+pub fn load_conditional_formats(
+    package: &mut Package,
+    sheet_name: &str,
+) -> Result<Vec<ConditionalFormat>> {
+    let part = package.worksheet_part(sheet_name)?;
+    let sheet = package.read_xml_skipping(&part, &["sheetData"])?;
+    if sheet.child("conditionalFormatting").is_none() {
+        return Ok(Vec::new());
+    }
+    let palette = Palette::load(package)?;
+    let dxfs = match package.workbook_part("styles")? {
+        Some(styles_part) => {
+            styles::differential_styles(&package.read_xml(&styles_part)?, &palette)
+        }
+        None => Vec::new(),
+    };
+    Ok(formats_from(&sheet, &dxfs, &palette))
+}
+
+fn formats_from(
+    sheet: &XmlElement,
+    dxfs: &[CellStyle],
+    palette: &Palette,
+) -> Vec<ConditionalFormat> {
+    let mut formats = Vec::new();
+    for block in sheet.children_named("conditionalFormatting") {
+        let ranges: Vec<_> = block
+            .attr("sqref")
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter_map(|r| parse_range_ref(r).or_else(|| parse_cell_ref(r).map(|c| (c, c))))
+            .collect();
+        for el in block.children_named("cfRule") {
+            if let Some(rule) = rule_from(el, dxfs, palette) {
+                formats.push(ConditionalFormat {
+                    ranges: ranges.clone(),
+                    priority: el
+                        .attr("priority")
+                        .and_then(|p| p.parse().ok())
+                        .unwrap_or(0),
+                    rule,
+                });
+            }
+        }
+    }
+    formats.sort_by_key(|format| format.priority);
+    formats
+}
+
+fn rule_from(el: &XmlElement, dxfs: &[CellStyle], palette: &Palette) -> Option<FormatRule> {
+    match el.attr("type")? {
+        "colorScale" => {
+            let scale = el.child("colorScale")?;
+            let points = scale.children_named("cfvo").map(scale_point);
+            let colors = scale.children_named("color").map(|c| palette.color(c));
+            let stops: Option<Vec<_>> = points
+                .zip(colors)
+                .map(|(point, color)| Some((point?, color?)))
+                .collect();
+            stops
+                .filter(|stops| stops.len() >= 2)
+                .map(FormatRule::ColorScale)
+        }
+        "dataBar" => {
+            let bar = el.child("dataBar")?;
+            let mut points = bar.children_named("cfvo").map(scale_point);
+            Some(FormatRule::DataBar {
+                min: points.next()??,
+                max: points.next()??,
+                color: palette.color(bar.child("color")?)?,
+            })
+        }
+        "cellIs" => {
+            let comparison = match el.attr("operator")? {
+                "equal" => Comparison::Equal,
+                "notEqual" => Comparison::NotEqual,
+                "greaterThan" => Comparison::Greater,
+                "greaterThanOrEqual" => Comparison::GreaterOrEqual,
+                "lessThan" => Comparison::Less,
+                "lessThanOrEqual" => Comparison::LessOrEqual,
+                "between" => Comparison::Between,
+                "notBetween" => Comparison::NotBetween,
+                _ => return None,
+            };
+            let operands: Option<Vec<Operand>> = el
+                .children_named("formula")
+                .map(|f| operand(&f.text))
+                .collect();
+            let style = dxfs.get(el.attr("dxfId")?.parse::<usize>().ok()?)?;
+            Some(FormatRule::CellIs {
+                comparison,
+                operands: operands.filter(|o| !o.is_empty())?,
+                style: *style,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn scale_point(el: &XmlElement) -> Option<ScalePoint> {
+    let value = || el.attr("val").and_then(|v| v.trim().parse::<f64>().ok());
+    match el.attr("type")? {
+        "min" => Some(ScalePoint::Min),
+        "max" => Some(ScalePoint::Max),
+        "num" => value().map(ScalePoint::Number),
+        "percent" => value().map(ScalePoint::Percent),
+        "percentile" => value().map(ScalePoint::Percentile),
+        _ => None,
+    }
+}
+
+/// A formula that's a plain number or a quoted string; cell references and functions
+/// can't be evaluated here
+fn operand(formula: &str) -> Option<Operand> {
+    let formula = formula.trim();
+    if let Some(text) = formula.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+        return Some(Operand::Text(text.replace("\"\"", "\"")));
+    }
+    formula.parse().ok().map(Operand::Number)
+}
+
+fn number(cell: &CellValue) -> Option<f64> {
+    match cell {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) if f.is_finite() => Some(*f),
+        _ => None,
+    }
+}
+
+/// Applies the rules to a sheet's data `rows`, whose first cell is at absolute `origin`,
+/// giving each formatted cell's look by absolute (row, col)
+pub fn evaluate(
+    formats: &[ConditionalFormat],
+    origin: (u32, u32),
+    rows: &[Vec<CellValue>],
+) -> HashMap<(u32, u32), CellFormat> {
+    let mut result: HashMap<(u32, u32), CellFormat> = HashMap::new();
+    // Rules come lowest priority number first; a later rule only fills in what's unset
+    for format in formats {
+        let cells = cells_in(&format.ranges, origin, rows);
+        match &format.rule {
+            FormatRule::ColorScale(stops) => {
+                let Some(points) = resolve(stops.iter().map(|(p, _)| *p), &cells) else {
+                    continue;
+                };
+                for &(pos, cell) in &cells {
+                    let Some(n) = number(cell) else { continue };
+                    let entry = result.entry(pos).or_default();
+                    if entry.style.bg.is_none() {
+                        let colors: Vec<Rgb> = stops.iter().map(|(_, c)| *c).collect();
+                        entry.style.bg = Some(scale_color(n, &points, &colors));
+                    }
+                }
+            }
+            FormatRule::DataBar { min, max, color } => {
+                let Some(ends) = resolve([*min, *max].into_iter(), &cells) else {
+                    continue;
+                };
+                for &(pos, cell) in &cells {
+                    let Some(n) = number(cell) else { continue };
+                    let fraction = if ends[1] > ends[0] {
+                        ((n - ends[0]) / (ends[1] - ends[0])).clamp(0.0, 1.0)
+                    } else {
+                        1.0
+                    };
+                    let entry = result.entry(pos).or_default();
+                    entry.bar.get_or_insert((fraction, *color));
+                }
+            }
+            FormatRule::CellIs {
+                comparison,
+                operands,
+                style,
+            } => {
+                for &(pos, cell) in &cells {
+                    if !passes(cell, *comparison, operands) {
+                        continue;
+                    }
+                    let entry = result.entry(pos).or_default();
+                    entry.style.fg = entry.style.fg.or(style.fg);
+                    entry.style.bg = entry.style.bg.or(style.bg);
+                    entry.style.bold |= style.bold;
+                    entry.style.italic |= style.italic;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// The data cells inside `ranges`, with their absolute positions
+fn cells_in<'a>(
+    ranges: &[CellRange],
+    (origin_row, origin_col): (u32, u32),
+    rows: &'a [Vec<CellValue>],
+) -> Vec<((u32, u32), &'a CellValue)> {
+    let mut cells = Vec::new();
+    for &((r1, c1), (r2, c2)) in ranges {
+        let first = r1.saturating_sub(origin_row) as usize;
+        let last = (r2.saturating_sub(origin_row) as usize).min(rows.len().saturating_sub(1));
+        for (i, row) in rows.iter().enumerate().take(last + 1).skip(first) {
+            let row_pos = origin_row + i as u32;
+            if row_pos < r1 || row_pos > r2 {
+                continue;
+            }
+            for col in c1.max(origin_col)..=c2 {
+                match row.get((col - origin_col) as usize) {
+                    Some(cell) => cells.push(((row_pos, col), cell)),
+                    None => break,
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// The numbers at each scale point among the cells; None when the cells have no numbers
+fn resolve(
+    points: impl Iterator<Item = ScalePoint>,
+    cells: &[((u32, u32), &CellValue)],
+) -> Option<Vec<f64>> {
+    let mut values: Vec<f64> = cells.iter().filter_map(|(_, cell)| number(cell)).collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let (lo, hi) = (values[0], values[values.len() - 1]);
+    Some(
+        points
+            .map(|point| match point {
+                ScalePoint::Min => lo,
+                ScalePoint::Max => hi,
+                ScalePoint::Number(n) => n,
+                ScalePoint::Percent(p) => lo + (hi - lo) * p / 100.0,
+                ScalePoint::Percentile(p) => {
+                    // Excel's PERCENTILE.INC: interpolate between the ranks around p
+                    let rank = (p / 100.0).clamp(0.0, 1.0) * (values.len() - 1) as f64;
+                    let below = values[rank.floor() as usize];
+                    let above = values[rank.ceil() as usize];
+                    below + (above - below) * rank.fract()
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The color for `n` blended between the stops around it
+fn scale_color(n: f64, points: &[f64], colors: &[Rgb]) -> Rgb {
+    if n <= points[0] {
+        return colors[0];
+    }
+    for i in 1..points.len() {
+        if n <= points[i] {
+            let span = points[i] - points[i - 1];
+            let t = if span > 0.0 {
+                (n - points[i - 1]) / span
+            } else {
+                1.0
+            };
+            let (a, b) = (colors[i - 1], colors[i]);
+            let blend = |x: u8, y: u8| (x as f64 + (y as f64 - x as f64) * t).round() as u8;
+            return (blend(a.0, b.0), blend(a.1, b.1), blend(a.2, b.2));
+        }
+    }
+    colors[colors.len() - 1]
+}
+
+fn passes(cell: &CellValue, comparison: Comparison, operands: &[Operand]) -> bool {
+    use std::cmp::Ordering;
+    let compare = |operand: &Operand| -> Option<Ordering> {
+        match (operand, cell) {
+            (Operand::Number(x), _) => number(cell)?.partial_cmp(x),
+            (Operand::Text(text), CellValue::String(s)) => {
+                Some(s.to_lowercase().cmp(&text.to_lowercase()))
+            }
+            _ => None,
+        }
+    };
+    let Some(first) = compare(&operands[0]) else {
+        // A blank or mismatched cell is only "not equal"
+        return comparison == Comparison::NotEqual;
+    };
+    match comparison {
+        Comparison::Equal => first.is_eq(),
+        Comparison::NotEqual => first.is_ne(),
+        Comparison::Greater => first.is_gt(),
+        Comparison::GreaterOrEqual => first.is_ge(),
+        Comparison::Less => first.is_lt(),
+        Comparison::LessOrEqual => first.is_le(),
+        Comparison::Between | Comparison::NotBetween => {
+            let inside = match operands.get(1).and_then(compare) {
+                Some(second) => first.is_ge() && second.is_le(),
+                None => false,
+            };
+            inside == (comparison == Comparison::Between)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_conditional_formats() {
+        let styles = parse_xml(
+            r#"<styleSheet><dxfs count="1"><dxf>
+  <font><b/><color rgb="FF9C0006"/></font>
+  <fill><patternFill><bgColor rgb="FFFFC7CE"/></patternFill></fill>
+</dxf></dxfs></styleSheet>"#,
+            &[],
+        )
+        .unwrap();
+        let sheet = parse_xml(
+            r#"<worksheet>
+  <conditionalFormatting sqref="B2:B5">
+    <cfRule type="colorScale" priority="2"><colorScale>
+      <cfvo type="min"/><cfvo type="max"/>
+      <color rgb="FFFFFFFF"/><color rgb="FF000000"/>
+    </colorScale></cfRule>
+    <cfRule type="cellIs" dxfId="0" priority="1" operator="greaterThan"><formula>25</formula></cfRule>
+  </conditionalFormatting>
+  <conditionalFormatting sqref="C2:C5">
+    <cfRule type="dataBar" priority="3"><dataBar>
+      <cfvo type="num" val="0"/><cfvo type="max"/><color rgb="FF638EC6"/>
+    </dataBar></cfRule>
+    <cfRule type="expression" dxfId="0" priority="4"><formula>$C2&gt;1</formula></cfRule>
+  </conditionalFormatting>
+</worksheet>"#,
+            &[],
+        )
+        .unwrap();
+
+        let palette = Palette::default();
+        let formats = formats_from(
+            &sheet,
+            &styles::differential_styles(&styles, &palette),
+            &palette,
+        );
+        assert_eq!(formats.len(), 3);
+        assert_eq!(formats[0].priority, 1);
+        let red = CellStyle {
+            fg: Some((0x9C, 0, 6)),
+            bg: Some((0xFF, 0xC7, 0xCE)),
+            bold: true,
+            italic: false,
+        };
+        assert_eq!(
+            formats[0].rule,
+            FormatRule::CellIs {
+                comparison: Comparison::Greater,
+                operands: vec![Operand::Number(25.0)],
+                style: red,
+            }
+        );
+
+        // Data starts at B2: column B holds 10, 20, 30; column C 5, 10, "n/a"
+        let rows = vec![
+            vec![CellValue::Int(10), CellValue::Int(5)],
+            vec![CellValue::Int(20), CellValue::Int(10)],
+            vec![CellValue::Int(30), CellValue::String("n/a".to_string())],
+        ];
+        let cells = evaluate(&formats, (1, 1), &rows);
+        assert_eq!(cells[&(1, 1)].style.bg, Some((255, 255, 255)));
+        assert_eq!(cells[&(2, 1)].style.bg, Some((128, 128, 128)));
+        // The higher-priority rule's fill wins over the scale
+        assert_eq!(cells[&(3, 1)].style, red);
+        assert_eq!(cells[&(1, 2)].bar, Some((0.5, (0x63, 0x8E, 0xC6))));
+        assert_eq!(cells[&(2, 2)].bar.map(|b| b.0), Some(1.0));
+        assert!(!cells.contains_key(&(3, 2)));
+    }
+
+    #[test]
+    fn test_cell_is_comparisons() {
+        let between = [Operand::Number(1.0), Operand::Number(3.0)];
+        assert!(passes(&CellValue::Int(2), Comparison::Between, &between));
+        assert!(!passes(&CellValue::Int(4), Comparison::Between, &between));
+        assert!(passes(&CellValue::Int(4), Comparison::NotBetween, &between));
+        let text = [operand("\"Open\"").unwrap()];
+        assert!(passes(
+            &CellValue::String("open".to_string()),
+            Comparison::Equal,
+            &text
+        ));
+        assert!(passes(&CellValue::Empty, Comparison::NotEqual, &text));
+        assert_eq!(operand("$A$1"), None);
+    }
+}
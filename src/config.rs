@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use crossterm::event::{KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,14 +13,27 @@ pub struct Config {
     pub theme: ThemeConfig,
     pub ui: UiConfig,
     pub keybindings: KeybindingsConfig,
+    pub scan: ScanConfig,
+    pub format: FormatConfig,
+    pub cache: CacheConfig,
+    pub session: SessionConfig,
+    pub csv: CsvConfig,
+    /// Custom themes by name, from `[themes.<name>]` tables and `themes/<name>.toml`
+    /// files next to the config file: ColorScheme field names mapped to colors
+    pub themes: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 /// Theme configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ThemeConfig {
-    /// Default theme to use on startup
+    /// Default theme to use on startup; "auto" picks `light` or `dark` to match the
+    /// terminal's background
     pub default: String,
+    /// Theme for light terminal backgrounds with `default = "auto"`
+    pub light: String,
+    /// Theme for dark terminal backgrounds (or an unknown one) with `default = "auto"`
+    pub dark: String,
 }
 
 /// UI configuration
@@ -31,6 +44,73 @@ pub struct UiConfig {
     pub max_rows: usize,
     /// Default maximum column width
     pub column_width: usize,
+    /// Show cells in the TUI with the fill and font colors set in the workbook (.xlsx)
+    pub excel_colors: bool,
+    /// Shade every other row in the TUI with the theme's alternating row background
+    pub striped_rows: bool,
+    /// Draw lines between rows in the TUI
+    pub row_lines: bool,
+    /// Draw lines between columns in the TUI
+    pub column_lines: bool,
+    /// Show Excel row numbers and column letters around the TUI table
+    pub gutter: bool,
+    /// Show formulas instead of their values in the TUI
+    pub formulas: bool,
+    /// Rows page_up/page_down move in the TUI; 0 for the rows on screen
+    pub page_size: usize,
+    /// Rows of context kept visible above and below the cursor in the TUI
+    pub scroll_margin: usize,
+    /// Colors the terminal can show: "auto", "truecolor", "256", "16", or "none"
+    pub color_mode: String,
+    /// TUI status bar text with placeholders like "{cell}" and "{value}"; empty for the
+    /// built-in text
+    pub status_bar: String,
+}
+
+/// How numbers and dates are displayed (terminal tables and the TUI; exports are unaffected)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// strftime pattern for dates, e.g. "%d.%m.%Y"
+    pub date_format: String,
+    pub decimal_separator: String,
+    /// Empty for no grouping
+    pub thousands_separator: String,
+    /// Shown with numbers in cells formatted as currency; a leading space puts it after
+    /// the number. Empty to show plain numbers.
+    pub currency_symbol: String,
+}
+
+/// On-disk cache of large parsed sheets (~/.cache/xleak)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Size limit; the least recently used sheets are removed beyond it
+    pub max_size_mb: u64,
+}
+
+/// Reopening a workbook in the TUI where it was left (sheet, cursor, filter, column widths)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub enabled: bool,
+    /// Keep bookmarks (`m` + letter) along with the rest of the session
+    pub marks: bool,
+}
+
+/// How CSV exports are written; the command-line flags of the same names override it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvConfig {
+    /// A single character, or "tab"
+    pub delimiter: String,
+    /// "necessary", "always", "nonnumeric", or "never"
+    pub quote: String,
+    /// "lf" or "crlf"
+    pub line_ending: String,
+    /// Start with a UTF-8 byte order mark
+    pub bom: bool,
 }
 
 /// Keybindings configuration
@@ -39,15 +119,43 @@ pub struct UiConfig {
 pub struct KeybindingsConfig {
     /// Keybinding profile: "default", "vim", or "custom"
     pub profile: String,
-    /// Custom keybindings (overrides profile)
+    /// Key that stands for "leader" in key sequences (e.g. `finder = "leader f"`)
+    pub leader: String,
+    /// Custom keybindings (overrides profile): a key, or keys separated by spaces to
+    /// press one after the other ("g e")
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub custom: HashMap<String, String>,
 }
 
+/// Sensitive-data scan configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Extra regex rules added to the built-in ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ScanRuleConfig>,
+}
+
+/// A user-defined scan rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanRuleConfig {
+    pub name: String,
+    pub pattern: String,
+    /// Rule set the rule belongs to, selectable with `--rules`
+    #[serde(default = "default_scan_set")]
+    pub set: String,
+}
+
+fn default_scan_set() -> String {
+    "custom".to_string()
+}
+
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
-            default: "Default".to_string(),
+            default: "auto".to_string(),
+            light: "Solarized Light".to_string(),
+            dark: "Default".to_string(),
         }
     }
 }
@@ -57,6 +165,57 @@ impl Default for UiConfig {
         Self {
             max_rows: 50,
             column_width: 30,
+            excel_colors: false,
+            striped_rows: true,
+            row_lines: false,
+            column_lines: false,
+            gutter: false,
+            formulas: false,
+            page_size: 0,
+            scroll_margin: 0,
+            color_mode: "auto".to_string(),
+            status_bar: String::new(),
+        }
+    }
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        let locale = xleak::locale::NumberLocale::default();
+        Self {
+            date_format: locale.date_format,
+            decimal_separator: locale.decimal_separator,
+            thousands_separator: locale.thousands_separator,
+            currency_symbol: locale.currency_symbol,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_mb: 500,
+        }
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            marks: true,
+        }
+    }
+}
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        Self {
+            delimiter: ",".to_string(),
+            quote: "necessary".to_string(),
+            line_ending: "lf".to_string(),
+            bom: false,
         }
     }
 }
@@ -65,6 +224,7 @@ impl Default for KeybindingsConfig {
     fn default() -> Self {
         Self {
             profile: "default".to_string(),
+            leader: "Space".to_string(),
             custom: HashMap::new(),
         }
     }
@@ -79,18 +239,66 @@ impl Config {
             Self::default_config_path()?
         };
 
-        if !config_path.exists() {
-            // No config file, return defaults
-            return Ok(Self::default());
-        }
+        let mut config = if config_path.exists() {
+            let config_str = fs::read_to_string(&config_path).with_context(|| {
+                format!("Failed to read config file: {}", config_path.display())
+            })?;
+            toml::from_str(&config_str).with_context(|| {
+                format!("Failed to parse config file: {}", config_path.display())
+            })?
+        } else {
+            // No config file, use defaults
+            Self::default()
+        };
+        config
+            .check_keybindings()
+            .with_context(|| format!("Invalid config file: {}", config_path.display()))?;
+        config.load_theme_files(&config_path.with_file_name("themes"))?;
 
-        let config_str = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        Ok(config)
+    }
 
-        let config: Config = toml::from_str(&config_str)
-            .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
+    /// Check `[keybindings.custom]` against the TUI's actions, since a misspelled action or
+    /// key would otherwise be ignored without a word
+    fn check_keybindings(&self) -> Result<()> {
+        let leader = &self.keybindings.leader;
+        if parse_key_string(leader).is_none() {
+            bail!("Invalid leader key '{leader}' in [keybindings]");
+        }
+        let actions = crate::keymap::actions();
+        for (action, key) in &self.keybindings.custom {
+            if !actions.contains(&action.as_str()) {
+                bail!("Unknown action '{action}' in [keybindings.custom]");
+            }
+            if parse_key_sequence(key, leader).is_none() {
+                bail!("Invalid key '{key}' for {action} in [keybindings.custom]");
+            }
+        }
+        Ok(())
+    }
 
-        Ok(config)
+    /// Add the themes in `dir`, one `<name>.toml` file each; a `[themes.<name>]` table in
+    /// the config file wins over a file of the same name
+    fn load_theme_files(&mut self, dir: &Path) -> Result<()> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let theme_str = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read theme file: {}", path.display()))?;
+            let theme = toml::from_str(&theme_str)
+                .with_context(|| format!("Failed to parse theme file: {}", path.display()))?;
+            self.themes.entry(name.to_string()).or_insert(theme);
+        }
+        Ok(())
     }
 
     /// Get the default config file path
@@ -142,66 +350,265 @@ impl Config {
 
     /// Generate example TOML config
     fn example_toml() -> String {
-        r#"# xleak configuration file
+        r##"# xleak configuration file
 # Location: $XDG_CONFIG_HOME/xleak/config.toml (usually ~/.config/xleak/config.toml)
 
 [theme]
 # Default theme to use on startup
-# Options: "Default", "Dracula", "Solarized Dark", "Solarized Light", "GitHub Dark", "Nord"
-default = "Default"
+# Options: "Default", "Dracula", "Solarized Dark", "Solarized Light", "GitHub Dark", "Nord",
+# a custom theme, or "auto" to pick `light` or `dark` by the terminal's background
+default = "auto"
+light = "Solarized Light"
+dark = "Default"
+
+# Custom themes join the `t` cycle; a file themes/<name>.toml next to this one works too.
+# Start from a built-in theme and set any ColorScheme field to "#rrggbb", a color name,
+# or an ANSI number
+# [themes.ocean]
+# base = "Nord"
+# header_fg = "#ffb86c"
+# current_cell_bg = "#005f87"
+# alternating_row_bg = "none"
 
 [ui]
 # Default maximum rows to display in non-interactive mode (0 = all)
 max_rows = 50
 # Default maximum column width in characters
 column_width = 30
+# Show .xlsx cells in the TUI with their fill and font colors (also --excel-colors)
+excel_colors = false
+# Shade every other row, and draw lines between rows and columns, in the TUI
+# (toggle with :set striped_rows, :set row_lines, :set column_lines)
+striped_rows = true
+row_lines = false
+column_lines = false
+# Excel row numbers on the left and column letters above the headers (key: #, :set gutter)
+gutter = false
+# Start the TUI showing formulas instead of their values (key: `, also --formulas)
+formulas = false
+# Rows page_up/page_down move (0 = the rows on screen), and rows kept visible above and
+# below the cursor while scrolling (:set page_size=N, :set scroll_margin=N)
+page_size = 0
+scroll_margin = 0
+# Colors the terminal can show: "auto" (from COLORTERM and TERM), "truecolor", "256", "16",
+# or "none"; theme and heatmap colors are mapped to the nearest ones (also --color-mode)
+color_mode = "auto"
+# TUI status bar text, e.g. " {cell} | {value} | {rows}x{cols} | {match} "; "" for the built-in
+# text. Placeholders: {cell} {value} {sheet} {file} {rows} {cols} {dims} {theme} {match}
+# {selection} {stats} {keys} {link} {mode}; |-separated parts left empty are dropped
+status_bar = ""
+
+[format]
+# How numbers and dates are displayed in tables and the TUI (exports keep plain values)
+# European style: date_format = "%d.%m.%Y", decimal_separator = ",", thousands_separator = "."
+date_format = "%Y-%m-%d"
+decimal_separator = "."
+thousands_separator = ","
+# Shown with numbers in cells formatted as currency, e.g. "$" (1,234.56 shows as
+# $1,234.56); start it with a space to put it after the number (" €" shows 1.234,56 €).
+# Empty shows the plain number.
+currency_symbol = ""
+
+[cache]
+# Keep large parsed sheets in ~/.cache/xleak so reopening a workbook is near-instant
+# (--no-cache skips it for one run)
+enabled = true
+# Size limit in MB; the least recently used sheets are removed beyond it
+max_size_mb = 500
+
+[session]
+# Reopen workbooks in the TUI at the sheet, cursor, filter, and column widths you left
+# (--no-session skips it for one run)
+enabled = true
+# Keep bookmarks set with m + letter too
+marks = true
+
+[csv]
+# How CSV exports are written (--delimiter, --quote, --line-ending, and --bom override it)
+# European Excel: delimiter = ";" and bom = true; Windows tools may want line_ending = "crlf"
+delimiter = ","
+quote = "necessary"
+line_ending = "lf"
+bom = false
 
 [keybindings]
 # Keybinding profile: "default" or "vim"
 profile = "default"
+# Key that "leader" stands for in key sequences
+leader = "Space"
 
 # Custom keybindings (optional - overrides profile)
-# Uncomment and modify to customize individual keys
+# Uncomment and modify to customize individual keys; keys separated by spaces are
+# pressed one after the other ("g e", "leader f")
 # [keybindings.custom]
 # quit = "q"
 # help = "?"
 # theme_toggle = "t"
 # search = "/"
+# search_workbook = "Ctrl+/"
+# search_results = "F"
+# command = ":"
 # next_match = "n"
 # prev_match = "N"
 # copy_cell = "c"
 # copy_row = "C"
+# visual_mode = "v"
+# export_selection = "w"
 # jump = "Ctrl+g"
+# filter = "f"
+# freeze_columns = "z"
+# hide_column = "H"
+# unhide_columns = "U"
+# pin_column = "p"
+# shrink_column = "<"
+# grow_column = ">"
+# autofit_column = "="
 # show_cell_detail = "Enter"
+# show_record = "R"
+# edit_cell = "i"
+# show_pivots = "P"
+# reload = "r"
+# set_mark = "m"
+# jump_to_mark = "'"
+# list_marks = "M"
+# jump_back = "Ctrl+o"
+# jump_forward = "Ctrl+n"
+# switch_pane = "Ctrl+w"
+# workbooks = "W"
+# finder = "Ctrl+p"
+# pivot = "a"
+# bars = "b"
+# heatmap = "B"
+# gutter = "#"
+# formulas = "`"
+# chart = "x"
+# next_error = "e"
+# open_link = "o"
+# next_change = "]"
+# prev_change = "["
 
 # VIM-style navigation (when profile = "vim")
 # up = "k"
 # down = "j"
 # left = "h"
 # right = "l"
-# page_up = "Ctrl+u"
-# page_down = "Ctrl+d"
-# jump_to_top = "g"
-# jump_to_bottom = "G"
+# half_page_up = "Ctrl+u"
+# half_page_down = "Ctrl+d"
+# jump_to_top = "g g"
+# jump_to_bottom = "g e"
 # jump_to_row_start = "0"
 # jump_to_row_end = "$"
-"#
+
+# Custom rules for `xleak scan` (optional - added to the built-in pii/secrets rules)
+# [[scan.rules]]
+# name = "employee_id"
+# pattern = "EMP-\\d{6}"
+# set = "internal"   # select with --rules internal (default set: "custom")
+"##
         .to_string()
     }
 
-    /// Get keybinding for an action based on profile and custom overrides
+    /// Get keybinding for an action based on profile and custom overrides; None for
+    /// actions bound to a sequence of keys
+    #[cfg(test)]
     pub fn get_keybinding(&self, action: &str) -> Option<(KeyCode, KeyModifiers)> {
+        match self.get_key_sequence(action)?.as_slice() {
+            [key] => Some(*key),
+            _ => None,
+        }
+    }
+
+    /// The keys to press one after the other for an action (usually just one)
+    pub fn get_key_sequence(&self, action: &str) -> Option<Vec<(KeyCode, KeyModifiers)>> {
         // Check custom bindings first
         if let Some(key_str) = self.keybindings.custom.get(action) {
-            return parse_key_string(key_str);
+            return parse_key_sequence(key_str, &self.keybindings.leader);
         }
 
         // Fall back to profile defaults
         match self.keybindings.profile.as_str() {
-            "vim" => get_vim_keybinding(action),
-            _ => get_default_keybinding(action),
+            "vim" => match get_vim_sequence(action) {
+                Some(keys) => parse_key_sequence(keys, &self.keybindings.leader),
+                None => get_vim_keybinding(action).map(|key| vec![key]),
+            },
+            _ => get_default_keybinding(action).map(|key| vec![key]),
         }
     }
+
+    /// Label of an action's keys: "Ctrl+G", or "gg" and "Space f" for sequences
+    pub fn key_sequence_label(&self, action: &str) -> Option<String> {
+        let labels: Vec<String> = self
+            .get_key_sequence(action)?
+            .into_iter()
+            .map(|(code, modifiers)| key_label(code, modifiers))
+            .collect();
+        let separator = if labels.iter().all(|label| label.chars().count() == 1) {
+            ""
+        } else {
+            " "
+        };
+        Some(labels.join(separator))
+    }
+}
+
+/// Human-readable label for a binding, e.g. "Ctrl+G", "Shift+Tab", "N", "↑"
+pub fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match code {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::Char(' ') => "Space".to_string(),
+        // Letters read better uppercased after Ctrl/Alt (Ctrl+G)
+        KeyCode::Char(c) if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+            c.to_ascii_uppercase().to_string()
+        }
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{other:?}"),
+    };
+
+    let mut label = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("Alt+");
+    }
+    // Shifted characters already show as themselves ("N", "?")
+    if modifiers.contains(KeyModifiers::SHIFT)
+        && !matches!(code, KeyCode::Char(_) | KeyCode::BackTab)
+    {
+        label.push_str("Shift+");
+    }
+    label.push_str(&key);
+    label
+}
+
+/// Parse keys separated by spaces ("g g", "leader f"), "leader" standing for `leader`
+fn parse_key_sequence(s: &str, leader: &str) -> Option<Vec<(KeyCode, KeyModifiers)>> {
+    let keys = s
+        .split_whitespace()
+        .map(|key| {
+            if key.eq_ignore_ascii_case("leader") {
+                parse_key_string(leader)
+            } else {
+                parse_key_string(key)
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!keys.is_empty()).then_some(keys)
 }
 
 /// Parse a key string like "q", "Ctrl+g", "Enter" into KeyCode and KeyModifiers
@@ -225,6 +632,7 @@ fn parse_key_string(s: &str) -> Option<(KeyCode, KeyModifiers)> {
         k if k.eq_ignore_ascii_case("esc") => KeyCode::Esc,
         k if k.eq_ignore_ascii_case("escape") => KeyCode::Esc,
         k if k.eq_ignore_ascii_case("tab") => KeyCode::Tab,
+        k if k.eq_ignore_ascii_case("space") => KeyCode::Char(' '),
         k if k.eq_ignore_ascii_case("backtab") => KeyCode::BackTab,
         k if k.eq_ignore_ascii_case("backspace") => KeyCode::Backspace,
         k if k.eq_ignore_ascii_case("delete") => KeyCode::Delete,
@@ -254,12 +662,47 @@ fn get_default_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
         "help" => ("?", KeyModifiers::SHIFT),
         "theme_toggle" => ("t", KeyModifiers::empty()),
         "search" => ("/", KeyModifiers::empty()),
+        "search_workbook" => ("/", KeyModifiers::CONTROL),
+        "search_results" => ("F", KeyModifiers::SHIFT),
+        "command" => (":", KeyModifiers::empty()),
         "next_match" => ("n", KeyModifiers::empty()),
         "prev_match" => ("N", KeyModifiers::SHIFT),
         "copy_cell" => ("c", KeyModifiers::empty()),
         "copy_row" => ("C", KeyModifiers::SHIFT),
+        "visual_mode" => ("v", KeyModifiers::empty()),
+        "export_selection" => ("w", KeyModifiers::empty()),
         "jump" => ("g", KeyModifiers::CONTROL),
+        "filter" => ("f", KeyModifiers::empty()),
+        "freeze_columns" => ("z", KeyModifiers::empty()),
+        "hide_column" => ("H", KeyModifiers::SHIFT),
+        "unhide_columns" => ("U", KeyModifiers::SHIFT),
+        "pin_column" => ("p", KeyModifiers::empty()),
+        "shrink_column" => ("<", KeyModifiers::empty()),
+        "grow_column" => (">", KeyModifiers::empty()),
+        "autofit_column" => ("=", KeyModifiers::empty()),
         "show_cell_detail" => ("Enter", KeyModifiers::empty()),
+        "show_record" => ("R", KeyModifiers::SHIFT),
+        "edit_cell" => ("i", KeyModifiers::empty()),
+        "show_pivots" => ("P", KeyModifiers::SHIFT),
+        "reload" => ("r", KeyModifiers::empty()),
+        "set_mark" => ("m", KeyModifiers::empty()),
+        "jump_to_mark" => ("'", KeyModifiers::empty()),
+        "list_marks" => ("M", KeyModifiers::SHIFT),
+        "jump_back" => ("o", KeyModifiers::CONTROL),
+        "jump_forward" => ("n", KeyModifiers::CONTROL),
+        "switch_pane" => ("w", KeyModifiers::CONTROL),
+        "workbooks" => ("W", KeyModifiers::SHIFT),
+        "finder" => ("p", KeyModifiers::CONTROL),
+        "pivot" => ("a", KeyModifiers::empty()),
+        "bars" => ("b", KeyModifiers::empty()),
+        "heatmap" => ("B", KeyModifiers::SHIFT),
+        "gutter" => ("#", KeyModifiers::SHIFT),
+        "formulas" => ("`", KeyModifiers::empty()),
+        "chart" => ("x", KeyModifiers::empty()),
+        "next_error" => ("e", KeyModifiers::empty()),
+        "open_link" => ("o", KeyModifiers::empty()),
+        "next_change" => ("]", KeyModifiers::empty()),
+        "prev_change" => ("[", KeyModifiers::empty()),
         "next_sheet" => ("Tab", KeyModifiers::empty()),
         "prev_sheet" => ("Tab", KeyModifiers::SHIFT),
         "up" => ("Up", KeyModifiers::empty()),
@@ -268,6 +711,8 @@ fn get_default_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
         "right" => ("Right", KeyModifiers::empty()),
         "page_up" => ("PageUp", KeyModifiers::empty()),
         "page_down" => ("PageDown", KeyModifiers::empty()),
+        "half_page_up" => ("u", KeyModifiers::CONTROL),
+        "half_page_down" => ("d", KeyModifiers::CONTROL),
         "jump_to_top" => ("Home", KeyModifiers::CONTROL),
         "jump_to_bottom" => ("End", KeyModifiers::CONTROL),
         "jump_to_row_start" => ("Home", KeyModifiers::empty()),
@@ -278,6 +723,15 @@ fn get_default_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
     parse_key_string(binding.0).map(|(code, _)| (code, binding.1))
 }
 
+/// VIM-style bindings that take more than one key
+fn get_vim_sequence(action: &str) -> Option<&'static str> {
+    match action {
+        "jump_to_top" => Some("g g"),
+        "jump_to_bottom" => Some("g e"),
+        _ => None,
+    }
+}
+
 /// Get VIM-style keybinding for an action
 fn get_vim_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
     let binding = match action {
@@ -286,10 +740,6 @@ fn get_vim_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
         "down" => ("j", KeyModifiers::empty()),
         "left" => ("h", KeyModifiers::empty()),
         "right" => ("l", KeyModifiers::empty()),
-        "page_up" => ("u", KeyModifiers::CONTROL),
-        "page_down" => ("d", KeyModifiers::CONTROL),
-        "jump_to_top" => ("g", KeyModifiers::empty()),
-        "jump_to_bottom" => ("G", KeyModifiers::SHIFT),
         "jump_to_row_start" => ("0", KeyModifiers::empty()),
         "jump_to_row_end" => ("$", KeyModifiers::SHIFT),
         // VIM-style actions
@@ -330,7 +780,9 @@ mod tests {
     #[test]
     fn test_default_config() {
         let config = Config::default();
-        assert_eq!(config.theme.default, "Default");
+        assert_eq!(config.theme.default, "auto");
+        assert_eq!(config.theme.light, "Solarized Light");
+        assert_eq!(config.theme.dark, "Default");
         assert_eq!(config.ui.max_rows, 50);
         assert_eq!(config.keybindings.profile, "default");
     }
@@ -360,6 +812,25 @@ mod tests {
         assert_eq!(config.keybindings.profile, "vim");
     }
 
+    #[test]
+    fn test_format_parsing() {
+        let config_str = "[format]\ndate_format = \"%d.%m.%Y\"\ndecimal_separator = \",\"";
+        let config: Config = toml::from_str(config_str).expect("Failed to parse TOML");
+        assert_eq!(config.format.date_format, "%d.%m.%Y");
+        assert_eq!(config.format.decimal_separator, ",");
+        assert_eq!(config.format.thousands_separator, ",");
+    }
+
+    #[test]
+    fn test_scan_rules_parsing() {
+        let config_str = "[[scan.rules]]\nname = \"employee_id\"\npattern = \"EMP-\\\\d{6}\"\n\n[[scan.rules]]\nname = \"project\"\npattern = \"PRJ-[0-9]+\"\nset = \"internal\"";
+        let config: Config = toml::from_str(config_str).expect("Failed to parse TOML");
+        assert_eq!(config.scan.rules.len(), 2);
+        assert_eq!(config.scan.rules[0].pattern, r"EMP-\d{6}");
+        assert_eq!(config.scan.rules[0].set, "custom");
+        assert_eq!(config.scan.rules[1].set, "internal");
+    }
+
     #[test]
     fn test_toml_parsing_windows_line_endings() {
         let config_str = "[theme]\r\ndefault = \"Nord\"\r\n\r\n[ui]\r\nmax_rows = 200\r\n\r\n[keybindings]\r\nprofile = \"default\"";
@@ -418,6 +889,26 @@ mod tests {
         assert_eq!(config.theme.default, "NonexistentTheme");
     }
 
+    #[test]
+    fn test_custom_themes() {
+        let config_str = "[theme]\ndefault = \"Ocean\"\n\n[themes.Ocean]\nbase = \"Nord\"\nheader_fg = \"#ffb86c\"";
+        let mut config: Config = toml::from_str(config_str).unwrap();
+        assert_eq!(config.themes["Ocean"]["header_fg"], "#ffb86c");
+
+        let dir = std::env::temp_dir().join(format!("xleak-test-{}-themes", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Ocean.toml"), "header_fg = \"red\"").unwrap();
+        fs::write(dir.join("paper.toml"), "base = \"Solarized Light\"").unwrap();
+        fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+        config.load_theme_files(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The config's own table wins over a file of the same name
+        assert_eq!(config.themes["Ocean"]["header_fg"], "#ffb86c");
+        assert_eq!(config.themes["paper"]["base"], "Solarized Light");
+        assert_eq!(config.themes.len(), 2);
+    }
+
     // =========================================================================
     // Keybinding Override Tests
     // =========================================================================
@@ -484,6 +975,67 @@ page_up = "Ctrl+b"
         );
     }
 
+    #[test]
+    fn test_check_keybindings() {
+        let mut config = Config::default();
+        config
+            .keybindings
+            .custom
+            .insert("quit".to_string(), "Ctrl+q".to_string());
+        assert!(config.check_keybindings().is_ok());
+
+        config
+            .keybindings
+            .custom
+            .insert("qiut".to_string(), "x".to_string());
+        assert!(config.check_keybindings().is_err());
+
+        config.keybindings.custom.remove("qiut");
+        config
+            .keybindings
+            .custom
+            .insert("quit".to_string(), "Hyper+q".to_string());
+        assert!(config.check_keybindings().is_err());
+    }
+
+    #[test]
+    fn test_key_sequences() {
+        let mut config = Config::default();
+        config.keybindings.profile = "vim".to_string();
+        let g = (KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(config.get_key_sequence("jump_to_top"), Some(vec![g, g]));
+        assert_eq!(config.get_keybinding("jump_to_top"), None);
+        assert_eq!(config.key_sequence_label("jump_to_top").unwrap(), "gg");
+        let e = (KeyCode::Char('e'), KeyModifiers::NONE);
+        assert_eq!(config.get_key_sequence("jump_to_bottom"), Some(vec![g, e]));
+        assert_eq!(config.get_keybinding("jump_to_bottom"), None);
+        assert_eq!(config.key_sequence_label("jump_to_bottom").unwrap(), "ge");
+
+        config
+            .keybindings
+            .custom
+            .insert("finder".to_string(), "leader f".to_string());
+        config
+            .keybindings
+            .custom
+            .insert("next_sheet".to_string(), "g t".to_string());
+        assert!(config.check_keybindings().is_ok());
+        assert_eq!(
+            config.get_key_sequence("finder"),
+            Some(vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('f'), KeyModifiers::NONE)
+            ])
+        );
+        assert_eq!(config.key_sequence_label("finder").unwrap(), "Space f");
+        assert_eq!(config.key_sequence_label("next_sheet").unwrap(), "gt");
+
+        config.keybindings.leader = ",".to_string();
+        assert_eq!(config.key_sequence_label("finder").unwrap(), ",f");
+        config.keybindings.leader = "Hyper".to_string();
+        assert!(config.check_keybindings().is_err());
+    }
+
     #[test]
     fn test_get_keybinding_returns_none_for_unknown_action() {
         let config = Config::default();
@@ -578,4 +1130,28 @@ page_up = "Ctrl+b"
             Some((KeyCode::Char('/'), KeyModifiers::empty()))
         );
     }
+
+    #[test]
+    fn test_key_labels() {
+        let default = Config::default();
+        let label = |config: &Config, action: &str| {
+            let (code, modifiers) = config.get_keybinding(action).unwrap();
+            key_label(code, modifiers)
+        };
+        assert_eq!(label(&default, "jump"), "Ctrl+G");
+        assert_eq!(label(&default, "prev_match"), "N");
+        assert_eq!(label(&default, "prev_sheet"), "Shift+Tab");
+        assert_eq!(label(&default, "jump_to_top"), "Ctrl+Home");
+        assert_eq!(label(&default, "up"), "↑");
+
+        let mut vim = Config::default();
+        vim.keybindings.profile = "vim".to_string();
+        vim.keybindings
+            .custom
+            .insert("quit".to_string(), "Ctrl+q".to_string());
+        assert_eq!(label(&vim, "up"), "k");
+        assert_eq!(label(&vim, "page_down"), "PageDown");
+        assert_eq!(label(&vim, "half_page_down"), "Ctrl+D");
+        assert_eq!(label(&vim, "quit"), "Ctrl+Q");
+    }
 }
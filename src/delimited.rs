@@ -0,0 +1,207 @@
+//! Plain CSV/TSV files opened as single-sheet workbooks
+
+use anyhow::{Context, Result};
+use calamine::{Data, Range};
+use std::path::Path;
+
+/// Delimiters tried when sniffing, in order of preference on ties
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Lines examined when sniffing the delimiter
+const SNIFF_LINES: usize = 20;
+
+/// The whole file as one sheet
+pub struct DelimitedSheet {
+    /// File stem, used as the sheet name
+    pub name: String,
+    /// "csv" or "tsv"
+    pub format: &'static str,
+    pub range: Range<Data>,
+}
+
+/// "csv" or "tsv" if the path has a delimited-text extension
+pub fn format_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    ["csv", "tsv"]
+        .into_iter()
+        .find(|format| ext.eq_ignore_ascii_case(format))
+}
+
+/// Reads and parses the file, sniffing the delimiter (tab is assumed for .tsv unless
+/// another delimiter fits better)
+pub fn load(path: &Path, format: &'static str) -> Result<DelimitedSheet> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    let default = if format == "tsv" { '\t' } else { ',' };
+    let rows = parse(text, sniff_delimiter(text, default));
+
+    Ok(DelimitedSheet {
+        name: path.file_stem().map_or_else(
+            || "Sheet1".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        ),
+        format,
+        range: to_range(&rows),
+    })
+}
+
+/// Parses delimited text (RFC 4180 quoting, LF or CRLF line endings) into rows of fields
+pub fn parse(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    // Last line without a trailing newline
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Picks the delimiter that splits the first lines into the same number of fields,
+/// preferring more fields; falls back to `default`
+pub fn sniff_delimiter(text: &str, default: char) -> char {
+    let lines: Vec<&str> = text.lines().take(SNIFF_LINES + 1).collect();
+    // A cut-off sample may end inside a quoted field, so skip its last row
+    let truncated = lines.len() > SNIFF_LINES;
+    let sample = lines[..lines.len().min(SNIFF_LINES)].join("\n");
+
+    let candidates =
+        std::iter::once(default).chain(DELIMITERS.into_iter().filter(|&d| d != default));
+    let mut best = (default, 1);
+    for delimiter in candidates {
+        let mut rows = parse(&sample, delimiter);
+        if truncated && rows.len() > 1 {
+            rows.pop();
+        }
+        let Some(fields) = rows.first().map(Vec::len) else {
+            continue;
+        };
+        let consistent = rows
+            .iter()
+            .all(|row| row.len() == fields || row.iter().all(String::is_empty));
+        if consistent && fields > best.1 {
+            best = (delimiter, fields);
+        }
+    }
+    best.0
+}
+
+/// Grid of typed cells; ragged rows are padded with empty cells
+fn to_range(rows: &[Vec<String>]) -> Range<Data> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        return Range::empty();
+    }
+    let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1));
+    for (r, row) in rows.iter().enumerate() {
+        for (c, field) in row.iter().enumerate() {
+            if !field.is_empty() {
+                range.set_value((r as u32, c as u32), infer(field));
+            }
+        }
+    }
+    range
+}
+
+/// Significant digits a float holds exactly, as Excel shows them
+const FLOAT_DIGITS: usize = 15;
+
+/// Numbers and TRUE/FALSE become typed cells; everything else stays text.
+/// Values with leading zeros (IDs, zip codes) are kept as text, and so are numbers too
+/// long for an i64 with more digits than a float keeps (`12345678901234567890`).
+fn infer(field: &str) -> Data {
+    let digits = field.strip_prefix('-').unwrap_or(field);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !leading_zero {
+        if let Ok(i) = field.parse::<i64>() {
+            return Data::Int(i);
+        }
+        // Rust also parses "inf" and "NaN", which are text in a spreadsheet
+        if field.bytes().any(|b| b.is_ascii_digit())
+            && significant_digits(field) <= FLOAT_DIGITS
+            && let Ok(f) = field.parse::<f64>()
+        {
+            return Data::Float(f);
+        }
+    }
+    if field.eq_ignore_ascii_case("true") {
+        Data::Bool(true)
+    } else if field.eq_ignore_ascii_case("false") {
+        Data::Bool(false)
+    } else {
+        Data::String(field.to_string())
+    }
+}
+
+/// Digits of a number's mantissa without its leading and trailing zeros
+fn significant_digits(number: &str) -> usize {
+    let mantissa = number.split(['e', 'E']).next().unwrap_or(number);
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    digits.trim_start_matches('0').trim_end_matches('0').len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiter() {
+        assert_eq!(sniff_delimiter("a;b;c\n1;2,5;3\n", ','), ';');
+        assert_eq!(sniff_delimiter("name\tnote\nx\t\"a, b\"\n", ','), '\t');
+        assert_eq!(sniff_delimiter("a,b\n\"1|2\",3\n", '\t'), ',');
+        // One column: nothing splits, so the default wins
+        assert_eq!(sniff_delimiter("total\n12\n", '\t'), '\t');
+    }
+
+    #[test]
+    fn test_typed_cells() {
+        let range = to_range(&parse(
+            "id,qty,price,ok,zip\nA1,3,-2.5,TRUE,02139\nB2\n",
+            ',',
+        ));
+        assert_eq!(range.get_size(), (3, 5));
+        assert_eq!(range.get_value((1, 0)), Some(&Data::String("A1".into())));
+        assert_eq!(range.get_value((1, 1)), Some(&Data::Int(3)));
+        assert_eq!(range.get_value((1, 2)), Some(&Data::Float(-2.5)));
+        assert_eq!(range.get_value((1, 3)), Some(&Data::Bool(true)));
+        assert_eq!(range.get_value((1, 4)), Some(&Data::String("02139".into())));
+        assert_eq!(range.get_value((2, 1)), Some(&Data::Empty));
+        assert_eq!(infer("NaN"), Data::String("NaN".into()));
+        assert_eq!(
+            infer("12345678901234567890"),
+            Data::String("12345678901234567890".into())
+        );
+        assert_eq!(infer("1e20"), Data::Float(1e20));
+        assert_eq!(infer("0.000120"), Data::Float(0.00012));
+    }
+}
@@ -0,0 +1,70 @@
+//! Finding the table inside a sheet laid out for people: past title rows, side notes,
+//! and stray cells
+
+/// Corners (top-left, bottom-right, inclusive) of the table in a grid of filled cells,
+/// relative to the grid; None when nothing is filled.
+///
+/// Table rows fill at least half as many cells as the fullest row (and at least two), so
+/// titles and one-cell notes fall outside; the longest unbroken run of such rows is the
+/// table. Within it, columns are kept when they have a header or are at least half filled,
+/// and the longest unbroken run of kept columns wins.
+pub fn detect_region(filled: &[Vec<bool>]) -> Option<((usize, usize), (usize, usize))> {
+    let counts: Vec<usize> = filled
+        .iter()
+        .map(|row| row.iter().filter(|&&cell| cell).count())
+        .collect();
+    let fullest = counts.iter().copied().max().filter(|&n| n > 0)?;
+    let dense = fullest.div_ceil(2).max(fullest.min(2));
+    let (top, bottom) = longest_run(counts.iter().map(|&n| n >= dense))?;
+
+    let rows = &filled[top..=bottom];
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let is_filled = |row: &Vec<bool>, col: usize| row.get(col).copied().unwrap_or(false);
+    let keep = (0..width).map(|col| {
+        let count = rows.iter().filter(|row| is_filled(row, col)).count();
+        is_filled(&rows[0], col) || count * 2 >= rows.len()
+    });
+    let (left, right) = longest_run(keep)?;
+    Some(((top, left), (bottom, right)))
+}
+
+/// First and last index of the longest run of `true`s (the earliest one on ties)
+fn longest_run(flags: impl Iterator<Item = bool>) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = None;
+    for (i, flag) in flags.enumerate() {
+        if !flag {
+            start = None;
+            continue;
+        }
+        let first = *start.get_or_insert(i);
+        if best.is_none_or(|(a, b)| i - first > b - a) {
+            best = Some((first, i));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(lines: &[&str]) -> Vec<Vec<bool>> {
+        lines
+            .iter()
+            .map(|line| line.chars().map(|c| c != '.').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_region() {
+        // Title, blank row, a 3x4 table with a gap in one cell, a stray note to the right,
+        // and a footnote under it
+        let filled = grid(&[
+            "x......", ".......", ".xxx...", ".xxx..x", ".x.x...", ".xxx...", ".......", ".x.....",
+        ]);
+        assert_eq!(detect_region(&filled), Some(((2, 1), (5, 3))));
+        assert_eq!(detect_region(&grid(&["...", "..."])), None);
+        assert_eq!(detect_region(&grid(&["..x"])), Some(((0, 2), (0, 2))));
+    }
+}
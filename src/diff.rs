@@ -0,0 +1,183 @@
+//! Cell-by-cell comparison of two workbooks
+
+use crate::workbook::{Workbook, column_letter};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// How a cell differs between the old and new workbook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// A cell that differs; `old` or `new` is empty for added and removed cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange {
+    /// Absolute 0-based position
+    pub row: usize,
+    pub col: usize,
+    pub kind: ChangeKind,
+    pub old: String,
+    pub new: String,
+}
+
+impl CellChange {
+    /// A1-style address
+    pub fn address(&self) -> String {
+        format!("{}{}", column_letter(self.col), self.row + 1)
+    }
+}
+
+/// Whether a sheet exists in both workbooks or only one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetStatus {
+    Compared,
+    /// Only in the new workbook
+    Added,
+    /// Only in the old workbook
+    Removed,
+}
+
+/// Differences within one sheet (none listed for sheets that exist on one side only)
+#[derive(Debug, Clone)]
+pub struct SheetDiff {
+    pub name: String,
+    pub status: SheetStatus,
+    pub changes: Vec<CellChange>,
+}
+
+impl SheetDiff {
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+}
+
+/// Compares every sheet by name: sheets of the old workbook in order, then sheets only
+/// the new one has. Two workbooks with a single sheet each (CSV files, say) have those
+/// sheets compared whatever they're called, under the new sheet's name.
+pub fn diff_workbooks(old: &mut Workbook, new: &mut Workbook) -> Result<Vec<SheetDiff>> {
+    let old_names = old.sheet_names();
+    let new_names = new.sheet_names();
+    if let ([old_name], [new_name]) = (old_names.as_slice(), new_names.as_slice()) {
+        return Ok(vec![SheetDiff {
+            name: new_name.clone(),
+            status: SheetStatus::Compared,
+            changes: diff_cells(old.sheet_cells(old_name)?, new.sheet_cells(new_name)?),
+        }]);
+    }
+
+    let mut diffs = Vec::new();
+    for name in &old_names {
+        let diff = if new_names.contains(name) {
+            SheetDiff {
+                name: name.clone(),
+                status: SheetStatus::Compared,
+                changes: diff_cells(old.sheet_cells(name)?, new.sheet_cells(name)?),
+            }
+        } else {
+            SheetDiff {
+                name: name.clone(),
+                status: SheetStatus::Removed,
+                changes: Vec::new(),
+            }
+        };
+        diffs.push(diff);
+    }
+    for name in new_names.iter().filter(|n| !old_names.contains(n)) {
+        diffs.push(SheetDiff {
+            name: name.clone(),
+            status: SheetStatus::Added,
+            changes: Vec::new(),
+        });
+    }
+    Ok(diffs)
+}
+
+/// Compares non-empty cells as (row, col, text) by position, in row-major order
+pub fn diff_cells(
+    old: Vec<(usize, usize, String)>,
+    new: Vec<(usize, usize, String)>,
+) -> Vec<CellChange> {
+    let mut cells: BTreeMap<(usize, usize), (String, String)> = BTreeMap::new();
+    for (row, col, text) in old {
+        cells.entry((row, col)).or_default().0 = text;
+    }
+    for (row, col, text) in new {
+        cells.entry((row, col)).or_default().1 = text;
+    }
+
+    cells
+        .into_iter()
+        .filter(|(_, (old, new))| old != new)
+        .map(|((row, col), (old, new))| {
+            let kind = if old.is_empty() {
+                ChangeKind::Added
+            } else if new.is_empty() {
+                ChangeKind::Removed
+            } else {
+                ChangeKind::Changed
+            };
+            CellChange {
+                row,
+                col,
+                kind,
+                old,
+                new,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(entries: &[(usize, usize, &str)]) -> Vec<(usize, usize, String)> {
+        entries
+            .iter()
+            .map(|&(r, c, t)| (r, c, t.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_cells() {
+        let old = cells(&[
+            (0, 0, "Item"),
+            (1, 1, "100"),
+            (8, 0, "Misc"),
+            (2, 0, "Rent"),
+        ]);
+        let new = cells(&[
+            (0, 0, "Item"),
+            (1, 1, "120"),
+            (4, 3, "Travel"),
+            (2, 0, "Rent"),
+        ]);
+        let changes = diff_cells(old, new);
+
+        let summary: Vec<(String, ChangeKind, &str, &str)> = changes
+            .iter()
+            .map(|c| (c.address(), c.kind, c.old.as_str(), c.new.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("B2".to_string(), ChangeKind::Changed, "100", "120"),
+                ("D5".to_string(), ChangeKind::Added, "", "Travel"),
+                ("A9".to_string(), ChangeKind::Removed, "Misc", ""),
+            ]
+        );
+    }
+}
@@ -1,33 +1,139 @@
-use crate::workbook::{CellValue, SheetData};
+//! Terminal table rendering and CSV/JSON/text/Markdown export
+
+use crate::aggregate::{self, Aggregation};
+use crate::color_mode::{self, ColorMode, TermColor};
+use crate::locale::{DateTimeFormat, ExportDates, NumberLocale};
+use crate::progress::Progress;
+use crate::styles::HorizontalAlign;
+use crate::workbook::{CellValue, SheetData, SheetRows};
 use anyhow::Result;
 use comfy_table::{
     Attribute, Cell, CellAlignment, Color, ColumnConstraint, ContentArrangement, Row, Table, Width,
 };
 use crossterm::style::Stylize;
-use std::io::IsTerminal;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-/// Format a cell value with width limiting
-fn format_cell_value(value: &str, max_width: usize, wrap: bool) -> String {
-    let char_count = value.chars().count();
-    if char_count <= max_width {
-        return value.to_string();
+static LOOK: OnceLock<TableLook> = OnceLock::new();
+
+/// How terminal tables are dressed (`--plain`, `--no-banner`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableLook {
+    /// ASCII borders and text with no ANSI styling, for logs and email
+    pub plain: bool,
+    /// Leave out the "xleak - Excel File Viewer" banner
+    pub no_banner: bool,
+}
+
+/// Use `look` for terminal tables. Only the first call has an effect.
+pub fn set_table_look(look: TableLook) {
+    let _ = LOOK.set(look);
+}
+
+fn table_look() -> TableLook {
+    LOOK.get().copied().unwrap_or_default()
+}
+
+/// Print the banner over a terminal table, unless it's turned off (plain output has none)
+pub fn print_banner(title: &str) {
+    let look = table_look();
+    if look.plain || look.no_banner {
+        return;
+    }
+    println!("\n╔═════════════════════════════════════════════════╗");
+    println!("║  {title:<47}║");
+    println!("╚═════════════════════════════════════════════════╝");
+}
+
+/// An empty terminal table, with no bold or colors when plain
+pub fn new_table() -> Table {
+    let mut table = Table::new();
+    if table_look().plain {
+        table.force_no_tty();
+    }
+    table
+}
+
+/// "3 rows × 4 columns" ("x" in plain output)
+pub fn dimensions(rows: usize, columns: usize) -> String {
+    let by = if table_look().plain { "x" } else { "×" };
+    format!("{rows} rows {by} {columns} columns")
+}
+
+/// Put in front of notes under a terminal table
+pub fn warning_prefix() -> &'static str {
+    if table_look().plain {
+        "Note:"
+    } else {
+        "⚠️ "
+    }
+}
+
+/// Terminal columns `text` takes: wide characters (CJK, most emoji) count two and
+/// combining marks none
+pub fn text_width(text: &str) -> usize {
+    text.width()
+}
+
+/// `text` cut to at most `max` terminal columns, whole characters (grapheme clusters) at
+/// a time, ending in `ellipsis` when anything was cut
+pub fn truncate_to_width(text: &str, max: usize, ellipsis: &str) -> String {
+    if text_width(text) <= max {
+        return text.to_string();
+    }
+    let ellipsis = if text_width(ellipsis) < max {
+        ellipsis
+    } else {
+        ""
+    };
+    let room = max - text_width(ellipsis);
+    let mut cut = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        used += text_width(grapheme);
+        if used > room {
+            break;
+        }
+        cut.push_str(grapheme);
     }
+    cut + ellipsis
+}
+
+/// `text` followed by spaces up to `width` terminal columns
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    format!(
+        "{text}{}",
+        " ".repeat(width.saturating_sub(text_width(text)))
+    )
+}
 
+/// Format a cell value with width limiting
+fn format_cell_value(value: &str, max_width: usize, wrap: bool) -> String {
     if wrap {
         // Return full text; comfy-table handles the multi-line wrap based on column width
         value.to_string()
     } else {
-        // Truncate with "..."
-        if max_width > 3 {
-            let truncated: String = value.chars().take(max_width - 3).collect();
-            format!("{}...", truncated)
-        } else {
-            value.chars().take(max_width).collect()
-        }
+        let ellipsis = if table_look().plain { "..." } else { "…" };
+        truncate_to_width(value, max_width, ellipsis)
     }
 }
 
-/// Display sheet data as a formatted table in the terminal
+/// How a column's cells are set to look in the workbook (.xlsx)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColumnLook {
+    pub align: Option<HorizontalAlign>,
+    /// Most of its cells have a currency number format
+    pub currency: bool,
+}
+
+/// Display sheet data as a formatted table in the terminal, with `heatmap` columns'
+/// numbers on a colored background, columns aligned and amounts of money shown as in
+/// Excel where `columns` says so, and a footer row for each of `totals`. Numbers are
+/// shown in `locale`.
+#[allow(clippy::too_many_arguments)]
 pub fn display_table(
     data: &SheetData,
     sheet_name: &str,
@@ -36,15 +142,18 @@ pub fn display_table(
     max_width: usize,
     wrap: bool,
     show_formulas: bool,
+    heatmap: &[usize],
+    columns: &[ColumnLook],
+    totals: &[Aggregation],
+    locale: &NumberLocale,
 ) -> Result<()> {
     // Print header info
-    println!("\n╔═════════════════════════════════════════════════╗");
-    println!("║  xleak - Excel File Viewer                      ║");
-    println!("╚═════════════════════════════════════════════════╝");
+    print_banner("xleak - Excel File Viewer");
     println!();
     println!(
-        "Sheet: {} ({} rows × {} columns)",
-        sheet_name, data.height, data.width
+        "Sheet: {} ({})",
+        sheet_name,
+        dimensions(data.height, data.width)
     );
 
     if all_sheets.len() > 1 {
@@ -82,7 +191,9 @@ pub fn display_table(
                 })
                 .sum();
             if blank_formula_count >= 2 {
-                let prefix = if std::io::stdout().is_terminal() {
+                let prefix = if std::io::stdout().is_terminal()
+                    && color_mode::color_mode() != ColorMode::None
+                {
                     format!("{}", "NOTE:".bold().yellow())
                 } else {
                     "NOTE:".to_string()
@@ -96,11 +207,11 @@ pub fn display_table(
     println!();
 
     if data.rows.is_empty() {
-        println!("⚠️  Sheet is empty");
+        println!("{} Sheet is empty", warning_prefix());
         return Ok(());
     }
 
-    let mut table = Table::new();
+    let mut table = new_table();
     if wrap {
         let width = (data.width as u16)
             .saturating_mul(max_width as u16 + 3)
@@ -113,11 +224,10 @@ pub fn display_table(
     let mut header_row = Row::new();
     for h in &data.headers {
         let formatted = format_cell_value(h, max_width, wrap);
-        header_row.add_cell(
-            Cell::new(formatted)
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Green),
-        );
+        header_row.add_cell(paint(
+            Cell::new(formatted).add_attribute(Attribute::Bold),
+            Color::Green,
+        ));
     }
     table.set_header(header_row);
     table.set_constraints(
@@ -130,6 +240,7 @@ pub fn display_table(
     } else {
         std::cmp::min(max_rows, data.rows.len())
     };
+    let heat_ranges = heat_ranges(&data.rows, heatmap);
 
     for (row_idx, row) in data.rows.iter().enumerate().take(rows_to_show) {
         let mut table_row = Row::new();
@@ -140,124 +251,963 @@ pub fn display_table(
                     .and_then(|formula_row| formula_row.get(col_idx))
                     .and_then(|f| f.as_ref())
                     .cloned()
-                    .unwrap_or_else(|| cell.to_string())
+                    .unwrap_or_else(|| cell.to_display_string(locale))
             } else {
-                cell.to_string()
+                let currency = columns.get(col_idx).is_some_and(|c| c.currency);
+                match cell {
+                    CellValue::Int(i) if currency => locale.currency(*i as f64),
+                    CellValue::Float(f) if currency => locale.currency(*f),
+                    _ => None,
+                }
+                .unwrap_or_else(|| cell.to_display_string(locale))
             };
 
             let formatted = format_cell_value(&value, max_width, wrap);
             let mut cell_obj = Cell::new(formatted);
 
             cell_obj = if show_formulas {
-                cell_obj.set_alignment(CellAlignment::Left).fg(Color::Green)
+                paint(cell_obj.set_alignment(CellAlignment::Left), Color::Green)
             } else {
                 match cell {
-                    CellValue::Int(_) | CellValue::Float(_) => {
-                        cell_obj.set_alignment(CellAlignment::Right)
-                    }
+                    CellValue::Int(_)
+                    | CellValue::Float(_)
+                    | CellValue::Time(_)
+                    | CellValue::Duration(_) => cell_obj.set_alignment(CellAlignment::Right),
                     CellValue::Bool(_) => cell_obj.set_alignment(CellAlignment::Center),
                     CellValue::Error(_) => {
-                        cell_obj.set_alignment(CellAlignment::Center).fg(Color::Red)
+                        paint(cell_obj.set_alignment(CellAlignment::Center), Color::Red)
                     }
                     _ => cell_obj.set_alignment(CellAlignment::Left),
                 }
             };
+            if !show_formulas && let Some(align) = columns.get(col_idx).and_then(|c| c.align) {
+                cell_obj = cell_obj.set_alignment(match align {
+                    HorizontalAlign::Left => CellAlignment::Left,
+                    HorizontalAlign::Center => CellAlignment::Center,
+                    HorizontalAlign::Right => CellAlignment::Right,
+                });
+            }
+            if let Some(&range) = heat_ranges.get(&col_idx) {
+                cell_obj = heat_cell(cell_obj, cell, range);
+            }
             table_row.add_cell(cell_obj);
         }
         table.add_row(table_row);
     }
+    add_totals(&mut table, &data.rows, data.width, totals, locale);
 
     println!("{}", table);
 
     println!();
     if rows_to_show < data.rows.len() {
         println!(
-            "⚠️  Showing {} of {} rows (use -n 0 to show all)",
+            "{} Showing {} of {} rows (use -n 0 to show all){}",
+            warning_prefix(),
             rows_to_show,
-            data.rows.len()
+            data.rows.len(),
+            if totals.is_empty() {
+                ""
+            } else {
+                "; totals cover all rows"
+            }
         );
     } else {
-        println!("Total: {} rows × {} columns", data.height, data.width);
+        println!("Total: {}", dimensions(data.height, data.width));
     }
 
     println!();
     Ok(())
 }
 
-/// Export data as CSV to stdout
-pub fn export_csv(data: &SheetData) -> Result<()> {
-    // Print headers
-    println!("{}", data.headers.join(","));
+/// Append bold `--totals` footer rows (see [`aggregate::totals`]) computed over all `rows`,
+/// including any not shown
+pub fn add_totals(
+    table: &mut Table,
+    rows: &[Vec<CellValue>],
+    width: usize,
+    aggregations: &[Aggregation],
+    locale: &NumberLocale,
+) {
+    for footer in aggregate::totals(rows, width, aggregations) {
+        let mut row = Row::new();
+        for cell in &footer {
+            let alignment = if cell.is_numeric() {
+                CellAlignment::Right
+            } else {
+                CellAlignment::Left
+            };
+            row.add_cell(
+                Cell::new(cell.to_display_string(locale))
+                    .set_alignment(alignment)
+                    .add_attribute(Attribute::Bold),
+            );
+        }
+        table.add_row(row);
+    }
+}
 
-    // Print rows
-    for row in &data.rows {
-        let row_str: Vec<String> = row
-            .iter()
-            .map(|cell| {
-                let val = cell.to_raw_string();
-                if val.contains(',') || val.contains('"') || val.contains('\n') {
-                    format!("\"{}\"", val.replace('"', "\"\""))
-                } else {
-                    val
-                }
+/// Human-readable byte count (e.g. "12.3 KB")
+pub fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// When CSV exports quote a field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvQuote {
+    /// Only fields holding the delimiter, a quote, or a line break
+    #[default]
+    Necessary,
+    Always,
+    /// Every field that isn't a number
+    NonNumeric,
+    /// No field, even one that then reads back differently
+    Never,
+}
+
+impl CsvQuote {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "necessary" => Some(CsvQuote::Necessary),
+            "always" => Some(CsvQuote::Always),
+            "nonnumeric" => Some(CsvQuote::NonNumeric),
+            "never" => Some(CsvQuote::Never),
+            _ => None,
+        }
+    }
+}
+
+/// How CSV exports are written (`--delimiter`, `--quote`, `--line-ending`, `--bom`, or
+/// `[csv]` in the config); RFC 4180 by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: CsvQuote,
+    /// End lines with CRLF instead of LF
+    pub crlf: bool,
+    /// Start with a UTF-8 byte order mark, which Excel needs to read non-ASCII text right
+    pub bom: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: CsvQuote::Necessary,
+            crlf: false,
+            bom: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// From the names used by the flags and the config: a single ASCII character or "tab"
+    /// as the delimiter, a quoting policy, and "lf" or "crlf"
+    pub fn parse(delimiter: &str, quote: &str, line_ending: &str, bom: bool) -> Result<Self> {
+        let delimiter = match delimiter {
+            "tab" | "\\t" | "\t" => b'\t',
+            _ if delimiter.len() == 1 && !matches!(delimiter, "\"" | "\n" | "\r") => {
+                delimiter.as_bytes()[0]
+            }
+            _ => anyhow::bail!(
+                "Invalid CSV delimiter '{delimiter}'. Use a single character such as ; or tab"
+            ),
+        };
+        let quote = CsvQuote::parse(quote).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown CSV quoting: {quote}. Use: necessary, always, nonnumeric, or never"
+            )
+        })?;
+        let crlf = match line_ending {
+            "lf" => false,
+            "crlf" => true,
+            other => anyhow::bail!("Unknown line ending: {other}. Use: lf or crlf"),
+        };
+        Ok(Self {
+            delimiter,
+            quote,
+            crlf,
+            bom,
+        })
+    }
+
+    /// A CSV writer over `out` in this dialect, after the byte order mark if the output
+    /// `starts` here and the dialect has one
+    pub fn writer<W: Write>(&self, mut out: W, starts: bool) -> Result<csv::Writer<W>> {
+        if starts && self.bom {
+            out.write_all("\u{feff}".as_bytes())?;
+        }
+        Ok(csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote_style(match self.quote {
+                CsvQuote::Necessary => csv::QuoteStyle::Necessary,
+                CsvQuote::Always => csv::QuoteStyle::Always,
+                CsvQuote::NonNumeric => csv::QuoteStyle::NonNumeric,
+                CsvQuote::Never => csv::QuoteStyle::Never,
             })
-            .collect();
-        println!("{}", row_str.join(","));
+            .terminator(if self.crlf {
+                csv::Terminator::CRLF
+            } else {
+                csv::Terminator::Any(b'\n')
+            })
+            .flexible(true)
+            .from_writer(out))
     }
+}
+
+/// How exported cells are written: the date format, what empty and error cells become,
+/// and the CSV dialect. The default writes ISO dates, empty cells as nothing (null in
+/// JSON), errors as their code, and RFC 4180 CSV.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    pub dates: ExportDates,
+    /// Text for empty cells (`--empty-as`); "null" stays a JSON null
+    pub empty: Option<String>,
+    /// Export error cells like empty ones (`--errors-as-empty`)
+    pub errors_as_empty: bool,
+    /// Write very large and very small floats in scientific notation (`--scientific`)
+    pub scientific: bool,
+    pub csv: CsvDialect,
+}
+
+/// Export data as CSV to stdout; the byte order mark, if any, only goes before the
+/// `first` sheet
+pub fn export_csv(data: &SheetData, first: bool, options: &ExportOptions) -> Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_csv_with(&mut out, &data.headers, &data.rows, options, first)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Write a header line and rows as CSV in the export dialect (RFC 4180 by default)
+pub fn write_csv<W: Write>(
+    out: &mut W,
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+) -> Result<()> {
+    write_csv_with(out, headers, rows, options, true)
+}
+
+fn write_csv_with<W: Write>(
+    out: &mut W,
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+    starts: bool,
+) -> Result<()> {
+    let mut progress = Progress::new("Writing CSV", rows.len());
+    let rows = rows.iter().enumerate().map(|(i, row)| {
+        progress.update(i);
+        Ok(row)
+    });
+    csv_records(out, headers, rows, options, starts)
+}
 
+/// Write a sheet as CSV one row at a time as it's read (`--stream`; see
+/// `Workbook::rows_iter`)
+pub fn stream_csv<W: Write>(
+    out: &mut W,
+    mut rows: SheetRows,
+    options: &ExportOptions,
+) -> Result<()> {
+    let headers = std::mem::take(&mut rows.headers);
+    csv_records(out, &headers, rows, options, true)
+}
+
+fn csv_records<W: Write, R: AsRef<[CellValue]>>(
+    out: &mut W,
+    headers: &[String],
+    rows: impl Iterator<Item = Result<R>>,
+    options: &ExportOptions,
+    starts: bool,
+) -> Result<()> {
+    let mut writer = options.csv.writer(out, starts)?;
+    writer.write_record(headers)?;
+    for row in rows {
+        let row = row?;
+        writer.write_record(
+            row.as_ref()
+                .iter()
+                .map(|cell| cell.to_export_string(options)),
+        )?;
+    }
+    writer.flush()?;
     Ok(())
 }
 
+/// Quote a field if it contains a comma, quote, or line break (comma-separated clipboard
+/// copies; exports go through [`CsvDialect`])
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Shape of JSON exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonMode {
+    /// Object with metadata, headers, and `data` as an array of row arrays
+    Rows,
+    /// Array of objects keyed by header
+    Records,
+}
+
 /// Export data as JSON to stdout
-pub fn export_json(data: &SheetData, sheet_name: &str) -> Result<()> {
-    println!("{{");
-    println!("  \"sheet\": \"{sheet_name}\",");
-    println!("  \"rows\": {},", data.height);
-    println!("  \"columns\": {},", data.width);
-    println!("  \"headers\": [");
-    for (i, header) in data.headers.iter().enumerate() {
-        let comma = if i < data.headers.len() - 1 { "," } else { "" };
-        println!("    \"{header}\"{comma}");
-    }
-    println!("  ],");
-    println!("  \"data\": [");
-
-    for (i, row) in data.rows.iter().enumerate() {
-        print!("    [");
-        for (j, cell) in row.iter().enumerate() {
-            let value = match cell {
-                CellValue::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
-                CellValue::Int(i) => i.to_string(),
-                CellValue::Float(f) => f.to_string(),
-                CellValue::Bool(b) => b.to_string(),
-                CellValue::Empty => "null".to_string(),
-                _ => format!("\"{cell}\""),
-            };
-            print!("{value}");
-            if j < row.len() - 1 {
-                print!(", ");
+pub fn export_json(
+    data: &SheetData,
+    sheet_name: &str,
+    mode: JsonMode,
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_json(
+        &mut out,
+        &[("sheet", sheet_name)],
+        &data.headers,
+        &data.rows,
+        mode,
+        options,
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Write rows as JSON one row at a time, so large sheets never build one big string.
+/// `meta` fields (e.g. sheet name) are included in `Rows` mode only.
+pub fn write_json<W: Write>(
+    out: &mut W,
+    meta: &[(&str, &str)],
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    mode: JsonMode,
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut progress = Progress::new("Writing JSON", rows.len());
+    match mode {
+        JsonMode::Rows => {
+            writeln!(out, "{{")?;
+            for (key, value) in meta {
+                writeln!(out, "  {}: {},", json_string(key), json_string(value))?;
             }
+            writeln!(out, "  \"rows\": {},", rows.len())?;
+            writeln!(out, "  \"columns\": {},", headers.len())?;
+            write!(out, "  \"headers\": ")?;
+            serde_json::to_writer(&mut *out, headers)?;
+            writeln!(out, ",")?;
+            writeln!(out, "  \"data\": [")?;
+            for (i, row) in rows.iter().enumerate() {
+                progress.update(i);
+                let values: Vec<serde_json::Value> =
+                    row.iter().map(|cell| json_value(cell, options)).collect();
+                write!(out, "    ")?;
+                serde_json::to_writer(&mut *out, &values)?;
+                writeln!(out, "{}", if i + 1 < rows.len() { "," } else { "" })?;
+            }
+            writeln!(out, "  ]")?;
+            writeln!(out, "}}")?;
+        }
+        JsonMode::Records => {
+            let keys = record_keys(headers);
+            writeln!(out, "[")?;
+            for (i, row) in rows.iter().enumerate() {
+                progress.update(i);
+                // Written by hand so keys keep column order
+                let fields: Vec<String> = keys
+                    .iter()
+                    .enumerate()
+                    .map(|(col, key)| {
+                        let value = row
+                            .get(col)
+                            .map(|cell| json_value(cell, options))
+                            .unwrap_or_default();
+                        format!("{}: {value}", json_string(key))
+                    })
+                    .collect();
+                let comma = if i + 1 < rows.len() { "," } else { "" };
+                writeln!(out, "  {{{}}}{comma}", fields.join(", "))?;
+            }
+            writeln!(out, "]")?;
+        }
+    }
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+/// Numbers and booleans stay typed; empty cells (and errors with `--errors-as-empty`)
+/// become null, or the `--empty-as` string
+fn json_value(cell: &CellValue, options: &ExportOptions) -> serde_json::Value {
+    match cell {
+        CellValue::Empty => match options.empty.as_deref() {
+            None | Some("null") => serde_json::Value::Null,
+            Some(text) => text.into(),
+        },
+        CellValue::Error(_) if options.errors_as_empty => json_value(&CellValue::Empty, options),
+        CellValue::String(s) => s.as_str().into(),
+        CellValue::Int(i) => (*i).into(),
+        // Whole floats print without ".0" (Excel stores most numbers as floats)
+        CellValue::Float(f) if f.fract() == 0.0 && f.abs() < 1e15 => (*f as i64).into(),
+        // NaN/infinity have no JSON representation and become null
+        CellValue::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or_default(),
+        CellValue::Bool(b) => (*b).into(),
+        CellValue::Error(_) => cell.to_string().into(),
+        CellValue::Time(_) | CellValue::Duration(_) => cell.to_raw_string().into(),
+        CellValue::DateTime(serial) if options.dates.format == DateTimeFormat::Serial => {
+            serde_json::Number::from_f64(*serial)
+                .map(serde_json::Value::Number)
+                .unwrap_or_default()
         }
-        let comma = if i < data.rows.len() - 1 { "," } else { "" };
-        println!("]{comma}");
+        // ISO dates whatever the display locale
+        CellValue::DateTime(_) => cell.to_export_string(options).into(),
     }
+}
 
-    println!("  ]");
-    println!("}}");
+/// Object keys for records mode: blank headers use the column letter, duplicates get a suffix
+pub fn record_keys(headers: &[String]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(headers.len());
+    for (col, header) in headers.iter().enumerate() {
+        let base = if header.trim().is_empty() {
+            crate::workbook::column_letter(col)
+        } else {
+            header.clone()
+        };
+        let mut key = base.clone();
+        let mut n = 2;
+        while keys.contains(&key) {
+            key = format!("{base}_{n}");
+            n += 1;
+        }
+        keys.push(key);
+    }
+    keys
+}
 
+/// Export data as plain text to stdout
+pub fn export_text(data: &SheetData, options: &ExportOptions) -> Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_text(&mut out, &data.headers, &data.rows, options)?;
+    out.flush()?;
     Ok(())
 }
 
-/// Export data as plain text to stdout
-pub fn export_text(data: &SheetData) -> Result<()> {
-    // Headers
-    println!("{}", data.headers.join("\t"));
+/// Write a header line and rows as tab-separated raw values
+pub fn write_text<W: Write>(
+    out: &mut W,
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+) -> Result<()> {
+    let mut progress = Progress::new("Writing text", rows.len());
+    let rows = rows.iter().enumerate().map(|(i, row)| {
+        progress.update(i);
+        Ok(row)
+    });
+    text_lines(out, headers, rows, options)
+}
+
+/// Write a sheet as tab-separated text one row at a time as it's read (`--stream`)
+pub fn stream_text<W: Write>(
+    out: &mut W,
+    mut rows: SheetRows,
+    options: &ExportOptions,
+) -> Result<()> {
+    let headers = std::mem::take(&mut rows.headers);
+    text_lines(out, &headers, rows, options)
+}
 
-    // Data rows
-    for row in &data.rows {
-        let row_str: Vec<String> = row.iter().map(|cell| cell.to_raw_string()).collect();
-        println!("{}", row_str.join("\t"));
+fn text_lines<W: Write, R: AsRef<[CellValue]>>(
+    out: &mut W,
+    headers: &[String],
+    rows: impl Iterator<Item = Result<R>>,
+    options: &ExportOptions,
+) -> Result<()> {
+    writeln!(out, "{}", headers.join("\t"))?;
+    for row in rows {
+        let row_str: Vec<String> = row?
+            .as_ref()
+            .iter()
+            .map(|cell| cell.to_export_string(options))
+            .collect();
+        writeln!(out, "{}", row_str.join("\t"))?;
     }
+    Ok(())
+}
 
+/// Export data as a GitHub-flavored Markdown table to stdout
+pub fn export_markdown(data: &SheetData, options: &ExportOptions) -> Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_markdown(&mut out, &data.headers, &data.rows, options)?;
+    out.flush()?;
     Ok(())
 }
+
+/// Write a GitHub-flavored Markdown table (see [`markdown_table`])
+pub fn write_markdown<W: Write>(
+    out: &mut W,
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+) -> Result<()> {
+    out.write_all(markdown_table(headers, rows, options).as_bytes())?;
+    Ok(())
+}
+
+/// Render a GFM pipe table; columns holding only numbers are right-aligned
+pub fn markdown_table(
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+) -> String {
+    // Pipes would end the cell and raw newlines would end the row
+    let escape = |s: &str| {
+        s.replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+    let width = rows
+        .iter()
+        .map(|r| r.len())
+        .chain(std::iter::once(headers.len()))
+        .max()
+        .unwrap_or(0);
+    if width == 0 {
+        return String::new();
+    }
+
+    let numeric = |col: usize| {
+        let mut values = rows
+            .iter()
+            .filter_map(|r| r.get(col))
+            .filter(|c| !c.is_empty())
+            .peekable();
+        values.peek().is_some()
+            && values.all(|c| matches!(c, CellValue::Int(_) | CellValue::Float(_)))
+    };
+
+    let mut out = String::new();
+    let header: Vec<String> = (0..width)
+        .map(|i| escape(headers.get(i).map(String::as_str).unwrap_or("")))
+        .collect();
+    out.push_str(&format!("| {} |\n", header.join(" | ")));
+    let separator: Vec<&str> = (0..width)
+        .map(|i| if numeric(i) { "---:" } else { "---" })
+        .collect();
+    out.push_str(&format!("| {} |\n", separator.join(" | ")));
+
+    for row in rows {
+        let cells: Vec<String> = (0..width)
+            .map(|i| {
+                row.get(i)
+                    .map(|cell| escape(&cell.to_export_string(options)))
+                    .unwrap_or_default()
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+// ===== Bars and sparklines =====
+
+/// Characters in a full-length bar (`--bars` and the TUI's bar toggle)
+pub const BAR_WIDTH: usize = 16;
+
+/// Partial blocks for a bar's last character, by eighths
+const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Sparkline levels, lowest first
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+fn number(cell: &CellValue) -> Option<f64> {
+    match cell {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) if f.is_finite() => Some(*f),
+        _ => None,
+    }
+}
+
+/// Scale for a column's bars: from 0 (or the minimum, when negative) to the maximum,
+/// so bar lengths stay proportional to the values; None when there are no numbers
+pub fn bar_scale<'a>(cells: impl IntoIterator<Item = &'a CellValue>) -> Option<(f64, f64)> {
+    cells
+        .into_iter()
+        .filter_map(number)
+        .fold(None, |scale, n| match scale {
+            None => Some((n.min(0.0), n.max(0.0))),
+            Some((lo, hi)) => Some((lo.min(n), hi.max(n))),
+        })
+}
+
+/// A bar for the cell on the `scale` from `bar_scale`, drawn in eighths of a character and
+/// padded to `width`; blank for cells that aren't numbers
+pub fn bar(cell: &CellValue, (lo, hi): (f64, f64), width: usize) -> String {
+    let fraction = match number(cell) {
+        Some(n) if hi > lo => ((n - lo) / (hi - lo)).clamp(0.0, 1.0),
+        _ => 0.0,
+    };
+    let eighths = (fraction * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    bar.push_str(EIGHTHS[eighths % 8]);
+    let drawn = bar.chars().count();
+    bar.push_str(&" ".repeat(width.saturating_sub(drawn)));
+    bar
+}
+
+/// The column's numbers as a sparkline at most `width` characters long, each character
+/// the average of an equal share of the values; empty when there are no numbers
+pub fn sparkline<'a>(cells: impl IntoIterator<Item = &'a CellValue>, width: usize) -> String {
+    let values: Vec<f64> = cells.into_iter().filter_map(number).collect();
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let count = width.min(values.len());
+    let buckets: Vec<f64> = (0..count)
+        .map(|i| {
+            let share = &values[i * values.len() / count..(i + 1) * values.len() / count];
+            share.iter().sum::<f64>() / share.len() as f64
+        })
+        .collect();
+    let (lo, hi) = buckets
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    buckets
+        .iter()
+        .map(|&v| {
+            let level = if hi > lo {
+                ((v - lo) / (hi - lo) * 7.0).round() as usize
+            } else {
+                3
+            };
+            LEVELS[level.min(7)]
+        })
+        .collect()
+}
+
+/// A scale end for a bar column's header, in the display number format
+fn scale_label(n: f64, locale: &NumberLocale) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        CellValue::Int(n as i64).to_display_string(locale)
+    } else {
+        CellValue::Float(n).to_display_string(locale)
+    }
+}
+
+/// Insert a column of bars after each of `columns` (picked like `--columns`), headed with
+/// the bars' scale. Returns the indices of the bar columns, last first.
+pub(crate) fn insert_bar_columns(
+    headers: &mut Vec<String>,
+    rows: &mut [Vec<CellValue>],
+    first_col: usize,
+    columns: &[String],
+    locale: &NumberLocale,
+) -> Result<Vec<usize>> {
+    let mut indices = crate::workbook::column_selection(headers, first_col, columns)?;
+    indices.sort_unstable_by(|a, b| b.cmp(a));
+    indices.dedup();
+    for &col in &indices {
+        let scale = bar_scale(rows.iter().filter_map(|row| row.get(col)));
+        let header = match scale {
+            Some((lo, hi)) => format!("{} to {}", scale_label(lo, locale), scale_label(hi, locale)),
+            None => String::new(),
+        };
+        headers.insert(col + 1, header);
+        for row in rows.iter_mut() {
+            if row.len() <= col {
+                row.resize(col + 1, CellValue::Empty);
+            }
+            let cell = match scale {
+                Some(scale) => CellValue::String(bar(&row[col], scale, BAR_WIDTH)),
+                None => CellValue::Empty,
+            };
+            row.insert(col + 1, cell);
+        }
+    }
+    Ok(indices.iter().map(|&col| col + 1).collect())
+}
+
+// ===== Heatmaps =====
+
+/// Background of a heatmap column's smallest and largest numbers in the terminal table
+pub const HEAT_LOW: (u8, u8, u8) = (49, 54, 149);
+pub const HEAT_HIGH: (u8, u8, u8) = (215, 48, 39);
+
+/// A column's smallest and largest numbers, the ends of its heatmap gradient; None when
+/// there are no numbers
+pub fn heat_range<'a>(cells: impl IntoIterator<Item = &'a CellValue>) -> Option<(f64, f64)> {
+    cells
+        .into_iter()
+        .filter_map(number)
+        .fold(None, |range, n| match range {
+            None => Some((n, n)),
+            Some((lo, hi)) => Some((lo.min(n), hi.max(n))),
+        })
+}
+
+/// The ranges of the `columns` that have numbers, by column
+pub fn heat_ranges(rows: &[Vec<CellValue>], columns: &[usize]) -> HashMap<usize, (f64, f64)> {
+    columns
+        .iter()
+        .filter_map(|&col| Some((col, heat_range(rows.iter().filter_map(|row| row.get(col)))?)))
+        .collect()
+}
+
+/// The cell's background, blended from `low` to `high` by where its number falls in
+/// `range` (the middle when all the numbers are equal); None for cells that aren't numbers
+pub fn heat_color(
+    cell: &CellValue,
+    (lo, hi): (f64, f64),
+    low: (u8, u8, u8),
+    high: (u8, u8, u8),
+) -> Option<(u8, u8, u8)> {
+    let n = number(cell)?;
+    let fraction = if hi > lo {
+        ((n - lo) / (hi - lo)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    };
+    let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+    Some((
+        blend(low.0, high.0),
+        blend(low.1, high.1),
+        blend(low.2, high.2),
+    ))
+}
+
+/// Black or white, whichever reads better on the `background`
+pub fn contrast_text((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    if luminance > 140.0 {
+        (0, 0, 0)
+    } else {
+        (255, 255, 255)
+    }
+}
+
+/// Color a terminal table cell by its place in the heatmap `range`
+pub fn heat_cell(cell_obj: Cell, cell: &CellValue, range: (f64, f64)) -> Cell {
+    let Some(bg) = heat_color(cell, range, HEAT_LOW, HEAT_HIGH) else {
+        return cell_obj;
+    };
+    match (table_color(bg), table_color(contrast_text(bg))) {
+        (Some(bg), Some(fg)) => cell_obj.bg(bg).fg(fg),
+        _ => cell_obj,
+    }
+}
+
+/// An RGB color as the terminal shows it in the `--color-mode`; None without colors
+fn table_color(rgb: (u8, u8, u8)) -> Option<Color> {
+    Some(match color_mode::color_mode().rgb(rgb)? {
+        TermColor::Rgb(r, g, b) => Color::Rgb { r, g, b },
+        TermColor::Indexed(index) => Color::AnsiValue(index),
+    })
+}
+
+/// Give a terminal table cell's text a color, unless colors are off (`--color-mode none`)
+pub fn paint(cell_obj: Cell, color: Color) -> Cell {
+    if color_mode::color_mode() == ColorMode::None {
+        cell_obj
+    } else {
+        cell_obj.fg(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(text_width("日本語"), 6);
+        assert_eq!(text_width("e\u{301}"), 1);
+        assert_eq!(truncate_to_width("Total", 5, "…"), "Total");
+        assert_eq!(truncate_to_width("Totals", 5, "…"), "Tota…");
+        assert_eq!(truncate_to_width("Totals", 5, "..."), "To...");
+        // Wide characters never straddle the limit
+        assert_eq!(truncate_to_width("日本語テキスト", 6, "…"), "日本…");
+        assert_eq!(truncate_to_width("日本語テキスト", 5, "…"), "日本…");
+        // Emoji sequences and combining marks stay whole
+        assert_eq!(truncate_to_width("👍🏽👍🏽👍🏽", 5, "…"), "👍🏽👍🏽…");
+        assert_eq!(truncate_to_width("cafe\u{301}s!", 5, "…"), "cafe\u{301}…");
+        // No room for the ellipsis
+        assert_eq!(truncate_to_width("Totals", 1, "…"), "T");
+        assert_eq!(truncate_to_width("日本", 1, "…"), "");
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let cells = [
+            CellValue::Int(10),
+            CellValue::Int(30),
+            CellValue::String("n/a".to_string()),
+            CellValue::Int(20),
+        ];
+        let range = heat_range(&cells).unwrap();
+        assert_eq!(range, (10.0, 30.0));
+        let (low, high) = ((0, 0, 0), (200, 100, 0));
+        assert_eq!(heat_color(&cells[0], range, low, high), Some(low));
+        assert_eq!(heat_color(&cells[1], range, low, high), Some(high));
+        assert_eq!(heat_color(&cells[2], range, low, high), None);
+        assert_eq!(heat_color(&cells[3], range, low, high), Some((100, 50, 0)));
+        assert_eq!(
+            heat_color(&cells[0], (10.0, 10.0), low, high),
+            Some((100, 50, 0))
+        );
+
+        assert_eq!(contrast_text((255, 255, 200)), (0, 0, 0));
+        assert_eq!(contrast_text(HEAT_LOW), (255, 255, 255));
+        assert_eq!(heat_ranges(&[cells.to_vec()], &[0, 2]).len(), 1);
+    }
+
+    #[test]
+    fn test_bars() {
+        let cells = [
+            CellValue::Int(10),
+            CellValue::Float(2.5),
+            CellValue::String("n/a".to_string()),
+            CellValue::Int(40),
+        ];
+        let scale = bar_scale(&cells).unwrap();
+        assert_eq!(scale, (0.0, 40.0));
+        assert_eq!(bar(&cells[0], scale, 4), "█   ");
+        assert_eq!(bar(&cells[1], scale, 4), "▎   ");
+        assert_eq!(bar(&cells[2], scale, 4), "    ");
+        assert_eq!(bar(&cells[3], scale, 4), "████");
+        assert_eq!(bar_scale(&[CellValue::Empty]), None);
+
+        assert_eq!(sparkline(&cells, 10), "▂▁█");
+        assert_eq!(sparkline(&cells, 2), "▁█");
+        assert_eq!(sparkline(&cells[2..3], 10), "");
+    }
+
+    #[test]
+    fn test_csv_quotes_headers_and_cells() {
+        let headers = vec!["Name".to_string(), "City, Country".to_string()];
+        let rows = vec![vec![
+            CellValue::String("say \"hi\"".to_string()),
+            CellValue::String("two\nlines".to_string()),
+        ]];
+        let mut out = Vec::new();
+        write_csv(&mut out, &headers, &rows, &ExportOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Name,\"City, Country\"\n\"say \"\"hi\"\"\",\"two\nlines\"\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_dialect() {
+        let headers = vec!["Name".to_string(), "Amount".to_string()];
+        let rows = vec![vec![
+            CellValue::String("Müller; Söhne".to_string()),
+            CellValue::Float(1234.5),
+        ]];
+        let output = |dialect: CsvDialect| {
+            let mut out = Vec::new();
+            let options = ExportOptions {
+                csv: dialect,
+                ..ExportOptions::default()
+            };
+            write_csv_with(&mut out, &headers, &rows, &options, true).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let european = CsvDialect::parse(";", "necessary", "crlf", true).unwrap();
+        assert_eq!(
+            output(european),
+            "\u{feff}Name;Amount\r\n\"Müller; Söhne\";1234.5\r\n"
+        );
+        let quoted = CsvDialect::parse("tab", "nonnumeric", "lf", false).unwrap();
+        assert_eq!(
+            output(quoted),
+            "\"Name\"\t\"Amount\"\n\"Müller; Söhne\"\t1234.5\n"
+        );
+        assert!(CsvDialect::parse(";;", "necessary", "lf", false).is_err());
+        assert!(CsvDialect::parse(",", "sometimes", "lf", false).is_err());
+        assert!(CsvDialect::parse(",", "necessary", "cr", false).is_err());
+    }
+
+    fn json_output(headers: &[&str], rows: &[Vec<CellValue>], mode: JsonMode) -> String {
+        let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        let mut out = Vec::new();
+        write_json(
+            &mut out,
+            &[("sheet", "Data")],
+            &headers,
+            rows,
+            mode,
+            &ExportOptions::default(),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_json_rows_escapes_strings() {
+        let rows = vec![vec![
+            CellValue::String("line\nbreak \\ \"quoted\" \u{1}".to_string()),
+            CellValue::Float(f64::NAN),
+            CellValue::Int(7),
+        ]];
+        let json = json_output(&["Text", "Nan", "N"], &rows, JsonMode::Rows);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["sheet"], "Data");
+        assert_eq!(parsed["rows"], 1);
+        assert_eq!(parsed["data"][0][0], "line\nbreak \\ \"quoted\" \u{1}");
+        assert!(parsed["data"][0][1].is_null());
+        assert_eq!(parsed["data"][0][2], 7);
+    }
+
+    #[test]
+    fn test_json_records_keys() {
+        let rows = vec![
+            vec![CellValue::Int(1), CellValue::Bool(true), CellValue::Empty],
+            vec![CellValue::Int(2)],
+        ];
+        let json = json_output(&["id", "id", ""], &rows, JsonMode::Records);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            parsed,
+            serde_json::json!([
+                {"id": 1, "id_2": true, "C": null},
+                {"id": 2, "id_2": null, "C": null}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let headers = vec!["Item".to_string(), "Qty".to_string()];
+        let rows = vec![
+            vec![CellValue::String("a|b".to_string()), CellValue::Int(3)],
+            vec![
+                CellValue::String("two\nlines".to_string()),
+                CellValue::Empty,
+            ],
+            vec![CellValue::Empty, CellValue::Float(2.5)],
+        ];
+        assert_eq!(
+            markdown_table(&headers, &rows, &ExportOptions::default()),
+            "| Item | Qty |\n\
+             | --- | ---: |\n\
+             | a\\|b | 3 |\n\
+             | two<br>lines |  |\n\
+             |  | 2.5 |\n"
+        );
+    }
+}
@@ -0,0 +1,1088 @@
+//! Evaluating formulas whose cached result is missing (`--evaluate`)
+//!
+//! Files written by libraries and converters often store formulas without the value Excel
+//! would have computed, so those cells read as empty. This covers the common cases:
+//! arithmetic, comparison, and `&` operators; cell, range, and whole-column references,
+//! including other sheets; and SUM, AVERAGE, MIN, MAX, COUNT, COUNTA, IF, IFERROR, AND, OR,
+//! NOT, VLOOKUP, ROUND, ABS, CONCATENATE/CONCAT, LEN, UPPER, LOWER, and TRIM.
+//! Anything else (other functions, defined names) evaluates to `#NAME?`.
+
+use calamine::{CellErrorType, Data, Range};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Nested evaluations allowed before giving up on a chain of references
+const MAX_DEPTH: usize = 256;
+
+/// Excel's last row, bounding whole-column references like `A:A`
+const MAX_ROWS: u32 = 1_048_576;
+
+/// A sheet's cached values and formulas, at absolute positions
+pub struct SheetGrid {
+    pub values: Range<Data>,
+    pub formulas: Option<Range<String>>,
+}
+
+impl SheetGrid {
+    /// Last (row, col) holding a value or formula
+    fn end(&self) -> (u32, u32) {
+        let values = self.values.end().unwrap_or((0, 0));
+        let formulas = self
+            .formulas
+            .as_ref()
+            .and_then(|f| f.end())
+            .unwrap_or((0, 0));
+        (values.0.max(formulas.0), values.1.max(formulas.1))
+    }
+
+    fn formula(&self, row: u32, col: u32) -> Option<&str> {
+        let formulas = self.formulas.as_ref()?;
+        formulas
+            .get_value((row, col))
+            .map(String::as_str)
+            .filter(|f| !f.is_empty())
+    }
+}
+
+/// Loads a sheet's grid by name (Excel matches sheet names case-insensitively)
+pub type GridLoader<'a> = Box<dyn FnMut(&str) -> Option<SheetGrid> + 'a>;
+
+/// A computed cell value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Empty,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(CellErrorType),
+}
+
+impl Value {
+    fn from_data(data: &Data) -> Self {
+        match data {
+            Data::Empty => Value::Empty,
+            Data::Int(i) => Value::Number(*i as f64),
+            Data::Float(f) => Value::Number(*f),
+            Data::DateTime(d) => Value::Number(d.as_f64()),
+            Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => Value::Text(s.clone()),
+            Data::Bool(b) => Value::Bool(*b),
+            Data::Error(e) => Value::Error(e.clone()),
+        }
+    }
+
+    /// The cell a formula with this result shows (an empty result displays as 0)
+    pub fn to_data(&self) -> Data {
+        match self {
+            Value::Empty => Data::Float(0.0),
+            Value::Number(n) => Data::Float(*n),
+            Value::Text(s) => Data::String(s.clone()),
+            Value::Bool(b) => Data::Bool(*b),
+            Value::Error(e) => Data::Error(e.clone()),
+        }
+    }
+
+    fn to_number(&self) -> Result<f64, CellErrorType> {
+        match self {
+            Value::Empty => Ok(0.0),
+            Value::Number(n) => Ok(*n),
+            Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+            Value::Text(s) => s.trim().parse().map_err(|_| CellErrorType::Value),
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+
+    fn to_text(&self) -> Result<String, CellErrorType> {
+        match self {
+            Value::Empty => Ok(String::new()),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => Ok(format!("{n:.0}")),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Text(s) => Ok(s.clone()),
+            Value::Bool(b) => Ok(if *b { "TRUE" } else { "FALSE" }.to_string()),
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+
+    fn to_bool(&self) -> Result<bool, CellErrorType> {
+        match self {
+            Value::Empty => Ok(false),
+            Value::Number(n) => Ok(*n != 0.0),
+            Value::Bool(b) => Ok(*b),
+            Value::Text(s) if s.eq_ignore_ascii_case("true") => Ok(true),
+            Value::Text(s) if s.eq_ignore_ascii_case("false") => Ok(false),
+            Value::Text(_) => Err(CellErrorType::Value),
+            Value::Error(e) => Err(e.clone()),
+        }
+    }
+}
+
+/// Numbers sort before text, text before booleans; text compares case-insensitively,
+/// and an empty cell acts as 0, "", or FALSE depending on the other side
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Number(_) | Value::Empty | Value::Error(_) => 0,
+            Value::Text(_) => 1,
+            Value::Bool(_) => 2,
+        }
+    }
+    match (a, b) {
+        (Value::Empty, Value::Text(t)) => "".cmp(t.as_str()),
+        (Value::Text(t), Value::Empty) => t.as_str().cmp(""),
+        (Value::Empty, Value::Bool(b)) => false.cmp(b),
+        (Value::Bool(b), Value::Empty) => b.cmp(&false),
+        (Value::Text(x), Value::Text(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ if rank(a) == 0 && rank(b) == 0 => {
+            let (x, y) = (a.to_number().unwrap_or(0.0), b.to_number().unwrap_or(0.0));
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// A cell or rectangular range, optionally on another sheet
+#[derive(Debug, Clone, PartialEq)]
+struct Reference {
+    sheet: Option<String>,
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(CellErrorType),
+    Ref(Reference),
+    Negate(Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    /// Function name (followed by an opening parenthesis)
+    Function(String),
+    /// A bare word that isn't a reference: TRUE, FALSE, or a defined name
+    Name(String),
+    Ref(Reference),
+    Error(CellErrorType),
+    Op(&'static str),
+    Open,
+    Close,
+    Comma,
+}
+
+const OPERATORS: [&str; 14] = [
+    "<>", "<=", ">=", "=", "<", ">", "+", "-", "*", "/", "^", "&", "%", ":",
+];
+
+fn tokenize(formula: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = formula.trim_start_matches('=').chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(char::is_ascii_digit) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(text.parse().ok()?));
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i)? {
+                    '"' if chars.get(i + 1) == Some(&'"') => {
+                        text.push('"');
+                        i += 2;
+                    }
+                    '"' => break,
+                    &ch => {
+                        text.push(ch);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push(Token::Text(text));
+        } else if c == '#' {
+            // #DIV/0!, #N/A, #NAME?, #REF!, ...
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '/') {
+                i += 1;
+            }
+            if matches!(chars.get(i), Some('!' | '?')) {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(Token::Error(text.parse().ok()?));
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else if c == '\'' || c == '[' || is_word_char(c) {
+            let (token, next) = word_token(&chars, i)?;
+            tokens.push(token);
+            i = next;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS.into_iter().find(|op| rest.starts_with(op))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Some(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '$')
+}
+
+fn read_word(chars: &[char], mut i: usize) -> (String, usize) {
+    let start = i;
+    while i < chars.len() && is_word_char(chars[i]) {
+        i += 1;
+    }
+    (chars[start..i].iter().collect(), i)
+}
+
+/// A reference (`A1`, `$B$2:C9`, `D:D`, `'Q1 Sales'!A1`, `[1]Rates!B2`), function name,
+/// or bare name
+fn word_token(chars: &[char], i: usize) -> Option<(Token, usize)> {
+    let (mut sheet, mut i) = (None, i);
+    if chars[i] == '\'' {
+        let mut name = String::new();
+        i += 1;
+        loop {
+            match chars.get(i)? {
+                '\'' if chars.get(i + 1) == Some(&'\'') => {
+                    name.push('\'');
+                    i += 2;
+                }
+                '\'' => break,
+                &ch => {
+                    name.push(ch);
+                    i += 1;
+                }
+            }
+        }
+        if chars.get(i + 1) != Some(&'!') {
+            return None;
+        }
+        sheet = Some(name);
+        i += 2;
+    } else if chars[i] == '[' {
+        // A sheet of another workbook, which the sheet name keeps: `[1]Rates!B2`
+        let close = i + chars[i..].iter().position(|&c| c == ']')?;
+        let (name, after) = read_word(chars, close + 1);
+        if chars.get(after) != Some(&'!') {
+            return None;
+        }
+        let book: String = chars[i..=close].iter().collect();
+        sheet = Some(format!("{book}{name}"));
+        i = after + 1;
+    }
+
+    let (mut word, mut next) = read_word(chars, i);
+    if sheet.is_none() && chars.get(next) == Some(&'!') {
+        sheet = Some(word);
+        (word, next) = read_word(chars, next + 1);
+    }
+    if sheet.is_none() && chars.get(next) == Some(&'(') {
+        let name = word.to_uppercase();
+        let name = name.strip_prefix("_XLFN.").unwrap_or(&name).to_string();
+        return Some((Token::Function(name), next + 1));
+    }
+
+    let corner = |word: &str| -> Option<(u32, u32)> {
+        let letters = word.trim_start_matches('$');
+        let split = letters
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(letters.len());
+        if split == 0 || split > 3 {
+            return None;
+        }
+        crate::ooxml::parse_cell_ref(word)
+    };
+    let column = |word: &str| -> Option<u32> {
+        let letters = word.trim_start_matches('$');
+        (!letters.is_empty()
+            && letters.len() <= 3
+            && letters.chars().all(|c| c.is_ascii_alphabetic()))
+        .then(|| crate::workbook::column_index(letters).map(|c| c as u32))
+        .flatten()
+    };
+
+    let range_end = (chars.get(next) == Some(&':')).then(|| read_word(chars, next + 1));
+    let reference = match (corner(&word), &range_end) {
+        (Some(start), Some((last, after))) if corner(last).is_some() => {
+            let end = corner(last)?;
+            next = *after;
+            Some((start, end))
+        }
+        (Some(cell), _) => Some((cell, cell)),
+        (None, Some((last, after))) => match (column(&word), column(last)) {
+            (Some(first), Some(last)) => {
+                next = *after;
+                Some(((0, first), (MAX_ROWS - 1, last)))
+            }
+            _ => None,
+        },
+        (None, None) => None,
+    };
+    match reference {
+        Some((start, end)) => Some((
+            Token::Ref(Reference {
+                sheet,
+                start: (start.0.min(end.0), start.1.min(end.1)),
+                end: (start.0.max(end.0), start.1.max(end.1)),
+            }),
+            next,
+        )),
+        None if sheet.is_some() => None,
+        None => Some((Token::Name(word.to_uppercase()), next)),
+    }
+}
+
+/// What a formula is made of, as far as reviewing it goes (`--audit-formulas`)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FormulaParts {
+    /// Sheets its references name, in order and without repeats; sheets of other
+    /// workbooks keep their workbook prefix (`[1]Rates`, `[Prices.xlsx]2024`)
+    pub sheets: Vec<String>,
+    /// Functions it calls, upper-cased, without repeats
+    pub functions: Vec<String>,
+    /// Number literals, with a `%` that follows them (`1.07`, `5%`)
+    pub numbers: Vec<String>,
+    /// Error literals, such as the `#REF!` a deleted row leaves behind
+    pub errors: Vec<String>,
+}
+
+/// Split a formula into the parts a review looks at; None if it can't be read (structured
+/// table references, array constants)
+pub fn formula_parts(formula: &str) -> Option<FormulaParts> {
+    let tokens = tokenize(formula)?;
+    let mut parts = FormulaParts::default();
+    let add = |list: &mut Vec<String>, item: String| {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    };
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Ref(Reference {
+                sheet: Some(sheet), ..
+            }) => add(&mut parts.sheets, sheet.clone()),
+            Token::Function(name) => add(&mut parts.functions, name.clone()),
+            Token::Number(n) => {
+                let percent = tokens.get(i + 1) == Some(&Token::Op("%"));
+                parts
+                    .numbers
+                    .push(format!("{n}{}", if percent { "%" } else { "" }));
+            }
+            Token::Error(error) => add(&mut parts.errors, error.to_string()),
+            _ => {}
+        }
+    }
+    Some(parts)
+}
+
+/// Recursive-descent parser following Excel's operator precedence
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(formula: &str) -> Option<Expr> {
+        let mut parser = Parser {
+            tokens: tokenize(formula)?,
+            pos: 0,
+        };
+        let expr = parser.expression(0)?;
+        (parser.pos == parser.tokens.len()).then_some(expr)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Binding power of a binary operator (higher binds tighter)
+    fn precedence(op: &str) -> Option<u8> {
+        match op {
+            "=" | "<>" | "<" | ">" | "<=" | ">=" => Some(1),
+            "&" => Some(2),
+            "+" | "-" => Some(3),
+            "*" | "/" => Some(4),
+            "^" => Some(5),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Option<Expr> {
+        let mut lhs = self.unary()?;
+        while let Some(&Token::Op(op)) = self.peek() {
+            let Some(precedence) = Self::precedence(op).filter(|&p| p > min_precedence) else {
+                break;
+            };
+            self.pos += 1;
+            let rhs = self.expression(precedence)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Some(lhs)
+    }
+
+    /// Prefix signs bind tighter than `^` in Excel (-2^2 is 4)
+    fn unary(&mut self) -> Option<Expr> {
+        match self.peek() {
+            Some(Token::Op("-")) => {
+                self.pos += 1;
+                Some(Expr::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Op("+")) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => {
+                let mut expr = self.primary()?;
+                while self.peek() == Some(&Token::Op("%")) {
+                    self.pos += 1;
+                    expr = Expr::Percent(Box::new(expr));
+                }
+                Some(expr)
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Option<Expr> {
+        match self.next()? {
+            Token::Number(n) => Some(Expr::Number(n)),
+            Token::Text(s) => Some(Expr::Text(s)),
+            Token::Error(e) => Some(Expr::Error(e)),
+            Token::Ref(r) => Some(Expr::Ref(r)),
+            Token::Name(name) => Some(match name.as_str() {
+                "TRUE" => Expr::Bool(true),
+                "FALSE" => Expr::Bool(false),
+                _ => Expr::Error(CellErrorType::Name),
+            }),
+            Token::Open => {
+                let expr = self.expression(0)?;
+                (self.next()? == Token::Close).then_some(expr)
+            }
+            Token::Function(name) => {
+                let mut args = Vec::new();
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                    return Some(Expr::Call(name, args));
+                }
+                loop {
+                    // An omitted argument, as in IF(A1,,1), counts as empty
+                    if matches!(self.peek(), Some(Token::Comma | Token::Close)) {
+                        args.push(Expr::Text(String::new()));
+                    } else {
+                        args.push(self.expression(0)?);
+                    }
+                    match self.next()? {
+                        Token::Comma => continue,
+                        Token::Close => return Some(Expr::Call(name, args)),
+                        _ => return None,
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A function argument: a single value or the cells of a range, row by row
+enum Operand {
+    Value(Value),
+    Range(Vec<Vec<Value>>),
+}
+
+impl Operand {
+    /// Every value, in row-major order
+    fn values(self) -> Vec<Value> {
+        match self {
+            Operand::Value(v) => vec![v],
+            Operand::Range(rows) => rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+/// Evaluates formulas across a workbook, loading sheets as references reach them
+pub struct Evaluator<'a> {
+    loader: GridLoader<'a>,
+    /// Loaded sheets by lowercase name (None if the sheet doesn't exist)
+    sheets: HashMap<String, Option<SheetGrid>>,
+    results: HashMap<(String, u32, u32), Value>,
+    in_progress: HashSet<(String, u32, u32)>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(loader: GridLoader<'a>) -> Self {
+        Self {
+            loader,
+            sheets: HashMap::new(),
+            results: HashMap::new(),
+            in_progress: HashSet::new(),
+        }
+    }
+
+    /// Computes every formula in `grid` that has no cached result, returning the values
+    /// grown to cover those cells (calamine leaves valueless cells out of the range)
+    pub fn fill_missing(&mut self, sheet: &str, grid: SheetGrid) -> Range<Data> {
+        let key = sheet.to_lowercase();
+        let formula_cells: Vec<(u32, u32)> = grid
+            .formulas
+            .iter()
+            .flat_map(|f| f.used_cells().map(move |(r, c, _)| (r, c, f.start())))
+            .filter_map(|(r, c, start)| {
+                let (row, col) = start?;
+                Some((row + r as u32, col + c as u32))
+            })
+            .filter(|&pos| {
+                grid.values
+                    .get_value(pos)
+                    .is_none_or(|v| matches!(v, Data::Empty))
+            })
+            .collect();
+        if formula_cells.is_empty() {
+            return grid.values;
+        }
+        self.sheets.insert(key.clone(), Some(grid));
+
+        // Row-major order resolves running totals (B3 = B2 + A3) without deep recursion
+        let computed: Vec<((u32, u32), Value)> = formula_cells
+            .into_iter()
+            .map(|(row, col)| ((row, col), self.cell(&key, row, col, 0)))
+            .collect();
+
+        let grid = self
+            .sheets
+            .remove(&key)
+            .flatten()
+            .expect("grid inserted above");
+        let mut start = grid.values.start().unwrap_or(computed[0].0);
+        let mut end = grid.values.end().unwrap_or(computed[0].0);
+        for &((row, col), _) in &computed {
+            start = (start.0.min(row), start.1.min(col));
+            end = (end.0.max(row), end.1.max(col));
+        }
+        let mut values = Range::new(start, end);
+        let origin = grid.values.start().unwrap_or((0, 0));
+        for (row, col, data) in grid.values.cells() {
+            values.set_value((origin.0 + row as u32, origin.1 + col as u32), data.clone());
+        }
+        for (pos, value) in computed {
+            values.set_value(pos, value.to_data());
+        }
+        values
+    }
+
+    /// Evaluates the formula at an absolute position, ignoring its cached result.
+    /// None if the cell has no formula.
+    pub fn evaluate_at(&mut self, sheet: &str, row: u32, col: u32) -> Option<Value> {
+        let key = sheet.to_lowercase();
+        let formula = self.grid(&key)?.formula(row, col)?.to_string();
+        self.in_progress.insert((key.clone(), row, col));
+        let value = self.evaluate_formula(&formula, &key, 0);
+        self.in_progress.remove(&(key, row, col));
+        Some(value)
+    }
+
+    fn grid(&mut self, key: &str) -> Option<&SheetGrid> {
+        if !self.sheets.contains_key(key) {
+            let grid = (self.loader)(key);
+            self.sheets.insert(key.to_string(), grid);
+        }
+        self.sheets.get(key)?.as_ref()
+    }
+
+    /// A cell's cached value, else its formula's result
+    fn cell(&mut self, sheet: &str, row: u32, col: u32, depth: usize) -> Value {
+        let key = (sheet.to_string(), row, col);
+        if let Some(value) = self.results.get(&key) {
+            return value.clone();
+        }
+        let Some(grid) = self.grid(sheet) else {
+            return Value::Error(CellErrorType::Ref);
+        };
+        if let Some(cached) = grid.values.get_value((row, col))
+            && !matches!(cached, Data::Empty)
+        {
+            return Value::from_data(cached);
+        }
+        let Some(formula) = grid.formula(row, col).map(str::to_string) else {
+            return Value::Empty;
+        };
+        // A circular reference, or a chain too deep to follow
+        if depth > MAX_DEPTH || !self.in_progress.insert(key.clone()) {
+            return Value::Error(CellErrorType::Ref);
+        }
+        let value = self.evaluate_formula(&formula, sheet, depth + 1);
+        self.in_progress.remove(&key);
+        self.results.insert(key, value.clone());
+        value
+    }
+
+    fn evaluate_formula(&mut self, formula: &str, sheet: &str, depth: usize) -> Value {
+        match Parser::parse(formula) {
+            Some(expr) => self.eval(&expr, sheet, depth),
+            None => Value::Error(CellErrorType::Name),
+        }
+    }
+
+    fn operand(&mut self, expr: &Expr, sheet: &str, depth: usize) -> Operand {
+        let Expr::Ref(reference) = expr else {
+            return Operand::Value(self.eval(expr, sheet, depth));
+        };
+        let target = reference
+            .sheet
+            .as_deref()
+            .map_or_else(|| sheet.to_string(), str::to_lowercase);
+        let Some(grid) = self.grid(&target) else {
+            return Operand::Value(Value::Error(CellErrorType::Ref));
+        };
+        // Whole-column references stop at the sheet's last used row
+        let last = grid.end();
+        let end = (
+            reference.end.0.min(last.0.max(reference.start.0)),
+            reference.end.1.min(last.1.max(reference.start.1)),
+        );
+        let rows = (reference.start.0..=end.0)
+            .map(|row| {
+                (reference.start.1..=end.1)
+                    .map(|col| self.cell(&target, row, col, depth))
+                    .collect()
+            })
+            .collect();
+        Operand::Range(rows)
+    }
+
+    fn eval(&mut self, expr: &Expr, sheet: &str, depth: usize) -> Value {
+        let result = match expr {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Text(s) => Ok(Value::Text(s.clone())),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Error(e) => Err(e.clone()),
+            Expr::Ref(_) => match self.operand(expr, sheet, depth) {
+                Operand::Range(rows) if rows.len() == 1 && rows[0].len() == 1 => {
+                    Ok(rows[0][0].clone())
+                }
+                Operand::Value(v) => Ok(v),
+                Operand::Range(_) => Err(CellErrorType::Value),
+            },
+            Expr::Negate(inner) => self
+                .eval(inner, sheet, depth)
+                .to_number()
+                .map(|n| Value::Number(-n)),
+            Expr::Percent(inner) => self
+                .eval(inner, sheet, depth)
+                .to_number()
+                .map(|n| Value::Number(n / 100.0)),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs, sheet, depth);
+                let rhs = self.eval(rhs, sheet, depth);
+                binary(op, &lhs, &rhs)
+            }
+            Expr::Call(name, args) => self.call(name, args, sheet, depth),
+        };
+        match result {
+            Ok(Value::Number(n)) if !n.is_finite() => Value::Error(CellErrorType::Num),
+            Ok(value) => value,
+            Err(e) => Value::Error(e),
+        }
+    }
+
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        sheet: &str,
+        depth: usize,
+    ) -> Result<Value, CellErrorType> {
+        let arity = |min: usize, max: usize| {
+            if (min..=max).contains(&args.len()) {
+                Ok(())
+            } else {
+                Err(CellErrorType::Value)
+            }
+        };
+        match name {
+            // IF and IFERROR only evaluate the branch they return
+            "IF" => {
+                arity(2, 3)?;
+                if self.eval(&args[0], sheet, depth).to_bool()? {
+                    Ok(self.eval(&args[1], sheet, depth))
+                } else if let Some(otherwise) = args.get(2) {
+                    Ok(self.eval(otherwise, sheet, depth))
+                } else {
+                    Ok(Value::Bool(false))
+                }
+            }
+            "IFERROR" => {
+                arity(2, 2)?;
+                match self.eval(&args[0], sheet, depth) {
+                    Value::Error(_) => Ok(self.eval(&args[1], sheet, depth)),
+                    value => Ok(value),
+                }
+            }
+            "SUM" | "AVERAGE" | "MIN" | "MAX" | "COUNT" => {
+                let numbers = self.numbers(args, sheet, depth)?;
+                let sum: f64 = numbers.iter().sum();
+                Ok(Value::Number(match name {
+                    "SUM" => sum,
+                    "AVERAGE" if numbers.is_empty() => return Err(CellErrorType::Div0),
+                    "AVERAGE" => sum / numbers.len() as f64,
+                    "MIN" => numbers.iter().copied().reduce(f64::min).unwrap_or(0.0),
+                    "MAX" => numbers.iter().copied().reduce(f64::max).unwrap_or(0.0),
+                    _ => numbers.len() as f64,
+                }))
+            }
+            "COUNTA" => {
+                let mut count = 0;
+                for arg in args {
+                    let values = self.operand(arg, sheet, depth).values();
+                    count += values.iter().filter(|v| **v != Value::Empty).count();
+                }
+                Ok(Value::Number(count as f64))
+            }
+            "AND" | "OR" => {
+                let mut flags = Vec::new();
+                for arg in args {
+                    match self.operand(arg, sheet, depth) {
+                        Operand::Value(v) => flags.push(v.to_bool()?),
+                        // Text and empty cells in ranges are ignored
+                        Operand::Range(rows) => {
+                            for v in rows.into_iter().flatten() {
+                                match v {
+                                    Value::Number(_) | Value::Bool(_) => flags.push(v.to_bool()?),
+                                    Value::Error(e) => return Err(e),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                }
+                if flags.is_empty() {
+                    return Err(CellErrorType::Value);
+                }
+                Ok(Value::Bool(if name == "AND" {
+                    flags.iter().all(|&f| f)
+                } else {
+                    flags.iter().any(|&f| f)
+                }))
+            }
+            "NOT" => {
+                arity(1, 1)?;
+                Ok(Value::Bool(!self.eval(&args[0], sheet, depth).to_bool()?))
+            }
+            "CONCATENATE" | "CONCAT" => {
+                let mut text = String::new();
+                for arg in args {
+                    for value in self.operand(arg, sheet, depth).values() {
+                        text.push_str(&value.to_text()?);
+                    }
+                }
+                Ok(Value::Text(text))
+            }
+            "ROUND" => {
+                arity(2, 2)?;
+                let n = self.eval(&args[0], sheet, depth).to_number()?;
+                let digits = self.eval(&args[1], sheet, depth).to_number()?.trunc() as i32;
+                let scale = 10f64.powi(digits);
+                Ok(Value::Number((n * scale).round() / scale))
+            }
+            "ABS" => {
+                arity(1, 1)?;
+                Ok(Value::Number(
+                    self.eval(&args[0], sheet, depth).to_number()?.abs(),
+                ))
+            }
+            "LEN" | "UPPER" | "LOWER" | "TRIM" => {
+                arity(1, 1)?;
+                let text = self.eval(&args[0], sheet, depth).to_text()?;
+                Ok(match name {
+                    "LEN" => Value::Number(text.chars().count() as f64),
+                    "UPPER" => Value::Text(text.to_uppercase()),
+                    "LOWER" => Value::Text(text.to_lowercase()),
+                    _ => Value::Text(
+                        text.split(' ')
+                            .filter(|w| !w.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                })
+            }
+            "VLOOKUP" => {
+                arity(3, 4)?;
+                let needle = self.eval(&args[0], sheet, depth);
+                if let Value::Error(e) = needle {
+                    return Err(e);
+                }
+                let Operand::Range(table) = self.operand(&args[1], sheet, depth) else {
+                    return Err(CellErrorType::Value);
+                };
+                let column = self.eval(&args[2], sheet, depth).to_number()?.trunc();
+                let approximate = match args.get(3) {
+                    Some(arg) => self.eval(arg, sheet, depth).to_bool()?,
+                    None => true,
+                };
+                vlookup(&needle, &table, column, approximate)
+            }
+            _ => Err(CellErrorType::Name),
+        }
+    }
+
+    /// Numeric arguments for aggregates: scalars are converted, while ranges contribute
+    /// only their numeric cells
+    fn numbers(
+        &mut self,
+        args: &[Expr],
+        sheet: &str,
+        depth: usize,
+    ) -> Result<Vec<f64>, CellErrorType> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match self.operand(arg, sheet, depth) {
+                Operand::Value(v) => numbers.push(v.to_number()?),
+                Operand::Range(rows) => {
+                    for v in rows.into_iter().flatten() {
+                        match v {
+                            Value::Number(n) => numbers.push(n),
+                            Value::Error(e) => return Err(e),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Ok(numbers)
+    }
+}
+
+fn binary(op: &str, lhs: &Value, rhs: &Value) -> Result<Value, CellErrorType> {
+    for side in [lhs, rhs] {
+        if let Value::Error(e) = side {
+            return Err(e.clone());
+        }
+    }
+    if op == "&" {
+        return Ok(Value::Text(lhs.to_text()? + &rhs.to_text()?));
+    }
+    if matches!(op, "=" | "<>" | "<" | ">" | "<=" | ">=") {
+        let ordering = compare(lhs, rhs);
+        return Ok(Value::Bool(match op {
+            "=" => ordering.is_eq(),
+            "<>" => ordering.is_ne(),
+            "<" => ordering.is_lt(),
+            ">" => ordering.is_gt(),
+            "<=" => ordering.is_le(),
+            _ => ordering.is_ge(),
+        }));
+    }
+    let (a, b) = (lhs.to_number()?, rhs.to_number()?);
+    Ok(Value::Number(match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" if b == 0.0 => return Err(CellErrorType::Div0),
+        "/" => a / b,
+        "^" => a.powf(b),
+        _ => return Err(CellErrorType::Value),
+    }))
+}
+
+/// Exact match finds the first equal key; approximate match takes the last key not
+/// greater than the one sought (the first column must be sorted ascending)
+fn vlookup(
+    needle: &Value,
+    table: &[Vec<Value>],
+    column: f64,
+    approximate: bool,
+) -> Result<Value, CellErrorType> {
+    if column < 1.0 {
+        return Err(CellErrorType::Value);
+    }
+    let column = column as usize - 1;
+    let mut found = None;
+    for (i, row) in table.iter().enumerate() {
+        let key = &row[0];
+        if approximate {
+            if std::mem::discriminant(key) != std::mem::discriminant(needle) {
+                continue;
+            }
+            if compare(key, needle).is_gt() {
+                break;
+            }
+            found = Some(i);
+        } else if compare(key, needle).is_eq() && *key != Value::Empty {
+            found = Some(i);
+            break;
+        }
+    }
+    let row = &table[found.ok_or(CellErrorType::NA)?];
+    row.get(column).cloned().ok_or(CellErrorType::Ref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(cells: &[(u32, u32, Data)], formulas: &[(u32, u32, &str)]) -> SheetGrid {
+        let values = Range::from_sparse(
+            cells
+                .iter()
+                .map(|(r, c, v)| calamine::Cell::new((*r, *c), v.clone()))
+                .collect(),
+        );
+        let formulas = Range::from_sparse(
+            formulas
+                .iter()
+                .map(|(r, c, f)| calamine::Cell::new((*r, *c), f.to_string()))
+                .collect(),
+        );
+        SheetGrid {
+            values,
+            formulas: Some(formulas),
+        }
+    }
+
+    fn eval(formula: &str) -> Value {
+        let mut evaluator = Evaluator::new(Box::new(|_| {
+            Some(grid(
+                &[
+                    (0, 0, Data::Int(2)),
+                    (1, 0, Data::Float(3.5)),
+                    (2, 0, Data::String("x".into())),
+                    (0, 1, Data::String("apple".into())),
+                    (1, 1, Data::String("pear".into())),
+                ],
+                &[],
+            ))
+        }));
+        evaluator.evaluate_formula(formula, "sheet1", 0)
+    }
+
+    #[test]
+    fn test_operators_and_precedence() {
+        assert_eq!(eval("1+2*3"), Value::Number(7.0));
+        assert_eq!(eval("-2^2"), Value::Number(4.0));
+        assert_eq!(eval("(A1+A2)/2"), Value::Number(2.75));
+        assert_eq!(eval("50%*A1"), Value::Number(1.0));
+        assert_eq!(eval("B1&\"-\"&A1"), Value::Text("apple-2".into()));
+        assert_eq!(eval("A1>=2"), Value::Bool(true));
+        assert_eq!(eval("\"Apple\"=B1"), Value::Bool(true));
+        assert_eq!(eval("A1/0"), Value::Error(CellErrorType::Div0));
+        assert_eq!(eval("A3+1"), Value::Error(CellErrorType::Value));
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("SUM(A1:A3, 10)"), Value::Number(15.5));
+        assert_eq!(eval("SUM(A:A)"), Value::Number(5.5));
+        assert_eq!(eval("AVERAGE(A1:A2)"), Value::Number(2.75));
+        assert_eq!(eval("COUNT(A1:B3)"), Value::Number(2.0));
+        assert_eq!(eval("COUNTA(A1:B3)"), Value::Number(5.0));
+        assert_eq!(
+            eval("IF(A1>2,\"big\",\"small\")"),
+            Value::Text("small".into())
+        );
+        assert_eq!(eval("IFERROR(1/0, -1)"), Value::Number(-1.0));
+        assert_eq!(eval("ROUND(2.345, 2)"), Value::Number(2.35));
+        assert_eq!(eval("_xlfn.CONCAT(B1:B2)"), Value::Text("applepear".into()));
+        assert_eq!(eval("TRIM(\"  a   b \")"), Value::Text("a b".into()));
+        assert_eq!(eval("AND(A1, TRUE, NOT(FALSE))"), Value::Bool(true));
+        assert_eq!(eval("NPV(0.1, A1)"), Value::Error(CellErrorType::Name));
+    }
+
+    #[test]
+    fn test_formula_parts() {
+        let parts = formula_parts("SUM(Data!A:A)*1.07+'Q1 Sales'!B2-5%*Data!C1").unwrap();
+        assert_eq!(parts.sheets, ["Data", "Q1 Sales"]);
+        assert_eq!(parts.functions, ["SUM"]);
+        assert_eq!(parts.numbers, ["1.07", "5%"]);
+
+        let parts = formula_parts("[1]Rates!B2+'[Prices.xlsx]2024'!A1+#REF!").unwrap();
+        assert_eq!(parts.sheets, ["[1]Rates", "[Prices.xlsx]2024"]);
+        assert_eq!(parts.errors, ["#REF!"]);
+        assert_eq!(formula_parts("SUM(Table1[Amount])"), None);
+    }
+
+    #[test]
+    fn test_vlookup() {
+        let table = vec![
+            vec![Value::Number(1.0), Value::Text("low".into())],
+            vec![Value::Number(5.0), Value::Text("mid".into())],
+            vec![Value::Number(10.0), Value::Text("high".into())],
+        ];
+        let lookup = |n, approximate| vlookup(&Value::Number(n), &table, 2.0, approximate);
+        assert_eq!(lookup(7.0, true), Ok(Value::Text("mid".into())));
+        assert_eq!(lookup(10.0, false), Ok(Value::Text("high".into())));
+        assert_eq!(lookup(7.0, false), Err(CellErrorType::NA));
+        assert_eq!(lookup(0.5, true), Err(CellErrorType::NA));
+        assert_eq!(
+            vlookup(&Value::Number(1.0), &table, 3.0, false),
+            Err(CellErrorType::Ref)
+        );
+    }
+
+    #[test]
+    fn test_fill_missing_across_sheets() {
+        let mut evaluator = Evaluator::new(Box::new(|name| {
+            (name == "rates").then(|| grid(&[(0, 0, Data::Float(0.5))], &[]))
+        }));
+        let sheet = grid(
+            &[(0, 0, Data::Int(10)), (1, 0, Data::Int(20))],
+            &[
+                (0, 1, "A1*Rates!$A$1"),
+                (1, 1, "B1+A2*'rates'!A1"),
+                (2, 1, "SUM(B1:B2)"),
+                (3, 1, "B4+1"),
+            ],
+        );
+        let values = evaluator.fill_missing("Data", sheet);
+
+        assert_eq!(values.end(), Some((3, 1)));
+        assert_eq!(values.get_value((0, 1)), Some(&Data::Float(5.0)));
+        assert_eq!(values.get_value((1, 1)), Some(&Data::Float(15.0)));
+        assert_eq!(values.get_value((2, 1)), Some(&Data::Float(20.0)));
+        assert_eq!(
+            values.get_value((3, 1)),
+            Some(&Data::Error(CellErrorType::Ref))
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Fuzzy matching for the TUI finder (Ctrl+P), in the spirit of fzf: the query's characters
+//! must appear in order, and matches at word starts or in runs rank higher
+
+/// Points for each matched character, plus bonuses for where it matched
+const MATCH: i64 = 16;
+const CONSECUTIVE: i64 = 12;
+const WORD_START: i64 = 10;
+/// Most a single gap between matched characters costs
+const MAX_GAP_PENALTY: i64 = 6;
+
+/// Score `candidate` against `query` (case-insensitive), with the positions of the matched
+/// characters; None when the query's characters don't all appear in order. Higher is better.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut next = 0;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+        // Prefer the next word start holding the character over its first occurrence
+        let found = |word_start: bool| {
+            (next..chars.len()).find(|&i| {
+                chars[i].to_lowercase().next() == Some(wanted)
+                    && (!word_start || is_word_start(&chars, i))
+            })
+        };
+        let first = found(false)?;
+        let at = match positions.last() {
+            Some(&last) if first == last + 1 => first,
+            _ => found(true).unwrap_or(first),
+        };
+
+        score += MATCH;
+        if is_word_start(&chars, at) {
+            score += WORD_START;
+        }
+        match positions.last() {
+            Some(&last) if at == last + 1 => score += CONSECUTIVE,
+            Some(&last) => score -= ((at - last - 1) as i64).min(MAX_GAP_PENALTY),
+            None => score -= (at as i64).min(MAX_GAP_PENALTY),
+        }
+        positions.push(at);
+        next = at + 1;
+    }
+    // Among equal matches, shorter candidates are closer; an empty query matches all equally
+    if !positions.is_empty() {
+        score -= (chars.len() - positions.len()) as i64 / 8;
+    }
+    Some((score, positions))
+}
+
+/// Start of a word: the first character, after a separator, or an upper-case letter
+/// after a lower-case one ("Sales_Q3", "netTotal")
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    let Some(before) = i.checked_sub(1).map(|j| chars[j]) else {
+        return true;
+    };
+    !before.is_alphanumeric() || (before.is_lowercase() && chars[i].is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "Sales"), Some((0, vec![])));
+        assert_eq!(
+            fuzzy_match("slq", "Sales_Q3").map(|(_, p)| p),
+            Some(vec![0, 2, 6])
+        );
+        assert!(fuzzy_match("qs", "Sales_Q3").is_none());
+
+        // Word starts and runs beat scattered letters
+        let score = |query, candidate| fuzzy_match(query, candidate).unwrap().0;
+        assert!(score("rs", "Raw Summary") > score("rs", "Errors"));
+        assert!(score("sum", "Summary") > score("sum", "Sales Unit Margin ratio"));
+        assert!(score("q3", "Q3 Results") > score("q3", "Quarter 3"));
+    }
+}
@@ -0,0 +1,171 @@
+//! `--help-topics`: help too long for `--help`, built from the CLI definition and the
+//! keybindings the config resolves to, so it never lists a key that isn't bound
+
+use crate::config::Config;
+use crate::keymap;
+use anyhow::{Result, bail};
+use clap::Command;
+
+/// Topics of `--help-topics TOPIC`, with what each prints
+const TOPICS: &[(&str, &str)] = &[
+    (
+        "keys",
+        "TUI shortcuts, as the config's profile and custom keys bind them",
+    ),
+    (
+        "man",
+        "A man page with every option, subcommand, and shortcut (xleak --help-topics man > xleak.1)",
+    ),
+];
+
+/// The text of a help topic; "list" names the topics
+pub fn topic(name: &str, command: &Command, config: &Config) -> Result<String> {
+    Ok(match name {
+        "list" => {
+            let mut text = String::from("Help topics (xleak --help-topics TOPIC):\n");
+            for (name, about) in TOPICS {
+                text.push_str(&format!("  {name:<6}{about}\n"));
+            }
+            text
+        }
+        "keys" => keymap::help_text(config),
+        "man" => man_page(command, config),
+        other => bail!("Unknown help topic '{other}'. Use: keys or man"),
+    })
+}
+
+/// Escape text for roff: backslashes and dashes, and a leading `.` or `'` that would
+/// read as a request
+fn roff(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An xleak(1) man page in roff: options and subcommands from the CLI definition, and
+/// the TUI shortcuts as `config` binds them
+fn man_page(command: &Command, config: &Config) -> String {
+    let mut command = command.clone();
+    command.build();
+    let about = command
+        .get_about()
+        .map(|about| about.to_string())
+        .unwrap_or_default();
+    let version = command.get_version().unwrap_or_default();
+
+    let mut page = format!(
+        ".TH XLEAK 1 \"\" \"xleak {version}\" \"User Commands\"\n\
+         .SH NAME\n\
+         xleak \\- {}\n\
+         .SH SYNOPSIS\n\
+         .B xleak\n\
+         [\\fIOPTIONS\\fR] \\fIFILE\\fR...\n\
+         .br\n\
+         .B xleak\n\
+         \\fICOMMAND\\fR [\\fIOPTIONS\\fR] \\fIFILE\\fR...\n\
+         .SH DESCRIPTION\n\
+         {}\n",
+        roff(&about),
+        roff(&about)
+    );
+
+    page.push_str(".SH OPTIONS\n");
+    for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        let value = arg
+            .get_value_names()
+            .map(|names| names.join(" "))
+            .unwrap_or_else(|| arg.get_id().as_str().to_uppercase());
+        let mut names = Vec::new();
+        if arg.is_positional() {
+            names.push(format!("\\fI{}\\fR", roff(&value)));
+        } else {
+            if let Some(short) = arg.get_short() {
+                names.push(format!("\\fB\\-{short}\\fR"));
+            }
+            if let Some(long) = arg.get_long() {
+                names.push(format!("\\fB\\-\\-{}\\fR", roff(long)));
+            }
+        }
+        let mut head = names.join(", ");
+        if !arg.is_positional() && arg.get_action().takes_values() {
+            head.push_str(&format!(" \\fI{}\\fR", roff(&value)));
+        }
+        let help = arg
+            .get_help()
+            .map(|help| help.to_string())
+            .unwrap_or_default();
+        page.push_str(&format!(".TP\n{head}\n{}\n", roff(&help)));
+    }
+
+    page.push_str(".SH COMMANDS\n");
+    for sub in command
+        .get_subcommands()
+        .filter(|sub| sub.get_name() != "help")
+    {
+        let about = sub
+            .get_about()
+            .map(|about| about.to_string())
+            .unwrap_or_default();
+        page.push_str(&format!(
+            ".TP\n\\fBxleak {}\\fR\n{}\n",
+            roff(sub.get_name()),
+            roff(&about)
+        ));
+    }
+
+    page.push_str(".SH KEYS\nShortcuts of the interactive mode (\\fB\\-i\\fR), as configured.\n");
+    for (heading, entries) in keymap::help_sections(config) {
+        page.push_str(&format!(".SS {}\n", roff(heading)));
+        for (label, description) in entries {
+            page.push_str(&format!(
+                ".TP\n\\fB{}\\fR\n{}\n",
+                roff(&label),
+                roff(description)
+            ));
+        }
+    }
+
+    page.push_str(
+        ".SH FILES\n\
+         .TP\n\
+         \\fI~/.config/xleak/config.toml\\fR\n\
+         Theme, display, keybinding, and cache settings (see \\fB\\-\\-config\\fR).\n",
+    );
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_man_page() {
+        let command = Command::new("xleak")
+            .version("1.0")
+            .about("Expose Excel files")
+            .arg(
+                clap::Arg::new("sheet")
+                    .short('s')
+                    .long("sheet")
+                    .value_name("SHEET")
+                    .help("Sheet to show"),
+            )
+            .subcommand(Command::new("meta").about("Show workbook details"));
+        let page = man_page(&command, &Config::default());
+        assert!(page.starts_with(".TH XLEAK 1 \"\" \"xleak 1.0\""));
+        assert!(
+            page.contains(".TP\n\\fB\\-s\\fR, \\fB\\-\\-sheet\\fR \\fISHEET\\fR\nSheet to show\n")
+        );
+        assert!(page.contains("\\fBxleak meta\\fR\nShow workbook details\n"));
+        assert!(page.contains(".TP\n\\fB?\\fR\nToggle this help screen\n"));
+        assert_eq!(roff(".hidden -x"), "\\&.hidden \\-x");
+    }
+}
@@ -0,0 +1,77 @@
+//! Hidden rows and columns recorded in a worksheet
+
+use crate::ooxml::{Package, XmlElement};
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+/// Excel's last column (XFD), bounding `<col>` spans that run to the sheet edge
+const MAX_COLUMNS: u32 = 16_384;
+
+/// Rows and columns a sheet hides, as 0-based absolute indices
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HiddenCells {
+    pub rows: BTreeSet<u32>,
+    pub cols: BTreeSet<u32>,
+}
+
+impl HiddenCells {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.cols.is_empty()
+    }
+}
+
+/// Streams the sheet's `row` and `col` records, collecting those marked hidden
+pub fn load_hidden_cells(package: &mut Package, sheet_name: &str) -> Result<HiddenCells> {
+    let part = package.worksheet_part(sheet_name)?;
+    let mut hidden = HiddenCells::default();
+    package.scan_elements(&part, &["row", "col"], |el| record(&mut hidden, el))?;
+    Ok(hidden)
+}
+
+fn record(hidden: &mut HiddenCells, el: &XmlElement) {
+    if !matches!(el.attr("hidden"), Some("1") | Some("true")) {
+        return;
+    }
+    let index = |attr: &str| el.attr(attr).and_then(|v| v.parse::<u32>().ok());
+    match el.name.as_str() {
+        "row" => {
+            if let Some(row) = index("r").and_then(|r| r.checked_sub(1)) {
+                hidden.rows.insert(row);
+            }
+        }
+        "col" => {
+            if let (Some(min), Some(max)) = (index("min"), index("max")) {
+                let max = max.min(MAX_COLUMNS);
+                hidden.cols.extend(min.saturating_sub(1)..max);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_hidden_rows_and_column_spans() {
+        let xml = r#"<worksheet>
+  <cols><col min="1" max="1" width="9"/><col min="2" max="3" hidden="1"/><col min="6" max="16384" hidden="true"/></cols>
+  <sheetData><row r="1"/><row r="4" hidden="1"/><row r="5" hidden="0"/></sheetData>
+</worksheet>"#;
+        let sheet = parse_xml(xml, &[]).unwrap();
+        let mut hidden = HiddenCells::default();
+        let cols = sheet.child("cols").unwrap().children_named("col");
+        let rows = sheet.child("sheetData").unwrap().children_named("row");
+        for el in cols.chain(rows) {
+            record(&mut hidden, el);
+        }
+
+        assert_eq!(hidden.rows.iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(hidden.cols.len(), 2 + (16_384 - 5));
+        assert!(hidden.cols.contains(&1) && hidden.cols.contains(&2));
+        assert!(!hidden.cols.contains(&3));
+        assert!(hidden.cols.contains(&16_383));
+    }
+}
@@ -0,0 +1,151 @@
+//! Cell hyperlinks declared in a worksheet's `hyperlinks` record
+
+use crate::ooxml::{Package, Relationship, XmlElement, parse_cell_ref, parse_range_ref};
+use anyhow::Result;
+
+/// A hyperlink attached to one cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperlink {
+    /// 0-based absolute position
+    pub row: u32,
+    pub col: u32,
+    /// URL, file path, or "#Sheet2!A1" for a location inside the workbook
+    pub target: String,
+    pub tooltip: Option<String>,
+}
+
+impl Hyperlink {
+    /// Whether the link leaves the workbook (URL, mail address, or file)
+    pub fn is_external(&self) -> bool {
+        !self.target.starts_with('#')
+    }
+}
+
+/// How exports show cells that carry a hyperlink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyperlinkMode {
+    /// The cell's display text only
+    Text,
+    /// The link target instead of the text
+    Url,
+    /// "text <url>" (the URL alone if the text is empty or the same)
+    Both,
+}
+
+impl HyperlinkMode {
+    /// Cell text to export for a linked cell showing `text`
+    pub fn render(&self, text: &str, target: &str) -> String {
+        match self {
+            Self::Text => text.to_string(),
+            Self::Url => target.to_string(),
+            Self::Both if text.is_empty() || text == target => target.to_string(),
+            Self::Both => format!("{text} <{target}>"),
+        }
+    }
+}
+
+/// Reads a sheet's hyperlinks, one entry per linked cell, in sheet order
+pub fn load_hyperlinks(package: &mut Package, sheet_name: &str) -> Result<Vec<Hyperlink>> {
+    let part = package.worksheet_part(sheet_name)?;
+
+    let sheet = package.read_xml_skipping(&part, &["sheetData"])?;
+    let Some(hyperlinks) = sheet.child("hyperlinks") else {
+        return Ok(Vec::new());
+    };
+    let rels = package.relationships(&part)?;
+    Ok(hyperlinks_from(hyperlinks, &rels))
+}
+
+fn hyperlinks_from(hyperlinks: &XmlElement, rels: &[Relationship]) -> Vec<Hyperlink> {
+    let mut links = Vec::new();
+    for el in hyperlinks.children_named("hyperlink") {
+        let external = el
+            .attr("id")
+            .and_then(|id| rels.iter().find(|r| r.id == id))
+            .map(|r| r.target.as_str());
+        let location = el.attr("location").filter(|l| !l.is_empty());
+        let target = match (external, location) {
+            (Some(url), Some(location)) => format!("{url}#{location}"),
+            (Some(url), None) => url.to_string(),
+            (None, Some(location)) => format!("#{location}"),
+            (None, None) => continue,
+        };
+
+        // A link may cover a range; every cell in it is clickable in Excel
+        let Some(reference) = el.attr("ref") else {
+            continue;
+        };
+        let Some(((r1, c1), (r2, c2))) = parse_range_ref(reference)
+            .or_else(|| parse_cell_ref(reference).map(|cell| (cell, cell)))
+        else {
+            continue;
+        };
+        for row in r1..=r2 {
+            for col in c1..=c2 {
+                links.push(Hyperlink {
+                    row,
+                    col,
+                    target: target.clone(),
+                    tooltip: el.attr("tooltip").map(str::to_string),
+                });
+            }
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_hyperlinks_from_rels_and_locations() {
+        let xml = r#"<hyperlinks xmlns:r="urn:r">
+  <hyperlink ref="B2" r:id="rId1" tooltip="Docs"/>
+  <hyperlink ref="A3:A4" location="'Q1 Sales'!C5"/>
+  <hyperlink ref="C1" r:id="rId2" location="top"/>
+  <hyperlink ref="D1" r:id="rId9"/>
+</hyperlinks>"#;
+        let rels = ["https://example.com/docs", "report.xlsx"]
+            .iter()
+            .enumerate()
+            .map(|(i, target)| Relationship {
+                id: format!("rId{}", i + 1),
+                kind: "hyperlink".to_string(),
+                target: target.to_string(),
+                external: true,
+            })
+            .collect::<Vec<_>>();
+
+        let links = hyperlinks_from(&parse_xml(xml, &[]).unwrap(), &rels);
+        let summary: Vec<(u32, u32, &str)> = links
+            .iter()
+            .map(|l| (l.row, l.col, l.target.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 1, "https://example.com/docs"),
+                (2, 0, "#'Q1 Sales'!C5"),
+                (3, 0, "#'Q1 Sales'!C5"),
+                (0, 2, "report.xlsx#top"),
+            ]
+        );
+        assert_eq!(links[0].tooltip.as_deref(), Some("Docs"));
+        assert!(links[0].is_external());
+        assert!(!links[1].is_external());
+    }
+
+    #[test]
+    fn test_hyperlink_mode_render() {
+        let url = "https://example.com";
+        assert_eq!(HyperlinkMode::Text.render("Site", url), "Site");
+        assert_eq!(HyperlinkMode::Url.render("Site", url), url);
+        assert_eq!(
+            HyperlinkMode::Both.render("Site", url),
+            "Site <https://example.com>"
+        );
+        assert_eq!(HyperlinkMode::Both.render("", url), url);
+    }
+}
@@ -0,0 +1,376 @@
+//! The TUI's configurable actions and shortcut help, shared by the config (which checks
+//! custom bindings against it), the help screen, and `--help-topics`
+
+use crate::config::Config;
+
+/// A help screen entry's key: a configurable action or a fixed key
+pub enum HelpKey {
+    Action(&'static str),
+    Fixed(&'static str),
+    /// A configurable action key followed by a fixed key (`]c`)
+    Sequence(&'static str, &'static str),
+}
+
+/// Help screen sections as (heading, [(key, description)])
+pub const HELP_SECTIONS: &[(&str, &[(HelpKey, &str)])] = &[
+    (
+        "NAVIGATION",
+        &[
+            (HelpKey::Action("up"), "Move cursor up"),
+            (HelpKey::Action("down"), "Move cursor down"),
+            (HelpKey::Action("left"), "Move cursor left"),
+            (HelpKey::Action("right"), "Move cursor right"),
+            (
+                HelpKey::Action("page_up"),
+                "Scroll up a page (the rows on screen, or page_size)",
+            ),
+            (
+                HelpKey::Action("page_down"),
+                "Scroll down a page (the rows on screen, or page_size)",
+            ),
+            (
+                HelpKey::Action("half_page_up"),
+                "Scroll up half the rows on screen",
+            ),
+            (
+                HelpKey::Action("half_page_down"),
+                "Scroll down half the rows on screen",
+            ),
+            (
+                HelpKey::Fixed("1-9 then a key"),
+                "Repeat a move, page, or half page (25j); 25gg goes to row 25",
+            ),
+            (
+                HelpKey::Action("jump_to_row_start"),
+                "Jump to first column (start of row)",
+            ),
+            (
+                HelpKey::Action("jump_to_row_end"),
+                "Jump to last column (end of row)",
+            ),
+            (
+                HelpKey::Action("jump_to_top"),
+                "Jump to first row (top of sheet)",
+            ),
+            (
+                HelpKey::Action("jump_to_bottom"),
+                "Jump to last row (bottom of sheet)",
+            ),
+            (
+                HelpKey::Action("jump"),
+                "Jump to row/cell/name (e.g., 100, A5, Summary!B12, 10,3, or Sales)",
+            ),
+            (
+                HelpKey::Action("next_error"),
+                "Jump to next error cell (#DIV/0!, #N/A, ...)",
+            ),
+            (
+                HelpKey::Action("freeze_columns"),
+                "Freeze/unfreeze columns up to the cursor",
+            ),
+            (
+                HelpKey::Action("visual_mode"),
+                "Visual mode: select a range (copy keys copy it)",
+            ),
+            (
+                HelpKey::Action("export_selection"),
+                "Export the selection to a file (in visual mode)",
+            ),
+            (HelpKey::Action("hide_column"), "Hide current column"),
+            (HelpKey::Action("unhide_columns"), "Show all hidden columns"),
+            (
+                HelpKey::Action("pin_column"),
+                "Pin/unpin current column at the left edge",
+            ),
+            (HelpKey::Action("shrink_column"), "Narrow current column"),
+            (HelpKey::Action("grow_column"), "Widen current column"),
+            (
+                HelpKey::Action("autofit_column"),
+                "Fit current column to the values on screen",
+            ),
+        ],
+    ),
+    (
+        "SEARCH",
+        &[
+            (
+                HelpKey::Action("search"),
+                "Start search (type query, Enter to confirm)",
+            ),
+            (
+                HelpKey::Fixed("Alt+r/c/w/l"),
+                "While searching: regex, match case, whole cell, current column",
+            ),
+            (HelpKey::Action("next_match"), "Jump to next search match"),
+            (
+                HelpKey::Action("prev_match"),
+                "Jump to previous search match",
+            ),
+            (
+                HelpKey::Action("search_workbook"),
+                "Search all sheets (n/N switch sheets as needed)",
+            ),
+            (
+                HelpKey::Sequence("command", "grep TEXT"),
+                "Search all sheets for TEXT",
+            ),
+            (
+                HelpKey::Sequence("command", "w FILE.xlsx"),
+                "Save edited cells to a new workbook (values only)",
+            ),
+            (
+                HelpKey::Action("search_results"),
+                "Show workbook search results by sheet",
+            ),
+            (
+                HelpKey::Action("filter"),
+                "Filter rows by column (e.g., B > 100, Status contains open)",
+            ),
+            (
+                HelpKey::Fixed("Esc"),
+                "Leave visual mode, clear filter, then search results",
+            ),
+        ],
+    ),
+    (
+        "DIFF (--diff OLD NEW -i)",
+        &[
+            (
+                HelpKey::Sequence("next_change", "c"),
+                "Jump to next changed cell",
+            ),
+            (
+                HelpKey::Sequence("prev_change", "c"),
+                "Jump to previous changed cell",
+            ),
+        ],
+    ),
+    (
+        "CLIPBOARD",
+        &[
+            (HelpKey::Action("copy_cell"), "Copy current cell value"),
+            (
+                HelpKey::Action("copy_row"),
+                "Copy entire current row (tab-separated)",
+            ),
+        ],
+    ),
+    (
+        "MARKS AND JUMPS",
+        &[
+            (
+                HelpKey::Sequence("set_mark", "a-z"),
+                "Bookmark the current cell under a letter",
+            ),
+            (
+                HelpKey::Sequence("jump_to_mark", "a-z"),
+                "Jump to a bookmark, on any sheet",
+            ),
+            (
+                HelpKey::Sequence("jump_to_mark", "'"),
+                "Jump back to where the last jump started",
+            ),
+            (
+                HelpKey::Action("list_marks"),
+                "List bookmarks (:delmarks a or :delmarks! to delete)",
+            ),
+            (
+                HelpKey::Action("jump_back"),
+                "Go back to where the cursor was before a jump",
+            ),
+            (
+                HelpKey::Action("jump_forward"),
+                "Forward again after jumping back",
+            ),
+        ],
+    ),
+    (
+        "FIND",
+        &[(
+            HelpKey::Action("finder"),
+            "Fuzzy-find a sheet, table, defined name, or column header and jump to it",
+        )],
+    ),
+    (
+        "WORKBOOKS",
+        &[(
+            HelpKey::Action("workbooks"),
+            "Switch between the files opened together (xleak A.xlsx B.xlsx -i)",
+        )],
+    ),
+    (
+        "SPLIT VIEW",
+        &[
+            (
+                HelpKey::Fixed(":split [SHEET]"),
+                "Split into stacked panes, on SHEET or the same place",
+            ),
+            (
+                HelpKey::Fixed(":vsplit [SHEET]"),
+                "Split into side-by-side panes",
+            ),
+            (
+                HelpKey::Action("switch_pane"),
+                "Move the focus to the other pane",
+            ),
+            (
+                HelpKey::Fixed(":only / :close"),
+                "Close the other pane / the focused one",
+            ),
+        ],
+    ),
+    (
+        "SHEET NAVIGATION",
+        &[
+            (HelpKey::Action("next_sheet"), "Switch to next sheet"),
+            (HelpKey::Action("prev_sheet"), "Switch to previous sheet"),
+        ],
+    ),
+    (
+        "GENERAL",
+        &[
+            (
+                HelpKey::Action("show_cell_detail"),
+                "Show cell details (type, formula, value)",
+            ),
+            (
+                HelpKey::Action("show_record"),
+                "Show the row as header/value pairs (←→ step rows)",
+            ),
+            (
+                HelpKey::Action("edit_cell"),
+                "Edit the cell's value (Enter to set, Esc to cancel)",
+            ),
+            (
+                HelpKey::Action("show_pivots"),
+                "Show pivot table definitions (.xlsx)",
+            ),
+            (
+                HelpKey::Action("pivot"),
+                "Group rows and sum/average/count columns (Esc goes back)",
+            ),
+            (
+                HelpKey::Action("bars"),
+                "Draw the column's numbers as bars, with a sparkline; again to stop",
+            ),
+            (
+                HelpKey::Action("heatmap"),
+                "Color the column's numbers from lowest to highest; again to stop",
+            ),
+            (
+                HelpKey::Action("formulas"),
+                "Show formulas instead of values in formula cells; again for values",
+            ),
+            (
+                HelpKey::Action("gutter"),
+                "Show Excel row numbers and column letters around the table",
+            ),
+            (
+                HelpKey::Action("chart"),
+                "Chart the column against the first column (Tab: bar/line)",
+            ),
+            (
+                HelpKey::Action("open_link"),
+                "Open the cell's hyperlink (underlined cells)",
+            ),
+            (
+                HelpKey::Action("reload"),
+                "Reload the workbook from disk (keeps cursor, filter, search)",
+            ),
+            (
+                HelpKey::Action("theme_toggle"),
+                "Cycle through color themes",
+            ),
+            (
+                HelpKey::Fixed(":set OPTION"),
+                "Turn on striped_rows, row_lines, column_lines, or gutter (noOPTION: off)",
+            ),
+            (
+                HelpKey::Fixed(":set OPTION=N"),
+                "Set page_size or scroll_margin (rows kept around the cursor)",
+            ),
+            (HelpKey::Action("help"), "Toggle this help screen"),
+            (HelpKey::Action("quit"), "Quit xleak"),
+            (HelpKey::Fixed("Esc"), "Quit xleak (or clear search)"),
+        ],
+    ),
+];
+
+/// Every configurable action, in help order
+pub fn actions() -> Vec<&'static str> {
+    let mut actions = Vec::new();
+    for (_, entries) in HELP_SECTIONS {
+        for (key, _) in *entries {
+            if let HelpKey::Action(action) | HelpKey::Sequence(action, _) = key
+                && !actions.contains(action)
+            {
+                actions.push(*action);
+            }
+        }
+    }
+    actions
+}
+
+/// Help sections with each key labeled as `config` binds it (profile + custom overrides)
+pub fn help_sections(config: &Config) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+    let bound = |action: &str| config.key_sequence_label(action);
+    HELP_SECTIONS
+        .iter()
+        .map(|(heading, entries)| {
+            let entries = entries
+                .iter()
+                .map(|(key, description)| {
+                    let label = match key {
+                        HelpKey::Action(action) => bound(action),
+                        HelpKey::Fixed(label) => Some(label.to_string()),
+                        HelpKey::Sequence(action, then) => {
+                            bound(action).map(|label| format!("{label}{then}"))
+                        }
+                    };
+                    (
+                        label.unwrap_or_else(|| "(unbound)".to_string()),
+                        *description,
+                    )
+                })
+                .collect();
+            (*heading, entries)
+        })
+        .collect()
+}
+
+/// The shortcut help as plain text, for `--help-topics keys`
+pub fn help_text(config: &Config) -> String {
+    let mut text = String::new();
+    for (heading, entries) in help_sections(config) {
+        text.push_str(heading);
+        text.push('\n');
+        for (label, description) in entries {
+            text.push_str(&format!("  {label:<17}{description}\n"));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_follows_keybindings() {
+        let mut config = Config::default();
+        config.keybindings.profile = "vim".to_string();
+        config
+            .keybindings
+            .custom
+            .insert("copy_cell".to_string(), "Ctrl+y".to_string());
+        let text = help_text(&config);
+        assert!(text.contains("  k                Move cursor up\n"));
+        assert!(text.contains("  Ctrl+Y           Copy current cell value\n"));
+        assert!(text.contains("  ]c               Jump to next changed cell\n"));
+
+        let actions = actions();
+        assert!(actions.contains(&"next_sheet"));
+        assert_eq!(actions.iter().filter(|a| **a == "jump_to_mark").count(), 1);
+    }
+}
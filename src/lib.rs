@@ -0,0 +1,64 @@
+//! Reading, inspecting, and exporting Excel workbooks.
+//!
+//! This is the library behind the `xleak` command-line viewer. It opens .xlsx, .xlsm,
+//! .xlsb, .xls, .ods, and .csv/.tsv files, loads sheets and Excel tables as typed cells,
+//! and writes them as CSV, JSON, plain text, Markdown, SQLite, .xlsx, or .ods.
+//!
+//! ```no_run
+//! use xleak::{ExportOptions, JsonMode, Workbook};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut workbook = Workbook::open("report.xlsx")?;
+//! let sheet = workbook.load_sheet(&workbook.sheet_names()[0])?;
+//! println!("{} rows x {} columns", sheet.height, sheet.width);
+//!
+//! let options = ExportOptions::default();
+//! let mut out = Vec::new();
+//! xleak::write_csv(&mut out, &sheet.headers, &sheet.rows, &options)?;
+//! xleak::write_json(&mut out, &[], &sheet.headers, &sheet.rows, JsonMode::Records, &options)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Package details that calamine doesn't parse (pivot tables, embedded images, external
+//! links, protection, document properties, frozen panes, conditional formatting, cell
+//! colors) are available from [`Workbook`]
+//! for .xlsx files.
+
+pub mod aggregate;
+pub mod audit;
+pub mod baseline;
+pub mod cache;
+pub mod color_mode;
+pub mod conditional;
+pub mod delimited;
+pub mod detect;
+pub mod diff;
+pub mod display;
+pub mod formula;
+pub mod hidden;
+pub mod hyperlinks;
+pub mod links;
+pub mod locale;
+pub mod macros;
+pub mod media;
+pub mod ods;
+pub mod ooxml;
+pub mod panes;
+pub mod pivot;
+pub mod progress;
+pub mod properties;
+pub mod protection;
+pub mod remote;
+pub mod save;
+pub mod schema;
+pub mod sqlite;
+pub mod stats;
+pub mod styles;
+pub mod workbook;
+
+pub use display::{
+    ExportOptions, JsonMode, stream_csv, stream_text, write_csv, write_json, write_markdown,
+    write_text,
+};
+pub use workbook::{CellValue, LazySheetData, SheetData, SheetRows, TableData, Workbook};
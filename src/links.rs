@@ -0,0 +1,394 @@
+//! External workbook links and data connections declared in the xlsx package, and the
+//! formulas, names, hyperlinks, and objects that use them (`--list-links`)
+
+use crate::formula::formula_parts;
+use crate::ooxml::{Package, XmlElement};
+use crate::workbook::{Workbook, cell_reference};
+use anyhow::Result;
+use serde::Serialize;
+
+/// Something the workbook reaches out to when refreshed or recalculated
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalReference {
+    /// "workbook link", "DDE link", "OLE link", "data connection", "web query", or "query table"
+    pub kind: &'static str,
+    pub name: String,
+    /// File path, URL, connection string, or the connection a query table uses
+    pub target: String,
+}
+
+/// Reads external links, connections, and query tables, in that order
+pub fn load_external_references(package: &mut Package) -> Result<Vec<ExternalReference>> {
+    let mut refs = Vec::new();
+    // Connection id -> name, for resolving query tables
+    let mut connection_names = Vec::new();
+    let workbook_rels = package.relationships("xl/workbook.xml")?;
+
+    for rel in workbook_rels.iter().filter(|r| !r.external) {
+        match rel.kind.as_str() {
+            "externalLink" => {
+                let link = package.read_xml(&rel.target)?;
+                // The linked file is the external target of the part's own relationship
+                let path = package
+                    .relationships(&rel.target)?
+                    .into_iter()
+                    .find(|r| r.external)
+                    .map(|r| r.target)
+                    .unwrap_or_default();
+                refs.extend(parse_external_link(&link, &path));
+            }
+            "connections" => {
+                let connections = package.read_xml(&rel.target)?;
+                for (id, reference) in parse_connections(&connections) {
+                    connection_names.push((id, reference.name.clone()));
+                    refs.push(reference);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (sheet_name, sheet_part) in package.worksheet_parts()? {
+        for rel in package.relationships(&sheet_part)? {
+            if rel.kind != "queryTable" || rel.external {
+                continue;
+            }
+            let query = package.read_xml(&rel.target)?;
+            let id = query.attr("connectionId").unwrap_or_default();
+            let target = connection_names
+                .iter()
+                .find(|(cid, _)| cid == id)
+                .map(|(_, name)| format!("connection {id} ({name})"))
+                .unwrap_or_else(|| format!("connection {id}"));
+            refs.push(ExternalReference {
+                kind: "query table",
+                name: format!("{sheet_name}: {}", query.attr("name").unwrap_or_default()),
+                target,
+            });
+        }
+    }
+
+    Ok(refs)
+}
+
+/// Files of the workbooks formulas refer to as `[1]`, `[2]`, ..., in the order of the
+/// workbook's `<externalReferences>`
+pub fn load_external_workbooks(package: &mut Package) -> Result<Vec<String>> {
+    let workbook = package.read_xml_skipping("xl/workbook.xml", &["sheets"])?;
+    let Some(references) = workbook.child("externalReferences") else {
+        return Ok(Vec::new());
+    };
+    let rels = package.relationships("xl/workbook.xml")?;
+    let mut paths = Vec::new();
+    for reference in references.children_named("externalReference") {
+        let part = reference
+            .attr("id")
+            .and_then(|id| rels.iter().find(|r| r.id == id));
+        let path = match part {
+            Some(part) => package
+                .relationships(&part.target)?
+                .into_iter()
+                .find(|r| r.external)
+                .map(|r| r.target)
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// An OLE object on a sheet that shows a file from outside the workbook
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedObject {
+    pub sheet: String,
+    /// 0-based (row, col) of its top-left corner, when the file records one
+    pub cell: Option<(u32, u32)>,
+    /// The program that shows it, e.g. "Word.Document.12"
+    pub prog_id: String,
+    /// The external link holding the file, as `[2]!'...'`
+    pub link: String,
+}
+
+/// Reads the linked (not embedded) OLE objects of every sheet
+pub fn load_linked_objects(package: &mut Package) -> Result<Vec<LinkedObject>> {
+    let mut objects = Vec::new();
+    for (sheet_name, part) in package.worksheet_parts()? {
+        let sheet = package.read_xml_skipping(&part, &["sheetData"])?;
+        objects.extend(linked_objects_in(&sheet, &sheet_name));
+    }
+    Ok(objects)
+}
+
+fn linked_objects_in(element: &XmlElement, sheet_name: &str) -> Vec<LinkedObject> {
+    let mut objects = Vec::new();
+    for child in &element.children {
+        // Objects are often wrapped in mc:AlternateContent, so look at every level
+        if child.name == "oleObject"
+            && let Some(link) = child.attr("link")
+        {
+            let corner = |name| -> Option<u32> {
+                let from = child.child("objectPr")?.child("anchor")?.child("from")?;
+                from.child(name)?.text.trim().parse().ok()
+            };
+            objects.push(LinkedObject {
+                sheet: sheet_name.to_string(),
+                cell: corner("row").zip(corner("col")),
+                prog_id: child.attr("progId").unwrap_or_default().to_string(),
+                link: link.to_string(),
+            });
+        } else {
+            objects.extend(linked_objects_in(child, sheet_name));
+        }
+    }
+    objects
+}
+
+/// Something the workbook reaches out to, and where (`--list-links`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Link {
+    /// "formula", "defined name", "hyperlink", "OLE object", or the kind of an
+    /// [`ExternalReference`]
+    pub kind: &'static str,
+    /// `Sheet!A1`, a sheet, a defined name, or "workbook" for links the workbook declares
+    pub location: String,
+    /// File path or URL
+    pub target: String,
+    /// The formula, the link's tooltip, the object's program, or the link's name
+    pub detail: String,
+}
+
+/// Every external link of an .xlsx workbook: the links and connections it declares,
+/// then the formulas and hyperlinks of each sheet that leave it, linked OLE objects, and
+/// defined names pointing to other workbooks
+pub fn list_links(workbook: &mut Workbook) -> Result<Vec<Link>> {
+    let workbooks = workbook.external_workbooks()?;
+    let mut links: Vec<Link> = workbook
+        .external_references()?
+        .into_iter()
+        .map(|reference| Link {
+            kind: reference.kind,
+            location: "workbook".to_string(),
+            target: reference.target,
+            detail: reference.name,
+        })
+        .collect();
+
+    for sheet in workbook.sheet_names() {
+        for (row, col, formula) in workbook.sheet_formulas(&sheet)? {
+            for target in formula_targets(&formula, &workbooks) {
+                links.push(Link {
+                    kind: "formula",
+                    location: cell_reference(&sheet, row, col),
+                    target,
+                    detail: format!("={formula}"),
+                });
+            }
+        }
+        for link in workbook.hyperlinks(&sheet)? {
+            if link.is_external() {
+                links.push(Link {
+                    kind: "hyperlink",
+                    location: cell_reference(&sheet, link.row as usize, link.col as usize),
+                    target: link.target,
+                    detail: link.tooltip.unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    for object in workbook.linked_objects()? {
+        let location = match object.cell {
+            Some((row, col)) => cell_reference(&object.sheet, row as usize, col as usize),
+            None => object.sheet,
+        };
+        links.push(Link {
+            kind: "OLE object",
+            location,
+            target: linked_workbook(&object.link, &workbooks).unwrap_or(object.link),
+            detail: object.prog_id,
+        });
+    }
+
+    for (name, formula) in workbook.defined_names() {
+        for target in formula_targets(&formula, &workbooks) {
+            links.push(Link {
+                kind: "defined name",
+                location: name.clone(),
+                target,
+                detail: format!("={formula}"),
+            });
+        }
+    }
+    Ok(links)
+}
+
+/// The other workbooks a formula refers to, without repeats
+fn formula_targets(formula: &str, workbooks: &[String]) -> Vec<String> {
+    let mut targets = Vec::new();
+    let sheets = formula_parts(formula).map(|parts| parts.sheets);
+    for sheet in sheets.unwrap_or_default() {
+        if let Some(target) = linked_workbook(&sheet, workbooks)
+            && !targets.contains(&target)
+        {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// The workbook a reference prefix names: `[1]Rates` is the first external link's file,
+/// and `C:\Data\[Prices.xlsx]2024` names its file directly
+fn linked_workbook(prefix: &str, workbooks: &[String]) -> Option<String> {
+    let (folder, rest) = prefix.split_once('[')?;
+    let (book, _sheet) = rest.split_once(']')?;
+    Some(match book.parse::<usize>() {
+        Ok(n) => n
+            .checked_sub(1)
+            .and_then(|i| workbooks.get(i))
+            .filter(|path| !path.is_empty())
+            .cloned()
+            .unwrap_or_else(|| format!("[{n}]")),
+        Err(_) => format!("{folder}{book}"),
+    })
+}
+
+fn parse_external_link(link: &XmlElement, path: &str) -> Vec<ExternalReference> {
+    let mut refs = Vec::new();
+
+    if let Some(book) = link.child("externalBook") {
+        let sheets: Vec<&str> = book
+            .child("sheetNames")
+            .map(|s| {
+                s.children_named("sheetName")
+                    .filter_map(|n| n.attr("val"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        refs.push(ExternalReference {
+            kind: "workbook link",
+            name: sheets.join(", "),
+            target: path.to_string(),
+        });
+    }
+    if let Some(dde) = link.child("ddeLink") {
+        refs.push(ExternalReference {
+            kind: "DDE link",
+            name: dde.attr("ddeTopic").unwrap_or_default().to_string(),
+            target: dde.attr("ddeService").unwrap_or_default().to_string(),
+        });
+    }
+    if let Some(ole) = link.child("oleLink") {
+        refs.push(ExternalReference {
+            kind: "OLE link",
+            name: ole.attr("progId").unwrap_or_default().to_string(),
+            target: path.to_string(),
+        });
+    }
+
+    refs
+}
+
+/// Connections paired with their ids
+fn parse_connections(root: &XmlElement) -> Vec<(String, ExternalReference)> {
+    root.children_named("connection")
+        .map(|conn| {
+            let id = conn.attr("id").unwrap_or_default().to_string();
+            let name = conn.attr("name").unwrap_or_default().to_string();
+            if let Some(web) = conn.child("webPr") {
+                let reference = ExternalReference {
+                    kind: "web query",
+                    name,
+                    target: web.attr("url").unwrap_or_default().to_string(),
+                };
+                return (id, reference);
+            }
+            let target = conn
+                .child("dbPr")
+                .and_then(|db| db.attr("connection"))
+                .or_else(|| conn.child("textPr").and_then(|t| t.attr("sourceFile")))
+                .or_else(|| conn.attr("sourceFile"))
+                .or_else(|| conn.attr("odcFile"))
+                .unwrap_or_default()
+                .to_string();
+            let reference = ExternalReference {
+                kind: "data connection",
+                name,
+                target,
+            };
+            (id, reference)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_parse_external_link() {
+        let xml = r#"<externalLink xmlns="urn:x"><externalBook xmlns:r="urn:r" r:id="rId1">
+  <sheetNames><sheetName val="Summary"/><sheetName val="Q1"/></sheetNames>
+</externalBook></externalLink>"#;
+        let link = parse_xml(xml, &[]).unwrap();
+        let refs = parse_external_link(&link, "file:///C:/Budget/2023.xlsx");
+        assert_eq!(
+            refs,
+            vec![ExternalReference {
+                kind: "workbook link",
+                name: "Summary, Q1".to_string(),
+                target: "file:///C:/Budget/2023.xlsx".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_linked_workbooks_and_objects() {
+        let workbooks = ["file:///C:/Rates.xlsx".to_string()];
+        assert_eq!(
+            formula_targets(
+                "[1]Rates!B2*'C:\\Data\\[Prices.xlsx]2024'!A1+[1]Rates!B3",
+                &workbooks
+            ),
+            ["file:///C:/Rates.xlsx", "C:\\Data\\Prices.xlsx"]
+        );
+        assert!(formula_targets("Data!A1+A2", &workbooks).is_empty());
+        assert_eq!(
+            linked_workbook("[2]!'Doc'", &workbooks).as_deref(),
+            Some("[2]")
+        );
+
+        let xml = r#"<worksheet xmlns="urn:x"><oleObjects><mc:AlternateContent xmlns:mc="urn:mc"><mc:Choice>
+  <oleObject progId="Word.Document.12" link="[1]!'Doc'" shapeId="1025"><objectPr><anchor>
+    <from><col>2</col><colOff>0</colOff><row>4</row><rowOff>0</rowOff></from>
+  </anchor></objectPr></oleObject>
+</mc:Choice></mc:AlternateContent><oleObject progId="Paint" r:id="rId3" xmlns:r="urn:r"/></oleObjects></worksheet>"#;
+        let objects = linked_objects_in(&parse_xml(xml, &[]).unwrap(), "Memo");
+        assert_eq!(
+            objects,
+            [LinkedObject {
+                sheet: "Memo".to_string(),
+                cell: Some((4, 2)),
+                prog_id: "Word.Document.12".to_string(),
+                link: "[1]!'Doc'".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_connections() {
+        let xml = r#"<connections xmlns="urn:x">
+  <connection id="1" name="SalesDB" type="5"><dbPr connection="Provider=SQLOLEDB;Data Source=db01" command="Sales"/></connection>
+  <connection id="2" name="Rates" type="4"><webPr url="https://example.com/rates"/></connection>
+</connections>"#;
+        let refs = parse_connections(&parse_xml(xml, &[]).unwrap());
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].0, "1");
+        assert_eq!(refs[0].1.kind, "data connection");
+        assert_eq!(refs[0].1.name, "SalesDB");
+        assert_eq!(refs[0].1.target, "Provider=SQLOLEDB;Data Source=db01");
+        assert_eq!(refs[1].1.kind, "web query");
+        assert_eq!(refs[1].1.target, "https://example.com/rates");
+    }
+}
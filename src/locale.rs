@@ -0,0 +1,379 @@
+//! How numbers and dates are shown to people (terminal tables, the TUI): the date pattern,
+//! the decimal and thousands separators, and the symbol for amounts of money. Exports
+//! keep plain machine-readable values, except that date-time cells can be written as
+//! ISO 8601, a strftime pattern, or serials ([`ExportDates`]).
+
+use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+/// Separators and date pattern for showing cells (`CellValue::to_display_string`);
+/// `CellValue`'s `Display` uses the default US style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberLocale {
+    /// chrono strftime pattern for the date part of dates and date-times
+    pub date_format: String,
+    pub decimal_separator: String,
+    /// Put between groups of three digits; empty for none
+    pub thousands_separator: String,
+    /// Shown with numbers in cells with a currency format: before them ("$1.50"), or after
+    /// them when it starts with a space ("1,50 €"); empty to show plain numbers
+    pub currency_symbol: String,
+    /// Decimal places for floats with a fraction; None for as many as it takes to read the
+    /// value back exactly
+    pub precision: Option<usize>,
+    /// Write floats of 1e15 and above or below 1e-5 in scientific notation ("1.5e-7"),
+    /// in exports too
+    pub scientific: bool,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self {
+            date_format: "%Y-%m-%d".to_string(),
+            decimal_separator: ".".to_string(),
+            thousands_separator: ",".to_string(),
+            currency_symbol: String::new(),
+            precision: Some(2),
+            scientific: false,
+        }
+    }
+}
+
+/// The default locale, built once for `CellValue`'s `Display`
+pub(crate) fn default_locale() -> &'static NumberLocale {
+    static DEFAULT: OnceLock<NumberLocale> = OnceLock::new();
+    DEFAULT.get_or_init(NumberLocale::default)
+}
+
+/// How date-time cells are written in CSV, JSON, text, Markdown, and SQLite exports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DateTimeFormat {
+    /// "2024-01-15", or "2024-01-15 12:00:00" with a time
+    #[default]
+    Plain,
+    /// "2024-01-15T12:00:00", with milliseconds when there are any and the offset when
+    /// a timezone is given (RFC 3339)
+    Iso8601,
+    /// The Excel serial number, e.g. 45306.5
+    Serial,
+    /// A chrono strftime pattern
+    Pattern(String),
+}
+
+impl DateTimeFormat {
+    /// "iso8601" (or "rfc3339"), "serial", or a strftime pattern
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(match text.to_ascii_lowercase().as_str() {
+            "iso8601" | "iso" | "rfc3339" => Self::Iso8601,
+            "serial" => Self::Serial,
+            _ if StrftimeItems::new(text).any(|item| matches!(item, Item::Error)) => {
+                anyhow::bail!(
+                    "Invalid datetime format '{text}'. Use iso8601, serial, or strftime fields, e.g. %Y-%m-%dT%H:%M:%S"
+                )
+            }
+            _ => Self::Pattern(text.to_string()),
+        })
+    }
+}
+
+/// Date-time format for exports and the offset the workbook's times are in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportDates {
+    pub format: DateTimeFormat,
+    /// Offset of the workbook's times, which Excel doesn't record; None leaves ISO 8601
+    /// times without one
+    pub offset: Option<FixedOffset>,
+}
+
+impl ExportDates {
+    /// Date-times written as `format`, in `timezone` ("Z", "+02:00")
+    pub fn new(format: DateTimeFormat, timezone: Option<&str>) -> Result<Self> {
+        let offset = match timezone {
+            None => None,
+            Some("Z" | "z" | "UTC") => FixedOffset::east_opt(0),
+            Some(tz) => Some(tz.parse::<FixedOffset>().ok().with_context(|| {
+                format!("Invalid timezone '{tz}'. Use an offset such as Z, +02:00, or -05:00")
+            })?),
+        };
+        let dates = Self { format, offset };
+        // Patterns with fields the value can't supply fail here rather than mid-export
+        if let DateTimeFormat::Pattern(pattern) = &dates.format {
+            let mut sample = String::new();
+            write_pattern(
+                &mut sample,
+                excel_datetime(45306.5).expect("valid date"),
+                pattern,
+                dates.offset,
+            )
+            .with_context(|| format!("Can't format dates with '{pattern}'"))?;
+        }
+        Ok(dates)
+    }
+
+    /// An Excel serial as an export string; None for [`DateTimeFormat::Plain`] or a
+    /// serial outside chrono's range
+    pub fn format_serial(&self, serial: f64) -> Option<String> {
+        let datetime = excel_datetime(serial)?;
+        let mut text = String::new();
+        match &self.format {
+            DateTimeFormat::Plain => return None,
+            DateTimeFormat::Serial => return Some(serial.to_string()),
+            DateTimeFormat::Iso8601 => {
+                let _ = write!(text, "{}", datetime.format("%Y-%m-%dT%H:%M:%S%.f"));
+                if let Some(offset) = self.offset {
+                    if offset.local_minus_utc() == 0 {
+                        text.push('Z');
+                    } else {
+                        let _ = write!(text, "{offset}");
+                    }
+                }
+            }
+            DateTimeFormat::Pattern(pattern) => {
+                write_pattern(&mut text, datetime, pattern, self.offset).ok()?;
+            }
+        }
+        Some(text)
+    }
+}
+
+/// Format with a pattern; %z and friends use the offset (UTC when none was given)
+fn write_pattern(
+    out: &mut String,
+    datetime: NaiveDateTime,
+    pattern: &str,
+    offset: Option<FixedOffset>,
+) -> std::fmt::Result {
+    let offset = offset.unwrap_or(FixedOffset::east_opt(0).expect("zero offset"));
+    let datetime = offset
+        .from_local_datetime(&datetime)
+        .single()
+        .ok_or(std::fmt::Error)?;
+    write!(out, "{}", datetime.format(pattern))
+}
+
+/// Date and time of an Excel serial (1900 date system), to the millisecond
+pub fn excel_datetime(serial: f64) -> Option<NaiveDateTime> {
+    // Excel epoch: December 31, 1899 (serial 0); serial 60 is the nonexistent Feb 29, 1900
+    let days = serial.floor() as i64;
+    let adjusted_days = if days > 60 { days - 1 } else { days };
+    let millis = (serial.fract() * 86_400_000.0).round() as i64;
+    NaiveDate::from_ymd_opt(1899, 12, 31)?
+        .and_hms_opt(0, 0, 0)?
+        .checked_add_signed(Duration::days(adjusted_days))?
+        .checked_add_signed(Duration::milliseconds(millis))
+}
+
+/// A float for exports: the shortest text that reads back as the same value, in
+/// scientific notation when `scientific` is set and the magnitude calls for it
+pub fn export_float(value: f64, scientific: bool) -> String {
+    if scientific && is_extreme(value) {
+        format!("{value:e}")
+    } else if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Too large or too small to read comfortably in positional notation
+fn is_extreme(value: f64) -> bool {
+    value.is_finite() && value != 0.0 && !(1e-5..1e15).contains(&value.abs())
+}
+
+impl NumberLocale {
+    /// Fails with a message naming the problem if the date pattern can't format dates
+    pub fn validate(&self) -> Result<()> {
+        if StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error)) {
+            anyhow::bail!(
+                "Invalid date format '{}'. Use strftime fields, e.g. %d.%m.%Y",
+                self.date_format
+            );
+        }
+        // Time and offset fields parse fine but can't be filled from a date
+        let sample = NaiveDate::from_ymd_opt(2024, 12, 31).expect("valid date");
+        if write!(String::new(), "{}", sample.format(&self.date_format)).is_err() {
+            anyhow::bail!(
+                "Invalid date format '{}': only date fields are allowed (times are shown after the date), e.g. %d.%m.%Y",
+                self.date_format
+            );
+        }
+        Ok(())
+    }
+
+    /// A float with this locale's separators and precision; whole floats have no fraction
+    pub fn float(&self, value: f64) -> String {
+        if self.scientific && is_extreme(value) {
+            let text = match self.precision {
+                Some(places) => format!("{value:.places$e}"),
+                None => format!("{value:e}"),
+            };
+            return text.replacen('.', &self.decimal_separator, 1);
+        }
+        let text = match self.precision {
+            _ if value.fract() == 0.0 => format!("{value:.0}"),
+            Some(places) => format!("{value:.places$}"),
+            None => value.to_string(),
+        };
+        match text.split_once('.') {
+            Some((digits, fraction)) => self.number(digits, Some(fraction)),
+            None => self.number(&text, None),
+        }
+    }
+
+    /// A date in `date_format`, or as 2024-12-31 if the pattern has fields a date can't
+    /// supply
+    pub fn date(&self, date: NaiveDate) -> String {
+        let mut text = String::new();
+        match write!(text, "{}", date.format(&self.date_format)) {
+            Ok(()) => text,
+            Err(_) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// A number in a cell with a currency format, with the currency symbol and always
+    /// `precision` decimal places (2 with full precision); None without a symbol
+    pub fn currency(&self, value: f64) -> Option<String> {
+        if self.currency_symbol.is_empty() {
+            return None;
+        }
+        let places = self.precision.unwrap_or(2);
+        let text = format!("{:.places$}", value.abs());
+        let amount = match text.split_once('.') {
+            Some((digits, fraction)) => self.number(digits, Some(fraction)),
+            None => self.number(&text, None),
+        };
+        let sign = if value < 0.0 && amount.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+            "-"
+        } else {
+            ""
+        };
+        Some(if self.currency_symbol.starts_with(' ') {
+            format!("{sign}{amount}{}", self.currency_symbol)
+        } else {
+            format!("{sign}{}{amount}", self.currency_symbol)
+        })
+    }
+
+    /// `digits` (an optional '-' then ASCII digits) with the thousands separator added,
+    /// followed by `fraction` after the decimal separator when there is one
+    pub fn number(&self, digits: &str, fraction: Option<&str>) -> String {
+        let (sign, digits) = match digits.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", digits),
+        };
+        let mut result = sign.to_string();
+        for (idx, ch) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                result.push_str(&self.thousands_separator);
+            }
+            result.push(ch);
+        }
+        if let Some(fraction) = fraction {
+            result.push_str(&self.decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let us = NumberLocale::default();
+        assert_eq!(us.number("-1234567", Some("50")), "-1,234,567.50");
+        assert_eq!(us.number("999", None), "999");
+
+        let european = NumberLocale {
+            date_format: "%d.%m.%Y".to_string(),
+            decimal_separator: ",".to_string(),
+            thousands_separator: ".".to_string(),
+            ..NumberLocale::default()
+        };
+        assert_eq!(european.number("1234", Some("56")), "1.234,56");
+        assert_eq!(european.number("-123", None), "-123");
+        assert_eq!(european.float(1234.5678), "1.234,57");
+        assert_eq!(european.float(-1e20), "-100.000.000.000.000.000.000");
+        assert_eq!(european.currency(1234.5), None);
+        let euros = NumberLocale {
+            currency_symbol: " €".to_string(),
+            ..european.clone()
+        };
+        assert_eq!(euros.currency(1234.5).unwrap(), "1.234,50 €");
+        let dollars = NumberLocale {
+            currency_symbol: "$".to_string(),
+            ..NumberLocale::default()
+        };
+        assert_eq!(dollars.currency(-1234.567).unwrap(), "-$1,234.57");
+        assert_eq!(dollars.currency(-0.001).unwrap(), "$0.00");
+
+        let exact = NumberLocale {
+            precision: None,
+            scientific: true,
+            ..NumberLocale::default()
+        };
+        assert_eq!(exact.float(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(exact.float(1234.5), "1,234.5");
+        assert_eq!(exact.float(-0.000000015), "-1.5e-8");
+        assert_eq!(exact.float(2.5e20), "2.5e20");
+        assert_eq!(
+            NumberLocale {
+                precision: Some(3),
+                ..exact
+            }
+            .float(12345678901234567.0),
+            "1.235e16"
+        );
+        assert!(
+            NumberLocale {
+                date_format: "%Q".to_string(),
+                ..european.clone()
+            }
+            .validate()
+            .is_err()
+        );
+        // Regression: time fields passed validation and made Display panic
+        let with_time = NumberLocale {
+            date_format: "%d.%m.%Y %H:%M".to_string(),
+            ..european
+        };
+        assert!(with_time.validate().is_err());
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        assert_eq!(with_time.date(date), "2024-12-31");
+        assert_eq!(NumberLocale::default().date(date), "2024-12-31");
+    }
+
+    #[test]
+    fn test_datetime_formats() {
+        assert_eq!(
+            DateTimeFormat::parse("ISO8601").unwrap(),
+            DateTimeFormat::Iso8601
+        );
+        assert_eq!(
+            DateTimeFormat::parse("%d/%m/%Y").unwrap(),
+            DateTimeFormat::Pattern("%d/%m/%Y".to_string())
+        );
+        assert!(DateTimeFormat::parse("%Q").is_err());
+
+        // 45306.5 + 250ms
+        let datetime = excel_datetime(45306.5 + 0.25 / 86_400.0).unwrap();
+        assert_eq!(
+            datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            "2024-01-15T12:00:00.250"
+        );
+        let mut text = String::new();
+        let offset = "+02:00".parse().ok();
+        write_pattern(&mut text, datetime, "%d.%m.%Y %H:%M %:z", offset).unwrap();
+        assert_eq!(text, "15.01.2024 12:00 +02:00");
+
+        let iso = ExportDates::new(DateTimeFormat::Iso8601, Some("Z")).unwrap();
+        assert_eq!(iso.format_serial(45306.5).unwrap(), "2024-01-15T12:00:00Z");
+        assert_eq!(ExportDates::default().format_serial(45306.5), None);
+        assert!(ExportDates::new(DateTimeFormat::Iso8601, Some("CET")).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! VBA macro modules extracted from a workbook's vbaProject
+
+use anyhow::{Context, Result};
+use calamine::vba::VbaProject;
+
+/// Procedure names Office runs automatically when a document is opened or closed
+const AUTO_EXEC_PROCEDURES: &[&str] = &[
+    "auto_open",
+    "autoopen",
+    "auto_close",
+    "autoclose",
+    "workbook_open",
+    "workbook_activate",
+    "workbook_beforeclose",
+    "document_open",
+];
+
+/// A decompressed VBA module
+#[derive(Debug, Clone)]
+pub struct VbaModule {
+    pub name: String,
+    pub source: String,
+}
+
+impl VbaModule {
+    pub fn line_count(&self) -> usize {
+        self.source.lines().count()
+    }
+
+    /// Auto-run procedures defined in this module (e.g. "Workbook_Open")
+    pub fn auto_exec_procedures(&self) -> Vec<String> {
+        self.source
+            .lines()
+            .filter_map(procedure_name)
+            .filter(|name| AUTO_EXEC_PROCEDURES.contains(&name.to_ascii_lowercase().as_str()))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// File name the VBA editor would export this module as: `.cls` for class and
+    /// document modules (ThisWorkbook, Sheet1), `.bas` for standard modules
+    pub fn file_name(&self) -> String {
+        // Only class-like modules carry VB_Base/VB_Exposed attributes
+        let is_class = self.source.lines().any(|line| {
+            line.starts_with("Attribute VB_Base") || line.starts_with("Attribute VB_Exposed")
+        });
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("{name}.{}", if is_class { "cls" } else { "bas" })
+    }
+}
+
+/// Decompresses every module in the project, sorted by name
+pub fn modules_from_project(project: &VbaProject) -> Result<Vec<VbaModule>> {
+    let mut modules = project
+        .get_module_names()
+        .into_iter()
+        .map(|name| {
+            let source = project
+                .get_module(name)
+                .with_context(|| format!("Failed to decompress VBA module '{name}'"))?;
+            Ok(VbaModule {
+                name: name.to_string(),
+                source,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(modules)
+}
+
+/// Name of the procedure declared on this line, if any
+fn procedure_name(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace().peekable();
+    // Skip visibility/modifier keywords
+    while let Some(word) = words.peek() {
+        match word.to_ascii_lowercase().as_str() {
+            "public" | "private" | "friend" | "static" => {
+                words.next();
+            }
+            _ => break,
+        }
+    }
+    match words.next()?.to_ascii_lowercase().as_str() {
+        "sub" | "function" => {}
+        _ => return None,
+    }
+    let rest = words.next()?;
+    Some(rest.split('(').next().unwrap_or(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_procedure_name() {
+        assert_eq!(procedure_name("Sub Foo()"), Some("Foo"));
+        assert_eq!(
+            procedure_name("Private Sub Workbook_Open()"),
+            Some("Workbook_Open")
+        );
+        assert_eq!(
+            procedure_name("Public Function Total(x As Long) As Long"),
+            Some("Total")
+        );
+        assert_eq!(procedure_name("    End Sub"), None);
+        assert_eq!(procedure_name("' Sub commented()"), None);
+    }
+
+    #[test]
+    fn test_auto_exec_procedures() {
+        let module = VbaModule {
+            name: "ThisWorkbook".to_string(),
+            source:
+                "Private Sub Workbook_Open()\n  Shell \"calc\"\nEnd Sub\nSub Helper()\nEnd Sub\n"
+                    .to_string(),
+        };
+        assert_eq!(module.auto_exec_procedures(), vec!["Workbook_Open"]);
+        assert_eq!(module.line_count(), 5);
+        assert_eq!(module.file_name(), "ThisWorkbook.bas");
+
+        let class = VbaModule {
+            name: "Sheet1".to_string(),
+            source: "Attribute VB_Name = \"Sheet1\"\nAttribute VB_Base = \"0{00020820}\"\n"
+                .to_string(),
+        };
+        assert_eq!(class.file_name(), "Sheet1.cls");
+    }
+}
@@ -1,31 +1,99 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use comfy_table::{
-    Attribute, Cell, CellAlignment, Color, ColumnConstraint, ContentArrangement, Row, Table, Width,
+    Attribute, Cell, CellAlignment, Color, ColumnConstraint, ContentArrangement, Row, Width,
 };
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
+mod background;
+mod chart;
 mod config;
-mod display;
+mod fuzzy;
+mod help;
+mod keymap;
+mod scan;
+mod session;
 mod tui;
-mod workbook;
+mod watch;
+
+use xleak::aggregate::Aggregation;
+use xleak::hyperlinks::HyperlinkMode;
+use xleak::workbook::HeaderRow;
+use xleak::{
+    aggregate, audit, baseline, cache, color_mode, diff, display, links, locale, ods, ooxml,
+    progress, remote, save, schema, sqlite, stats, workbook,
+};
 
 #[derive(Parser)]
 #[command(name = "xleak")]
 #[command(author, version, about = "Expose Excel files in your terminal - no Microsoft Excel required", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(
+    after_long_help = "TUI shortcuts as your config binds them: xleak --help-topics keys\nMan page: xleak --help-topics man > xleak.1"
+)]
 struct Cli {
-    /// Path to the Excel file (.xlsx, .xls, .xlsm, .ods)
-    #[arg(value_name = "FILE")]
-    file: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    view: ViewArgs,
+
+    /// Path to custom config file (default: $XDG_CONFIG_HOME/xleak/config.toml)
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+}
+
+/// Options of `xleak FILE`, shared by the view, export, tables, stats, diff, and grep
+/// subcommands (each of which presets one of them)
+#[derive(Args)]
+struct ViewArgs {
+    /// Path or http(s) URL of the workbook (.xlsx, .xls, .xlsm, .ods, or .csv/.tsv); several files or a glob with --batch or -i
+    #[arg(value_name = "FILE", required_unless_present = "help_topics")]
+    files: Vec<PathBuf>,
+
+    /// Print a longer help topic: keys (TUI shortcuts as your config binds them) or man (a man page); without TOPIC, list the topics
+    #[arg(
+        long,
+        value_name = "TOPIC",
+        num_args = 0..=1,
+        default_missing_value = "list"
+    )]
+    help_topics: Option<String>,
+
+    /// Convert every sheet of every FILE into DIR as {file}_{sheet}.csv (or .json with --export json)
+    #[arg(long, value_name = "DIR")]
+    batch: Option<PathBuf>,
+
+    /// Compare two workbooks cell by cell: xleak --diff OLD NEW (text report, or --export json)
+    #[arg(long)]
+    diff: bool,
+
+    /// Print `file:sheet!A1: value` for every cell containing PATTERN, in every FILE; exits 1 if nothing matches
+    #[arg(long, value_name = "PATTERN")]
+    grep: Option<String>,
+
+    /// Treat the --grep PATTERN as a regular expression
+    #[arg(long)]
+    regex: bool,
+
+    /// Match --grep PATTERN case-insensitively
+    #[arg(long)]
+    ignore_case: bool,
 
     /// Sheet name or index to display (default: first sheet)
     #[arg(short, long, value_name = "SHEET")]
     sheet: Option<String>,
 
-    /// Export format: csv, json, text
-    #[arg(short, long, value_name = "FORMAT")]
+    /// Export format: csv, json, text, markdown, sqlite, xlsx, ods
+    #[arg(short, long, visible_alias = "to", value_name = "FORMAT")]
     export: Option<String>,
 
+    /// File written by --export sqlite, xlsx, or ods
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
     /// Maximum number of rows to display (0 = all)
     #[arg(short = 'n', long, default_value = "50")]
     max_rows: usize,
@@ -34,6 +102,10 @@ struct Cli {
     #[arg(short, long)]
     formulas: bool,
 
+    /// Compute formulas that have no cached result (SUM, IF, VLOOKUP, arithmetic, and other common functions)
+    #[arg(long)]
+    evaluate: bool,
+
     /// Maximum column width in characters (default: 30)
     #[arg(short = 'w', long, default_value = "30")]
     max_width: usize,
@@ -42,78 +114,964 @@ struct Cli {
     #[arg(long)]
     wrap: bool,
 
+    /// How dates are displayed, as a strftime pattern (e.g. "%d.%m.%Y"); overrides the config
+    #[arg(long, value_name = "PATTERN")]
+    date_format: Option<String>,
+
+    /// Decimal separator for displayed numbers (e.g. ","); overrides the config
+    #[arg(long, value_name = "SEP")]
+    decimal_separator: Option<String>,
+
+    /// Thousands separator for displayed numbers ("" for none); overrides the config
+    #[arg(long, value_name = "SEP")]
+    thousands_separator: Option<String>,
+
+    /// Symbol shown with numbers in cells formatted as currency (e.g. "$", or " €" to put it after); overrides the config
+    #[arg(long, value_name = "SYMBOL")]
+    currency_symbol: Option<String>,
+
+    /// Decimal places for displayed numbers (default 2), or "full" for every digit needed to read the value back exactly
+    #[arg(long, value_name = "N|full")]
+    precision: Option<String>,
+
+    /// Show and export very large and very small numbers in scientific notation (1.5e-7)
+    #[arg(long)]
+    scientific: bool,
+
+    /// Write date-times in exports as iso8601 (RFC 3339 with --timezone), serial (Excel serial numbers), or a strftime pattern
+    #[arg(long, value_name = "FORMAT")]
+    datetime_format: Option<String>,
+
+    /// Write empty cells in CSV, text, and Markdown exports as TEXT (e.g. NULL or NA), and in JSON as that string instead of null (--empty-as "" gives empty strings; "null" keeps null)
+    #[arg(long, value_name = "TEXT")]
+    empty_as: Option<String>,
+
+    /// Export error cells (#DIV/0!, #N/A, ...) as empty: nothing (or the --empty-as text) in CSV, text, and Markdown, null in JSON and SQLite
+    #[arg(long)]
+    errors_as_empty: bool,
+
+    /// Offset of the workbook's times for --datetime-format, e.g. Z or +02:00 (Excel doesn't record one)
+    #[arg(long, value_name = "OFFSET", requires = "datetime_format")]
+    timezone: Option<String>,
+
+    /// Read date serials as the 1900 or 1904 (older Mac Excel) date system instead of the workbook's own setting
+    #[arg(long, value_name = "SYSTEM")]
+    date_system: Option<String>,
+
     /// Interactive TUI mode
     #[arg(short, long)]
     interactive: bool,
 
-    /// Enable horizontal scrolling in TUI mode (auto-size columns)
-    #[arg(short = 'H', long)]
-    horizontal_scroll: bool,
+    /// Write the workbook to a new .xlsx file (cell values only); with -i, where `:w` saves edits
+    #[arg(long, value_name = "FILE")]
+    save_as: Option<PathBuf>,
 
-    /// Path to custom config file (default: $XDG_CONFIG_HOME/xleak/config.toml)
-    #[arg(long, value_name = "PATH")]
-    config: Option<PathBuf>,
+    /// No longer needed: the TUI always sizes columns to content and scrolls horizontally
+    #[arg(short = 'H', long = "horizontal-scroll", hide = true)]
+    _horizontal_scroll: bool,
 
-    /// List all Excel tables in the workbook (.xlsx only)
+    /// List all Excel tables in the workbook with their sheet and range (.xlsx only); JSON with --export json
     #[arg(long)]
     list_tables: bool,
 
+    /// List the sheets with their dimensions, visibility, and tables; JSON with --export json
+    #[arg(long, conflicts_with = "list_tables")]
+    list_sheets: bool,
+
+    /// Report formulas with error results, references to other sheets or workbooks, volatile functions, or hard-coded numbers; JSON with --export json
+    #[arg(long, conflicts_with_all = ["list_tables", "list_sheets"])]
+    audit_formulas: bool,
+
+    /// List external workbook references, hyperlinks, OLE links, and data connections with their sheet/cell locations (.xlsx only); JSON with --export json
+    #[arg(long, conflicts_with_all = ["list_tables", "list_sheets", "audit_formulas"])]
+    list_links: bool,
+
     /// Extract a specific Excel table by name (.xlsx only)
     #[arg(short = 't', long, value_name = "TABLE")]
     table: Option<String>,
+
+    /// List pivot table definitions in the workbook (.xlsx only)
+    #[arg(long)]
+    list_pivots: bool,
+
+    /// Only load this rectangular region of the sheet; its top row is the header (e.g. "B2:F100")
+    #[arg(long, value_name = "RANGE")]
+    range: Option<String>,
+
+    /// Find the table in each sheet (skipping titles, side notes, and stray cells) and load only that
+    #[arg(long, conflicts_with_all = ["range", "table", "header_row", "skip_footer"])]
+    detect_table: bool,
+
+    /// The sheet has no header row: every row is data and columns are named A, B, C, ...
+    #[arg(long, conflicts_with_all = ["header_row", "table"])]
+    no_header: bool,
+
+    /// Skip N rows of titles or decoration above the header row
+    #[arg(
+        long,
+        visible_alias = "skip-rows",
+        value_name = "N",
+        conflicts_with = "table"
+    )]
+    header_row: Option<usize>,
+
+    /// Leave out the last N rows of the sheet (totals, notes, disclaimers)
+    #[arg(long, value_name = "N", default_value = "0", conflicts_with = "table")]
+    skip_footer: usize,
+
+    /// Keep blank rows and columns at the bottom and right of sheets (dropped by default, as used ranges often reach far past the data)
+    #[arg(long)]
+    no_trim: bool,
+
+    /// Sort rows by columns, comma-separated, each optionally :asc or :desc (e.g. "Region,Total:desc")
+    #[arg(long, value_name = "COLUMN[:asc|desc]", value_delimiter = ',')]
+    sort: Vec<String>,
+
+    /// Show only these columns, in this order: header names, letters, or ranges (e.g. "Name,C,E:G")
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// Show a summary table instead of the rows: one row per group of --rows values, with the --values columns aggregated
+    #[arg(long)]
+    pivot: bool,
+
+    /// Columns to group by with --pivot, comma-separated (e.g. "Region,Product")
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        requires = "pivot"
+    )]
+    rows: Vec<String>,
+
+    /// Columns to aggregate with --pivot, each :sum, :avg, :count, :min, or :max (default sum), e.g. "Amount:sum,Amount:avg"
+    #[arg(
+        long,
+        value_name = "COLUMN[:AGG]",
+        value_delimiter = ',',
+        requires = "pivot"
+    )]
+    values: Vec<String>,
+
+    /// Show how often each value of COLUMN occurs, most frequent first, with its share of the rows
+    #[arg(long, value_name = "COLUMN", conflicts_with = "pivot")]
+    count_by: Option<String>,
+
+    /// Draw a bar chart column after each of these numeric columns in the terminal table (e.g. "Amount,Tax")
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        conflicts_with_all = ["export", "canonical", "stats", "schema", "interactive", "batch"]
+    )]
+    bars: Vec<String>,
+
+    /// Color the numbers of these columns in the terminal table on a gradient from their minimum (blue) to maximum (red)
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        conflicts_with_all = ["export", "canonical", "stats", "schema", "interactive", "batch"]
+    )]
+    heatmap: Vec<String>,
+
+    /// Add a footer row totaling the numeric columns to the terminal table and csv, text, or markdown exports; --totals=avg,max picks other aggregates (sum, avg, count, min, max)
+    #[arg(
+        long,
+        value_name = "AGG",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "sum",
+        value_delimiter = ',',
+        conflicts_with_all = ["interactive", "canonical", "stats", "schema", "chart", "stream"]
+    )]
+    totals: Vec<String>,
+
+    /// Draw a bar or line chart of the --y column against the --x column in the terminal: bar or line
+    #[arg(
+        long,
+        value_name = "KIND",
+        requires = "chart_y",
+        conflicts_with_all = [
+            "export", "canonical", "stats", "schema", "interactive", "bars", "heatmap", "batch"
+        ]
+    )]
+    chart: Option<String>,
+
+    /// Column that labels the --chart points (default: row numbers)
+    #[arg(long = "x", value_name = "COLUMN", requires = "chart")]
+    chart_x: Option<String>,
+
+    /// Numeric column to chart with --chart
+    #[arg(long = "y", value_name = "COLUMN", requires = "chart")]
+    chart_y: Option<String>,
+
+    /// Hide columns in output by header name, comma-separated (e.g. "SSN,Email")
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    redact: Vec<String>,
+
+    /// How --redact hides columns: mask (replace values), drop (remove columns), or hash (stable pseudonyms)
+    #[arg(long, value_name = "MODE", default_value = "mask")]
+    redact_mode: String,
+
+    /// Secret mixed into --redact-mode hash so pseudonyms can't be reversed by guessing values
+    #[arg(long, value_name = "SALT", env = "XLEAK_HASH_SALT", default_value = "")]
+    hash_salt: String,
+
+    /// Keep only N evenly spaced rows (useful with --export and --redact-mode hash for test fixtures)
+    #[arg(long, value_name = "N")]
+    sample: Option<usize>,
+
+    /// Report external workbook links, web queries, and data connections (.xlsx only)
+    #[arg(long)]
+    check_external: bool,
+
+    /// List VBA macro modules with their line counts and auto-run procedures (same as `xleak macros FILE`)
+    #[arg(long)]
+    list_macros: bool,
+
+    /// Write the decompressed source of each VBA module into DIR as .bas/.cls files
+    #[arg(long, value_name = "DIR")]
+    dump_macros: Option<PathBuf>,
+
+    /// Write embedded pictures, charts, and OLE objects into DIR and list their anchor cells (.xlsx only)
+    #[arg(long, value_name = "DIR")]
+    extract_media: Option<PathBuf>,
+
+    /// Show workbook details without loading sheet data (same as `xleak meta FILE`)
+    #[arg(long)]
+    info: bool,
+
+    /// How exports show hyperlinked cells: text (display text), url (link target), or both ("text <url>")
+    #[arg(long, value_name = "MODE", default_value = "text")]
+    hyperlinks: String,
+
+    /// Include hidden sheets, rows, and columns (skipped by default, with a note on stderr)
+    #[arg(long)]
+    show_hidden: bool,
+
+    /// JSON layout: rows (object with headers and row arrays) or records (array of objects keyed by header)
+    #[arg(long, value_name = "MODE", default_value = "rows")]
+    json_mode: String,
+
+    /// Field separator for CSV exports: a single character such as ";" (what Excel expects in many European locales) or "tab"; overrides the config
+    #[arg(long, value_name = "CHAR", conflicts_with = "canonical")]
+    delimiter: Option<String>,
+
+    /// Which CSV fields are quoted: necessary, always, nonnumeric, or never; overrides the config
+    #[arg(long, value_name = "POLICY", conflicts_with = "canonical")]
+    quote: Option<String>,
+
+    /// CSV line endings: lf, or crlf for Windows tools; overrides the config
+    #[arg(long, value_name = "lf|crlf", conflicts_with = "canonical")]
+    line_ending: Option<String>,
+
+    /// Start CSV exports with a UTF-8 byte order mark, so Excel reads accented and other non-ASCII text right
+    #[arg(long, conflicts_with = "canonical")]
+    bom: bool,
+
+    /// Byte-stable export for snapshot tests: fixed number formatting, LF line endings,
+    /// and every sheet in name order unless --sheet is given (defaults to --export csv)
+    #[arg(long)]
+    canonical: bool,
+
+    /// Print per-column summaries (counts, distinct values, numeric/date ranges, top values)
+    #[arg(long)]
+    stats: bool,
+
+    /// Print each column's inferred type (integer, float, bool, date, time, duration, string, mixed) and nullability; JSON with --export json
+    #[arg(long, conflicts_with = "stats")]
+    schema: bool,
+
+    /// Write a CSV or text export row by row while the sheet is read, so huge .xlsx/.xlsb sheets never sit in memory (hidden rows and columns are kept)
+    #[arg(
+        long,
+        requires = "export",
+        conflicts_with_all = [
+            "interactive", "batch", "diff", "grep", "table", "range", "detect_table", "sort",
+            "columns", "pivot", "count_by", "redact", "sample", "evaluate", "canonical", "stats",
+            "schema", "chart"
+        ]
+    )]
+    stream: bool,
+
+    /// Redraw the table (or reload the sheet in -i) whenever the file changes on disk
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "batch", "diff", "grep", "table", "export", "canonical", "stream", "stats",
+            "schema", "info", "save_as"
+        ]
+    )]
+    watch: bool,
+
+    /// Print tables with ASCII borders and no colors or banner, for logs and email
+    #[arg(long, conflicts_with = "interactive")]
+    plain: bool,
+
+    /// Leave out the "xleak - Excel File Viewer" banner above tables
+    #[arg(long, conflicts_with = "interactive")]
+    no_banner: bool,
+
+    /// Colors the terminal can show: auto, truecolor, 256, 16, or none (theme and heatmap
+    /// colors are mapped to the nearest ones) [default: auto, or color_mode in the config]
+    #[arg(long, value_name = "MODE")]
+    color_mode: Option<String>,
+
+    /// Show cells in the TUI with the fill and font colors set in the workbook (.xlsx)
+    #[arg(long, requires = "interactive")]
+    excel_colors: bool,
+
+    /// Open the TUI at the first sheet instead of where this file was left last time, and don't remember this visit
+    #[arg(long)]
+    no_session: bool,
+
+    /// Parse sheets from the file instead of the on-disk cache of large sheets, and don't save them there
+    #[arg(long)]
+    no_cache: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compare a sheet against a CSV baseline; exits non-zero on any mismatch
+    Assert {
+        /// Path to the Excel file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Baseline CSV to compare against (header row included, as written by --export csv)
+        #[arg(long, value_name = "CSV")]
+        matches: PathBuf,
+
+        /// Sheet name or index to compare (default: first sheet)
+        #[arg(short, long, value_name = "SHEET")]
+        sheet: Option<String>,
+
+        /// Maximum allowed difference between numeric values
+        #[arg(long, value_name = "DELTA", default_value = "0")]
+        tolerance: f64,
+    },
+
+    /// Compare two workbooks cell by cell (same as `xleak --diff OLD NEW`)
+    Diff(ViewArgs),
+
+    /// Export a sheet or table, as CSV unless --to names another format (same as `xleak FILE --export csv`)
+    Export(ViewArgs),
+
+    /// Print `file:sheet!A1: value` for every cell containing PATTERN (same as `xleak --grep PATTERN FILE...`)
+    Grep {
+        /// Text to look for (a regular expression with --regex)
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+
+        #[command(flatten)]
+        args: ViewArgs,
+    },
+
+    /// List pictures embedded in the workbook (.xlsx only)
+    Images {
+        /// Path to the Excel file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Extract the pictures into this directory
+        #[arg(long, value_name = "DIR")]
+        extract: Option<PathBuf>,
+    },
+
+    /// List VBA macro modules (.xlsm, .xls, .xlsb)
+    Macros {
+        /// Path to the Excel file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Print the decompressed source of every module
+        #[arg(long)]
+        source: bool,
+    },
+
+    /// Show workbook details: format, properties, defined names, sheets, and protection
+    Meta {
+        /// Path to the Excel file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+
+    /// Scan all sheets for sensitive data (emails, card numbers, IDs, keys)
+    Scan {
+        /// Path to the Excel file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Rule sets or rule names to run, comma-separated: pii, secrets, all, or config-defined
+        #[arg(long, value_name = "RULES", default_value = "all")]
+        rules: String,
+    },
+
+    /// Print per-column summaries (same as `xleak FILE --stats`)
+    Stats(ViewArgs),
+
+    /// List the Excel tables, or extract one with --table (.xlsx only; same as `xleak FILE --list-tables`)
+    Tables(ViewArgs),
+
+    /// Show a sheet in the terminal, or in the TUI with -i (same as `xleak FILE`)
+    View(ViewArgs),
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let Cli {
+        command,
+        view,
+        config: config_path,
+    } = Cli::parse();
+    // The view, export, tables, stats, diff, and grep subcommands are `xleak FILE` with
+    // one option preset; the others run on their own after the shared setup below
+    let (cli, command) = match command {
+        Some(Command::View(args)) => (args, None),
+        Some(Command::Export(mut args)) => {
+            args.export.get_or_insert_with(|| "csv".to_string());
+            (args, None)
+        }
+        Some(Command::Tables(mut args)) => {
+            args.list_tables = args.table.is_none();
+            (args, None)
+        }
+        Some(Command::Stats(mut args)) => {
+            args.stats = true;
+            (args, None)
+        }
+        Some(Command::Diff(mut args)) => {
+            args.diff = true;
+            (args, None)
+        }
+        Some(Command::Grep { pattern, mut args }) => {
+            args.grep = Some(pattern);
+            (args, None)
+        }
+        command => (view, command),
+    };
+    if (cli.regex || cli.ignore_case) && cli.grep.is_none() {
+        anyhow::bail!("--regex and --ignore-case only apply to --grep");
+    }
+    if (cli.watch || cli.stream) && (cli.diff || cli.grep.is_some() || cli.stats) {
+        anyhow::bail!("--watch and --stream can't be combined with diff, grep, or stats");
+    }
 
     // Load configuration
-    let config = config::Config::load(cli.config.clone())?;
+    let mut config = config::Config::load(config_path)?;
+    if let Some(topic) = &cli.help_topics {
+        print!("{}", help::topic(topic, &Cli::command(), &config)?);
+        return Ok(());
+    }
+    if cli.excel_colors {
+        config.ui.excel_colors = true;
+    }
+    if cli.formulas {
+        config.ui.formulas = true;
+    }
+    let color_mode = cli.color_mode.as_deref().unwrap_or(&config.ui.color_mode);
+    color_mode::set_color_mode(if cli.plain {
+        color_mode::ColorMode::None
+    } else {
+        color_mode::ColorMode::parse(color_mode)?
+    });
+    display::set_table_look(display::TableLook {
+        plain: cli.plain,
+        no_banner: cli.no_banner,
+    });
+    let locale = locale::NumberLocale {
+        date_format: cli
+            .date_format
+            .clone()
+            .unwrap_or_else(|| config.format.date_format.clone()),
+        decimal_separator: cli
+            .decimal_separator
+            .clone()
+            .unwrap_or_else(|| config.format.decimal_separator.clone()),
+        thousands_separator: cli
+            .thousands_separator
+            .clone()
+            .unwrap_or_else(|| config.format.thousands_separator.clone()),
+        currency_symbol: cli
+            .currency_symbol
+            .clone()
+            .unwrap_or_else(|| config.format.currency_symbol.clone()),
+        precision: match cli.precision.as_deref() {
+            None => Some(2),
+            Some("full") => None,
+            Some(places) => Some(places.parse().with_context(|| {
+                format!("Invalid precision '{places}'. Use a number of decimal places or full")
+            })?),
+        },
+        scientific: cli.scientific,
+    };
+    locale.validate()?;
+    let export_options = display::ExportOptions {
+        dates: match &cli.datetime_format {
+            Some(format) => locale::ExportDates::new(
+                locale::DateTimeFormat::parse(format)?,
+                cli.timezone.as_deref(),
+            )?,
+            None => locale::ExportDates::default(),
+        },
+        empty: cli.empty_as.clone(),
+        errors_as_empty: cli.errors_as_empty,
+        scientific: cli.scientific,
+        // Canonical exports are byte-stable whatever the config says
+        csv: if cli.canonical {
+            display::CsvDialect::default()
+        } else {
+            display::CsvDialect::parse(
+                cli.delimiter.as_deref().unwrap_or(&config.csv.delimiter),
+                cli.quote.as_deref().unwrap_or(&config.csv.quote),
+                cli.line_ending
+                    .as_deref()
+                    .unwrap_or(&config.csv.line_ending),
+                cli.bom || config.csv.bom,
+            )?
+        },
+    };
+
+    let date_system = match cli.date_system.as_deref() {
+        None => None,
+        Some("1900") => Some(workbook::DateSystem::D1900),
+        Some("1904") => Some(workbook::DateSystem::D1904),
+        Some(other) => anyhow::bail!("Unknown date system: {other}. Use: 1900 or 1904"),
+    };
 
-    // Validate file exists
-    if !cli.file.exists() {
-        anyhow::bail!("File not found: {}", cli.file.display());
+    if config.cache.enabled
+        && !cli.no_cache
+        && let Some(dir) = cache::default_dir()
+    {
+        cache::enable(dir, config.cache.max_size_mb.saturating_mul(1024 * 1024));
     }
 
-    // Load the workbook
-    let mut wb = workbook::Workbook::open(&cli.file).context("Failed to open Excel file")?;
+    // The TUI draws its own progress; elsewhere huge sheets get a bar on stderr
+    if !cli.interactive {
+        progress::enable();
+    }
 
-    // Handle table operations (xlsx only)
-    if cli.list_tables {
-        wb.load_tables()?;
-        let table_names = wb.table_names()?;
+    if let Some(command) = &command {
+        return run_command(command, &config);
+    }
+
+    let redact_mode = match cli.redact_mode.as_str() {
+        "mask" => workbook::RedactMode::Mask,
+        "drop" => workbook::RedactMode::Drop,
+        "hash" => workbook::RedactMode::Hash {
+            salt: cli.hash_salt.clone(),
+        },
+        other => anyhow::bail!("Unknown redact mode: {other}. Use: mask, drop, or hash"),
+    };
+    if (!cli.redact.is_empty()
+        || cli.range.is_some()
+        || cli.detect_table
+        || !cli.columns.is_empty()
+        || !cli.sort.is_empty()
+        || cli.pivot
+        || cli.count_by.is_some()
+        || cli.sample.is_some())
+        && cli.interactive
+    {
+        anyhow::bail!(
+            "--range, --detect-table, --sort, --columns, --pivot, --count-by, --redact, and --sample are not supported in interactive mode (-i).\n\
+             Use them with terminal display or --export to produce sanitized output."
+        );
+    }
+    let chart_kind = match cli.chart.as_deref() {
+        None => None,
+        Some(kind) => match chart::ChartKind::parse(kind) {
+            Some(kind) => Some(kind),
+            None => anyhow::bail!("Unknown chart kind: {kind}. Use: bar or line"),
+        },
+    };
+    let totals = totals_aggregations(&cli.totals)?;
+    if !totals.is_empty()
+        && !matches!(
+            cli.export.as_deref(),
+            None | Some("csv" | "text" | "markdown" | "md")
+        )
+    {
+        anyhow::bail!(
+            "--totals adds rows to the terminal table and csv, text, or markdown exports"
+        );
+    }
+    let json_mode = match cli.json_mode.as_str() {
+        "rows" => display::JsonMode::Rows,
+        "records" => display::JsonMode::Records,
+        other => anyhow::bail!("Unknown JSON mode: {other}. Use: rows or records"),
+    };
+    let hyperlinks = match cli.hyperlinks.as_str() {
+        "text" => HyperlinkMode::Text,
+        "url" => HyperlinkMode::Url,
+        "both" => HyperlinkMode::Both,
+        other => anyhow::bail!("Unknown hyperlink mode: {other}. Use: text, url, or both"),
+    };
+    if hyperlinks != HyperlinkMode::Text && cli.table.is_some() {
+        anyhow::bail!("--hyperlinks url/both can't be combined with --table");
+    }
+    if cli.canonical && cli.interactive {
+        anyhow::bail!("--canonical is an export mode and can't be combined with -i");
+    }
+    if cli.stats && (cli.interactive || cli.export.is_some() || cli.canonical) {
+        anyhow::bail!(
+            "--stats prints a report and can't be combined with -i, --export, or --canonical"
+        );
+    }
+    if cli.schema
+        && (cli.interactive
+            || cli.canonical
+            || !matches!(cli.export.as_deref(), None | Some("json")))
+    {
+        anyhow::bail!(
+            "--schema prints a report (as JSON with --export json) and can't be combined with -i, --canonical, or other export formats"
+        );
+    }
+    let export = cli.export.as_deref().or(cli.canonical.then_some("csv"));
+    let window = match cli.range.as_deref() {
+        Some(range) => Some(ooxml::parse_range_ref(range).with_context(|| {
+            format!("Invalid range '{range}'. Use A1-style corners, e.g. A1:D50")
+        })?),
+        None => None,
+    };
+    if window.is_some() && cli.table.is_some() {
+        anyhow::bail!("--range can't be combined with --table (a table already is a range)");
+    }
+    let header = match (cli.no_header, cli.header_row) {
+        (true, _) => HeaderRow::None,
+        (false, Some(rows)) => HeaderRow::Skip(rows),
+        (false, None) => HeaderRow::First,
+    };
+    // SQLite, xlsx, and ods exports write a file rather than stdout
+    let file_output = match (export, cli.output.as_deref()) {
+        (Some("sqlite" | "xlsx" | "ods"), Some(path)) => Some(path),
+        (Some(format @ ("sqlite" | "xlsx" | "ods")), None) => {
+            anyhow::bail!("--export {format} needs --output <PATH>")
+        }
+        _ => None,
+    };
+    let pipeline = SheetPipeline {
+        window,
+        sort: &cli.sort,
+        pivot: cli.pivot.then_some((&cli.rows[..], &cli.values[..])),
+        count_by: cli.count_by.as_deref(),
+        bars: &cli.bars,
+        sample: cli.sample,
+        columns: &cli.columns,
+        redact: &cli.redact,
+        redact_mode,
+        hyperlinks,
+        show_hidden: cli.show_hidden,
+        evaluate: cli.evaluate,
+        header,
+        skip_footer: cli.skip_footer,
+        trim: !cli.no_trim,
+        detect_table: cli.detect_table,
+        canonical: cli.canonical,
+        date_system,
+        locale: &locale,
+    };
+
+    if cli.save_as.is_some()
+        && (cli.diff
+            || cli.grep.is_some()
+            || cli.batch.is_some()
+            || cli.table.is_some()
+            || cli.export.is_some())
+    {
+        anyhow::bail!(
+            "--save-as can't be combined with --diff, --grep, --batch, --table, or --export"
+        );
+    }
+
+    // Shells expand globs on Unix; expand quoted patterns (and on Windows) ourselves
+    let files = expand_file_args(&cli.files)?;
+    if cli.diff {
+        if cli.batch.is_some() || cli.table.is_some() || cli.grep.is_some() {
+            anyhow::bail!("--diff can't be combined with --batch, --table, or --grep");
+        }
+        let [old, new] = files.as_slice() else {
+            anyhow::bail!("--diff compares exactly two files: xleak --diff OLD NEW");
+        };
+        if cli.interactive {
+            return run_diff_tui(
+                old,
+                new,
+                cli.evaluate,
+                &config,
+                date_system,
+                locale,
+                export_options,
+            );
+        }
+        let json = match export {
+            None | Some("text") => false,
+            Some("json") => true,
+            Some(other) => anyhow::bail!("--diff reports as text or json, not {other}"),
+        };
+        return run_diff(old, new, cli.evaluate, json, date_system);
+    }
+    if let Some(pattern) = &cli.grep {
+        if cli.interactive || cli.batch.is_some() || cli.export.is_some() || cli.table.is_some() {
+            anyhow::bail!("--grep can't be combined with -i, --batch, --export, or --table");
+        }
+        let pattern = if cli.regex {
+            pattern.clone()
+        } else {
+            regex::escape(pattern)
+        };
+        let matcher = regex::RegexBuilder::new(&pattern)
+            .case_insensitive(cli.ignore_case)
+            .build()
+            .context("Invalid --grep pattern")?;
+        return run_grep(
+            &files,
+            &matcher,
+            cli.sheet.as_deref(),
+            cli.show_hidden,
+            date_system,
+        );
+    }
+    if let Some(dir) = &cli.batch {
+        if cli.interactive || cli.table.is_some() || cli.stats || cli.schema || cli.info {
+            anyhow::bail!(
+                "--batch can't be combined with -i, --table, --stats, --schema, or --info"
+            );
+        }
+        let format = match export {
+            None => "csv",
+            Some(format @ ("csv" | "json")) => format,
+            Some(other) => anyhow::bail!("--batch writes csv or json, not {other}"),
+        };
+        return run_batch(
+            &files,
+            dir,
+            format,
+            cli.sheet.as_deref(),
+            &pipeline,
+            json_mode,
+            &export_options,
+        );
+    }
+    // Several files with -i open together, switched with W in the TUI
+    if cli.interactive && files.len() > 1 {
+        if cli.save_as.is_some() || cli.info || cli.stream {
+            anyhow::bail!("--save-as, --info, and --stream take a single workbook");
+        }
+        let mut workbooks = Vec::new();
+        for file in &files {
+            if cli.watch && remote::as_url(file).is_some() {
+                anyhow::bail!("--watch needs a local file, not a URL");
+            }
+            let mut wb = open_workbook(file)?;
+            wb.set_date_system(date_system);
+            let sheet_names = wb.sheet_names();
+            if sheet_names.is_empty() {
+                anyhow::bail!("No sheets found in {}", file.display());
+            }
+            // --sheet applies to the files that have such a sheet
+            let sheet_name = match cli.sheet.as_deref() {
+                Some(sheet) => resolve_sheet(&sheet_names, Some(sheet)).ok(),
+                None => None,
+            }
+            .unwrap_or_else(|| first_shown_sheet(&wb, cli.show_hidden));
+            workbooks.push(tui_workbook(file, wb, sheet_name, &cli, &config, header)?);
+        }
+        return tui::run_tui(workbooks, &config, None, None, locale, export_options);
+    }
+    let [file] = files.as_slice() else {
+        anyhow::bail!(
+            "{} files given; convert several workbooks with --batch <DIR>, or open them with -i",
+            files.len()
+        );
+    };
+    if cli.watch && remote::as_url(file).is_some() {
+        anyhow::bail!("--watch needs a local file, not a URL");
+    }
+    let mut wb = open_workbook(file)?;
+    wb.set_date_system(date_system);
+
+    // Without -i, --save-as just rewrites the workbook; in the TUI it's the `:w` target
+    if let Some(path) = &cli.save_as
+        && !cli.interactive
+    {
+        wb.set_evaluate_formulas(cli.evaluate);
+        save::save_workbook(&mut wb, &Default::default(), path)?;
+        eprintln!("Saved {} (cell values only)", path.display());
+        return Ok(());
+    }
+
+    if cli.info {
+        return print_workbook_info(file, &mut wb);
+    }
 
-        if table_names.is_empty() {
-            println!("No tables found in workbook");
+    if cli.list_sheets || cli.list_tables || cli.audit_formulas || cli.list_links {
+        let json = match export {
+            None | Some("text") => false,
+            Some("json") => true,
+            Some(other) => anyhow::bail!(
+                "--list-sheets, --list-tables, --audit-formulas, and --list-links report as text or json, not {other}"
+            ),
+        };
+        return if cli.list_sheets {
+            list_sheets(&mut wb, json)
+        } else if cli.list_tables {
+            list_tables(&mut wb, json)
+        } else if cli.audit_formulas {
+            print_formula_audit(&mut wb, json)
         } else {
-            println!("Sheet\tTable");
-            println!("-----\t-----");
-            for table_name in &table_names {
-                // Get which sheet this table is in
-                let sheet_names = wb.sheet_names();
-                for sheet in &sheet_names {
-                    let tables_in_sheet = wb.table_names_in_sheet(sheet)?;
-                    if tables_in_sheet.contains(table_name) {
-                        println!("{sheet}\t{table_name}");
-                        break;
-                    }
+            print_links(&mut wb, json)
+        };
+    }
+
+    if cli.list_pivots {
+        let pivots = wb.pivot_tables()?;
+
+        if pivots.is_empty() {
+            println!("No pivot tables found in workbook");
+        } else {
+            for (i, pivot) in pivots.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{}", pivot.name);
+                for line in pivot.describe() {
+                    println!("{line}");
                 }
             }
         }
         return Ok(());
     }
 
+    if cli.list_macros || cli.dump_macros.is_some() {
+        return print_macros(&mut wb, false, cli.dump_macros.as_deref());
+    }
+
+    if let Some(ref dir) = cli.extract_media {
+        return extract_media(&wb, dir);
+    }
+
+    if cli.check_external {
+        let refs = wb.external_references()?;
+
+        if refs.is_empty() {
+            println!("No external links or data connections found in workbook");
+        } else {
+            println!("Kind\tName\tTarget");
+            println!("----\t----\t------");
+            for r in &refs {
+                println!("{}\t{}\t{}", r.kind, r.name, r.target);
+            }
+        }
+        return Ok(());
+    }
+
     if let Some(ref table_name) = cli.table {
         wb.load_tables()?;
-        let table_data = wb.table_by_name(table_name)?;
+        let mut table_data = wb.table_by_name(table_name)?;
+        if cli.pivot {
+            table_data.pivot(&cli.rows, &cli.values)?;
+        }
+        if let Some(ref column) = cli.count_by {
+            table_data.count_values(column)?;
+        }
+        if !cli.sort.is_empty() {
+            table_data.sort_rows(&cli.sort)?;
+        }
+        if let Some(n) = cli.sample {
+            table_data.sample_rows(n);
+        }
+        if !cli.columns.is_empty() {
+            table_data.select_columns(&cli.columns)?;
+        }
+        if !cli.redact.is_empty() {
+            table_data.redact_columns(&cli.redact, &pipeline.redact_mode)?;
+        }
+        if cli.canonical {
+            table_data.canonicalize();
+        }
+        if !cli.bars.is_empty() {
+            table_data.add_bars(&cli.bars, &locale)?;
+        }
+        if cli.stats {
+            print_stats(&table_data.headers, &table_data.rows);
+            return Ok(());
+        }
+        if let Some(kind) = chart_kind {
+            return print_chart(
+                &cli,
+                kind,
+                &table_data.headers,
+                0,
+                &table_data.rows,
+                &locale,
+            );
+        }
+        if cli.schema {
+            return print_schema(
+                &table_data.name,
+                &table_data.headers,
+                &table_data.rows,
+                export.is_some(),
+            );
+        }
 
         // Handle export formats (non-interactive)
-        if let Some(format) = cli.export.as_deref() {
+        if let Some(format) = export {
+            let total_rows = aggregate::totals(&table_data.rows, table_data.headers.len(), &totals);
+            table_data.rows.extend(total_rows);
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            let (headers, rows) = (&table_data.headers, &table_data.rows);
             match format {
-                "json" => export_table_json(&table_data)?,
-                "csv" => export_table_csv(&table_data)?,
-                "text" => export_table_text(&table_data)?,
-                _ => anyhow::bail!("Unknown export format: {format}. Use: csv, json, or text"),
+                "json" => display::write_json(
+                    &mut out,
+                    &[
+                        ("table", &table_data.name),
+                        ("sheet", &table_data.sheet_name),
+                    ],
+                    headers,
+                    rows,
+                    json_mode,
+                    &export_options,
+                )?,
+                "csv" => display::write_csv(&mut out, headers, rows, &export_options)?,
+                "text" => display::write_text(&mut out, headers, rows, &export_options)?,
+                "markdown" | "md" => {
+                    display::write_markdown(&mut out, headers, rows, &export_options)?
+                }
+                "sqlite" => {
+                    let tables = [sqlite::SqliteTable {
+                        name: &table_data.name,
+                        headers: &table_data.headers,
+                        rows: &table_data.rows,
+                    }];
+                    write_sqlite(
+                        file_output.context("No output path")?,
+                        &tables,
+                        &export_options,
+                    )?;
+                }
+                "xlsx" => {
+                    let sheets = [save::XlsxSheet {
+                        name: &table_data.name,
+                        headers: &table_data.headers,
+                        rows: &table_data.rows,
+                    }];
+                    write_xlsx(file_output.context("No output path")?, &sheets)?;
+                }
+                "ods" => {
+                    let sheets = [ods::OdsSheet {
+                        name: &table_data.name,
+                        headers: &table_data.headers,
+                        rows: &table_data.rows,
+                    }];
+                    write_ods(file_output.context("No output path")?, &sheets)?;
+                }
+                _ => anyhow::bail!(
+                    "Unknown export format: {format}. Use: csv, json, text, markdown, sqlite, xlsx, or ods"
+                ),
             }
+            out.flush()?;
             return Ok(());
         }
 
@@ -129,7 +1087,8 @@ fn main() -> Result<()> {
         }
 
         // Default: display table in terminal
-        display_table_data(&table_data, cli.max_rows)?;
+        let heatmap = workbook::column_selection(&table_data.headers, 0, &cli.heatmap)?;
+        display_table_data(&table_data, cli.max_rows, &heatmap, &totals, &locale)?;
         return Ok(());
     }
 
@@ -139,88 +1098,1437 @@ fn main() -> Result<()> {
         anyhow::bail!("No sheets found in workbook");
     }
 
-    // Determine which sheet to display
-    let sheet_name = if let Some(ref name) = cli.sheet {
-        // Try as name first
-        if sheet_names.iter().any(|s| s == name) {
-            name.clone()
-        } else {
-            // Try as index
-            if let Ok(idx) = name.parse::<usize>() {
-                if idx > 0 && idx <= sheet_names.len() {
-                    sheet_names[idx - 1].clone()
-                } else {
-                    anyhow::bail!("Sheet index {} out of range (1-{})", idx, sheet_names.len());
-                }
-            } else {
-                anyhow::bail!(
-                    "Sheet '{}' not found. Available sheets: {}",
-                    name,
-                    sheet_names.join(", ")
-                );
-            }
-        }
-    } else {
-        sheet_names[0].clone()
+    // Determine which sheet to display: the named one, else the first one Excel would show
+    let sheet_name = match cli.sheet.as_deref() {
+        Some(sheet) => resolve_sheet(&sheet_names, Some(sheet))?,
+        None => first_shown_sheet(&wb, cli.show_hidden),
     };
 
+    if cli.watch && !cli.interactive {
+        return watch_table(file, &sheet_name, &pipeline, &cli);
+    }
+
+    if cli.stream {
+        if cli.hyperlinks != "text" {
+            anyhow::bail!("--stream can't rewrite hyperlinked cells; drop --hyperlinks");
+        }
+        wb.set_header_row(header);
+        wb.set_skip_footer(cli.skip_footer);
+        wb.set_trim(!cli.no_trim);
+        return stream_export(
+            &mut wb,
+            &sheet_name,
+            export.unwrap_or("csv"),
+            &export_options,
+        );
+    }
+
     // Display, export, or run TUI
     if cli.interactive {
         // Interactive TUI mode - pass the workbook so it can switch sheets
-        tui::run_tui(wb, &sheet_name, &config, cli.horizontal_scroll)?;
+        let open = tui_workbook(file, wb, sheet_name, &cli, &config, header)?;
+        tui::run_tui(
+            vec![open],
+            &config,
+            None,
+            cli.save_as.clone(),
+            locale,
+            export_options,
+        )?;
     } else {
-        // Load the sheet data for non-interactive modes
-        let data = wb
-            .load_sheet(&sheet_name)
-            .with_context(|| format!("Failed to load sheet '{sheet_name}'"))?;
-        match cli.export.as_deref() {
-            Some("csv") => {
-                display::export_csv(&data)?;
-            }
-            Some("json") => {
-                display::export_json(&data, &sheet_name)?;
+        // Canonical and file (SQLite, xlsx, ods) exports cover every sheet unless one was requested
+        let targets = if cli.canonical && cli.sheet.is_none() {
+            let mut sorted = pipeline.default_sheets(&wb);
+            sorted.sort();
+            sorted
+        } else if file_output.is_some() && cli.sheet.is_none() {
+            pipeline.default_sheets(&wb)
+        } else {
+            vec![sheet_name]
+        };
+        // File exports write every sheet in one go once they're all loaded
+        let mut file_sheets = Vec::new();
+
+        for (i, sheet_name) in targets.iter().enumerate() {
+            // Load the sheet data for non-interactive modes
+            let (mut data, looks) = pipeline.load_with(&mut wb, sheet_name, export.is_none())?;
+            if cli.stats {
+                println!("Sheet: {sheet_name} ({} rows)\n", data.rows.len());
+                print_stats(&data.headers, &data.rows);
+                continue;
             }
-            Some("text") => {
-                display::export_text(&data)?;
+            if let Some(kind) = chart_kind {
+                println!("Sheet: {sheet_name}\n");
+                print_chart(
+                    &cli,
+                    kind,
+                    &data.headers,
+                    data.first_col,
+                    &data.rows,
+                    &locale,
+                )?;
+                continue;
             }
-            Some(format) => {
-                anyhow::bail!("Unknown export format: {format}. Use: csv, json, or text");
+            if cli.schema {
+                print_schema(sheet_name, &data.headers, &data.rows, export.is_some())?;
+                continue;
             }
-            None => {
+
+            let Some(format) = export else {
                 // Non-interactive display
                 let sheet_names_refs: Vec<&str> = sheet_names.iter().map(|s| s.as_str()).collect();
+                let heatmap =
+                    workbook::column_selection(&data.headers, data.first_col, &cli.heatmap)?;
                 display::display_table(
                     &data,
-                    &sheet_name,
+                    sheet_name,
                     cli.max_rows,
                     &sheet_names_refs,
                     cli.max_width,
                     cli.wrap,
                     cli.formulas,
+                    &heatmap,
+                    &looks,
+                    &totals,
+                    &locale,
                 )?;
+                continue;
+            };
+            if file_output.is_some() {
+                file_sheets.push((sheet_name, data));
+                continue;
+            }
+            let total_rows = aggregate::totals(&data.rows, data.width, &totals);
+            data.rows.extend(total_rows);
+
+            // Several sheets: CSV/text blocks get a "# <sheet>" line, JSON becomes an array
+            let multiple = targets.len() > 1;
+            match (multiple, format) {
+                (true, "json") => println!("{}", if i == 0 { "[" } else { "," }),
+                (true, _) => {
+                    if i > 0 {
+                        println!();
+                    }
+                    println!("# {sheet_name}");
+                }
+                _ => {}
+            }
+            match format {
+                "csv" => display::export_csv(&data, i == 0, &export_options)?,
+                "json" => display::export_json(&data, sheet_name, json_mode, &export_options)?,
+                "text" => display::export_text(&data, &export_options)?,
+                "markdown" | "md" => display::export_markdown(&data, &export_options)?,
+                _ => anyhow::bail!(
+                    "Unknown export format: {format}. Use: csv, json, text, markdown, sqlite, xlsx, or ods"
+                ),
             }
+            if multiple && format == "json" && i == targets.len() - 1 {
+                println!("]");
+            }
+        }
+
+        match (file_output, export) {
+            (Some(path), Some("xlsx")) => {
+                let sheets: Vec<save::XlsxSheet> = file_sheets
+                    .iter()
+                    .map(|(name, data)| save::XlsxSheet {
+                        name,
+                        headers: &data.headers,
+                        rows: &data.rows,
+                    })
+                    .collect();
+                write_xlsx(path, &sheets)?;
+            }
+            (Some(path), Some("ods")) => {
+                let sheets: Vec<ods::OdsSheet> = file_sheets
+                    .iter()
+                    .map(|(name, data)| ods::OdsSheet {
+                        name,
+                        headers: &data.headers,
+                        rows: &data.rows,
+                    })
+                    .collect();
+                write_ods(path, &sheets)?;
+            }
+            (Some(path), _) => {
+                let tables: Vec<sqlite::SqliteTable> = file_sheets
+                    .iter()
+                    .map(|(name, data)| sqlite::SqliteTable {
+                        name,
+                        headers: &data.headers,
+                        rows: &data.rows,
+                    })
+                    .collect();
+                write_sqlite(path, &tables, &export_options)?;
+            }
+            (None, _) => {}
         }
     }
 
     Ok(())
 }
 
-/// Display table data in terminal (default behavior)
-fn display_table_data(table: &workbook::TableData, max_rows: usize) -> Result<()> {
-    println!("\n╔═════════════════════════════════════════════════╗");
-    println!("║  xleak - Excel Table Viewer                     ║");
-    println!("╚═════════════════════════════════════════════════╝");
-    println!();
+/// Range, row, and column options applied to each loaded sheet, in order
+struct SheetPipeline<'a> {
+    window: Option<((u32, u32), (u32, u32))>,
+    sort: &'a [String],
+    /// --rows and --values when pivoting
+    pivot: Option<(&'a [String], &'a [String])>,
+    count_by: Option<&'a str>,
+    bars: &'a [String],
+    sample: Option<usize>,
+    columns: &'a [String],
+    redact: &'a [String],
+    redact_mode: workbook::RedactMode,
+    hyperlinks: HyperlinkMode,
+    show_hidden: bool,
+    evaluate: bool,
+    header: HeaderRow,
+    skip_footer: usize,
+    trim: bool,
+    detect_table: bool,
+    canonical: bool,
+    /// --date-system, for files whose dates are four years and a day off
+    date_system: Option<workbook::DateSystem>,
+    /// Number format of bar scales, and whether a currency symbol is shown at all
+    locale: &'a locale::NumberLocale,
+}
+
+impl SheetPipeline<'_> {
+    fn load(&self, wb: &mut workbook::Workbook, sheet_name: &str) -> Result<workbook::SheetData> {
+        Ok(self.load_with(wb, sheet_name, false)?.0)
+    }
+
+    /// Load a sheet, and with `align` the Excel alignment and currency format of each
+    /// resulting column for the terminal table (.xlsx only; matched by header, as columns
+    /// may be picked or reordered)
+    fn load_with(
+        &self,
+        wb: &mut workbook::Workbook,
+        sheet_name: &str,
+        align: bool,
+    ) -> Result<(workbook::SheetData, Vec<display::ColumnLook>)> {
+        wb.set_evaluate_formulas(self.evaluate);
+        wb.set_header_row(self.header);
+        wb.set_skip_footer(self.skip_footer);
+        wb.set_trim(self.trim);
+        wb.set_date_system(self.date_system);
+        let window = if self.detect_table {
+            let detected = wb.detect_table(sheet_name)?;
+            if let Some((first, last)) = detected {
+                eprintln!(
+                    "Detected table {}{}:{}{} in sheet '{sheet_name}'",
+                    workbook::column_letter(first.1 as usize),
+                    first.0 + 1,
+                    workbook::column_letter(last.1 as usize),
+                    last.0 + 1
+                );
+            }
+            detected
+        } else {
+            self.window
+        };
+        let mut data = match window {
+            Some((first, last)) => wb.load_sheet_window(sheet_name, first, last),
+            None => wb.load_sheet(sheet_name),
+        }
+        .with_context(|| format!("Failed to load sheet '{sheet_name}'"))?;
+        // Links are matched by cell position, so apply them before reordering anything
+        if self.hyperlinks != HyperlinkMode::Text {
+            data.apply_hyperlinks(&wb.hyperlinks(sheet_name)?, self.hyperlinks);
+        }
+        let mut header_looks: HashMap<String, display::ColumnLook> = HashMap::new();
+        if align {
+            let columns = wb
+                .column_alignments(sheet_name, data.first_row as u32)
+                .unwrap_or_default();
+            let currency = if self.locale.currency_symbol.is_empty() {
+                Default::default()
+            } else {
+                wb.currency_columns(sheet_name, data.first_row as u32)
+                    .unwrap_or_default()
+            };
+            for (i, header) in data.headers.iter().enumerate() {
+                let col = (data.first_col + i) as u32;
+                let look = display::ColumnLook {
+                    align: columns.get(&col).copied(),
+                    currency: currency.contains(&col),
+                };
+                if look != display::ColumnLook::default() {
+                    header_looks.insert(header.clone(), look);
+                }
+            }
+        }
+        // Only .xlsx records hidden rows and columns; other formats keep everything
+        let hidden = if self.show_hidden {
+            Default::default()
+        } else {
+            wb.hidden_cells(sheet_name).unwrap_or_default()
+        };
+        let hidden_rows = data.remove_hidden_rows(&hidden);
+        // Sorting, sampling, and picking columns then work on the summary
+        if let Some((group_by, values)) = self.pivot {
+            data.pivot(group_by, values)?;
+        }
+        if let Some(column) = self.count_by {
+            data.count_values(column)?;
+        }
+        if !self.sort.is_empty() {
+            data.sort_rows(self.sort)?;
+        }
+        if let Some(n) = self.sample {
+            data.sample_rows(n);
+        }
+        // Columns picked with --columns or summarized by --pivot or --count-by are shown even
+        // if hidden
+        let mut hidden_cols = 0;
+        if !self.columns.is_empty() {
+            data.select_columns(self.columns)?;
+        } else if self.pivot.is_none() && self.count_by.is_none() {
+            hidden_cols = data.remove_hidden_columns(&hidden);
+        }
+        if hidden_rows + hidden_cols > 0 {
+            eprintln!(
+                "Note: skipped {} in sheet '{sheet_name}' (use --show-hidden to include them)",
+                hidden_summary(&[(hidden_rows, "row"), (hidden_cols, "column")])
+            );
+        }
+        if !self.redact.is_empty() {
+            data.redact_columns(self.redact, &self.redact_mode)?;
+        }
+        if self.canonical {
+            data.canonicalize();
+        }
+        if !self.bars.is_empty() {
+            data.add_bars(self.bars, self.locale)?;
+        }
+        let looks = data
+            .headers
+            .iter()
+            .map(|header| header_looks.get(header).copied().unwrap_or_default())
+            .collect();
+        Ok((data, looks))
+    }
+}
+
+impl SheetPipeline<'_> {
+    /// Sheets to cover when none was named: visible ones, or all with --show-hidden.
+    /// Skipped sheets are noted on stderr.
+    fn default_sheets(&self, wb: &workbook::Workbook) -> Vec<String> {
+        let (shown, hidden): (Vec<_>, Vec<_>) = wb
+            .sheet_visibility()
+            .into_iter()
+            .partition(|(_, visibility)| self.show_hidden || *visibility == "visible");
+        if shown.is_empty() {
+            return wb.sheet_names();
+        }
+        if !hidden.is_empty() {
+            let names: Vec<&str> = hidden.iter().map(|(name, _)| name.as_str()).collect();
+            eprintln!(
+                "Note: skipped {} ({}); use --show-hidden to include them",
+                hidden_summary(&[(hidden.len(), "sheet")]),
+                names.join(", ")
+            );
+        }
+        shown.into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+/// "2 hidden rows and 1 hidden column" (zero counts left out)
+fn hidden_summary(counts: &[(usize, &str)]) -> String {
+    let parts: Vec<String> = counts
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, what)| format!("{n} hidden {what}{}", if *n == 1 { "" } else { "s" }))
+        .collect();
+    parts.join(" and ")
+}
+
+/// Expand FILE arguments containing glob characters; other paths pass through unchanged
+fn expand_file_args(args: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for arg in args {
+        let pattern = arg.to_string_lossy();
+        if arg.exists() || remote::as_url(arg).is_some() || !pattern.contains(['*', '?', '[']) {
+            files.push(arg.clone());
+            continue;
+        }
+        let matches: Vec<PathBuf> = glob::glob(&pattern)
+            .with_context(|| format!("Invalid file pattern '{pattern}'"))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect();
+        if matches.is_empty() {
+            anyhow::bail!("No files match '{pattern}'");
+        }
+        files.extend(matches);
+    }
+    Ok(files)
+}
+
+/// The sheet Excel would show first: the first visible one, or any with --show-hidden
+fn first_shown_sheet(wb: &workbook::Workbook, show_hidden: bool) -> String {
+    wb.sheet_visibility()
+        .into_iter()
+        .find(|(_, visibility)| show_hidden || *visibility == "visible")
+        .map_or_else(|| wb.sheet_names()[0].clone(), |(name, _)| name)
+}
+
+/// Get a workbook ready for the TUI: formula and header options, --watch, and the saved
+/// session, whose sheet it starts on unless --sheet was given
+fn tui_workbook(
+    file: &Path,
+    mut wb: workbook::Workbook,
+    sheet_name: String,
+    cli: &ViewArgs,
+    config: &config::Config,
+    header: HeaderRow,
+) -> Result<tui::OpenWorkbook> {
+    wb.set_evaluate_formulas(cli.evaluate);
+    wb.set_header_row(header);
+    wb.set_skip_footer(cli.skip_footer);
+    wb.set_trim(!cli.no_trim);
+    let watcher = cli
+        .watch
+        .then(|| watch::FileWatcher::new(file))
+        .transpose()?;
+    // Reopen where this file was left last time, unless a sheet was asked for
+    let resume = (config.session.enabled && !cli.no_session && remote::as_url(file).is_none())
+        .then(|| session::Resume::load(file));
+    let sheet = match resume.as_ref().and_then(|resume| resume.session.as_ref()) {
+        Some(session) if cli.sheet.is_none() && wb.sheet_names().contains(&session.sheet) => {
+            session.sheet.clone()
+        }
+        _ => sheet_name,
+    };
+    let name = file.file_name().map_or_else(
+        || file.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    Ok(tui::OpenWorkbook {
+        name,
+        workbook: wb,
+        sheet,
+        watcher,
+        resume,
+    })
+}
+
+/// Browse the newer workbook in the TUI with cells that differ from the older one highlighted
+fn run_diff_tui(
+    old_file: &Path,
+    new_file: &Path,
+    evaluate: bool,
+    config: &config::Config,
+    date_system: Option<workbook::DateSystem>,
+    locale: locale::NumberLocale,
+    export_options: display::ExportOptions,
+) -> Result<()> {
+    let mut old = open_workbook(old_file)?;
+    let mut new = open_workbook(new_file)?;
+    old.set_evaluate_formulas(evaluate);
+    new.set_evaluate_formulas(evaluate);
+    old.set_date_system(date_system);
+    new.set_date_system(date_system);
+    let sheets = diff::diff_workbooks(&mut old, &mut new)?;
+
+    // Start on the first sheet with changes, else the first sheet of the new workbook
+    let start = sheets
+        .iter()
+        .find(|s| s.status == diff::SheetStatus::Compared && !s.changes.is_empty())
+        .map(|s| s.name.clone())
+        .or_else(|| new.sheet_names().into_iter().next())
+        .context("No sheets found in workbook")?;
+    let view = tui::DiffView::new(old_file.display().to_string(), sheets);
+    let open = tui::OpenWorkbook {
+        name: new_file.display().to_string(),
+        workbook: new,
+        sheet: start,
+        watcher: None,
+        resume: None,
+    };
+    tui::run_tui(vec![open], config, Some(view), None, locale, export_options)
+}
+
+/// JSON layout of `--diff --export json`
+#[derive(serde::Serialize)]
+struct DiffReport<'a> {
+    old: String,
+    new: String,
+    sheets: Vec<SheetDiffReport<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct SheetDiffReport<'a> {
+    name: &'a str,
+    /// "compared", or "added"/"removed" for sheets on one side only
+    status: &'static str,
+    changed: usize,
+    added: usize,
+    removed: usize,
+    cells: Vec<CellChangeReport<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct CellChangeReport<'a> {
+    cell: String,
+    change: &'static str,
+    old: &'a str,
+    new: &'a str,
+}
+
+/// Report cells added, removed, and changed between two workbooks, sheet by sheet
+fn run_diff(
+    old_file: &Path,
+    new_file: &Path,
+    evaluate: bool,
+    json: bool,
+    date_system: Option<workbook::DateSystem>,
+) -> Result<()> {
+    let mut old = open_workbook(old_file)?;
+    let mut new = open_workbook(new_file)?;
+    old.set_evaluate_formulas(evaluate);
+    new.set_evaluate_formulas(evaluate);
+    old.set_date_system(date_system);
+    new.set_date_system(date_system);
+    let sheets = diff::diff_workbooks(&mut old, &mut new)?;
+
+    let kinds = [
+        diff::ChangeKind::Changed,
+        diff::ChangeKind::Added,
+        diff::ChangeKind::Removed,
+    ];
+    if json {
+        let report = DiffReport {
+            old: old_file.display().to_string(),
+            new: new_file.display().to_string(),
+            sheets: sheets
+                .iter()
+                .map(|sheet| SheetDiffReport {
+                    name: &sheet.name,
+                    status: match sheet.status {
+                        diff::SheetStatus::Compared => "compared",
+                        diff::SheetStatus::Added => "added",
+                        diff::SheetStatus::Removed => "removed",
+                    },
+                    changed: sheet.count(diff::ChangeKind::Changed),
+                    added: sheet.count(diff::ChangeKind::Added),
+                    removed: sheet.count(diff::ChangeKind::Removed),
+                    cells: sheet
+                        .changes
+                        .iter()
+                        .map(|c| CellChangeReport {
+                            cell: c.address(),
+                            change: c.kind.label(),
+                            old: &c.old,
+                            new: &c.new,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let mut totals = [0; 3];
+    for sheet in &sheets {
+        match sheet.status {
+            diff::SheetStatus::Added => {
+                println!("Sheet '{}': only in {}", sheet.name, new_file.display());
+                continue;
+            }
+            diff::SheetStatus::Removed => {
+                println!("Sheet '{}': only in {}", sheet.name, old_file.display());
+                continue;
+            }
+            diff::SheetStatus::Compared => {}
+        }
+        if sheet.changes.is_empty() {
+            println!("Sheet '{}': no changes", sheet.name);
+            continue;
+        }
+        let counts: Vec<String> = kinds
+            .iter()
+            .map(|&kind| format!("{} {}", sheet.count(kind), kind.label()))
+            .collect();
+        println!("Sheet '{}': {}", sheet.name, counts.join(", "));
+        for change in &sheet.changes {
+            let address = change.address();
+            match change.kind {
+                diff::ChangeKind::Changed => {
+                    println!("  ~ {address}: {} -> {}", change.old, change.new)
+                }
+                diff::ChangeKind::Added => println!("  + {address}: {}", change.new),
+                diff::ChangeKind::Removed => println!("  - {address}: {}", change.old),
+            }
+        }
+        for (total, kind) in totals.iter_mut().zip(kinds) {
+            *total += sheet.count(kind);
+        }
+    }
+
+    let compared = sheets
+        .iter()
+        .filter(|s| s.status == diff::SheetStatus::Compared)
+        .count();
+    println!();
+    println!(
+        "{compared} sheet(s) compared, {} cell(s) differ ({} changed, {} added, {} removed)",
+        totals.iter().sum::<usize>(),
+        totals[0],
+        totals[1],
+        totals[2]
+    );
+    Ok(())
+}
+
+/// Print matching cells of every workbook as `file:sheet!A1: value`, like grep does for lines
+fn run_grep(
+    files: &[PathBuf],
+    matcher: &regex::Regex,
+    sheet: Option<&str>,
+    show_hidden: bool,
+    date_system: Option<workbook::DateSystem>,
+) -> Result<()> {
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    let mut found = 0;
+    let mut failed = 0;
+    for file in files {
+        let result = (|| -> Result<()> {
+            let mut wb = open_workbook(file)?;
+            wb.set_date_system(date_system);
+            let sheets = match sheet {
+                Some(sheet) => vec![resolve_sheet(&wb.sheet_names(), Some(sheet))?],
+                None => wb
+                    .sheet_visibility()
+                    .into_iter()
+                    .filter(|(_, visibility)| show_hidden || *visibility == "visible")
+                    .map(|(name, _)| name)
+                    .collect(),
+            };
+            for name in sheets {
+                for (row, col, text) in wb.sheet_cells(&name)? {
+                    if matcher.is_match(&text) {
+                        found += 1;
+                        writeln!(
+                            out,
+                            "{}:{}: {}",
+                            file.display(),
+                            workbook::cell_reference(&name, row, col),
+                            text.replace('\n', " ")
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        })();
+        if let Err(e) = result {
+            eprintln!("{}: {e:#}", file.display());
+            failed += 1;
+        }
+    }
+    out.flush()?;
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} workbook(s) couldn't be searched",
+            files.len()
+        );
+    }
+    if found == 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Export sheets of several workbooks into `dir`, one file per sheet.
+/// A file that fails is reported and skipped; the run fails at the end if any did.
+fn run_batch(
+    files: &[PathBuf],
+    dir: &Path,
+    format: &str,
+    sheet: Option<&str>,
+    pipeline: &SheetPipeline,
+    json_mode: display::JsonMode,
+    options: &display::ExportOptions,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create output directory {}", dir.display()))?;
+
+    let mut written = 0;
+    let mut failed = 0;
+    let mut taken = HashSet::new();
+    for file in files {
+        match convert_workbook(
+            file, dir, format, sheet, pipeline, json_mode, options, &mut taken,
+        ) {
+            Ok(count) => {
+                eprintln!("{}: {count} sheet(s)", file.display());
+                written += count;
+            }
+            Err(e) => {
+                eprintln!("{}: {e:#}", file.display());
+                failed += 1;
+            }
+        }
+    }
+
+    eprintln!("Wrote {written} file(s) to {}", dir.display());
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} workbook(s) failed to convert", files.len());
+    }
+    Ok(())
+}
+
+/// Write one workbook's sheets as `{file}_{sheet}.{format}`; returns the number written
+#[allow(clippy::too_many_arguments)]
+fn convert_workbook(
+    file: &Path,
+    dir: &Path,
+    format: &str,
+    sheet: Option<&str>,
+    pipeline: &SheetPipeline,
+    json_mode: display::JsonMode,
+    options: &display::ExportOptions,
+    taken: &mut HashSet<String>,
+) -> Result<usize> {
+    let mut wb = open_workbook(file)?;
+    let sheet_names = wb.sheet_names();
+    let targets = match sheet {
+        Some(sheet) => vec![resolve_sheet(&sheet_names, Some(sheet))?],
+        None => pipeline.default_sheets(&wb),
+    };
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    for sheet_name in &targets {
+        let data = pipeline.load(&mut wb, sheet_name)?;
+        let name = format!("{}_{}", safe_file_name(&stem), safe_file_name(sheet_name));
+        let path = unique_output(dir, &name, format, taken);
+        let output = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(output);
+        match format {
+            "json" => display::write_json(
+                &mut out,
+                &[("sheet", sheet_name)],
+                &data.headers,
+                &data.rows,
+                json_mode,
+                options,
+            )?,
+            _ => display::write_csv(&mut out, &data.headers, &data.rows, options)?,
+        }
+        out.flush()
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(targets.len())
+}
+
+/// `dir/{name}.{format}`, numbered `{name}_2`, `{name}_3`, ... when the run already wrote
+/// that name (same-named files from different directories, or sheet names that only differ
+/// in replaced characters). Names are compared ignoring case, as some file systems do.
+fn unique_output(dir: &Path, name: &str, format: &str, taken: &mut HashSet<String>) -> PathBuf {
+    let mut file_name = format!("{name}.{format}");
+    let mut n = 1;
+    while !taken.insert(file_name.to_lowercase()) {
+        n += 1;
+        file_name = format!("{name}_{n}.{format}");
+    }
+    dir.join(file_name)
+}
+
+/// Replace characters that aren't allowed in file names on common platforms
+fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Validate the path and open the workbook
+fn open_workbook(file: &Path) -> Result<workbook::Workbook> {
+    if let Some(url) = remote::as_url(file) {
+        return remote::open(url).context("Failed to open Excel file");
+    }
+    if !file.exists() {
+        anyhow::bail!("File not found: {}", file.display());
+    }
+    workbook::Workbook::open(file).context("Failed to open Excel file")
+}
+
+/// Print a summary block per column
+fn print_stats(headers: &[String], rows: &[Vec<workbook::CellValue>]) {
+    for (i, column) in stats::column_stats(headers, rows).iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for line in column.describe() {
+            println!("{line}");
+        }
+    }
+}
+
+/// Print the `--chart` of the `--y` column against the `--x` column, as wide as the terminal
+fn print_chart(
+    cli: &ViewArgs,
+    kind: chart::ChartKind,
+    headers: &[String],
+    first_col: usize,
+    rows: &[Vec<workbook::CellValue>],
+    locale: &locale::NumberLocale,
+) -> Result<()> {
+    let y = workbook::find_column(
+        headers,
+        first_col,
+        cli.chart_y.as_deref().unwrap_or_default(),
+    )?;
+    let x = cli
+        .chart_x
+        .as_deref()
+        .map(|x| workbook::find_column(headers, first_col, x))
+        .transpose()?;
+    let series = chart::Series::new(headers, rows, x, y, locale);
+    if series.points.is_empty() {
+        anyhow::bail!("Column '{}' has no numbers to chart", series.y_name);
+    }
+    let width = crossterm::terminal::size()
+        .map_or(80, |(cols, _)| cols)
+        .min(160);
+    println!("{}\n", series.title());
+    for line in chart::render_text(kind, &series, width, CHART_HEIGHT) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Lines in a `--chart line` plot
+const CHART_HEIGHT: u16 = 20;
+
+/// JSON layout of `--schema --export json`
+#[derive(serde::Serialize)]
+struct SchemaReport<'a> {
+    name: &'a str,
+    rows: usize,
+    columns: Vec<schema::ColumnSchema>,
+}
+
+/// Print the inferred type and nullability of each column, as aligned text or JSON
+fn print_schema(
+    name: &str,
+    headers: &[String],
+    rows: &[Vec<workbook::CellValue>],
+    json: bool,
+) -> Result<()> {
+    let columns = schema::infer_schema(headers, rows);
+    if json {
+        let report = SchemaReport {
+            name,
+            rows: rows.len(),
+            columns,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!("{name} ({} rows)\n", rows.len());
+    for line in schema::describe(&columns) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// One sheet in `--list-sheets --export json`
+#[derive(serde::Serialize)]
+struct SheetListing {
+    index: usize,
+    name: String,
+    range: Option<String>,
+    rows: u32,
+    columns: u32,
+    visibility: &'static str,
+    tables: Vec<String>,
+}
+
+/// One table in `--list-tables --export json`
+#[derive(serde::Serialize)]
+struct TableListing {
+    name: String,
+    sheet: String,
+    range: Option<String>,
+    rows: usize,
+    columns: usize,
+}
+
+/// Rows and columns covered by a range like "A1:D50"
+fn range_size(range: Option<&str>) -> (u32, u32) {
+    range
+        .and_then(ooxml::parse_range_ref)
+        .map(|(first, last)| (last.0 - first.0 + 1, last.1 - first.1 + 1))
+        .unwrap_or((0, 0))
+}
+
+/// Print each sheet's used range, size, visibility, and tables, tab-separated or as JSON
+fn list_sheets(wb: &mut workbook::Workbook, json: bool) -> Result<()> {
+    let dimensions = wb.sheet_dimensions()?;
+    // Tables exist only in .xlsx; other formats list none
+    let has_tables = wb.load_tables().is_ok();
+    let mut sheets = Vec::new();
+    for (index, (name, visibility)) in wb.sheet_visibility().into_iter().enumerate() {
+        let range = dimensions
+            .iter()
+            .find(|(n, _)| *n == name)
+            .and_then(|(_, range)| range.clone());
+        let (rows, columns) = range_size(range.as_deref());
+        let tables = if has_tables {
+            wb.table_names_in_sheet(&name)?
+        } else {
+            Vec::new()
+        };
+        sheets.push(SheetListing {
+            index,
+            name,
+            range,
+            rows,
+            columns,
+            visibility,
+            tables,
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&sheets)?);
+        return Ok(());
+    }
+    println!("Index\tSheet\tRange\tRows\tColumns\tVisibility\tTables");
+    for sheet in &sheets {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            sheet.index,
+            sheet.name,
+            sheet.range.as_deref().unwrap_or("-"),
+            sheet.rows,
+            sheet.columns,
+            sheet.visibility,
+            sheet.tables.join(",")
+        );
+    }
+    Ok(())
+}
+
+/// Print the formulas an audit flags, one finding per line (tab-separated) or as JSON
+fn print_formula_audit(wb: &mut workbook::Workbook, json: bool) -> Result<()> {
+    let audit = audit::audit_formulas(wb)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&audit)?);
+        return Ok(());
+    }
+    if audit.formulas == 0 {
+        println!("No formulas found in workbook");
+        return Ok(());
+    }
+    if audit.entries.is_empty() {
+        println!("No findings in {} formulas", audit.formulas);
+    } else {
+        println!("Finding\tCell\tDetail\tFormula");
+        for entry in &audit.entries {
+            println!(
+                "{}\t{}\t{}\t{}",
+                entry.finding.name(),
+                entry.cell,
+                entry.detail,
+                entry.formula
+            );
+        }
+    }
+    if audit.unread > 0 {
+        eprintln!(
+            "{} of {} formulas couldn't be read (structured references or array constants); only their results were checked for errors",
+            audit.unread, audit.formulas
+        );
+    }
+    Ok(())
+}
+
+/// Print every external link with where it's used, tab-separated or as JSON (.xlsx only)
+fn print_links(wb: &mut workbook::Workbook, json: bool) -> Result<()> {
+    let links = links::list_links(wb)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&links)?);
+    } else if links.is_empty() {
+        println!("No external links found in workbook");
+    } else {
+        println!("Kind\tLocation\tTarget\tDetail");
+        for link in &links {
+            println!(
+                "{}\t{}\t{}\t{}",
+                link.kind, link.location, link.target, link.detail
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print each Excel table's sheet, range, and size, tab-separated or as JSON (.xlsx only)
+fn list_tables(wb: &mut workbook::Workbook, json: bool) -> Result<()> {
+    wb.load_tables()?;
+    let mut tables = Vec::new();
+    for name in wb.table_names()? {
+        let table = wb.table_by_name(&name)?;
+        let columns = table.headers.len();
+        // The header row sits just above the data
+        let range = table.data_start.map(|(row, col)| {
+            let first_row = row.saturating_sub(1);
+            let last_row = row as usize + table.rows.len().saturating_sub(1);
+            let last_col = col as usize + columns.saturating_sub(1);
+            format!(
+                "{}{}:{}{}",
+                workbook::column_letter(col as usize),
+                first_row + 1,
+                workbook::column_letter(last_col),
+                last_row + 1
+            )
+        });
+        tables.push(TableListing {
+            name,
+            sheet: table.sheet_name,
+            range,
+            rows: table.rows.len(),
+            columns,
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tables)?);
+        return Ok(());
+    }
+    if tables.is_empty() {
+        println!("No tables found in workbook");
+        return Ok(());
+    }
+    println!("Sheet\tTable\tRange\tRows\tColumns");
+    for table in &tables {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            table.sheet,
+            table.name,
+            table.range.as_deref().unwrap_or("-"),
+            table.rows,
+            table.columns
+        );
+    }
+    Ok(())
+}
+
+/// Write tables to a SQLite database and report what was written
+fn write_sqlite(
+    path: &Path,
+    tables: &[sqlite::SqliteTable],
+    options: &display::ExportOptions,
+) -> Result<()> {
+    sqlite::write_tables(path, tables, options)?;
+    let rows: usize = tables.iter().map(|t| t.rows.len()).sum();
+    eprintln!(
+        "Wrote {} table(s), {rows} rows to {}",
+        tables.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Write sheets to a new .xlsx file and report what was written
+fn write_xlsx(path: &Path, sheets: &[save::XlsxSheet]) -> Result<()> {
+    save::write_sheets(path, sheets)?;
+    let rows: usize = sheets.iter().map(|s| s.rows.len()).sum();
+    eprintln!(
+        "Wrote {} sheet(s), {rows} rows to {}",
+        sheets.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Write sheets to a new .ods file and report what was written
+fn write_ods(path: &Path, sheets: &[ods::OdsSheet]) -> Result<()> {
+    ods::write_sheets(path, sheets)?;
+    let rows: usize = sheets.iter().map(|s| s.rows.len()).sum();
+    eprintln!(
+        "Wrote {} sheet(s), {rows} rows to {}",
+        sheets.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Resolve a --sheet argument (name or 1-based index), defaulting to the first sheet
+fn resolve_sheet(sheet_names: &[String], sheet: Option<&str>) -> Result<String> {
+    let Some(name) = sheet else {
+        return Ok(sheet_names[0].clone());
+    };
+    // Try as name first
+    if sheet_names.iter().any(|s| s == name) {
+        return Ok(name.to_string());
+    }
+    // Try as index
+    if let Ok(idx) = name.parse::<usize>() {
+        if idx > 0 && idx <= sheet_names.len() {
+            Ok(sheet_names[idx - 1].clone())
+        } else {
+            anyhow::bail!("Sheet index {} out of range (1-{})", idx, sheet_names.len());
+        }
+    } else {
+        anyhow::bail!(
+            "Sheet '{}' not found. Available sheets: {}",
+            name,
+            sheet_names.join(", ")
+        );
+    }
+}
+
+/// Run a subcommand
+fn run_command(command: &Command, config: &config::Config) -> Result<()> {
+    match command {
+        Command::Assert {
+            file,
+            matches,
+            sheet,
+            tolerance,
+        } => run_assert(file, matches, sheet.as_deref(), *tolerance),
+        Command::Images { file, extract } => run_images(file, extract.as_deref()),
+        Command::Macros { file, source } => run_macros(file, *source),
+        Command::Meta { file } => run_meta(file),
+        Command::Scan { file, rules } => run_scan(file, rules, config),
+        Command::Diff(_)
+        | Command::Export(_)
+        | Command::Grep { .. }
+        | Command::Stats(_)
+        | Command::Tables(_)
+        | Command::View(_) => unreachable!("run as `xleak FILE` with an option preset"),
+    }
+}
+
+/// Compare a sheet with a CSV baseline, failing with a cell-level report on mismatch
+fn run_assert(file: &Path, baseline: &Path, sheet: Option<&str>, tolerance: f64) -> Result<()> {
+    let mut wb = open_workbook(file)?;
+    let sheet_names = wb.sheet_names();
+    if sheet_names.is_empty() {
+        anyhow::bail!("No sheets found in workbook");
+    }
+    let sheet_name = resolve_sheet(&sheet_names, sheet)?;
+
+    let text = std::fs::read_to_string(baseline)
+        .with_context(|| format!("Failed to read baseline '{}'", baseline.display()))?;
+    let expected = baseline::parse_csv(&text);
+
+    let lazy = wb.load_sheet_lazy(&sheet_name)?;
+    let (origin_row, origin_col) = lazy.origin();
+    let data = lazy.to_sheet_data();
+    let mut actual = vec![data.headers.clone()];
+    actual.extend(
+        data.rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_raw_string()).collect()),
+    );
+
+    let mismatches = baseline::compare(&actual, &expected, tolerance);
+    if mismatches.is_empty() {
+        println!(
+            "Sheet '{sheet_name}' matches {} ({} rows × {} columns)",
+            baseline.display(),
+            data.height,
+            data.width
+        );
+        return Ok(());
+    }
+
+    println!("Cell\tExpected\tActual");
+    println!("----\t--------\t------");
+    for m in &mismatches {
+        println!(
+            "{}{}\t{}\t{}",
+            workbook::column_letter(origin_col + m.col),
+            origin_row + m.row,
+            m.expected,
+            m.actual
+        );
+    }
+    println!();
+    anyhow::bail!(
+        "Sheet '{sheet_name}' differs from {} in {} cell(s)",
+        baseline.display(),
+        mismatches.len()
+    );
+}
+
+/// List embedded pictures, optionally writing them to a directory
+fn run_images(file: &Path, extract: Option<&Path>) -> Result<()> {
+    let wb = open_workbook(file)?;
+    let images = wb.images()?;
+
+    if images.is_empty() {
+        println!("No embedded images found in workbook");
+        return Ok(());
+    }
+
+    println!("Sheet\tAnchor\tFormat\tSize\tDimensions\tName");
+    println!("-----\t------\t------\t----\t----------\t----");
+    for image in &images {
+        let dimensions = image
+            .dimensions()
+            .map(|(w, h)| format!("{w}x{h}"))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            image.sheet_name,
+            image.anchor,
+            image.format(),
+            display::format_bytes(image.data.len()),
+            dimensions,
+            image.name
+        );
+    }
+
+    if let Some(dir) = extract {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        for image in &images {
+            let path = dir.join(image.output_name());
+            std::fs::write(&path, &image.data)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        println!();
+        println!("Extracted {} image(s) to {}", images.len(), dir.display());
+    }
+
+    Ok(())
+}
+
+/// Write every picture, chart, and embedded object to a directory, listing where each sits
+fn extract_media(wb: &workbook::Workbook, dir: &Path) -> Result<()> {
+    let media = wb.media()?;
+    if media.is_empty() {
+        println!("No embedded pictures, charts, or objects found in workbook");
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    println!("Kind\tSheet\tAnchor\tSize\tName\tFile");
+    println!("----\t-----\t------\t----\t----\t----");
+    for item in &media {
+        let file = item.output_name();
+        let path = dir.join(&file);
+        std::fs::write(&path, &item.data)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            item.kind.name(),
+            item.sheet_name,
+            item.anchor,
+            display::format_bytes(item.data.len()),
+            item.name,
+            file
+        );
+    }
+    println!();
+    println!("Extracted {} item(s) to {}", media.len(), dir.display());
+    Ok(())
+}
+
+/// List VBA modules, optionally printing their source
+fn run_macros(file: &Path, source: bool) -> Result<()> {
+    let mut wb = open_workbook(file)?;
+    print_macros(&mut wb, source, None)
+}
+
+/// List VBA modules, printing their source or writing it into `dump` as .bas/.cls files
+fn print_macros(wb: &mut workbook::Workbook, source: bool, dump: Option<&Path>) -> Result<()> {
+    let modules = wb.vba_modules()?;
+
+    if modules.is_empty() {
+        println!("No VBA macros found in workbook");
+        return Ok(());
+    }
+
+    println!("Module\tLines\tAuto-run");
+    println!("------\t-----\t--------");
+    for module in &modules {
+        let auto_exec = module.auto_exec_procedures();
+        println!(
+            "{}\t{}\t{}",
+            module.name,
+            module.line_count(),
+            if auto_exec.is_empty() {
+                "-".to_string()
+            } else {
+                auto_exec.join(", ")
+            }
+        );
+    }
+
+    if source {
+        for module in &modules {
+            println!();
+            println!("' ===== {} =====", module.name);
+            println!("{}", module.source.trim_end());
+        }
+    }
+
+    if let Some(dir) = dump {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        for module in &modules {
+            let path = dir.join(module.file_name());
+            std::fs::write(&path, &module.source)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        println!();
+        println!("Wrote {} module(s) to {}", modules.len(), dir.display());
+    }
+
+    Ok(())
+}
+
+/// Print workbook-level details and a per-sheet listing
+fn run_meta(file: &Path) -> Result<()> {
+    let mut wb = open_workbook(file)?;
+    print_workbook_info(file, &mut wb)
+}
+
+/// Workbook overview shared by `xleak meta` and `--info`
+fn print_workbook_info(file: &Path, wb: &mut workbook::Workbook) -> Result<()> {
+    let workbook_protection = wb.workbook_protection();
+    let is_xlsx = workbook_protection.is_ok();
+
+    println!("File: {}", file.display());
+    let size = std::fs::metadata(wb.path())
+        .map(|m| display::format_bytes(m.len() as usize))
+        .unwrap_or_else(|_| "?".to_string());
+    println!("Format: {} ({size})", wb.format());
+    if let Ok(properties) = wb.document_properties() {
+        for (label, value) in properties.fields() {
+            println!("{label}: {value}");
+        }
+    }
+    let protection = match workbook_protection {
+        Ok(Some(protection)) => protection.summary(),
+        Ok(None) => "not protected".to_string(),
+        Err(_) => "n/a (.xlsx only)".to_string(),
+    };
+    println!("Workbook protection: {protection}");
+
+    let names = wb.defined_names();
+    println!("Defined names: {}", names.len());
+    for (name, formula) in &names {
+        println!("  {name} = {formula}");
+    }
+    let tables = if is_xlsx {
+        wb.load_tables()?;
+        wb.table_names()?.len().to_string()
+    } else {
+        "n/a (.xlsx only)".to_string()
+    };
+    println!("Tables: {tables}");
+    println!();
+
+    let dimensions = wb.sheet_dimensions()?;
+    println!("Sheet\tDimensions\tVisibility\tProtection");
+    println!("-----\t----------\t----------\t----------");
+    for (name, visibility) in wb.sheet_visibility() {
+        let dimension = match dimensions.iter().find(|(n, _)| *n == name) {
+            Some((_, Some(range))) => match ooxml::parse_range_ref(range) {
+                Some((first, last)) => format!(
+                    "{range} ({} rows × {} columns)",
+                    last.0 - first.0 + 1,
+                    last.1 - first.1 + 1
+                ),
+                None => range.clone(),
+            },
+            _ => "empty".to_string(),
+        };
+        let protection = if is_xlsx {
+            wb.sheet_protection(&name)
+                .map(|p| p.summary())
+                .unwrap_or_else(|_| "-".to_string())
+        } else {
+            "-".to_string()
+        };
+        println!("{name}\t{dimension}\t{visibility}\t{protection}");
+    }
+
+    Ok(())
+}
+
+/// Run regex rules over every sheet and report hits by cell
+fn run_scan(file: &Path, rules: &str, config: &config::Config) -> Result<()> {
+    let rules = scan::select_rules(rules, &config.scan.rules)?;
+    let mut wb = open_workbook(file)?;
+
+    let mut hits = Vec::new();
+    for sheet in wb.sheet_names() {
+        let cells = wb.sheet_cells(&sheet)?;
+        hits.extend(scan::scan_cells(&sheet, &cells, &rules));
+    }
+
+    if hits.is_empty() {
+        println!("No sensitive data found ({} rules)", rules.len());
+        return Ok(());
+    }
+
+    println!("Sheet\tCell\tRule\tMatch");
+    println!("-----\t----\t----\t-----");
+    for hit in &hits {
+        println!(
+            "{}\t{}{}\t{}\t{}",
+            hit.sheet,
+            workbook::column_letter(hit.col),
+            hit.row + 1,
+            hit.rule,
+            hit.matched
+        );
+    }
+    println!();
+    println!("{} match(es) found", hits.len());
+
+    Ok(())
+}
+
+/// The aggregations named by `--totals` (none without the flag)
+fn totals_aggregations(names: &[String]) -> Result<Vec<Aggregation>> {
+    names
+        .iter()
+        .map(|name| {
+            Aggregation::parse(name).with_context(|| {
+                format!("Unknown aggregate for --totals: {name}. Use: sum, avg, count, min, or max")
+            })
+        })
+        .collect()
+}
+
+/// Display table data in terminal (default behavior)
+fn display_table_data(
+    table: &workbook::TableData,
+    max_rows: usize,
+    heatmap: &[usize],
+    totals: &[Aggregation],
+    locale: &locale::NumberLocale,
+) -> Result<()> {
+    display::print_banner("xleak - Excel Table Viewer");
+    println!();
     println!("Table: {} (from sheet: {})", table.name, table.sheet_name);
     println!(
-        "{} rows × {} columns",
-        table.rows.len(),
-        table.headers.len()
+        "{}",
+        display::dimensions(table.rows.len(), table.headers.len())
     );
     println!();
 
     let max_width = 30u16;
-    let mut table_obj = Table::new();
+    let mut table_obj = display::new_table();
     table_obj.set_content_arrangement(ContentArrangement::Dynamic);
     table_obj.set_width(
         (table.headers.len() as u16)
@@ -230,7 +2538,10 @@ fn display_table_data(table: &workbook::TableData, max_rows: usize) -> Result<()
 
     let mut header_row = Row::new();
     for h in &table.headers {
-        header_row.add_cell(Cell::new(h).add_attribute(Attribute::Bold).fg(Color::Green));
+        header_row.add_cell(display::paint(
+            Cell::new(h).add_attribute(Attribute::Bold),
+            Color::Green,
+        ));
     }
     table_obj.set_header(header_row);
     table_obj.set_constraints(
@@ -242,41 +2553,56 @@ fn display_table_data(table: &workbook::TableData, max_rows: usize) -> Result<()
     } else {
         std::cmp::min(max_rows, table.rows.len())
     };
+    let heat_ranges = display::heat_ranges(&table.rows, heatmap);
 
     for row in table.rows.iter().take(rows_to_show) {
         let mut table_row = Row::new();
-        for cell in row {
-            let cell_obj = match cell {
-                workbook::CellValue::Int(_) | workbook::CellValue::Float(_) => {
+        for (col_idx, cell) in row.iter().enumerate() {
+            let mut cell_obj = match cell {
+                workbook::CellValue::Int(_)
+                | workbook::CellValue::Float(_)
+                | workbook::CellValue::Time(_)
+                | workbook::CellValue::Duration(_) => {
                     Cell::new(cell.to_string()).set_alignment(CellAlignment::Right)
                 }
                 workbook::CellValue::Bool(_) => {
                     Cell::new(cell.to_string()).set_alignment(CellAlignment::Center)
                 }
-                workbook::CellValue::Error(_) => Cell::new(cell.to_string())
-                    .set_alignment(CellAlignment::Center)
-                    .fg(Color::Red),
+                workbook::CellValue::Error(_) => display::paint(
+                    Cell::new(cell.to_string()).set_alignment(CellAlignment::Center),
+                    Color::Red,
+                ),
                 _ => Cell::new(cell.to_string()).set_alignment(CellAlignment::Left),
             };
+            if let Some(&range) = heat_ranges.get(&col_idx) {
+                cell_obj = display::heat_cell(cell_obj, cell, range);
+            }
             table_row.add_cell(cell_obj);
         }
         table_obj.add_row(table_row);
     }
+    display::add_totals(
+        &mut table_obj,
+        &table.rows,
+        table.headers.len(),
+        totals,
+        locale,
+    );
 
     println!("{}", table_obj);
 
     println!();
     if rows_to_show < table.rows.len() {
         println!(
-            "⚠️  Showing {} of {} rows (use -n 0 to show all)",
+            "{} Showing {} of {} rows (use -n 0 to show all)",
+            display::warning_prefix(),
             rows_to_show,
             table.rows.len()
         );
     } else {
         println!(
-            "Total: {} rows × {} columns",
-            table.rows.len(),
-            table.headers.len()
+            "Total: {}",
+            display::dimensions(table.rows.len(), table.headers.len())
         );
     }
 
@@ -284,82 +2610,71 @@ fn display_table_data(table: &workbook::TableData, max_rows: usize) -> Result<()
     Ok(())
 }
 
-/// Export table data as JSON
-fn export_table_json(table: &workbook::TableData) -> Result<()> {
-    println!("{{");
-    println!("  \"table\": \"{}\",", table.name);
-    println!("  \"sheet\": \"{}\",", table.sheet_name);
-    println!("  \"columns\": {},", table.headers.len());
-    println!("  \"rows\": {},", table.rows.len());
-    println!("  \"headers\": [");
-    for (i, header) in table.headers.iter().enumerate() {
-        let comma = if i < table.headers.len() - 1 { "," } else { "" };
-        println!("    \"{header}\"{comma}");
-    }
-    println!("  ],");
-    println!("  \"data\": [");
-
-    for (i, row) in table.rows.iter().enumerate() {
-        print!("    [");
-        for (j, cell) in row.iter().enumerate() {
-            let value = match cell {
-                workbook::CellValue::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
-                workbook::CellValue::Int(i) => i.to_string(),
-                workbook::CellValue::Float(f) => f.to_string(),
-                workbook::CellValue::Bool(b) => b.to_string(),
-                workbook::CellValue::Empty => "null".to_string(),
-                _ => format!("\"{cell}\""),
-            };
-            print!("{value}");
-            if j < row.len() - 1 {
-                print!(", ");
-            }
+/// Redraw a sheet's table every time the file changes (--watch without -i)
+fn watch_table(
+    file: &Path,
+    sheet_name: &str,
+    pipeline: &SheetPipeline,
+    cli: &ViewArgs,
+) -> Result<()> {
+    let mut watcher = watch::FileWatcher::new(file)?;
+    let clear = std::io::stdout().is_terminal();
+    let totals = totals_aggregations(&cli.totals)?;
+    loop {
+        if clear {
+            print!("\x1b[2J\x1b[H");
+        }
+        let shown = open_workbook(file).and_then(|mut wb| {
+            let (data, looks) = pipeline.load_with(&mut wb, sheet_name, true)?;
+            let sheet_names = wb.sheet_names();
+            let sheet_names_refs: Vec<&str> = sheet_names.iter().map(|s| s.as_str()).collect();
+            let heatmap = workbook::column_selection(&data.headers, data.first_col, &cli.heatmap)?;
+            display::display_table(
+                &data,
+                sheet_name,
+                cli.max_rows,
+                &sheet_names_refs,
+                cli.max_width,
+                cli.wrap,
+                cli.formulas,
+                &heatmap,
+                &looks,
+                &totals,
+                pipeline.locale,
+            )
+        });
+        // Often a file caught mid-write; the next change tries again
+        if let Err(e) = shown {
+            eprintln!("Error: {e:#}");
         }
-        let comma = if i < table.rows.len() - 1 { "," } else { "" };
-        println!("]{comma}");
+        eprintln!(
+            "Updated {} - watching {} for changes (Ctrl+C to stop)",
+            chrono::Local::now().format("%H:%M:%S"),
+            file.display()
+        );
+        watcher.wait()?;
     }
-
-    println!("  ]");
-    println!("}}");
-
-    Ok(())
 }
 
-/// Export table data as CSV
-fn export_table_csv(table: &workbook::TableData) -> Result<()> {
-    // Print headers
-    println!("{}", table.headers.join(","));
-
-    // Print rows
-    for row in &table.rows {
-        let row_str: Vec<String> = row
-            .iter()
-            .map(|cell| {
-                let val = cell.to_raw_string();
-                // Quote if contains comma or quotes
-                if val.contains(',') || val.contains('"') {
-                    format!("\"{}\"", val.replace('"', "\"\""))
-                } else {
-                    val
-                }
-            })
-            .collect();
-        println!("{}", row_str.join(","));
+/// Write a sheet as CSV or tab-separated text to stdout one row at a time (--stream)
+fn stream_export(
+    wb: &mut workbook::Workbook,
+    sheet_name: &str,
+    format: &str,
+    options: &display::ExportOptions,
+) -> Result<()> {
+    if !matches!(format, "csv" | "text") {
+        anyhow::bail!("--stream writes csv or text, not {format}");
     }
-
-    Ok(())
-}
-
-/// Export table data as plain text (tab-separated)
-fn export_table_text(table: &workbook::TableData) -> Result<()> {
-    // Print headers
-    println!("{}", table.headers.join("\t"));
-
-    // Print rows
-    for row in &table.rows {
-        let row_str: Vec<String> = row.iter().map(|cell| cell.to_raw_string()).collect();
-        println!("{}", row_str.join("\t"));
+    let rows = wb
+        .rows_iter(sheet_name)
+        .with_context(|| format!("Failed to read sheet '{sheet_name}'"))?;
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    if format == "csv" {
+        display::stream_csv(&mut out, rows, options)?;
+    } else {
+        display::stream_text(&mut out, rows, options)?;
     }
-
+    out.flush()?;
     Ok(())
 }
@@ -0,0 +1,347 @@
+//! Pictures, charts, and OLE objects embedded in worksheets
+
+use crate::ooxml::{Package, XmlElement};
+use crate::workbook::column_letter;
+use anyhow::Result;
+
+/// What an embedded part holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Picture,
+    /// A chart's DrawingML definition (`xl/charts/chart1.xml`)
+    Chart,
+    /// An embedded OLE object: another Office file, or an OLE container (`.bin`)
+    Object,
+}
+
+impl MediaKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaKind::Picture => "picture",
+            MediaKind::Chart => "chart",
+            MediaKind::Object => "object",
+        }
+    }
+}
+
+/// A picture, chart, or OLE object anchored on a worksheet
+#[derive(Debug, Clone)]
+pub struct EmbeddedMedia {
+    pub kind: MediaKind,
+    pub sheet_name: String,
+    /// Top-left cell the item is anchored to (e.g. "B4"), or "absolute"
+    pub anchor: String,
+    /// Shape name from the drawing (e.g. "Picture 1"), or the program of an OLE object
+    /// (e.g. "Word.Document.12")
+    pub name: String,
+    /// Part name inside the package (e.g. "xl/media/image1.png")
+    pub part: String,
+    pub data: Vec<u8>,
+}
+
+impl EmbeddedMedia {
+    /// Lowercase file extension of the media part (e.g. "png")
+    pub fn format(&self) -> String {
+        self.part
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_else(|| "bin".to_string())
+    }
+
+    /// File name of the media part (e.g. "image1.png")
+    pub fn file_name(&self) -> &str {
+        self.part.rsplit('/').next().unwrap_or(&self.part)
+    }
+
+    /// Pixel dimensions for PNG, GIF and JPEG data
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        image_dimensions(&self.data)
+    }
+
+    /// File name to extract to: `<sheet>_<anchor>_<part file name>`, so items reusing
+    /// one part stay distinct
+    pub fn output_name(&self) -> String {
+        let sheet: String = self
+            .sheet_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{sheet}_{}_{}", self.anchor, self.file_name())
+    }
+}
+
+/// Reads every picture referenced from worksheet drawings, in sheet order
+pub fn load_images(package: &mut Package) -> Result<Vec<EmbeddedMedia>> {
+    let mut media = load_media(package)?;
+    media.retain(|item| item.kind == MediaKind::Picture);
+    Ok(media)
+}
+
+/// Reads every picture and chart in worksheet drawings and every embedded OLE object,
+/// sheet by sheet in the order the sheet lists them
+pub fn load_media(package: &mut Package) -> Result<Vec<EmbeddedMedia>> {
+    let mut media = Vec::new();
+
+    for (sheet_name, sheet_part) in package.worksheet_parts()? {
+        let sheet_rels = package.relationships(&sheet_part)?;
+        for rel in &sheet_rels {
+            if rel.kind != "drawing" || rel.external {
+                continue;
+            }
+            let drawing = package.read_xml(&rel.target)?;
+            let drawing_rels = package.relationships(&rel.target)?;
+
+            for anchor in &drawing.children {
+                let cell = anchor_cell(anchor);
+                let mut items = Vec::new();
+                collect_pictures(anchor, &mut items);
+
+                for (kind, name, id) in items {
+                    let rel_kind = match kind {
+                        MediaKind::Chart => "chart",
+                        _ => "image",
+                    };
+                    let Some(target) = drawing_rels
+                        .iter()
+                        .find(|r| r.id == id && r.kind == rel_kind && !r.external)
+                    else {
+                        continue;
+                    };
+                    media.push(EmbeddedMedia {
+                        kind,
+                        sheet_name: sheet_name.clone(),
+                        anchor: cell.clone(),
+                        name,
+                        part: target.target.clone(),
+                        data: package.read_bytes(&target.target)?,
+                    });
+                }
+            }
+        }
+
+        if !sheet_rels
+            .iter()
+            .any(|r| matches!(r.kind.as_str(), "oleObject" | "package"))
+        {
+            continue;
+        }
+        let sheet = package.read_xml_skipping(&sheet_part, &["sheetData"])?;
+        let mut objects = Vec::new();
+        collect_objects(&sheet, &mut objects);
+        for (anchor, prog_id, id) in objects {
+            // Linked objects have no part; --list-links reports those
+            let Some(target) = sheet_rels.iter().find(|r| {
+                r.id == id && matches!(r.kind.as_str(), "oleObject" | "package") && !r.external
+            }) else {
+                continue;
+            };
+            media.push(EmbeddedMedia {
+                kind: MediaKind::Object,
+                sheet_name: sheet_name.clone(),
+                anchor,
+                name: prog_id,
+                part: target.target.clone(),
+                data: package.read_bytes(&target.target)?,
+            });
+        }
+    }
+
+    Ok(media)
+}
+
+/// Cell of the `from` marker of a one/two-cell anchor
+fn anchor_cell(anchor: &XmlElement) -> String {
+    let marker = || -> Option<String> {
+        let from = anchor.child("from")?;
+        let col: usize = from.child("col")?.text.trim().parse().ok()?;
+        let row: usize = from.child("row")?.text.trim().parse().ok()?;
+        Some(format!("{}{}", column_letter(col), row + 1))
+    };
+    marker().unwrap_or_else(|| "absolute".to_string())
+}
+
+/// Finds `pic` elements and chart frames (including inside shape groups) as (kind, name,
+/// relationship id)
+fn collect_pictures(element: &XmlElement, out: &mut Vec<(MediaKind, String, String)>) {
+    for child in &element.children {
+        match child.name.as_str() {
+            "pic" => {
+                let name = child
+                    .child("nvPicPr")
+                    .and_then(|p| p.child("cNvPr"))
+                    .and_then(|p| p.attr("name"))
+                    .unwrap_or_default()
+                    .to_string();
+                let embed = child
+                    .child("blipFill")
+                    .and_then(|f| f.child("blip"))
+                    .and_then(|b| b.attr("embed"));
+                if let Some(embed) = embed {
+                    out.push((MediaKind::Picture, name, embed.to_string()));
+                }
+            }
+            "graphicFrame" => {
+                let name = child
+                    .child("nvGraphicFramePr")
+                    .and_then(|p| p.child("cNvPr"))
+                    .and_then(|p| p.attr("name"))
+                    .unwrap_or_default()
+                    .to_string();
+                let chart = child
+                    .child("graphic")
+                    .and_then(|g| g.child("graphicData"))
+                    .and_then(|d| d.child("chart"))
+                    .and_then(|c| c.attr("id"));
+                if let Some(chart) = chart {
+                    out.push((MediaKind::Chart, name, chart.to_string()));
+                }
+            }
+            "grpSp" => collect_pictures(child, out),
+            _ => {}
+        }
+    }
+}
+
+/// Finds `oleObject` elements of a worksheet as (anchor cell, program, relationship id)
+fn collect_objects(element: &XmlElement, out: &mut Vec<(String, String, String)>) {
+    for child in &element.children {
+        if child.name == "oleObject" {
+            if let Some(id) = child.attr("id") {
+                let anchor = child
+                    .child("objectPr")
+                    .and_then(|p| p.child("anchor"))
+                    .map(anchor_cell)
+                    .unwrap_or_else(|| "absolute".to_string());
+                let prog_id = child.attr("progId").unwrap_or_default().to_string();
+                out.push((anchor, prog_id, id.to_string()));
+            }
+        } else {
+            // Objects are usually wrapped in mc:AlternateContent
+            collect_objects(child, out);
+        }
+    }
+}
+
+/// Reads width and height from PNG, GIF or JPEG headers
+fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be32 = |b: &[u8]| u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+
+    if data.len() >= 24 && data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(&data[16..20]), be32(&data[20..24])));
+    }
+    if data.len() >= 10 && data.starts_with(b"GIF8") {
+        let w = u16::from_le_bytes([data[6], data[7]]);
+        let h = u16::from_le_bytes([data[8], data[9]]);
+        return Some((w as u32, h as u32));
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        // Walk JPEG segments until a start-of-frame marker
+        let mut i = 2;
+        while i + 9 < data.len() {
+            if data[i] != 0xFF {
+                return None;
+            }
+            let marker = data[i + 1];
+            let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            let is_sof = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_sof {
+                let h = u16::from_be_bytes([data[i + 5], data[i + 6]]);
+                let w = u16::from_be_bytes([data[i + 7], data[i + 8]]);
+                return Some((w as u32, h as u32));
+            }
+            i += 2 + len;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_anchor_and_pictures() {
+        let xml = r#"<xdr:wsDr xmlns:xdr="urn:xdr" xmlns:a="urn:a" xmlns:r="urn:r">
+  <xdr:twoCellAnchor>
+    <xdr:from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>3</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>
+    <xdr:pic>
+      <xdr:nvPicPr><xdr:cNvPr id="2" name="Picture 1"/></xdr:nvPicPr>
+      <xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill>
+    </xdr:pic>
+  </xdr:twoCellAnchor>
+  <xdr:absoluteAnchor><xdr:grpSp><xdr:pic><xdr:blipFill><a:blip r:embed="rId2"/></xdr:blipFill></xdr:pic></xdr:grpSp></xdr:absoluteAnchor>
+</xdr:wsDr>"#;
+        let root = parse_xml(xml, &[]).unwrap();
+
+        assert_eq!(anchor_cell(&root.children[0]), "B4");
+        let mut pictures = Vec::new();
+        collect_pictures(&root.children[0], &mut pictures);
+        assert_eq!(
+            pictures,
+            vec![(
+                MediaKind::Picture,
+                "Picture 1".to_string(),
+                "rId1".to_string()
+            )]
+        );
+
+        assert_eq!(anchor_cell(&root.children[1]), "absolute");
+        pictures.clear();
+        collect_pictures(&root.children[1], &mut pictures);
+        assert_eq!(pictures[0].2, "rId2");
+    }
+
+    #[test]
+    fn test_charts_and_objects() {
+        let drawing = r#"<xdr:wsDr xmlns:xdr="urn:xdr" xmlns:a="urn:a" xmlns:c="urn:c" xmlns:r="urn:r">
+  <xdr:graphicFrame macro="">
+    <xdr:nvGraphicFramePr><xdr:cNvPr id="3" name="Chart 1"/></xdr:nvGraphicFramePr>
+    <a:graphic><a:graphicData><c:chart r:id="rId4"/></a:graphicData></a:graphic>
+  </xdr:graphicFrame>
+</xdr:wsDr>"#;
+        let mut charts = Vec::new();
+        collect_pictures(&parse_xml(drawing, &[]).unwrap(), &mut charts);
+        assert_eq!(
+            charts,
+            vec![(MediaKind::Chart, "Chart 1".to_string(), "rId4".to_string())]
+        );
+
+        let sheet = r#"<worksheet xmlns="urn:x" xmlns:mc="urn:mc" xmlns:r="urn:r"><oleObjects>
+  <mc:AlternateContent><mc:Choice Requires="x14">
+    <oleObject progId="Word.Document.12" shapeId="1025" r:id="rId2"><objectPr><anchor>
+      <from><col>3</col><colOff>0</colOff><row>9</row><rowOff>0</rowOff></from>
+    </anchor></objectPr></oleObject>
+  </mc:Choice></mc:AlternateContent>
+</oleObjects></worksheet>"#;
+        let mut objects = Vec::new();
+        collect_objects(&parse_xml(sheet, &[]).unwrap(), &mut objects);
+        assert_eq!(
+            objects,
+            vec![(
+                "D10".to_string(),
+                "Word.Document.12".to_string(),
+                "rId2".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+
+        let gif = b"GIF89a\x20\x00\x10\x00";
+        assert_eq!(image_dimensions(gif), Some((32, 16)));
+
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+}
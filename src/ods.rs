@@ -0,0 +1,200 @@
+//! Writing sheets as an OpenDocument spreadsheet (.ods)
+
+use crate::progress::Progress;
+use crate::workbook::CellValue;
+use anyhow::{Context, Result};
+use quick_xml::escape::escape;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::CompressionMethod;
+use zip::write::{SimpleFileOptions, ZipWriter};
+
+/// One sheet or Excel table to write with [`write_sheets`]
+pub struct OdsSheet<'a> {
+    pub name: &'a str,
+    pub headers: &'a [String],
+    pub rows: &'a [Vec<CellValue>],
+}
+
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+ <manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+/// Bold header cells, and date styles so date cells display as dates
+const CONTENT_START: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:number="urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.2">
+<office:automatic-styles>
+<number:date-style style:name="N1"><number:year number:style="long"/><number:text>-</number:text><number:month number:style="long"/><number:text>-</number:text><number:day number:style="long"/></number:date-style>
+<number:date-style style:name="N2"><number:year number:style="long"/><number:text>-</number:text><number:month number:style="long"/><number:text>-</number:text><number:day number:style="long"/><number:text> </number:text><number:hours number:style="long"/><number:text>:</number:text><number:minutes number:style="long"/><number:text>:</number:text><number:seconds number:style="long"/></number:date-style>
+<style:style style:name="header" style:family="table-cell"><style:text-properties fo:font-weight="bold"/></style:style>
+<style:style style:name="date" style:family="table-cell" style:data-style-name="N1"/>
+<style:style style:name="datetime" style:family="table-cell" style:data-style-name="N2"/>
+</office:automatic-styles>
+<office:body>
+<office:spreadsheet>
+"#;
+
+const CONTENT_END: &str = "</office:spreadsheet>\n</office:body>\n</office:document-content>\n";
+
+/// Write each entry as a table of a new .ods file: a bold header row, then the rows with
+/// numbers, booleans, and dates typed the same way as the xlsx export
+pub fn write_sheets(path: &Path, sheets: &[OdsSheet]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create '{}'", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+
+    // The mimetype entry must come first and be stored uncompressed
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/vnd.oasis.opendocument.spreadsheet")?;
+    zip.start_file("META-INF/manifest.xml", deflated)?;
+    zip.write_all(MANIFEST.as_bytes())?;
+    zip.start_file("content.xml", deflated)?;
+    zip.write_all(content_xml(sheets).as_bytes())?;
+
+    zip.finish()
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    Ok(())
+}
+
+fn content_xml(sheets: &[OdsSheet]) -> String {
+    let mut xml = String::from(CONTENT_START);
+    for sheet in sheets {
+        let _ = writeln!(xml, r#"<table:table table:name="{}">"#, escape(sheet.name));
+        xml.push_str("<table:table-row>");
+        for header in sheet.headers {
+            let _ = write!(
+                xml,
+                r#"<table:table-cell table:style-name="header" office:value-type="string">{}</table:table-cell>"#,
+                paragraphs(header)
+            );
+        }
+        xml.push_str("</table:table-row>\n");
+        let mut progress = Progress::new(format!("Writing '{}'", sheet.name), sheet.rows.len());
+        for (i, row) in sheet.rows.iter().enumerate() {
+            progress.update(i);
+            xml.push_str("<table:table-row>");
+            for cell in row {
+                xml.push_str(&cell_xml(cell));
+            }
+            xml.push_str("</table:table-row>\n");
+        }
+        xml.push_str("</table:table>\n");
+    }
+    xml.push_str(CONTENT_END);
+    xml
+}
+
+/// A `table:table-cell` element; errors are written as their "#..." text
+fn cell_xml(cell: &CellValue) -> String {
+    match cell {
+        CellValue::Empty => "<table:table-cell/>".to_string(),
+        CellValue::Int(_) | CellValue::Float(_) => {
+            let value = cell.to_raw_string();
+            format!(
+                r#"<table:table-cell office:value-type="float" office:value="{value}"><text:p>{value}</text:p></table:table-cell>"#
+            )
+        }
+        CellValue::Bool(b) => format!(
+            r#"<table:table-cell office:value-type="boolean" office:boolean-value="{b}"><text:p>{}</text:p></table:table-cell>"#,
+            if *b { "TRUE" } else { "FALSE" }
+        ),
+        CellValue::DateTime(dt) => {
+            let text = cell.to_raw_string();
+            let style = if dt.fract() == 0.0 {
+                "date"
+            } else {
+                "datetime"
+            };
+            format!(
+                r#"<table:table-cell table:style-name="{style}" office:value-type="date" office:date-value="{}"><text:p>{text}</text:p></table:table-cell>"#,
+                text.replace(' ', "T")
+            )
+        }
+        CellValue::Time(days) | CellValue::Duration(days) => {
+            let text = cell.to_raw_string();
+            let seconds = (days * 86400.0).round() as i64;
+            let sign = if seconds < 0 { "-" } else { "" };
+            let seconds = seconds.abs();
+            format!(
+                r#"<table:table-cell office:value-type="time" office:time-value="{sign}PT{}H{:02}M{:02}S"><text:p>{text}</text:p></table:table-cell>"#,
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            )
+        }
+        CellValue::String(_) | CellValue::Error(_) => format!(
+            r#"<table:table-cell office:value-type="string">{}</table:table-cell>"#,
+            paragraphs(&cell.to_raw_string())
+        ),
+    }
+}
+
+/// Cell text as one `text:p` per line
+fn paragraphs(text: &str) -> String {
+    text.split('\n')
+        .map(|line| format!("<text:p>{}</text:p>", escape(line)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workbook::Workbook;
+
+    #[test]
+    fn test_cell_xml() {
+        assert_eq!(
+            cell_xml(&CellValue::String("R&D <east>".to_string())),
+            r#"<table:table-cell office:value-type="string"><text:p>R&amp;D &lt;east&gt;</text:p></table:table-cell>"#
+        );
+        assert_eq!(
+            cell_xml(&CellValue::DateTime(45306.5)),
+            r#"<table:table-cell table:style-name="datetime" office:value-type="date" office:date-value="2024-01-15T12:00:00"><text:p>2024-01-15 12:00:00</text:p></table:table-cell>"#
+        );
+        assert_eq!(
+            cell_xml(&CellValue::Duration(1.5625)),
+            r#"<table:table-cell office:value-type="time" office:time-value="PT37H30M00S"><text:p>37:30:00</text:p></table:table-cell>"#
+        );
+    }
+
+    #[test]
+    fn test_write_sheets_round_trip() {
+        let path = std::env::temp_dir().join(format!("xleak-test-{}.ods", std::process::id()));
+        let headers = vec!["Item".to_string(), "Qty".to_string(), "Paid".to_string()];
+        let rows = vec![
+            vec![
+                CellValue::String("Pens".to_string()),
+                CellValue::Int(3),
+                CellValue::Bool(true),
+            ],
+            vec![
+                CellValue::String("Paper\nA4".to_string()),
+                CellValue::Float(2.5),
+                CellValue::Empty,
+            ],
+        ];
+        let sheets = [OdsSheet {
+            name: "Q1 \"Orders\"",
+            headers: &headers,
+            rows: &rows,
+        }];
+        write_sheets(&path, &sheets).unwrap();
+
+        let mut workbook = Workbook::open(&path).unwrap();
+        let sheet = workbook.load_sheet("Q1 \"Orders\"").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sheet.headers, headers);
+        assert_eq!(sheet.rows[0][1], CellValue::Float(3.0));
+        assert_eq!(sheet.rows[0][2], CellValue::Bool(true));
+        assert_eq!(sheet.rows[1][0], CellValue::String("Paper\nA4".to_string()));
+        assert_eq!(sheet.rows[1][1], CellValue::Float(2.5));
+    }
+}
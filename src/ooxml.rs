@@ -0,0 +1,393 @@
+//! Read-only access to parts of an OOXML package (.xlsx/.xlsm) that calamine doesn't expose
+
+use anyhow::{Context, Result};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// An opened .xlsx/.xlsm zip package
+pub struct Package {
+    archive: ZipArchive<BufReader<File>>,
+}
+
+/// Relationship between two parts, read from a `_rels/*.rels` file
+#[derive(Debug, Clone)]
+pub struct Relationship {
+    pub id: String,
+    /// Last segment of the relationship type URI (e.g. "pivotTable", "image")
+    pub kind: String,
+    /// Resolved part name, or the raw target for external relationships
+    pub target: String,
+    pub external: bool,
+}
+
+/// Minimal element tree for small XML parts
+#[derive(Debug, Clone, Default)]
+pub struct XmlElement {
+    /// Local name (namespace prefix stripped)
+    pub name: String,
+    /// Attributes keyed by local name
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    pub text: String,
+}
+
+impl XmlElement {
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+impl Package {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+        let archive = ZipArchive::new(BufReader::new(file))
+            .context("Not a valid .xlsx package (zip archive expected)")?;
+        Ok(Self { archive })
+    }
+
+    pub fn has_part(&self, name: &str) -> bool {
+        self.archive.file_names().any(|n| n == name)
+    }
+
+    pub fn read_bytes(&mut self, name: &str) -> Result<Vec<u8>> {
+        let mut file = self
+            .archive
+            .by_name(name)
+            .with_context(|| format!("Part '{name}' not found in package"))?;
+        let mut buf = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut buf)
+            .with_context(|| format!("Failed to read part '{name}'"))?;
+        Ok(buf)
+    }
+
+    pub fn read_xml(&mut self, name: &str) -> Result<XmlElement> {
+        self.read_xml_skipping(name, &[])
+    }
+
+    /// Parses a part, skipping the subtrees of `skip` elements (e.g. `sheetData`)
+    pub fn read_xml_skipping(&mut self, name: &str, skip: &[&str]) -> Result<XmlElement> {
+        let bytes = self.read_bytes(name)?;
+        let xml = String::from_utf8_lossy(&bytes);
+        parse_xml(&xml, skip).with_context(|| format!("Failed to parse part '{name}'"))
+    }
+
+    /// Streams a part, calling `f` for each element named in `names` (attributes only, no children)
+    ///
+    /// Suited to large parts like worksheets where building a tree would be wasteful.
+    pub fn scan_elements(
+        &mut self,
+        name: &str,
+        names: &[&str],
+        mut f: impl FnMut(&XmlElement),
+    ) -> Result<()> {
+        let bytes = self.read_bytes(name)?;
+        let mut reader = Reader::from_reader(bytes.as_slice());
+        let mut buf = Vec::new();
+        loop {
+            match reader
+                .read_event_into(&mut buf)
+                .with_context(|| format!("Failed to parse part '{name}'"))?
+            {
+                Event::Start(e) | Event::Empty(e)
+                    if names.contains(&local_name(e.name().as_ref()).as_str()) =>
+                {
+                    f(&element_from_start(&e)?);
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+
+    /// Part name of the first workbook relationship of `kind` (e.g. "styles")
+    pub fn workbook_part(&mut self, kind: &str) -> Result<Option<String>> {
+        Ok(self
+            .relationships("xl/workbook.xml")?
+            .into_iter()
+            .find(|r| r.kind == kind && !r.external)
+            .map(|r| r.target))
+    }
+
+    /// Relationships of a part; empty if it has no `.rels` file
+    pub fn relationships(&mut self, part: &str) -> Result<Vec<Relationship>> {
+        let rels_name = rels_path(part);
+        if !self.has_part(&rels_name) {
+            return Ok(Vec::new());
+        }
+        let root = self.read_xml(&rels_name)?;
+        Ok(root
+            .children_named("Relationship")
+            .map(|rel| {
+                let external = rel.attr("TargetMode") == Some("External");
+                let raw_target = rel.attr("Target").unwrap_or_default();
+                Relationship {
+                    id: rel.attr("Id").unwrap_or_default().to_string(),
+                    kind: rel
+                        .attr("Type")
+                        .and_then(|t| t.rsplit('/').next())
+                        .unwrap_or_default()
+                        .to_string(),
+                    target: if external {
+                        raw_target.to_string()
+                    } else {
+                        resolve_target(part, raw_target)
+                    },
+                    external,
+                }
+            })
+            .collect())
+    }
+
+    /// Sheet names paired with their worksheet part names, in workbook order
+    pub fn worksheet_parts(&mut self) -> Result<Vec<(String, String)>> {
+        let workbook = self.read_xml("xl/workbook.xml")?;
+        let rels = self.relationships("xl/workbook.xml")?;
+        let mut parts = Vec::new();
+        if let Some(sheets) = workbook.child("sheets") {
+            for sheet in sheets.children_named("sheet") {
+                let name = sheet.attr("name").unwrap_or_default();
+                let rid = sheet.attr("id").unwrap_or_default();
+                if let Some(rel) = rels.iter().find(|r| r.id == rid) {
+                    parts.push((name.to_string(), rel.target.clone()));
+                }
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Worksheet part name of the named sheet
+    pub fn worksheet_part(&mut self, sheet_name: &str) -> Result<String> {
+        self.worksheet_parts()?
+            .into_iter()
+            .find(|(name, _)| name == sheet_name)
+            .map(|(_, part)| part)
+            .with_context(|| format!("Sheet '{sheet_name}' not found"))
+    }
+}
+
+/// `xl/worksheets/sheet1.xml` -> `xl/worksheets/_rels/sheet1.xml.rels`
+fn rels_path(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((dir, file)) => format!("{dir}/_rels/{file}.rels"),
+        None => format!("_rels/{part}.rels"),
+    }
+}
+
+/// Resolves a relationship target relative to the part that owns it
+pub fn resolve_target(source_part: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = source_part.split('/').collect();
+    segments.pop(); // Drop the source file name
+    for seg in target.split('/') {
+        match seg {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            _ => segments.push(seg),
+        }
+    }
+    segments.join("/")
+}
+
+/// Parses an A1-style reference ("B12", "$B$12") into 0-based (row, col)
+pub fn parse_cell_ref(reference: &str) -> Option<(u32, u32)> {
+    let reference = reference.replace('$', "");
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let col = letters.chars().try_fold(0u32, |acc, c| {
+        acc.checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    })?;
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+/// Parses an A1-style range ("A1:D50", "D50:A1") into 0-based (top-left, bottom-right)
+pub fn parse_range_ref(reference: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (first, last) = reference.split_once(':')?;
+    let (r1, c1) = parse_cell_ref(first.trim())?;
+    let (r2, c2) = parse_cell_ref(last.trim())?;
+    Some(((r1.min(r2), c1.min(c2)), (r1.max(r2), c1.max(c2))))
+}
+
+fn local_name(qname: &[u8]) -> String {
+    let local = match qname.iter().position(|&b| b == b':') {
+        Some(idx) => &qname[idx + 1..],
+        None => qname,
+    };
+    String::from_utf8_lossy(local).into_owned()
+}
+
+fn element_from_start(start: &BytesStart) -> Result<XmlElement> {
+    let mut attrs = Vec::new();
+    for attr in start.attributes().with_checks(false) {
+        let attr = attr?;
+        attrs.push((
+            local_name(attr.key.as_ref()),
+            attr.unescape_value()?.into_owned(),
+        ));
+    }
+    Ok(XmlElement {
+        name: local_name(start.name().as_ref()),
+        attrs,
+        children: Vec::new(),
+        text: String::new(),
+    })
+}
+
+/// Parses an XML document into an element tree, skipping subtrees named in `skip`
+pub fn parse_xml(xml: &str, skip: &[&str]) -> Result<XmlElement> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<XmlElement> = vec![XmlElement::default()];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let element = element_from_start(&e)?;
+                if skip.contains(&element.name.as_str()) {
+                    reader.read_to_end(e.name())?;
+                    continue;
+                }
+                stack.push(element);
+            }
+            Event::Empty(e) => {
+                let element = element_from_start(&e)?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let done = stack.pop().expect("stack has a parent");
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(done);
+                }
+            }
+            Event::Text(e) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&e.xml_content()?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&e.decode()?);
+                }
+            }
+            Event::GeneralRef(e) => {
+                let entity = format!("&{};", e.decode()?);
+                if let Some(current) = stack.last_mut() {
+                    let resolved = quick_xml::escape::unescape(&entity)
+                        .map(|s| s.into_owned())
+                        .unwrap_or(entity);
+                    current.text.push_str(&resolved);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // The synthetic document node holds the root element
+    let document = stack.into_iter().next().unwrap_or_default();
+    document
+        .children
+        .into_iter()
+        .next()
+        .context("XML document has no root element")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_target_relative() {
+        assert_eq!(
+            resolve_target(
+                "xl/pivotTables/pivotTable1.xml",
+                "../pivotCache/pivotCacheDefinition1.xml"
+            ),
+            "xl/pivotCache/pivotCacheDefinition1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl/workbook.xml", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+    }
+
+    #[test]
+    fn test_resolve_target_absolute() {
+        assert_eq!(
+            resolve_target("xl/workbook.xml", "/xl/worksheets/sheet2.xml"),
+            "xl/worksheets/sheet2.xml"
+        );
+    }
+
+    #[test]
+    fn test_rels_path() {
+        assert_eq!(
+            rels_path("xl/worksheets/sheet1.xml"),
+            "xl/worksheets/_rels/sheet1.xml.rels"
+        );
+        assert_eq!(rels_path("workbook.xml"), "_rels/workbook.xml.rels");
+    }
+
+    #[test]
+    fn test_parse_cell_ref() {
+        assert_eq!(parse_cell_ref("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_ref("B12"), Some((11, 1)));
+        assert_eq!(parse_cell_ref("$AA$3"), Some((2, 26)));
+        assert_eq!(parse_cell_ref("12"), None);
+        assert_eq!(parse_cell_ref("A0"), None);
+        assert_eq!(parse_range_ref("A1:D50"), Some(((0, 0), (49, 3))));
+        assert_eq!(parse_range_ref("D50:b2"), Some(((1, 1), (49, 3))));
+        assert_eq!(parse_range_ref("A1"), None);
+    }
+
+    #[test]
+    fn test_parse_xml_strips_prefixes_and_unescapes() {
+        let xml = r#"<?xml version="1.0"?>
+<x:root xmlns:x="urn:x" xmlns:r="urn:r">
+  <x:item r:id="rId1" name="A &amp; B"/>
+  <x:text>1 &lt; 2</x:text>
+</x:root>"#;
+        let root = parse_xml(xml, &[]).unwrap();
+        assert_eq!(root.name, "root");
+        let item = root.child("item").unwrap();
+        assert_eq!(item.attr("id"), Some("rId1"));
+        assert_eq!(item.attr("name"), Some("A & B"));
+        assert_eq!(root.child("text").unwrap().text, "1 < 2");
+    }
+
+    #[test]
+    fn test_parse_xml_skips_subtrees() {
+        let xml = "<worksheet><sheetData><row><c/></row></sheetData><tail/></worksheet>";
+        let root = parse_xml(xml, &["sheetData"]).unwrap();
+        assert!(root.child("sheetData").is_none());
+        assert!(root.child("tail").is_some());
+        assert_eq!(root.children.len(), 1);
+    }
+}
@@ -0,0 +1,61 @@
+//! Frozen panes recorded in a worksheet's sheet view
+
+use crate::ooxml::{Package, XmlElement};
+use anyhow::Result;
+
+/// Rows and columns Excel keeps in place while scrolling, counted from the sheet's top-left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrozenPanes {
+    pub rows: u32,
+    pub cols: u32,
+}
+
+/// Reads the first sheet view's pane; None unless it is frozen
+pub fn load_frozen_panes(package: &mut Package, sheet_name: &str) -> Result<Option<FrozenPanes>> {
+    let part = package.worksheet_part(sheet_name)?;
+    let sheet = package.read_xml_skipping(&part, &["sheetData"])?;
+    Ok(sheet
+        .child("sheetViews")
+        .and_then(|views| views.child("sheetView"))
+        .and_then(|view| view.child("pane"))
+        .and_then(frozen_from))
+}
+
+/// Split panes without `state="frozen"` can be dragged freely, so only frozen ones count
+fn frozen_from(pane: &XmlElement) -> Option<FrozenPanes> {
+    if !matches!(pane.attr("state"), Some("frozen") | Some("frozenSplit")) {
+        return None;
+    }
+    // For frozen panes the splits are whole row and column counts
+    let split = |attr: &str| {
+        pane.attr(attr)
+            .and_then(|v| v.parse::<f64>().ok())
+            .map_or(0, |v| v.max(0.0) as u32)
+    };
+    let panes = FrozenPanes {
+        rows: split("ySplit"),
+        cols: split("xSplit"),
+    };
+    (panes != FrozenPanes::default()).then_some(panes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_frozen_from_pane() {
+        let pane = |xml: &str| frozen_from(&parse_xml(xml, &[]).unwrap());
+        assert_eq!(
+            pane(r#"<pane xSplit="2" ySplit="1" topLeftCell="C2" state="frozen"/>"#),
+            Some(FrozenPanes { rows: 1, cols: 2 })
+        );
+        assert_eq!(
+            pane(r#"<pane ySplit="3" topLeftCell="A4" state="frozen"/>"#),
+            Some(FrozenPanes { rows: 3, cols: 0 })
+        );
+        // A movable split is not a freeze
+        assert_eq!(pane(r#"<pane xSplit="2880" ySplit="1200"/>"#), None);
+    }
+}
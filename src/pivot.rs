@@ -0,0 +1,235 @@
+//! Pivot table definitions parsed from the xlsx package
+
+use crate::ooxml::{Package, XmlElement};
+use anyhow::Result;
+
+/// A pivot table definition (layout only; cached data is not read)
+#[derive(Debug, Clone)]
+pub struct PivotTable {
+    pub name: String,
+    pub sheet_name: String,
+    /// Range the pivot occupies on its sheet (e.g. "A3:D20")
+    pub location: String,
+    /// Source data description (e.g. "Data!A1:F500", a table name, or "external")
+    pub source: String,
+    pub row_fields: Vec<String>,
+    pub column_fields: Vec<String>,
+    pub filter_fields: Vec<String>,
+    pub values: Vec<PivotValue>,
+}
+
+/// A data field of a pivot table and its aggregation
+#[derive(Debug, Clone)]
+pub struct PivotValue {
+    pub name: String,
+    pub field: String,
+    pub aggregation: String,
+}
+
+/// Reads every pivot table definition in the package, in sheet order
+pub fn load_pivot_tables(package: &mut Package) -> Result<Vec<PivotTable>> {
+    let mut pivots = Vec::new();
+
+    for (sheet_name, sheet_part) in package.worksheet_parts()? {
+        for rel in package.relationships(&sheet_part)? {
+            if rel.kind != "pivotTable" || rel.external {
+                continue;
+            }
+            let definition = package.read_xml(&rel.target)?;
+
+            // Each pivot table links to the cache definition holding source and field names
+            let cache = match package
+                .relationships(&rel.target)?
+                .into_iter()
+                .find(|r| r.kind == "pivotCacheDefinition")
+            {
+                Some(cache_rel) => Some(package.read_xml(&cache_rel.target)?),
+                None => None,
+            };
+
+            pivots.push(parse_pivot_table(&definition, cache.as_ref(), &sheet_name));
+        }
+    }
+
+    Ok(pivots)
+}
+
+fn parse_pivot_table(
+    definition: &XmlElement,
+    cache: Option<&XmlElement>,
+    sheet_name: &str,
+) -> PivotTable {
+    let field_names: Vec<String> = cache
+        .and_then(|c| c.child("cacheFields"))
+        .map(|fields| {
+            fields
+                .children_named("cacheField")
+                .map(|f| f.attr("name").unwrap_or_default().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let field_name = |idx: Option<&str>| -> String {
+        match idx.and_then(|i| i.parse::<i64>().ok()) {
+            // -2 is the synthetic "Σ Values" field used when several data fields exist
+            Some(-2) => "Values".to_string(),
+            Some(i) if i >= 0 => field_names
+                .get(i as usize)
+                .cloned()
+                .unwrap_or_else(|| format!("field {i}")),
+            _ => "?".to_string(),
+        }
+    };
+
+    let axis_fields = |container: &str, item: &str, attr: &str| -> Vec<String> {
+        definition
+            .child(container)
+            .map(|c| {
+                c.children_named(item)
+                    .map(|f| field_name(f.attr(attr)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let values = definition
+        .child("dataFields")
+        .map(|c| {
+            c.children_named("dataField")
+                .map(|f| {
+                    let field = field_name(f.attr("fld"));
+                    PivotValue {
+                        name: f
+                            .attr("name")
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| field.clone()),
+                        field,
+                        // Excel omits the attribute for the default aggregation
+                        aggregation: f.attr("subtotal").unwrap_or("sum").to_string(),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    PivotTable {
+        name: definition.attr("name").unwrap_or_default().to_string(),
+        sheet_name: sheet_name.to_string(),
+        location: definition
+            .child("location")
+            .and_then(|l| l.attr("ref"))
+            .unwrap_or_default()
+            .to_string(),
+        source: cache
+            .map(describe_source)
+            .unwrap_or_else(|| "?".to_string()),
+        row_fields: axis_fields("rowFields", "field", "x"),
+        column_fields: axis_fields("colFields", "field", "x"),
+        filter_fields: axis_fields("pageFields", "pageField", "fld"),
+        values,
+    }
+}
+
+fn describe_source(cache: &XmlElement) -> String {
+    let Some(source) = cache.child("cacheSource") else {
+        return "?".to_string();
+    };
+    match source.attr("type") {
+        Some("worksheet") | None => {
+            let Some(ws) = source.child("worksheetSource") else {
+                return "worksheet".to_string();
+            };
+            match (ws.attr("name"), ws.attr("sheet"), ws.attr("ref")) {
+                (Some(name), _, _) => name.to_string(),
+                (None, Some(sheet), Some(range)) => format!("{sheet}!{range}"),
+                (None, None, Some(range)) => range.to_string(),
+                _ => "worksheet".to_string(),
+            }
+        }
+        Some(other) => match source.attr("connectionId") {
+            Some(id) => format!("{other} (connection {id})"),
+            None => other.to_string(),
+        },
+    }
+}
+
+impl PivotTable {
+    /// Multi-line human-readable description shared by the CLI and TUI
+    pub fn describe(&self) -> Vec<String> {
+        let list = |fields: &[String]| {
+            if fields.is_empty() {
+                "(none)".to_string()
+            } else {
+                fields.join(", ")
+            }
+        };
+        let values: Vec<String> = self
+            .values
+            .iter()
+            .map(|v| format!("{} ({} of {})", v.name, v.aggregation, v.field))
+            .collect();
+
+        vec![
+            format!("  Location: {}!{}", self.sheet_name, self.location),
+            format!("  Source:   {}", self.source),
+            format!("  Rows:     {}", list(&self.row_fields)),
+            format!("  Columns:  {}", list(&self.column_fields)),
+            format!("  Filters:  {}", list(&self.filter_fields)),
+            format!("  Values:   {}", list(&values)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    const CACHE: &str = r#"<pivotCacheDefinition xmlns="urn:x" xmlns:r="urn:r" r:id="rId1">
+  <cacheSource type="worksheet"><worksheetSource ref="A1:D100" sheet="Data"/></cacheSource>
+  <cacheFields count="4">
+    <cacheField name="Region"/><cacheField name="Product"/>
+    <cacheField name="Year"/><cacheField name="Amount"/>
+  </cacheFields>
+</pivotCacheDefinition>"#;
+
+    const DEFINITION: &str = r#"<pivotTableDefinition xmlns="urn:x" name="SalesPivot" cacheId="1">
+  <location ref="A3:E12" firstHeaderRow="1" firstDataRow="2" firstDataCol="1"/>
+  <rowFields count="2"><field x="0"/><field x="1"/></rowFields>
+  <colFields count="1"><field x="2"/></colFields>
+  <dataFields count="2">
+    <dataField name="Sum of Amount" fld="3" baseField="0" baseItem="0"/>
+    <dataField name="Count of Amount" fld="3" subtotal="count"/>
+  </dataFields>
+</pivotTableDefinition>"#;
+
+    #[test]
+    fn test_parse_pivot_table() {
+        let definition = parse_xml(DEFINITION, &[]).unwrap();
+        let cache = parse_xml(CACHE, &[]).unwrap();
+        let pivot = parse_pivot_table(&definition, Some(&cache), "Summary");
+
+        assert_eq!(pivot.name, "SalesPivot");
+        assert_eq!(pivot.location, "A3:E12");
+        assert_eq!(pivot.source, "Data!A1:D100");
+        assert_eq!(pivot.row_fields, vec!["Region", "Product"]);
+        assert_eq!(pivot.column_fields, vec!["Year"]);
+        assert!(pivot.filter_fields.is_empty());
+        assert_eq!(pivot.values.len(), 2);
+        assert_eq!(pivot.values[0].aggregation, "sum");
+        assert_eq!(pivot.values[1].aggregation, "count");
+        assert_eq!(pivot.values[1].field, "Amount");
+    }
+
+    #[test]
+    fn test_values_pseudo_field() {
+        let definition = parse_xml(
+            r#"<pivotTableDefinition name="P"><colFields><field x="-2"/></colFields></pivotTableDefinition>"#,
+            &[],
+        )
+        .unwrap();
+        let pivot = parse_pivot_table(&definition, None, "Sheet1");
+        assert_eq!(pivot.column_fields, vec!["Values"]);
+        assert_eq!(pivot.source, "?");
+    }
+}
@@ -0,0 +1,115 @@
+//! Progress bars on stderr for loading and exporting huge sheets, so a long conversion
+//! doesn't look hung. Off until [`enable`] is called (the TUI owns the terminal), and only
+//! drawn on a terminal once an operation has run for half a second.
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Wait this long before drawing, so quick operations stay silent
+const DELAY: Duration = Duration::from_millis(500);
+/// Redraw at most this often
+const INTERVAL: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 30;
+
+/// Draw progress bars from now on when stderr is a terminal
+pub fn enable() {
+    ENABLED.store(std::io::stderr().is_terminal(), Ordering::Relaxed);
+}
+
+/// A "Loading 'Sales' [=====>    ] 52% 520,000/1,000,000 rows" line, cleared when dropped
+pub struct Progress {
+    label: String,
+    /// Rows expected; 0 when unknown, which shows the count without a bar
+    total: usize,
+    started: Instant,
+    drawn: Option<Instant>,
+    active: bool,
+}
+
+impl Progress {
+    pub fn new(label: impl Into<String>, total: usize) -> Self {
+        Self {
+            label: label.into(),
+            total,
+            started: Instant::now(),
+            drawn: None,
+            active: ENABLED.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Report that `done` rows are finished
+    pub fn update(&mut self, done: usize) {
+        if !self.active {
+            return;
+        }
+        let now = Instant::now();
+        let due = match self.drawn {
+            Some(drawn) => now - drawn >= INTERVAL,
+            None => now - self.started >= DELAY,
+        };
+        if due {
+            self.drawn = Some(now);
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r{}", self.line(done));
+            let _ = stderr.flush();
+        }
+    }
+
+    fn line(&self, done: usize) -> String {
+        let locale = crate::locale::default_locale();
+        let count = |n: usize| locale.number(&n.to_string(), None);
+        if self.total == 0 {
+            return format!("{} {} rows", self.label, count(done));
+        }
+        let done = done.min(self.total);
+        let filled = done * BAR_WIDTH / self.total;
+        let bar = if filled < BAR_WIDTH {
+            format!(
+                "{}>{}",
+                "=".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled - 1)
+            )
+        } else {
+            "=".repeat(BAR_WIDTH)
+        };
+        format!(
+            "{} [{bar}] {:>3}% {}/{} rows",
+            self.label,
+            done * 100 / self.total,
+            count(done),
+            count(self.total)
+        )
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.drawn.is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let mut progress = Progress::new("Loading 'Sales'", 1_000_000);
+        assert_eq!(
+            progress.line(520_000),
+            "Loading 'Sales' [===============>              ]  52% 520,000/1,000,000 rows"
+        );
+        assert!(
+            progress
+                .line(1_000_000)
+                .contains("[==============================] 100%")
+        );
+        progress.total = 0;
+        assert_eq!(progress.line(1234), "Loading 'Sales' 1,234 rows");
+    }
+}
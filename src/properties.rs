@@ -0,0 +1,94 @@
+//! Document properties (title, author, dates) from `docProps/core.xml` and `docProps/app.xml`
+
+use crate::ooxml::{Package, XmlElement};
+use anyhow::Result;
+
+/// Properties Excel shows under File > Info; any may be missing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentProperties {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub creator: Option<String>,
+    pub last_modified_by: Option<String>,
+    /// W3CDTF timestamps as stored (e.g. "2024-03-01T09:30:00Z")
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub application: Option<String>,
+}
+
+impl DocumentProperties {
+    /// (label, value) for each property that is set, in display order
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("Title", &self.title),
+            ("Subject", &self.subject),
+            ("Author", &self.creator),
+            ("Last modified by", &self.last_modified_by),
+            ("Created", &self.created),
+            ("Modified", &self.modified),
+            ("Application", &self.application),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.as_deref().map(|v| (label, v)))
+        .collect()
+    }
+}
+
+/// Reads core and app properties; parts that are absent leave their fields empty
+pub fn load_document_properties(package: &mut Package) -> Result<DocumentProperties> {
+    let mut properties = DocumentProperties::default();
+    if package.has_part("docProps/core.xml") {
+        apply_core_properties(&mut properties, &package.read_xml("docProps/core.xml")?);
+    }
+    if package.has_part("docProps/app.xml") {
+        let app = package.read_xml("docProps/app.xml")?;
+        properties.application = text(&app, "Application");
+    }
+    Ok(properties)
+}
+
+fn apply_core_properties(properties: &mut DocumentProperties, core: &XmlElement) {
+    properties.title = text(core, "title");
+    properties.subject = text(core, "subject");
+    properties.creator = text(core, "creator");
+    properties.last_modified_by = text(core, "lastModifiedBy");
+    properties.created = text(core, "created");
+    properties.modified = text(core, "modified");
+}
+
+/// Trimmed text of a child element, None if missing or blank
+fn text(parent: &XmlElement, name: &str) -> Option<String> {
+    parent
+        .child(name)
+        .map(|el| el.text.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_core_properties() {
+        let xml = r#"<cp:coreProperties xmlns:cp="urn:cp" xmlns:dc="urn:dc" xmlns:dcterms="urn:dcterms">
+  <dc:title>Q1 Budget</dc:title>
+  <dc:creator>Alice</dc:creator>
+  <cp:lastModifiedBy>Bob</cp:lastModifiedBy>
+  <dc:subject></dc:subject>
+  <dcterms:created xsi:type="dcterms:W3CDTF">2024-03-01T09:30:00Z</dcterms:created>
+</cp:coreProperties>"#;
+        let mut properties = DocumentProperties::default();
+        apply_core_properties(&mut properties, &parse_xml(xml, &[]).unwrap());
+
+        assert_eq!(
+            properties.fields(),
+            vec![
+                ("Title", "Q1 Budget"),
+                ("Author", "Alice"),
+                ("Last modified by", "Bob"),
+                ("Created", "2024-03-01T09:30:00Z"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Workbook and sheet protection flags, and which cells the author left editable
+
+use crate::ooxml::{Package, XmlElement, parse_cell_ref};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Sheet protection attributes as (attribute, label, protected by default).
+/// An attribute set to true means the action is blocked while the sheet is protected.
+const PERMISSIONS: &[(&str, &str, bool)] = &[
+    ("selectLockedCells", "select locked cells", false),
+    ("selectUnlockedCells", "select unlocked cells", false),
+    ("formatCells", "format cells", true),
+    ("formatColumns", "format columns", true),
+    ("formatRows", "format rows", true),
+    ("insertColumns", "insert columns", true),
+    ("insertRows", "insert rows", true),
+    ("insertHyperlinks", "insert hyperlinks", true),
+    ("deleteColumns", "delete columns", true),
+    ("deleteRows", "delete rows", true),
+    ("sort", "sort", true),
+    ("autoFilter", "use autofilter", true),
+    ("pivotTables", "use pivot tables", true),
+    ("objects", "edit objects", false),
+    ("scenarios", "edit scenarios", false),
+];
+
+/// Workbook-level protection (`workbookProtection`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkbookProtection {
+    pub structure: bool,
+    pub windows: bool,
+    pub password: bool,
+}
+
+impl WorkbookProtection {
+    pub fn summary(&self) -> String {
+        let mut locked = Vec::new();
+        if self.structure {
+            locked.push("structure");
+        }
+        if self.windows {
+            locked.push("windows");
+        }
+        if locked.is_empty() {
+            return "not protected".to_string();
+        }
+        let password = if self.password { " (password)" } else { "" };
+        format!("{} locked{password}", locked.join(" and "))
+    }
+}
+
+/// Sheet protection plus the cells whose style unlocks them
+#[derive(Debug, Clone, Default)]
+pub struct SheetProtection {
+    pub protected: bool,
+    pub password: bool,
+    /// Actions still permitted while protected (e.g. "sort")
+    pub allowed: Vec<&'static str>,
+    /// Unlocked cells as 0-based absolute (row, col)
+    unlocked: HashSet<(u32, u32)>,
+    /// Cells whose formulas are hidden while protected
+    hidden: HashSet<(u32, u32)>,
+}
+
+impl SheetProtection {
+    /// Whether the cell's style locks it (Excel's default for unstyled cells)
+    pub fn is_locked(&self, row: u32, col: u32) -> bool {
+        !self.unlocked.contains(&(row, col))
+    }
+
+    pub fn is_formula_hidden(&self, row: u32, col: u32) -> bool {
+        self.hidden.contains(&(row, col))
+    }
+
+    pub fn summary(&self) -> String {
+        if !self.protected {
+            return "not protected".to_string();
+        }
+        let mut summary = "protected".to_string();
+        if self.password {
+            summary.push_str(" (password)");
+        }
+        if !self.allowed.is_empty() {
+            summary.push_str(&format!("; allowed: {}", self.allowed.join(", ")));
+        }
+        summary.push_str(&format!("; {} unlocked cells", self.unlocked.len()));
+        summary
+    }
+}
+
+/// Reads `workbookProtection`; None if the workbook isn't protected
+pub fn load_workbook_protection(package: &mut Package) -> Result<Option<WorkbookProtection>> {
+    let workbook = package.read_xml_skipping("xl/workbook.xml", &["sheets", "definedNames"])?;
+    Ok(workbook
+        .child("workbookProtection")
+        .map(|p| WorkbookProtection {
+            structure: flag(p, "lockStructure", false),
+            windows: flag(p, "lockWindows", false),
+            password: p.attr("workbookPassword").is_some() || p.attr("workbookHashValue").is_some(),
+        }))
+}
+
+/// Reads a sheet's protection flags and the locked/hidden state of its styled cells
+pub fn load_sheet_protection(package: &mut Package, sheet_name: &str) -> Result<SheetProtection> {
+    let part = package.worksheet_part(sheet_name)?;
+
+    // (locked, hidden) per cell format index
+    let formats: Vec<(bool, bool)> = match package.workbook_part("styles")? {
+        Some(styles_part) => {
+            let styles = package.read_xml(&styles_part)?;
+            cell_format_protection(&styles)
+        }
+        None => Vec::new(),
+    };
+
+    let mut protection = SheetProtection::default();
+    package.scan_elements(&part, &["c", "sheetProtection"], |el| {
+        if el.name == "sheetProtection" {
+            apply_sheet_protection(&mut protection, el);
+            return;
+        }
+        let style = el
+            .attr("s")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(0);
+        let Some(&(locked, hidden)) = formats.get(style) else {
+            return;
+        };
+        if let Some(pos) = el.attr("r").and_then(parse_cell_ref) {
+            if !locked {
+                protection.unlocked.insert(pos);
+            }
+            if hidden {
+                protection.hidden.insert(pos);
+            }
+        }
+    })?;
+
+    Ok(protection)
+}
+
+/// Boolean attribute, accepting "1"/"true" and "0"/"false"
+fn flag(el: &XmlElement, attr: &str, default: bool) -> bool {
+    match el.attr(attr) {
+        Some("1") | Some("true") => true,
+        Some("0") | Some("false") => false,
+        _ => default,
+    }
+}
+
+fn apply_sheet_protection(protection: &mut SheetProtection, el: &XmlElement) {
+    protection.protected = flag(el, "sheet", false);
+    protection.password = el.attr("password").is_some() || el.attr("hashValue").is_some();
+    protection.allowed = PERMISSIONS
+        .iter()
+        .filter(|(attr, _, default)| !flag(el, attr, *default))
+        .map(|(_, label, _)| *label)
+        .collect();
+}
+
+/// (locked, hidden) for each `cellXfs` entry
+fn cell_format_protection(styles: &XmlElement) -> Vec<(bool, bool)> {
+    styles
+        .child("cellXfs")
+        .map(|xfs| {
+            xfs.children_named("xf")
+                .map(|xf| match xf.child("protection") {
+                    Some(p) => (flag(p, "locked", true), flag(p, "hidden", false)),
+                    None => (true, false),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_sheet_protection_permissions() {
+        let el = parse_xml(
+            r#"<sheetProtection sheet="1" hashValue="abc" objects="1" scenarios="1" formatCells="0" sort="0" selectLockedCells="1"/>"#,
+            &[],
+        )
+        .unwrap();
+        let mut protection = SheetProtection::default();
+        apply_sheet_protection(&mut protection, &el);
+
+        assert!(protection.protected);
+        assert!(protection.password);
+        assert_eq!(
+            protection.allowed,
+            vec!["select unlocked cells", "format cells", "sort"]
+        );
+    }
+
+    #[test]
+    fn test_cell_format_protection() {
+        let styles = parse_xml(
+            r#"<styleSheet><cellXfs count="3">
+  <xf numFmtId="0"/>
+  <xf numFmtId="0" applyProtection="1"><protection locked="0"/></xf>
+  <xf numFmtId="0" applyProtection="1"><protection hidden="1"/></xf>
+</cellXfs></styleSheet>"#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            cell_format_protection(&styles),
+            vec![(true, false), (false, false), (true, true)]
+        );
+    }
+
+    #[test]
+    fn test_workbook_protection_summary() {
+        let protection = WorkbookProtection {
+            structure: true,
+            windows: false,
+            password: true,
+        };
+        assert_eq!(protection.summary(), "structure locked (password)");
+        assert_eq!(WorkbookProtection::default().summary(), "not protected");
+    }
+}
@@ -0,0 +1,179 @@
+//! Opening workbooks given as http(s) URLs (`url` feature)
+
+use crate::workbook::Workbook;
+use anyhow::Result;
+use std::path::Path;
+
+/// The argument as a URL, if it starts with http:// or https://
+pub fn as_url(arg: &Path) -> Option<&str> {
+    let arg = arg.to_str()?;
+    let scheme = arg.split_once("://")?.0;
+    (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")).then_some(arg)
+}
+
+/// Download the workbook to a temporary file (removed when the workbook is dropped) and open it
+#[cfg(feature = "url")]
+pub fn open(url: &str) -> Result<Workbook> {
+    Workbook::open_download(download::fetch(url)?)
+}
+
+#[cfg(not(feature = "url"))]
+pub fn open(url: &str) -> Result<Workbook> {
+    anyhow::bail!("Can't open {url}: xleak was built without the `url` feature")
+}
+
+#[cfg(feature = "url")]
+mod download {
+    use crate::display::format_bytes;
+    use anyhow::{Context, Result};
+    use std::io::{IsTerminal, Read, Write};
+    use tempfile::TempPath;
+
+    /// File extensions xleak can open, used to name the temporary file
+    const EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "xls", "ods", "csv", "tsv"];
+
+    /// MIME types servers commonly send for each format
+    const MIME_TYPES: &[(&str, &str)] = &[
+        (
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        ),
+        ("application/vnd.ms-excel.sheet.macroenabled.12", "xlsm"),
+        (
+            "application/vnd.ms-excel.sheet.binary.macroenabled.12",
+            "xlsb",
+        ),
+        ("application/vnd.ms-excel", "xls"),
+        ("application/vnd.oasis.opendocument.spreadsheet", "ods"),
+        ("text/csv", "csv"),
+        ("text/tab-separated-values", "tsv"),
+    ];
+
+    /// Stream the response body to a temporary file, showing progress on a terminal
+    pub fn fetch(url: &str) -> Result<TempPath> {
+        let mut response = ureq::get(url)
+            .call()
+            .with_context(|| format!("Failed to download {url}"))?;
+        let extension = extension_for(url, response.body().mime_type());
+        let total = response.body().content_length();
+
+        let mut file = tempfile::Builder::new()
+            .prefix("xleak-")
+            .suffix(&format!(".{extension}"))
+            .tempfile()
+            .context("Failed to create a temporary file for the download")?;
+        let mut reader = response.body_mut().as_reader();
+        let mut progress = Progress::new(file_name(url), total);
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = reader
+                .read(&mut buf)
+                .with_context(|| format!("Failed to download {url}"))?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n])
+                .context("Failed to write the downloaded workbook")?;
+            progress.advance(n as u64);
+        }
+        progress.finish();
+
+        Ok(file.into_temp_path())
+    }
+
+    /// Last path segment of the URL, without query or fragment
+    fn file_name(url: &str) -> &str {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        path.rsplit('/').next().unwrap_or(path)
+    }
+
+    /// Extension from the URL path, else from the Content-Type, else xlsx
+    fn extension_for(url: &str, mime_type: Option<&str>) -> &'static str {
+        let from_path = file_name(url)
+            .rsplit_once('.')
+            .and_then(|(_, ext)| EXTENSIONS.iter().find(|e| e.eq_ignore_ascii_case(ext)));
+        let from_mime = mime_type.and_then(|mime| {
+            MIME_TYPES
+                .iter()
+                .find(|(m, _)| m.eq_ignore_ascii_case(mime))
+                .map(|(_, ext)| ext)
+        });
+        from_path.or(from_mime).copied().unwrap_or("xlsx")
+    }
+
+    /// One-line download progress on stderr, only when stderr is a terminal
+    struct Progress<'a> {
+        name: &'a str,
+        total: Option<u64>,
+        done: u64,
+        shown: bool,
+    }
+
+    impl<'a> Progress<'a> {
+        fn new(name: &'a str, total: Option<u64>) -> Self {
+            Self {
+                name,
+                total,
+                done: 0,
+                shown: std::io::stderr().is_terminal(),
+            }
+        }
+
+        fn advance(&mut self, bytes: u64) {
+            self.done += bytes;
+            if !self.shown {
+                return;
+            }
+            let done = format_bytes(self.done as usize);
+            match self.total {
+                Some(total) if total > 0 => eprint!(
+                    "\rDownloading {}: {done} / {} ({}%)",
+                    self.name,
+                    format_bytes(total as usize),
+                    self.done * 100 / total
+                ),
+                _ => eprint!("\rDownloading {}: {done}", self.name),
+            }
+        }
+
+        fn finish(&self) {
+            if self.shown && self.done > 0 {
+                eprintln!();
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_extension_for() {
+            assert_eq!(
+                extension_for("https://example.com/q1/Report.XLSM?token=abc", None),
+                "xlsm"
+            );
+            assert_eq!(
+                extension_for(
+                    "https://example.com/export?id=7",
+                    Some("application/vnd.oasis.opendocument.spreadsheet")
+                ),
+                "ods"
+            );
+            assert_eq!(extension_for("https://example.com/download", None), "xlsx");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_url() {
+        assert!(as_url(Path::new("https://example.com/a.xlsx")).is_some());
+        assert!(as_url(Path::new("HTTP://intranet/report.xls")).is_some());
+        assert!(as_url(Path::new("reports/a.xlsx")).is_none());
+        assert!(as_url(Path::new("ftp://example.com/a.xlsx")).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+//! Writing .xlsx files: exported sheets, or a workbook with cell edits applied (values only)
+
+use crate::progress::Progress;
+use crate::workbook::{CellValue, Workbook};
+use anyhow::{Context, Result};
+use rust_xlsxwriter::{Format, Workbook as XlsxWorkbook, Worksheet};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// New cell values by absolute 0-based (row, col); `Empty` clears the cell
+pub type CellEdits = BTreeMap<(usize, usize), CellValue>;
+
+/// One sheet or Excel table to write with [`write_sheets`]
+pub struct XlsxSheet<'a> {
+    pub name: &'a str,
+    pub headers: &'a [String],
+    pub rows: &'a [Vec<CellValue>],
+}
+
+/// Write each entry as a worksheet of a new .xlsx file: a bold header row, then the rows
+/// with numbers, booleans, and dates kept as such
+pub fn write_sheets(path: &Path, sheets: &[XlsxSheet]) -> Result<()> {
+    let mut xlsx = XlsxWorkbook::new();
+    let formats = CellFormats::new();
+    let bold = Format::new().set_bold();
+    for entry in sheets {
+        let sheet = xlsx.add_worksheet();
+        sheet
+            .set_name(entry.name)
+            .with_context(|| format!("Can't name a sheet '{}' in an .xlsx file", entry.name))?;
+        for (col, header) in entry.headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, header, &bold)?;
+        }
+        let mut progress = Progress::new(format!("Writing '{}'", entry.name), entry.rows.len());
+        for (row, cells) in entry.rows.iter().enumerate() {
+            progress.update(row);
+            for (col, value) in cells.iter().enumerate() {
+                write_cell(sheet, row + 1, col, value, &formats)
+                    .with_context(|| format!("Failed to write a cell of sheet '{}'", entry.name))?;
+            }
+        }
+    }
+
+    xlsx.save(path)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    Ok(())
+}
+
+/// Number formats that make date and time serials display as such
+struct CellFormats {
+    date: Format,
+    datetime: Format,
+    time: Format,
+    duration: Format,
+}
+
+impl CellFormats {
+    fn new() -> Self {
+        Self {
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            time: Format::new().set_num_format("hh:mm:ss"),
+            duration: Format::new().set_num_format("[h]:mm:ss"),
+        }
+    }
+}
+
+/// Write one value at a 0-based position; errors are written as their "#..." text
+fn write_cell(
+    sheet: &mut Worksheet,
+    row: usize,
+    col: usize,
+    value: &CellValue,
+    formats: &CellFormats,
+) -> Result<()> {
+    let (row, col) = (row as u32, col as u16);
+    match value {
+        CellValue::Empty => return Ok(()),
+        CellValue::String(s) => sheet.write_string(row, col, s),
+        CellValue::Int(i) => sheet.write_number(row, col, *i as f64),
+        CellValue::Float(f) => sheet.write_number(row, col, *f),
+        CellValue::Bool(b) => sheet.write_boolean(row, col, *b),
+        CellValue::DateTime(dt) if dt.fract() == 0.0 => {
+            sheet.write_number_with_format(row, col, *dt, &formats.date)
+        }
+        CellValue::DateTime(dt) => sheet.write_number_with_format(row, col, *dt, &formats.datetime),
+        CellValue::Time(t) => sheet.write_number_with_format(row, col, *t, &formats.time),
+        CellValue::Duration(d) => sheet.write_number_with_format(row, col, *d, &formats.duration),
+        CellValue::Error(_) => sheet.write_string(row, col, value.to_raw_string()),
+    }?;
+    Ok(())
+}
+
+/// Interpret typed text the way Excel does for a constant: numbers, TRUE/FALSE, or text.
+/// A leading apostrophe keeps the rest as text ("'007").
+pub fn parse_value(text: &str) -> CellValue {
+    if let Some(rest) = text.strip_prefix('\'') {
+        return CellValue::String(rest.to_string());
+    }
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return CellValue::Empty;
+    }
+    if trimmed.eq_ignore_ascii_case("true") {
+        return CellValue::Bool(true);
+    }
+    if trimmed.eq_ignore_ascii_case("false") {
+        return CellValue::Bool(false);
+    }
+    if let Ok(i) = trimmed.parse::<i64>() {
+        return CellValue::Int(i);
+    }
+    match trimmed.parse::<f64>() {
+        Ok(f) if f.is_finite() => CellValue::Float(f),
+        _ => CellValue::String(text.to_string()),
+    }
+}
+
+/// Write every sheet of `workbook` (hidden ones stay hidden) to a new .xlsx file, with the
+/// edits for each sheet name applied. Cell values only: formulas, styles, and other
+/// package parts are not copied.
+pub fn save_workbook(
+    workbook: &mut Workbook,
+    edits: &HashMap<String, CellEdits>,
+    path: &Path,
+) -> Result<()> {
+    let is_xlsx = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx"));
+    if !is_xlsx {
+        anyhow::bail!("Can only save as .xlsx, not '{}'", path.display());
+    }
+    if let (Ok(target), Ok(source)) = (path.canonicalize(), workbook.path().canonicalize())
+        && target == source
+    {
+        anyhow::bail!(
+            "Won't overwrite '{}': saving keeps values only, so its formulas and formatting \
+             would be lost. Save to a new file instead.",
+            path.display()
+        );
+    }
+
+    let mut xlsx = XlsxWorkbook::new();
+    let formats = CellFormats::new();
+    let mut active_set = false;
+    for (name, visibility) in workbook.sheet_visibility() {
+        let mut cells: CellEdits = workbook
+            .sheet_values(&name)?
+            .into_iter()
+            .map(|(row, col, value)| ((row, col), value))
+            .collect();
+        if let Some(sheet_edits) = edits.get(&name) {
+            cells.extend(sheet_edits.iter().map(|(&pos, value)| (pos, value.clone())));
+        }
+
+        let sheet = xlsx.add_worksheet();
+        sheet
+            .set_name(&name)
+            .with_context(|| format!("Can't name a sheet '{name}' in an .xlsx file"))?;
+        match visibility {
+            "hidden" => {
+                sheet.set_hidden(true);
+            }
+            "very hidden" => {
+                sheet.set_very_hidden(true);
+            }
+            _ if !active_set => {
+                sheet.set_active(true);
+                active_set = true;
+            }
+            _ => {}
+        }
+
+        for (&(row, col), value) in &cells {
+            write_cell(sheet, row, col, value, &formats)
+                .with_context(|| format!("Failed to write a cell of sheet '{name}'"))?;
+        }
+    }
+
+    xlsx.save(path)
+        .with_context(|| format!("Failed to save '{}'", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("42"), CellValue::Int(42));
+        assert_eq!(parse_value(" -1.5 "), CellValue::Float(-1.5));
+        assert_eq!(parse_value("TRUE"), CellValue::Bool(true));
+        assert_eq!(parse_value("false"), CellValue::Bool(false));
+        assert_eq!(parse_value(""), CellValue::Empty);
+        assert_eq!(parse_value("'007"), CellValue::String("007".to_string()));
+        assert_eq!(parse_value("NaN"), CellValue::String("NaN".to_string()));
+        assert_eq!(
+            parse_value("Acme Ltd"),
+            CellValue::String("Acme Ltd".to_string())
+        );
+    }
+
+    #[test]
+    fn test_write_sheets_keeps_types() {
+        let path =
+            std::env::temp_dir().join(format!("xleak-test-{}-export.xlsx", std::process::id()));
+        let headers = vec!["Item".to_string(), "Qty".to_string(), "Paid".to_string()];
+        let rows = vec![
+            vec![
+                CellValue::String("Pens".to_string()),
+                CellValue::Int(3),
+                CellValue::Bool(true),
+            ],
+            vec![CellValue::String("Paper".to_string()), CellValue::Empty],
+        ];
+        let sheets = [XlsxSheet {
+            name: "Orders",
+            headers: &headers,
+            rows: &rows,
+        }];
+        write_sheets(&path, &sheets).unwrap();
+
+        let mut workbook = Workbook::open(&path).unwrap();
+        let sheet = workbook.load_sheet("Orders").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sheet.headers, headers);
+        assert_eq!(
+            sheet.rows,
+            vec![
+                vec![
+                    CellValue::String("Pens".to_string()),
+                    CellValue::Float(3.0),
+                    CellValue::Bool(true),
+                ],
+                vec![
+                    CellValue::String("Paper".to_string()),
+                    CellValue::Empty,
+                    CellValue::Empty,
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_workbook_round_trip() {
+        let dir = std::env::temp_dir();
+        let source = dir.join(format!("xleak-test-{}.csv", std::process::id()));
+        let target = dir.join(format!("xleak-test-{}.xlsx", std::process::id()));
+        std::fs::write(&source, "Item,Qty\nPens,3\nPaper,10\n").unwrap();
+
+        let mut workbook = Workbook::open(&source).unwrap();
+        let name = workbook.sheet_names()[0].clone();
+        let mut sheet_edits = CellEdits::new();
+        sheet_edits.insert((1, 0), CellValue::String("Pencils".to_string()));
+        sheet_edits.insert((2, 1), CellValue::Empty);
+        sheet_edits.insert((3, 0), CellValue::Bool(true));
+        let edits = HashMap::from([(name.clone(), sheet_edits)]);
+        save_workbook(&mut workbook, &edits, &target).unwrap();
+
+        // Saving over the file that's open is refused
+        let mut saved = Workbook::open(&target).unwrap();
+        assert!(save_workbook(&mut saved, &HashMap::new(), &target).is_err());
+        let values = saved.sheet_values(&name).unwrap();
+        std::fs::remove_file(&source).unwrap();
+        std::fs::remove_file(&target).unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                (0, 0, CellValue::String("Item".to_string())),
+                (0, 1, CellValue::String("Qty".to_string())),
+                (1, 0, CellValue::String("Pencils".to_string())),
+                (1, 1, CellValue::Float(3.0)),
+                (2, 0, CellValue::String("Paper".to_string())),
+                (3, 0, CellValue::Bool(true)),
+            ]
+        );
+    }
+}
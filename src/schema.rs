@@ -0,0 +1,167 @@
+//! Column type inference (`--schema`)
+
+use crate::workbook::CellValue;
+use serde::Serialize;
+
+/// The type every non-empty cell of a column fits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// Whole numbers, including whole floats (Excel stores all numbers as floats)
+    Integer,
+    Float,
+    Bool,
+    Date,
+    /// Time of day
+    Time,
+    Duration,
+    /// Text and error values; also columns with no values at all
+    String,
+    Mixed,
+}
+
+impl ColumnType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Date => "date",
+            Self::Time => "time",
+            Self::Duration => "duration",
+            Self::String => "string",
+            Self::Mixed => "mixed",
+        }
+    }
+
+    /// The type of a column holding values of both types; integers widen to floats
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Float) | (Self::Float, Self::Integer) => Self::Float,
+            _ => Self::Mixed,
+        }
+    }
+}
+
+/// Inferred type of one column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: ColumnType,
+    /// Whether any data cell is empty
+    pub nullable: bool,
+}
+
+/// Infers each column's type from its data cells; rows shorter than the header count as
+/// empty cells
+pub fn infer_schema(headers: &[String], rows: &[Vec<CellValue>]) -> Vec<ColumnSchema> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(col, name)| {
+            let mut ty = None;
+            let mut nullable = false;
+            for cell in rows
+                .iter()
+                .map(|row| row.get(col).unwrap_or(&CellValue::Empty))
+            {
+                let Some(cell_type) = cell_type(cell) else {
+                    nullable = true;
+                    continue;
+                };
+                ty = Some(ty.map_or(cell_type, |ty: ColumnType| ty.merge(cell_type)));
+            }
+            ColumnSchema {
+                name: name.clone(),
+                ty: ty.unwrap_or(ColumnType::String),
+                nullable,
+            }
+        })
+        .collect()
+}
+
+/// None for empty cells
+fn cell_type(cell: &CellValue) -> Option<ColumnType> {
+    Some(match cell {
+        CellValue::Empty => return None,
+        CellValue::String(s) if s.is_empty() => return None,
+        CellValue::Int(_) => ColumnType::Integer,
+        CellValue::Float(f) if f.fract() == 0.0 => ColumnType::Integer,
+        CellValue::Float(_) => ColumnType::Float,
+        CellValue::Bool(_) => ColumnType::Bool,
+        CellValue::DateTime(_) => ColumnType::Date,
+        CellValue::Time(_) => ColumnType::Time,
+        CellValue::Duration(_) => ColumnType::Duration,
+        CellValue::String(_) | CellValue::Error(_) => ColumnType::String,
+    })
+}
+
+/// Aligned "Column  Type  Nullable" lines
+pub fn describe(schema: &[ColumnSchema]) -> Vec<String> {
+    let width = schema
+        .iter()
+        .map(|c| c.name.chars().count())
+        .chain(["Column".len()])
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![format!("{:width$}  {:8}  Nullable", "Column", "Type")];
+    lines.extend(schema.iter().map(|c| {
+        format!(
+            "{:width$}  {:8}  {}",
+            c.name,
+            c.ty.label(),
+            if c.nullable { "yes" } else { "no" }
+        )
+    }));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_schema() {
+        let headers: Vec<String> = ["Qty", "Price", "Paid", "When", "Note", "Code"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let rows = vec![
+            vec![
+                CellValue::Float(3.0),
+                CellValue::Int(2),
+                CellValue::Bool(true),
+                CellValue::DateTime(45306.0),
+                CellValue::Empty,
+                CellValue::String("A1".to_string()),
+            ],
+            vec![
+                CellValue::Int(4),
+                CellValue::Float(2.5),
+                CellValue::Bool(false),
+                CellValue::DateTime(45307.0),
+                CellValue::String(String::new()),
+                CellValue::Int(7),
+            ],
+            vec![CellValue::Int(5)],
+        ];
+
+        let summary: Vec<(&str, bool)> = infer_schema(&headers, &rows)
+            .iter()
+            .map(|c| (c.ty.label(), c.nullable))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("integer", false),
+                ("float", true),
+                ("bool", true),
+                ("date", true),
+                ("string", true),
+                ("mixed", true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Where the TUI was left in each workbook, so reopening one picks up there. Sessions are
+//! kept in `sessions.json` in the state directory (~/.local/state/xleak on Linux).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sessions kept; the least recently saved are dropped beyond this
+const MAX_SESSIONS: usize = 200;
+
+/// The TUI's place in one workbook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub sheet: String,
+    /// Cursor (data row, column)
+    pub cursor: (usize, usize),
+    /// Vertical and horizontal scroll offsets
+    pub scroll: (usize, usize),
+    /// Row filter as typed, e.g. "Amount > 100"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// Widths set with the resize keys, by sheet name and column
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub column_widths: HashMap<String, HashMap<usize, usize>>,
+    /// Bookmarked cells by letter
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub marks: BTreeMap<char, Mark>,
+    /// Unix time of the save, for dropping old sessions
+    #[serde(default)]
+    pub saved_at: i64,
+}
+
+/// A bookmarked cell (`m` + letter in the TUI)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mark {
+    pub sheet: String,
+    /// Data row and column, like the cursor
+    pub row: usize,
+    pub col: usize,
+}
+
+/// A workbook's saved session, and the file it's saved under again on quit
+pub struct Resume {
+    pub file: PathBuf,
+    pub session: Option<Session>,
+}
+
+impl Resume {
+    /// The saved session for a workbook file, if any
+    pub fn load(file: &Path) -> Self {
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        let session = store_path().and_then(|store| load_from(&store, &file));
+        Self { file, session }
+    }
+
+    pub fn save(&self, session: Session) -> Result<()> {
+        let store = store_path().context("No state directory to save the session in")?;
+        save_to(&store, &self.file, session)
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    let dir = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    Some(dir.join("xleak").join("sessions.json"))
+}
+
+fn read_store(store: &Path) -> HashMap<String, Session> {
+    fs::read_to_string(store)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn load_from(store: &Path, file: &Path) -> Option<Session> {
+    read_store(store).remove(&file.to_string_lossy().into_owned())
+}
+
+fn save_to(store: &Path, file: &Path, mut session: Session) -> Result<()> {
+    let mut sessions = read_store(store);
+    session.saved_at = chrono::Utc::now().timestamp();
+    sessions.insert(file.to_string_lossy().into_owned(), session);
+    if sessions.len() > MAX_SESSIONS {
+        let mut saved: Vec<(i64, String)> = sessions
+            .iter()
+            .map(|(file, session)| (session.saved_at, file.clone()))
+            .collect();
+        saved.sort();
+        for (_, file) in &saved[..sessions.len() - MAX_SESSIONS] {
+            sessions.remove(file);
+        }
+    }
+
+    if let Some(dir) = store.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(&sessions)?;
+    fs::write(store, json).with_context(|| format!("Failed to write {}", store.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let store =
+            std::env::temp_dir().join(format!("xleak-sessions-{}.json", std::process::id()));
+        let file = Path::new("/reports/sales.xlsx");
+        assert_eq!(load_from(&store, file), None);
+
+        let session = Session {
+            sheet: "Q3".to_string(),
+            cursor: (120, 4),
+            scroll: (100, 2),
+            filter: Some("Region = West".to_string()),
+            column_widths: HashMap::from([("Q3".to_string(), HashMap::from([(2, 40)]))]),
+            marks: BTreeMap::from([(
+                'a',
+                Mark {
+                    sheet: "Summary".to_string(),
+                    row: 3,
+                    col: 1,
+                },
+            )]),
+            saved_at: 0,
+        };
+        save_to(&store, file, session.clone()).unwrap();
+        save_to(&store, Path::new("/reports/other.xlsx"), session.clone()).unwrap();
+
+        let loaded = load_from(&store, file).unwrap();
+        assert!(loaded.saved_at > 0);
+        assert_eq!(
+            Session {
+                saved_at: 0,
+                ..loaded
+            },
+            session
+        );
+        fs::remove_file(&store).unwrap();
+    }
+}
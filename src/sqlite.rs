@@ -0,0 +1,180 @@
+//! Writing sheets and tables into a SQLite database
+
+use crate::display::{ExportOptions, record_keys};
+use crate::progress::Progress;
+use crate::workbook::CellValue;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params_from_iter, types::Value};
+use std::path::Path;
+
+/// One sheet or Excel table to write as a database table
+pub struct SqliteTable<'a> {
+    pub name: &'a str,
+    pub headers: &'a [String],
+    pub rows: &'a [Vec<CellValue>],
+}
+
+/// Create (or replace) one database table per entry, in a single transaction
+pub fn write_tables(path: &Path, tables: &[SqliteTable], options: &ExportOptions) -> Result<()> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database '{}'", path.display()))?;
+    let tx = conn.transaction()?;
+
+    for table in tables {
+        let columns = record_keys(table.headers);
+        let types: Vec<&str> = (0..columns.len())
+            .map(|col| column_type(table.rows, col))
+            .collect();
+        let name = quote_ident(table.name);
+
+        let definitions: Vec<String> = columns
+            .iter()
+            .zip(&types)
+            .map(|(column, ty)| format!("{} {ty}", quote_ident(column)))
+            .collect();
+        tx.execute(&format!("DROP TABLE IF EXISTS {name}"), [])?;
+        tx.execute(
+            &format!("CREATE TABLE {name} ({})", definitions.join(", ")),
+            [],
+        )
+        .with_context(|| format!("Failed to create table for '{}'", table.name))?;
+
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!("INSERT INTO {name} VALUES ({placeholders})"))?;
+        let mut progress = Progress::new(format!("Writing '{}'", table.name), table.rows.len());
+        for (i, row) in table.rows.iter().enumerate() {
+            progress.update(i);
+            let values = (0..columns.len()).map(|col| sql_value(row.get(col), types[col], options));
+            insert.execute(params_from_iter(values))?;
+        }
+    }
+
+    tx.commit().context("Failed to write SQLite database")?;
+    Ok(())
+}
+
+/// Double-quoted SQL identifier, so sheet and header names can contain anything
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Largest magnitude up to which every whole float is exact, so it converts to an i64
+/// without rounding or saturating
+const MAX_EXACT_INT: f64 = 9_007_199_254_740_992.0; // 2^53
+
+/// Whether a float is a whole number an INTEGER column can hold exactly
+fn is_exact_int(f: f64) -> bool {
+    f.fract() == 0.0 && f.abs() <= MAX_EXACT_INT
+}
+
+/// INTEGER for whole numbers and booleans, REAL for other numbers (and whole floats past
+/// 2^53), TEXT otherwise
+fn column_type(rows: &[Vec<CellValue>], col: usize) -> &'static str {
+    let mut ty = None;
+    for cell in rows.iter().filter_map(|r| r.get(col)) {
+        let cell_ty = match cell {
+            CellValue::Empty => continue,
+            CellValue::Int(_) | CellValue::Bool(_) => "INTEGER",
+            CellValue::Float(f) if is_exact_int(*f) => "INTEGER",
+            CellValue::Float(_) => "REAL",
+            _ => return "TEXT",
+        };
+        ty = match (ty, cell_ty) {
+            (None, t) => Some(t),
+            (Some("REAL"), _) | (_, "REAL") => Some("REAL"),
+            (Some(t), _) => Some(t),
+        };
+    }
+    ty.unwrap_or("TEXT")
+}
+
+/// Dates become ISO strings and errors their "#..." text (NULL with `--errors-as-empty`)
+fn sql_value(cell: Option<&CellValue>, ty: &str, options: &ExportOptions) -> Value {
+    match cell {
+        None | Some(CellValue::Empty) => Value::Null,
+        Some(CellValue::Error(_)) if options.errors_as_empty => Value::Null,
+        Some(CellValue::Int(i)) => Value::Integer(*i),
+        Some(CellValue::Bool(b)) => Value::Integer(*b as i64),
+        Some(CellValue::Float(f)) if ty == "INTEGER" && is_exact_int(*f) => {
+            Value::Integer(*f as i64)
+        }
+        Some(CellValue::Float(f)) if ty != "TEXT" => Value::Real(*f),
+        Some(other) => Value::Text(other.to_export_string(options)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_type() {
+        let rows = vec![
+            vec![
+                CellValue::Int(1),
+                CellValue::Float(1.5),
+                CellValue::String("a".to_string()),
+            ],
+            vec![CellValue::Float(2.0), CellValue::Int(2), CellValue::Int(3)],
+            vec![CellValue::Empty, CellValue::Empty, CellValue::Empty],
+        ];
+        assert_eq!(column_type(&rows, 0), "INTEGER");
+        assert_eq!(column_type(&rows, 1), "REAL");
+        assert_eq!(column_type(&rows, 2), "TEXT");
+        assert_eq!(column_type(&rows, 3), "TEXT");
+
+        // Whole floats past 2^53 would saturate as an i64
+        let big = vec![vec![CellValue::Float(1e20)], vec![CellValue::Int(1)]];
+        assert_eq!(column_type(&big, 0), "REAL");
+        assert_eq!(
+            sql_value(
+                Some(&CellValue::Float(1e20)),
+                "REAL",
+                &ExportOptions::default()
+            ),
+            Value::Real(1e20)
+        );
+    }
+
+    #[test]
+    fn test_write_tables_round_trip() {
+        let path = std::env::temp_dir().join(format!("xleak-test-{}.sqlite", std::process::id()));
+        let headers = vec!["Name".to_string(), "Qty".to_string(), "Name".to_string()];
+        let rows = vec![
+            vec![
+                CellValue::String("O'Brien".to_string()),
+                CellValue::Float(3.0),
+                CellValue::Bool(true),
+            ],
+            vec![CellValue::String("Lee".to_string()), CellValue::Empty],
+        ];
+        let tables = [SqliteTable {
+            name: "Q1 \"Sales\"",
+            headers: &headers,
+            rows: &rows,
+        }];
+        let options = ExportOptions::default();
+        write_tables(&path, &tables, &options).unwrap();
+        // Writing again replaces the table instead of failing
+        write_tables(&path, &tables, &options).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let total: i64 = conn
+            .query_row("SELECT SUM(Qty) FROM \"Q1 \"\"Sales\"\"\"", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        let flag: i64 = conn
+            .query_row(
+                "SELECT Name_2 FROM \"Q1 \"\"Sales\"\"\" WHERE Name = 'O''Brien'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(total, 3);
+        assert_eq!(flag, 1);
+    }
+}
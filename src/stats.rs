@@ -0,0 +1,243 @@
+//! Per-column summaries for quick data profiling (`--stats`)
+
+use crate::workbook::CellValue;
+use std::collections::HashMap;
+
+/// Most frequent values listed for text columns
+const TOP_VALUES: usize = 5;
+
+/// Summary of one column's data cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub name: String,
+    /// "number", "date", "time", "duration", "text", "boolean", "error", "mixed", or "empty"
+    pub kind: &'static str,
+    pub count: usize,
+    pub non_empty: usize,
+    pub distinct: usize,
+    pub numbers: Option<NumberStats>,
+    /// Earliest and latest date, as Excel serials
+    pub dates: Option<(f64, f64)>,
+    /// Most frequent text values with their counts, most common first
+    pub top: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// Sample standard deviation; None with fewer than two values
+    pub stddev: Option<f64>,
+}
+
+/// Summarizes every column; rows shorter than the header count as empty cells
+pub fn column_stats(headers: &[String], rows: &[Vec<CellValue>]) -> Vec<ColumnStats> {
+    (0..headers.len())
+        .map(|col| {
+            let cells = rows
+                .iter()
+                .map(|row| row.get(col).unwrap_or(&CellValue::Empty));
+            summarize(&headers[col], cells)
+        })
+        .collect()
+}
+
+fn summarize<'a>(name: &str, cells: impl Iterator<Item = &'a CellValue>) -> ColumnStats {
+    let mut count = 0;
+    let mut numbers = Vec::new();
+    let mut dates = Vec::new();
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    let mut text: HashMap<String, usize> = HashMap::new();
+    let mut kinds = Vec::new();
+
+    for cell in cells {
+        count += 1;
+        let kind = match cell {
+            CellValue::Empty => continue,
+            CellValue::String(s) if s.is_empty() => continue,
+            CellValue::Int(i) => {
+                numbers.push(*i as f64);
+                "number"
+            }
+            CellValue::Float(f) => {
+                numbers.push(*f);
+                "number"
+            }
+            CellValue::DateTime(d) => {
+                dates.push(*d);
+                "date"
+            }
+            CellValue::String(s) => {
+                *text.entry(s.clone()).or_default() += 1;
+                "text"
+            }
+            CellValue::Time(_) => "time",
+            CellValue::Duration(_) => "duration",
+            CellValue::Bool(_) => "boolean",
+            CellValue::Error(_) => "error",
+        };
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+        *frequencies.entry(cell.to_raw_string()).or_default() += 1;
+    }
+
+    let mut top: Vec<(String, usize)> = text.into_iter().collect();
+    // Most common first; ties alphabetical so output is stable
+    top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top.truncate(TOP_VALUES);
+
+    ColumnStats {
+        name: name.to_string(),
+        kind: match kinds[..] {
+            [] => "empty",
+            [kind] => kind,
+            _ => "mixed",
+        },
+        count,
+        non_empty: frequencies.values().sum(),
+        distinct: frequencies.len(),
+        numbers: number_stats(&mut numbers),
+        dates: dates
+            .iter()
+            .copied()
+            .reduce(f64::min)
+            .zip(dates.iter().copied().reduce(f64::max)),
+        top,
+    }
+}
+
+fn number_stats(values: &mut [f64]) -> Option<NumberStats> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    let mean = values.iter().sum::<f64>() / n as f64;
+    let median = if n.is_multiple_of(2) {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    } else {
+        values[n / 2]
+    };
+    let stddev = (n > 1).then(|| {
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        variance.sqrt()
+    });
+    Some(NumberStats {
+        min: values[0],
+        max: values[n - 1],
+        mean,
+        median,
+        stddev,
+    })
+}
+
+/// Whole numbers without decimals, others rounded to 4 places with trailing zeros trimmed
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{value:.0}");
+    }
+    let fixed = format!("{value:.4}");
+    fixed
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+impl ColumnStats {
+    /// Multi-line human-readable summary
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("{} ({})", self.name, self.kind),
+            format!(
+                "  Count: {}  Non-empty: {}  Distinct: {}",
+                self.count, self.non_empty, self.distinct
+            ),
+        ];
+        if let Some(n) = &self.numbers {
+            lines.push(format!(
+                "  Min: {}  Max: {}  Mean: {}  Median: {}  Std dev: {}",
+                format_number(n.min),
+                format_number(n.max),
+                format_number(n.mean),
+                format_number(n.median),
+                n.stddev.map_or("-".to_string(), format_number)
+            ));
+        }
+        if let Some((earliest, latest)) = self.dates {
+            lines.push(format!(
+                "  Earliest: {}  Latest: {}",
+                CellValue::DateTime(earliest).to_raw_string(),
+                CellValue::DateTime(latest).to_raw_string()
+            ));
+        }
+        if !self.top.is_empty() {
+            let top: Vec<String> = self
+                .top
+                .iter()
+                .map(|(value, count)| format!("{value} ({count})"))
+                .collect();
+            lines.push(format!("  Top: {}", top.join(", ")));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_column_stats() {
+        let headers = vec!["Qty".to_string()];
+        let rows: Vec<Vec<CellValue>> = [
+            CellValue::Int(4),
+            CellValue::Float(1.5),
+            CellValue::Empty,
+            CellValue::Int(4),
+            CellValue::Float(0.5),
+        ]
+        .into_iter()
+        .map(|c| vec![c])
+        .collect();
+
+        let stats = &column_stats(&headers, &rows)[0];
+        assert_eq!(stats.kind, "number");
+        assert_eq!((stats.count, stats.non_empty, stats.distinct), (5, 4, 3));
+        let numbers = stats.numbers.as_ref().unwrap();
+        assert_eq!((numbers.min, numbers.max), (0.5, 4.0));
+        assert_eq!(numbers.mean, 2.5);
+        assert_eq!(numbers.median, 2.75);
+        assert_eq!(format_number(numbers.stddev.unwrap()), "1.7795");
+        assert!(stats.top.is_empty());
+    }
+
+    #[test]
+    fn test_text_column_top_values() {
+        let headers = vec!["City".to_string(), "Missing".to_string()];
+        let rows: Vec<Vec<CellValue>> = ["Rome", "Paris", "Rome", "Oslo", "Paris", "Rome"]
+            .iter()
+            .map(|s| vec![CellValue::String(s.to_string())])
+            .chain([vec![CellValue::Int(7)]])
+            .collect();
+
+        let stats = column_stats(&headers, &rows);
+        assert_eq!(stats[0].kind, "mixed");
+        assert_eq!(
+            stats[0].top,
+            vec![
+                ("Rome".to_string(), 3),
+                ("Paris".to_string(), 2),
+                ("Oslo".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            stats[0].describe()[3],
+            "  Top: Rome (3), Paris (2), Oslo (1)"
+        );
+        assert_eq!(stats[1].kind, "empty");
+        assert_eq!(stats[1].count, 7);
+    }
+}
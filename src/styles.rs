@@ -0,0 +1,620 @@
+//! Cell fill, font, alignment, and currency number formats from styles.xml, and the
+//! colors they name: RGB values, the workbook theme's colors (with tints), and the legacy
+//! indexed palette
+
+use crate::ooxml::{Package, XmlElement, parse_cell_ref};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+pub type Rgb = (u8, u8, u8);
+
+/// Text and background look of a cell (a `xf` or conditional `dxf` record)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CellStyle {
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl CellStyle {
+    /// Nothing that changes how the cell looks
+    pub fn is_plain(&self) -> bool {
+        *self == CellStyle::default()
+    }
+}
+
+/// A horizontal alignment set on a cell; "General" (numbers right, text left) is no
+/// alignment at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HorizontalAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl HorizontalAlign {
+    /// From an `<alignment horizontal="...">` value; justified and distributed text reads
+    /// from the left, and "fill" and "general" leave the cell as it is
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "left" | "justify" | "distributed" => Some(HorizontalAlign::Left),
+            "center" | "centerContinuous" => Some(HorizontalAlign::Center),
+            "right" => Some(HorizontalAlign::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Excel's default indexed colors (`indexed="0"` to `"63"`); 64 and 65 are the system
+/// foreground and background, left to the terminal
+const INDEXED: [u32; 64] = [
+    0x000000, 0xFFFFFF, 0xFF0000, 0x00FF00, 0x0000FF, 0xFFFF00, 0xFF00FF, 0x00FFFF, //
+    0x000000, 0xFFFFFF, 0xFF0000, 0x00FF00, 0x0000FF, 0xFFFF00, 0xFF00FF, 0x00FFFF, //
+    0x800000, 0x008000, 0x000080, 0x808000, 0x800080, 0x008080, 0xC0C0C0, 0x808080, //
+    0x9999FF, 0x993366, 0xFFFFCC, 0xCCFFFF, 0x660066, 0xFF8080, 0x0066CC, 0xCCCCFF, //
+    0x000080, 0xFF00FF, 0xFFFF00, 0x00FFFF, 0x800080, 0x800000, 0x008080, 0x0000FF, //
+    0x00CCFF, 0xCCFFFF, 0xCCFFCC, 0xFFFF99, 0x99CCFF, 0xFF99CC, 0xCC99FF, 0xFFCC99, //
+    0x3366FF, 0x33CCCC, 0x99CC00, 0xFFCC00, 0xFF9900, 0xFF6600, 0x666699, 0x969696, //
+    0x003366, 0x339966, 0x003300, 0x333300, 0x993300, 0x993366, 0x333399, 0x333333, //
+];
+
+/// The colors a workbook refers to by number
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    /// Theme colors in `theme="N"` order: light 1, dark 1, light 2, dark 2, accents 1-6,
+    /// hyperlink, followed hyperlink
+    theme: Vec<Rgb>,
+}
+
+impl Palette {
+    /// Reads the workbook theme's color scheme; empty when there's no theme part
+    pub fn load(package: &mut Package) -> Result<Self> {
+        match package.workbook_part("theme")? {
+            Some(part) => Ok(Self::from_theme(&package.read_xml(&part)?)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn from_theme(theme: &XmlElement) -> Self {
+        let Some(scheme) = theme
+            .child("themeElements")
+            .and_then(|elements| elements.child("clrScheme"))
+        else {
+            return Self::default();
+        };
+        let color = |name: &str| -> Option<Rgb> {
+            let el = scheme.child(name)?.children.first()?;
+            let hex = match el.name.as_str() {
+                "srgbClr" => el.attr("val")?,
+                "sysClr" => el.attr("lastClr")?,
+                _ => return None,
+            };
+            parse_hex(hex)
+        };
+        // The scheme lists dark before light, but theme numbers put light first
+        let names = [
+            "lt1", "dk1", "lt2", "dk2", "accent1", "accent2", "accent3", "accent4", "accent5",
+            "accent6", "hlink", "folHlink",
+        ];
+        let theme: Option<Vec<Rgb>> = names.iter().map(|name| color(name)).collect();
+        Self {
+            theme: theme.unwrap_or_default(),
+        }
+    }
+
+    /// The color of a `color`, `fgColor`, or `bgColor` element; None for automatic and
+    /// system colors, or theme colors without a theme
+    pub fn color(&self, el: &XmlElement) -> Option<Rgb> {
+        let base = if let Some(hex) = el.attr("rgb") {
+            parse_hex(hex)?
+        } else if let Some(index) = el.attr("theme") {
+            *self.theme.get(index.parse::<usize>().ok()?)?
+        } else if let Some(index) = el.attr("indexed") {
+            let rgb = *INDEXED.get(index.parse::<usize>().ok()?)?;
+            ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+        } else {
+            return None;
+        };
+        match el.attr("tint").and_then(|t| t.parse::<f64>().ok()) {
+            Some(tint) if tint != 0.0 => Some(apply_tint(base, tint)),
+            _ => Some(base),
+        }
+    }
+}
+
+/// "FFRRGGBB" or "RRGGBB"
+fn parse_hex(hex: &str) -> Option<Rgb> {
+    let hex = hex.get(hex.len().checked_sub(6)?..)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Lighten (positive tint) or darken (negative) a color the way Excel does, by scaling
+/// its HSL lightness
+fn apply_tint((r, g, b): Rgb, tint: f64) -> Rgb {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let lightness = (max + min) / 2.0;
+    let (hue, saturation) = if max == min {
+        (0.0, 0.0)
+    } else {
+        let d = max - min;
+        let s = if lightness > 0.5 {
+            d / (2.0 - max - min)
+        } else {
+            d / (max + min)
+        };
+        let h = if max == r {
+            (g - b) / d + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / d + 2.0
+        } else {
+            (r - g) / d + 4.0
+        };
+        (h / 6.0, s)
+    };
+    let lightness = if tint < 0.0 {
+        lightness * (1.0 + tint)
+    } else {
+        lightness * (1.0 - tint) + tint
+    };
+
+    let channel = |t: f64| -> u8 {
+        let q = if lightness < 0.5 {
+            lightness * (1.0 + saturation)
+        } else {
+            lightness + saturation - lightness * saturation
+        };
+        let p = 2.0 * lightness - q;
+        let t = t.rem_euclid(1.0);
+        let v = if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        };
+        (v * 255.0).round().clamp(0.0, 255.0) as u8
+    };
+    (
+        channel(hue + 1.0 / 3.0),
+        channel(hue),
+        channel(hue - 1.0 / 3.0),
+    )
+}
+
+/// The look a `font` and `fill` give. Cell fills keep their color in `fgColor`, while
+/// conditional (`dxf`) fills use `bgColor`.
+pub(crate) fn style_of(
+    font: Option<&XmlElement>,
+    fill: Option<&XmlElement>,
+    palette: &Palette,
+    differential: bool,
+) -> CellStyle {
+    let flag = |name: &str| {
+        font.and_then(|f| f.child(name))
+            .is_some_and(|el| !matches!(el.attr("val"), Some("0") | Some("false")))
+    };
+    let pattern = fill.and_then(|f| f.child("patternFill"));
+    let bg = pattern.and_then(|p| {
+        let (first, second) = if differential {
+            ("bgColor", "fgColor")
+        } else {
+            // Only solid cell fills show their color; "none" and "gray125" are the defaults
+            if p.attr("patternType") != Some("solid") {
+                return None;
+            }
+            ("fgColor", "bgColor")
+        };
+        p.child(first)
+            .or_else(|| p.child(second))
+            .and_then(|c| palette.color(c))
+    });
+    CellStyle {
+        fg: font
+            .and_then(|f| f.child("color"))
+            .and_then(|c| palette.color(c)),
+        bg,
+        bold: flag("b"),
+        italic: flag("i"),
+    }
+}
+
+/// The `dxfs` records of styles.xml (used by conditional formatting), in index order
+pub(crate) fn differential_styles(styles: &XmlElement, palette: &Palette) -> Vec<CellStyle> {
+    let Some(dxfs) = styles.child("dxfs") else {
+        return Vec::new();
+    };
+    dxfs.children_named("dxf")
+        .map(|dxf| style_of(dxf.child("font"), dxf.child("fill"), palette, true))
+        .collect()
+}
+
+/// The look of each `cellXfs` record, by the index cells refer to with `s="N"`. A font
+/// color matching the workbook's default font is dropped on unfilled cells, so plain
+/// black text keeps the terminal's own color.
+fn cell_formats(styles: &XmlElement, palette: &Palette) -> Vec<CellStyle> {
+    let list = |parent: &str, name: &'static str| -> Vec<&XmlElement> {
+        styles
+            .child(parent)
+            .map(|p| p.children_named(name).collect())
+            .unwrap_or_default()
+    };
+    let (fonts, fills) = (list("fonts", "font"), list("fills", "fill"));
+    let default_fg = fonts
+        .first()
+        .and_then(|f| f.child("color"))
+        .and_then(|c| palette.color(c));
+    list("cellXfs", "xf")
+        .into_iter()
+        .map(|xf| {
+            let index = |attr: &str| xf.attr(attr).and_then(|i| i.parse::<usize>().ok());
+            let font = index("fontId").and_then(|i| fonts.get(i).copied());
+            let fill = index("fillId").and_then(|i| fills.get(i).copied());
+            let mut style = style_of(font, fill, palette, false);
+            if style.bg.is_none() && style.fg == default_fg {
+                style.fg = None;
+            }
+            style
+        })
+        .collect()
+}
+
+/// The horizontal alignment of each `cellXfs` record, by the index cells refer to
+fn cell_alignments(styles: &XmlElement) -> Vec<Option<HorizontalAlign>> {
+    let Some(xfs) = styles.child("cellXfs") else {
+        return Vec::new();
+    };
+    xfs.children_named("xf")
+        .map(|xf| {
+            xf.child("alignment")
+                .and_then(|a| a.attr("horizontal"))
+                .and_then(HorizontalAlign::parse)
+        })
+        .collect()
+}
+
+/// Calls `f` with the 0-based absolute (row, col) and alignment of every cell of a sheet
+/// that has a style record; `Ok(false)` without reading the sheet when no style aligns
+fn scan_alignments(
+    package: &mut Package,
+    sheet_name: &str,
+    mut f: impl FnMut((u32, u32), Option<HorizontalAlign>),
+) -> Result<bool> {
+    let Some(styles_part) = package.workbook_part("styles")? else {
+        return Ok(false);
+    };
+    let alignments = cell_alignments(&package.read_xml(&styles_part)?);
+    if alignments.iter().all(Option::is_none) {
+        return Ok(false);
+    }
+    let part = package.worksheet_part(sheet_name)?;
+    package.scan_elements(&part, &["c"], |el| {
+        if let Some(pos) = el.attr("r").and_then(parse_cell_ref) {
+            let style = el.attr("s").and_then(|s| s.parse::<usize>().ok());
+            f(
+                pos,
+                style.and_then(|s| alignments.get(s).copied().flatten()),
+            );
+        }
+    })?;
+    Ok(true)
+}
+
+/// Reads the horizontal alignment of every aligned cell in a sheet, by 0-based absolute
+/// (row, col)
+pub fn load_cell_alignments(
+    package: &mut Package,
+    sheet_name: &str,
+) -> Result<HashMap<(u32, u32), HorizontalAlign>> {
+    let mut cells = HashMap::new();
+    scan_alignments(package, sheet_name, |pos, align| {
+        if let Some(align) = align {
+            cells.insert(pos, align);
+        }
+    })?;
+    Ok(cells)
+}
+
+/// The alignment of each column, by 0-based absolute column: the one set on most of the
+/// column's cells from `first_row` down (headers above it are often centered on their own)
+pub fn load_column_alignments(
+    package: &mut Package,
+    sheet_name: &str,
+    first_row: u32,
+) -> Result<HashMap<u32, HorizontalAlign>> {
+    // Per column: cells seen, and how many have each alignment
+    let mut counts: HashMap<u32, (usize, HashMap<HorizontalAlign, usize>)> = HashMap::new();
+    scan_alignments(package, sheet_name, |(row, col), align| {
+        if row < first_row {
+            return;
+        }
+        let (cells, aligned) = counts.entry(col).or_default();
+        *cells += 1;
+        if let Some(align) = align {
+            *aligned.entry(align).or_default() += 1;
+        }
+    })?;
+    Ok(counts
+        .into_iter()
+        .filter_map(|(col, (cells, aligned))| {
+            let (align, n) = aligned.into_iter().max_by_key(|&(_, n)| n)?;
+            (n * 2 > cells).then_some((col, align))
+        })
+        .collect())
+}
+
+/// Built-in number formats that show a currency symbol: `$#,##0` and friends (5-8) and
+/// the accounting formats with a symbol (42, 44)
+const CURRENCY_FORMAT_IDS: &[usize] = &[5, 6, 7, 8, 42, 44];
+
+/// Whether a custom number format shows a currency symbol: one written out ("€#,##0.00",
+/// "\"$\"#,##0") or a `[$€-407]` tag. `[$-409]` tags only name a locale.
+fn is_currency_code(code: &str) -> bool {
+    let mut rest = code;
+    while let Some(ch) = rest.chars().next() {
+        if ch == '[' {
+            let (tag, after) = rest[1..].split_once(']').unwrap_or((&rest[1..], ""));
+            if tag
+                .strip_prefix('$')
+                .is_some_and(|t| !t.is_empty() && !t.starts_with('-'))
+            {
+                return true;
+            }
+            rest = after;
+            continue;
+        }
+        if matches!(ch, '$' | '€' | '£' | '¥' | '₹' | '₩' | '₽' | '¤') {
+            return true;
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    false
+}
+
+/// Whether each `cellXfs` record shows numbers as an amount of money, by the index cells
+/// refer to
+fn currency_formats(styles: &XmlElement) -> Vec<bool> {
+    let custom: HashMap<usize, bool> = styles
+        .child("numFmts")
+        .map(|fmts| {
+            fmts.children_named("numFmt")
+                .filter_map(|fmt| {
+                    let id = fmt.attr("numFmtId")?.parse().ok()?;
+                    Some((id, is_currency_code(fmt.attr("formatCode")?)))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let Some(xfs) = styles.child("cellXfs") else {
+        return Vec::new();
+    };
+    xfs.children_named("xf")
+        .map(|xf| {
+            xf.attr("numFmtId")
+                .and_then(|id| id.parse::<usize>().ok())
+                .is_some_and(|id| {
+                    custom
+                        .get(&id)
+                        .copied()
+                        .unwrap_or(CURRENCY_FORMAT_IDS.contains(&id))
+                })
+        })
+        .collect()
+}
+
+/// Calls `f` with the 0-based absolute (row, col) of every cell of a sheet and whether
+/// its number format is a currency one; `Ok(false)` without reading the sheet when no
+/// style is
+fn scan_currency(
+    package: &mut Package,
+    sheet_name: &str,
+    mut f: impl FnMut((u32, u32), bool),
+) -> Result<bool> {
+    let Some(styles_part) = package.workbook_part("styles")? else {
+        return Ok(false);
+    };
+    let currency = currency_formats(&package.read_xml(&styles_part)?);
+    if !currency.contains(&true) {
+        return Ok(false);
+    }
+    let part = package.worksheet_part(sheet_name)?;
+    package.scan_elements(&part, &["c"], |el| {
+        if let Some(pos) = el.attr("r").and_then(parse_cell_ref) {
+            let style = el.attr("s").and_then(|s| s.parse::<usize>().ok());
+            f(pos, style.is_some_and(|s| currency.get(s) == Some(&true)));
+        }
+    })?;
+    Ok(true)
+}
+
+/// The cells of a sheet with a currency number format, by 0-based absolute (row, col)
+pub fn load_currency_cells(package: &mut Package, sheet_name: &str) -> Result<HashSet<(u32, u32)>> {
+    let mut cells = HashSet::new();
+    scan_currency(package, sheet_name, |pos, currency| {
+        if currency {
+            cells.insert(pos);
+        }
+    })?;
+    Ok(cells)
+}
+
+/// The columns most of whose cells from `first_row` down have a currency number format,
+/// by 0-based absolute column
+pub fn load_currency_columns(
+    package: &mut Package,
+    sheet_name: &str,
+    first_row: u32,
+) -> Result<HashSet<u32>> {
+    // Per column: cells seen, and how many are currency
+    let mut counts: HashMap<u32, (usize, usize)> = HashMap::new();
+    scan_currency(package, sheet_name, |(row, col), currency| {
+        if row < first_row {
+            return;
+        }
+        let (cells, money) = counts.entry(col).or_default();
+        *cells += 1;
+        *money += usize::from(currency);
+    })?;
+    Ok(counts
+        .into_iter()
+        .filter(|&(_, (cells, money))| money * 2 > cells)
+        .map(|(col, _)| col)
+        .collect())
+}
+
+/// Reads the fill and font of every styled cell in a sheet, by 0-based absolute
+/// (row, col); cells that look plain are left out
+pub fn load_cell_styles(
+    package: &mut Package,
+    sheet_name: &str,
+) -> Result<HashMap<(u32, u32), CellStyle>> {
+    let Some(styles_part) = package.workbook_part("styles")? else {
+        return Ok(HashMap::new());
+    };
+    let palette = Palette::load(package)?;
+    let formats = cell_formats(&package.read_xml(&styles_part)?, &palette);
+    if formats.iter().all(CellStyle::is_plain) {
+        return Ok(HashMap::new());
+    }
+
+    let part = package.worksheet_part(sheet_name)?;
+    let mut cells = HashMap::new();
+    package.scan_elements(&part, &["c"], |el| {
+        let style = el
+            .attr("s")
+            .and_then(|s| s.parse::<usize>().ok())
+            .and_then(|s| formats.get(s));
+        if let (Some(style), Some(pos)) = (style, el.attr("r").and_then(parse_cell_ref))
+            && !style.is_plain()
+        {
+            cells.insert(pos, *style);
+        }
+    })?;
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml::parse_xml;
+
+    #[test]
+    fn test_palette_colors() {
+        let theme = parse_xml(
+            r#"<a:theme xmlns:a="urn:a"><a:themeElements><a:clrScheme name="Office">
+  <a:dk1><a:sysClr val="windowText" lastClr="000000"/></a:dk1>
+  <a:lt1><a:sysClr val="window" lastClr="FFFFFF"/></a:lt1>
+  <a:dk2><a:srgbClr val="44546A"/></a:dk2><a:lt2><a:srgbClr val="E7E6E6"/></a:lt2>
+  <a:accent1><a:srgbClr val="4472C4"/></a:accent1><a:accent2><a:srgbClr val="ED7D31"/></a:accent2>
+  <a:accent3><a:srgbClr val="A5A5A5"/></a:accent3><a:accent4><a:srgbClr val="FFC000"/></a:accent4>
+  <a:accent5><a:srgbClr val="5B9BD5"/></a:accent5><a:accent6><a:srgbClr val="70AD47"/></a:accent6>
+  <a:hlink><a:srgbClr val="0563C1"/></a:hlink><a:folHlink><a:srgbClr val="954F72"/></a:folHlink>
+</a:clrScheme></a:themeElements></a:theme>"#,
+            &[],
+        )
+        .unwrap();
+        let palette = Palette::from_theme(&theme);
+        let color = |xml: &str| palette.color(&parse_xml(xml, &[]).unwrap());
+
+        assert_eq!(color(r#"<color rgb="FF9C0006"/>"#), Some((0x9C, 0, 6)));
+        assert_eq!(color(r#"<color theme="1"/>"#), Some((0, 0, 0)));
+        assert_eq!(color(r#"<color theme="4"/>"#), Some((0x44, 0x72, 0xC4)));
+        // "Accent 1, lighter 80%" and "White, darker 50%" from Excel's color picker
+        assert_eq!(
+            color(r#"<color theme="4" tint="0.79998168889431442"/>"#),
+            Some((0xDA, 0xE3, 0xF3))
+        );
+        assert_eq!(
+            color(r#"<color theme="0" tint="-0.499984740745262"/>"#),
+            Some((0x80, 0x80, 0x80))
+        );
+        assert_eq!(color(r#"<color indexed="10"/>"#), Some((255, 0, 0)));
+        assert_eq!(color(r#"<color indexed="64"/>"#), None);
+        assert_eq!(color(r#"<color auto="1"/>"#), None);
+    }
+
+    #[test]
+    fn test_cell_formats() {
+        let styles = parse_xml(
+            r#"<styleSheet>
+  <fonts><font><color theme="1"/></font><font><b/><color rgb="FFC00000"/></font><font><i/><color theme="1"/></font></fonts>
+  <fills><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill>
+    <fill><patternFill patternType="solid"><fgColor rgb="FFFFFF00"/><bgColor indexed="64"/></patternFill></fill></fills>
+  <cellXfs><xf fontId="0" fillId="0"/><xf fontId="1" fillId="0"/><xf fontId="0" fillId="2"/><xf fontId="2" fillId="1"/></cellXfs>
+</styleSheet>"#,
+            &[],
+        )
+        .unwrap();
+        let formats = cell_formats(&styles, &Palette::default());
+        assert!(formats[0].is_plain());
+        assert_eq!(
+            formats[1],
+            CellStyle {
+                fg: Some((0xC0, 0, 0)),
+                bold: true,
+                ..CellStyle::default()
+            }
+        );
+        assert_eq!(formats[2].bg, Some((255, 255, 0)));
+        assert_eq!(
+            formats[3],
+            CellStyle {
+                italic: true,
+                ..CellStyle::default()
+            }
+        );
+    }
+    #[test]
+    fn test_cell_alignments() {
+        let styles = parse_xml(
+            r#"<styleSheet><cellXfs count="4">
+  <xf fontId="0"/>
+  <xf fontId="0" applyAlignment="1"><alignment horizontal="center" vertical="top"/></xf>
+  <xf fontId="0" applyAlignment="1"><alignment horizontal="right"/></xf>
+  <xf fontId="0" applyAlignment="1"><alignment vertical="center" wrapText="1"/></xf>
+</cellXfs></styleSheet>"#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            cell_alignments(&styles),
+            [
+                None,
+                Some(HorizontalAlign::Center),
+                Some(HorizontalAlign::Right),
+                None
+            ]
+        );
+        assert_eq!(
+            HorizontalAlign::parse("justify"),
+            Some(HorizontalAlign::Left)
+        );
+        assert_eq!(HorizontalAlign::parse("general"), None);
+    }
+
+    #[test]
+    fn test_currency_formats() {
+        let styles = parse_xml(
+            r##"<styleSheet><numFmts count="3">
+  <numFmt numFmtId="164" formatCode="#,##0.00\ &quot;€&quot;"/>
+  <numFmt numFmtId="165" formatCode="[$-409]d/m/yyyy"/>
+  <numFmt numFmtId="166" formatCode="[$CHF-807]\ #,##0.00"/>
+</numFmts><cellXfs count="6">
+  <xf numFmtId="0"/><xf numFmtId="7"/><xf numFmtId="164"/>
+  <xf numFmtId="165"/><xf numFmtId="166"/><xf numFmtId="4"/>
+</cellXfs></styleSheet>"##,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            currency_formats(&styles),
+            [false, true, true, false, true, false]
+        );
+        assert!(is_currency_code("£#,##0"));
+        assert!(!is_currency_code("0.00%"));
+    }
+}
@@ -1,4 +1,3 @@
-use crate::workbook::{CellValue, LazySheetData, SheetData, Workbook};
 use anyhow::{Context, Result};
 use arboard::Clipboard;
 use crossterm::{
@@ -9,13 +8,37 @@ use crossterm::{
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
 };
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
 use std::time::{Duration, Instant};
 
+use crate::background::{self, Background};
+use crate::chart::{self, ChartKind, Series};
+use crate::session::{Mark, Resume, Session};
+use crate::watch::FileWatcher;
+use xleak::aggregate::{self, Aggregation};
+use xleak::color_mode::{self, ColorMode, TermColor};
+use xleak::conditional::{self, CellFormat};
+use xleak::diff::{CellChange, ChangeKind, SheetDiff, SheetStatus};
+use xleak::display::{self, ExportOptions};
+use xleak::hidden::HiddenCells;
+use xleak::hyperlinks::Hyperlink;
+use xleak::locale::NumberLocale;
+use xleak::ooxml::parse_cell_ref;
+use xleak::protection::SheetProtection;
+use xleak::save::{self, CellEdits};
+use xleak::styles::{CellStyle, HorizontalAlign};
+use xleak::workbook::{CellStats, CellValue, LazySheetData, SheetData, Workbook};
+
 /// Available themes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Theme {
@@ -40,13 +63,6 @@ impl Theme {
         ]
     }
 
-    /// Get the next theme in the cycle
-    pub fn next(&self) -> Theme {
-        let themes = Self::all();
-        let current_idx = themes.iter().position(|t| t == self).unwrap_or(0);
-        themes[(current_idx + 1) % themes.len()]
-    }
-
     /// Get theme name for display
     pub fn name(&self) -> &'static str {
         match self {
@@ -72,6 +88,20 @@ impl Theme {
     }
 }
 
+/// A theme in the `t` cycle, built in or defined by the user
+struct NamedTheme {
+    name: String,
+    colors: ColorScheme,
+}
+
+/// Theme names compare without case or spaces, so "solarizeddark" is "Solarized Dark"
+fn theme_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Color scheme for the TUI
 #[derive(Debug, Clone)]
 pub struct ColorScheme {
@@ -80,6 +110,7 @@ pub struct ColorScheme {
     pub number_fg: Color,
     pub bool_fg: Color,
     pub datetime_fg: Color,
+    pub duration_fg: Color,
     pub error_fg: Color,
     pub empty_fg: Color,
 
@@ -102,9 +133,70 @@ pub struct ColorScheme {
     pub border_fg: Color,
     pub status_bar_fg: Color,
     pub status_bar_bg: Option<Color>,
+
+    // Heatmap backgrounds of a column's lowest and highest numbers
+    pub heatmap_low: Color,
+    pub heatmap_high: Color,
 }
 
 impl ColorScheme {
+    /// A custom theme from the config: `base` names the built-in theme to start from
+    /// (Default without one) and every other key sets the field of that name to a color,
+    /// given as "#rrggbb", a color name, or an ANSI color number ("none" clears the
+    /// optional backgrounds)
+    pub fn from_definition(definition: &BTreeMap<String, String>) -> Result<Self> {
+        let mut colors = match definition.get("base") {
+            Some(base) => Theme::all()
+                .iter()
+                .find(|theme| theme_key(theme.name()) == theme_key(base))
+                .with_context(|| format!("Unknown base theme '{base}'"))?
+                .colors(),
+            None => Self::default_theme(),
+        };
+        for (key, value) in definition {
+            let color = || {
+                value
+                    .parse::<Color>()
+                    .map_err(|_| anyhow::anyhow!("Invalid color '{value}' for {key}"))
+            };
+            let optional = || -> Result<Option<Color>> {
+                if value.eq_ignore_ascii_case("none") {
+                    Ok(None)
+                } else {
+                    color().map(Some)
+                }
+            };
+            match key.as_str() {
+                "base" => {}
+                "string_fg" => colors.string_fg = color()?,
+                "number_fg" => colors.number_fg = color()?,
+                "bool_fg" => colors.bool_fg = color()?,
+                "datetime_fg" => colors.datetime_fg = color()?,
+                "duration_fg" => colors.duration_fg = color()?,
+                "error_fg" => colors.error_fg = color()?,
+                "empty_fg" => colors.empty_fg = color()?,
+                "header_fg" => colors.header_fg = color()?,
+                "header_bg" => colors.header_bg = optional()?,
+                "current_cell_fg" => colors.current_cell_fg = color()?,
+                "current_cell_bg" => colors.current_cell_bg = color()?,
+                "current_row_bg" => colors.current_row_bg = color()?,
+                "current_col_fg" => colors.current_col_fg = color()?,
+                "alternating_row_bg" => colors.alternating_row_bg = optional()?,
+                "search_match_fg" => colors.search_match_fg = color()?,
+                "search_match_bg" => colors.search_match_bg = color()?,
+                "current_search_fg" => colors.current_search_fg = color()?,
+                "current_search_bg" => colors.current_search_bg = color()?,
+                "border_fg" => colors.border_fg = color()?,
+                "status_bar_fg" => colors.status_bar_fg = color()?,
+                "status_bar_bg" => colors.status_bar_bg = optional()?,
+                "heatmap_low" => colors.heatmap_low = color()?,
+                "heatmap_high" => colors.heatmap_high = color()?,
+                _ => anyhow::bail!("Unknown theme color '{key}'"),
+            }
+        }
+        Ok(colors)
+    }
+
     /// Default theme (current behavior with enhancements)
     pub fn default_theme() -> Self {
         Self {
@@ -113,6 +205,7 @@ impl ColorScheme {
             number_fg: Color::Cyan,
             bool_fg: Color::Magenta,
             datetime_fg: Color::Green,
+            duration_fg: Color::Yellow,
             error_fg: Color::Red,
             empty_fg: Color::DarkGray,
 
@@ -135,6 +228,10 @@ impl ColorScheme {
             border_fg: Color::White,
             status_bar_fg: Color::White,
             status_bar_bg: None,
+
+            // Heatmap
+            heatmap_low: Color::Rgb(30, 50, 110),
+            heatmap_high: Color::Rgb(180, 40, 40),
         }
     }
 
@@ -142,12 +239,13 @@ impl ColorScheme {
     pub fn dracula() -> Self {
         Self {
             // Cell types - Dracula palette
-            string_fg: Color::Rgb(248, 248, 242),  // Foreground
-            number_fg: Color::Rgb(189, 147, 249),  // Purple
-            bool_fg: Color::Rgb(255, 121, 198),    // Pink
-            datetime_fg: Color::Rgb(80, 250, 123), // Green
-            error_fg: Color::Rgb(255, 85, 85),     // Red
-            empty_fg: Color::Rgb(98, 114, 164),    // Comment
+            string_fg: Color::Rgb(248, 248, 242),   // Foreground
+            number_fg: Color::Rgb(189, 147, 249),   // Purple
+            bool_fg: Color::Rgb(255, 121, 198),     // Pink
+            datetime_fg: Color::Rgb(80, 250, 123),  // Green
+            duration_fg: Color::Rgb(255, 184, 108), // Orange
+            error_fg: Color::Rgb(255, 85, 85),      // Red
+            empty_fg: Color::Rgb(98, 114, 164),     // Comment
 
             // UI elements
             header_fg: Color::Rgb(139, 233, 253),    // Cyan
@@ -168,6 +266,10 @@ impl ColorScheme {
             border_fg: Color::Rgb(98, 114, 164), // Comment
             status_bar_fg: Color::Rgb(248, 248, 242),
             status_bar_bg: Some(Color::Rgb(68, 71, 90)),
+
+            // Heatmap
+            heatmap_low: Color::Rgb(68, 71, 90),   // Current line
+            heatmap_high: Color::Rgb(255, 85, 85), // Red
         }
     }
 
@@ -179,6 +281,7 @@ impl ColorScheme {
             number_fg: Color::Rgb(38, 139, 210),  // Blue
             bool_fg: Color::Rgb(211, 54, 130),    // Magenta
             datetime_fg: Color::Rgb(133, 153, 0), // Green
+            duration_fg: Color::Rgb(181, 137, 0), // Yellow
             error_fg: Color::Rgb(220, 50, 47),    // Red
             empty_fg: Color::Rgb(88, 110, 117),   // Base01
 
@@ -201,6 +304,10 @@ impl ColorScheme {
             border_fg: Color::Rgb(88, 110, 117),
             status_bar_fg: Color::Rgb(131, 148, 150),
             status_bar_bg: Some(Color::Rgb(7, 54, 66)),
+
+            // Heatmap
+            heatmap_low: Color::Rgb(7, 54, 66),    // Base02
+            heatmap_high: Color::Rgb(220, 50, 47), // Red
         }
     }
 
@@ -212,6 +319,7 @@ impl ColorScheme {
             number_fg: Color::Rgb(38, 139, 210),  // Blue
             bool_fg: Color::Rgb(211, 54, 130),    // Magenta
             datetime_fg: Color::Rgb(133, 153, 0), // Green
+            duration_fg: Color::Rgb(181, 137, 0), // Yellow
             error_fg: Color::Rgb(220, 50, 47),    // Red
             empty_fg: Color::Rgb(147, 161, 161),  // Base1
 
@@ -234,6 +342,10 @@ impl ColorScheme {
             border_fg: Color::Rgb(147, 161, 161),
             status_bar_fg: Color::Rgb(101, 123, 131),
             status_bar_bg: Some(Color::Rgb(238, 232, 213)),
+
+            // Heatmap
+            heatmap_low: Color::Rgb(238, 232, 213), // Base2
+            heatmap_high: Color::Rgb(203, 75, 22),  // Orange
         }
     }
 
@@ -245,6 +357,7 @@ impl ColorScheme {
             number_fg: Color::Rgb(121, 192, 255),   // prettylights-syntax-constant
             bool_fg: Color::Rgb(255, 125, 163),     // prettylights-syntax-entity
             datetime_fg: Color::Rgb(127, 219, 202), // prettylights-syntax-string
+            duration_fg: Color::Rgb(255, 166, 87),  // prettylights-syntax-variable
             error_fg: Color::Rgb(248, 81, 73),      // danger-fg
             empty_fg: Color::Rgb(110, 118, 129),    // fgMuted
 
@@ -267,6 +380,10 @@ impl ColorScheme {
             border_fg: Color::Rgb(48, 54, 61), // border-default
             status_bar_fg: Color::Rgb(201, 209, 217),
             status_bar_bg: Some(Color::Rgb(33, 38, 45)),
+
+            // Heatmap
+            heatmap_low: Color::Rgb(33, 38, 45), // canvas-subtle
+            heatmap_high: Color::Rgb(248, 81, 73), // danger-fg
         }
     }
 
@@ -278,6 +395,7 @@ impl ColorScheme {
             number_fg: Color::Rgb(136, 192, 208),   // nord8
             bool_fg: Color::Rgb(180, 142, 173),     // nord15
             datetime_fg: Color::Rgb(163, 190, 140), // nord14
+            duration_fg: Color::Rgb(235, 203, 139), // nord13
             error_fg: Color::Rgb(191, 97, 106),     // nord11
             empty_fg: Color::Rgb(76, 86, 106),      // nord3
 
@@ -300,6 +418,10 @@ impl ColorScheme {
             border_fg: Color::Rgb(76, 86, 106), // nord3
             status_bar_fg: Color::Rgb(216, 222, 233),
             status_bar_bg: Some(Color::Rgb(59, 66, 82)),
+
+            // Heatmap
+            heatmap_low: Color::Rgb(59, 66, 82),    // nord1
+            heatmap_high: Color::Rgb(191, 97, 106), // nord11
         }
     }
 
@@ -312,8 +434,208 @@ impl ColorScheme {
             CellValue::Bool(_) => self.bool_fg,
             CellValue::Error(_) => self.error_fg,
             CellValue::DateTime(_) => self.datetime_fg,
+            CellValue::Time(_) | CellValue::Duration(_) => self.duration_fg,
+        }
+    }
+
+    /// Background and readable text for a number in a heatmap column spanning `range`
+    pub fn heat_style(&self, cell: &CellValue, range: (f64, f64)) -> Option<Style> {
+        let rgb = |color: Color| match color {
+            Color::Rgb(r, g, b) => (r, g, b),
+            _ => (128, 128, 128),
+        };
+        let (r, g, b) =
+            display::heat_color(cell, range, rgb(self.heatmap_low), rgb(self.heatmap_high))?;
+        let (fr, fg, fb) = display::contrast_text((r, g, b));
+        Some(
+            Style::default()
+                .bg(Color::Rgb(r, g, b))
+                .fg(Color::Rgb(fr, fg, fb)),
+        )
+    }
+}
+
+/// Whether a pressed key is a bound one. Some terminals omit SHIFT for shifted symbol
+/// characters (e.g. '?', '$') because the shift is already encoded in the character
+/// itself, so SHIFT is ignored on both sides for non-alphabetic chars.
+fn same_key(
+    bound: (KeyCode, crossterm::event::KeyModifiers),
+    pressed: (KeyCode, crossterm::event::KeyModifiers),
+) -> bool {
+    if bound.0 != pressed.0 {
+        return false;
+    }
+    if let KeyCode::Char(c) = pressed.0
+        && !c.is_alphabetic()
+    {
+        let strip = crossterm::event::KeyModifiers::SHIFT;
+        return (pressed.1 - strip) == (bound.1 - strip);
+    }
+    pressed.1 == bound.1
+}
+
+/// The scroll offset (first displayed row) that shows the row at `position` with up to
+/// `margin` rows of context on either side, moving as little as possible from `offset`.
+/// The margin gives way at the ends of the sheet, `bounds` being the first and last
+/// offsets; one of half the viewport or more keeps the cursor centered.
+fn scroll_offset_for(
+    position: usize,
+    offset: usize,
+    viewport_height: usize,
+    margin: usize,
+    bounds: (usize, usize),
+) -> usize {
+    let margin = margin.min(viewport_height.saturating_sub(1) / 2);
+    let mut offset = offset;
+    // Scroll down if cursor is below visible area
+    if position + margin >= offset + viewport_height {
+        offset = (position + margin + 1)
+            .saturating_sub(viewport_height)
+            .min(bounds.1)
+            .max(offset);
+    }
+    // Scroll up if cursor is above visible area
+    if position < offset + margin {
+        offset = position.saturating_sub(margin).max(bounds.0);
+    }
+    offset
+}
+
+/// Fills the `{name}` placeholders of a `status_bar` template; unknown names stay as
+/// written, and parts between `|` that end up blank are dropped with their separator
+fn fill_status_template(template: &str, field: impl Fn(&str) -> Option<String>) -> String {
+    template
+        .split('|')
+        .filter_map(|part| {
+            let mut filled = String::new();
+            let mut rest = part;
+            let mut placeholders = false;
+            while let Some(start) = rest.find('{') {
+                let Some(len) = rest[start..].find('}') else {
+                    break;
+                };
+                let name = &rest[start + 1..start + len];
+                filled.push_str(&rest[..start]);
+                match field(name) {
+                    Some(value) => {
+                        placeholders = true;
+                        filled.push_str(&value);
+                    }
+                    None => filled.push_str(&rest[start..=start + len]),
+                }
+                rest = &rest[start + len + 1..];
+            }
+            filled.push_str(rest);
+            (!placeholders || !filled.trim().is_empty()).then_some(filled)
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// The column and row lines `:set column_lines` and `:set row_lines` draw over a table
+/// with one-line rows; `col_widths`, `header_height`, and `rows` are the table's, and
+/// `inner` its area inside the borders
+fn grid_lines(
+    buf: &mut ratatui::buffer::Buffer,
+    inner: Rect,
+    col_widths: &[Constraint],
+    header_height: u16,
+    rows: usize,
+    row_lines: bool,
+    column_lines: bool,
+) {
+    // The same layout the table gives its columns, one space apart
+    let columns = Layout::horizontal(col_widths.iter().copied())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(Rect::new(inner.x, inner.y, inner.width, 1));
+    let Some(last) = columns.last() else {
+        return;
+    };
+    let gaps: Vec<u16> = columns
+        .windows(2)
+        .map(|pair| pair[0].right())
+        .filter(|&x| x < inner.right())
+        .collect();
+    let step = if row_lines { 2 } else { 1 };
+    // The header, then each data row
+    let header_end = inner.y + header_height - 1;
+    let bottom = (header_end + (rows as u16 + 1) * step).min(inner.bottom());
+    let style = Style::default().fg(Color::DarkGray);
+    for y in inner.y..bottom {
+        let is_line = row_lines && y > header_end && (y - header_end) % 2 == 1;
+        if is_line {
+            for x in inner.x..last.right().min(inner.right()) {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_symbol("─").set_style(style);
+                }
+            }
+        }
+        if column_lines {
+            for &x in &gaps {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_symbol(if is_line { "┼" } else { "│" })
+                        .set_style(style);
+                }
+            }
+        }
+    }
+}
+
+/// The nearest color to `color` the terminal has in `mode` (Reset without colors)
+fn fit_color(color: Color, mode: ColorMode) -> Color {
+    let fitted = match color {
+        Color::Reset => return Color::Reset,
+        Color::Rgb(r, g, b) => mode.rgb((r, g, b)),
+        Color::Indexed(index) => mode.indexed(index),
+        _ if mode == ColorMode::None => return Color::Reset,
+        named => return named,
+    };
+    match fitted {
+        Some(TermColor::Rgb(r, g, b)) => Color::Rgb(r, g, b),
+        Some(TermColor::Indexed(index)) => Color::Indexed(index),
+        None => Color::Reset,
+    }
+}
+
+/// A cell's text in the table, by absolute (row, col): numbers in `currency_cells` get the
+/// currency symbol
+fn cell_text(
+    locale: &NumberLocale,
+    currency_cells: &HashSet<(u32, u32)>,
+    pos: (u32, u32),
+    cell: &CellValue,
+) -> String {
+    let currency = currency_cells.contains(&pos);
+    match cell {
+        CellValue::Int(i) if currency => locale.currency(*i as f64),
+        CellValue::Float(f) if currency => locale.currency(*f),
+        _ => None,
+    }
+    .unwrap_or_else(|| cell.to_display_string(locale))
+}
+
+/// A ratatui style for a workbook cell's look (its own or from conditional formatting)
+fn excel_style(look: &CellStyle) -> Style {
+    let mut style = Style::default();
+    if let Some((r, g, b)) = look.fg {
+        style = style.fg(Color::Rgb(r, g, b));
+    }
+    if let Some((r, g, b)) = look.bg {
+        style = style.bg(Color::Rgb(r, g, b));
+        // Keep the text readable on a fill without its own font color
+        if look.fg.is_none() {
+            let (r, g, b) = display::contrast_text((r, g, b));
+            style = style.fg(Color::Rgb(r, g, b));
         }
     }
+    if look.bold {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if look.italic {
+        style = style.add_modifier(Modifier::ITALIC);
+    }
+    style
 }
 
 /// Cached row data for lazy loading
@@ -334,6 +656,19 @@ enum SheetDataSource {
 }
 
 impl SheetDataSource {
+    /// No rows or columns, until a sheet is loaded
+    fn empty() -> Self {
+        SheetDataSource::Eager(SheetData {
+            headers: Vec::new(),
+            rows: Vec::new(),
+            formulas: Vec::new(),
+            width: 0,
+            height: 0,
+            first_row: 0,
+            first_col: 0,
+        })
+    }
+
     fn headers(&self) -> &[String] {
         match self {
             SheetDataSource::Eager(data) => &data.headers,
@@ -355,6 +690,29 @@ impl SheetDataSource {
         }
     }
 
+    /// A handle a worker thread can read rows from (lazy sheets only)
+    fn background_source(&self) -> Option<LazySheetData> {
+        match self {
+            SheetDataSource::Eager(_) => None,
+            SheetDataSource::Lazy { data, .. } => Some(data.clone()),
+        }
+    }
+
+    /// Every data row's values (reads a lazy sheet in full)
+    fn all_values(&self) -> Vec<Vec<CellValue>> {
+        match self {
+            SheetDataSource::Eager(data) => data.rows.clone(),
+            SheetDataSource::Lazy { data, .. } => data.get_values(0, data.height),
+        }
+    }
+
+    fn cell_stats(&self) -> CellStats {
+        match self {
+            SheetDataSource::Eager(data) => data.cell_stats(),
+            SheetDataSource::Lazy { data, .. } => data.cell_stats(),
+        }
+    }
+
     /// Fetches rows with automatic cache management
     fn get_rows(
         &mut self,
@@ -401,6 +759,22 @@ impl SheetDataSource {
         }
     }
 
+    /// Replace a cell's value, dropping its formula (eager sheets only); false if the
+    /// sheet is lazy or the cell is outside it
+    fn set_cell(&mut self, row: usize, col: usize, value: CellValue) -> bool {
+        let SheetDataSource::Eager(data) = self else {
+            return false;
+        };
+        let Some(cell) = data.rows.get_mut(row).and_then(|r| r.get_mut(col)) else {
+            return false;
+        };
+        *cell = value;
+        if let Some(formula) = data.formulas.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *formula = None;
+        }
+        true
+    }
+
     fn get_cell(&mut self, row: usize, col: usize) -> (Option<CellValue>, Option<String>) {
         match self {
             SheetDataSource::Eager(data) => {
@@ -463,6 +837,644 @@ impl ProgressInfo {
     }
 }
 
+/// Matches streamed back from a search running on a worker thread
+enum SearchUpdate {
+    /// Matches among the rows searched so far (`rows_done` in total)
+    Found {
+        matches: Vec<(usize, usize)>,
+        rows_done: usize,
+    },
+    Done,
+}
+
+/// A sheet search running on a worker thread; dropping it stops the worker
+struct BackgroundSearch {
+    updates: mpsc::Receiver<SearchUpdate>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl BackgroundSearch {
+    const CHUNK_SIZE: usize = 2000;
+
+    fn spawn(
+        data: LazySheetData,
+        matcher: Regex,
+        column: Option<usize>,
+        locale: NumberLocale,
+    ) -> Self {
+        let (sender, updates) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancel);
+        std::thread::spawn(move || {
+            for start in (0..data.height).step_by(Self::CHUNK_SIZE) {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                let rows = data.get_values(start, Self::CHUNK_SIZE);
+                let matches = find_matches(&rows, start, &matcher, column, &locale)
+                    .into_iter()
+                    .map(|(row, col, _)| (row, col))
+                    .collect();
+                let rows_done = (start + Self::CHUNK_SIZE).min(data.height);
+                if sender
+                    .send(SearchUpdate::Found { matches, rows_done })
+                    .is_err()
+                {
+                    return; // The search was dropped
+                }
+            }
+            let _ = sender.send(SearchUpdate::Done);
+        });
+        Self { updates, cancel }
+    }
+}
+
+impl Drop for BackgroundSearch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Why a sheet is being loaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LoadKind {
+    Initial, // The first sheet shown: there is nothing to go back to
+    Switch,  // Moving to another sheet
+    Open,    // The first sheet of another open workbook, shown for the first time
+    Reload,  // The file changed on disk (--watch); the reopened workbook replaces ours
+}
+
+/// A sheet being loaded on a worker thread from its own handle on the workbook file, so
+/// the TUI stays responsive; dropping it discards the result
+struct SheetLoader {
+    sheet: usize, // Index of the sheet being loaded
+    name: String,
+    kind: LoadKind,
+    result: mpsc::Receiver<Result<(Workbook, LazySheetData)>>,
+    started_at: Instant,
+}
+
+impl SheetLoader {
+    const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    /// Quick loads swap the sheet in without flashing the loading popup
+    const POPUP_DELAY: Duration = Duration::from_millis(200);
+
+    fn spawn(workbook: &Workbook, sheet: usize, name: String, kind: LoadKind) -> Self {
+        let (sender, result) = mpsc::channel();
+        let reopen = workbook.reopener();
+        let sheet_name = name.clone();
+        std::thread::spawn(move || {
+            let loaded = reopen().and_then(|mut workbook| {
+                let data = workbook.load_sheet_lazy(&sheet_name)?;
+                Ok((workbook, data))
+            });
+            let _ = sender.send(loaded); // The receiver is gone if loading was cancelled
+        });
+        Self {
+            sheet,
+            name,
+            kind,
+            result,
+            started_at: Instant::now(),
+        }
+    }
+
+    fn spinner(&self) -> &'static str {
+        let frame = self.started_at.elapsed().as_millis() / 100;
+        Self::SPINNER[frame as usize % Self::SPINNER.len()]
+    }
+}
+
+/// How the search query is matched; toggled with Alt+letter while typing a search
+#[derive(Debug, Clone, Copy, Default)]
+struct SearchOptions {
+    regex: bool,          // The query is a regular expression (Alt+r)
+    case_sensitive: bool, // Match case (Alt+c)
+    whole_cell: bool,     // The query must match the whole cell text (Alt+w)
+    current_column: bool, // Only search the cursor's column (Alt+l)
+}
+
+impl SearchOptions {
+    /// Compile the query; plain queries match literally
+    fn matcher(&self, query: &str) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let pattern = if self.whole_cell {
+            format!("^(?:{pattern})$")
+        } else {
+            pattern
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+    }
+
+    /// Flip the option an Alt+letter key toggles; false if the key isn't one of them
+    fn toggle(&mut self, key: char) -> bool {
+        match key.to_ascii_lowercase() {
+            'r' => self.regex = !self.regex,
+            'c' => self.case_sensitive = !self.case_sensitive,
+            'w' => self.whole_cell = !self.whole_cell,
+            'l' => self.current_column = !self.current_column,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Active options for the search prompt, e.g. "regex, match case, column B"
+    fn describe(&self, column: &str) -> String {
+        let mut labels = Vec::new();
+        if self.regex {
+            labels.push("regex".to_string());
+        }
+        if self.case_sensitive {
+            labels.push("match case".to_string());
+        }
+        if self.whole_cell {
+            labels.push("whole cell".to_string());
+        }
+        if self.current_column {
+            labels.push(format!("column {column}"));
+        }
+        labels.join(", ")
+    }
+}
+
+/// Matches in a block of rows as (row, col, cell text as shown in `locale`), only in
+/// `column` if given; `first_row` is the index of `rows[0]` in the sheet
+fn find_matches(
+    rows: &[Vec<CellValue>],
+    first_row: usize,
+    matcher: &Regex,
+    column: Option<usize>,
+    locale: &NumberLocale,
+) -> Vec<(usize, usize, String)> {
+    let mut matches = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        for (col, cell) in row.iter().enumerate() {
+            if column.is_some_and(|c| c != col) {
+                continue;
+            }
+            let text = cell.to_display_string(locale);
+            if matcher.is_match(&text) {
+                matches.push((first_row + i, col, text));
+            }
+        }
+    }
+    matches
+}
+
+/// Comparison a row filter applies to one column
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FilterOp {
+    Contains,
+    Equals,
+    Greater,
+    Less,
+}
+
+/// A "COLUMN OP VALUE" row filter, e.g. `Amount > 100` or `B contains open`
+#[derive(Debug)]
+struct FilterSpec {
+    col: usize,
+    op: FilterOp,
+    value: String, // Lowercased for case-insensitive text comparisons
+}
+
+impl FilterSpec {
+    /// COLUMN is a header name or a column letter; OP is contains, equals (=), >, or <
+    fn parse(input: &str, headers: &[String]) -> Result<Self, String> {
+        const OPS: [(&str, FilterOp); 5] = [
+            (" contains ", FilterOp::Contains),
+            (" equals ", FilterOp::Equals),
+            ("=", FilterOp::Equals),
+            (">", FilterOp::Greater),
+            ("<", FilterOp::Less),
+        ];
+        // ASCII lowercasing keeps byte offsets valid for slicing `input`
+        let lower = format!("{} ", input.to_ascii_lowercase());
+        let (start, len, op) = OPS
+            .iter()
+            .filter_map(|&(pat, op)| lower.find(pat).map(|i| (i, pat.len(), op)))
+            .min_by_key(|&(i, ..)| i)
+            .ok_or("Use: column op value, with op contains, equals, >, or <")?;
+
+        let column = input[..start].trim();
+        let value = input.get(start + len..).unwrap_or("").trim();
+        let col = headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(column))
+            .or_else(|| {
+                TuiState::parse_cell_address(&format!("{column}1"))
+                    .map(|(col, _)| col)
+                    .filter(|&col| col < headers.len())
+            })
+            .ok_or_else(|| format!("Unknown column: {column}"))?;
+        Ok(Self {
+            col,
+            op,
+            value: value.to_lowercase(),
+        })
+    }
+
+    /// Numbers compare numerically; anything else compares as case-insensitive text, as
+    /// shown in `locale`
+    fn matches(&self, locale: &NumberLocale, cell: &CellValue) -> bool {
+        let number = match cell {
+            CellValue::Int(n) => Some(*n as f64),
+            CellValue::Float(n) => Some(*n),
+            CellValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        let text = cell.to_display_string(locale).to_lowercase();
+        let ordering = match (number, self.value.parse::<f64>()) {
+            (Some(n), Ok(v)) => n.partial_cmp(&v),
+            // A numeric bound never matches text or empty cells
+            (None, Ok(_)) if self.op != FilterOp::Contains => return false,
+            _ if matches!(cell, CellValue::Empty) => return self.value.is_empty(),
+            _ => Some(text.as_str().cmp(self.value.as_str())),
+        };
+        match self.op {
+            FilterOp::Contains => text.contains(&self.value),
+            FilterOp::Equals => ordering == Some(std::cmp::Ordering::Equal),
+            FilterOp::Greater => ordering == Some(std::cmp::Ordering::Greater),
+            FilterOp::Less => ordering == Some(std::cmp::Ordering::Less),
+        }
+    }
+}
+
+/// Ctrl+/ (reported as Ctrl+7 by terminals that send it as the 0x1F control byte)
+fn is_ctrl_slash(code: KeyCode, modifiers: crossterm::event::KeyModifiers) -> bool {
+    modifiers.contains(crossterm::event::KeyModifiers::CONTROL)
+        && matches!(code, KeyCode::Char('/') | KeyCode::Char('7'))
+}
+
+/// A cell's text on one line, cut to `max` terminal columns with an ellipsis
+fn snippet(text: &str, max: usize) -> String {
+    display::truncate_to_width(&text.replace(['\n', '\r'], " "), max, "…")
+}
+
+/// Quote a clipboard field if it contains a tab, line break, or quote
+fn tsv_field(value: &str) -> String {
+    if value.contains('\t') || value.contains('\n') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write cells to a file in the format its extension names
+fn write_selection(
+    path: &std::path::Path,
+    headers: &[String],
+    rows: &[Vec<CellValue>],
+    options: &ExportOptions,
+) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut out = io::BufWriter::new(file);
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("csv") => xleak::write_csv(&mut out, headers, rows, options)?,
+        Some("json") => xleak::write_json(
+            &mut out,
+            &[],
+            headers,
+            rows,
+            xleak::JsonMode::Records,
+            options,
+        )?,
+        Some("md") => xleak::write_markdown(&mut out, headers, rows, options)?,
+        _ => xleak::write_text(&mut out, headers, rows, options)?,
+    }
+    io::Write::flush(&mut out).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Columns the user hid or pinned on one sheet, kept while the session lasts
+#[derive(Debug, Default)]
+struct ColumnView {
+    hidden: BTreeSet<usize>,
+    pinned: Vec<usize>, // Shown at the left edge, in the order they were pinned
+    widths: HashMap<usize, usize>, // Widths set with the resize keys
+}
+
+/// Positions the cursor jumped away from, for going back (`Ctrl+o`) and forward again
+#[derive(Debug, Default)]
+struct JumpList {
+    entries: Vec<Mark>,
+    /// Where back/forward currently is; `entries.len()` when not stepping through
+    index: usize,
+}
+
+impl JumpList {
+    /// Oldest positions are dropped beyond this
+    const MAX_ENTRIES: usize = 100;
+
+    /// Note a jump away from `from`, dropping anything ahead of the current spot
+    fn record(&mut self, from: Mark) {
+        self.entries.truncate(self.index);
+        if self.entries.last() != Some(&from) {
+            self.entries.push(from);
+        }
+        if self.entries.len() > Self::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.index = self.entries.len();
+    }
+
+    /// The position before `current`, which is kept so forward can return to it
+    fn back(&mut self, current: Mark) -> Option<Mark> {
+        if self.index == 0 {
+            return None;
+        }
+        if self.index == self.entries.len() {
+            self.entries.push(current);
+        } else {
+            self.entries[self.index] = current;
+        }
+        self.index -= 1;
+        Some(self.entries[self.index].clone())
+    }
+
+    fn forward(&mut self, current: Mark) -> Option<Mark> {
+        if self.index + 1 >= self.entries.len() {
+            return None;
+        }
+        self.entries[self.index] = current;
+        self.index += 1;
+        Some(self.entries[self.index].clone())
+    }
+}
+
+/// What a pending mark key does with the letter typed next
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MarkKey {
+    Set,  // `m`: bookmark the cursor cell
+    Jump, // `'`: go to a bookmark
+}
+
+/// A second view of the workbook (`:split` / `:vsplit`)
+struct Split {
+    direction: Direction, // Vertical stacks the panes, Horizontal puts them side by side
+    focus_first: bool,    // Whether the focused pane is the top/left one
+    pane: PaneView,       // The unfocused pane's view
+}
+
+/// Everything that differs between two panes; the focused pane's view lives in `TuiState`
+/// and switching focus swaps it with the stored one
+struct PaneView {
+    sheet_index: usize,
+    sheet_data: SheetDataSource,
+    sheet_origin: (usize, usize),
+    cursor: (usize, usize),
+    scroll: (usize, usize),
+    column_widths: Vec<usize>,
+    table_viewport: (usize, usize),
+    cell_stats: CellStats,
+    hyperlinks: HashMap<(u32, u32), Hyperlink>,
+    conditional: HashMap<(u32, u32), CellFormat>,
+    cell_styles: HashMap<(u32, u32), CellStyle>,
+    cell_alignments: HashMap<(u32, u32), HorizontalAlign>,
+    currency_cells: HashSet<(u32, u32)>,
+    hidden_cells: HiddenCells,
+    sheet_changes: HashMap<(u32, u32), CellChange>,
+    search_matches: Vec<(usize, usize)>,
+    current_match_index: Option<usize>,
+    row_filter: Option<RowFilter>,
+    frozen: (usize, usize),
+    frozen_row_cache: Vec<(usize, Vec<CellValue>)>,
+    visual_anchor: Option<(usize, usize)>,
+    pivot: Option<PivotView>,
+    bar_columns: HashMap<usize, (f64, f64)>,
+    heat_columns: HashMap<usize, (f64, f64)>,
+}
+
+impl PaneView {
+    fn empty(sheet_index: usize) -> Self {
+        Self {
+            sheet_index,
+            sheet_data: SheetDataSource::empty(),
+            sheet_origin: (0, 0),
+            cursor: (0, 0),
+            scroll: (0, 0),
+            column_widths: Vec::new(),
+            table_viewport: (0, 0),
+            cell_stats: CellStats::default(),
+            hyperlinks: HashMap::new(),
+            conditional: HashMap::new(),
+            cell_styles: HashMap::new(),
+            cell_alignments: HashMap::new(),
+            currency_cells: HashSet::new(),
+            hidden_cells: HiddenCells::default(),
+            sheet_changes: HashMap::new(),
+            search_matches: Vec::new(),
+            current_match_index: None,
+            row_filter: None,
+            frozen: (0, 0),
+            frozen_row_cache: Vec::new(),
+            visual_anchor: None,
+            pivot: None,
+            bar_columns: HashMap::new(),
+            heat_columns: HashMap::new(),
+        }
+    }
+}
+
+/// A workbook to show in the TUI and where to start in it
+pub struct OpenWorkbook {
+    pub name: String, // File name, shown when several workbooks are open
+    pub workbook: Workbook,
+    pub sheet: String, // Sheet to start on
+    pub watcher: Option<FileWatcher>,
+    pub resume: Option<Resume>,
+}
+
+/// An open workbook's state while another one is shown (`xleak a.xlsx b.xlsx -i`);
+/// switching swaps it with the fields in `TuiState`, like `PaneView` for panes
+struct WorkbookView {
+    name: String,
+    workbook: Workbook,
+    sheet_names: Vec<String>,
+    sheet_visibility: Vec<&'static str>,
+    warnings: Vec<String>,
+    protection: Option<(usize, Option<SheetProtection>)>,
+    pane: PaneView,
+    split: Option<Split>,
+    column_views: HashMap<usize, ColumnView>,
+    edits: HashMap<String, CellEdits>,
+    unsaved_edits: bool,
+    save_path: Option<PathBuf>,
+    marks: BTreeMap<char, Mark>,
+    previous_position: Option<Mark>,
+    jump_list: JumpList,
+    watcher: Option<FileWatcher>,
+    resume: Option<Resume>,
+    loaded: bool, // Whether it has been shown; its first sheet loads on the first switch
+}
+
+/// What the pivot dialog does with one column
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PivotRole {
+    Skip,
+    Group,
+    Value(Aggregation),
+}
+
+/// The pivot dialog: a role for each of the sheet's columns
+struct PivotDialog {
+    columns: Vec<String>,
+    roles: Vec<PivotRole>,
+    selected: usize,
+}
+
+impl PivotDialog {
+    /// Most columns listed at once; the list scrolls to keep the selection in view
+    const MAX_SHOWN: usize = 15;
+
+    /// Keys that set the selected column's role
+    const ROLE_KEYS: [(char, PivotRole); 6] = [
+        ('r', PivotRole::Group),
+        ('s', PivotRole::Value(Aggregation::Sum)),
+        ('a', PivotRole::Value(Aggregation::Average)),
+        ('c', PivotRole::Value(Aggregation::Count)),
+        ('m', PivotRole::Value(Aggregation::Min)),
+        ('x', PivotRole::Value(Aggregation::Max)),
+    ];
+}
+
+/// A pivot shown in place of its sheet; the sheet's own view waits in `sheet`
+struct PivotView {
+    description: String,   // "by Region, Product", for the title
+    roles: Vec<PivotRole>, // To reopen the dialog as it was
+    sheet: Box<PaneView>,
+}
+
+/// The chart popup: a column charted against the sheet's first column
+struct ChartPopup {
+    kind: ChartKind,
+    column: usize,
+    series: Series,
+    scroll: usize, // First bar shown
+}
+
+/// The fuzzy finder (Ctrl+P) over sheets, tables, defined names, and column headers
+struct Finder {
+    query: String,
+    items: Vec<FinderItem>,
+    /// Indexes into `items` that match the query, best first, with the matched characters
+    matches: Vec<(usize, Vec<usize>)>,
+    selected: usize,
+}
+
+struct FinderItem {
+    kind: &'static str, // "sheet", "table", "name", or "column"
+    label: String,
+    target: FinderTarget,
+}
+
+enum FinderTarget {
+    Sheet(usize),
+    Table { name: String, sheet: String },
+    Location(String), // "Sheet!A1" of a defined name
+    Column(usize),    // On the current sheet
+}
+
+impl Finder {
+    /// Most matches listed at once
+    const MAX_SHOWN: usize = 12;
+
+    fn new(items: Vec<FinderItem>) -> Self {
+        let mut finder = Self {
+            query: String::new(),
+            items,
+            matches: Vec::new(),
+            selected: 0,
+        };
+        finder.update();
+        finder
+    }
+
+    /// Rank the items against the query
+    fn update(&mut self) {
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let (score, positions) = crate::fuzzy::fuzzy_match(&self.query, &item.label)?;
+                Some((score, index, positions))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored
+            .into_iter()
+            .map(|(_, index, positions)| (index, positions))
+            .collect();
+        self.selected = 0;
+    }
+}
+
+/// Rows left visible by a filter
+struct RowFilter {
+    description: String, // The filter as typed, for the title and status bar
+    rows: Vec<usize>,    // Matching data rows, ascending
+}
+
+/// Open a URL or file with the platform's default handler, without waiting for it
+fn open_with_system(target: &str) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        // `cmd /C start` would interpret `&` in URLs
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+
+    command
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// A match from a workbook-wide search
+#[derive(Debug, Clone)]
+struct WorkbookMatch {
+    sheet: usize, // Index into sheet_names
+    row: usize,
+    col: usize,
+    text: String, // Cell display text, for the results panel
+}
+
+/// Cell changes against an older workbook, shown over the newer one (`--diff OLD NEW -i`)
+pub struct DiffView {
+    /// Label of the older workbook, for the detail popup and warnings
+    old_name: String,
+    sheets: Vec<SheetDiff>,
+}
+
+impl DiffView {
+    pub fn new(old_name: String, sheets: Vec<SheetDiff>) -> Self {
+        Self { old_name, sheets }
+    }
+
+    fn sheet(&self, name: &str) -> Option<&SheetDiff> {
+        self.sheets.iter().find(|sheet| sheet.name == name)
+    }
+}
+
 /// TUI application state
 pub struct TuiState {
     workbook: Workbook,
@@ -470,29 +1482,113 @@ pub struct TuiState {
     current_sheet_index: usize,
     sheet_data: SheetDataSource,
     should_quit: bool,
-    cursor_row: usize,               // Current row (0-indexed in data)
-    cursor_col: usize,               // Current column (0-indexed)
-    scroll_offset: usize,            // Vertical scroll offset
-    horizontal_scroll_offset: usize, // Horizontal scroll offset
-    horizontal_scroll_enabled: bool, // Whether horizontal scrolling is enabled
-    column_widths: Vec<usize>,       // Cached column widths for horizontal scroll
-    show_help: bool,                 // Help overlay visible
-    show_cell_detail: bool,          // Cell detail popup visible
-    cell_detail_scroll: usize,       // Scroll offset for cell detail popup
+    cursor_row: usize,                        // Current row (0-indexed in data)
+    cursor_col: usize,                        // Current column (0-indexed)
+    scroll_offset: usize,                     // Vertical scroll offset
+    horizontal_scroll_offset: usize,          // Horizontal scroll offset
+    column_widths: Vec<usize>,                // Cached column widths for horizontal scroll
+    table_viewport: (usize, usize),           // Table (width, scrolled rows) at the last render
+    show_help: bool,                          // Help overlay visible
+    show_cell_detail: bool,                   // Cell detail popup visible
+    show_record: bool,                        // Record view (current row as header/value pairs)
+    record_scroll: usize,                     // Scroll offset for the record view
+    cell_detail_scroll: usize,                // Scroll offset for cell detail popup
+    cell_detail_evaluated: Option<CellValue>, // Formula result computed when the popup opened
+    show_pivots: bool,                        // Pivot table popup visible
+    pivot_lines: Vec<String>,                 // Rendered pivot descriptions (built when opened)
+    pivot_scroll: usize,                      // Scroll offset for pivot popup
+    pivot_dialog: Option<PivotDialog>,        // Pivot dialog, while open
+    pivot: Option<PivotView>,                 // Pivot shown in place of the sheet
+    chart: Option<ChartPopup>,                // Chart popup, while open
+    bar_columns: HashMap<usize, (f64, f64)>,  // Columns drawn with bars, and their scale
+    heat_columns: HashMap<usize, (f64, f64)>, // Heatmap columns, and their number range
+    warnings: Vec<String>,                    // Workbook warnings shown in a banner above the table
+    cell_stats: CellStats,                    // Error/empty cell counts for the current sheet
+    sheet_origin: (usize, usize), // Absolute (row, col) of the current sheet's first data cell
+    protection: Option<(usize, Option<SheetProtection>)>, // Cached protection for a sheet index
+    hyperlinks: HashMap<(u32, u32), Hyperlink>, // Current sheet's links by absolute (row, col)
+    conditional: HashMap<(u32, u32), CellFormat>, // Conditional formatting by absolute (row, col)
+    cell_styles: HashMap<(u32, u32), CellStyle>, // Workbook cell colors (with excel_colors on)
+    cell_alignments: HashMap<(u32, u32), HorizontalAlign>, // Cells centered or aligned in Excel
+    currency_cells: HashSet<(u32, u32)>, // Cells formatted as currency (with a currency_symbol)
+    sheet_visibility: Vec<&'static str>, // "visible", "hidden", or "very hidden" per sheet
+    hidden_cells: HiddenCells,    // Current sheet's hidden rows and columns
+    diff: Option<DiffView>,       // Changes against an older workbook (--diff -i)
+    sheet_changes: HashMap<(u32, u32), CellChange>, // Current sheet's changes by absolute (row, col)
+    pending_change_jump: Option<bool>, // `]`/`[` pressed, waiting for `c` (true = forward)
+    marks: BTreeMap<char, Mark>,       // Bookmarked cells by letter
+    pending_mark: Option<MarkKey>,     // `m` or `'` pressed, waiting for the letter
+    typed_keys: Vec<(KeyCode, crossterm::event::KeyModifiers)>, // Keys of a sequence so far (`g` of `gg`)
+    count: Option<usize>,                 // Count typed before a motion (`25j`)
+    previous_position: Option<Mark>,      // Where the last jump started (`''` goes back)
+    jump_list: JumpList,                  // Jumps to step back and forward through
+    search_start: Option<Mark>,           // Cursor when `/` was pressed, until a match moves it
+    show_marks: bool,                     // Bookmark list popup visible
+    split: Option<Split>,                 // The other pane, when the view is split
+    workbook_name: String,                // File name of the workbook shown
+    workbooks: Vec<Option<WorkbookView>>, // Every open workbook, None for the one shown
+    current_workbook: usize,              // Index of the shown workbook in `workbooks`
+    show_workbooks: bool,                 // Workbook switcher popup visible
+    finder: Option<Finder>,               // Fuzzy finder popup (Ctrl+P), while open
+    workbooks_selected: usize,            // Selected entry in the switcher
     // Search state
     search_mode: bool,                   // Whether we're in search input mode
     search_query: String,                // Current search query
     search_matches: Vec<(usize, usize)>, // List of (row, col) matches
     current_match_index: Option<usize>,  // Index in search_matches
+    search_workbook: bool,               // Whether the search spans all sheets
+    search_options: SearchOptions,       // Match toggles, kept between searches
+    search_error: Option<String>,        // Why the query doesn't compile (bad regex)
+    background_search: Option<BackgroundSearch>, // Lazy-sheet search still running
+    sheet_loader: Option<SheetLoader>,   // Sheet being loaded on a worker thread
+    watcher: Option<FileWatcher>,        // Reloads the sheet when the file changes (--watch)
+    resume: Option<Resume>,              // Saved place in this workbook, restored and saved on quit
+    workbook_matches: Vec<WorkbookMatch>, // Matches across all sheets, in sheet order
+    workbook_match_index: Option<usize>, // Index in workbook_matches
+    show_search_results: bool,           // Workbook search results panel visible
+    search_results_selected: usize,      // Selected entry in the results panel
     // Jump mode state
     jump_mode: bool,    // Whether we're in jump input mode
     jump_input: String, // Current jump input (row number or cell address)
+    // Command line state (`:grep TEXT`)
+    command_mode: bool,    // Whether we're typing a command
+    command_input: String, // Command being typed, without the colon
+    // Filter state
+    filter_mode: bool,             // Whether we're in filter input mode
+    filter_input: String,          // Filter being typed ("COLUMN OP VALUE")
+    row_filter: Option<RowFilter>, // Active filter on the current sheet
+    // Frozen panes (data rows/columns pinned while scrolling; from the sheet or toggled)
+    frozen_rows: usize,
+    frozen_cols: usize,
+    frozen_row_cache: Vec<(usize, Vec<CellValue>)>, // Pinned rows' cells by data row
+    column_views: HashMap<usize, ColumnView>,       // Hidden and pinned columns by sheet index
+    // Visual mode (rectangular selection from the anchor to the cursor)
+    visual_anchor: Option<(usize, usize)>, // (data row, column) where the selection started
+    export_mode: bool,                     // Whether we're typing a file name for the selection
+    export_input: String,                  // File name being typed
+    // Cell editing (values only, saved to a new .xlsx with `:w`)
+    edit_mode: bool,    // Whether we're typing a new value for the cursor cell
+    edit_input: String, // Value being typed
+    edits: HashMap<String, CellEdits>, // Edited cells by sheet name
+    unsaved_edits: bool, // Edits made since the last save
+    quit_warned: bool,  // Quit was pressed once with unsaved edits
+    save_path: Option<PathBuf>, // Where `:w` saves without a file name (--save-as)
+    locale: NumberLocale, // How numbers and dates are shown (--decimal-separator etc.)
+    export_options: ExportOptions, // How `:export` writes the selection (--empty-as etc.)
     // Clipboard state
     copy_feedback: Option<(String, Instant)>, // Message and timestamp for copy feedback
     // Progress state
     progress: Option<ProgressInfo>, // Current operation progress
     // Theme state
-    current_theme: Theme, // Current color theme
+    themes: Vec<NamedTheme>, // Built-in themes, then the config's custom ones
+    current_theme: usize,    // Index into themes
+    striped_rows: bool,      // Shade every other row (:set striped_rows)
+    row_lines: bool,         // Lines between rows (:set row_lines)
+    column_lines: bool,      // Lines between columns (:set column_lines)
+    gutter: bool,            // Excel row numbers and column letters (:set gutter)
+    show_formulas: bool,     // Formula cells show their formulas instead of values
+    page_size: usize,        // Rows a page moves, 0 for the rows on screen (:set page_size=N)
+    scroll_margin: usize,    // Rows kept visible around the cursor (:set scroll_margin=N)
     // Config state
     config: crate::config::Config, // User configuration
 }
@@ -500,40 +1596,43 @@ pub struct TuiState {
 impl TuiState {
     const LAZY_LOADING_THRESHOLD: usize = 1000; // Use lazy loading for sheets with >1000 rows
     const ROW_CACHE_SIZE: usize = 200; // Cache 200 rows at a time for lazy loading
+    const MIN_COLUMN_WIDTH: usize = 3;
+    const SNIPPET_LENGTH: usize = 60; // Characters of a cell shown in the search results
+    const MAX_COUNT: usize = 9_999_999; // Largest count typed before a motion (`25j`)
+    const COLUMN_RESIZE_STEP: isize = 2; // Characters per press of the resize keys
 
     pub fn new(
         mut workbook: Workbook,
         initial_sheet_name: &str,
         config: &crate::config::Config,
-        horizontal_scroll: bool,
     ) -> Result<Self> {
+        let themes = Self::theme_list(config)?;
+        let current_theme = Self::find_theme(&themes, &config.theme.default);
         let sheet_names = workbook.sheet_names();
         let current_sheet_index = sheet_names
             .iter()
             .position(|name| name == initial_sheet_name)
             .unwrap_or(0);
 
-        // Load sheet lazily first to check size
-        let lazy_data = workbook.load_sheet_lazy(&sheet_names[current_sheet_index])?;
-        let sheet_height = lazy_data.height;
+        // The sheet loads on a worker thread while the TUI comes up; until then it's empty
+        let sheet_loader = SheetLoader::spawn(
+            &workbook,
+            current_sheet_index,
+            sheet_names[current_sheet_index].clone(),
+            LoadKind::Initial,
+        );
+        let sheet_data = SheetDataSource::empty();
 
-        // Choose loading strategy based on size
-        let sheet_data = if sheet_height > Self::LAZY_LOADING_THRESHOLD {
-            eprintln!(
-                "📊 Large file detected ({} rows) - using lazy loading",
-                sheet_height
-            );
-            SheetDataSource::Lazy {
-                data: lazy_data,
-                cache: None,
-                cache_size: Self::ROW_CACHE_SIZE,
-            }
-        } else {
-            // Convert to eager loading for small files
-            SheetDataSource::Eager(lazy_data.to_sheet_data())
-        };
+        let warnings = Self::workbook_warnings(&mut workbook);
+        let sheet_visibility = workbook
+            .sheet_visibility()
+            .into_iter()
+            .map(|(_, visibility)| visibility)
+            .collect();
 
-        let mut state = Self {
+        let cell_stats = sheet_data.cell_stats();
+
+        Ok(Self {
             workbook,
             sheet_names,
             current_sheet_index,
@@ -543,41 +1642,178 @@ impl TuiState {
             cursor_col: 0,
             scroll_offset: 0,
             horizontal_scroll_offset: 0,
-            horizontal_scroll_enabled: horizontal_scroll,
             column_widths: Vec::new(),
+            table_viewport: (0, 0),
             show_help: false,
             show_cell_detail: false,
+            show_record: false,
+            record_scroll: 0,
             cell_detail_scroll: 0,
+            cell_detail_evaluated: None,
+            show_pivots: false,
+            pivot_lines: Vec::new(),
+            pivot_scroll: 0,
+            pivot_dialog: None,
+            pivot: None,
+            chart: None,
+            bar_columns: HashMap::new(),
+            heat_columns: HashMap::new(),
+            warnings,
+            cell_stats,
+            sheet_origin: (0, 0),
+            protection: None,
+            hyperlinks: HashMap::new(),
+            conditional: HashMap::new(),
+            cell_styles: HashMap::new(),
+            cell_alignments: HashMap::new(),
+            currency_cells: HashSet::new(),
+            sheet_visibility,
+            hidden_cells: HiddenCells::default(),
+            diff: None,
+            sheet_changes: HashMap::new(),
+            pending_change_jump: None,
+            marks: BTreeMap::new(),
+            pending_mark: None,
+            typed_keys: Vec::new(),
+            count: None,
+            previous_position: None,
+            jump_list: JumpList::default(),
+            search_start: None,
+            show_marks: false,
+            split: None,
+            workbook_name: String::new(),
+            workbooks: Vec::new(),
+            current_workbook: 0,
+            show_workbooks: false,
+            finder: None,
+            workbooks_selected: 0,
             search_mode: false,
             search_query: String::new(),
+            search_options: SearchOptions::default(),
+            search_error: None,
+            background_search: None,
+            sheet_loader: Some(sheet_loader),
+            watcher: None,
+            resume: None,
             search_matches: Vec::new(),
             current_match_index: None,
+            search_workbook: false,
+            workbook_matches: Vec::new(),
+            workbook_match_index: None,
+            show_search_results: false,
+            search_results_selected: 0,
             jump_mode: false,
+            command_mode: false,
+            command_input: String::new(),
             jump_input: String::new(),
+            filter_mode: false,
+            filter_input: String::new(),
+            row_filter: None,
+            frozen_rows: 0,
+            frozen_cols: 0,
+            frozen_row_cache: Vec::new(),
+            column_views: HashMap::new(),
+            visual_anchor: None,
+            export_mode: false,
+            export_input: String::new(),
+            edit_mode: false,
+            edit_input: String::new(),
+            edits: HashMap::new(),
+            unsaved_edits: false,
+            quit_warned: false,
+            save_path: None,
+            locale: NumberLocale::default(),
+            export_options: ExportOptions::default(),
             copy_feedback: None,
             progress: None,
-            current_theme: Self::parse_theme_name(&config.theme.default),
+            themes,
+            current_theme,
+            striped_rows: config.ui.striped_rows,
+            row_lines: config.ui.row_lines,
+            column_lines: config.ui.column_lines,
+            gutter: config.ui.gutter,
+            show_formulas: config.ui.formulas,
+            page_size: config.ui.page_size,
+            scroll_margin: config.ui.scroll_margin,
             config: config.clone(),
-        };
+        })
+    }
+
+    /// Security-relevant findings worth surfacing before the user interacts with the file
+    fn workbook_warnings(workbook: &mut Workbook) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Ok(modules) = workbook.vba_modules()
+            && !modules.is_empty()
+        {
+            let auto_exec: Vec<String> = modules
+                .iter()
+                .flat_map(|m| m.auto_exec_procedures())
+                .collect();
+            let mut message = format!("Contains VBA macros ({} modules", modules.len());
+            if !auto_exec.is_empty() {
+                message.push_str(&format!(", auto-run: {}", auto_exec.join(", ")));
+            }
+            message.push_str(") - inspect with `xleak macros`");
+            warnings.push(message);
+        }
 
-        // Calculate column widths if horizontal scrolling is enabled
-        if horizontal_scroll {
-            state.column_widths = state.calculate_column_widths();
+        if let Ok(refs) = workbook.external_references()
+            && !refs.is_empty()
+        {
+            let mut kinds: Vec<&str> = Vec::new();
+            for r in &refs {
+                if !kinds.contains(&r.kind) {
+                    kinds.push(r.kind);
+                }
+            }
+            warnings.push(format!(
+                "References external sources ({}) - list with `--check-external`",
+                kinds.join(", ")
+            ));
         }
 
-        Ok(state)
+        warnings
     }
 
-    /// Parse theme name from config string
-    fn parse_theme_name(name: &str) -> Theme {
-        match name.to_lowercase().as_str() {
-            "dracula" => Theme::Dracula,
-            "solarized dark" | "solarizeddark" => Theme::SolarizedDark,
-            "solarized light" | "solarizedlight" => Theme::SolarizedLight,
-            "github dark" | "githubdark" => Theme::GitHubDark,
-            "nord" => Theme::Nord,
-            _ => Theme::Default, // Fallback to default for unknown themes
+    /// The built-in themes followed by the custom ones from the config, by name
+    fn theme_list(config: &crate::config::Config) -> Result<Vec<NamedTheme>> {
+        let mut themes: Vec<NamedTheme> = Theme::all()
+            .iter()
+            .map(|theme| NamedTheme {
+                name: theme.name().to_string(),
+                colors: theme.colors(),
+            })
+            .collect();
+        for (name, definition) in &config.themes {
+            let colors = ColorScheme::from_definition(definition)
+                .with_context(|| format!("Invalid theme '{name}'"))?;
+            // A custom theme with a built-in's name replaces it in place
+            let theme = NamedTheme {
+                name: name.clone(),
+                colors,
+            };
+            match themes
+                .iter()
+                .position(|t| theme_key(&t.name) == theme_key(name))
+            {
+                Some(i) => themes[i] = theme,
+                None => themes.push(theme),
+            }
         }
+        Ok(themes)
+    }
+
+    /// Position of the theme with this name, the first (Default) for unknown names
+    fn find_theme(themes: &[NamedTheme], name: &str) -> usize {
+        themes
+            .iter()
+            .position(|theme| theme_key(&theme.name) == theme_key(name))
+            .unwrap_or(0)
+    }
+
+    fn theme(&self) -> &NamedTheme {
+        &self.themes[self.current_theme]
     }
 
     fn current_sheet_name(&self) -> &str {
@@ -589,10 +1825,8 @@ impl TuiState {
             return Ok(()); // No other sheets to switch to
         }
 
-        self.current_sheet_index = (self.current_sheet_index + 1) % self.sheet_names.len();
-        self.load_current_sheet()?;
-        self.reset_cursor();
-        self.clear_search(); // Clear search when changing sheets
+        self.note_jump(self.cursor_mark());
+        self.start_loading_sheet((self.current_sheet_index + 1) % self.sheet_names.len());
         Ok(())
     }
 
@@ -601,947 +1835,4247 @@ impl TuiState {
             return Ok(()); // No other sheets to switch to
         }
 
-        self.current_sheet_index = if self.current_sheet_index == 0 {
+        let sheet = if self.current_sheet_index == 0 {
             self.sheet_names.len() - 1
         } else {
             self.current_sheet_index - 1
         };
-        self.load_current_sheet()?;
-        self.reset_cursor();
-        self.clear_search(); // Clear search when changing sheets
+        self.note_jump(self.cursor_mark());
+        self.start_loading_sheet(sheet);
         Ok(())
     }
 
-    fn load_current_sheet(&mut self) -> Result<()> {
-        let sheet_name = self.sheet_names[self.current_sheet_index].clone();
+    /// Load a sheet on a worker thread; the current one stays on screen until it's ready
+    fn start_loading_sheet(&mut self, sheet: usize) {
+        let name = self.sheet_names[sheet].clone();
+        self.sheet_loader = Some(SheetLoader::spawn(
+            &self.workbook,
+            sheet,
+            name,
+            LoadKind::Switch,
+        ));
+    }
 
-        // Load sheet lazily first to check size
-        let lazy_data = self.workbook.load_sheet_lazy(&sheet_name)?;
-        let sheet_height = lazy_data.height;
+    /// Reopen the file after it changed on disk (--watch) once any load in progress is done
+    fn poll_watcher(&mut self) {
+        if self.sheet_loader.is_some() {
+            return;
+        }
+        if self
+            .watcher
+            .as_mut()
+            .is_some_and(|watcher| watcher.changed())
+        {
+            self.start_reload();
+        }
+    }
 
-        // Choose loading strategy based on size
-        self.sheet_data = if sheet_height > Self::LAZY_LOADING_THRESHOLD {
-            eprintln!(
-                "📊 Large file detected ({} rows) - using lazy loading",
-                sheet_height
-            );
-            SheetDataSource::Lazy {
-                data: lazy_data,
-                cache: None,
-                cache_size: Self::ROW_CACHE_SIZE,
+    /// Start from a saved session: its column widths apply as soon as each sheet loads
+    fn set_resume(&mut self, resume: Resume) {
+        if let Some(session) = &resume.session {
+            for (sheet, widths) in &session.column_widths {
+                if let Some(index) = self.sheet_names.iter().position(|name| name == sheet) {
+                    self.column_views.entry(index).or_default().widths = widths.clone();
+                }
             }
-        } else {
-            // Convert to eager loading for small files
-            SheetDataSource::Eager(lazy_data.to_sheet_data())
-        };
+            if self.config.session.marks {
+                self.marks = session.marks.clone();
+            }
+        }
+        self.resume = Some(resume);
+    }
 
-        // Recalculate column widths if horizontal scrolling is enabled
-        if self.horizontal_scroll_enabled {
-            self.column_widths = self.calculate_column_widths();
+    /// Put the cursor, scroll position, and filter back where the saved session left them
+    fn restore_session(&mut self) {
+        let Some(session) = self
+            .resume
+            .as_mut()
+            .and_then(|resume| resume.session.take())
+        else {
+            return;
+        };
+        if session.sheet != self.current_sheet_name() || self.sheet_data.height() == 0 {
+            return;
+        }
+        self.cursor_row = session.cursor.0.min(self.sheet_data.height() - 1);
+        self.cursor_col = session
+            .cursor
+            .1
+            .min(self.sheet_data.width().saturating_sub(1));
+        self.scroll_offset = session.scroll.0.min(self.cursor_row);
+        self.horizontal_scroll_offset = session.scroll.1.min(self.cursor_col);
+        if let Some(filter) = session.filter {
+            self.filter_input = filter;
+            self.apply_filter();
         }
+        self.copy_feedback = Some((
+            format!("Back at {} where you left off", self.current_cell_address()),
+            Instant::now(),
+        ));
+    }
 
-        Ok(())
+    /// Remember where the TUI is for the next time this workbook is opened
+    fn save_session(&self) -> Result<()> {
+        let Some(resume) = &self.resume else {
+            return Ok(());
+        };
+        // Quit before the first sheet showed up: keep the previous session
+        if self
+            .sheet_loader
+            .as_ref()
+            .is_some_and(|loader| loader.kind == LoadKind::Initial)
+        {
+            return Ok(());
+        }
+        let column_widths = self
+            .column_views
+            .iter()
+            .filter(|(_, view)| !view.widths.is_empty())
+            .filter_map(|(&index, view)| {
+                Some((self.sheet_names.get(index)?.clone(), view.widths.clone()))
+            })
+            .collect();
+        resume.save(Session {
+            sheet: self.current_sheet_name().to_string(),
+            cursor: (self.cursor_row, self.cursor_col),
+            scroll: (self.scroll_offset, self.horizontal_scroll_offset),
+            filter: self.row_filter.as_ref().map(|f| f.description.clone()),
+            column_widths,
+            marks: if self.config.session.marks {
+                self.marks.clone()
+            } else {
+                BTreeMap::new()
+            },
+            saved_at: 0,
+        })
     }
 
-    fn reset_cursor(&mut self) {
-        self.cursor_row = 0;
-        self.cursor_col = 0;
-        self.scroll_offset = 0;
-        self.horizontal_scroll_offset = 0;
+    /// The cursor cell as a bookmark
+    fn cursor_mark(&self) -> Mark {
+        Mark {
+            sheet: self.current_sheet_name().to_string(),
+            row: self.cursor_row,
+            col: self.cursor_col,
+        }
     }
 
-    /// Perform case-insensitive search across all cells
-    fn perform_search(&mut self) {
-        self.search_matches.clear();
-        self.current_match_index = None;
+    fn set_mark(&mut self, letter: char) {
+        self.marks.insert(letter, self.cursor_mark());
+        self.copy_feedback = Some((
+            format!("Mark '{letter}' set at {}", self.current_cell_address()),
+            Instant::now(),
+        ));
+    }
 
-        if self.search_query.is_empty() {
-            self.progress = None;
+    /// Go to a bookmark, switching sheets if needed; `'` goes back to where the last jump
+    /// started
+    fn jump_to_mark(&mut self, letter: char) {
+        let mark = if letter == '\'' {
+            self.previous_position.clone()
+        } else {
+            self.marks.get(&letter).cloned()
+        };
+        let Some(mark) = mark else {
+            self.copy_feedback = Some((format!("Mark '{letter}' isn't set"), Instant::now()));
+            return;
+        };
+        let from = self.cursor_mark();
+        if let Err(message) = self.go_to(&mark) {
+            self.copy_feedback = Some((message, Instant::now()));
             return;
         }
+        self.record_jump(from);
+        self.copy_feedback = Some((
+            format!(
+                "Mark '{letter}': {}!{}",
+                mark.sheet,
+                self.current_cell_address()
+            ),
+            Instant::now(),
+        ));
+    }
 
-        let query_lower = self.search_query.to_lowercase();
-        let total_height = self.sheet_data.height();
+    /// Move the cursor to a position, loading its sheet if needed
+    fn go_to(&mut self, mark: &Mark) -> std::result::Result<(), String> {
+        if mark.sheet != self.current_sheet_name() {
+            let Some(index) = self.sheet_names.iter().position(|name| *name == mark.sheet) else {
+                return Err(format!("Sheet '{}' is gone", mark.sheet));
+            };
+            self.current_sheet_index = index;
+            if let Err(e) = self.load_current_sheet() {
+                return Err(format!("Failed to load sheet '{}': {e}", mark.sheet));
+            }
+            self.reset_cursor();
+            self.refresh_search_for_sheet();
+        }
+        self.cursor_row = mark.row.min(self.sheet_data.height().saturating_sub(1));
+        self.cursor_col = mark.col.min(self.sheet_data.width().saturating_sub(1));
+        self.reveal_cursor_row();
+        Ok(())
+    }
 
-        // Show progress for large sheets
-        if total_height > 1000 {
-            self.progress = Some(ProgressInfo::new("Searching", total_height));
+    /// Note that the cursor jumped away from `from`, unless it's still there
+    fn record_jump(&mut self, from: Mark) {
+        if from != self.cursor_mark() {
+            self.note_jump(from);
         }
+    }
 
-        // Search through all cells (load in chunks for lazy data)
-        const SEARCH_CHUNK_SIZE: usize = 500;
-        for chunk_start in (0..total_height).step_by(SEARCH_CHUNK_SIZE) {
-            let chunk_size = SEARCH_CHUNK_SIZE.min(total_height - chunk_start);
-            let (rows, _formulas) = self.sheet_data.get_rows(chunk_start, chunk_size);
+    /// Note a jump that is still under way, such as a sheet that is loading
+    fn note_jump(&mut self, from: Mark) {
+        self.jump_list.record(from.clone());
+        self.previous_position = Some(from);
+    }
 
-            for (chunk_idx, row) in rows.iter().enumerate() {
-                let row_idx = chunk_start + chunk_idx;
-                for (col_idx, cell) in row.iter().enumerate() {
-                    let cell_str = cell.to_string().to_lowercase();
-                    if cell_str.contains(&query_lower) {
-                        self.search_matches.push((row_idx, col_idx));
-                    }
-                }
-            }
+    /// Step back (or forward) through the jump list
+    fn step_jump_list(&mut self, forward: bool) {
+        let current = self.cursor_mark();
+        let target = if forward {
+            self.jump_list.forward(current)
+        } else {
+            self.jump_list.back(current)
+        };
+        let Some(target) = target else {
+            let end = if forward { "newest" } else { "oldest" };
+            self.copy_feedback = Some((format!("Already at the {end} jump"), Instant::now()));
+            return;
+        };
+        if let Err(message) = self.go_to(&target) {
+            self.copy_feedback = Some((message, Instant::now()));
+        }
+    }
 
-            // Update progress
-            if let Some(ref mut progress) = self.progress {
-                progress.update(chunk_start + chunk_size);
+    /// Split the view: the new pane shows `sheet`, or the same place when it's empty, and
+    /// takes the focus
+    fn open_split(&mut self, direction: Direction, sheet: &str) {
+        let index = if sheet.is_empty() {
+            self.current_sheet_index
+        } else {
+            match self.sheet_names.iter().position(|name| name == sheet) {
+                Some(index) => index,
+                None => {
+                    self.copy_feedback =
+                        Some((format!("No sheet named '{sheet}'"), Instant::now()));
+                    return;
+                }
             }
+        };
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        let same_sheet = index == self.current_sheet_index;
+        let previous = self.split.replace(Split {
+            direction,
+            focus_first: false,
+            pane: PaneView::empty(index),
+        });
+        self.swap_pane_state();
+        if let Err(e) = self.load_current_sheet() {
+            self.swap_pane_state();
+            self.split = previous;
+            self.copy_feedback = Some((
+                format!("Failed to load sheet '{}': {e}", self.sheet_names[index]),
+                Instant::now(),
+            ));
+            return;
         }
-
-        // Clear progress when done
-        self.progress = None;
-
-        // If we found matches, select the first one
-        if !self.search_matches.is_empty() {
-            self.current_match_index = Some(0);
-            self.jump_to_current_match();
+        if same_sheet {
+            self.cursor_row = row;
+            self.cursor_col = col;
         }
+        self.refresh_search_for_sheet();
+        self.copy_feedback = Some((
+            format!(
+                "{} to switch panes, :only to close the other",
+                self.key_label("switch_pane")
+            ),
+            Instant::now(),
+        ));
     }
 
-    /// Jump to the next search match
-    fn jump_to_next_match(&mut self) {
-        if self.search_matches.is_empty() {
+    /// Move the focus to the other pane
+    fn switch_pane(&mut self) {
+        let Some(split) = &mut self.split else {
+            self.copy_feedback = Some((
+                "Only one pane (:split or :vsplit opens another)".to_string(),
+                Instant::now(),
+            ));
             return;
+        };
+        split.focus_first = !split.focus_first;
+        self.swap_pane_state();
+        self.refresh_search_for_sheet();
+    }
+
+    /// `:close`: drop the focused pane and keep the other
+    fn close_pane(&mut self) {
+        if self.split.is_some() {
+            self.swap_pane_state();
+            self.split = None;
+            self.refresh_search_for_sheet();
         }
+    }
 
-        self.current_match_index = Some(match self.current_match_index {
-            Some(idx) => (idx + 1) % self.search_matches.len(),
-            None => 0,
-        });
+    /// Exchange the focused view with the stored pane's
+    fn swap_pane_state(&mut self) {
+        if let Some(mut split) = self.split.take() {
+            self.swap_view(&mut split.pane);
+            self.split = Some(split);
+        }
+    }
 
-        self.jump_to_current_match();
+    fn swap_view(&mut self, pane: &mut PaneView) {
+        std::mem::swap(&mut self.current_sheet_index, &mut pane.sheet_index);
+        std::mem::swap(&mut self.sheet_data, &mut pane.sheet_data);
+        std::mem::swap(&mut self.sheet_origin, &mut pane.sheet_origin);
+        std::mem::swap(&mut self.cursor_row, &mut pane.cursor.0);
+        std::mem::swap(&mut self.cursor_col, &mut pane.cursor.1);
+        std::mem::swap(&mut self.scroll_offset, &mut pane.scroll.0);
+        std::mem::swap(&mut self.horizontal_scroll_offset, &mut pane.scroll.1);
+        std::mem::swap(&mut self.column_widths, &mut pane.column_widths);
+        std::mem::swap(&mut self.table_viewport, &mut pane.table_viewport);
+        std::mem::swap(&mut self.cell_stats, &mut pane.cell_stats);
+        std::mem::swap(&mut self.hyperlinks, &mut pane.hyperlinks);
+        std::mem::swap(&mut self.conditional, &mut pane.conditional);
+        std::mem::swap(&mut self.cell_styles, &mut pane.cell_styles);
+        std::mem::swap(&mut self.cell_alignments, &mut pane.cell_alignments);
+        std::mem::swap(&mut self.currency_cells, &mut pane.currency_cells);
+        std::mem::swap(&mut self.hidden_cells, &mut pane.hidden_cells);
+        std::mem::swap(&mut self.sheet_changes, &mut pane.sheet_changes);
+        std::mem::swap(&mut self.search_matches, &mut pane.search_matches);
+        std::mem::swap(&mut self.current_match_index, &mut pane.current_match_index);
+        std::mem::swap(&mut self.row_filter, &mut pane.row_filter);
+        std::mem::swap(&mut self.frozen_rows, &mut pane.frozen.0);
+        std::mem::swap(&mut self.frozen_cols, &mut pane.frozen.1);
+        std::mem::swap(&mut self.frozen_row_cache, &mut pane.frozen_row_cache);
+        std::mem::swap(&mut self.visual_anchor, &mut pane.visual_anchor);
+        std::mem::swap(&mut self.pivot, &mut pane.pivot);
+        std::mem::swap(&mut self.bar_columns, &mut pane.bar_columns);
+        std::mem::swap(&mut self.heat_columns, &mut pane.heat_columns);
     }
 
-    /// Jump to the previous search match
-    fn jump_to_prev_match(&mut self) {
-        if self.search_matches.is_empty() {
-            return;
+    /// Open the fuzzy finder over the sheets, tables, defined names, and current headers
+    fn open_finder(&mut self) {
+        let mut items: Vec<FinderItem> = self
+            .sheet_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| FinderItem {
+                kind: "sheet",
+                label: name.clone(),
+                target: FinderTarget::Sheet(index),
+            })
+            .collect();
+        // Tables are an .xlsx feature; other formats just have none
+        if self.workbook.load_tables().is_ok() {
+            for sheet in &self.sheet_names {
+                for name in self
+                    .workbook
+                    .table_names_in_sheet(sheet)
+                    .unwrap_or_default()
+                {
+                    items.push(FinderItem {
+                        kind: "table",
+                        label: name.clone(),
+                        target: FinderTarget::Table {
+                            name,
+                            sheet: sheet.clone(),
+                        },
+                    });
+                }
+            }
         }
+        // Names that refer to cells; constants and formulas have nowhere to jump
+        for (name, formula) in self.workbook.defined_names() {
+            let location = formula.trim_start_matches('=');
+            if location.contains('!') && !location.contains(',') {
+                items.push(FinderItem {
+                    kind: "name",
+                    label: name,
+                    target: FinderTarget::Location(location.to_string()),
+                });
+            }
+        }
+        for (col, header) in self.sheet_data.headers().iter().enumerate() {
+            items.push(FinderItem {
+                kind: "column",
+                label: header.clone(),
+                target: FinderTarget::Column(col),
+            });
+        }
+        self.finder = Some(Finder::new(items));
+    }
 
-        self.current_match_index = Some(match self.current_match_index {
-            Some(idx) => {
-                if idx == 0 {
-                    self.search_matches.len() - 1
-                } else {
-                    idx - 1
+    /// Jump to the finder's selection
+    fn finder_jump(&mut self) {
+        let Some(finder) = self.finder.take() else {
+            return;
+        };
+        let Some(item) = finder
+            .matches
+            .get(finder.selected)
+            .and_then(|(index, _)| finder.items.get(*index))
+        else {
+            return;
+        };
+        let message = match &item.target {
+            FinderTarget::Sheet(index) => {
+                if *index != self.current_sheet_index {
+                    self.note_jump(self.cursor_mark());
+                    self.start_loading_sheet(*index);
                 }
+                return;
             }
-            None => self.search_matches.len() - 1,
-        });
+            FinderTarget::Table { name, sheet } => match self.workbook.table_by_name(name) {
+                Ok(table) => {
+                    let (row, col) = table.data_start.unwrap_or_default();
+                    let location =
+                        xleak::workbook::cell_reference(sheet, row as usize, col as usize);
+                    match self.follow_internal_link(&location) {
+                        Ok(_) => format!("Table {name} ({location})"),
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => format!("Couldn't open table {name}: {e}"),
+            },
+            FinderTarget::Location(location) => match self.follow_internal_link(location) {
+                Ok(_) => format!("{} ({location})", item.label),
+                Err(e) => e,
+            },
+            FinderTarget::Column(col) => {
+                let from = self.cursor_mark();
+                self.cursor_col = *col;
+                self.reveal_cursor_col();
+                self.record_jump(from);
+                format!("Column {}", item.label)
+            }
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
 
-        self.jump_to_current_match();
+    /// Keep another workbook open; it loads the first time it's switched to
+    fn add_workbook(&mut self, open: OpenWorkbook) {
+        let OpenWorkbook {
+            name,
+            mut workbook,
+            sheet,
+            watcher,
+            resume,
+        } = open;
+        if self.workbooks.is_empty() {
+            self.workbooks.push(None);
+        }
+        let sheet_names = workbook.sheet_names();
+        let sheet_index = sheet_names
+            .iter()
+            .position(|name| *name == sheet)
+            .unwrap_or(0);
+        self.workbooks.push(Some(WorkbookView {
+            name,
+            warnings: Self::workbook_warnings(&mut workbook),
+            sheet_visibility: workbook
+                .sheet_visibility()
+                .into_iter()
+                .map(|(_, visibility)| visibility)
+                .collect(),
+            workbook,
+            sheet_names,
+            protection: None,
+            pane: PaneView::empty(sheet_index),
+            split: None,
+            column_views: HashMap::new(),
+            edits: HashMap::new(),
+            unsaved_edits: false,
+            save_path: None,
+            marks: BTreeMap::new(),
+            previous_position: None,
+            jump_list: JumpList::default(),
+            watcher,
+            resume,
+            loaded: false,
+        }));
     }
 
-    /// Move cursor to the current search match
-    fn jump_to_current_match(&mut self) {
-        if let Some(idx) = self.current_match_index
-            && let Some(&(row, col)) = self.search_matches.get(idx)
-        {
-            self.cursor_row = row;
-            self.cursor_col = col;
+    /// Show another open workbook as it was left, loading its sheet the first time
+    fn switch_workbook(&mut self, index: usize) {
+        let Some(mut view) = self.workbooks.get_mut(index).and_then(Option::take) else {
+            return;
+        };
+        self.swap_workbook_state(&mut view);
+        let loaded = std::mem::replace(&mut view.loaded, true);
+        self.workbooks[self.current_workbook] = Some(view);
+        self.current_workbook = index;
+        self.clear_search();
+        if !loaded {
+            if let Some(resume) = self.resume.take() {
+                self.set_resume(resume);
+            }
+            self.sheet_loader = Some(SheetLoader::spawn(
+                &self.workbook,
+                self.current_sheet_index,
+                self.current_sheet_name().to_string(),
+                LoadKind::Open,
+            ));
         }
     }
 
-    /// Clear search state
-    fn clear_search(&mut self) {
-        self.search_query.clear();
-        self.search_matches.clear();
-        self.current_match_index = None;
+    fn swap_workbook_state(&mut self, view: &mut WorkbookView) {
+        std::mem::swap(&mut self.workbook_name, &mut view.name);
+        std::mem::swap(&mut self.workbook, &mut view.workbook);
+        std::mem::swap(&mut self.sheet_names, &mut view.sheet_names);
+        std::mem::swap(&mut self.sheet_visibility, &mut view.sheet_visibility);
+        std::mem::swap(&mut self.warnings, &mut view.warnings);
+        std::mem::swap(&mut self.protection, &mut view.protection);
+        self.swap_view(&mut view.pane);
+        std::mem::swap(&mut self.split, &mut view.split);
+        std::mem::swap(&mut self.column_views, &mut view.column_views);
+        std::mem::swap(&mut self.edits, &mut view.edits);
+        std::mem::swap(&mut self.unsaved_edits, &mut view.unsaved_edits);
+        std::mem::swap(&mut self.save_path, &mut view.save_path);
+        std::mem::swap(&mut self.marks, &mut view.marks);
+        std::mem::swap(&mut self.previous_position, &mut view.previous_position);
+        std::mem::swap(&mut self.jump_list, &mut view.jump_list);
+        std::mem::swap(&mut self.watcher, &mut view.watcher);
+        std::mem::swap(&mut self.resume, &mut view.resume);
     }
 
-    /// Enter jump mode
-    fn enter_jump_mode(&mut self) {
-        self.jump_mode = true;
-        self.jump_input.clear();
+    /// Save where every workbook that was shown is left, for the next time it's opened
+    fn save_sessions(&mut self) -> Result<()> {
+        self.save_session()?;
+        for index in 0..self.workbooks.len() {
+            if self.workbooks[index]
+                .as_ref()
+                .is_some_and(|view| view.loaded)
+            {
+                self.switch_workbook(index);
+                self.save_session()?;
+            }
+        }
+        Ok(())
     }
 
-    /// Parse jump input and navigate to that location
-    /// Supports formats: "100" (row), "A5" (cell address), "5,3" (row,col)
-    fn perform_jump(&mut self) {
-        if self.jump_input.is_empty() {
-            self.jump_mode = false;
+    /// After a reload, load the unfocused pane's sheet again from the new workbook
+    fn reload_other_pane(&mut self) {
+        if self.split.is_none() {
             return;
         }
+        self.swap_pane_state();
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        if self.current_sheet_index < self.sheet_names.len() && self.load_current_sheet().is_ok() {
+            self.cursor_row = row.min(self.sheet_data.height().saturating_sub(1));
+            self.cursor_col = col.min(self.sheet_data.width().saturating_sub(1));
+            self.refresh_search_for_sheet();
+            self.swap_pane_state();
+        } else {
+            // The sheet is gone or unreadable; keep the focused pane only
+            self.swap_pane_state();
+            self.split = None;
+        }
+    }
 
-        let input = self.jump_input.trim();
-
-        // Try to parse as row number (1-indexed)
-        if let Ok(row_num) = input.parse::<usize>() {
-            if row_num > 0 && row_num <= self.sheet_data.height() {
-                self.cursor_row = row_num - 1; // Convert to 0-indexed
-                self.copy_feedback = Some((format!("Jumped to row {}", row_num), Instant::now()));
+    fn delete_marks(&mut self, letters: &str) {
+        let message = if letters == "!" {
+            self.marks.clear();
+            "Deleted all marks".to_string()
+        } else {
+            let letters: Vec<char> = letters
+                .chars()
+                .filter(|letter| self.marks.remove(letter).is_some())
+                .collect();
+            if letters.is_empty() {
+                "No such marks".to_string()
             } else {
-                self.copy_feedback = Some((
-                    format!(
-                        "Invalid row: {} (max: {})",
-                        row_num,
-                        self.sheet_data.height()
-                    ),
-                    Instant::now(),
-                ));
+                format!("Deleted marks {}", letters.into_iter().collect::<String>())
             }
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Reopen the workbook file and reload the current sheet on a worker thread
+    fn start_reload(&mut self) {
+        self.sheet_loader = Some(SheetLoader::spawn(
+            &self.workbook,
+            self.current_sheet_index,
+            self.current_sheet_name().to_string(),
+            LoadKind::Reload,
+        ));
+    }
+
+    /// Swap in the reopened workbook, keeping the cursor, scroll position, and filter
+    fn show_reloaded_sheet(&mut self, workbook: Workbook, name: &str, data: LazySheetData) {
+        self.workbook = workbook;
+        self.sheet_names = self.workbook.sheet_names();
+        self.sheet_visibility = self
+            .workbook
+            .sheet_visibility()
+            .into_iter()
+            .map(|(_, visibility)| visibility)
+            .collect();
+        self.current_sheet_index = self
+            .sheet_names
+            .iter()
+            .position(|sheet| sheet == name)
+            .unwrap_or(0);
+        self.protection = None;
+
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        let (scroll, horizontal_scroll) = (self.scroll_offset, self.horizontal_scroll_offset);
+        let filter = self.row_filter.as_ref().map(|f| f.description.clone());
+        let bars: Vec<usize> = self.bar_columns.keys().copied().collect();
+        let heat: Vec<usize> = self.heat_columns.keys().copied().collect();
+        self.show_sheet(data);
+        for bar_col in bars {
+            self.show_bars(bar_col);
         }
-        // Try to parse as cell address like "A5" or "B10"
-        else if let Some((col, row)) = Self::parse_cell_address(input) {
-            if row < self.sheet_data.height() && col < self.sheet_data.width() {
-                self.cursor_row = row;
-                self.cursor_col = col;
-                self.copy_feedback = Some((
-                    format!("Jumped to {}", input.to_uppercase()),
-                    Instant::now(),
-                ));
-            } else {
+        for heat_col in heat {
+            self.show_heatmap(heat_col);
+        }
+        self.cursor_row = row.min(self.sheet_data.height().saturating_sub(1));
+        self.cursor_col = col.min(self.sheet_data.width().saturating_sub(1));
+        self.scroll_offset = scroll.min(self.cursor_row);
+        self.horizontal_scroll_offset = horizontal_scroll.min(self.cursor_col);
+        let mut message = format!("Reloaded '{name}' from disk");
+        if let Some(filter) = filter {
+            self.filter_input = filter;
+            self.apply_filter();
+            if self.row_filter.is_none() {
+                message.push_str("; no rows match the filter anymore");
+            }
+        }
+        self.refresh_search_for_sheet();
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Show the sheet once its worker is done; fails only when the first sheet can't load
+    fn poll_sheet_loader(&mut self) -> Result<()> {
+        let Some(loader) = &self.sheet_loader else {
+            return Ok(());
+        };
+        let result = match loader.result.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            Err(mpsc::TryRecvError::Disconnected) => Err(anyhow::anyhow!("the loader stopped")),
+        };
+        let Some(loader) = self.sheet_loader.take() else {
+            return Ok(());
+        };
+        let name = &loader.name;
+        match (result, loader.kind) {
+            (Ok((workbook, data)), LoadKind::Reload) => {
+                self.show_reloaded_sheet(workbook, name, data);
+                self.reload_other_pane();
+            }
+            (Ok((_, data)), kind) => {
+                self.current_sheet_index = loader.sheet;
+                self.show_sheet(data);
+                self.reset_cursor();
+                if kind == LoadKind::Initial || kind == LoadKind::Open {
+                    self.restore_session();
+                }
+                self.refresh_search_for_sheet();
+            }
+            (Err(e), LoadKind::Initial) => {
+                return Err(e).with_context(|| format!("Failed to load sheet '{name}'"));
+            }
+            (Err(e), LoadKind::Switch | LoadKind::Open) => {
                 self.copy_feedback = Some((
-                    format!("Cell address out of bounds: {}", input),
+                    format!("Failed to load sheet '{name}': {e}"),
                     Instant::now(),
                 ));
             }
-        }
-        // Try to parse as "row,col" format
-        else if let Some((row, col)) = input.split_once(',') {
-            if let (Ok(row_num), Ok(col_num)) =
-                (row.trim().parse::<usize>(), col.trim().parse::<usize>())
-            {
-                if row_num > 0
-                    && row_num <= self.sheet_data.height()
-                    && col_num > 0
-                    && col_num <= self.sheet_data.width()
-                {
-                    self.cursor_row = row_num - 1;
-                    self.cursor_col = col_num - 1;
-                    self.copy_feedback = Some((
-                        format!("Jumped to row {}, col {}", row_num, col_num),
-                        Instant::now(),
-                    ));
-                } else {
-                    self.copy_feedback =
-                        Some(("Invalid row/column number".to_string(), Instant::now()));
-                }
-            } else {
+            // Often a file caught mid-write; the next change tries again
+            (Err(e), LoadKind::Reload) => {
                 self.copy_feedback = Some((
-                    "Invalid format. Use: row number, cell (A5), or row,col".to_string(),
+                    format!("Failed to reload '{name}', showing the old data: {e}"),
                     Instant::now(),
                 ));
             }
-        } else {
-            self.copy_feedback = Some((
-                "Invalid format. Use: row number, cell (A5), or row,col".to_string(),
-                Instant::now(),
-            ));
         }
-
-        self.jump_mode = false;
-        self.jump_input.clear();
+        Ok(())
     }
 
-    /// Parse cell address like "A5", "B10", "AA100" into (col, row) indices
-    fn parse_cell_address(addr: &str) -> Option<(usize, usize)> {
-        let addr = addr.to_uppercase();
-        let mut col = 0usize;
-        let mut row_str = String::new();
-
-        for ch in addr.chars() {
-            if ch.is_ascii_alphabetic() {
-                col = col * 26 + (ch as usize - 'A' as usize + 1);
-            } else if ch.is_ascii_digit() {
-                row_str.push(ch);
-            } else {
-                return None;
+    /// Stop loading a sheet; there's nothing to show if it was the first one
+    fn cancel_sheet_load(&mut self) {
+        let Some(loader) = self.sheet_loader.take() else {
+            return;
+        };
+        let message = match loader.kind {
+            LoadKind::Initial => {
+                self.should_quit = true;
+                return;
             }
-        }
-
-        if row_str.is_empty() || col == 0 {
-            return None;
-        }
-
-        let row = row_str.parse::<usize>().ok()?;
-        Some((col - 1, row - 1)) // Convert to 0-indexed
+            LoadKind::Switch | LoadKind::Open => format!("Stopped loading '{}'", loader.name),
+            LoadKind::Reload => {
+                format!("Stopped reloading '{}'; showing the old data", loader.name)
+            }
+        };
+        self.copy_feedback = Some((message, Instant::now()));
     }
 
-    /// Copy the current cell value to clipboard
-    fn copy_current_cell(&mut self) {
-        let (cell, _formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
-        let cell_value = cell.map(|v| v.to_raw_string()).unwrap_or_default();
+    fn load_current_sheet(&mut self) -> Result<()> {
+        self.sheet_loader = None;
+        let sheet_name = self.current_sheet_name().to_string();
+        let lazy_data = self.workbook.load_sheet_lazy(&sheet_name)?;
+        self.show_sheet(lazy_data);
+        Ok(())
+    }
 
-        match Clipboard::new() {
-            Ok(mut clipboard) => {
-                if let Err(e) = clipboard.set_text(&cell_value) {
-                    self.copy_feedback = Some((format!("Copy failed: {}", e), Instant::now()));
-                } else {
-                    let cell_addr = self.current_cell_address();
-                    self.copy_feedback =
-                        Some((format!("Copied cell {}", cell_addr), Instant::now()));
+    /// Make a loaded sheet the current sheet's data
+    fn show_sheet(&mut self, lazy_data: LazySheetData) {
+        let sheet_name = self.current_sheet_name().to_string();
+        let sheet_height = lazy_data.height;
+        self.sheet_origin = lazy_data.origin();
+        self.row_filter = None;
+        self.pivot = None;
+        self.bar_columns.clear();
+        self.heat_columns.clear();
+        self.visual_anchor = None;
+        self.frozen_row_cache.clear();
+
+        // Choose loading strategy based on size; sparse sheets would fill in every cell
+        self.sheet_data = if sheet_height > Self::LAZY_LOADING_THRESHOLD || lazy_data.is_sparse() {
+            SheetDataSource::Lazy {
+                data: lazy_data,
+                cache: None,
+                cache_size: Self::ROW_CACHE_SIZE,
+            }
+        } else {
+            // Convert to eager loading for small files
+            SheetDataSource::Eager(lazy_data.to_sheet_data())
+        };
+        // Edits are kept by absolute position; put them back into the reloaded rows
+        if let Some(edits) = self.edits.get(&sheet_name) {
+            let (origin_row, origin_col) = self.sheet_origin;
+            for (&(row, col), value) in edits {
+                if let (Some(row), Some(col)) =
+                    (row.checked_sub(origin_row), col.checked_sub(origin_col))
+                {
+                    self.sheet_data.set_cell(row, col, value.clone());
                 }
             }
-            Err(e) => {
-                self.copy_feedback = Some((format!("Clipboard error: {}", e), Instant::now()));
+        }
+        self.cell_stats = self.sheet_data.cell_stats();
+        self.load_sheet_annotations();
+        self.column_widths = self.calculate_column_widths();
+        if let Some(view) = self.column_views.get(&self.current_sheet_index) {
+            for (&col, &width) in &view.widths {
+                if let Some(w) = self.column_widths.get_mut(col) {
+                    *w = width;
+                }
             }
         }
     }
 
-    /// Copy the current row to clipboard (tab-separated)
-    fn copy_current_row(&mut self) {
-        let (rows, _formulas) = self.sheet_data.get_rows(self.cursor_row, 1);
-        let row_values = rows
-            .first()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| {
-                        let value = cell.to_raw_string();
-                        // Escape cells that contain tabs, newlines, or quotes
-                        if value.contains('\t') || value.contains('\n') || value.contains('"') {
-                            format!("\"{}\"", value.replace('"', "\"\""))
-                        } else {
-                            value
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\t")
-            })
-            .unwrap_or_default();
+    fn reset_cursor(&mut self) {
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+        self.scroll_offset = 0;
+        self.horizontal_scroll_offset = 0;
+    }
 
-        match Clipboard::new() {
-            Ok(mut clipboard) => {
-                if let Err(e) = clipboard.set_text(&row_values) {
-                    self.copy_feedback = Some((format!("Copy failed: {}", e), Instant::now()));
-                } else {
-                    self.copy_feedback = Some((
-                        format!(
-                            "Copied row {} ({} cells)",
-                            self.cursor_row + 1,
-                            self.sheet_data.width()
-                        ),
-                        Instant::now(),
-                    ));
-                }
+    /// Compile the query with the current options, noting a bad regex for the prompt
+    fn search_matcher(&mut self) -> Option<(Regex, Option<usize>)> {
+        self.search_error = None;
+        if self.search_query.is_empty() {
+            return None;
+        }
+        match self.search_options.matcher(&self.search_query) {
+            Ok(matcher) => {
+                let column = self
+                    .search_options
+                    .current_column
+                    .then_some(self.cursor_col);
+                Some((matcher, column))
             }
-            Err(e) => {
-                self.copy_feedback = Some((format!("Clipboard error: {}", e), Instant::now()));
+            Err(_) => {
+                self.search_error = Some("invalid regex".to_string());
+                None
             }
         }
     }
 
-    fn move_up(&mut self) {
-        if self.cursor_row > 0 {
-            self.cursor_row -= 1;
-            // Auto-scroll up if cursor moves above viewport
-            if self.cursor_row < self.scroll_offset {
-                self.scroll_offset = self.cursor_row;
-            }
-        }
-    }
+    /// Search all cells (case-insensitive substring unless the search options say otherwise);
+    /// lazy sheets are searched on a worker thread, see `poll_background_search`
+    fn perform_search(&mut self) {
+        self.search_matches.clear();
+        self.current_match_index = None;
+        self.background_search = None;
 
-    fn move_down(&mut self) {
-        if self.cursor_row < self.sheet_data.height().saturating_sub(1) {
-            self.cursor_row += 1;
-            // Auto-scroll down will be handled in render based on viewport height
-        }
-    }
+        let Some((matcher, column)) = self.search_matcher() else {
+            self.progress = None;
+            return;
+        };
+        let total_height = self.sheet_data.height();
 
-    /// Update scroll offset to keep cursor visible
-    fn update_scroll(&mut self, viewport_height: usize) {
-        // Scroll down if cursor is below visible area
-        if self.cursor_row >= self.scroll_offset + viewport_height {
-            self.scroll_offset = self.cursor_row.saturating_sub(viewport_height - 1);
-        }
-        // Scroll up if cursor is above visible area
-        if self.cursor_row < self.scroll_offset {
-            self.scroll_offset = self.cursor_row;
+        if let Some(data) = self.sheet_data.background_source() {
+            self.background_search = Some(BackgroundSearch::spawn(
+                data,
+                matcher,
+                column,
+                self.locale.clone(),
+            ));
+            self.progress = Some(ProgressInfo::new("Searching (Esc to stop)", total_height));
+            return;
         }
-    }
-
-    /// Calculate column widths based on content
-    fn calculate_column_widths(&mut self) -> Vec<usize> {
-        let num_cols = self.sheet_data.width();
-        let mut widths = vec![0; num_cols];
 
-        // Measure headers
-        let headers = self.sheet_data.headers();
-        for (i, header) in headers.iter().enumerate() {
-            widths[i] = header.len();
+        // Show progress for large sheets
+        if total_height > 1000 {
+            self.progress = Some(ProgressInfo::new("Searching", total_height));
         }
 
-        // Sample first 100 rows (or fewer if sheet is smaller)
-        let sample_size = 100.min(self.sheet_data.height());
-        let (sample_rows, _) = self.sheet_data.get_rows(0, sample_size);
+        // Search through all cells (load in chunks for lazy data)
+        const SEARCH_CHUNK_SIZE: usize = 500;
+        for chunk_start in (0..total_height).step_by(SEARCH_CHUNK_SIZE) {
+            let chunk_size = SEARCH_CHUNK_SIZE.min(total_height - chunk_start);
+            let (rows, _formulas) = self.sheet_data.get_rows(chunk_start, chunk_size);
+            for (row, col, _) in find_matches(rows, chunk_start, &matcher, column, &self.locale) {
+                self.search_matches.push((row, col));
+            }
 
-        for row in sample_rows.iter() {
-            for (col_idx, cell) in row.iter().enumerate() {
-                let len = cell.to_string().len();
-                widths[col_idx] = widths[col_idx].max(len);
+            // Update progress
+            if let Some(ref mut progress) = self.progress {
+                progress.update(chunk_start + chunk_size);
             }
         }
 
-        // Apply constraints: min 3 chars, max 30 chars
-        widths.iter().map(|&w| w.clamp(3, 30)).collect()
+        // Clear progress when done
+        self.progress = None;
+
+        // If we found matches, select the first one
+        if !self.search_matches.is_empty() {
+            self.current_match_index = Some(0);
+            self.jump_to_current_match();
+        }
     }
 
-    /// Update horizontal scroll offset to keep cursor visible
-    fn update_horizontal_scroll(&mut self, viewport_width: usize) {
-        if !self.horizontal_scroll_enabled {
+    /// Take in the matches a background search found since the last call; the first one
+    /// moves the cursor, and the rest can be navigated while the search goes on
+    fn poll_background_search(&mut self) {
+        let Some(search) = &self.background_search else {
             return;
+        };
+        let mut finished = false;
+        let mut rows_done = None;
+        for update in search.updates.try_iter() {
+            match update {
+                SearchUpdate::Found {
+                    matches,
+                    rows_done: done,
+                } => {
+                    self.search_matches.extend(matches);
+                    rows_done = Some(done);
+                }
+                SearchUpdate::Done => finished = true,
+            }
         }
-
-        // Calculate which columns are visible
-        let mut total_width = 0;
-        let mut visible_end = self.horizontal_scroll_offset;
-
-        for i in self.horizontal_scroll_offset..self.column_widths.len() {
-            total_width += self.column_widths[i] + 1; // +1 for separator
-            visible_end = i + 1; // Always include current column
-            if total_width > viewport_width {
-                break; // Break after including partially-visible column
+        if let (Some(done), Some(progress)) = (rows_done, &mut self.progress) {
+            progress.message = format!(
+                "Searching, {} found (Esc to stop)",
+                self.search_matches.len()
+            );
+            progress.update(done);
+        }
+        if self.current_match_index.is_none() && !self.search_matches.is_empty() {
+            self.current_match_index = Some(0);
+            self.jump_to_current_match();
+        }
+        if finished {
+            self.background_search = None;
+            self.progress = None;
+            if self.search_matches.is_empty() {
+                self.copy_feedback = Some((
+                    format!("No matches for '{}'", self.search_query),
+                    Instant::now(),
+                ));
             }
         }
+    }
+
+    /// Stop a background search, keeping the matches it found so far
+    fn stop_background_search(&mut self) {
+        let searched = self.progress.as_ref().map_or(0, |p| p.current);
+        self.background_search = None;
+        self.progress = None;
+        self.copy_feedback = Some((
+            format!(
+                "Search stopped: {} matches in the first {searched} rows",
+                self.search_matches.len()
+            ),
+            Instant::now(),
+        ));
+    }
+
+    /// Search every sheet, loading the others from the workbook, then open the results panel
+    fn perform_workbook_search(&mut self) {
+        self.workbook_matches.clear();
+        self.workbook_match_index = None;
+        let Some((matcher, column)) = self.search_matcher() else {
+            return;
+        };
 
-        // Scroll right if cursor is beyond visible area
-        if self.cursor_col >= visible_end {
-            self.horizontal_scroll_offset += 1;
+        for sheet in 0..self.sheet_names.len() {
+            let found = if sheet == self.current_sheet_index {
+                let (rows, _formulas) = self.sheet_data.get_rows(0, self.sheet_data.height());
+                find_matches(rows, 0, &matcher, column, &self.locale)
+            } else {
+                match self.workbook.load_sheet(&self.sheet_names[sheet]) {
+                    Ok(data) => find_matches(&data.rows, 0, &matcher, column, &self.locale),
+                    Err(_) => continue,
+                }
+            };
+            self.workbook_matches
+                .extend(found.into_iter().map(|(row, col, text)| WorkbookMatch {
+                    sheet,
+                    row,
+                    col,
+                    text,
+                }));
         }
 
-        // Scroll left if cursor is before visible area
-        if self.cursor_col < self.horizontal_scroll_offset {
-            self.horizontal_scroll_offset = self.cursor_col;
+        if self.workbook_matches.is_empty() {
+            self.copy_feedback = Some((
+                format!("No matches for '{}' in any sheet", self.search_query),
+                Instant::now(),
+            ));
+            return;
         }
+        // Start from the first match on or after the current sheet
+        let first = self
+            .workbook_matches
+            .iter()
+            .position(|m| m.sheet >= self.current_sheet_index)
+            .unwrap_or(0);
+        self.workbook_match_index = Some(first);
+        self.jump_to_workbook_match();
+        self.search_results_selected = first;
+        self.show_search_results = true;
     }
 
-    fn move_left(&mut self) {
-        if self.cursor_col > 0 {
-            self.cursor_col -= 1;
-            // Auto-scroll left if cursor moves before visible area
-            if self.horizontal_scroll_enabled && self.cursor_col < self.horizontal_scroll_offset {
-                self.horizontal_scroll_offset = self.cursor_col;
+    /// Move to the current workbook match, switching sheets if needed
+    fn jump_to_workbook_match(&mut self) {
+        let Some(m) = self
+            .workbook_match_index
+            .and_then(|idx| self.workbook_matches.get(idx))
+            .cloned()
+        else {
+            return;
+        };
+        let from = self.cursor_mark();
+        if m.sheet != self.current_sheet_index {
+            self.current_sheet_index = m.sheet;
+            if self.load_current_sheet().is_err() {
+                return;
             }
+            self.reset_cursor();
         }
+        self.refresh_search_for_sheet();
+        self.cursor_row = m.row;
+        self.cursor_col = m.col;
+        self.record_jump(from);
     }
 
-    fn move_right(&mut self) {
-        if self.cursor_col < self.sheet_data.width().saturating_sub(1) {
-            self.cursor_col += 1;
-            // Auto-scroll right will be handled in render based on viewport width
+    /// After a sheet change: keep workbook search highlights, or drop a single-sheet search
+    fn refresh_search_for_sheet(&mut self) {
+        if !self.search_workbook {
+            self.clear_search();
+            return;
         }
+        let sheet = self.current_sheet_index;
+        self.search_matches = self
+            .workbook_matches
+            .iter()
+            .filter(|m| m.sheet == sheet)
+            .map(|m| (m.row, m.col))
+            .collect();
+        self.current_match_index = self
+            .workbook_match_index
+            .and_then(|idx| self.workbook_matches.get(idx))
+            .filter(|m| m.sheet == sheet)
+            .and_then(|m| {
+                self.search_matches
+                    .iter()
+                    .position(|&pos| pos == (m.row, m.col))
+            });
     }
 
-    fn move_to_start_of_row(&mut self) {
-        self.cursor_col = 0;
-        if self.horizontal_scroll_enabled {
-            self.horizontal_scroll_offset = 0;
+    /// Jump to the next search match
+    fn jump_to_next_match(&mut self) {
+        if self.search_workbook {
+            if !self.workbook_matches.is_empty() {
+                self.workbook_match_index = Some(match self.workbook_match_index {
+                    Some(idx) => (idx + 1) % self.workbook_matches.len(),
+                    None => 0,
+                });
+                self.jump_to_workbook_match();
+            }
+            return;
+        }
+        if self.search_matches.is_empty() {
+            return;
         }
-    }
 
-    fn move_to_end_of_row(&mut self) {
-        self.cursor_col = self.sheet_data.width().saturating_sub(1);
-        // Horizontal scroll will be updated in render to show the last column
-    }
+        self.current_match_index = Some(match self.current_match_index {
+            Some(idx) => (idx + 1) % self.search_matches.len(),
+            None => 0,
+        });
 
-    fn page_up(&mut self, page_size: usize) {
-        self.cursor_row = self.cursor_row.saturating_sub(page_size);
+        self.jump_to_current_match();
     }
 
-    fn page_down(&mut self, page_size: usize) {
-        self.cursor_row =
-            (self.cursor_row + page_size).min(self.sheet_data.height().saturating_sub(1));
-    }
+    /// Jump to the previous search match
+    fn jump_to_prev_match(&mut self) {
+        if self.search_workbook {
+            let len = self.workbook_matches.len();
+            if len > 0 {
+                self.workbook_match_index = Some(match self.workbook_match_index {
+                    Some(idx) => (idx + len - 1) % len,
+                    None => len - 1,
+                });
+                self.jump_to_workbook_match();
+            }
+            return;
+        }
+        if self.search_matches.is_empty() {
+            return;
+        }
 
-    fn move_to_top(&mut self) {
-        self.cursor_row = 0;
-    }
+        self.current_match_index = Some(match self.current_match_index {
+            Some(idx) => {
+                if idx == 0 {
+                    self.search_matches.len() - 1
+                } else {
+                    idx - 1
+                }
+            }
+            None => self.search_matches.len() - 1,
+        });
 
-    fn move_to_bottom(&mut self) {
-        self.cursor_row = self.sheet_data.height().saturating_sub(1);
+        self.jump_to_current_match();
     }
 
-    fn col_to_letter(&self, col: usize) -> String {
-        let mut result = String::new();
-        let mut n = col + 1;
-        while n > 0 {
-            n -= 1;
-            result.push((b'A' + (n % 26) as u8) as char);
-            n /= 26;
+    /// Move cursor to the current search match; while typing a search, only the first
+    /// move counts as a jump
+    fn jump_to_current_match(&mut self) {
+        if let Some(idx) = self.current_match_index
+            && let Some(&(row, col)) = self.search_matches.get(idx)
+        {
+            let from = if self.search_mode {
+                self.search_start.take()
+            } else {
+                Some(self.cursor_mark())
+            };
+            self.cursor_row = row;
+            self.cursor_col = col;
+            if let Some(from) = from {
+                self.record_jump(from);
+            }
         }
-        result.chars().rev().collect()
-    }
-
-    fn current_cell_address(&self) -> String {
-        format!(
-            "{}{}",
-            self.col_to_letter(self.cursor_col),
-            self.cursor_row + 1
-        )
     }
 
-    /// Check if a key press matches a configured action
-    fn key_matches(
-        &self,
-        code: KeyCode,
-        modifiers: crossterm::event::KeyModifiers,
-        action: &str,
-    ) -> bool {
-        if let Some((expected_code, expected_mods)) = self.config.get_keybinding(action) {
-            if code != expected_code {
-                return false;
-            }
-            // Some terminals omit SHIFT for shifted symbol characters (e.g. '?', '$')
-            // because the shift is already encoded in the character itself. Strip SHIFT
-            // from both sides for non-alphabetic chars so bindings work consistently.
-            if let KeyCode::Char(c) = code
-                && !c.is_alphabetic()
-            {
-                let strip = crossterm::event::KeyModifiers::SHIFT;
-                return (modifiers - strip) == (expected_mods - strip);
-            }
-            modifiers == expected_mods
-        } else {
-            false
+    /// Clear search state
+    fn clear_search(&mut self) {
+        self.search_query.clear();
+        self.search_error = None;
+        if self.background_search.take().is_some() {
+            self.progress = None;
         }
+        self.search_matches.clear();
+        self.current_match_index = None;
+        self.search_workbook = false;
+        self.workbook_matches.clear();
+        self.workbook_match_index = None;
+        self.show_search_results = false;
     }
 
-    fn handle_event(&mut self, event: Event) {
-        if let Event::Key(KeyEvent {
-            code,
-            modifiers,
-            kind: KeyEventKind::Press,
-            ..
-        }) = event
-        {
-            // If help is showing, any key closes it
-            if self.show_help {
-                self.show_help = false;
-                return;
-            }
-
-            // If cell detail is showing, handle scrolling or close
-            if self.show_cell_detail {
-                match code {
-                    KeyCode::Up => {
-                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(1);
-                    }
-                    KeyCode::Down => {
-                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(1);
-                    }
-                    KeyCode::PageUp => {
-                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(10);
-                    }
-                    KeyCode::PageDown => {
-                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(10);
-                    }
-                    KeyCode::Home => {
-                        self.cell_detail_scroll = 0;
-                    }
-                    _ => {
-                        // Any other key closes the detail view
-                        self.show_cell_detail = false;
-                        self.cell_detail_scroll = 0;
-                    }
-                }
-                return;
-            }
-
-            // If in search mode, handle search input
-            if self.search_mode {
-                match code {
-                    KeyCode::Char(c) => {
-                        self.search_query.push(c);
-                        self.perform_search();
-                    }
-                    KeyCode::Backspace => {
-                        self.search_query.pop();
-                        self.perform_search();
-                    }
-                    KeyCode::Enter => {
-                        // Exit search mode but keep results
-                        self.search_mode = false;
-                    }
-                    KeyCode::Esc => {
-                        // Exit search mode and clear search
-                        self.search_mode = false;
-                        self.clear_search();
-                    }
-                    _ => {}
-                }
-                return;
+    /// Open the pivot table popup, reading definitions from the workbook
+    fn open_pivot_view(&mut self) {
+        self.pivot_lines = match self.workbook.pivot_tables() {
+            Ok(pivots) if pivots.is_empty() => {
+                vec!["No pivot tables found in workbook".to_string()]
             }
-
-            // If in jump mode, handle jump input
-            if self.jump_mode {
-                match code {
-                    KeyCode::Char(c) => {
-                        self.jump_input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.jump_input.pop();
-                    }
-                    KeyCode::Enter => {
-                        // Perform jump
-                        self.perform_jump();
-                    }
-                    KeyCode::Esc => {
-                        // Exit jump mode
-                        self.jump_mode = false;
-                        self.jump_input.clear();
-                    }
-                    _ => {}
+            Ok(pivots) => {
+                let mut lines = Vec::new();
+                for pivot in &pivots {
+                    lines.push(pivot.name.clone());
+                    lines.extend(pivot.describe());
+                    lines.push(String::new());
                 }
-                return;
+                lines
             }
+            Err(e) => vec![format!("{e:#}")],
+        };
+        self.pivot_scroll = 0;
+        self.show_pivots = true;
+    }
 
-            // Normal navigation and commands - using configured keybindings
-            // Check actions in order of priority
-            if self.key_matches(code, modifiers, "quit") {
-                self.should_quit = true;
-            } else if self.key_matches(code, modifiers, "help") {
-                self.show_help = true;
-            } else if self.key_matches(code, modifiers, "theme_toggle") {
-                self.current_theme = self.current_theme.next();
-            } else if self.key_matches(code, modifiers, "search") {
-                self.search_mode = true;
-                self.clear_search();
-            } else if self.key_matches(code, modifiers, "next_match") {
-                self.jump_to_next_match();
-            } else if self.key_matches(code, modifiers, "prev_match") {
-                self.jump_to_prev_match();
-            } else if self.key_matches(code, modifiers, "copy_cell") {
-                self.copy_current_cell();
-            } else if self.key_matches(code, modifiers, "copy_row") {
-                self.copy_current_row();
-            } else if self.key_matches(code, modifiers, "jump") {
-                self.enter_jump_mode();
-            } else if self.key_matches(code, modifiers, "show_cell_detail") {
-                self.show_cell_detail = true;
-                self.cell_detail_scroll = 0;
-            } else if self.key_matches(code, modifiers, "next_sheet") {
-                let _ = self.switch_to_next_sheet();
-            } else if self.key_matches(code, modifiers, "prev_sheet") || code == KeyCode::BackTab {
-                // BackTab is another way to detect Shift+Tab on some terminals
-                let _ = self.switch_to_prev_sheet();
-            } else if self.key_matches(code, modifiers, "up") {
-                self.move_up();
-            } else if self.key_matches(code, modifiers, "down") {
-                self.move_down();
-            } else if self.key_matches(code, modifiers, "left") {
-                self.move_left();
-            } else if self.key_matches(code, modifiers, "right") {
-                self.move_right();
-            } else if self.key_matches(code, modifiers, "jump_to_top") {
-                self.move_to_top();
-            } else if self.key_matches(code, modifiers, "jump_to_bottom") {
-                self.move_to_bottom();
-            } else if self.key_matches(code, modifiers, "jump_to_row_start") {
-                self.move_to_start_of_row();
-            } else if self.key_matches(code, modifiers, "jump_to_row_end") {
-                self.move_to_end_of_row();
-            } else if self.key_matches(code, modifiers, "page_up") {
-                self.page_up(10);
-            } else if self.key_matches(code, modifiers, "page_down") {
-                self.page_down(10);
-            } else if code == KeyCode::Esc {
-                // Special handling for Esc - clear search if active, otherwise quit
-                if !self.search_matches.is_empty() {
-                    self.clear_search();
-                } else {
-                    self.should_quit = true;
-                }
-            }
+    /// Open the pivot dialog on the sheet's columns, as last set when a pivot is shown
+    fn open_pivot_dialog(&mut self) {
+        let (columns, roles, selected) = match &self.pivot {
+            Some(pivot) => (
+                pivot.sheet.sheet_data.headers().to_vec(),
+                pivot.roles.clone(),
+                0,
+            ),
+            None => (
+                self.sheet_data.headers().to_vec(),
+                vec![PivotRole::Skip; self.sheet_data.width()],
+                self.cursor_col,
+            ),
+        };
+        if columns.is_empty() {
+            self.copy_feedback = Some(("No columns to pivot".to_string(), Instant::now()));
+            return;
         }
+        let selected = selected.min(columns.len() - 1);
+        self.pivot_dialog = Some(PivotDialog {
+            columns,
+            roles,
+            selected,
+        });
     }
 
-    fn render(&mut self, frame: &mut Frame) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Min(3),    // Main content
-                Constraint::Length(3), // Status bar
-            ])
-            .split(frame.area());
+    /// Show the sheet grouped and aggregated as `roles` say, in place of the sheet (or the
+    /// rows the filter keeps); nothing picked goes back to the sheet
+    fn show_pivot(&mut self, roles: Vec<PivotRole>) {
+        self.close_pivot();
+        let keys: Vec<usize> = (0..roles.len())
+            .filter(|&i| roles[i] == PivotRole::Group)
+            .collect();
+        let measures: Vec<(usize, Aggregation)> = roles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, role)| match role {
+                PivotRole::Value(aggregation) => Some((i, *aggregation)),
+                _ => None,
+            })
+            .collect();
+        if keys.is_empty() && measures.is_empty() {
+            return;
+        }
 
-        // Calculate visible viewport
-        let table_height = chunks[0].height.saturating_sub(3) as usize; // Account for borders and header
-        let viewport_width = chunks[0].width.saturating_sub(2) as usize; // Account for borders
+        let rows = self.filtered_values();
+        let headers = self.sheet_data.headers();
+        let (headers, rows) = aggregate::pivot_columns(headers, &rows, &keys, &measures);
+        let mut description = if keys.is_empty() {
+            "totals".to_string()
+        } else {
+            let names: Vec<&str> = headers[..keys.len()].iter().map(String::as_str).collect();
+            format!("by {}", names.join(", "))
+        };
+        if let Some(filter) = &self.row_filter {
+            description.push_str(&format!(" where {}", filter.description));
+        }
+        let groups = rows.len();
+
+        let mut view = PaneView::empty(self.current_sheet_index);
+        view.sheet_data = SheetDataSource::Eager(SheetData {
+            formulas: vec![vec![None; headers.len()]; rows.len()],
+            width: headers.len(),
+            height: rows.len(),
+            headers,
+            rows,
+            first_row: 0,
+            first_col: 0,
+        });
+        self.swap_view(&mut view);
+        self.pivot = Some(PivotView {
+            description,
+            roles,
+            sheet: Box::new(view),
+        });
+        self.cell_stats = self.sheet_data.cell_stats();
+        self.column_widths = self.calculate_column_widths();
+        self.copy_feedback = Some((
+            format!(
+                "{groups} group{} (Esc goes back to the sheet)",
+                if groups == 1 { "" } else { "s" }
+            ),
+            Instant::now(),
+        ));
+    }
 
-        // Update scroll to keep cursor visible
-        self.update_scroll(table_height);
-        self.update_horizontal_scroll(viewport_width);
+    /// Every row's values, or only the rows the filter keeps
+    fn filtered_values(&self) -> Vec<Vec<CellValue>> {
+        let mut rows = self.sheet_data.all_values();
+        if let Some(filter) = &self.row_filter {
+            rows = filter
+                .rows
+                .iter()
+                .filter_map(|&i| rows.get_mut(i).map(std::mem::take))
+                .collect();
+        }
+        rows
+    }
 
-        let visible_start = self.scroll_offset;
+    /// Chart a column against the first column, or against row numbers for the first
+    /// column itself; None when it has no numbers
+    fn chart_series(&self, col: usize) -> Option<Series> {
+        let x = if col == 0 { None } else { Some(0) };
+        let series = Series::new(
+            self.sheet_data.headers(),
+            &self.filtered_values(),
+            x,
+            col,
+            &self.locale,
+        );
+        (!series.points.is_empty()).then_some(series)
+    }
 
-        // Calculate visible column range
-        let (visible_col_start, visible_col_end) = if self.horizontal_scroll_enabled {
-            // Calculate which columns fit in viewport
-            let mut total_width = 0;
-            let mut end = self.horizontal_scroll_offset;
-
-            for i in self.horizontal_scroll_offset..self.column_widths.len() {
-                total_width += self.column_widths[i] + 1; // +1 for separator
-                end = i + 1; // Always include current column
-                if total_width > viewport_width {
-                    break; // Break after including partially-visible column
-                }
+    /// Open the chart popup on the current column (the rows the filter keeps, if any)
+    fn open_chart(&mut self) {
+        let column = self.cursor_col;
+        match self.chart_series(column) {
+            Some(series) => {
+                self.chart = Some(ChartPopup {
+                    kind: ChartKind::Bar,
+                    column,
+                    series,
+                    scroll: 0,
+                })
+            }
+            None => {
+                self.copy_feedback = Some((
+                    "No numbers in this column to chart".to_string(),
+                    Instant::now(),
+                ))
             }
-            (self.horizontal_scroll_offset, end)
+        }
+    }
+
+    /// Chart the next column with numbers to the right (or left, `forward` false)
+    fn chart_next_column(&mut self, forward: bool) {
+        let Some(current) = self.chart.as_ref().map(|chart| chart.column) else {
+            return;
+        };
+        let width = self.sheet_data.width();
+        let next = if forward {
+            (current + 1..width).find_map(|col| Some((col, self.chart_series(col)?)))
         } else {
-            (0, self.sheet_data.width())
+            (0..current)
+                .rev()
+                .find_map(|col| Some((col, self.chart_series(col)?)))
         };
+        if let (Some((column, series)), Some(chart)) = (next, &mut self.chart) {
+            chart.column = column;
+            chart.series = series;
+            chart.scroll = 0;
+        }
+    }
 
-        // Clone headers to avoid borrow issues
-        let headers = self.sheet_data.headers().to_vec();
+    /// Draw the current column's numbers as bars, or stop drawing them; turning them on
+    /// also shows the whole column as a sparkline
+    fn toggle_bars(&mut self) {
+        let col = self.cursor_col;
+        if self.bar_columns.remove(&col).is_some() {
+            if let Some(width) = self.column_widths.get_mut(col) {
+                *width = width.saturating_sub(display::BAR_WIDTH + 1);
+            }
+            return;
+        }
+        let message = match self.show_bars(col) {
+            Some(sparkline) => sparkline,
+            None => "No numbers in this column to draw".to_string(),
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
 
-        // Get theme colors
-        let colors = self.current_theme.colors();
+    /// Start drawing bars in a column, widened to fit them; the column's header and
+    /// sparkline, or None when it has no numbers
+    fn show_bars(&mut self, col: usize) -> Option<String> {
+        let rows = self.sheet_data.all_values();
+        let cells = || rows.iter().filter_map(|row| row.get(col));
+        let scale = display::bar_scale(cells())?;
+        self.bar_columns.insert(col, scale);
+        if let Some(width) = self.column_widths.get_mut(col) {
+            *width += display::BAR_WIDTH + 1;
+        }
+        let header = self
+            .sheet_data
+            .headers()
+            .get(col)
+            .cloned()
+            .unwrap_or_default();
+        Some(format!("{header} {}", display::sparkline(cells(), 60)))
+    }
 
-        // Build table rows with highlighting
-        let header_cells: Vec<Cell> = headers
-            .iter()
-            .enumerate()
-            .skip(visible_col_start)
-            .take(visible_col_end - visible_col_start)
-            .map(|(col_idx, h)| {
-                let mut style = Style::default()
-                    .fg(colors.header_fg)
-                    .add_modifier(Modifier::BOLD);
+    /// Color the current column's numbers on the theme's heatmap gradient, or stop
+    fn toggle_heatmap(&mut self) {
+        let col = self.cursor_col;
+        if self.heat_columns.remove(&col).is_none() && !self.show_heatmap(col) {
+            self.copy_feedback = Some((
+                "No numbers in this column to color".to_string(),
+                Instant::now(),
+            ));
+        }
+    }
 
-                if let Some(bg) = colors.header_bg {
-                    style = style.bg(bg);
-                }
+    /// Start coloring a column by its numbers' range; false when it has none
+    fn show_heatmap(&mut self, col: usize) -> bool {
+        let rows = self.sheet_data.all_values();
+        let Some(range) = display::heat_range(rows.iter().filter_map(|row| row.get(col))) else {
+            return false;
+        };
+        self.heat_columns.insert(col, range);
+        true
+    }
 
-                // Highlight current column header
-                if col_idx == self.cursor_col {
-                    style = style.fg(colors.current_col_fg);
-                }
+    /// Go back from a pivot to its sheet, as it was left
+    fn close_pivot(&mut self) {
+        if let Some(mut pivot) = self.pivot.take() {
+            self.swap_view(&mut pivot.sheet);
+        }
+    }
 
-                Cell::from(h.as_str()).style(style)
-            })
-            .collect();
+    /// Move to the next error cell after the cursor, wrapping to the first
+    fn jump_to_next_error(&mut self) {
+        let errors = &self.cell_stats.errors;
+        if errors.is_empty() {
+            self.copy_feedback = Some(("No error cells in this sheet".to_string(), Instant::now()));
+            return;
+        }
 
-        let header = Row::new(header_cells).height(1);
+        let cursor = (self.cursor_row, self.cursor_col);
+        let idx = errors.iter().position(|&pos| pos > cursor).unwrap_or(0);
+        let (row, col) = errors[idx];
+        self.cursor_row = row;
+        self.cursor_col = col;
+        self.copy_feedback = Some((
+            format!("Error {}/{}", idx + 1, errors.len()),
+            Instant::now(),
+        ));
+    }
 
-        // Get visible rows from data source (handles lazy loading if needed)
-        let (visible_rows, _visible_formulas) =
-            self.sheet_data.get_rows(visible_start, table_height);
+    /// Read the current sheet's protection once per sheet; None for non-xlsx files
+    fn load_sheet_protection(&mut self) {
+        if matches!(self.protection, Some((index, _)) if index == self.current_sheet_index) {
+            return;
+        }
+        let sheet_name = &self.sheet_names[self.current_sheet_index];
+        let protection = self.workbook.sheet_protection(sheet_name).ok();
+        self.protection = Some((self.current_sheet_index, protection));
+    }
 
-        let data_rows: Vec<Row> = visible_rows
-            .iter()
-            .enumerate()
-            .map(|(visible_idx, row)| {
-                let row_idx = visible_start + visible_idx; // Absolute row index
-                let cells: Vec<Cell> = row
+    /// Read the current sheet's hyperlinks, conditional formatting, cell colors (when
+    /// shown), alignment, and hidden rows and columns; none for non-xlsx files
+    fn load_sheet_annotations(&mut self) {
+        let sheet_name = &self.sheet_names[self.current_sheet_index];
+        self.hyperlinks = self
+            .workbook
+            .hyperlinks(sheet_name)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|link| ((link.row, link.col), link))
+            .collect();
+        self.hidden_cells = self.workbook.hidden_cells(sheet_name).unwrap_or_default();
+        self.cell_alignments = self
+            .workbook
+            .cell_alignments(sheet_name)
+            .unwrap_or_default();
+        self.currency_cells = if self.locale.currency_symbol.is_empty() {
+            HashSet::new()
+        } else {
+            self.workbook.currency_cells(sheet_name).unwrap_or_default()
+        };
+        if self.config.ui.excel_colors {
+            self.cell_styles = self.workbook.cell_styles(sheet_name).unwrap_or_default();
+        }
+        let formats = self
+            .workbook
+            .conditional_formats(sheet_name)
+            .unwrap_or_default();
+        self.conditional = if formats.is_empty() {
+            HashMap::new()
+        } else {
+            let origin = (self.sheet_origin.0 as u32, self.sheet_origin.1 as u32);
+            conditional::evaluate(&formats, origin, &self.sheet_data.all_values())
+        };
+        // The sheet's frozen panes count from A1; the header row is always shown anyway
+        let panes = self
+            .workbook
+            .frozen_panes(sheet_name)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let (origin_row, origin_col) = self.sheet_origin;
+        self.frozen_rows = (panes.rows as usize).saturating_sub(origin_row);
+        self.frozen_cols = (panes.cols as usize).saturating_sub(origin_col);
+        self.sheet_changes = self
+            .diff
+            .as_ref()
+            .and_then(|diff| diff.sheet(sheet_name))
+            .map(|sheet| {
+                sheet
+                    .changes
                     .iter()
-                    .enumerate()
-                    .skip(visible_col_start)
-                    .take(visible_col_end - visible_col_start)
-                    .map(|(col_idx, cell)| {
-                        // Start with cell type color
-                        let mut style = Style::default().fg(colors.cell_color(cell));
+                    .map(|c| ((c.row as u32, c.col as u32), c.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
 
-                        // Add alternating row background (only if not the current row)
-                        let is_alternating_row = row_idx % 2 == 1;
-                        if is_alternating_row && let Some(alt_bg) = colors.alternating_row_bg {
-                            style = style.bg(alt_bg);
-                        }
+    /// Show changes against an older workbook; sheets it alone has are listed as a warning
+    fn set_diff(&mut self, diff: DiffView) {
+        let removed: Vec<&str> = diff
+            .sheets
+            .iter()
+            .filter(|sheet| sheet.status == SheetStatus::Removed)
+            .map(|sheet| sheet.name.as_str())
+            .collect();
+        if !removed.is_empty() {
+            self.warnings.push(format!(
+                "Sheets only in {}: {}",
+                diff.old_name,
+                removed.join(", ")
+            ));
+        }
+        self.diff = Some(diff);
+        self.load_sheet_annotations();
+    }
 
-                        // Check if this cell is a search match
-                        let is_search_match = self.search_matches.contains(&(row_idx, col_idx));
-                        let is_current_match = self
-                            .current_match_index
-                            .and_then(|idx| self.search_matches.get(idx))
-                            .map(|&pos| pos == (row_idx, col_idx))
-                            .unwrap_or(false);
+    /// Title suffix summarizing the current sheet's changes in diff mode
+    fn diff_annotation(&self) -> String {
+        let Some(sheet) = self
+            .diff
+            .as_ref()
+            .and_then(|diff| diff.sheet(self.current_sheet_name()))
+        else {
+            return String::new();
+        };
+        if sheet.status == SheetStatus::Added {
+            return "[new sheet] ".to_string();
+        }
+        if sheet.changes.is_empty() {
+            return "[no changes] ".to_string();
+        }
+        let counts: Vec<String> = [ChangeKind::Changed, ChangeKind::Added, ChangeKind::Removed]
+            .into_iter()
+            .map(|kind| format!("{} {}", sheet.count(kind), kind.label()))
+            .collect();
+        format!("[{}] ", counts.join(", "))
+    }
 
-                        // Highlight current search match (highest priority)
-                        if is_current_match {
-                            style = style
-                                .bg(colors.current_search_bg)
-                                .fg(colors.current_search_fg)
-                                .add_modifier(Modifier::BOLD);
-                        }
-                        // Highlight current cell
-                        else if row_idx == self.cursor_row && col_idx == self.cursor_col {
-                            style = style
-                                .bg(colors.current_cell_bg)
-                                .fg(colors.current_cell_fg)
-                                .add_modifier(Modifier::BOLD);
-                        }
-                        // Highlight other search matches
-                        else if is_search_match {
-                            style = style.bg(colors.search_match_bg).fg(colors.search_match_fg);
-                        }
-                        // Highlight current row
-                        else if row_idx == self.cursor_row {
-                            style = style.bg(colors.current_row_bg);
-                        }
-                        // Highlight current column
-                        else if col_idx == self.cursor_col {
-                            style = style.fg(colors.current_col_fg);
-                        }
-                        Cell::from(cell.to_string()).style(style)
-                    })
-                    .collect();
-                Row::new(cells).height(1)
+    /// Title suffix counting the current sheet's edited cells, and whether they're unsaved
+    fn edit_annotation(&self) -> String {
+        match self.edits.get(self.current_sheet_name()) {
+            Some(edits) if !edits.is_empty() => format!(
+                "[{} edited cell{}{}] ",
+                edits.len(),
+                if edits.len() == 1 { "" } else { "s" },
+                if self.unsaved_edits { ", unsaved" } else { "" }
+            ),
+            _ => String::new(),
+        }
+    }
+
+    fn pivot_annotation(&self) -> String {
+        match &self.pivot {
+            Some(pivot) => format!("[pivot {}] ", pivot.description),
+            None => String::new(),
+        }
+    }
+
+    /// Title suffix naming the active row filter
+    fn filter_annotation(&self) -> String {
+        match &self.row_filter {
+            Some(filter) => format!("[filter: {}] ", filter.description),
+            None => String::new(),
+        }
+    }
+
+    /// Changed cells inside the loaded data, as sorted (row, col) data positions
+    fn change_positions(&self) -> Vec<(usize, usize)> {
+        let (origin_row, origin_col) = self.sheet_origin;
+        let (height, width) = (self.sheet_data.height(), self.sheet_data.width());
+        let mut positions: Vec<(usize, usize)> = self
+            .sheet_changes
+            .keys()
+            .filter_map(|&(row, col)| {
+                let row = (row as usize).checked_sub(origin_row)?;
+                let col = (col as usize).checked_sub(origin_col)?;
+                (row < height && col < width).then_some((row, col))
             })
             .collect();
+        positions.sort_unstable();
+        positions
+    }
 
-        // Calculate column widths
-        let col_widths: Vec<Constraint> = if self.horizontal_scroll_enabled {
-            // Use fixed widths based on content
-            self.column_widths[visible_col_start..visible_col_end]
-                .iter()
-                .map(|&w| Constraint::Length(w as u16))
-                .collect()
+    /// Move to the next (or previous) changed cell in the sheet, wrapping around
+    fn jump_to_change(&mut self, forward: bool) {
+        let positions = self.change_positions();
+        if positions.is_empty() {
+            self.copy_feedback =
+                Some(("No changed cells in this sheet".to_string(), Instant::now()));
+            return;
+        }
+        let cursor = (self.cursor_row, self.cursor_col);
+        let idx = if forward {
+            positions.iter().position(|&pos| pos > cursor).unwrap_or(0)
         } else {
-            // Use percentage-based widths (current behavior)
-            let sheet_width = self.sheet_data.width();
-            headers
+            positions
                 .iter()
-                .map(|_| Constraint::Percentage((100 / sheet_width.max(1)) as u16))
-                .collect()
+                .rposition(|&pos| pos < cursor)
+                .unwrap_or(positions.len() - 1)
         };
+        (self.cursor_row, self.cursor_col) = positions[idx];
+        self.copy_feedback = Some((
+            format!("Change {}/{}", idx + 1, positions.len()),
+            Instant::now(),
+        ));
+    }
 
-        let table_title = if self.sheet_names.len() > 1 {
-            format!(
-                " {} (Sheet {}/{}) ",
-                self.current_sheet_name(),
-                self.current_sheet_index + 1,
-                self.sheet_names.len()
-            )
-        } else {
-            format!(" {} ", self.current_sheet_name())
-        };
+    /// The cursor cell's change in diff mode
+    fn change_at_cursor(&self) -> Option<&CellChange> {
+        let row = (self.sheet_origin.0 + self.cursor_row) as u32;
+        let col = (self.sheet_origin.1 + self.cursor_col) as u32;
+        self.sheet_changes.get(&(row, col))
+    }
 
-        let table = Table::new(data_rows, col_widths).header(header).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(colors.border_fg))
-                .title(table_title),
-        );
+    /// Title suffix marking a hidden sheet and counting hidden rows and columns in the data
+    fn hidden_annotation(&self) -> String {
+        let mut notes = Vec::new();
+        match self.sheet_visibility.get(self.current_sheet_index) {
+            Some(&"hidden") => notes.push("hidden sheet".to_string()),
+            Some(&"very hidden") => notes.push("very hidden sheet".to_string()),
+            _ => {}
+        }
+        let (origin_row, origin_col) = self.sheet_origin;
+        let (height, width) = (self.sheet_data.height(), self.sheet_data.width());
+        let count = |set: &BTreeSet<u32>, start: usize, len: usize| {
+            set.range(start as u32..(start + len) as u32).count()
+        };
+        let rows = count(&self.hidden_cells.rows, origin_row, height);
+        let cols = count(&self.hidden_cells.cols, origin_col, width);
+        if rows > 0 {
+            notes.push(format!(
+                "{rows} hidden row{}",
+                if rows == 1 { "" } else { "s" }
+            ));
+        }
+        if cols > 0 {
+            notes.push(format!(
+                "{cols} hidden column{}",
+                if cols == 1 { "" } else { "s" }
+            ));
+        }
+        let user_hidden = self
+            .column_views
+            .get(&self.current_sheet_index)
+            .map_or(0, |view| view.hidden.len());
+        if user_hidden > 0 {
+            notes.push(format!(
+                "hiding {user_hidden} column{}",
+                if user_hidden == 1 { "" } else { "s" }
+            ));
+        }
+        if notes.is_empty() {
+            String::new()
+        } else {
+            format!("[{}] ", notes.join(", "))
+        }
+    }
 
-        frame.render_widget(table, chunks[0]);
+    /// Hyperlink on a data cell (0-indexed data row and column)
+    fn hyperlink_at(&self, row: usize, col: usize) -> Option<&Hyperlink> {
+        if self.hyperlinks.is_empty() {
+            return None;
+        }
+        // Data rows start one below the header row
+        let row = (self.sheet_origin.0 + row) as u32;
+        let col = (self.sheet_origin.1 + col) as u32;
+        self.hyperlinks.get(&(row, col))
+    }
 
-        // Status bar with current cell info
-        let (cell, _) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
-        let current_cell_value = cell.map(|v| v.to_string()).unwrap_or_default();
+    /// With --evaluate, compute the cursor cell's formula for the detail popup
+    fn evaluate_current_cell(&mut self) {
+        self.cell_detail_evaluated = None;
+        if !self.workbook.evaluates_formulas() {
+            return;
+        }
+        let (_, formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
+        if formula.is_none() {
+            return;
+        }
+        let row = (self.sheet_origin.0 + self.cursor_row) as u32;
+        let col = (self.sheet_origin.1 + self.cursor_col) as u32;
+        let sheet_name = self.sheet_names[self.current_sheet_index].clone();
+        self.cell_detail_evaluated = self.workbook.evaluate_cell(&sheet_name, row, col);
+    }
 
-        // Format sheet dimensions with scroll indicator
-        let sheet_dims = if self.horizontal_scroll_enabled && self.horizontal_scroll_offset > 0 {
-            let first_col = headers
-                .get(visible_col_start)
-                .map(|s| s.as_str())
-                .unwrap_or("?");
-            let last_col = headers
-                .get(visible_col_end.saturating_sub(1))
-                .map(|s| s.as_str())
-                .unwrap_or("?");
-            format!(
-                "{} rows × {} columns (showing {}-{})",
-                self.sheet_data.height(),
-                self.sheet_data.width(),
-                first_col,
-                last_col
-            )
-        } else {
-            format!(
-                "{} rows × {} columns",
-                self.sheet_data.height(),
-                self.sheet_data.width()
-            )
+    /// Open the cursor cell's link: external targets with the system handler,
+    /// "#Sheet!A1" locations by jumping to that cell
+    fn open_current_link(&mut self) {
+        let Some(target) = self
+            .hyperlink_at(self.cursor_row, self.cursor_col)
+            .map(|link| link.target.clone())
+        else {
+            self.copy_feedback = Some(("No link in this cell".to_string(), Instant::now()));
+            return;
         };
+        let message = match target.strip_prefix('#') {
+            Some(location) => self.follow_internal_link(location).unwrap_or_else(|e| e),
+            None => match open_with_system(&target) {
+                Ok(()) => format!("Opened {target}"),
+                Err(e) => format!("Couldn't open link: {e}"),
+            },
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
 
-        let status_text = if let Some(ref progress) = self.progress {
-            // Show progress indicator
-            format!(" ⏳ {} ", progress.format())
-        } else if self.jump_mode {
-            format!(
-                " Jump to (row, cell like A5, or row,col): {} ",
-                self.jump_input
-            )
-        } else if self.search_mode {
-            format!(" Search: {} ", self.search_query)
-        } else if let Some(idx) = self.current_match_index {
-            // Show search results
-            let match_info = format!("Match {}/{} | ", idx + 1, self.search_matches.len());
-            if self.sheet_names.len() > 1 {
-                format!(
-                    " {} | {}n:next N:prev Esc:clear | {} | Tab:next sheet ?:help q:quit ",
-                    match_info,
-                    self.current_cell_address(),
-                    sheet_dims
-                )
-            } else {
-                format!(
-                    " {} | {}n:next N:prev Esc:clear | {} | ?:help q:quit ",
-                    match_info,
-                    self.current_cell_address(),
-                    sheet_dims
-                )
+    /// Jump to a "Sheet!A1", "'My Sheet'!A1:B5", or bare "A1" location; the error says
+    /// why the cursor couldn't go there
+    fn follow_internal_link(&mut self, location: &str) -> Result<String, String> {
+        let (sheet, cell) = match location.rsplit_once('!') {
+            Some((sheet, cell)) => (sheet.trim_matches('\'').replace("''", "'"), cell),
+            None => (self.current_sheet_name().to_string(), location),
+        };
+        // Excel matches sheet names case-insensitively
+        let index = self
+            .sheet_names
+            .iter()
+            .position(|name| *name == sheet)
+            .or_else(|| {
+                let sheet = sheet.to_lowercase();
+                self.sheet_names
+                    .iter()
+                    .position(|name| name.to_lowercase() == sheet)
+            });
+        let Some(index) = index else {
+            return Err(format!("Sheet '{sheet}' not found"));
+        };
+        let from = self.cursor_mark();
+        if index != self.current_sheet_index {
+            self.current_sheet_index = index;
+            if let Err(e) = self.load_current_sheet() {
+                return Err(format!("Failed to load sheet '{sheet}': {e}"));
             }
-        } else {
-            // Show loading mode indicator for large files
-            let mode_indicator = match &self.sheet_data {
-                SheetDataSource::Lazy { .. } => " [Lazy] ",
-                SheetDataSource::Eager(_) => "",
-            };
+            self.reset_cursor();
+            self.refresh_search_for_sheet();
+        }
+        // Ranges jump to their top-left cell; defined names just switch sheets
+        if let Some((row, col)) = parse_cell_ref(cell.split(':').next().unwrap_or(cell)) {
+            let row = (row as usize).saturating_sub(self.sheet_origin.0);
+            let col = (col as usize).saturating_sub(self.sheet_origin.1);
+            self.cursor_row = row.min(self.sheet_data.height().saturating_sub(1));
+            self.cursor_col = col.min(self.sheet_data.width().saturating_sub(1));
+        }
+        self.record_jump(from);
+        Ok(format!("Followed link to {location}"))
+    }
 
-            if self.sheet_names.len() > 1 {
-                format!(
-                    " {} | {}{} | Theme: {} | t:theme /:search Tab:sheet ?:help q:quit ",
-                    self.current_cell_address(),
-                    sheet_dims,
-                    mode_indicator,
-                    self.current_theme.name()
-                )
-            } else {
-                format!(
-                    " {} | {}{} | Theme: {} | t:theme /:search ?:help q:quit ",
-                    self.current_cell_address(),
-                    sheet_dims,
-                    mode_indicator,
-                    self.current_theme.name()
-                )
-            }
+    /// Select from the cursor to the bottom-right cell of a "Sheet!A1:B5" range, so a
+    /// jump to a named range shows all of it; single cells select nothing
+    fn select_range_end(&mut self, location: &str) {
+        let cells = location.rsplit('!').next().unwrap_or(location);
+        let Some(end) = cells
+            .split_once(':')
+            .and_then(|(_, end)| parse_cell_ref(end))
+        else {
+            return;
         };
+        let row = (end.0 as usize).saturating_sub(self.sheet_origin.0);
+        let col = (end.1 as usize).saturating_sub(self.sheet_origin.1);
+        let end = (
+            row.min(self.sheet_data.height().saturating_sub(1)),
+            col.min(self.sheet_data.width().saturating_sub(1)),
+        );
+        if end != (self.cursor_row, self.cursor_col) {
+            self.visual_anchor = Some(end);
+        }
+    }
 
-        let mut status_style = Style::default().fg(colors.status_bar_fg);
-        if let Some(bg) = colors.status_bar_bg {
-            status_style = status_style.bg(bg);
+    /// Lock state of the cursor cell for the detail popup, e.g. "Unlocked (editable)"
+    fn cell_protection_status(&self) -> Option<String> {
+        let Some((index, Some(protection))) = &self.protection else {
+            return None;
+        };
+        if *index != self.current_sheet_index {
+            return None;
+        }
+        // Data rows start one below the header row
+        let row = (self.sheet_origin.0 + self.cursor_row) as u32;
+        let col = (self.sheet_origin.1 + self.cursor_col) as u32;
+
+        let mut status = match (protection.is_locked(row, col), protection.protected) {
+            (true, true) => "Locked (sheet protected)".to_string(),
+            (true, false) => "Locked (sheet not protected)".to_string(),
+            (false, true) => "Unlocked (editable)".to_string(),
+            (false, false) => "Unlocked (sheet not protected)".to_string(),
+        };
+        if protection.is_formula_hidden(row, col) {
+            status.push_str(", formula hidden");
         }
+        Some(status)
+    }
 
-        let status = Paragraph::new(status_text).style(status_style).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(colors.border_fg))
-                .title(format!(" {} ", current_cell_value)),
-        );
+    /// The status bar from the `status_bar` template in the config
+    fn custom_status(
+        &self,
+        dims: &str,
+        value: &str,
+        selection: Option<&((usize, usize), Vec<usize>)>,
+    ) -> String {
+        let field = |name: &str| -> Option<String> {
+            Some(match name {
+                "cell" => self.current_cell_address(),
+                "value" => value.to_string(),
+                "sheet" => self.current_sheet_name().to_string(),
+                "file" => self
+                    .workbook
+                    .path()
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                "rows" => match &self.row_filter {
+                    Some(filter) => filter.rows.len(),
+                    None => self.sheet_data.height(),
+                }
+                .to_string(),
+                "cols" => self.sheet_data.width().to_string(),
+                "dims" => dims.to_string(),
+                "theme" => self.theme().name.clone(),
+                "match" => match (self.search_workbook, self.workbook_match_index) {
+                    (true, Some(idx)) => {
+                        format!("Match {}/{}", idx + 1, self.workbook_matches.len())
+                    }
+                    _ => self
+                        .current_match_index
+                        .map(|idx| format!("Match {}/{}", idx + 1, self.search_matches.len()))
+                        .unwrap_or_default(),
+                },
+                "selection" => selection
+                    .map(|(rows, cols)| {
+                        format!(
+                            "{} ({} × {})",
+                            self.selection_label(*rows, cols),
+                            rows.1 - rows.0 + 1,
+                            cols.len()
+                        )
+                    })
+                    .unwrap_or_default(),
+                "stats" => self
+                    .cell_stats_indicator()
+                    .trim_start_matches(" | ")
+                    .to_string(),
+                "keys" => {
+                    let keys: String = self
+                        .typed_keys
+                        .iter()
+                        .map(|&(code, modifiers)| crate::config::key_label(code, modifiers))
+                        .collect();
+                    let count = self.count.map(|count| count.to_string());
+                    format!("{}{keys}", count.unwrap_or_default())
+                }
+                "link" => self
+                    .hyperlink_at(self.cursor_row, self.cursor_col)
+                    .map(|link| link.target.clone())
+                    .unwrap_or_default(),
+                "mode" => match &self.sheet_data {
+                    SheetDataSource::Lazy { data, .. } if data.is_sparse() => "sparse".to_string(),
+                    SheetDataSource::Lazy { .. } => "lazy".to_string(),
+                    SheetDataSource::Eager(_) => String::new(),
+                },
+                _ => return None,
+            })
+        };
+        fill_status_template(&self.config.ui.status_bar, field)
+    }
 
-        frame.render_widget(status, chunks[1]);
+    /// Compact error/empty counts for the status bar (e.g. " | ⚠ 12 errors, 340 empty")
+    fn cell_stats_indicator(&self) -> String {
+        let mut parts = Vec::new();
+        match self.cell_stats.errors.len() {
+            0 => {}
+            1 => parts.push("⚠ 1 error".to_string()),
+            n => parts.push(format!("⚠ {n} errors")),
+        }
+        if self.cell_stats.empty > 0 {
+            parts.push(format!("{} empty", self.cell_stats.empty));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" | {}", parts.join(", "))
+        }
+    }
 
-        // Render cell detail overlay if visible
-        if self.show_cell_detail {
-            self.render_cell_detail(frame);
+    /// Enter jump mode
+    fn enter_jump_mode(&mut self) {
+        self.jump_mode = true;
+        self.jump_input.clear();
+    }
+
+    /// Start a workbook-wide search, typing the query
+    fn start_workbook_search(&mut self) {
+        self.search_mode = true;
+        self.clear_search();
+        self.search_workbook = true;
+    }
+
+    /// Run the typed `:` command
+    fn run_command(&mut self) {
+        self.command_mode = false;
+        let input = std::mem::take(&mut self.command_input);
+        let (command, argument) = input
+            .trim()
+            .split_once(char::is_whitespace)
+            .map(|(command, argument)| (command, argument.trim()))
+            .unwrap_or((input.trim(), ""));
+        match command {
+            "" => {}
+            "grep" | "g" if !argument.is_empty() => {
+                self.clear_search();
+                self.search_workbook = true;
+                self.search_query = argument.to_string();
+                self.perform_workbook_search();
+            }
+            "grep" | "g" => self.start_workbook_search(),
+            "w" | "write" => self.save_edits(argument),
+            "marks" => self.show_marks = true,
+            "split" | "sp" => self.open_split(Direction::Vertical, argument),
+            "vsplit" | "vs" => self.open_split(Direction::Horizontal, argument),
+            "only" | "on" => self.split = None,
+            "close" | "clo" => self.close_pane(),
+            "delmarks!" | "delm!" => self.delete_marks("!"),
+            "delmarks" | "delm" if !argument.is_empty() => self.delete_marks(argument),
+            "set" | "se" => self.set_option(argument),
+            _ => {
+                self.copy_feedback = Some((
+                    format!("Unknown command :{command} (try :grep TEXT or :w FILE.xlsx)"),
+                    Instant::now(),
+                ));
+            }
         }
+    }
 
-        // Render help overlay if visible
-        if self.show_help {
-            self.render_help(frame);
+    /// `:set OPTION` turns a display option on, `:set noOPTION` off, and `:set OPTION!`
+    /// flips it
+    fn set_option(&mut self, argument: &str) {
+        if let Some((name, value)) = argument.split_once('=') {
+            let option = match name.trim() {
+                "page_size" => &mut self.page_size,
+                "scroll_margin" => &mut self.scroll_margin,
+                _ => {
+                    self.copy_feedback = Some((
+                        format!("Unknown option {name:?} (page_size, scroll_margin)"),
+                        Instant::now(),
+                    ));
+                    return;
+                }
+            };
+            let message = match value.trim().parse() {
+                Ok(rows) => {
+                    *option = rows;
+                    format!("{} = {rows}", name.trim())
+                }
+                Err(_) => format!("{name} needs a number of rows, not {value:?}"),
+            };
+            self.copy_feedback = Some((message, Instant::now()));
+            return;
         }
+        let (name, value) = if let Some(name) = argument.strip_suffix('!') {
+            (name, None)
+        } else if let Some(name) = argument.strip_prefix("no") {
+            (name, Some(false))
+        } else {
+            (argument, Some(true))
+        };
+        let option = match name {
+            "striped_rows" => &mut self.striped_rows,
+            "row_lines" => &mut self.row_lines,
+            "column_lines" => &mut self.column_lines,
+            "gutter" => &mut self.gutter,
+            _ => {
+                self.copy_feedback = Some((
+                    format!(
+                        "Unknown option {argument:?} (striped_rows, row_lines, column_lines, gutter)"
+                    ),
+                    Instant::now(),
+                ));
+                return;
+            }
+        };
+        *option = value.unwrap_or(!*option);
+        let state = if *option { "on" } else { "off" };
+        self.copy_feedback = Some((format!("{name} {state}"), Instant::now()));
+    }
 
-        // Render copy feedback if active (and not expired)
-        if let Some((ref message, timestamp)) = self.copy_feedback {
-            // Show feedback for 2 seconds
-            if timestamp.elapsed() < Duration::from_secs(2) {
-                self.render_copy_feedback(frame, message);
-            } else {
-                // Clear expired feedback
-                self.copy_feedback = None;
+    /// Map the frame's colors to the ones the terminal has (`--color-mode`). Without
+    /// colors, cells with a background are reversed instead, so the cursor and highlights
+    /// still show; the row stripes and current-row shading are just dropped.
+    fn fit_colors(&self, buf: &mut ratatui::buffer::Buffer) {
+        let mode = color_mode::color_mode();
+        if mode == ColorMode::Truecolor {
+            return;
+        }
+        let colors = &self.theme().colors;
+        let shading = [colors.alternating_row_bg, Some(colors.current_row_bg)];
+        for cell in &mut buf.content {
+            if mode == ColorMode::None
+                && cell.bg != Color::Reset
+                && !shading.contains(&Some(cell.bg))
+            {
+                cell.modifier.insert(Modifier::REVERSED);
             }
+            cell.fg = fit_color(cell.fg, mode);
+            cell.bg = fit_color(cell.bg, mode);
         }
     }
 
-    fn render_help(&self, frame: &mut Frame) {
-        use ratatui::text::{Line, Span};
+    /// Parse jump input and navigate to that location
+    /// Supports formats: "100" (row), "Sales" (defined name or table), "Summary!B12" or
+    /// "'My Sheet'!A1:C5" (cell on any sheet), "A5" (cell address), "5,3" (row,col)
+    fn perform_jump(&mut self) {
+        if self.jump_input.is_empty() {
+            self.jump_mode = false;
+            return;
+        }
 
-        // Build help content with rich formatting
-        let help_lines = vec![
-            Line::from(vec![
+        let from = self.cursor_mark();
+        let input = self.jump_input.trim().to_string();
+        let input = input.as_str();
+
+        // Excel names can't look like numbers or cell addresses, so they can go first
+        let named = match input.parse::<usize>() {
+            Ok(_) => None,
+            Err(_) => self
+                .workbook
+                .named_range(input)
+                .map(|location| (format!("{input} ({location})"), location)),
+        };
+        // References pasted from formulas, like Summary!$B$12
+        let target = named.or_else(|| {
+            let (sheet, cells) = input.rsplit_once('!')?;
+            let cells = cells.replace('$', "").to_uppercase();
+            parse_cell_ref(cells.split(':').next().unwrap_or_default())?;
+            let location = format!("{sheet}!{cells}");
+            Some((location.clone(), location))
+        });
+        if let Some((label, location)) = target {
+            self.jump_mode = false;
+            self.jump_input.clear();
+            let message = match self.follow_internal_link(&location) {
+                Ok(_) => {
+                    self.select_range_end(&location);
+                    format!("Jumped to {label}")
+                }
+                Err(e) => e,
+            };
+            self.copy_feedback = Some((message, Instant::now()));
+            return;
+        }
+
+        // Try to parse as row number (1-indexed)
+        if let Ok(row_num) = input.parse::<usize>() {
+            if row_num > 0 && row_num <= self.sheet_data.height() {
+                self.cursor_row = row_num - 1; // Convert to 0-indexed
+                self.copy_feedback = Some((format!("Jumped to row {}", row_num), Instant::now()));
+            } else {
+                self.copy_feedback = Some((
+                    format!(
+                        "Invalid row: {} (max: {})",
+                        row_num,
+                        self.sheet_data.height()
+                    ),
+                    Instant::now(),
+                ));
+            }
+        }
+        // Try to parse as cell address like "A5" or "B10", in the workbook's coordinates as
+        // for Sheet!A5
+        else if let Some((col, row)) = Self::parse_cell_address(input) {
+            let row = row.checked_sub(self.sheet_origin.0).unwrap_or(usize::MAX);
+            let col = col.checked_sub(self.sheet_origin.1).unwrap_or(usize::MAX);
+            if row < self.sheet_data.height() && col < self.sheet_data.width() {
+                self.cursor_row = row;
+                self.cursor_col = col;
+                self.copy_feedback = Some((
+                    format!("Jumped to {}", input.to_uppercase()),
+                    Instant::now(),
+                ));
+            } else {
+                self.copy_feedback = Some((
+                    format!("Cell address out of bounds: {}", input),
+                    Instant::now(),
+                ));
+            }
+        }
+        // Try to parse as "row,col" format
+        else if let Some((row, col)) = input.split_once(',') {
+            if let (Ok(row_num), Ok(col_num)) =
+                (row.trim().parse::<usize>(), col.trim().parse::<usize>())
+            {
+                if row_num > 0
+                    && row_num <= self.sheet_data.height()
+                    && col_num > 0
+                    && col_num <= self.sheet_data.width()
+                {
+                    self.cursor_row = row_num - 1;
+                    self.cursor_col = col_num - 1;
+                    self.copy_feedback = Some((
+                        format!("Jumped to row {}, col {}", row_num, col_num),
+                        Instant::now(),
+                    ));
+                } else {
+                    self.copy_feedback =
+                        Some(("Invalid row/column number".to_string(), Instant::now()));
+                }
+            } else {
+                self.copy_feedback = Some((
+                    "Invalid format. Use: row number, cell (A5 or Sheet!A5), row,col, or a defined name or table".to_string(),
+                    Instant::now(),
+                ));
+            }
+        } else {
+            self.copy_feedback = Some((
+                "Invalid format. Use: row number, cell (A5 or Sheet!A5), row,col, or a defined name or table"
+                    .to_string(),
+                Instant::now(),
+            ));
+        }
+
+        self.record_jump(from);
+        self.jump_mode = false;
+        self.jump_input.clear();
+    }
+
+    /// Parse cell address like "A5", "B10", "AA100" into (col, row) indices
+    fn parse_cell_address(addr: &str) -> Option<(usize, usize)> {
+        let addr = addr.to_uppercase();
+        let mut col = 0usize;
+        let mut row_str = String::new();
+
+        for ch in addr.chars() {
+            if ch.is_ascii_alphabetic() {
+                col = col * 26 + (ch as usize - 'A' as usize + 1);
+            } else if ch.is_ascii_digit() {
+                row_str.push(ch);
+            } else {
+                return None;
+            }
+        }
+
+        if row_str.is_empty() || col == 0 {
+            return None;
+        }
+
+        let row = row_str.parse::<usize>().ok()?;
+        Some((col - 1, row - 1)) // Convert to 0-indexed
+    }
+
+    /// Start typing a new value for the cursor cell, beginning from its current value
+    fn start_edit(&mut self) {
+        if self.pivot.is_some() {
+            self.copy_feedback = Some((
+                "A pivot can't be edited (Esc goes back to the sheet)".to_string(),
+                Instant::now(),
+            ));
+            return;
+        }
+        if matches!(self.sheet_data, SheetDataSource::Lazy { .. }) {
+            self.copy_feedback = Some((
+                "Editing isn't available for large (lazy-loaded) sheets".to_string(),
+                Instant::now(),
+            ));
+            return;
+        }
+        if self.cursor_row >= self.sheet_data.height() {
+            return;
+        }
+        let (cell, _formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
+        self.edit_input = cell.map(|v| v.to_raw_string()).unwrap_or_default();
+        self.edit_mode = true;
+    }
+
+    /// Set the cursor cell to the typed value (numbers and TRUE/FALSE are parsed)
+    fn commit_edit(&mut self) {
+        self.edit_mode = false;
+        let value = save::parse_value(&std::mem::take(&mut self.edit_input));
+        if !self
+            .sheet_data
+            .set_cell(self.cursor_row, self.cursor_col, value.clone())
+        {
+            return;
+        }
+        let pos = (
+            self.sheet_origin.0 + self.cursor_row,
+            self.sheet_origin.1 + self.cursor_col,
+        );
+        // A pane on the same sheet shows the edit too
+        if let Some(split) = &mut self.split
+            && split.pane.sheet_index == self.current_sheet_index
+        {
+            let pane = match &mut split.pane.pivot {
+                Some(pivot) => &mut *pivot.sheet,
+                None => &mut split.pane,
+            };
+            let (origin_row, origin_col) = pane.sheet_origin;
+            if let (Some(row), Some(col)) =
+                (pos.0.checked_sub(origin_row), pos.1.checked_sub(origin_col))
+            {
+                pane.sheet_data.set_cell(row, col, value.clone());
+            }
+        }
+        let sheet_name = self.current_sheet_name().to_string();
+        self.edits.entry(sheet_name).or_default().insert(pos, value);
+        self.unsaved_edits = true;
+        self.quit_warned = false;
+        self.cell_stats = self.sheet_data.cell_stats();
+        let hint = match &self.save_path {
+            Some(path) => format!(":w saves to {}", path.display()),
+            None => ":w FILE.xlsx to save".to_string(),
+        };
+        self.copy_feedback = Some((
+            format!("Edited {} ({hint})", self.current_cell_address()),
+            Instant::now(),
+        ));
+    }
+
+    /// `:w [FILE]`: write every sheet with the edits applied to a new .xlsx file
+    fn save_edits(&mut self, argument: &str) {
+        let path = if argument.is_empty() {
+            self.save_path.clone()
+        } else {
+            Some(PathBuf::from(argument))
+        };
+        let Some(path) = path else {
+            self.copy_feedback = Some(("Usage: :w FILE.xlsx".to_string(), Instant::now()));
+            return;
+        };
+        let feedback = match save::save_workbook(&mut self.workbook, &self.edits, &path) {
+            Ok(()) => {
+                self.unsaved_edits = false;
+                self.save_path = Some(path.clone());
+                format!("Saved {} (cell values only)", path.display())
+            }
+            Err(e) => format!("Save failed: {e:#}"),
+        };
+        self.copy_feedback = Some((feedback, Instant::now()));
+    }
+
+    /// Copy the current cell value to clipboard
+    fn copy_current_cell(&mut self) {
+        let (cell, _formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
+        let cell_value = cell.map(|v| v.to_raw_string()).unwrap_or_default();
+        let message = format!("Copied cell {}", self.current_cell_address());
+        self.copy_to_clipboard(&cell_value, message);
+    }
+
+    /// Put text on the clipboard, reporting `message` on success
+    fn copy_to_clipboard(&mut self, text: &str, message: String) {
+        let feedback = match Clipboard::new() {
+            Ok(mut clipboard) => match clipboard.set_text(text) {
+                Ok(()) => message,
+                Err(e) => format!("Copy failed: {}", e),
+            },
+            Err(e) => format!("Clipboard error: {}", e),
+        };
+        self.copy_feedback = Some((feedback, Instant::now()));
+    }
+
+    /// Copy the current row to clipboard (tab-separated)
+    fn copy_current_row(&mut self) {
+        let (rows, _formulas) = self.sheet_data.get_rows(self.cursor_row, 1);
+        let row_values = rows
+            .first()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| tsv_field(&cell.to_raw_string()))
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            })
+            .unwrap_or_default();
+
+        let message = format!(
+            "Copied row {} ({} cells)",
+            self.cursor_row + 1,
+            self.sheet_data.width()
+        );
+        self.copy_to_clipboard(&row_values, message);
+    }
+
+    /// Start a selection at the cursor, or leave visual mode
+    fn toggle_visual_mode(&mut self) {
+        self.visual_anchor = match self.visual_anchor {
+            Some(_) => None,
+            None => Some((self.cursor_row, self.cursor_col)),
+        };
+    }
+
+    /// The visual-mode selection: first and last display position, and the selected
+    /// columns in display order
+    fn selection(&self) -> Option<((usize, usize), Vec<usize>)> {
+        let (anchor_row, anchor_col) = self.visual_anchor?;
+        let anchor = self.display_position(anchor_row);
+        let cursor = self.display_position(self.cursor_row);
+        let rows = (anchor.min(cursor), anchor.max(cursor));
+
+        let order = self.column_order();
+        let position = |col: usize| order.iter().position(|&c| c == col);
+        let cursor = position(self.cursor_col)?;
+        // The anchor's column may have been hidden since; select from the cursor then
+        let anchor = position(anchor_col).unwrap_or(cursor);
+        let cols = order[anchor.min(cursor)..=anchor.max(cursor)].to_vec();
+        Some((rows, cols))
+    }
+
+    /// Top-left and bottom-right addresses of the selection, e.g. "B2:D5"
+    fn selection_label(&self, (first, last): (usize, usize), cols: &[usize]) -> String {
+        let row_at = |position: usize| match &self.row_filter {
+            Some(filter) => filter.rows.get(position).copied().unwrap_or(0),
+            None => position,
+        };
+        let (origin_row, origin_col) = self.sheet_origin;
+        format!(
+            "{}{}:{}{}",
+            xleak::workbook::column_letter(origin_col + cols[0]),
+            origin_row + row_at(first) + 1,
+            xleak::workbook::column_letter(origin_col + cols[cols.len() - 1]),
+            origin_row + row_at(last) + 1
+        )
+    }
+
+    /// The selected columns' headers and the selected cells, row by row
+    fn selection_cells(&mut self) -> Option<(Vec<String>, Vec<Vec<CellValue>>)> {
+        let ((first, last), cols) = self.selection()?;
+        let rows: Vec<usize> = match &self.row_filter {
+            Some(filter) => filter.rows[first..=last].to_vec(),
+            None => (first..=last).collect(),
+        };
+        let headers = self.sheet_data.headers();
+        let headers = cols
+            .iter()
+            .map(|&col| headers.get(col).cloned().unwrap_or_default())
+            .collect();
+        let cells = rows
+            .into_iter()
+            .map(|row| {
+                let (rows, _formulas) = self.sheet_data.get_rows(row, 1);
+                let cells = rows.first();
+                cols.iter()
+                    .map(|&col| {
+                        cells
+                            .and_then(|cells| cells.get(col))
+                            .cloned()
+                            .unwrap_or(CellValue::Empty)
+                    })
+                    .collect()
+            })
+            .collect();
+        Some((headers, cells))
+    }
+
+    /// Copy the selection as tab- or comma-separated cells (no header row) and leave
+    /// visual mode
+    fn copy_selection(&mut self, csv: bool) {
+        let Some(((first, last), cols)) = self.selection() else {
+            return;
+        };
+        let label = self.selection_label((first, last), &cols);
+        let Some((_headers, rows)) = self.selection_cells() else {
+            return;
+        };
+        let (field, separator): (fn(&str) -> String, &str) = if csv {
+            (xleak::display::csv_field, ",")
+        } else {
+            (tsv_field, "\t")
+        };
+        let text = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| field(&cell.to_raw_string()))
+                    .collect::<Vec<_>>()
+                    .join(separator)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let message = format!(
+            "Copied {label} as {} ({} × {} cells)",
+            if csv { "CSV" } else { "TSV" },
+            rows.len(),
+            cols.len()
+        );
+        self.copy_to_clipboard(&text, message);
+        self.visual_anchor = None;
+    }
+
+    /// Write the selection, with its column headers, to the typed file (format from the
+    /// extension: .csv, .json, .md, otherwise tab-separated text)
+    fn export_selection(&mut self) {
+        self.export_mode = false;
+        let path = std::mem::take(&mut self.export_input);
+        let path = path.trim();
+        if path.is_empty() {
+            return;
+        }
+        let Some((headers, rows)) = self.selection_cells() else {
+            return;
+        };
+        let message = match write_selection(
+            std::path::Path::new(path),
+            &headers,
+            &rows,
+            &self.export_options,
+        ) {
+            Ok(()) => {
+                self.visual_anchor = None;
+                format!(
+                    "Exported {} × {} cells to {path}",
+                    rows.len(),
+                    headers.len()
+                )
+            }
+            Err(e) => format!("Export failed: {e:#}"),
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    fn move_up(&mut self) {
+        let position = self.display_position(self.cursor_row);
+        if position > 0 {
+            self.move_to_display_position(position - 1);
+            // Auto-scroll up if cursor moves above viewport
+            if position - 1 < self.scroll_offset {
+                self.scroll_offset = position - 1;
+            }
+        }
+    }
+
+    fn move_down(&mut self) {
+        let position = self.display_position(self.cursor_row);
+        if position < self.visible_row_count().saturating_sub(1) {
+            self.move_to_display_position(position + 1);
+            // Auto-scroll down will be handled in render based on viewport height
+        }
+    }
+
+    /// Update scroll offset to keep cursor visible (offsets count displayed rows;
+    /// frozen rows are always shown, so the offset starts after them)
+    fn update_scroll(&mut self, viewport_height: usize) {
+        let frozen = self.frozen_row_count(viewport_height);
+        self.scroll_offset = self.scroll_offset.max(frozen);
+        let position = self.display_position(self.cursor_row);
+        if position < frozen {
+            return;
+        }
+        let viewport_height = viewport_height - frozen;
+        let last_offset = self
+            .visible_row_count()
+            .saturating_sub(viewport_height)
+            .max(frozen);
+        self.scroll_offset = scroll_offset_for(
+            position,
+            self.scroll_offset,
+            viewport_height,
+            self.scroll_margin,
+            (frozen, last_offset),
+        );
+    }
+
+    /// Rows half_page_up/half_page_down move: half the scrolling rows on screen
+    fn half_page_rows(&self) -> usize {
+        (self.table_viewport.1 / 2).max(1)
+    }
+
+    /// Rows page_up/page_down move: page_size, or the scrolling rows on screen at the
+    /// last render, like less
+    fn page_rows(&self) -> usize {
+        if self.page_size > 0 {
+            self.page_size
+        } else {
+            self.table_viewport.1.max(1)
+        }
+    }
+
+    /// Frozen data rows to pin, leaving at least one row of the viewport to scroll
+    fn frozen_row_count(&self, viewport_height: usize) -> usize {
+        self.frozen_rows
+            .min(self.visible_row_count())
+            .min(viewport_height.saturating_sub(1))
+    }
+
+    /// Columns in display order, split into the ones kept at the left edge (pinned, then
+    /// frozen; at most half the viewport) and the ones that scroll; hidden ones are left out
+    fn column_layout(&self, viewport_width: usize) -> (Vec<usize>, Vec<usize>) {
+        let view = self.column_views.get(&self.current_sheet_index);
+        let hidden = |col: &usize| view.is_some_and(|v| v.hidden.contains(col));
+        let pinned = view.map_or(&[][..], |v| v.pinned.as_slice());
+
+        let mut width = 0;
+        let sticky: Vec<usize> = pinned
+            .iter()
+            .copied()
+            .chain((0..self.frozen_cols).filter(|col| !pinned.contains(col)))
+            .filter(|col| *col < self.column_widths.len() && !hidden(col))
+            .take_while(|&col| {
+                width += self.column_widths[col] + 1;
+                width <= viewport_width / 2
+            })
+            .collect();
+        let scrolling: Vec<usize> = (0..self.column_widths.len())
+            .filter(|col| !hidden(col) && !sticky.contains(col))
+            .collect();
+        // Always leave something to scroll
+        if scrolling.is_empty() && !sticky.is_empty() {
+            return (Vec::new(), sticky);
+        }
+        (sticky, scrolling)
+    }
+
+    /// Hide the cursor's column (for this sheet, until unhidden) and move to the next one
+    fn hide_current_column(&mut self) {
+        let (sticky, scrolling) = self.column_layout(usize::MAX);
+        if sticky.len() + scrolling.len() <= 1 {
+            self.copy_feedback = Some((
+                "Can't hide the last visible column".to_string(),
+                Instant::now(),
+            ));
+            return;
+        }
+        let col = self.cursor_col;
+        let view = self
+            .column_views
+            .entry(self.current_sheet_index)
+            .or_default();
+        view.hidden.insert(col);
+        view.pinned.retain(|&c| c != col);
+        let hidden = view.hidden.len();
+        self.copy_feedback = Some((
+            format!(
+                "Hid column {} ({hidden} hidden, {} to show all)",
+                self.col_to_letter(col),
+                self.key_label("unhide_columns")
+            ),
+            Instant::now(),
+        ));
+        self.move_to_visible_column(col);
+    }
+
+    fn unhide_columns(&mut self) {
+        let count = self
+            .column_views
+            .get_mut(&self.current_sheet_index)
+            .map_or(0, |view| std::mem::take(&mut view.hidden).len());
+        let message = match count {
+            0 => "No hidden columns".to_string(),
+            1 => "Showing 1 hidden column".to_string(),
+            n => format!("Showing {n} hidden columns"),
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Pin the cursor's column to the left edge, or unpin it
+    fn toggle_pinned_column(&mut self) {
+        let col = self.cursor_col;
+        let letter = self.col_to_letter(col);
+        let view = self
+            .column_views
+            .entry(self.current_sheet_index)
+            .or_default();
+        let message = if view.pinned.contains(&col) {
+            view.pinned.retain(|&c| c != col);
+            format!("Unpinned column {letter}")
+        } else {
+            view.pinned.push(col);
+            format!("Pinned column {letter} to the left edge")
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Set the cursor column's width (kept for this sheet while the session lasts)
+    fn set_column_width(&mut self, width: usize) {
+        let col = self.cursor_col;
+        if col >= self.column_widths.len() {
+            return;
+        }
+        let max = self.table_viewport.0.max(Self::MIN_COLUMN_WIDTH);
+        let width = width.clamp(Self::MIN_COLUMN_WIDTH, max);
+        self.column_widths[col] = width;
+        self.column_views
+            .entry(self.current_sheet_index)
+            .or_default()
+            .widths
+            .insert(col, width);
+        self.copy_feedback = Some((
+            format!("Column {} width: {width}", self.col_to_letter(col)),
+            Instant::now(),
+        ));
+    }
+
+    fn resize_column(&mut self, delta: isize) {
+        if let Some(&width) = self.column_widths.get(self.cursor_col) {
+            self.set_column_width(width.saturating_add_signed(delta));
+        }
+    }
+
+    /// Size the cursor column to the longest value on screen, header included
+    fn autofit_column(&mut self) {
+        let col = self.cursor_col;
+        let start = self.scroll_offset;
+        let rows: Vec<usize> = match &self.row_filter {
+            Some(filter) => filter
+                .rows
+                .iter()
+                .skip(start)
+                .take(self.table_viewport.1)
+                .copied()
+                .collect(),
+            None => (start..self.sheet_data.height())
+                .take(self.table_viewport.1)
+                .collect(),
+        };
+        let cell_width = |cells: &[CellValue]| {
+            cells.get(col).map_or(0, |c| {
+                display::text_width(&c.to_display_string(&self.locale))
+            })
+        };
+        let mut width = self
+            .sheet_data
+            .headers()
+            .get(col)
+            .map_or(0, |h| display::text_width(h));
+        for (_, cells) in &self.frozen_row_cache {
+            width = width.max(cell_width(cells));
+        }
+        for row in rows {
+            let (cells, _formulas) = self.sheet_data.get_rows(row, 1);
+            if let Some(cells) = cells.first() {
+                width = width.max(cell_width(cells));
+            }
+        }
+        self.set_column_width(width);
+    }
+
+    /// Move the cursor off a hidden column: to the next shown one, else the previous one
+    fn move_to_visible_column(&mut self, from: usize) {
+        let (sticky, scrolling) = self.column_layout(usize::MAX);
+        let shown = |col: &usize| sticky.contains(col) || scrolling.contains(col);
+        if let Some(col) = (from..self.column_widths.len())
+            .find(shown)
+            .or_else(|| (0..from).rev().find(shown))
+        {
+            self.cursor_col = col;
+        }
+    }
+
+    /// Show a column again when a jump or search lands on it
+    fn reveal_cursor_col(&mut self) {
+        let col = self.cursor_col;
+        if let Some(view) = self.column_views.get_mut(&self.current_sheet_index)
+            && view.hidden.remove(&col)
+        {
+            self.copy_feedback = Some((
+                format!("Showing hidden column {}", self.col_to_letter(col)),
+                Instant::now(),
+            ));
+        }
+    }
+
+    /// Display label of an action's key, for hints in messages
+    fn key_label(&self, action: &str) -> String {
+        self.config
+            .key_sequence_label(action)
+            .unwrap_or_else(|| "(unbound)".to_string())
+    }
+
+    /// Keep copies of the pinned rows so lazy sheets don't reload the row cache every frame
+    fn refresh_frozen_row_cache(&mut self, count: usize) {
+        let rows: Vec<usize> = match &self.row_filter {
+            Some(filter) => filter.rows.iter().take(count).copied().collect(),
+            None => (0..count).collect(),
+        };
+        if self
+            .frozen_row_cache
+            .iter()
+            .map(|(row, _)| *row)
+            .eq(rows.iter().copied())
+        {
+            return;
+        }
+        self.frozen_row_cache = rows
+            .into_iter()
+            .map(|row| {
+                let (cells, _formulas) = self.sheet_data.get_rows(row, 1);
+                (row, cells.first().cloned().unwrap_or_default())
+            })
+            .collect();
+    }
+
+    /// Freeze the columns up to the cursor, or unfreeze if that's already what's frozen
+    fn toggle_frozen_columns(&mut self) {
+        let count = self.cursor_col + 1;
+        let message = if self.frozen_cols == count {
+            self.frozen_cols = 0;
+            "Columns unfrozen".to_string()
+        } else {
+            self.frozen_cols = count;
+            match count {
+                1 => "Froze column A".to_string(),
+                _ => format!("Froze columns A-{}", self.col_to_letter(count - 1)),
+            }
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Rows on display: the filter's matches, or the whole sheet
+    fn visible_row_count(&self) -> usize {
+        match &self.row_filter {
+            Some(filter) => filter.rows.len(),
+            None => self.sheet_data.height(),
+        }
+    }
+
+    /// Where a data row appears on display (the nearest shown row if the filter hides it)
+    fn display_position(&self, row: usize) -> usize {
+        match &self.row_filter {
+            Some(filter) => filter
+                .rows
+                .binary_search(&row)
+                .unwrap_or_else(|i| i.min(filter.rows.len().saturating_sub(1))),
+            None => row,
+        }
+    }
+
+    /// Put the cursor on the row shown at a display position
+    fn move_to_display_position(&mut self, position: usize) {
+        let position = position.min(self.visible_row_count().saturating_sub(1));
+        self.cursor_row = match &self.row_filter {
+            Some(filter) => filter.rows.get(position).copied().unwrap_or(0),
+            None => position,
+        };
+    }
+
+    fn enter_filter_mode(&mut self) {
+        self.filter_mode = true;
+        // Start from the cursor's column so the usual case is just "OP VALUE"
+        self.filter_input = format!("{} ", self.col_to_letter(self.cursor_col));
+    }
+
+    /// Parse the typed filter and show only the rows it matches
+    fn apply_filter(&mut self) {
+        self.filter_mode = false;
+        let input = std::mem::take(&mut self.filter_input);
+        let spec = match FilterSpec::parse(&input, self.sheet_data.headers()) {
+            Ok(spec) => spec,
+            Err(message) => {
+                self.copy_feedback = Some((message, Instant::now()));
+                return;
+            }
+        };
+
+        let total_height = self.sheet_data.height();
+        if total_height > 1000 {
+            self.progress = Some(ProgressInfo::new("Filtering", total_height));
+        }
+        const FILTER_CHUNK_SIZE: usize = 500;
+        let mut rows = Vec::new();
+        for chunk_start in (0..total_height).step_by(FILTER_CHUNK_SIZE) {
+            let chunk_size = FILTER_CHUNK_SIZE.min(total_height - chunk_start);
+            let (chunk, _formulas) = self.sheet_data.get_rows(chunk_start, chunk_size);
+            for (i, row) in chunk.iter().enumerate() {
+                if spec.matches(&self.locale, row.get(spec.col).unwrap_or(&CellValue::Empty)) {
+                    rows.push(chunk_start + i);
+                }
+            }
+            if let Some(ref mut progress) = self.progress {
+                progress.update(chunk_start + chunk_size);
+            }
+        }
+        self.progress = None;
+
+        if rows.is_empty() {
+            self.copy_feedback = Some((format!("No rows match: {}", input.trim()), Instant::now()));
+            return;
+        }
+        let count = rows.len();
+        self.row_filter = Some(RowFilter {
+            description: input.trim().to_string(),
+            rows,
+        });
+        self.scroll_offset = 0;
+        self.move_to_display_position(self.display_position(self.cursor_row));
+        self.copy_feedback = Some((
+            format!("Showing {count} of {total_height} rows"),
+            Instant::now(),
+        ));
+    }
+
+    fn clear_filter(&mut self) {
+        self.row_filter = None;
+        self.copy_feedback = Some(("Filter cleared".to_string(), Instant::now()));
+    }
+
+    /// Drop the filter when a jump or search lands on a row it hides
+    fn reveal_cursor_row(&mut self) {
+        if let Some(filter) = &self.row_filter
+            && filter.rows.binary_search(&self.cursor_row).is_err()
+        {
+            self.row_filter = None;
+            self.copy_feedback = Some((
+                format!("Filter cleared to show row {}", self.cursor_row + 1),
+                Instant::now(),
+            ));
+        }
+    }
+
+    /// Calculate column widths based on content
+    fn calculate_column_widths(&mut self) -> Vec<usize> {
+        let num_cols = self.sheet_data.width();
+        let mut widths = vec![0; num_cols];
+
+        // Measure headers
+        let headers = self.sheet_data.headers();
+        for (i, header) in headers.iter().enumerate() {
+            widths[i] = display::text_width(header);
+        }
+
+        // Sample first 100 rows (or fewer if sheet is smaller)
+        let sample_size = 100.min(self.sheet_data.height());
+        let (sample_rows, _) = self.sheet_data.get_rows(0, sample_size);
+
+        for (row_idx, row) in sample_rows.iter().enumerate() {
+            for (col_idx, cell) in row.iter().enumerate() {
+                let pos = (
+                    (self.sheet_origin.0 + row_idx) as u32,
+                    (self.sheet_origin.1 + col_idx) as u32,
+                );
+                let len =
+                    display::text_width(&cell_text(&self.locale, &self.currency_cells, pos, cell));
+                widths[col_idx] = widths[col_idx].max(len);
+            }
+        }
+
+        // Apply constraints: min 3 chars, max 30 chars (the resize keys can go wider)
+        widths
+            .iter()
+            .map(|&w| w.clamp(Self::MIN_COLUMN_WIDTH, 30))
+            .collect()
+    }
+
+    /// Update horizontal scroll offset (an index into the scrolling columns) to keep the
+    /// cursor visible; the columns kept at the left edge take their share of the viewport
+    fn update_horizontal_scroll(&mut self, viewport_width: usize) {
+        let (sticky, scrolling) = self.column_layout(viewport_width);
+        self.horizontal_scroll_offset = self
+            .horizontal_scroll_offset
+            .min(scrolling.len().saturating_sub(1));
+        let Some(position) = scrolling.iter().position(|&col| col == self.cursor_col) else {
+            return; // On a column kept at the left edge
+        };
+        let sticky_width: usize = sticky.iter().map(|&col| self.column_widths[col] + 1).sum();
+        let viewport_width = viewport_width.saturating_sub(sticky_width);
+
+        // Scroll left if cursor is before visible area
+        if position < self.horizontal_scroll_offset {
+            self.horizontal_scroll_offset = position;
+            return;
+        }
+        // Scroll right until the whole cursor column fits (a single column always "fits")
+        let span_width = |start: usize| -> usize {
+            scrolling[start..=position]
+                .iter()
+                .map(|&col| self.column_widths[col] + 1)
+                .sum()
+        };
+        let mut offset = self.horizontal_scroll_offset;
+        while offset < position && span_width(offset) > viewport_width {
+            offset += 1;
+        }
+        self.horizontal_scroll_offset = offset;
+    }
+
+    /// Columns in the order they're shown, ignoring the viewport's width
+    fn column_order(&self) -> Vec<usize> {
+        let (sticky, scrolling) = self.column_layout(usize::MAX);
+        sticky.into_iter().chain(scrolling).collect()
+    }
+
+    fn move_left(&mut self, columns: usize) {
+        let order = self.column_order();
+        if let Some(i) = order.iter().position(|&col| col == self.cursor_col) {
+            self.cursor_col = order[i.saturating_sub(columns)];
+            // Horizontal scroll will be updated in render to keep the cursor visible
+        }
+    }
+
+    fn move_right(&mut self, columns: usize) {
+        let order = self.column_order();
+        if let Some(i) = order.iter().position(|&col| col == self.cursor_col) {
+            self.cursor_col = order[i.saturating_add(columns).min(order.len() - 1)];
+            // Auto-scroll right will be handled in render based on viewport width
+        }
+    }
+
+    fn move_to_start_of_row(&mut self) {
+        if let Some(&first) = self.column_order().first() {
+            self.cursor_col = first;
+        }
+        self.horizontal_scroll_offset = 0;
+    }
+
+    fn move_to_end_of_row(&mut self) {
+        if let Some(&last) = self.column_order().last() {
+            self.cursor_col = last;
+        }
+        // Horizontal scroll will be updated in render to show the last column
+    }
+
+    fn page_up(&mut self, page_size: usize) {
+        let position = self.display_position(self.cursor_row);
+        self.move_to_display_position(position.saturating_sub(page_size));
+    }
+
+    fn page_down(&mut self, page_size: usize) {
+        let position = self.display_position(self.cursor_row);
+        self.move_to_display_position(position + page_size);
+    }
+
+    fn move_to_top(&mut self) {
+        self.move_to_display_position(0);
+    }
+
+    fn move_to_bottom(&mut self) {
+        self.move_to_display_position(usize::MAX);
+    }
+
+    fn col_to_letter(&self, col: usize) -> String {
+        let mut result = String::new();
+        let mut n = col + 1;
+        while n > 0 {
+            n -= 1;
+            result.push((b'A' + (n % 26) as u8) as char);
+            n /= 26;
+        }
+        result.chars().rev().collect()
+    }
+
+    /// The cursor's address in the workbook, e.g. "C7" on a sheet whose data starts at B3
+    fn current_cell_address(&self) -> String {
+        format!(
+            "{}{}",
+            xleak::workbook::column_letter(self.sheet_origin.1 + self.cursor_col),
+            self.sheet_origin.0 + self.cursor_row + 1
+        )
+    }
+
+    /// Whether the key is bound to an action, or starts an action's key sequence
+    fn is_bound(&self, code: KeyCode, modifiers: crossterm::event::KeyModifiers) -> bool {
+        crate::keymap::actions().iter().any(|action| {
+            self.config
+                .get_key_sequence(action)
+                .is_some_and(|keys| same_key(keys[0], (code, modifiers)))
+        })
+    }
+
+    /// Whether the keys typed so far start a longer key sequence bound to an action
+    fn starts_sequence(&self) -> bool {
+        crate::keymap::actions().iter().any(|action| {
+            self.config.get_key_sequence(action).is_some_and(|keys| {
+                keys.len() > self.typed_keys.len()
+                    && keys
+                        .iter()
+                        .zip(&self.typed_keys)
+                        .all(|(a, b)| same_key(*a, *b))
+            })
+        })
+    }
+
+    /// Whether the key triggers the action: a single-key binding pressed on its own, or
+    /// the last key of the action's sequence while the keys before it were typed
+    fn key_matches(
+        &self,
+        code: KeyCode,
+        modifiers: crossterm::event::KeyModifiers,
+        action: &str,
+    ) -> bool {
+        let Some(keys) = self.config.get_key_sequence(action) else {
+            return false;
+        };
+        match keys.as_slice() {
+            [key] => self.typed_keys.len() <= 1 && same_key(*key, (code, modifiers)),
+            keys => {
+                keys.len() == self.typed_keys.len()
+                    && keys
+                        .iter()
+                        .zip(&self.typed_keys)
+                        .all(|(a, b)| same_key(*a, *b))
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        {
+            // While a sheet loads, Esc stops it and the quit key still quits
+            if self.sheet_loader.is_some() {
+                if code == KeyCode::Esc {
+                    self.cancel_sheet_load();
+                    return;
+                }
+                if !self.key_matches(code, modifiers, "quit") {
+                    return;
+                }
+            }
+
+            // If help is showing, any key closes it
+            if self.show_help {
+                self.show_help = false;
+                return;
+            }
+
+            // If cell detail is showing, handle scrolling or close
+            if self.show_cell_detail {
+                match code {
+                    KeyCode::Up => {
+                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(1);
+                    }
+                    KeyCode::PageUp => {
+                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(10);
+                    }
+                    KeyCode::PageDown => {
+                        self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(10);
+                    }
+                    KeyCode::Home => {
+                        self.cell_detail_scroll = 0;
+                    }
+                    _ => {
+                        // Any other key closes the detail view
+                        self.show_cell_detail = false;
+                        self.cell_detail_scroll = 0;
+                    }
+                }
+                return;
+            }
+
+            // If the record view is showing, scroll, step through rows, or close
+            if self.show_record {
+                match code {
+                    KeyCode::Up => self.record_scroll = self.record_scroll.saturating_sub(1),
+                    KeyCode::Down => self.record_scroll = self.record_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.record_scroll = self.record_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.record_scroll = self.record_scroll.saturating_add(10),
+                    KeyCode::Home => self.record_scroll = 0,
+                    KeyCode::Left => self.move_up(),
+                    KeyCode::Right => self.move_down(),
+                    _ => self.show_record = false,
+                }
+                return;
+            }
+
+            // Fuzzy finder: typing narrows the list, arrows pick, Enter jumps
+            if let Some(finder) = &mut self.finder {
+                let last = finder
+                    .matches
+                    .len()
+                    .min(Finder::MAX_SHOWN)
+                    .saturating_sub(1);
+                match code {
+                    KeyCode::Char(c)
+                        if !modifiers.contains(crossterm::event::KeyModifiers::CONTROL) =>
+                    {
+                        finder.query.push(c);
+                        finder.update();
+                    }
+                    KeyCode::Backspace => {
+                        finder.query.pop();
+                        finder.update();
+                    }
+                    KeyCode::Up => finder.selected = finder.selected.saturating_sub(1),
+                    KeyCode::Down => finder.selected = (finder.selected + 1).min(last),
+                    KeyCode::Enter => self.finder_jump(),
+                    KeyCode::Esc => self.finder = None,
+                    _ => {}
+                }
+                return;
+            }
+
+            // Pivot dialog: a letter sets the selected column's role, Enter shows the pivot
+            if let Some(dialog) = &mut self.pivot_dialog {
+                let last = dialog.columns.len().saturating_sub(1);
+                match code {
+                    KeyCode::Up => dialog.selected = dialog.selected.saturating_sub(1),
+                    KeyCode::Down => dialog.selected = (dialog.selected + 1).min(last),
+                    KeyCode::Char(' ') | KeyCode::Delete | KeyCode::Backspace => {
+                        dialog.roles[dialog.selected] = PivotRole::Skip
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(&(_, role)) =
+                            PivotDialog::ROLE_KEYS.iter().find(|(key, _)| *key == c)
+                        {
+                            // The same key again clears the role
+                            let current = &mut dialog.roles[dialog.selected];
+                            *current = if *current == role {
+                                PivotRole::Skip
+                            } else {
+                                role
+                            };
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if let Some(dialog) = self.pivot_dialog.take() {
+                            self.show_pivot(dialog.roles);
+                        }
+                    }
+                    KeyCode::Esc => self.pivot_dialog = None,
+                    _ => {}
+                }
+                return;
+            }
+
+            // Chart popup: Tab switches bar/line, Left/Right change the column
+            if let Some(chart) = &mut self.chart {
+                let last = chart.series.points.len().saturating_sub(1);
+                match code {
+                    KeyCode::Tab => {
+                        chart.kind = match chart.kind {
+                            ChartKind::Bar => ChartKind::Line,
+                            ChartKind::Line => ChartKind::Bar,
+                        }
+                    }
+                    KeyCode::Left => self.chart_next_column(false),
+                    KeyCode::Right => self.chart_next_column(true),
+                    KeyCode::Up => chart.scroll = chart.scroll.saturating_sub(1),
+                    KeyCode::Down => chart.scroll = (chart.scroll + 1).min(last),
+                    KeyCode::PageUp => chart.scroll = chart.scroll.saturating_sub(10),
+                    KeyCode::PageDown => chart.scroll = (chart.scroll + 10).min(last),
+                    _ => self.chart = None,
+                }
+                return;
+            }
+
+            // Workbook switcher: pick with the arrows and Enter, or by number
+            if self.show_workbooks {
+                let last = self.workbooks.len().saturating_sub(1);
+                match code {
+                    KeyCode::Up => {
+                        self.workbooks_selected = self.workbooks_selected.saturating_sub(1)
+                    }
+                    KeyCode::Down => {
+                        self.workbooks_selected = (self.workbooks_selected + 1).min(last)
+                    }
+                    KeyCode::Enter => {
+                        self.show_workbooks = false;
+                        self.switch_workbook(self.workbooks_selected);
+                    }
+                    KeyCode::Char(digit @ '1'..='9') => {
+                        self.show_workbooks = false;
+                        self.switch_workbook(digit as usize - '1' as usize);
+                    }
+                    _ => self.show_workbooks = false,
+                }
+                return;
+            }
+
+            // The bookmark list: a mark's letter jumps to it, anything else closes
+            if self.show_marks {
+                self.show_marks = false;
+                if let KeyCode::Char(letter) = code
+                    && (self.marks.contains_key(&letter) || letter == '\'')
+                {
+                    self.jump_to_mark(letter);
+                }
+                return;
+            }
+
+            // If pivot view is showing, handle scrolling or close
+            if self.show_pivots {
+                match code {
+                    KeyCode::Up => self.pivot_scroll = self.pivot_scroll.saturating_sub(1),
+                    KeyCode::Down => self.pivot_scroll = self.pivot_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.pivot_scroll = self.pivot_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.pivot_scroll = self.pivot_scroll.saturating_add(10),
+                    KeyCode::Home => self.pivot_scroll = 0,
+                    _ => self.show_pivots = false,
+                }
+                return;
+            }
+
+            // If the workbook search results panel is showing, move the selection or jump
+            if self.show_search_results {
+                let last = self.workbook_matches.len().saturating_sub(1);
+                match code {
+                    KeyCode::Up => {
+                        self.search_results_selected =
+                            self.search_results_selected.saturating_sub(1)
+                    }
+                    KeyCode::Down => {
+                        self.search_results_selected = (self.search_results_selected + 1).min(last)
+                    }
+                    KeyCode::PageUp => {
+                        self.search_results_selected =
+                            self.search_results_selected.saturating_sub(10)
+                    }
+                    KeyCode::PageDown => {
+                        self.search_results_selected = (self.search_results_selected + 10).min(last)
+                    }
+                    KeyCode::Home => self.search_results_selected = 0,
+                    KeyCode::End => self.search_results_selected = last,
+                    KeyCode::Enter => {
+                        self.workbook_match_index = Some(self.search_results_selected);
+                        self.jump_to_workbook_match();
+                        self.show_search_results = false;
+                    }
+                    _ => self.show_search_results = false,
+                }
+                return;
+            }
+
+            // If in search mode, handle search input
+            if self.search_mode {
+                // Alt+letter toggles a search option
+                if let KeyCode::Char(c) = code
+                    && modifiers.contains(crossterm::event::KeyModifiers::ALT)
+                {
+                    if self.search_options.toggle(c) && !self.search_workbook {
+                        self.perform_search();
+                    }
+                    return;
+                }
+                match code {
+                    // Workbook searches load every sheet, so they only run on Enter
+                    KeyCode::Char(c) => {
+                        self.search_query.push(c);
+                        if !self.search_workbook {
+                            self.perform_search();
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        self.search_query.pop();
+                        if !self.search_workbook {
+                            self.perform_search();
+                        }
+                    }
+                    KeyCode::Enter => {
+                        // Exit search mode but keep results
+                        self.search_mode = false;
+                        if self.search_workbook {
+                            self.perform_workbook_search();
+                        }
+                    }
+                    KeyCode::Esc => {
+                        // Exit search mode and clear search
+                        self.search_mode = false;
+                        self.clear_search();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // If in jump mode, handle jump input
+            if self.jump_mode {
+                match code {
+                    KeyCode::Char(c) => {
+                        self.jump_input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.jump_input.pop();
+                    }
+                    KeyCode::Enter => {
+                        // Perform jump
+                        self.perform_jump();
+                    }
+                    KeyCode::Esc => {
+                        // Exit jump mode
+                        self.jump_mode = false;
+                        self.jump_input.clear();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // If typing a `:` command, handle command input
+            if self.command_mode {
+                match code {
+                    KeyCode::Char(c) => self.command_input.push(c),
+                    // Backspace on an empty command line leaves it, as in vim
+                    KeyCode::Backspace if self.command_input.pop().is_none() => {
+                        self.command_mode = false;
+                    }
+                    KeyCode::Enter => self.run_command(),
+                    KeyCode::Esc => {
+                        self.command_mode = false;
+                        self.command_input.clear();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // If editing a cell, handle the value input
+            if self.edit_mode {
+                match code {
+                    KeyCode::Char(c) => self.edit_input.push(c),
+                    KeyCode::Backspace => {
+                        self.edit_input.pop();
+                    }
+                    KeyCode::Enter => self.commit_edit(),
+                    KeyCode::Esc => {
+                        self.edit_mode = false;
+                        self.edit_input.clear();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // If in filter mode, handle filter input
+            if self.filter_mode {
+                match code {
+                    KeyCode::Char(c) => self.filter_input.push(c),
+                    KeyCode::Backspace => {
+                        self.filter_input.pop();
+                    }
+                    KeyCode::Enter => self.apply_filter(),
+                    KeyCode::Esc => {
+                        self.filter_mode = false;
+                        self.filter_input.clear();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // If typing a file name for the selection, handle that input
+            if self.export_mode {
+                match code {
+                    KeyCode::Char(c) => self.export_input.push(c),
+                    KeyCode::Backspace => {
+                        self.export_input.pop();
+                    }
+                    KeyCode::Enter => self.export_selection(),
+                    KeyCode::Esc => {
+                        self.export_mode = false;
+                        self.export_input.clear();
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // Visual mode: the copy keys copy the selection, Esc leaves it
+            if self.visual_anchor.is_some() {
+                if self.key_matches(code, modifiers, "copy_cell") {
+                    self.copy_selection(false);
+                    return;
+                } else if self.key_matches(code, modifiers, "copy_row") {
+                    self.copy_selection(true);
+                    return;
+                } else if self.key_matches(code, modifiers, "export_selection") {
+                    self.export_mode = true;
+                    return;
+                } else if code == KeyCode::Esc {
+                    self.visual_anchor = None;
+                    return;
+                }
+            }
+
+            // `]c` / `[c`: the bracket key waits for a `c`; anything else is handled normally
+            if let Some(forward) = self.pending_change_jump.take()
+                && code == KeyCode::Char('c')
+            {
+                self.jump_to_change(forward);
+                return;
+            }
+
+            // `m` / `'` wait for a mark letter; any other key cancels
+            if let Some(action) = self.pending_mark.take() {
+                if let KeyCode::Char(letter) = code {
+                    match action {
+                        MarkKey::Set if letter.is_ascii_alphabetic() => self.set_mark(letter),
+                        MarkKey::Jump if letter.is_ascii_alphabetic() || letter == '\'' => {
+                            self.jump_to_mark(letter)
+                        }
+                        _ => {}
+                    }
+                }
+                return;
+            }
+
+            // Digits before a motion repeat it (`25j`). A 0 only extends a count, and a
+            // digit bound to an action starts none, so `0` in the vim profile still works.
+            if let KeyCode::Char(digit @ '0'..='9') = code
+                && self.typed_keys.is_empty()
+                && (modifiers - crossterm::event::KeyModifiers::SHIFT).is_empty()
+                && (self.count.is_some() || (digit != '0' && !self.is_bound(code, modifiers)))
+            {
+                let count =
+                    self.count.unwrap_or(0).saturating_mul(10) + digit as usize - '0' as usize;
+                self.count = Some(count.min(Self::MAX_COUNT));
+                return;
+            }
+            let count = self.count.take();
+            if (count.is_some() || !self.typed_keys.is_empty()) && code == KeyCode::Esc {
+                self.typed_keys.clear();
+                return;
+            }
+
+            // Key sequences (`gg`, `leader f`): a key that starts one waits for the next.
+            // When the keys stop matching any sequence, the last one is handled alone.
+            self.typed_keys.push((code, modifiers));
+            if self.starts_sequence() {
+                self.count = count;
+                return;
+            }
+            if self.typed_keys.len() > 1
+                && !crate::keymap::actions()
+                    .iter()
+                    .any(|action| self.key_matches(code, modifiers, action))
+            {
+                self.typed_keys = vec![(code, modifiers)];
+            }
+            self.handle_action_key(code, modifiers, count);
+            self.typed_keys.clear();
+        }
+    }
+
+    /// Run the action bound to the key (or the key sequence just completed), repeated or
+    /// aimed by the count typed before it
+    fn handle_action_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: crossterm::event::KeyModifiers,
+        count: Option<usize>,
+    ) {
+        let times = count.unwrap_or(1);
+        // Normal navigation and commands - using configured keybindings
+        // Check actions in order of priority
+        if self.diff.is_some() && self.key_matches(code, modifiers, "next_change") {
+            self.pending_change_jump = Some(true);
+        } else if self.diff.is_some() && self.key_matches(code, modifiers, "prev_change") {
+            self.pending_change_jump = Some(false);
+        } else if self.key_matches(code, modifiers, "quit") {
+            // Unsaved edits (in any open workbook): the first press only warns
+            let unsaved = self.unsaved_edits
+                || self
+                    .workbooks
+                    .iter()
+                    .flatten()
+                    .any(|view| view.unsaved_edits);
+            if unsaved && !self.quit_warned {
+                self.quit_warned = true;
+                self.copy_feedback = Some((
+                    format!(
+                        "Unsaved edits: :w FILE.xlsx to save, or {} again to quit",
+                        self.key_label("quit")
+                    ),
+                    Instant::now(),
+                ));
+            } else {
+                self.should_quit = true;
+            }
+        } else if self.key_matches(code, modifiers, "help") {
+            self.show_help = true;
+        } else if self.key_matches(code, modifiers, "theme_toggle") {
+            self.current_theme = (self.current_theme + 1) % self.themes.len();
+        } else if self.key_matches(code, modifiers, "search") {
+            self.search_mode = true;
+            self.search_start = Some(self.cursor_mark());
+            self.clear_search();
+        } else if self.key_matches(code, modifiers, "search_workbook")
+            || is_ctrl_slash(code, modifiers)
+        {
+            self.start_workbook_search();
+        } else if self.key_matches(code, modifiers, "command") {
+            self.command_mode = true;
+            self.command_input.clear();
+        } else if self.key_matches(code, modifiers, "search_results") {
+            if !self.workbook_matches.is_empty() {
+                self.search_results_selected = self.workbook_match_index.unwrap_or(0);
+                self.show_search_results = true;
+            }
+        } else if self.key_matches(code, modifiers, "next_match") {
+            self.jump_to_next_match();
+        } else if self.key_matches(code, modifiers, "prev_match") {
+            self.jump_to_prev_match();
+        } else if self.key_matches(code, modifiers, "copy_cell") {
+            self.copy_current_cell();
+        } else if self.key_matches(code, modifiers, "copy_row") {
+            self.copy_current_row();
+        } else if self.key_matches(code, modifiers, "visual_mode") {
+            self.toggle_visual_mode();
+        } else if self.key_matches(code, modifiers, "jump") {
+            self.enter_jump_mode();
+        } else if self.key_matches(code, modifiers, "filter") {
+            self.enter_filter_mode();
+        } else if self.key_matches(code, modifiers, "freeze_columns") {
+            self.toggle_frozen_columns();
+        } else if self.key_matches(code, modifiers, "hide_column") {
+            self.hide_current_column();
+        } else if self.key_matches(code, modifiers, "unhide_columns") {
+            self.unhide_columns();
+        } else if self.key_matches(code, modifiers, "pin_column") {
+            self.toggle_pinned_column();
+        } else if self.key_matches(code, modifiers, "shrink_column") {
+            self.resize_column(-Self::COLUMN_RESIZE_STEP);
+        } else if self.key_matches(code, modifiers, "grow_column") {
+            self.resize_column(Self::COLUMN_RESIZE_STEP);
+        } else if self.key_matches(code, modifiers, "autofit_column") {
+            self.autofit_column();
+        } else if self.key_matches(code, modifiers, "show_cell_detail") {
+            self.show_cell_detail = true;
+            self.cell_detail_scroll = 0;
+            self.load_sheet_protection();
+            self.evaluate_current_cell();
+        } else if self.key_matches(code, modifiers, "edit_cell") {
+            self.start_edit();
+        } else if self.key_matches(code, modifiers, "show_record") {
+            self.show_record = true;
+            self.record_scroll = 0;
+        } else if self.key_matches(code, modifiers, "show_pivots") {
+            self.open_pivot_view();
+        } else if self.key_matches(code, modifiers, "pivot") {
+            self.open_pivot_dialog();
+        } else if self.key_matches(code, modifiers, "bars") {
+            self.toggle_bars();
+        } else if self.key_matches(code, modifiers, "heatmap") {
+            self.toggle_heatmap();
+        } else if self.key_matches(code, modifiers, "gutter") {
+            self.gutter = !self.gutter;
+        } else if self.key_matches(code, modifiers, "formulas") {
+            self.show_formulas = !self.show_formulas;
+            let shown = if self.show_formulas {
+                "Showing formulas"
+            } else {
+                "Showing values"
+            };
+            self.copy_feedback = Some((shown.to_string(), Instant::now()));
+        } else if self.key_matches(code, modifiers, "chart") {
+            self.open_chart();
+        } else if self.key_matches(code, modifiers, "reload") {
+            self.start_reload();
+        } else if self.key_matches(code, modifiers, "set_mark") {
+            self.pending_mark = Some(MarkKey::Set);
+        } else if self.key_matches(code, modifiers, "jump_to_mark") {
+            self.pending_mark = Some(MarkKey::Jump);
+        } else if self.key_matches(code, modifiers, "list_marks") {
+            self.show_marks = true;
+        } else if self.key_matches(code, modifiers, "finder") {
+            self.open_finder();
+        } else if self.key_matches(code, modifiers, "workbooks") {
+            if self.workbooks.is_empty() {
+                self.copy_feedback = Some((
+                    "Only one workbook open (xleak A.xlsx B.xlsx -i opens several)".to_string(),
+                    Instant::now(),
+                ));
+            } else {
+                self.show_workbooks = true;
+                self.workbooks_selected = self.current_workbook;
+            }
+        } else if self.key_matches(code, modifiers, "switch_pane") {
+            self.switch_pane();
+        } else if self.key_matches(code, modifiers, "jump_back") {
+            self.step_jump_list(false);
+        } else if self.key_matches(code, modifiers, "jump_forward") {
+            self.step_jump_list(true);
+        } else if self.key_matches(code, modifiers, "next_error") {
+            self.jump_to_next_error();
+        } else if self.key_matches(code, modifiers, "open_link") {
+            self.open_current_link();
+        } else if self.key_matches(code, modifiers, "next_sheet") {
+            let _ = self.switch_to_next_sheet();
+        } else if self.key_matches(code, modifiers, "prev_sheet") || code == KeyCode::BackTab {
+            // BackTab is another way to detect Shift+Tab on some terminals
+            let _ = self.switch_to_prev_sheet();
+        } else if self.key_matches(code, modifiers, "up") {
+            self.page_up(times);
+        } else if self.key_matches(code, modifiers, "down") {
+            self.page_down(times);
+        } else if self.key_matches(code, modifiers, "left") {
+            self.move_left(times);
+        } else if self.key_matches(code, modifiers, "right") {
+            self.move_right(times);
+        } else if let Some(row) = count.filter(|_| {
+            self.key_matches(code, modifiers, "jump_to_top")
+                || self.key_matches(code, modifiers, "jump_to_bottom")
+        }) {
+            // `25gg` (or `25ge`) goes to the 25th row shown, as `25G` does in vim
+            self.move_to_display_position(row.saturating_sub(1));
+        } else if self.key_matches(code, modifiers, "jump_to_top") {
+            self.move_to_top();
+        } else if self.key_matches(code, modifiers, "jump_to_bottom") {
+            self.move_to_bottom();
+        } else if self.key_matches(code, modifiers, "jump_to_row_start") {
+            self.move_to_start_of_row();
+        } else if self.key_matches(code, modifiers, "jump_to_row_end") {
+            self.move_to_end_of_row();
+        } else if self.key_matches(code, modifiers, "page_up") {
+            self.page_up(self.page_rows().saturating_mul(times));
+        } else if self.key_matches(code, modifiers, "page_down") {
+            self.page_down(self.page_rows().saturating_mul(times));
+        } else if self.key_matches(code, modifiers, "half_page_up") {
+            self.page_up(self.half_page_rows().saturating_mul(times));
+        } else if self.key_matches(code, modifiers, "half_page_down") {
+            self.page_down(self.half_page_rows().saturating_mul(times));
+        } else if code == KeyCode::Esc {
+            // Special handling for Esc - stop a running search, clear filter or search
+            // if active, leave a pivot, otherwise quit
+            if self.background_search.is_some() {
+                self.stop_background_search();
+            } else if self.row_filter.is_some() {
+                self.clear_filter();
+            } else if !self.search_matches.is_empty() || !self.workbook_matches.is_empty() {
+                self.clear_search();
+            } else if self.pivot.is_some() {
+                self.close_pivot();
+            } else {
+                self.should_quit = true;
+            }
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let mut area = frame.area();
+        if !self.warnings.is_empty() {
+            let banner = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(self.warnings.len() as u16), // Warning banner
+                    Constraint::Min(0),
+                ])
+                .split(area);
+            self.render_warnings(frame, banner[0]);
+            area = banner[1];
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(3),    // Main content
+                Constraint::Length(3), // Status bar
+            ])
+            .split(area);
+
+        // One pane, or the focused pane and the other one stacked or side by side
+        let sheet_dims = match &self.split {
+            None => self.render_table(frame, chunks[0], true),
+            Some(split) => {
+                let panes = Layout::default()
+                    .direction(split.direction)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(chunks[0]);
+                let (focused, other) = if split.focus_first {
+                    (panes[0], panes[1])
+                } else {
+                    (panes[1], panes[0])
+                };
+                self.swap_pane_state();
+                self.render_table(frame, other, false);
+                self.swap_pane_state();
+                self.render_table(frame, focused, true)
+            }
+        };
+        let selection = self.selection();
+        let colors = self.theme().colors.clone();
+
+        // Status bar with current cell info
+        let (cell, _) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
+        let current_cell_value = cell
+            .map(|v| v.to_display_string(&self.locale))
+            .unwrap_or_default();
+
+        let status_text = if let Some(ref progress) = self.progress {
+            // Show progress indicator
+            format!(" ⏳ {} ", progress.format())
+        } else if self.jump_mode {
+            format!(
+                " Jump to (row, A5, Sheet!A5, row,col, or a name): {} ",
+                self.jump_input
+            )
+        } else if self.command_mode {
+            format!(" :{} ", self.command_input)
+        } else if self.edit_mode {
+            format!(
+                " Edit {} (Enter to set, Esc to cancel): {} ",
+                self.current_cell_address(),
+                self.edit_input
+            )
+        } else if self.filter_mode {
+            format!(
+                " Filter (column contains/equals/>/< value, Enter to apply): {} ",
+                self.filter_input
+            )
+        } else if self.export_mode {
+            format!(
+                " Export selection to (.csv, .json, .md, or tab-separated): {} ",
+                self.export_input
+            )
+        } else if !self.config.ui.status_bar.is_empty() && !self.search_mode {
+            self.custom_status(&sheet_dims, &current_cell_value, selection.as_ref())
+        } else if let Some((rows, cols)) = &selection {
+            format!(
+                " VISUAL {} ({} × {} cells) | {}:copy TSV {}:copy CSV {}:export Esc:cancel | {} ",
+                self.selection_label(*rows, cols),
+                rows.1 - rows.0 + 1,
+                cols.len(),
+                self.key_label("copy_cell"),
+                self.key_label("copy_row"),
+                self.key_label("export_selection"),
+                sheet_dims
+            )
+        } else if self.search_mode {
+            let options = self
+                .search_options
+                .describe(&self.col_to_letter(self.cursor_col));
+            let prompt = match (self.search_workbook, options.is_empty()) {
+                (true, true) => "Search all sheets".to_string(),
+                (true, false) => format!("Search all sheets ({options})"),
+                (false, true) => "Search".to_string(),
+                (false, false) => format!("Search ({options})"),
+            };
+            let error = self
+                .search_error
+                .as_ref()
+                .map(|e| format!(" [{e}]"))
+                .unwrap_or_default();
+            let hint = if self.search_workbook {
+                "Enter to search, "
+            } else {
+                ""
+            };
+            format!(
+                " {prompt}: {}{error} ({hint}Alt+r regex, Alt+c case, Alt+w whole cell, Alt+l column) ",
+                self.search_query
+            )
+        } else if self.search_workbook
+            && let Some(idx) = self.workbook_match_index
+        {
+            format!(
+                " Match {}/{} in workbook | {} {} | n:next N:prev F:results Esc:clear | {} ",
+                idx + 1,
+                self.workbook_matches.len(),
+                self.current_sheet_name(),
+                self.current_cell_address(),
+                sheet_dims
+            )
+        } else if let Some(idx) = self.current_match_index {
+            // Show search results
+            let match_info = format!("Match {}/{} | ", idx + 1, self.search_matches.len());
+            if self.sheet_names.len() > 1 {
+                format!(
+                    " {} | {}n:next N:prev Esc:clear | {} | Tab:next sheet ?:help q:quit ",
+                    match_info,
+                    self.current_cell_address(),
+                    sheet_dims
+                )
+            } else {
+                format!(
+                    " {} | {}n:next N:prev Esc:clear | {} | ?:help q:quit ",
+                    match_info,
+                    self.current_cell_address(),
+                    sheet_dims
+                )
+            }
+        } else {
+            // Show loading mode indicator for large files
+            let mode_indicator = match &self.sheet_data {
+                SheetDataSource::Lazy { data, .. } if data.is_sparse() => " [Sparse] ",
+                SheetDataSource::Lazy { .. } => " [Lazy] ",
+                SheetDataSource::Eager(_) => "",
+            };
+
+            let mut stats = self.cell_stats_indicator();
+            // Like vim's showcmd: the count and sequence keys typed so far
+            if self.count.is_some() || !self.typed_keys.is_empty() {
+                let keys: String = self
+                    .typed_keys
+                    .iter()
+                    .map(|&(code, modifiers)| crate::config::key_label(code, modifiers))
+                    .collect();
+                let count = self
+                    .count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default();
+                stats.push_str(&format!(" | {count}{keys}"));
+            }
+            if let Some(link) = self.hyperlink_at(self.cursor_row, self.cursor_col) {
+                stats.push_str(&format!(" | 🔗 {} (o:open)", link.target));
+            }
+
+            if self.sheet_names.len() > 1 {
+                format!(
+                    " {} | {}{}{} | Theme: {} | t:theme /:search Tab:sheet ?:help q:quit ",
+                    self.current_cell_address(),
+                    sheet_dims,
+                    mode_indicator,
+                    stats,
+                    self.theme().name
+                )
+            } else {
+                format!(
+                    " {} | {}{}{} | Theme: {} | t:theme /:search ?:help q:quit ",
+                    self.current_cell_address(),
+                    sheet_dims,
+                    mode_indicator,
+                    stats,
+                    self.theme().name
+                )
+            }
+        };
+
+        let mut status_style = Style::default().fg(colors.status_bar_fg);
+        if let Some(bg) = colors.status_bar_bg {
+            status_style = status_style.bg(bg);
+        }
+
+        let status = Paragraph::new(status_text).style(status_style).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.border_fg))
+                .title(format!(" {} ", current_cell_value)),
+        );
+
+        frame.render_widget(status, chunks[1]);
+
+        // Render cell detail overlay if visible
+        if self.show_cell_detail {
+            self.render_cell_detail(frame);
+        }
+
+        // Render record view overlay if visible
+        if self.show_record {
+            self.render_record(frame);
+        }
+
+        // Render pivot table overlay if visible
+        if self.show_pivots {
+            self.render_pivots(frame);
+        }
+
+        if self.show_marks {
+            self.render_marks(frame);
+        }
+
+        if self.show_workbooks {
+            self.render_workbooks(frame);
+        }
+
+        if let Some(finder) = &self.finder {
+            self.render_finder(frame, finder);
+        }
+
+        if let Some(dialog) = &self.pivot_dialog {
+            self.render_pivot_dialog(frame, dialog);
+        }
+
+        if let Some(chart) = &self.chart {
+            self.render_chart(frame, chart);
+        }
+
+        // Render workbook search results if visible
+        if self.show_search_results {
+            self.render_search_results(frame);
+        }
+
+        // Render help overlay if visible
+        if self.show_help {
+            self.render_help(frame);
+        }
+
+        if let Some(loader) = &self.sheet_loader
+            && (loader.kind == LoadKind::Initial
+                || loader.started_at.elapsed() >= SheetLoader::POPUP_DELAY)
+        {
+            self.render_loading(frame, loader);
+        }
+
+        // Render copy feedback if active (and not expired)
+        if let Some((ref message, timestamp)) = self.copy_feedback {
+            // Show feedback for 2 seconds
+            if timestamp.elapsed() < Duration::from_secs(2) {
+                self.render_copy_feedback(frame, message);
+            } else {
+                // Clear expired feedback
+                self.copy_feedback = None;
+            }
+        }
+    }
+
+    /// Draw the current sheet's table in `area` (dimmed when it's the unfocused pane);
+    /// returns its size and visible columns for the status bar
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, focused: bool) -> String {
+        // The gutter: Excel row numbers left of the rows and column letters above the
+        // headers (a pivot's rows have no addresses)
+        let gutter = self.gutter && self.pivot.is_none();
+        let gutter_width = (self.sheet_origin.0 + self.sheet_data.height())
+            .to_string()
+            .len();
+        let header_height = if gutter { 2 } else { 1 };
+
+        // Calculate visible viewport
+        let table_height = area.height.saturating_sub(2 + header_height) as usize; // Account for borders and header
+        // Lines between rows take a screen line after the header and after each row (the
+        // last row's can be cut off)
+        let table_height = if self.row_lines {
+            table_height.saturating_sub(1).div_ceil(2)
+        } else {
+            table_height
+        };
+        let mut viewport_width = area.width.saturating_sub(2) as usize; // Account for borders
+        if gutter {
+            viewport_width = viewport_width.saturating_sub(gutter_width + 1);
+        }
+
+        // Update scroll to keep cursor visible
+        self.reveal_cursor_row();
+        self.reveal_cursor_col();
+        self.update_scroll(table_height);
+        self.update_horizontal_scroll(viewport_width);
+        self.table_viewport = (
+            viewport_width,
+            table_height.saturating_sub(self.frozen_row_count(table_height)),
+        );
+
+        let visible_start = self.scroll_offset;
+        let frozen_rows = self.frozen_row_count(table_height);
+        let (sticky_cols, scrolling_cols) = self.column_layout(viewport_width);
+        self.refresh_frozen_row_cache(frozen_rows);
+
+        // Calculate visible columns: pinned and frozen ones first, then the scrolled ones
+        // that fit whole (a partial column would make the layout squeeze the others)
+        let mut visible_cols: Vec<usize> = Vec::new();
+        let mut total_width = 0;
+        let scrolled = scrolling_cols.iter().skip(self.horizontal_scroll_offset);
+        for &i in sticky_cols.iter().chain(scrolled) {
+            total_width += self.column_widths[i] + 1; // +1 for separator
+            if total_width > viewport_width && visible_cols.len() > sticky_cols.len() {
+                break;
+            }
+            visible_cols.push(i);
+        }
+
+        // Clone headers to avoid borrow issues
+        let headers = self.sheet_data.headers().to_vec();
+        let selection = self.selection();
+
+        // Get theme colors
+        let colors = self.theme().colors.clone();
+
+        // Build table rows with highlighting
+        let mut header_cells: Vec<Cell> = visible_cols
+            .iter()
+            .filter_map(|&col_idx| headers.get(col_idx).map(|h| (col_idx, h)))
+            .map(|(col_idx, h)| {
+                let mut style = Style::default()
+                    .fg(colors.header_fg)
+                    .add_modifier(Modifier::BOLD);
+
+                if let Some(bg) = colors.header_bg {
+                    style = style.bg(bg);
+                }
+
+                // Highlight current column header
+                if col_idx == self.cursor_col {
+                    style = style.fg(colors.current_col_fg);
+                }
+
+                if gutter {
+                    let letter = xleak::workbook::column_letter(self.sheet_origin.1 + col_idx);
+                    let letter = ratatui::text::Line::from(letter)
+                        .style(Style::default().remove_modifier(Modifier::BOLD));
+                    return Cell::from(ratatui::text::Text::from(vec![letter, h.as_str().into()]))
+                        .style(style);
+                }
+                Cell::from(h.as_str()).style(style)
+            })
+            .collect();
+
+        let gutter_style = Style::default().fg(colors.border_fg);
+        if gutter {
+            // A header row is the row above the first data row; CSV-like sheets without
+            // one start at row 1
+            let header_row = match self.sheet_origin.0 {
+                0 => String::new(),
+                row => row.to_string(),
+            };
+            let lines = vec!["".into(), format!("{header_row:>gutter_width$}").into()];
+            header_cells.insert(
+                0,
+                Cell::from(ratatui::text::Text::from(lines)).style(gutter_style),
+            );
+        }
+
+        let margin = u16::from(self.row_lines);
+        let header = Row::new(header_cells)
+            .height(header_height)
+            .bottom_margin(margin);
+
+        // Get visible rows from data source (handles lazy loading if needed),
+        // as (data row, cells); a filter picks its rows one at a time
+        let scroll_height = table_height.saturating_sub(frozen_rows);
+        // Formulas mode: the formulas of the rows on screen, by data row
+        let formula_rows: HashMap<usize, Vec<Option<String>>> = if self.show_formulas {
+            let scrolled: Vec<usize> = match &self.row_filter {
+                Some(filter) => filter
+                    .rows
+                    .iter()
+                    .skip(visible_start)
+                    .take(scroll_height)
+                    .copied()
+                    .collect(),
+                None => (visible_start..visible_start + scroll_height)
+                    .take_while(|&row| row < self.sheet_data.height())
+                    .collect(),
+            };
+            self.frozen_row_cache
+                .iter()
+                .map(|(row, _)| *row)
+                .chain(scrolled)
+                .filter_map(|row| {
+                    let (_rows, formulas) = self.sheet_data.get_rows(row, 1);
+                    Some((row, formulas.first()?.clone()))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        let filtered_rows: Vec<(usize, Vec<CellValue>)>;
+        let scrolled_rows: Vec<(usize, &Vec<CellValue>)> = match &self.row_filter {
+            Some(filter) => {
+                filtered_rows = filter
+                    .rows
+                    .iter()
+                    .skip(visible_start)
+                    .take(scroll_height)
+                    .map(|&row| {
+                        let (rows, _formulas) = self.sheet_data.get_rows(row, 1);
+                        (row, rows.first().cloned().unwrap_or_default())
+                    })
+                    .collect();
+                filtered_rows
+                    .iter()
+                    .map(|(row, cells)| (*row, cells))
+                    .collect()
+            }
+            None => {
+                let (rows, _formulas) = self.sheet_data.get_rows(visible_start, scroll_height);
+                rows.iter()
+                    .enumerate()
+                    .map(|(i, cells)| (visible_start + i, cells))
+                    .collect()
+            }
+        };
+        // Frozen rows stay on top, followed by the scrolled ones, as (display position, data row, cells)
+        let visible_rows: Vec<(usize, usize, &Vec<CellValue>)> = self
+            .frozen_row_cache
+            .iter()
+            .enumerate()
+            .map(|(position, (row, cells))| (position, *row, cells))
+            .chain(
+                scrolled_rows
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (row, cells))| (visible_start + i, row, cells)),
+            )
+            .collect();
+        let sheet_edits = self.edits.get(&self.sheet_names[self.current_sheet_index]);
+
+        let data_rows: Vec<Row> = visible_rows
+            .iter()
+            .map(|&(display_idx, row_idx, row)| {
+                let mut cells: Vec<Cell> = visible_cols
+                    .iter()
+                    .filter_map(|&col_idx| row.get(col_idx).map(|cell| (col_idx, cell)))
+                    .map(|(col_idx, cell)| {
+                        // Start with cell type color; formulas mode shows formulas in
+                        // green, as --formulas does
+                        let formula = formula_rows
+                            .get(&row_idx)
+                            .and_then(|formulas| formulas.get(col_idx))
+                            .and_then(|formula| formula.as_ref());
+                        let mut style = Style::default().fg(match formula {
+                            Some(_) => Color::Green,
+                            None => colors.cell_color(cell),
+                        });
+
+                        // Add alternating row background (only if not the current row)
+                        let is_alternating_row = display_idx % 2 == 1;
+                        if self.striped_rows
+                            && is_alternating_row
+                            && let Some(alt_bg) = colors.alternating_row_bg
+                        {
+                            style = style.bg(alt_bg);
+                        }
+                        // The workbook's cell colors and conditional formatting, then the
+                        // heatmap toggle
+                        let pos = (
+                            (self.sheet_origin.0 + row_idx) as u32,
+                            (self.sheet_origin.1 + col_idx) as u32,
+                        );
+                        if let Some(look) = self.cell_styles.get(&pos) {
+                            style = style.patch(excel_style(look));
+                        }
+                        let format = self.conditional.get(&pos);
+                        if let Some(format) = format {
+                            style = style.patch(excel_style(&format.style));
+                        }
+                        if let Some(&range) = self.heat_columns.get(&col_idx)
+                            && let Some(heat) = colors.heat_style(cell, range)
+                        {
+                            style = style.patch(heat);
+                        }
+
+                        // Check if this cell is a search match
+                        let is_search_match = self.search_matches.contains(&(row_idx, col_idx));
+                        let is_current_match = self
+                            .current_match_index
+                            .and_then(|idx| self.search_matches.get(idx))
+                            .map(|&pos| pos == (row_idx, col_idx))
+                            .unwrap_or(false);
+
+                        // Highlight current search match (highest priority)
+                        if is_current_match {
+                            style = style
+                                .bg(colors.current_search_bg)
+                                .fg(colors.current_search_fg)
+                                .add_modifier(Modifier::BOLD);
+                        }
+                        // Highlight current cell
+                        else if row_idx == self.cursor_row && col_idx == self.cursor_col {
+                            style = style
+                                .bg(colors.current_cell_bg)
+                                .fg(colors.current_cell_fg)
+                                .add_modifier(Modifier::BOLD);
+                        }
+                        // Highlight other search matches
+                        else if is_search_match {
+                            style = style.bg(colors.search_match_bg).fg(colors.search_match_fg);
+                        }
+                        // Highlight current row
+                        else if row_idx == self.cursor_row {
+                            style = style.bg(colors.current_row_bg);
+                        }
+                        // Highlight current column
+                        else if col_idx == self.cursor_col {
+                            style = style.fg(colors.current_col_fg);
+                        }
+                        // Underline linked cells (fields only: the rows still borrow sheet_data)
+                        if self.hyperlinks.contains_key(&pos) {
+                            style = style.add_modifier(Modifier::UNDERLINED);
+                        }
+                        // Visual mode: selected cells are shown reversed
+                        if let Some(((first, last), cols)) = &selection
+                            && (*first..=*last).contains(&display_idx)
+                            && cols.contains(&col_idx)
+                        {
+                            style = style.add_modifier(Modifier::REVERSED);
+                        }
+                        // Diff mode: changed cells get a background by kind of change
+                        let is_cursor = row_idx == self.cursor_row && col_idx == self.cursor_col;
+                        if !is_cursor
+                            && sheet_edits.is_some_and(|edits| {
+                                edits.contains_key(&(pos.0 as usize, pos.1 as usize))
+                            })
+                        {
+                            style = style.fg(Color::Magenta).add_modifier(Modifier::ITALIC);
+                        }
+                        if !is_cursor && let Some(change) = self.sheet_changes.get(&pos) {
+                            let bg = match change.kind {
+                                ChangeKind::Changed => Color::Yellow,
+                                ChangeKind::Added => Color::Green,
+                                ChangeKind::Removed => Color::Red,
+                            };
+                            style = style.bg(bg).fg(Color::Black);
+                        }
+                        let text = match (formula, self.bar_columns.get(&col_idx)) {
+                            (Some(formula), _) => format!("={formula}"),
+                            (None, Some(&scale)) => {
+                                format!(
+                                    "{} {}",
+                                    display::bar(cell, scale, display::BAR_WIDTH),
+                                    cell.to_display_string(&self.locale)
+                                )
+                            }
+                            (None, None) => {
+                                cell_text(&self.locale, &self.currency_cells, pos, cell)
+                            }
+                        };
+                        // Text wider than its column ends in an ellipsis
+                        let width = self.column_widths[col_idx];
+                        let text = display::truncate_to_width(&text, width, "…");
+                        // A data bar fills the start of the cell, as in Excel
+                        if !is_cursor && let Some((fraction, color)) = format.and_then(|f| f.bar) {
+                            let text = display::pad_to_width(&text, width);
+                            let filled = display::truncate_to_width(
+                                &text,
+                                (fraction * width as f64).round() as usize,
+                                "",
+                            );
+                            let (r, g, b) = display::contrast_text(color);
+                            let line = ratatui::text::Line::from(vec![
+                                ratatui::text::Span::styled(
+                                    text[..filled.len()].to_string(),
+                                    Style::default()
+                                        .bg(Color::Rgb(color.0, color.1, color.2))
+                                        .fg(Color::Rgb(r, g, b)),
+                                ),
+                                ratatui::text::Span::raw(text[filled.len()..].to_string()),
+                            ]);
+                            return Cell::from(line).style(style);
+                        }
+                        // Cells centered or right-aligned in Excel keep that; formulas stay left
+                        let alignment = match (formula, self.cell_alignments.get(&pos)) {
+                            (None, Some(HorizontalAlign::Center)) => Alignment::Center,
+                            (None, Some(HorizontalAlign::Right)) => Alignment::Right,
+                            _ => Alignment::Left,
+                        };
+                        Cell::from(ratatui::text::Line::from(text).alignment(alignment))
+                            .style(style)
+                    })
+                    .collect();
+                if gutter {
+                    let number = self.sheet_origin.0 + row_idx + 1;
+                    let mut style = gutter_style;
+                    if row_idx == self.cursor_row {
+                        style = style.fg(colors.current_col_fg).add_modifier(Modifier::BOLD);
+                    }
+                    cells.insert(
+                        0,
+                        Cell::from(format!("{number:>gutter_width$}")).style(style),
+                    );
+                }
+                Row::new(cells).height(1).bottom_margin(margin)
+            })
+            .collect();
+        let shown_rows = data_rows.len();
+
+        // Fixed widths based on content
+        let mut col_widths: Vec<Constraint> = visible_cols
+            .iter()
+            .map(|&col| Constraint::Length(self.column_widths[col] as u16))
+            .collect();
+        if gutter {
+            col_widths.insert(0, Constraint::Length(gutter_width as u16));
+        }
+
+        let mut table_title = if self.sheet_names.len() > 1 {
+            format!(
+                " {} (Sheet {}/{}) {}{}{}{}{}",
+                self.current_sheet_name(),
+                self.current_sheet_index + 1,
+                self.sheet_names.len(),
+                self.pivot_annotation(),
+                self.hidden_annotation(),
+                self.diff_annotation(),
+                self.edit_annotation(),
+                self.filter_annotation()
+            )
+        } else {
+            format!(
+                " {} {}{}{}{}{}",
+                self.current_sheet_name(),
+                self.pivot_annotation(),
+                self.hidden_annotation(),
+                self.diff_annotation(),
+                self.edit_annotation(),
+                self.filter_annotation()
+            )
+        };
+        if self.show_formulas {
+            table_title.push_str("[formulas] ");
+        }
+        if !self.workbooks.is_empty() {
+            table_title.insert_str(0, &format!(" {} ›", self.workbook_name));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if focused {
+                colors.border_fg
+            } else {
+                Color::DarkGray
+            }))
+            .title(table_title);
+        let inner = block.inner(area);
+        let table = Table::new(data_rows, col_widths.clone())
+            .header(header)
+            .block(block);
+
+        frame.render_widget(table, area);
+        if self.row_lines || self.column_lines {
+            grid_lines(
+                frame.buffer_mut(),
+                inner,
+                &col_widths,
+                header_height,
+                shown_rows,
+                self.row_lines,
+                self.column_lines,
+            );
+        }
+
+        // Format sheet dimensions with scroll indicator
+        let row_count = match &self.row_filter {
+            Some(filter) => format!(
+                "showing {} of {} rows",
+                filter.rows.len(),
+                self.sheet_data.height()
+            ),
+            None => format!("{} rows", self.sheet_data.height()),
+        };
+        // Wider than the screen: which columns are in view, and where the cursor is
+        let sheet_width = self.sheet_data.width();
+        if visible_cols.len() < sticky_cols.len() + scrolling_cols.len() {
+            let first_col = scrolling_cols
+                .get(self.horizontal_scroll_offset)
+                .and_then(|&col| headers.get(col))
+                .map(|s| s.as_str())
+                .unwrap_or("?");
+            let last_col = visible_cols
+                .last()
+                .and_then(|&col| headers.get(col))
+                .map(|s| s.as_str())
+                .unwrap_or("?");
+            format!(
+                "{} × {} columns (showing {}-{}, col {}/{})",
+                row_count,
+                sheet_width,
+                first_col,
+                last_col,
+                self.cursor_col + 1,
+                sheet_width
+            )
+        } else {
+            format!("{} × {} columns", row_count, sheet_width)
+        }
+    }
+
+    /// Shortcut sections built from the active keybindings (profile + custom overrides)
+    fn keybinding_help_lines(&self) -> Vec<ratatui::text::Line<'static>> {
+        use ratatui::text::{Line, Span};
+
+        let mut lines = Vec::new();
+        for (heading, entries) in crate::keymap::help_sections(&self.config) {
+            lines.push(Line::from(Span::styled(
+                heading,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            for (label, description) in entries {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {label:<17}"), Style::default().fg(Color::Green)),
+                    Span::raw(description),
+                ]));
+            }
+            lines.push(Line::from(""));
+        }
+        lines
+    }
+
+    fn render_help(&self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
+        // Build help content with rich formatting
+        let mut help_lines = vec![
+            Line::from(vec![
                 Span::styled(
                     "xleak",
                     Style::default()
@@ -1551,210 +6085,640 @@ impl TuiState {
                 Span::raw(" - Interactive Excel Viewer"),
             ]),
             Line::from(""),
+        ];
+        help_lines.extend(self.keybinding_help_lines());
+        help_lines.extend(vec![
             Line::from(Span::styled(
-                "NAVIGATION",
+                "VISUAL CUES",
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             )),
             Line::from(vec![
-                Span::styled("  ↑ ↓ ← →          ", Style::default().fg(Color::Green)),
-                Span::raw("Move cursor one cell"),
-            ]),
-            Line::from(vec![
-                Span::styled("  Page Up/Down     ", Style::default().fg(Color::Green)),
-                Span::raw("Scroll 10 rows"),
+                Span::styled(
+                    "  Blue background  ",
+                    Style::default().bg(Color::Blue).fg(Color::White),
+                ),
+                Span::raw("  Current cell (selected)"),
             ]),
             Line::from(vec![
-                Span::styled("  Home             ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to first column (start of row)"),
+                Span::styled("  Dark gray bg     ", Style::default().bg(Color::DarkGray)),
+                Span::raw("  Current row highlight"),
             ]),
             Line::from(vec![
-                Span::styled("  End              ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to last column (end of row)"),
+                Span::styled("  Cyan text        ", Style::default().fg(Color::Cyan)),
+                Span::raw("  Current column highlight"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+Home        ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to first row (top of sheet)"),
+                Span::styled(
+                    "  Yellow bold      ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  Column headers"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+End         ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to last row (bottom of sheet)"),
+                Span::styled(
+                    "  Yellow bg        ",
+                    Style::default().bg(Color::Yellow).fg(Color::Black),
+                ),
+                Span::raw("  Current search match"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+G           ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to row/cell (e.g., 100, A5, or 10,3)"),
+                Span::styled(
+                    "  Light yellow bg  ",
+                    Style::default().bg(Color::LightYellow).fg(Color::Black),
+                ),
+                Span::raw("  Other search matches"),
             ]),
             Line::from(""),
-            Line::from(Span::styled(
-                "SEARCH",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(vec![
-                Span::styled("  /                ", Style::default().fg(Color::Green)),
-                Span::raw("Start search (type query, Enter to confirm)"),
-            ]),
-            Line::from(vec![
-                Span::styled("  n                ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to next search match"),
-            ]),
-            Line::from(vec![
-                Span::styled("  N (Shift+n)      ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to previous search match"),
-            ]),
-            Line::from(vec![
-                Span::styled("  Esc              ", Style::default().fg(Color::Green)),
-                Span::raw("Clear search results"),
-            ]),
+            Line::from("  Cell colors vary by type and current theme:"),
+            Line::from("  • Numbers, strings, dates, booleans, errors each have distinct colors"),
+            Line::from("  • Alternating row backgrounds improve readability"),
+            Line::from("  • Press 't' to cycle through the built-in and custom themes"),
             Line::from(""),
             Line::from(Span::styled(
-                "CLIPBOARD",
+                "STATUS BAR INFO",
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             )),
-            Line::from(vec![
-                Span::styled("  c                ", Style::default().fg(Color::Green)),
-                Span::raw("Copy current cell value"),
-            ]),
-            Line::from(vec![
-                Span::styled("  C (Shift+c)      ", Style::default().fg(Color::Green)),
-                Span::raw("Copy entire current row (tab-separated)"),
-            ]),
+            Line::from("  Cell address (e.g., B7) shown in bottom left"),
+            Line::from("  Current cell value displayed in status bar title"),
+            Line::from("  Sheet dimensions (rows × columns) shown"),
+            Line::from("  Match counter shown when searching (e.g., Match 3/12)"),
             Line::from(""),
             Line::from(Span::styled(
-                "SHEET NAVIGATION",
+                "CONFIGURATION",
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             )),
-            Line::from(vec![
-                Span::styled("  Tab              ", Style::default().fg(Color::Green)),
-                Span::raw("Switch to next sheet"),
-            ]),
-            Line::from(vec![
-                Span::styled("  Shift+Tab        ", Style::default().fg(Color::Green)),
-                Span::raw("Switch to previous sheet"),
-            ]),
+            Line::from("  Customize keybindings and theme in config file:"),
+            Line::from("  ~/.config/xleak/config.toml"),
             Line::from(""),
-            Line::from(Span::styled(
-                "GENERAL",
+            Line::from("  Supports VIM-style navigation (hjkl, gg, G, 0, $)"),
+            Line::from("  Custom keybindings per action"),
+            Line::from("  Default theme selection"),
+            Line::from(""),
+            Line::from("  See config.toml.example for all options"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Press any key to close",
                 Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(vec![
-                Span::styled("  Enter            ", Style::default().fg(Color::Green)),
-                Span::raw("Show cell details (type, formula, value)"),
-            ]),
-            Line::from(vec![
-                Span::styled("  t                ", Style::default().fg(Color::Green)),
-                Span::raw("Cycle through color themes"),
-            ]),
-            Line::from(vec![
-                Span::styled("  ?                ", Style::default().fg(Color::Green)),
-                Span::raw("Toggle this help screen"),
-            ]),
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::ITALIC),
+            )]),
+        ]);
+
+        // Calculate popup size (centered, 70% width, auto height)
+        let area = frame.area();
+        let popup_width = (area.width as f32 * 0.7).min(80.0) as u16;
+        let popup_height =
+            (help_lines.len() + 4).min(area.height.saturating_sub(2) as usize) as u16;
+
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the area behind the popup
+        frame.render_widget(Clear, popup_area);
+
+        // Create help content with styled text
+        let help_paragraph = Paragraph::new(help_lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            "Help",
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(" - Keyboard Shortcuts "),
+                    ])
+                    .title_alignment(Alignment::Center),
+            )
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(help_paragraph, popup_area);
+    }
+
+    fn render_cell_detail(&mut self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
+        // Get current cell info
+        let (cell_value, cell_formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
+
+        let cell_addr = self.current_cell_address();
+        let header = self
+            .sheet_data
+            .headers()
+            .get(self.cursor_col)
+            .map(|s| s.as_str())
+            .unwrap_or("");
+
+        // Build detail lines
+        let mut detail_lines = vec![
             Line::from(vec![
-                Span::styled("  q                ", Style::default().fg(Color::Green)),
-                Span::raw("Quit xleak"),
+                Span::styled(
+                    "Cell: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(cell_addr.clone(), Style::default().fg(Color::Cyan)),
             ]),
             Line::from(vec![
-                Span::styled("  Esc              ", Style::default().fg(Color::Green)),
-                Span::raw("Quit xleak (or clear search)"),
+                Span::styled(
+                    "Column: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(header),
             ]),
-            Line::from(""),
-            Line::from(Span::styled(
-                "VISUAL CUES",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(vec![
+        ];
+
+        if let Some(link) = self.hyperlink_at(self.cursor_row, self.cursor_col) {
+            let mut target = link.target.clone();
+            if let Some(tooltip) = &link.tooltip {
+                target.push_str(&format!(" ({tooltip})"));
+            }
+            detail_lines.push(Line::from(vec![
+                Span::styled(
+                    "Link: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(target, Style::default().fg(Color::Cyan)),
+            ]));
+        }
+
+        if let Some(change) = self.change_at_cursor() {
+            let shown = |value: &str| {
+                if value.is_empty() {
+                    "(empty)".to_string()
+                } else {
+                    value.to_string()
+                }
+            };
+            let old_name = self.diff.as_ref().map_or("", |diff| diff.old_name.as_str());
+            for (label, value) in [
+                ("Change: ", change.kind.label().to_string()),
+                ("Old: ", format!("{} ({old_name})", shown(&change.old))),
+                ("New: ", shown(&change.new)),
+            ] {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        label,
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(value),
+                ]));
+            }
+        }
+
+        if let Some(status) = self.cell_protection_status() {
+            detail_lines.push(Line::from(vec![
                 Span::styled(
-                    "  Blue background  ",
-                    Style::default().bg(Color::Blue).fg(Color::White),
+                    "Protection: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw("  Current cell (selected)"),
-            ]),
-            Line::from(vec![
-                Span::styled("  Dark gray bg     ", Style::default().bg(Color::DarkGray)),
-                Span::raw("  Current row highlight"),
-            ]),
-            Line::from(vec![
-                Span::styled("  Cyan text        ", Style::default().fg(Color::Cyan)),
-                Span::raw("  Current column highlight"),
-            ]),
-            Line::from(vec![
+                Span::raw(status),
+            ]));
+        }
+        detail_lines.push(Line::from(""));
+
+        // Show formula first if it exists (more important than type)
+        if let Some(ref formula) = cell_formula {
+            detail_lines.push(Line::from(vec![
                 Span::styled(
-                    "  Yellow bold      ",
+                    "Formula: ",
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw("  Column headers"),
-            ]),
-            Line::from(vec![
                 Span::styled(
-                    "  Yellow bg        ",
-                    Style::default().bg(Color::Yellow).fg(Color::Black),
+                    formula.clone(),
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw("  Current search match"),
-            ]),
-            Line::from(vec![
+            ]));
+            if let Some(evaluated) = &self.cell_detail_evaluated {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Evaluated: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(evaluated.to_raw_string()),
+                ]));
+            }
+            detail_lines.push(Line::from(""));
+        }
+
+        if let Some(cell) = cell_value {
+            // Cell type
+            let cell_type = match cell {
+                CellValue::Empty => "Empty",
+                CellValue::String(_) => "String",
+                CellValue::Int(_) => "Integer",
+                CellValue::Float(_) => "Float",
+                CellValue::Bool(_) => "Boolean",
+                CellValue::Error(_) => "Error",
+                CellValue::DateTime(_) => "DateTime",
+                CellValue::Time(_) => "Time",
+                CellValue::Duration(_) => "Duration",
+            };
+
+            detail_lines.push(Line::from(vec![
                 Span::styled(
-                    "  Light yellow bg  ",
-                    Style::default().bg(Color::LightYellow).fg(Color::Black),
+                    "Type: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw("  Other search matches"),
-            ]),
-            Line::from(""),
-            Line::from("  Cell colors vary by type and current theme:"),
-            Line::from("  • Numbers, strings, dates, booleans, errors each have distinct colors"),
-            Line::from("  • Alternating row backgrounds improve readability"),
-            Line::from("  • Press 't' to cycle through 6 built-in themes"),
-            Line::from(""),
-            Line::from(Span::styled(
-                "STATUS BAR INFO",
+                Span::styled(cell_type, Style::default().fg(Color::Green)),
+            ]));
+
+            // Raw value (unformatted)
+            let raw_value = cell.to_raw_string();
+
+            // If cell is empty but has a formula, add explanation
+            if raw_value.is_empty() && cell_formula.is_some() {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Value: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        "(empty - formula not evaluated, try --evaluate)",
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::ITALIC),
+                    ),
+                ]));
+            } else {
+                let value_display = if raw_value.is_empty() {
+                    "(empty)".to_string()
+                } else {
+                    raw_value.clone()
+                };
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Value: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(value_display),
+                ]));
+            }
+
+            // Formatted display value
+            let display_value = cell.to_display_string(&self.locale);
+            if display_value != raw_value {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Display Value: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(display_value.clone()),
+                ]));
+            }
+
+            detail_lines.push(Line::from(""));
+            detail_lines.push(Line::from(Span::styled(
+                "Full Content:",
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
-            )),
-            Line::from("  Cell address (e.g., B7) shown in bottom left"),
-            Line::from("  Current cell value displayed in status bar title"),
-            Line::from("  Sheet dimensions (rows × columns) shown"),
-            Line::from("  Match counter shown when searching (e.g., Match 3/12)"),
-            Line::from(""),
-            Line::from(Span::styled(
-                "CONFIGURATION",
+            )));
+            detail_lines.push(Line::from(""));
+
+            // Split content by lines for multi-line display
+            for line in raw_value.lines() {
+                detail_lines.push(Line::from(Span::raw(line.to_string())));
+            }
+        } else {
+            // No cell value - might be a formula cell or truly empty
+            if cell_formula.is_some() {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Value: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        "(formula not evaluated by Excel reader)",
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::ITALIC),
+                    ),
+                ]));
+            } else {
+                detail_lines.push(Line::from(Span::styled(
+                    "No cell data",
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                )));
+            }
+        }
+
+        detail_lines.push(Line::from(""));
+        detail_lines.push(Line::from(vec![Span::styled(
+            "↑↓ to scroll | Any other key to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        )]));
+
+        // Calculate popup size (60% width, auto height)
+        let area = frame.area();
+        let popup_width = (area.width as f32 * 0.6).min(80.0) as u16;
+        let popup_height =
+            (detail_lines.len() + 4).min(area.height.saturating_sub(2) as usize) as u16;
+
+        // Calculate content area (inside borders)
+        let content_height = popup_height.saturating_sub(2) as usize; // Subtract borders
+        let total_lines = detail_lines.len();
+
+        // Clamp scroll offset to valid range
+        let max_scroll = total_lines.saturating_sub(content_height);
+        let scroll_offset = self.cell_detail_scroll.min(max_scroll);
+
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the area behind the popup
+        frame.render_widget(Clear, popup_area);
+
+        // Build title with scroll indicator
+        let mut title_spans = vec![
+            Span::raw(" "),
+            Span::styled(
+                "Cell Details",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - "),
+            Span::styled(cell_addr.clone(), Style::default().fg(Color::Cyan)),
+        ];
+
+        // Add scroll indicator if content is scrollable
+        if total_lines > content_height {
+            let scroll_info = format!(" [{}/{}]", scroll_offset + 1, max_scroll + 1);
+            title_spans.push(Span::styled(
+                scroll_info,
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            ));
+        }
+
+        title_spans.push(Span::raw(" "));
+
+        // Create detail content with scroll
+        let detail_paragraph = Paragraph::new(detail_lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .scroll((scroll_offset as u16, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title(title_spans)
+                    .title_alignment(Alignment::Center),
+            )
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(detail_paragraph, popup_area);
+    }
+
+    /// The current row as one "header  value" line per column, values wrapped to the popup
+    fn render_record(&mut self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
+        let area = frame.area();
+        let popup_width = (area.width as f32 * 0.8).min(110.0) as u16;
+        let popup_height = area.height.saturating_sub(2);
+        let inner_width = popup_width.saturating_sub(2) as usize;
+
+        let headers = self.sheet_data.headers().to_vec();
+        let (rows, _formulas) = self.sheet_data.get_rows(self.cursor_row, 1);
+        let cells = rows.first().cloned().unwrap_or_default();
+
+        let label_width = headers
+            .iter()
+            .map(|h| display::text_width(h))
+            .max()
+            .unwrap_or(0)
+            .clamp(1, 24);
+        let value_width = inner_width.saturating_sub(label_width + 3).max(10);
+        let mut lines = Vec::new();
+        for (col, header) in headers.iter().enumerate() {
+            let value = cells
+                .get(col)
+                .map(|c| c.to_display_string(&self.locale))
+                .unwrap_or_default();
+            let mut label = display::truncate_to_width(header, label_width, "…");
+            if header.is_empty() {
+                label = self.col_to_letter(col);
+            }
+            let label_style = if col == self.cursor_col {
                 Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Yellow)
+            };
+
+            // Wrap the value by hand so continuation lines stay in the value column
+            let mut pieces = Vec::new();
+            for line in value.split('\n') {
+                if line.is_empty() {
+                    pieces.push(String::new());
+                }
+                let mut rest = line;
+                while !rest.is_empty() {
+                    let piece = display::truncate_to_width(rest, value_width, "");
+                    rest = &rest[piece.len()..];
+                    pieces.push(piece);
+                }
+            }
+            for (i, piece) in pieces.into_iter().enumerate() {
+                let label = if i == 0 { label.as_str() } else { "" };
+                lines.push(Line::from(vec![
+                    Span::styled(display::pad_to_width(label, label_width), label_style),
+                    Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
+                    Span::raw(piece),
+                ]));
+            }
+        }
+
+        let content_height = popup_height.saturating_sub(3) as usize; // Borders and footer
+        let max_scroll = lines.len().saturating_sub(content_height);
+        self.record_scroll = self.record_scroll.min(max_scroll);
+        lines.push(Line::from(Span::styled(
+            "↑↓ scroll | ←→ previous/next row | Any other key to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        )));
+        // Keep the footer visible below the scrolled fields
+        let footer = lines.pop();
+        let mut visible: Vec<Line> = lines
+            .into_iter()
+            .skip(self.record_scroll)
+            .take(content_height)
+            .collect();
+        visible.extend(footer);
+
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        frame.render_widget(Clear, popup_area);
+
+        let title = format!(
+            " Record: row {} ({}/{}) ",
+            self.cursor_row + 1,
+            self.display_position(self.cursor_row) + 1,
+            self.visible_row_count()
+        );
+        let paragraph = Paragraph::new(visible)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title(title)
+                    .title_alignment(Alignment::Center),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    /// Spinner popup while a sheet loads on its worker thread
+    fn render_loading(&self, frame: &mut Frame, loader: &SheetLoader) {
+        use ratatui::text::{Line, Span};
+
+        let (action, cancel) = match loader.kind {
+            LoadKind::Initial => ("Loading", "Esc or quit key to quit"),
+            LoadKind::Switch => ("Loading", "Esc to stay on this sheet"),
+            LoadKind::Open => ("Loading", "Esc to stop"),
+            LoadKind::Reload => ("Reloading", "Esc to keep the old data"),
+        };
+        let lines = vec![
+            Line::from(format!(
+                "{} {action} '{}'... {:.1}s",
+                loader.spinner(),
+                loader.name,
+                loader.started_at.elapsed().as_secs_f64()
             )),
-            Line::from("  Customize keybindings and theme in config file:"),
-            Line::from("  ~/.config/xleak/config.toml"),
-            Line::from(""),
-            Line::from("  Supports VIM-style navigation (hjkl, gg, G, 0, $)"),
-            Line::from("  Custom keybindings per action"),
-            Line::from("  Default theme selection"),
-            Line::from(""),
-            Line::from("  See config.toml.example for all options"),
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "Press any key to close",
+            Line::from(Span::styled(
+                cancel,
                 Style::default()
                     .fg(Color::Magenta)
                     .add_modifier(Modifier::ITALIC),
-            )]),
+            )),
         ];
 
-        // Calculate popup size (centered, 70% width, auto height)
         let area = frame.area();
-        let popup_width = (area.width as f32 * 0.7).min(80.0) as u16;
-        let popup_height =
-            (help_lines.len() + 4).min(area.height.saturating_sub(2) as usize) as u16;
+        let popup_width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4)
+            .min(area.width as usize) as u16;
+        let popup_height = 4.min(area.height);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        frame.render_widget(Clear, popup_area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn render_pivots(&self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
+        let mut lines: Vec<Line> = self
+            .pivot_lines
+            .iter()
+            .map(|l| {
+                if l.starts_with(' ') || l.is_empty() {
+                    Line::from(l.as_str())
+                } else {
+                    // Pivot names are unindented
+                    Line::from(Span::styled(
+                        l.as_str(),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                }
+            })
+            .collect();
+        lines.push(Line::from(vec![Span::styled(
+            "↑↓ to scroll | Any other key to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        )]));
+
+        let area = frame.area();
+        let popup_width = (area.width as f32 * 0.7).min(90.0) as u16;
+        let popup_height = (lines.len() + 2).min(area.height.saturating_sub(2) as usize) as u16;
+        let max_scroll = lines
+            .len()
+            .saturating_sub(popup_height.saturating_sub(2) as usize);
+        let scroll_offset = self.pivot_scroll.min(max_scroll);
 
         let popup_area = Rect {
             x: (area.width.saturating_sub(popup_width)) / 2,
@@ -1763,12 +6727,11 @@ impl TuiState {
             height: popup_height,
         };
 
-        // Clear the area behind the popup
         frame.render_widget(Clear, popup_area);
 
-        // Create help content with styled text
-        let help_paragraph = Paragraph::new(help_lines)
+        let paragraph = Paragraph::new(lines)
             .style(Style::default().fg(Color::White).bg(Color::Black))
+            .scroll((scroll_offset as u16, 0))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
@@ -1780,210 +6743,370 @@ impl TuiState {
                     .title(vec![
                         Span::raw(" "),
                         Span::styled(
-                            "Help",
+                            "Pivot Tables",
                             Style::default()
                                 .fg(Color::Yellow)
                                 .add_modifier(Modifier::BOLD),
                         ),
-                        Span::raw(" - Keyboard Shortcuts "),
+                        Span::raw(" "),
                     ])
                     .title_alignment(Alignment::Center),
             )
             .wrap(Wrap { trim: false });
 
-        frame.render_widget(help_paragraph, popup_area);
+        frame.render_widget(paragraph, popup_area);
     }
 
-    fn render_cell_detail(&mut self, frame: &mut Frame) {
+    /// Fuzzy finder: the query, then the best matches with the matched letters highlighted
+    fn render_finder(&self, frame: &mut Frame, finder: &Finder) {
         use ratatui::text::{Line, Span};
 
-        // Get current cell info
-        let (cell_value, cell_formula) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
-
-        let cell_addr = self.current_cell_address();
-        let header = self
-            .sheet_data
-            .headers()
-            .get(self.cursor_col)
-            .map(|s| s.as_str())
-            .unwrap_or("");
-
-        // Build detail lines
-        let mut detail_lines = vec![
-            Line::from(vec![
-                Span::styled(
-                    "Cell: ",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(cell_addr.clone(), Style::default().fg(Color::Cyan)),
-            ]),
+        let mut lines = vec![
             Line::from(vec![
-                Span::styled(
-                    "Column: ",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(header),
+                Span::styled(" > ", Style::default().fg(Color::Yellow)),
+                Span::raw(finder.query.clone()),
+                Span::styled("█", Style::default().fg(Color::Gray)),
             ]),
             Line::from(""),
         ];
-
-        // Show formula first if it exists (more important than type)
-        if let Some(ref formula) = cell_formula {
-            detail_lines.push(Line::from(vec![
-                Span::styled(
-                    "Formula: ",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    formula.clone(),
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]));
-            detail_lines.push(Line::from(""));
+        for (shown, (index, positions)) in finder.matches.iter().take(Finder::MAX_SHOWN).enumerate()
+        {
+            let item = &finder.items[*index];
+            let mut style = Style::default();
+            if shown == finder.selected {
+                style = style.bg(Color::Blue).add_modifier(Modifier::BOLD);
+            }
+            let mut spans = vec![Span::styled(
+                format!(" {:<7}", item.kind),
+                style.fg(Color::DarkGray),
+            )];
+            for (i, c) in item.label.chars().enumerate() {
+                let style = if positions.contains(&i) {
+                    style.fg(Color::Yellow)
+                } else {
+                    style
+                };
+                spans.push(Span::styled(c.to_string(), style));
+            }
+            spans.push(Span::styled(" ", style));
+            lines.push(Line::from(spans));
         }
+        if finder.matches.is_empty() {
+            lines.push(Line::from(" No matches"));
+        } else if finder.matches.len() > Finder::MAX_SHOWN {
+            lines.push(Line::from(Span::styled(
+                format!(" … {} more", finder.matches.len() - Finder::MAX_SHOWN),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Type to filter | ↑↓ select | Enter to jump | Esc to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        )));
 
-        if let Some(cell) = cell_value {
-            // Cell type
-            let cell_type = match cell {
-                crate::workbook::CellValue::Empty => "Empty",
-                crate::workbook::CellValue::String(_) => "String",
-                crate::workbook::CellValue::Int(_) => "Integer",
-                crate::workbook::CellValue::Float(_) => "Float",
-                crate::workbook::CellValue::Bool(_) => "Boolean",
-                crate::workbook::CellValue::Error(_) => "Error",
-                crate::workbook::CellValue::DateTime(_) => "DateTime",
-            };
-
-            detail_lines.push(Line::from(vec![
-                Span::styled(
-                    "Type: ",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(cell_type, Style::default().fg(Color::Green)),
-            ]));
-
-            // Raw value (unformatted)
-            let raw_value = cell.to_raw_string();
+        let area = frame.area();
+        let popup_width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4)
+            .clamp(40, area.width as usize) as u16;
+        let popup_height = (lines.len() + 2).min(area.height as usize) as u16;
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
 
-            // If cell is empty but has a formula, add explanation
-            if raw_value.is_empty() && cell_formula.is_some() {
-                detail_lines.push(Line::from(vec![
-                    Span::styled(
-                        "Value: ",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(
-                        "(empty - formula not evaluated)",
-                        Style::default()
-                            .fg(Color::DarkGray)
-                            .add_modifier(Modifier::ITALIC),
-                    ),
-                ]));
-            } else {
-                let value_display = if raw_value.is_empty() {
-                    "(empty)".to_string()
-                } else {
-                    raw_value.clone()
-                };
-                detail_lines.push(Line::from(vec![
-                    Span::styled(
-                        "Value: ",
+        frame.render_widget(Clear, popup_area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        " Find sheet, table, name, or column ",
                         Style::default()
                             .fg(Color::Yellow)
                             .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(value_display),
-                ]));
+                    ))
+                    .title_alignment(Alignment::Center),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    /// Chart popup: bars, one per row, or a line across most of the screen
+    fn render_chart(&self, frame: &mut Frame, chart: &ChartPopup) {
+        use ratatui::text::{Line, Span};
+
+        let area = frame.area();
+        let popup_area = Rect {
+            x: area.width / 10,
+            y: area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        frame.render_widget(Clear, popup_area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .title(Span::styled(
+                format!(" {} ({}) ", chart.series.title(), chart.kind.name()),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+        if inner.height < 2 {
+            return;
+        }
+
+        let plot = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        match chart.kind {
+            ChartKind::Bar => {
+                let lines: Vec<Line> = chart::bar_lines(&chart.series, plot.width as usize)
+                    .into_iter()
+                    .skip(chart.scroll)
+                    .map(Line::from)
+                    .collect();
+                frame.render_widget(
+                    Paragraph::new(lines).style(Style::default().fg(Color::Cyan)),
+                    plot,
+                );
+            }
+            ChartKind::Line => chart::draw_line_chart(&chart.series, plot, frame.buffer_mut()),
+        }
+        let hint = Paragraph::new(Span::styled(
+            "Tab bar/line | ←/→ column | ↑/↓ scroll | Esc to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        ))
+        .alignment(Alignment::Center);
+        frame.render_widget(
+            hint,
+            Rect {
+                y: inner.y + inner.height - 1,
+                height: 1,
+                ..inner
+            },
+        );
+    }
+
+    /// Pivot dialog: the sheet's columns, each with what the pivot does with it
+    fn render_pivot_dialog(&self, frame: &mut Frame, dialog: &PivotDialog) {
+        use ratatui::text::{Line, Span};
+
+        let name_width = dialog
+            .columns
+            .iter()
+            .map(|name| display::text_width(name))
+            .max()
+            .unwrap_or(0)
+            .clamp(6, 30);
+        let first = dialog.selected.saturating_sub(PivotDialog::MAX_SHOWN - 1);
+        let mut lines = vec![Line::from("")];
+        for (i, name) in dialog
+            .columns
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(PivotDialog::MAX_SHOWN)
+        {
+            let mut style = Style::default();
+            if i == dialog.selected {
+                style = style.bg(Color::Blue).add_modifier(Modifier::BOLD);
             }
+            let name: String = if name.trim().is_empty() {
+                xleak::workbook::column_letter(self.sheet_origin.1 + i)
+            } else {
+                display::truncate_to_width(name, name_width, "…")
+            };
+            let (role, role_style) = match dialog.roles[i] {
+                PivotRole::Skip => ("", style),
+                PivotRole::Group => ("group by", style.fg(Color::Yellow)),
+                PivotRole::Value(aggregation) => (aggregation.name(), style.fg(Color::Green)),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {}  ", display::pad_to_width(&name, name_width)),
+                    style,
+                ),
+                Span::styled(format!("{role:<8} "), role_style),
+            ]));
+        }
+        if dialog.columns.len() > first + PivotDialog::MAX_SHOWN {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " … {} more",
+                    dialog.columns.len() - first - PivotDialog::MAX_SHOWN
+                ),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(""));
+        for hint in [
+            "r group by | s sum | a avg | c count | m min | x max",
+            "Space to clear | Enter to show | Esc to cancel",
+        ] {
+            lines.push(Line::from(Span::styled(
+                hint,
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+        }
 
-            // Formatted display value
-            let display_value = cell.to_string();
-            if display_value != raw_value {
-                detail_lines.push(Line::from(vec![
-                    Span::styled(
-                        "Display Value: ",
+        let area = frame.area();
+        let popup_width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4)
+            .clamp(40, area.width as usize) as u16;
+        let popup_height = (lines.len() + 2).min(area.height as usize) as u16;
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        frame.render_widget(Clear, popup_area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        " Pivot ",
                         Style::default()
                             .fg(Color::Yellow)
                             .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(display_value.clone()),
-                ]));
-            }
+                    ))
+                    .title_alignment(Alignment::Center),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
 
-            detail_lines.push(Line::from(""));
-            detail_lines.push(Line::from(Span::styled(
-                "Full Content:",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )));
-            detail_lines.push(Line::from(""));
+    /// Workbook switcher: each open file with its sheet count, the shown one marked
+    fn render_workbooks(&self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
 
-            // Split content by lines for multi-line display
-            for line in raw_value.lines() {
-                detail_lines.push(Line::from(Span::raw(line.to_string())));
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, view) in self.workbooks.iter().enumerate() {
+            let (name, sheets, unsaved) = match view {
+                Some(view) => (&view.name, view.sheet_names.len(), view.unsaved_edits),
+                None => (
+                    &self.workbook_name,
+                    self.sheet_names.len(),
+                    self.unsaved_edits,
+                ),
+            };
+            let mut style = Style::default();
+            if index == self.workbooks_selected {
+                style = style.bg(Color::Blue).add_modifier(Modifier::BOLD);
             }
-        } else {
-            // No cell value - might be a formula cell or truly empty
-            if cell_formula.is_some() {
-                detail_lines.push(Line::from(vec![
-                    Span::styled(
-                        "Value: ",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(
-                        "(formula not evaluated by Excel reader)",
-                        Style::default()
-                            .fg(Color::DarkGray)
-                            .add_modifier(Modifier::ITALIC),
-                    ),
-                ]));
+            let current = if index == self.current_workbook {
+                "●"
             } else {
-                detail_lines.push(Line::from(Span::styled(
-                    "No cell data",
-                    Style::default()
-                        .fg(Color::DarkGray)
-                        .add_modifier(Modifier::ITALIC),
-                )));
-            }
+                " "
+            };
+            let plural = if sheets == 1 { "" } else { "s" };
+            let unsaved = if unsaved { ", unsaved edits" } else { "" };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {} {current} ", index + 1),
+                    style.fg(Color::Yellow),
+                ),
+                Span::styled(format!("{name} ({sheets} sheet{plural}{unsaved}) "), style),
+            ]));
         }
-
-        detail_lines.push(Line::from(""));
-        detail_lines.push(Line::from(vec![Span::styled(
-            "↑↓ to scroll | Any other key to close",
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "↑↓ select | Enter or 1-9 to switch | Any other key to close",
             Style::default()
                 .fg(Color::Magenta)
                 .add_modifier(Modifier::ITALIC),
-        )]));
+        )));
 
-        // Calculate popup size (60% width, auto height)
         let area = frame.area();
-        let popup_width = (area.width as f32 * 0.6).min(80.0) as u16;
-        let popup_height =
-            (detail_lines.len() + 4).min(area.height.saturating_sub(2) as usize) as u16;
+        let popup_width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4)
+            .min(area.width as usize) as u16;
+        let popup_height = (lines.len() + 2).min(area.height as usize) as u16;
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
 
-        // Calculate content area (inside borders)
-        let content_height = popup_height.saturating_sub(2) as usize; // Subtract borders
-        let total_lines = detail_lines.len();
+        frame.render_widget(Clear, popup_area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        " Workbooks ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .title_alignment(Alignment::Center),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
 
-        // Clamp scroll offset to valid range
-        let max_scroll = total_lines.saturating_sub(content_height);
-        let scroll_offset = self.cell_detail_scroll.min(max_scroll);
+    /// Bookmark list: letter, sheet, and cell of each mark
+    fn render_marks(&self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
+        let mut lines: Vec<Line> = Vec::new();
+        let mut marks: Vec<(char, &Mark)> = self.marks.iter().map(|(&l, m)| (l, m)).collect();
+        if let Some(previous) = &self.previous_position {
+            marks.push(('\'', previous));
+        }
+        for (letter, mark) in marks {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {letter}  "),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(
+                    "{}!{}{}",
+                    mark.sheet,
+                    self.col_to_letter(mark.col),
+                    mark.row + 1
+                )),
+            ]));
+        }
+        if lines.is_empty() {
+            lines.push(Line::from(format!(
+                " No marks yet: {} then a letter sets one",
+                self.key_label("set_mark")
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Letter to jump | Any other key to close",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::ITALIC),
+        )));
 
+        let area = frame.area();
+        let popup_width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4)
+            .max(20)
+            .min(area.width as usize) as u16;
+        let popup_height = (lines.len() + 2).min(area.height as usize) as u16;
         let popup_area = Rect {
             x: (area.width.saturating_sub(popup_width)) / 2,
             y: (area.height.saturating_sub(popup_height)) / 2,
@@ -1991,37 +7114,80 @@ impl TuiState {
             height: popup_height,
         };
 
-        // Clear the area behind the popup
         frame.render_widget(Clear, popup_area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(Span::styled(
+                        " Marks ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .title_alignment(Alignment::Center),
+            );
+        frame.render_widget(paragraph, popup_area);
+    }
 
-        // Build title with scroll indicator
-        let mut title_spans = vec![
-            Span::raw(" "),
-            Span::styled(
-                "Cell Details",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" - "),
-            Span::styled(cell_addr.clone(), Style::default().fg(Color::Cyan)),
-        ];
+    fn render_search_results(&self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
 
-        // Add scroll indicator if content is scrollable
-        if total_lines > content_height {
-            let scroll_info = format!(" [{}/{}]", scroll_offset + 1, max_scroll + 1);
-            title_spans.push(Span::styled(
-                scroll_info,
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut current_sheet = None;
+        for (i, m) in self.workbook_matches.iter().enumerate() {
+            // Group hits under a heading per sheet
+            if current_sheet != Some(m.sheet) {
+                current_sheet = Some(m.sheet);
+                let count = self
+                    .workbook_matches
+                    .iter()
+                    .filter(|other| other.sheet == m.sheet)
+                    .count();
+                if !lines.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(Line::from(Span::styled(
+                    format!("{} ({count})", self.sheet_names[m.sheet]),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            }
+            let address = format!("{}{}", self.col_to_letter(m.col), m.row + 1);
+            let style = if i == self.search_results_selected {
+                selected_line = lines.len();
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
                 Style::default()
-                    .fg(Color::DarkGray)
-                    .add_modifier(Modifier::ITALIC),
-            ));
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {address:<8}"), style.fg(Color::Green)),
+                Span::styled(snippet(&m.text, Self::SNIPPET_LENGTH), style),
+            ]));
         }
 
-        title_spans.push(Span::raw(" "));
+        let area = frame.area();
+        let popup_width = (area.width as f32 * 0.7).min(90.0) as u16;
+        let popup_height = (lines.len() + 3).min(area.height.saturating_sub(2) as usize) as u16;
+        // Keep the selection in view (minus borders and the footer line)
+        let inner_height = popup_height.saturating_sub(3) as usize;
+        let scroll_offset = (selected_line + 1).saturating_sub(inner_height);
+        lines.push(Line::from(""));
 
-        // Create detail content with scroll
-        let detail_paragraph = Paragraph::new(detail_lines)
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        frame.render_widget(Clear, popup_area);
+
+        let paragraph = Paragraph::new(lines)
             .style(Style::default().fg(Color::White).bg(Color::Black))
             .scroll((scroll_offset as u16, 0))
             .block(
@@ -2032,12 +7198,50 @@ impl TuiState {
                             .fg(Color::Cyan)
                             .add_modifier(Modifier::BOLD),
                     )
-                    .title(title_spans)
-                    .title_alignment(Alignment::Center),
-            )
-            .wrap(Wrap { trim: false });
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            format!(
+                                "{} matches for '{}'",
+                                self.workbook_matches.len(),
+                                self.search_query
+                            ),
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(" "),
+                    ])
+                    .title_alignment(Alignment::Center)
+                    .title_bottom(
+                        Line::from(Span::styled(
+                            " ↑↓ select | Enter to jump | Any other key to close ",
+                            Style::default()
+                                .fg(Color::Magenta)
+                                .add_modifier(Modifier::ITALIC),
+                        ))
+                        .centered(),
+                    ),
+            );
 
-        frame.render_widget(detail_paragraph, popup_area);
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn render_warnings(&self, frame: &mut Frame, area: Rect) {
+        use ratatui::text::Line;
+
+        let lines: Vec<Line> = self
+            .warnings
+            .iter()
+            .map(|w| Line::from(format!(" ⚠ {w}")))
+            .collect();
+        let banner = Paragraph::new(lines).style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_widget(banner, area);
     }
 
     fn render_copy_feedback(&self, frame: &mut Frame, message: &str) {
@@ -2085,10 +7289,12 @@ impl TuiState {
 
 /// Run the TUI application
 pub fn run_tui(
-    workbook: Workbook,
-    sheet_name: &str,
+    workbooks: Vec<OpenWorkbook>,
     config: &crate::config::Config,
-    horizontal_scroll: bool,
+    diff: Option<DiffView>,
+    save_as: Option<PathBuf>,
+    locale: NumberLocale,
+    export_options: ExportOptions,
 ) -> Result<()> {
     // Check if stdout is a TTY before attempting to use interactive mode
     use std::io::IsTerminal;
@@ -2107,8 +7313,34 @@ pub fn run_tui(
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).context("Failed to initialize terminal backend")?;
 
-    // Create app state
-    let mut app = TuiState::new(workbook, sheet_name, config, horizontal_scroll)?;
+    // With theme "auto", ask the terminal for its background now that it's in raw mode
+    let mut config = config.clone();
+    if theme_key(&config.theme.default) == "auto" {
+        config.theme.default = match background::detect() {
+            Some(Background::Light) => config.theme.light.clone(),
+            _ => config.theme.dark.clone(),
+        };
+    }
+    let config = &config;
+
+    // Create app state; the first workbook is shown and the others wait until switched to
+    let mut workbooks = workbooks.into_iter();
+    let first = workbooks.next().context("No workbook to show")?;
+    let mut app = TuiState::new(first.workbook, &first.sheet, config)?;
+    app.workbook_name = first.name;
+    app.locale = locale;
+    app.export_options = export_options;
+    if let Some(diff) = diff {
+        app.set_diff(diff);
+    }
+    app.save_path = save_as;
+    app.watcher = first.watcher;
+    if let Some(resume) = first.resume {
+        app.set_resume(resume);
+    }
+    for open in workbooks {
+        app.add_workbook(open);
+    }
 
     // Main event loop
     let res = run_event_loop(&mut terminal, &mut app);
@@ -2118,6 +7350,11 @@ pub fn run_tui(
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    if res.is_ok()
+        && let Err(e) = app.save_sessions()
+    {
+        eprintln!("Warning: couldn't save where you left off: {e:#}");
+    }
     res
 }
 
@@ -2129,12 +7366,18 @@ fn run_event_loop(
         // Draw needs mutable access to app for scroll updates
         terminal.draw(|f| {
             app.render(f);
+            app.fit_colors(f.buffer_mut());
         })?;
 
-        if event::poll(std::time::Duration::from_millis(100))? {
+        // Poll more often while a sheet loads, so it shows up as soon as it's ready
+        let timeout = if app.sheet_loader.is_some() { 30 } else { 100 };
+        if event::poll(std::time::Duration::from_millis(timeout))? {
             let event = event::read()?;
             app.handle_event(event);
         }
+        app.poll_background_search();
+        app.poll_sheet_loader()?;
+        app.poll_watcher();
 
         if app.should_quit {
             break;
@@ -2147,6 +7390,154 @@ fn run_event_loop(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_custom_themes() {
+        let mut config = crate::config::Config::default();
+        let ocean = BTreeMap::from([
+            ("base".to_string(), "Nord".to_string()),
+            ("header_fg".to_string(), "#ffb86c".to_string()),
+            ("alternating_row_bg".to_string(), "none".to_string()),
+        ]);
+        config.themes.insert("Ocean".to_string(), ocean);
+        config
+            .themes
+            .insert("dracula".to_string(), BTreeMap::from([]));
+
+        let themes = TuiState::theme_list(&config).unwrap();
+        let names: Vec<&str> = themes.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names.len(), Theme::all().len() + 1);
+        assert_eq!(names[1], "dracula");
+        let ocean = &themes[TuiState::find_theme(&themes, "ocean")];
+        assert_eq!(ocean.colors.header_fg, Color::Rgb(0xff, 0xb8, 0x6c));
+        assert_eq!(ocean.colors.alternating_row_bg, None);
+        assert_eq!(ocean.colors.border_fg, ColorScheme::nord().border_fg);
+        assert_eq!(TuiState::find_theme(&themes, "solarizeddark"), 2);
+        assert_eq!(TuiState::find_theme(&themes, "missing"), 0);
+
+        let bad = |key: &str, value: &str| {
+            let definition = BTreeMap::from([(key.to_string(), value.to_string())]);
+            ColorScheme::from_definition(&definition)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(bad("header_fg", "#12"), "Invalid color '#12' for header_fg");
+        assert_eq!(bad("header", "red"), "Unknown theme color 'header'");
+        assert_eq!(bad("base", "Matrix"), "Unknown base theme 'Matrix'");
+    }
+
+    #[test]
+    fn test_fill_status_template() {
+        let field = |name: &str| match name {
+            "cell" => Some("B12".to_string()),
+            "match" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            fill_status_template(" {cell} | {match} | {nope} | q:quit ", field),
+            " B12 | {nope} | q:quit "
+        );
+        assert_eq!(fill_status_template("{match}|{cell}", field), "B12");
+        assert_eq!(fill_status_template(" {cell ", field), " {cell ");
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        let area = Rect::new(0, 0, 10, 5);
+        let widths = [Constraint::Length(3), Constraint::Length(4)];
+        let grid = |header_height: u16| {
+            let mut buf = ratatui::buffer::Buffer::empty(area);
+            grid_lines(&mut buf, area, &widths, header_height, 1, true, true);
+            (0..area.height)
+                .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            grid(1),
+            [
+                "   │      ",
+                "───┼────  ",
+                "   │      ",
+                "───┼────  ",
+                "          "
+            ]
+        );
+        // The gutter's column letters make the header two lines high
+        assert_eq!(
+            grid(2),
+            [
+                "   │      ",
+                "   │      ",
+                "───┼────  ",
+                "   │      ",
+                "───┼────  "
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scroll_margin() {
+        // No margin: scroll only once the cursor leaves the 10 visible rows
+        assert_eq!(scroll_offset_for(9, 0, 10, 0, (0, 90)), 0);
+        assert_eq!(scroll_offset_for(10, 0, 10, 0, (0, 90)), 1);
+        // Three rows of context below and above the cursor
+        assert_eq!(scroll_offset_for(7, 0, 10, 3, (0, 90)), 1);
+        assert_eq!(scroll_offset_for(22, 20, 10, 3, (0, 90)), 19);
+        assert_eq!(scroll_offset_for(25, 20, 10, 3, (0, 90)), 20);
+        // ...except at the top and bottom of the sheet
+        assert_eq!(scroll_offset_for(1, 0, 10, 3, (0, 90)), 0);
+        assert_eq!(scroll_offset_for(98, 85, 10, 3, (0, 90)), 90);
+        // A huge margin centers the cursor
+        assert_eq!(scroll_offset_for(50, 0, 11, 99, (0, 90)), 45);
+        assert_eq!(scroll_offset_for(40, 45, 11, 99, (0, 90)), 35);
+    }
+
+    #[test]
+    fn test_filter_spec() {
+        let headers = vec!["Item".to_string(), "Amount".to_string()];
+        let locale = NumberLocale::default();
+        let spec = FilterSpec::parse("amount > 100", &headers).unwrap();
+        assert_eq!((spec.col, spec.op), (1, FilterOp::Greater));
+        assert!(spec.matches(&locale, &CellValue::Int(120)));
+        assert!(!spec.matches(&locale, &CellValue::Float(99.5)));
+        assert!(!spec.matches(&locale, &CellValue::String("n/a".to_string())));
+
+        let spec = FilterSpec::parse("A contains RENT", &headers).unwrap();
+        assert_eq!((spec.col, spec.op), (0, FilterOp::Contains));
+        assert!(spec.matches(&locale, &CellValue::String("Office rent".to_string())));
+
+        let spec = FilterSpec::parse("B = 30", &headers).unwrap();
+        assert!(spec.matches(&locale, &CellValue::String("30".to_string())));
+        assert!(FilterSpec::parse("Total > 5", &headers).is_err());
+        assert!(FilterSpec::parse("Amount 5", &headers).is_err());
+    }
+
+    #[test]
+    fn test_jump_list() {
+        let at = |row| Mark {
+            sheet: "Data".to_string(),
+            row,
+            col: 0,
+        };
+        let mut jumps = JumpList::default();
+        assert_eq!(jumps.back(at(0)), None);
+
+        jumps.record(at(1));
+        jumps.record(at(5));
+        assert_eq!(jumps.back(at(9)), Some(at(5)));
+        assert_eq!(jumps.back(at(5)), Some(at(1)));
+        assert_eq!(jumps.back(at(1)), None);
+        assert_eq!(jumps.forward(at(2)), Some(at(5)));
+        assert_eq!(jumps.forward(at(5)), Some(at(9)));
+        assert_eq!(jumps.forward(at(9)), None);
+
+        // A new jump after going back drops the positions ahead
+        assert_eq!(jumps.back(at(9)), Some(at(5)));
+        jumps.record(at(5));
+        assert_eq!(jumps.forward(at(7)), None);
+        assert_eq!(jumps.back(at(7)), Some(at(5)));
+        assert_eq!(jumps.back(at(5)), Some(at(2)));
+    }
+
     #[test]
     fn test_parse_cell_address_basic() {
         assert_eq!(TuiState::parse_cell_address("A1"), Some((0, 0)));
@@ -2211,4 +7602,48 @@ mod tests {
         assert_eq!(col_to_letter(col_z), "Z");
         assert_eq!(col_to_letter(col_aa), "AA");
     }
+
+    #[test]
+    fn test_find_matches_offsets_rows() {
+        let rows = vec![
+            vec![CellValue::String("Alpha".to_string()), CellValue::Int(42)],
+            vec![CellValue::Empty, CellValue::String("alphabet".to_string())],
+        ];
+        let plain = SearchOptions::default();
+        let locale = NumberLocale::default();
+        assert_eq!(
+            find_matches(&rows, 500, &plain.matcher("alpha").unwrap(), None, &locale),
+            vec![
+                (500, 0, "Alpha".to_string()),
+                (501, 1, "alphabet".to_string())
+            ]
+        );
+        assert!(find_matches(&rows, 0, &plain.matcher("zzz").unwrap(), None, &locale).is_empty());
+        assert_eq!(
+            find_matches(&rows, 0, &plain.matcher("alpha").unwrap(), Some(1), &locale),
+            vec![(1, 1, "alphabet".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_search_options_matcher() {
+        let mut options = SearchOptions::default();
+        assert!(options.matcher("a.c").unwrap().is_match("xA.Cx"));
+        assert!(!options.matcher("a.c").unwrap().is_match("abc"));
+
+        options.regex = true;
+        assert!(options.matcher(r"^ID-\d{3}$").unwrap().is_match("id-042"));
+        assert!(options.matcher("(").is_err());
+
+        options.case_sensitive = true;
+        assert!(!options.matcher(r"^ID-\d{3}$").unwrap().is_match("id-042"));
+
+        options = SearchOptions {
+            whole_cell: true,
+            ..SearchOptions::default()
+        };
+        assert!(options.matcher("42").unwrap().is_match("42"));
+        assert!(!options.matcher("42").unwrap().is_match("1042"));
+        assert_eq!(options.describe("B"), "whole cell");
+    }
 }
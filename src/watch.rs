@@ -0,0 +1,92 @@
+//! Noticing when the workbook file is rewritten on disk (`--watch`)
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long the file must stay untouched after a change before it counts: generators
+/// often write a workbook in several steps, or save a temporary file and rename it
+const QUIET: Duration = Duration::from_millis(500);
+
+/// Watches one file through its directory, so replacing the file (save-and-rename) is
+/// noticed as well as writing it in place
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    events: mpsc::Receiver<()>,
+    /// When the last change not yet reported happened
+    pending: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Can't watch {}", path.display()))?;
+        let dir = path
+            .parent()
+            .context("Can't watch a file without a directory")?;
+        let name = path.file_name().map(|name| name.to_os_string());
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if !matches!(event.kind, EventKind::Access(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|changed| changed.file_name().map(|n| n.to_os_string()) == name)
+                {
+                    let _ = sender.send(());
+                }
+            })
+            .context("Failed to start watching for file changes")?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Can't watch {}", dir.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            pending: None,
+        })
+    }
+
+    /// Whether the file changed and has since been quiet; never blocks
+    pub fn changed(&mut self) -> bool {
+        while self.events.try_recv().is_ok() {
+            self.pending = Some(Instant::now());
+        }
+        match self.pending {
+            Some(at) if at.elapsed() >= QUIET => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Block until the file changes and has been quiet for a moment
+    pub fn wait(&mut self) -> Result<()> {
+        if self.pending.is_none() {
+            self.events
+                .recv()
+                .context("Stopped watching for file changes")?;
+        }
+        loop {
+            match self.events.recv_timeout(QUIET) {
+                Ok(()) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    anyhow::bail!("Stopped watching for file changes")
+                }
+            }
+        }
+        self.pending = None;
+        Ok(())
+    }
+}
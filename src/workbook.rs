@@ -1,58 +1,1238 @@
+//! Opening workbooks and loading sheets and tables as typed cells
+
 use anyhow::{Context, Result, anyhow};
-use calamine::{Data, Range, Reader, Sheets, Table, open_workbook_auto};
+use calamine::{
+    Cell, CellErrorType, Data, DataRef, Dimensions, ExcelDateTime, ExcelDateTimeType, Range,
+    Reader, SheetVisible, Sheets, Table, open_workbook_auto,
+};
 use chrono::{Duration, NaiveDate};
-use std::path::Path;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use crate::aggregate;
+use crate::cache::{self, Part};
+use crate::conditional::{self, ConditionalFormat};
+use crate::delimited::{self, DelimitedSheet};
+use crate::detect;
+use crate::display::{self, ExportOptions};
+use crate::formula::{Evaluator, SheetGrid};
+use crate::hidden::{self, HiddenCells};
+use crate::hyperlinks::{self, Hyperlink, HyperlinkMode};
+use crate::links::{self, ExternalReference, LinkedObject};
+use crate::locale::{self, NumberLocale};
+use crate::macros::{self, VbaModule};
+use crate::media::{self, EmbeddedMedia};
+use crate::ooxml::Package;
+use crate::panes::{self, FrozenPanes};
+use crate::pivot::{self, PivotTable};
+use crate::progress::Progress;
+use crate::properties::{self, DocumentProperties};
+use crate::protection::{self, SheetProtection, WorkbookProtection};
+use crate::styles::{self, CellStyle, HorizontalAlign};
+
+/// Where sheet data comes from (one per open workbook, so the size difference doesn't matter)
+#[allow(clippy::large_enum_variant)]
+enum Source {
+    Excel(Sheets<std::io::BufReader<std::fs::File>>),
+    /// A CSV/TSV file presented as a single sheet
+    Delimited(DelimitedSheet),
+}
+
+/// Which row of a sheet holds the column names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderRow {
+    /// The first row of the sheet's used range
+    #[default]
+    First,
+    /// Skip this many rows at the top of the used range (title banners and the like);
+    /// the next row is the header
+    Skip(usize),
+    /// Every row is data; columns are named by letter (A, B, C, ...)
+    None,
+}
+
+/// Top-left and bottom-right (row, col) corners of a block of cells, 0-based and inclusive
+pub type CellRange = ((u32, u32), (u32, u32));
+
+/// A sheet's rows as they are read from the file, from [`Workbook::rows_iter`]
+pub struct SheetRows<'a> {
+    /// Column names from the header row, or column letters with [`HeaderRow::None`]
+    pub headers: Vec<String>,
+    rows: RowStream<'a>,
+    /// Rows read ahead so the last `skip_footer` rows can be left out
+    held: VecDeque<Vec<CellValue>>,
+    skip_footer: usize,
+}
+
+type RowStream<'a> = Box<dyn Iterator<Item = Result<Vec<CellValue>>> + 'a>;
+
+/// A sheet's non-empty cells and formulas down to some row, and whether it has data below
+type CellsTo = (Vec<Cell<Data>>, Vec<Cell<String>>, bool);
+
+impl<'a> SheetRows<'a> {
+    /// Skips rows above the header and reads the header row; `columns` (first column,
+    /// count) name the columns when there is no header row
+    fn new(
+        mut rows: RowStream<'a>,
+        columns: (u32, usize),
+        header: HeaderRow,
+        skip_footer: usize,
+    ) -> Result<Self> {
+        let skip = match header {
+            HeaderRow::Skip(n) => n,
+            HeaderRow::First | HeaderRow::None => 0,
+        };
+        for _ in 0..skip {
+            rows.next().transpose()?;
+        }
+        let headers = match header {
+            HeaderRow::None => (0..columns.1)
+                .map(|col| column_letter(columns.0 as usize + col))
+                .collect(),
+            HeaderRow::First | HeaderRow::Skip(_) => rows
+                .next()
+                .transpose()?
+                .unwrap_or_default()
+                .iter()
+                .map(CellValue::to_raw_string)
+                .collect(),
+        };
+        Ok(Self {
+            headers,
+            rows,
+            held: VecDeque::new(),
+            skip_footer,
+        })
+    }
+}
+
+impl Iterator for SheetRows<'_> {
+    type Item = Result<Vec<CellValue>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.held.len() <= self.skip_footer {
+            match self.rows.next()? {
+                Ok(row) => self.held.push_back(row),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.held.pop_front().map(Ok)
+    }
+}
+
+/// Groups the cells `next_cell` yields (in row order) into rows of the recorded used
+/// range, from the first row with a value; blank rows in between come out empty
+fn stream_rows<'a, E>(
+    name: &str,
+    dimensions: Dimensions,
+    date_system: Option<DateSystem>,
+    mut next_cell: impl FnMut() -> std::result::Result<Option<Cell<DataRef<'a>>>, E> + 'a,
+) -> RowStream<'a>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let name = name.to_string();
+    let (first_col, width) = used_columns(dimensions);
+    let mut progress = Progress::new(format!("Reading '{name}'"), row_count(dimensions));
+    let mut row = vec![CellValue::Empty; width];
+    // Row whose cells are being collected, and the last row handed out
+    let mut current: Option<u32> = None;
+    let mut emitted: Option<u32> = None;
+    let mut finished: Option<(u32, Vec<CellValue>)> = None;
+    let mut done = false;
+    Box::new(std::iter::from_fn(move || {
+        loop {
+            if let Some((index, _)) = &finished {
+                let index = *index;
+                let next = emitted.map_or(index, |last| last + 1);
+                emitted = Some(next);
+                if next < index {
+                    return Some(Ok(vec![CellValue::Empty; width]));
+                }
+                return finished.take().map(|(_, row)| Ok(row));
+            }
+            if done {
+                return None;
+            }
+            let cell = match next_cell() {
+                Ok(cell) => cell,
+                Err(e) => {
+                    done = true;
+                    return Some(Err(e).with_context(|| format!("Failed to read sheet '{name}'")));
+                }
+            };
+            let Some(cell) = cell else {
+                done = true;
+                finished = current
+                    .take()
+                    .map(|index| (index, std::mem::take(&mut row)));
+                continue;
+            };
+            if matches!(cell.get_value(), DataRef::Empty) {
+                continue;
+            }
+            let (index, col) = cell.get_position();
+            let Some(col) = col.checked_sub(first_col) else {
+                done = true;
+                return Some(Err(anyhow!(
+                    "Sheet '{name}' has cells outside its recorded used range; load it without streaming"
+                )));
+            };
+            if current.is_some_and(|c| c != index) {
+                let previous = current.replace(index).expect("row in progress");
+                let cells = std::mem::replace(&mut row, vec![CellValue::Empty; width]);
+                finished = Some((previous, cells));
+            }
+            current = Some(index);
+            progress.update(index as usize);
+            let col = col as usize;
+            if row.len() <= col {
+                row.resize(col + 1, CellValue::Empty);
+            }
+            row[col] = cell_value_in(&Data::from(cell.get_value().clone()), date_system);
+        }
+    }))
+}
+
+/// Empty, or text that is only whitespace
+fn is_blank(cell: &Data) -> bool {
+    match cell {
+        Data::Empty => true,
+        Data::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// First column and column count of a sheet's recorded used range
+fn used_columns(dimensions: Dimensions) -> (u32, usize) {
+    let first_col = dimensions.start.1;
+    (
+        first_col,
+        (dimensions.end.1 + 1).saturating_sub(first_col) as usize,
+    )
+}
+
+/// Rows up to the end of a sheet's recorded used range; 0 when it records none
+fn row_count(dimensions: Dimensions) -> usize {
+    if dimensions.end.0 > 0 {
+        dimensions.end.0 as usize + 1
+    } else {
+        0
+    }
+}
+
+/// Collects the non-empty cells `next_cell` yields, which calamine's `worksheet_range`
+/// lays out as a range, with a progress bar over the sheet's `rows`
+fn read_cells<'a, E>(
+    name: &str,
+    rows: usize,
+    mut next_cell: impl FnMut() -> std::result::Result<Option<Cell<DataRef<'a>>>, E>,
+) -> Result<Vec<Cell<Data>>>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut progress = Progress::new(format!("Loading '{name}'"), rows);
+    let mut cells = Vec::new();
+    while let Some(cell) = next_cell().with_context(|| format!("Failed to read sheet '{name}'"))? {
+        let (row, col) = cell.get_position();
+        progress.update(row as usize);
+        if !matches!(cell.get_value(), DataRef::Empty) {
+            cells.push(Cell::new((row, col), Data::from(cell.get_value().clone())));
+        }
+    }
+    Ok(cells)
+}
+
+/// Like [`read_cells`] down to row `last_row` only, without a progress bar; also returns
+/// whether a cell below it has data (blank text counts unless `trim`), where reading stops
+fn read_cells_to<'a, E>(
+    name: &str,
+    last_row: u32,
+    trim: bool,
+    mut next_cell: impl FnMut() -> std::result::Result<Option<Cell<DataRef<'a>>>, E>,
+) -> Result<(Vec<Cell<Data>>, bool)>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut cells = Vec::new();
+    while let Some(cell) = next_cell().with_context(|| format!("Failed to read sheet '{name}'"))? {
+        let value = Data::from(cell.get_value().clone());
+        if cell.get_position().0 > last_row {
+            if !(matches!(value, Data::Empty) || trim && is_blank(&value)) {
+                return Ok((cells, true));
+            }
+        } else if !matches!(value, Data::Empty) {
+            cells.push(Cell::new(cell.get_position(), value));
+        }
+    }
+    Ok((cells, false))
+}
+
+/// The formulas `next_formula` yields down to row `last_row`, skipping the cells without one
+fn read_formulas<E>(
+    last_row: u32,
+    mut next_formula: impl FnMut() -> std::result::Result<Option<Cell<String>>, E>,
+) -> Vec<Cell<String>> {
+    let mut formulas = Vec::new();
+    while let Ok(Some(cell)) = next_formula() {
+        if cell.get_position().0 > last_row {
+            break;
+        }
+        if !cell.get_value().is_empty() {
+            formulas.push(cell);
+        }
+    }
+    formulas
+}
+
+/// Sheets whose filled cells span more than this many cells are kept sparse when
+/// mostly empty, rather than laid out as a grid
+const SPARSE_MIN_CELLS: usize = 1_000_000;
+/// Mostly empty: at most one cell in this many is filled
+const SPARSE_FILL_RATIO: usize = 10;
+
+/// Whether `cells` span a huge area they leave mostly empty
+fn is_mostly_empty(cells: &[Cell<Data>]) -> bool {
+    let area = cell_area(cells);
+    area > SPARSE_MIN_CELLS && cells.len().saturating_mul(SPARSE_FILL_RATIO) < area
+}
+
+/// Cells in the smallest range holding `cells`
+fn cell_area(cells: &[Cell<Data>]) -> usize {
+    cell_bounds(cells.iter()).map_or(0, |((top, left), (bottom, right))| {
+        (bottom - top + 1) as usize * (right - left + 1) as usize
+    })
+}
+
+/// Top-left and bottom-right corners of the smallest range holding `cells`
+fn cell_bounds<'a>(
+    cells: impl Iterator<Item = &'a Cell<Data>>,
+) -> Option<((u32, u32), (u32, u32))> {
+    cells.fold(None, |bounds, cell| {
+        let (row, col) = cell.get_position();
+        Some(bounds.map_or(
+            ((row, col), (row, col)),
+            |((top, left), (bottom, right))| {
+                (
+                    (top.min(row), left.min(col)),
+                    (bottom.max(row), right.max(col)),
+                )
+            },
+        ))
+    })
+}
+
+/// The epoch a workbook's date serials count from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSystem {
+    /// Serial 1 is 1900-01-01 (Windows Excel and most files)
+    D1900,
+    /// Serial 0 is 1904-01-01 (older Mac Excel); these serials are 1462 days smaller
+    D1904,
+}
+
+/// A date cell's serial in the 1900 system, which is what `CellValue::DateTime` holds
+fn date_serial(date: &ExcelDateTime) -> f64 {
+    let serial = date.as_f64();
+    // calamine keeps the workbook's date1904 flag on each cell without exposing it
+    let system = if *date == ExcelDateTime::new(serial, ExcelDateTimeType::DateTime, true) {
+        DateSystem::D1904
+    } else {
+        DateSystem::D1900
+    };
+    match system {
+        DateSystem::D1900 => serial,
+        DateSystem::D1904 => serial + 1462.0,
+    }
+}
+
+/// Mark a date cell's serial as counted in `system`, whatever the workbook says
+fn force_date_system(cell: &mut Data, system: DateSystem) {
+    if let Data::DateTime(date) = cell {
+        let kind = if date.is_duration() {
+            ExcelDateTimeType::TimeDelta
+        } else {
+            ExcelDateTimeType::DateTime
+        };
+        *date = ExcelDateTime::new(date.as_f64(), kind, system == DateSystem::D1904);
+    }
+}
+
+/// A cell's value with its date serial read in `system`, if one is forced
+fn cell_value_in(cell: &Data, system: Option<DateSystem>) -> CellValue {
+    match (cell, system) {
+        (Data::DateTime(_), Some(system)) => {
+            let mut cell = cell.clone();
+            force_date_system(&mut cell, system);
+            SheetData::datatype_to_cellvalue(&cell)
+        }
+        _ => SheetData::datatype_to_cellvalue(cell),
+    }
+}
+
+/// `range` with its date serials read in `system`, if one is forced
+fn range_in_date_system(mut range: Range<Data>, system: Option<DateSystem>) -> Range<Data> {
+    if let Some(system) = system {
+        for row in 0..range.height() {
+            for cell in range[row].iter_mut() {
+                force_date_system(cell, system);
+            }
+        }
+    }
+    range
+}
 
 pub struct Workbook {
-    sheets: Sheets<std::io::BufReader<std::fs::File>>,
+    source: Source,
+    path: PathBuf,
+    /// Compute formulas that have no cached result when loading sheets
+    evaluate_formulas: bool,
+    /// Where loaded sheets take their headers from
+    header: HeaderRow,
+    /// Rows at the bottom of each sheet's used range to leave out (totals, disclaimers)
+    skip_footer: usize,
+    /// Drop blank rows and columns at the bottom and right of loaded sheets
+    trim: bool,
+    /// Date system the file's serials are read in; None for the one the workbook records
+    date_system: Option<DateSystem>,
+    /// Identifies the file's parsed sheets in the on-disk cache; hashed on first use
+    cache_key: OnceLock<Option<String>>,
+    /// Temporary copy of a downloaded workbook, deleted when the workbook is dropped
+    #[cfg(feature = "url")]
+    _download: Option<tempfile::TempPath>,
 }
 
 impl Workbook {
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
-        let sheets = open_workbook_auto(path.as_ref()).context("Failed to open workbook")?;
+        let path = path.as_ref();
+        let source = match delimited::format_for(path) {
+            Some(format) => Source::Delimited(delimited::load(path, format)?),
+            None => Source::Excel(open_workbook_auto(path).context("Failed to open workbook")?),
+        };
+
+        Ok(Self {
+            source,
+            path: path.to_path_buf(),
+            evaluate_formulas: false,
+            header: HeaderRow::First,
+            skip_footer: 0,
+            trim: true,
+            date_system: None,
+            cache_key: OnceLock::new(),
+            #[cfg(feature = "url")]
+            _download: None,
+        })
+    }
+
+    /// Opens a downloaded workbook, keeping the temporary file alive as long as the workbook
+    #[cfg(feature = "url")]
+    pub fn open_download(download: tempfile::TempPath) -> Result<Self> {
+        let mut workbook = Self::open(&download)?;
+        workbook._download = Some(download);
+        Ok(workbook)
+    }
+
+    /// Read date serials as `system` (`None`: as the workbook records), for files whose
+    /// dates come out four years and a day off
+    pub fn set_date_system(&mut self, system: Option<DateSystem>) {
+        self.date_system = system;
+    }
+
+    /// Fill in formula results missing from the file when sheets are loaded (see [`crate::formula`])
+    pub fn set_evaluate_formulas(&mut self, evaluate: bool) {
+        self.evaluate_formulas = evaluate;
+    }
+
+    pub fn evaluates_formulas(&self) -> bool {
+        self.evaluate_formulas
+    }
 
-        Ok(Self { sheets })
+    /// Where sheets loaded from now on take their column names from
+    pub fn set_header_row(&mut self, header: HeaderRow) {
+        self.header = header;
+    }
+
+    /// Leave out this many rows at the bottom of sheets loaded from now on
+    pub fn set_skip_footer(&mut self, rows: usize) {
+        self.skip_footer = rows;
+    }
+
+    /// Whether sheets loaded from now on drop the blank rows and columns at their bottom
+    /// and right (on by default); used ranges often reach far past the data because of
+    /// formatting or cleared cells
+    pub fn set_trim(&mut self, trim: bool) {
+        self.trim = trim;
+    }
+
+    /// Local path of the workbook file (a temporary copy for downloads)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens the same file again with the same loading settings, so a sheet can be loaded
+    /// on another thread. Call the result on that thread: opening reads the workbook's
+    /// shared strings. A downloaded workbook must outlive the reopened one.
+    pub fn reopener(&self) -> impl FnOnce() -> Result<Self> + Send + 'static {
+        let path = self.path.clone();
+        let (evaluate_formulas, header, skip_footer, trim, date_system) = (
+            self.evaluate_formulas,
+            self.header,
+            self.skip_footer,
+            self.trim,
+            self.date_system,
+        );
+        move || {
+            let mut workbook = Self::open(&path)?;
+            workbook.evaluate_formulas = evaluate_formulas;
+            workbook.header = header;
+            workbook.skip_footer = skip_footer;
+            workbook.trim = trim;
+            workbook.date_system = date_system;
+            Ok(workbook)
+        }
     }
 
     pub fn sheet_names(&self) -> Vec<String> {
-        self.sheets.sheet_names()
+        match &self.source {
+            Source::Excel(sheets) => sheets.sheet_names(),
+            Source::Delimited(sheet) => vec![sheet.name.clone()],
+        }
+    }
+
+    /// File format label: "xlsx", "xlsm", "xlsb", "xls", "ods", "csv", or "tsv"
+    pub fn format(&self) -> &'static str {
+        let macro_enabled = self
+            .path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsm"));
+        match &self.source {
+            Source::Excel(Sheets::Xlsx(_)) if macro_enabled => "xlsm",
+            Source::Excel(Sheets::Xlsx(_)) => "xlsx",
+            Source::Excel(Sheets::Xlsb(_)) => "xlsb",
+            Source::Excel(Sheets::Xls(_)) => "xls",
+            Source::Excel(Sheets::Ods(_)) => "ods",
+            Source::Delimited(sheet) => sheet.format,
+        }
+    }
+
+    /// Workbook-level defined names as (name, formula)
+    pub fn defined_names(&self) -> Vec<(String, String)> {
+        match &self.source {
+            Source::Excel(sheets) => sheets.defined_names().to_vec(),
+            Source::Delimited(_) => Vec::new(),
+        }
+    }
+
+    /// Where a defined name or Excel table is, as "Sheet!A1:B5" (a table's data rows);
+    /// names match case-insensitively, as in Excel. None for unknown names and names that
+    /// hold constants or formulas rather than cells.
+    pub fn named_range(&mut self, name: &str) -> Option<String> {
+        let defined = self
+            .defined_names()
+            .into_iter()
+            .find(|(defined, _)| defined.eq_ignore_ascii_case(name));
+        if let Some((_, formula)) = defined {
+            let location = formula.trim_start_matches('=');
+            return (location.contains('!') && !location.contains(','))
+                .then(|| location.replace('$', ""));
+        }
+
+        // Tables are an .xlsx feature; other formats just have none
+        self.load_tables().ok()?;
+        let table = self
+            .sheet_names()
+            .iter()
+            .flat_map(|sheet| self.table_names_in_sheet(sheet).unwrap_or_default())
+            .find(|table| table.eq_ignore_ascii_case(name))?;
+        let table = self.table_by_name(&table).ok()?;
+        let (row, col) = table.data_start?;
+        let (row, col) = (row as usize, col as usize);
+        let last_row = row + table.rows.len().max(1) - 1;
+        let last_col = col + table.headers.len().max(1) - 1;
+        Some(format!(
+            "{}:{}{}",
+            cell_reference(&table.sheet_name, row, col),
+            column_letter(last_col),
+            last_row + 1
+        ))
+    }
+
+    /// Used range of each sheet (e.g. "A1:D50"), None for empty sheets.
+    /// For .xlsx this reads the sheet's `dimension` record instead of loading its cells.
+    pub fn sheet_dimensions(&mut self) -> Result<Vec<(String, Option<String>)>> {
+        let mut recorded = Vec::new();
+        if let Ok(mut package) = self.package("Sheet dimensions") {
+            for (name, part) in package.worksheet_parts()? {
+                let sheet = package.read_xml_skipping(&part, &["sheetData"])?;
+                let dimension = sheet
+                    .child("dimension")
+                    .and_then(|d| d.attr("ref"))
+                    .map(str::to_string);
+                recorded.push((name, dimension));
+            }
+        }
+
+        let mut dimensions = Vec::new();
+        for name in self.sheet_names() {
+            let known = recorded
+                .iter()
+                .find(|(n, _)| *n == name)
+                .and_then(|(_, d)| d.clone());
+            let dimension = match known {
+                Some(dimension) => Some(dimension),
+                // Other formats (or a missing record) need the cells themselves
+                None => {
+                    let range = self.worksheet_range(&name)?;
+                    range.start().zip(range.end()).map(|(start, end)| {
+                        format!(
+                            "{}{}:{}{}",
+                            column_letter(start.1 as usize),
+                            start.0 + 1,
+                            column_letter(end.1 as usize),
+                            end.0 + 1
+                        )
+                    })
+                }
+            };
+            dimensions.push((name, dimension));
+        }
+        Ok(dimensions)
     }
 
     /// Loads all rows eagerly into memory
     pub fn load_sheet(&mut self, name: &str) -> Result<SheetData> {
-        let range = self
-            .sheets
-            .worksheet_range(name)
-            .with_context(|| format!("Sheet '{name}' not found"))?;
+        let range = self.worksheet_range(name)?;
+        let (range, has_header) = self.table_range(range);
 
         // Try to load formulas, but don't fail if they're not available
-        let formula_range = self.sheets.worksheet_formula(name).ok();
+        let formula_range = self.worksheet_formula(name);
+
+        Ok(SheetData::from_range(range, formula_range, has_header))
+    }
+
+    /// Loads only the cells inside `first..=last` (0-based, inclusive), clipped to the
+    /// sheet's data and, like [`Self::load_sheet`], to the rows between the skipped ones
+    /// ([`HeaderRow::Skip`]) and the footer. The window's top row becomes the header row
+    /// unless [`HeaderRow::None`] is set. .xlsx and .xlsb sheets are read only down to the
+    /// window's last row and footer, so the window's columns are clipped to the data in
+    /// the rows read.
+    pub fn load_sheet_window(
+        &mut self,
+        name: &str,
+        first: (u32, u32),
+        last: (u32, u32),
+    ) -> Result<SheetData> {
+        let has_header = self.header != HeaderRow::None;
+        let skip = match self.header {
+            HeaderRow::Skip(rows) => rows,
+            HeaderRow::First | HeaderRow::None => 0,
+        };
+        // The footer reaches into the window unless the data goes on for longer than it
+        let read_to = last.0.saturating_add(self.skip_footer as u32);
+        let (cells, formulas, more) = self.cells_to(name, read_to)?;
+        let start = cell_bounds(cells.iter()).map(|(start, _)| start);
+        let end = cell_bounds(
+            cells
+                .iter()
+                .filter(|cell| !self.trim || !is_blank(cell.get_value())),
+        )
+        .map(|(_, end)| end);
+        let (Some(start), Some(end)) = (start, end) else {
+            return Ok(SheetData::from_range(Range::empty(), None, has_header));
+        };
+
+        // Clip first so a huge request like A1:XFD1048576 doesn't allocate a huge grid
+        let top = (start.0 as usize + skip).min(u32::MAX as usize) as u32;
+        let bottom = if more {
+            last.0
+        } else {
+            // Exclusive bottom row once the footer is dropped
+            match (end.0 as usize + 1).checked_sub(self.skip_footer) {
+                Some(bottom) if bottom > 0 => last.0.min(bottom as u32 - 1),
+                _ => return Ok(SheetData::from_range(Range::empty(), None, has_header)),
+            }
+        };
+        let first = (first.0.max(top), first.1.max(start.1));
+        let last = (bottom, last.1.min(end.1));
+        if first.0 > last.0 || first.1 > last.1 {
+            anyhow::bail!(
+                "Range is outside the data in sheet '{name}' ({}{}:{}{})",
+                column_letter(start.1 as usize),
+                start.0 + 1,
+                column_letter(end.1 as usize),
+                end.0 + 1
+            );
+        }
+
+        let in_window = |(row, col): (u32, u32)| {
+            (first.0..=last.0).contains(&row) && (first.1..=last.1).contains(&col)
+        };
+        let mut range = Range::new(first, last);
+        for cell in cells.into_iter().filter(|c| in_window(c.get_position())) {
+            range.set_value(cell.get_position(), cell.get_value().clone());
+        }
+        let formulas: Vec<_> = formulas
+            .into_iter()
+            .filter(|c| in_window(c.get_position()))
+            .collect();
+        let formula_range = (!formulas.is_empty()).then(|| {
+            let mut range = Range::new(first, last);
+            for cell in formulas {
+                range.set_value(cell.get_position(), cell.get_value().clone());
+            }
+            range
+        });
+        Ok(SheetData::from_range(range, formula_range, has_header))
+    }
 
-        Ok(SheetData::from_range_with_formulas(range, formula_range))
+    /// A sheet's non-empty cells and formulas down to row `last_row`, and whether the sheet
+    /// has data below it. .xlsx and .xlsb sheets stop reading there; other formats, and
+    /// sheets whose formulas are evaluated, are loaded whole first.
+    fn cells_to(&mut self, name: &str, last_row: u32) -> Result<CellsTo> {
+        let trim = self.trim;
+        let streamed = match &mut self.source {
+            _ if self.evaluate_formulas => None,
+            Source::Excel(Sheets::Xlsx(xlsx)) => {
+                let read = match xlsx.worksheet_cells_reader(name) {
+                    Ok(mut reader) => {
+                        Some(read_cells_to(name, last_row, trim, || reader.next_cell())?)
+                    }
+                    Err(_) => None,
+                };
+                read.map(|(cells, more)| {
+                    let formulas = match xlsx.worksheet_cells_reader(name) {
+                        Ok(mut reader) => read_formulas(last_row, || reader.next_formula()),
+                        Err(_) => Vec::new(),
+                    };
+                    (cells, formulas, more)
+                })
+            }
+            Source::Excel(Sheets::Xlsb(xlsb)) => {
+                let read = match xlsb.worksheet_cells_reader(name) {
+                    Ok(mut reader) => {
+                        Some(read_cells_to(name, last_row, trim, || reader.next_cell())?)
+                    }
+                    Err(_) => None,
+                };
+                read.map(|(cells, more)| {
+                    let formulas = match xlsb.worksheet_cells_reader(name) {
+                        Ok(mut reader) => read_formulas(last_row, || reader.next_formula()),
+                        Err(_) => Vec::new(),
+                    };
+                    (cells, formulas, more)
+                })
+            }
+            _ => None,
+        };
+        if let Some((cells, formulas, more)) = streamed {
+            return Ok((self.cells_in_date_system(cells), formulas, more));
+        }
+
+        let range = self.worksheet_range(name)?;
+        let Some((top, left)) = range.start() else {
+            return Ok((Vec::new(), Vec::new(), false));
+        };
+        let at = |row: usize, col: usize| (top + row as u32, left + col as u32);
+        let mut cells = Vec::new();
+        let mut more = false;
+        for (row, col, value) in range.used_cells() {
+            let pos = at(row, col);
+            if pos.0 <= last_row {
+                cells.push(Cell::new(pos, value.clone()));
+            } else if !(trim && is_blank(value)) {
+                more = true;
+            }
+        }
+        let formulas = self
+            .worksheet_formula(name)
+            .and_then(|formulas| {
+                let (top, left) = formulas.start()?;
+                Some(
+                    formulas
+                        .used_cells()
+                        .map(|(row, col, formula)| {
+                            Cell::new((top + row as u32, left + col as u32), formula.clone())
+                        })
+                        .filter(|cell| {
+                            cell.get_position().0 <= last_row && !cell.get_value().is_empty()
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+        Ok((cells, formulas, more))
     }
 
-    /// Loads only headers; rows fetched on demand
+    /// Loads only headers; rows fetched on demand. Huge, mostly empty .xlsx and .xlsb
+    /// sheets keep just their filled cells (see [`LazySheetData::is_sparse`]).
     pub fn load_sheet_lazy(&mut self, name: &str) -> Result<LazySheetData> {
-        let range = self
-            .sheets
-            .worksheet_range(name)
-            .with_context(|| format!("Sheet '{name}' not found"))?;
+        let range = match self.cached_cells(name)? {
+            Some(cells) if is_mostly_empty(&cells) => {
+                let formulas = self.formula_cells(name);
+                return Ok(self.sparse_sheet(cells, formulas));
+            }
+            Some(cells) => Range::from_sparse(cells),
+            None => self.worksheet_range(name)?,
+        };
+        let (range, has_header) = self.table_range(range);
 
         // Try to load formulas, but don't fail if they're not available
-        let formula_range = self.sheets.worksheet_formula(name).ok();
+        let formula_range = self.worksheet_formula(name);
 
-        Ok(LazySheetData::from_range_with_formulas(
-            range,
-            formula_range,
-        ))
+        Ok(LazySheetData::from_range(range, formula_range, has_header))
+    }
+
+    /// Streams a sheet's rows from the file without loading the whole sheet: .xlsx and .xlsb
+    /// sheets are read one row at a time (other formats are loaded first). Header row and
+    /// footer settings apply; formulas are never evaluated, and columns start at the sheet's
+    /// recorded used range, which can include blank formatted columns `load_sheet` drops.
+    pub fn rows_iter(&mut self, name: &str) -> Result<SheetRows<'_>> {
+        let (header, skip_footer) = (self.header, self.skip_footer);
+        let date_system = self.date_system;
+        if !matches!(
+            self.source,
+            Source::Excel(Sheets::Xlsx(_) | Sheets::Xlsb(_))
+        ) {
+            let range = self.cached_range(name)?;
+            let range = self.trimmed(range);
+            let first_col = range.start().map_or(0, |(_, col)| col);
+            let (height, width) = range.get_size();
+            let rows = (0..height).map(move |row| {
+                Ok((0..width)
+                    .map(|col| {
+                        range
+                            .get((row, col))
+                            .map_or(CellValue::Empty, SheetData::datatype_to_cellvalue)
+                    })
+                    .collect())
+            });
+            let columns = (first_col, width);
+            return SheetRows::new(Box::new(rows), columns, header, skip_footer);
+        }
+        let (columns, rows) = match &mut self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => {
+                let mut reader = xlsx
+                    .worksheet_cells_reader(name)
+                    .with_context(|| format!("Sheet '{name}' not found"))?;
+                let dimensions = reader.dimensions();
+                (
+                    used_columns(dimensions),
+                    stream_rows(name, dimensions, date_system, move || reader.next_cell()),
+                )
+            }
+            Source::Excel(Sheets::Xlsb(xlsb)) => {
+                let mut reader = xlsb
+                    .worksheet_cells_reader(name)
+                    .with_context(|| format!("Sheet '{name}' not found"))?;
+                let dimensions = reader.dimensions();
+                (
+                    used_columns(dimensions),
+                    stream_rows(name, dimensions, date_system, move || reader.next_cell()),
+                )
+            }
+            _ => unreachable!("other formats are loaded above"),
+        };
+        SheetRows::new(rows, columns, header, skip_footer)
+    }
+
+    /// `range` without its blank bottom rows and right columns, unless trimming is off
+    fn trimmed(&self, range: Range<Data>) -> Range<Data> {
+        let Some(start) = range.start().filter(|_| self.trim) else {
+            return range;
+        };
+        let last = range
+            .used_cells()
+            .filter(|(_, _, cell)| !is_blank(cell))
+            .fold(None, |last: Option<(usize, usize)>, (row, col, _)| {
+                Some(last.map_or((row, col), |(r, c)| (r.max(row), c.max(col))))
+            });
+        match last {
+            Some((row, col)) => range.range(start, (start.0 + row as u32, start.1 + col as u32)),
+            None => Range::empty(),
+        }
+    }
+
+    /// Like [`Self::table_range`] for a sheet's filled cells, kept sparse along with the
+    /// sheet's `formulas`
+    fn sparse_sheet(
+        &self,
+        mut cells: Vec<Cell<Data>>,
+        formulas: Vec<Cell<String>>,
+    ) -> LazySheetData {
+        let has_header = self.header != HeaderRow::None;
+        let skip = match self.header {
+            HeaderRow::Skip(rows) => rows,
+            HeaderRow::First | HeaderRow::None => 0,
+        };
+        let start = cell_bounds(cells.iter()).map(|(start, _)| start);
+        let end = cell_bounds(
+            cells
+                .iter()
+                .filter(|cell| !self.trim || !is_blank(cell.get_value())),
+        )
+        .map(|(_, end)| end);
+        let (Some((top, left)), Some((bottom, right))) = (start, end) else {
+            return LazySheetData::from_range(Range::empty(), None, has_header);
+        };
+        // Exclusive bottom row once the footer is dropped
+        let top = top as usize + skip;
+        let bottom = (bottom as usize + 1).saturating_sub(self.skip_footer);
+        if top >= bottom {
+            return LazySheetData::from_range(Range::empty(), None, has_header);
+        }
+        let (left, width) = (left as usize, (right - left) as usize + 1);
+        let in_table = |(row, col): (u32, u32)| {
+            (top..bottom).contains(&(row as usize))
+                && (left..left + width).contains(&(col as usize))
+        };
+        cells.retain(|cell| in_table(cell.get_position()));
+        cells.sort_unstable_by_key(|cell| cell.get_position());
+
+        let first_row = top + has_header as usize;
+        let headers = if has_header {
+            let mut headers = vec![String::new(); width];
+            for cell in cells
+                .iter()
+                .take_while(|cell| cell.get_position().0 as usize == top)
+            {
+                headers[cell.get_position().1 as usize - left] =
+                    SheetData::cell_to_string(cell.get_value());
+            }
+            headers
+        } else {
+            (left..left + width).map(column_letter).collect()
+        };
+        let height = bottom - first_row;
+        let mut row_starts = Vec::with_capacity(height + 1);
+        let mut values = Vec::new();
+        for cell in cells
+            .iter()
+            .skip_while(|cell| (cell.get_position().0 as usize) < first_row)
+        {
+            let (row, col) = cell.get_position();
+            while row_starts.len() <= row as usize - first_row {
+                row_starts.push(values.len());
+            }
+            values.push((
+                col as usize - left,
+                SheetData::datatype_to_cellvalue(cell.get_value()),
+            ));
+        }
+        row_starts.resize(height + 1, values.len());
+        let mut formulas: Vec<(usize, usize, String)> = formulas
+            .into_iter()
+            .filter(|cell| {
+                let (row, col) = cell.get_position();
+                row as usize >= first_row && in_table((row, col))
+            })
+            .map(|cell| {
+                let (row, col) = cell.get_position();
+                (
+                    row as usize - first_row,
+                    col as usize - left,
+                    cell.get_value().clone(),
+                )
+            })
+            .collect();
+        formulas.sort_unstable_by_key(|&(row, col, _)| (row, col));
+
+        LazySheetData {
+            cells: LazyCells::Sparse(Arc::new(SparseCells {
+                origin: (first_row, left),
+                row_starts,
+                values,
+                formulas,
+            })),
+            has_header,
+            headers,
+            width,
+            height,
+        }
+    }
+
+    /// The part of a sheet's cells that holds the table, and whether its top row is the header
+    fn table_range(&self, range: Range<Data>) -> (Range<Data>, bool) {
+        let range = self.trimmed(range);
+        let has_header = self.header != HeaderRow::None;
+        let skip = match self.header {
+            HeaderRow::Skip(rows) => rows,
+            HeaderRow::First | HeaderRow::None => 0,
+        };
+        let (Some(start), Some(end)) = (range.start(), range.end()) else {
+            return (range, has_header);
+        };
+        if skip == 0 && self.skip_footer == 0 {
+            return (range, has_header);
+        }
+        // Exclusive bottom row once the footer is dropped
+        let top = start.0 as usize + skip;
+        match (end.0 as usize + 1).checked_sub(self.skip_footer) {
+            Some(bottom) if top < bottom => (
+                range.range((top as u32, start.1), (bottom as u32 - 1, end.1)),
+                has_header,
+            ),
+            _ => (Range::empty(), has_header),
+        }
+    }
+
+    /// Absolute 0-based corners of the table found by [`detect::detect_region`], or None
+    /// for an empty sheet
+    pub fn detect_table(&mut self, name: &str) -> Result<Option<CellRange>> {
+        let range = self.worksheet_range(name)?;
+        let Some((start_row, start_col)) = range.start() else {
+            return Ok(None);
+        };
+        let filled: Vec<Vec<bool>> = range
+            .rows()
+            .map(|row| row.iter().map(|cell| !is_blank(cell)).collect())
+            .collect();
+        Ok(detect::detect_region(&filled).map(|(first, last)| {
+            (
+                (start_row + first.0 as u32, start_col + first.1 as u32),
+                (start_row + last.0 as u32, start_col + last.1 as u32),
+            )
+        }))
+    }
+
+    /// Non-empty cells as (row, col, text), with absolute 0-based positions
+    pub fn sheet_cells(&mut self, name: &str) -> Result<Vec<(usize, usize, String)>> {
+        let range = self.worksheet_range(name)?;
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+
+        Ok(range
+            .used_cells()
+            .map(|(row, col, cell)| {
+                (
+                    start_row as usize + row,
+                    start_col as usize + col,
+                    SheetData::cell_to_string(cell),
+                )
+            })
+            .filter(|(_, _, text)| !text.is_empty())
+            .collect())
+    }
+
+    /// Non-empty cells as (row, col, value), with absolute 0-based positions
+    pub fn sheet_values(&mut self, name: &str) -> Result<Vec<(usize, usize, CellValue)>> {
+        let range = self.worksheet_range(name)?;
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+
+        Ok(range
+            .used_cells()
+            .map(|(row, col, cell)| {
+                (
+                    start_row as usize + row,
+                    start_col as usize + col,
+                    SheetData::datatype_to_cellvalue(cell),
+                )
+            })
+            .filter(|(_, _, value)| !value.is_empty())
+            .collect())
+    }
+
+    /// Formulas of a sheet as (row, col, formula without the `=`), with absolute 0-based
+    /// positions; none for formats that don't store formulas
+    pub fn sheet_formulas(&mut self, name: &str) -> Result<Vec<(usize, usize, String)>> {
+        let Some(range) = self.worksheet_formula(name) else {
+            return Ok(Vec::new());
+        };
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+        Ok(range
+            .used_cells()
+            .filter(|(_, _, formula)| !formula.is_empty())
+            .map(|(row, col, formula)| {
+                (
+                    start_row as usize + row,
+                    start_col as usize + col,
+                    formula.clone(),
+                )
+            })
+            .collect())
+    }
+
+    /// Cell values of a sheet, with missing formula results computed if enabled
+    fn worksheet_range(&mut self, name: &str) -> Result<Range<Data>> {
+        let values = self.cached_range(name)?;
+        if !self.evaluate_formulas {
+            return Ok(values);
+        }
+        let Some(formulas) = self.worksheet_formula(name) else {
+            return Ok(values);
+        };
+        let grid = SheetGrid {
+            values,
+            formulas: Some(formulas),
+        };
+        Ok(self.evaluator().fill_missing(name, grid))
+    }
+
+    /// Computes the formula at an absolute (row, col), even if the file has a cached result.
+    /// None if the cell has no formula.
+    pub fn evaluate_cell(&mut self, sheet_name: &str, row: u32, col: u32) -> Option<CellValue> {
+        let value = self.evaluator().evaluate_at(sheet_name, row, col)?;
+        Some(SheetData::datatype_to_cellvalue(&value.to_data()))
+    }
+
+    /// Evaluator reading other sheets from this workbook as formulas reference them
+    fn evaluator(&mut self) -> Evaluator<'_> {
+        let names = self.sheet_names();
+        Evaluator::new(Box::new(move |sheet| {
+            let name = names.iter().find(|n| n.eq_ignore_ascii_case(sheet))?;
+            Some(SheetGrid {
+                values: self.cached_range(name).ok()?,
+                formulas: self.worksheet_formula(name),
+            })
+        }))
+    }
+
+    /// Cell values of a sheet as stored in the file, from the on-disk cache when possible
+    fn cached_range(&mut self, name: &str) -> Result<Range<Data>> {
+        let key = self.cache_key();
+        if let Some(key) = &key
+            && let Some(range) = cache::load(key, name, Part::Values)
+        {
+            return Ok(range_in_date_system(range, self.date_system));
+        }
+        let range = self.parse_range(name)?;
+        if let Some(key) = &key
+            && cache::worth_caching(&range)
+        {
+            cache::store(key, name, Part::Values, &range);
+        }
+        Ok(range_in_date_system(range, self.date_system))
+    }
+
+    /// Like [`Self::cached_range`] for an .xlsx or .xlsb sheet's non-empty cells, before
+    /// they are laid out as a range; None for other formats and sheets, and when formulas
+    /// are evaluated
+    fn cached_cells(&mut self, name: &str) -> Result<Option<Vec<Cell<Data>>>> {
+        if self.evaluate_formulas {
+            return Ok(None);
+        }
+        let key = self.cache_key();
+        if matches!(
+            self.source,
+            Source::Excel(Sheets::Xlsx(_) | Sheets::Xlsb(_))
+        ) && let Some(key) = &key
+            && let Some(cells) = cache::load_cells(key, name, Part::Values)
+        {
+            return Ok(Some(self.cells_in_date_system(cells)));
+        }
+        let Some(cells) = self.parse_cells(name)? else {
+            return Ok(None);
+        };
+        if let Some(key) = &key
+            && cache::worth_caching_area(cell_area(&cells))
+        {
+            cache::store_cells(key, name, Part::Values, &cells);
+        }
+        Ok(Some(self.cells_in_date_system(cells)))
+    }
+
+    /// `cells` with their date serials read in the forced date system, if any
+    fn cells_in_date_system(&self, cells: Vec<Cell<Data>>) -> Vec<Cell<Data>> {
+        let Some(system) = self.date_system else {
+            return cells;
+        };
+        cells
+            .into_iter()
+            .map(|cell| {
+                let mut value = cell.get_value().clone();
+                force_date_system(&mut value, system);
+                Cell::new(cell.get_position(), value)
+            })
+            .collect()
+    }
+
+    /// Key of this file's entries in the sheet cache; None if caching is off. Text files
+    /// are parsed when opened, so there is nothing to save.
+    fn cache_key(&self) -> Option<String> {
+        if matches!(self.source, Source::Delimited(_)) {
+            return None;
+        }
+        self.cache_key
+            .get_or_init(|| cache::file_key(&self.path))
+            .clone()
+    }
+
+    fn parse_range(&mut self, name: &str) -> Result<Range<Data>> {
+        if let Some(cells) = self.parse_cells(name)? {
+            return Ok(Range::from_sparse(cells));
+        }
+        match &mut self.source {
+            Source::Excel(sheets) => sheets
+                .worksheet_range(name)
+                .with_context(|| format!("Sheet '{name}' not found")),
+            Source::Delimited(sheet) if sheet.name == name => Ok(sheet.range.clone()),
+            Source::Delimited(_) => Err(anyhow!("Sheet '{name}' not found")),
+        }
+    }
+
+    /// .xlsx and .xlsb sheets are read cell by cell to show progress on huge sheets; None
+    /// for other formats, and for chart sheets and other non-worksheets, left to calamine
+    fn parse_cells(&mut self, name: &str) -> Result<Option<Vec<Cell<Data>>>> {
+        match &mut self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => {
+                if let Ok(mut reader) = xlsx.worksheet_cells_reader(name) {
+                    let rows = row_count(reader.dimensions());
+                    return read_cells(name, rows, || reader.next_cell()).map(Some);
+                }
+            }
+            Source::Excel(Sheets::Xlsb(xlsb)) => {
+                if let Ok(mut reader) = xlsb.worksheet_cells_reader(name) {
+                    let rows = row_count(reader.dimensions());
+                    return read_cells(name, rows, || reader.next_cell()).map(Some);
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Formulas of an .xlsx or .xlsb sheet, read cell by cell rather than as a range
+    fn formula_cells(&mut self, name: &str) -> Vec<Cell<String>> {
+        match &mut self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => match xlsx.worksheet_cells_reader(name) {
+                Ok(mut reader) => read_formulas(u32::MAX, || reader.next_formula()),
+                Err(_) => Vec::new(),
+            },
+            Source::Excel(Sheets::Xlsb(xlsb)) => match xlsb.worksheet_cells_reader(name) {
+                Ok(mut reader) => read_formulas(u32::MAX, || reader.next_formula()),
+                Err(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Formulas of a sheet, if the format stores any
+    fn worksheet_formula(&mut self, name: &str) -> Option<Range<String>> {
+        let key = self.cache_key();
+        if let Some(key) = &key
+            && let Some(range) = cache::load(key, name, Part::Formulas)
+        {
+            return Some(range);
+        }
+        let range = match &mut self.source {
+            Source::Excel(sheets) => sheets.worksheet_formula(name).ok()?,
+            Source::Delimited(_) => return None,
+        };
+        // Kept for the sheets whose values are cached, which the formulas always accompany
+        if let Some(key) = &key
+            && cache::contains(key, name, Part::Values)
+        {
+            cache::store(key, name, Part::Formulas, &range);
+        }
+        Some(range)
     }
 
     // ===== Table API (Xlsx only) =====
 
     /// Load table metadata from the workbook (Xlsx only)
     pub fn load_tables(&mut self) -> Result<()> {
-        match &mut self.sheets {
-            Sheets::Xlsx(xlsx) => xlsx
+        match &mut self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => xlsx
                 .load_tables()
                 .context("Failed to load table metadata")
                 .map_err(|e| anyhow!("{e}")),
@@ -62,16 +1242,18 @@ impl Workbook {
 
     /// Get all table names in the workbook (Xlsx only)
     pub fn table_names(&self) -> Result<Vec<String>> {
-        match &self.sheets {
-            Sheets::Xlsx(xlsx) => Ok(xlsx.table_names().iter().map(|s| (*s).clone()).collect()),
+        match &self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => {
+                Ok(xlsx.table_names().iter().map(|s| (*s).clone()).collect())
+            }
             _ => Err(anyhow!("Tables are only supported in .xlsx files")),
         }
     }
 
     /// Get table names in a specific sheet (Xlsx only)
     pub fn table_names_in_sheet(&self, sheet_name: &str) -> Result<Vec<String>> {
-        match &self.sheets {
-            Sheets::Xlsx(xlsx) => Ok(xlsx
+        match &self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => Ok(xlsx
                 .table_names_in_sheet(sheet_name)
                 .iter()
                 .map(|s| (*s).clone())
@@ -82,17 +1264,228 @@ impl Workbook {
 
     /// Get table data by name (Xlsx only)
     pub fn table_by_name(&mut self, table_name: &str) -> Result<TableData> {
-        match &mut self.sheets {
-            Sheets::Xlsx(xlsx) => {
+        match &mut self.source {
+            Source::Excel(Sheets::Xlsx(xlsx)) => {
                 let table = xlsx
                     .table_by_name(table_name)
                     .map_err(|e| anyhow!("Table '{table_name}' not found: {e}"))?;
 
-                Ok(TableData::from_calamine_table(table))
+                Ok(TableData::from_table_in(table, self.date_system))
             }
             _ => Err(anyhow!("Tables are only supported in .xlsx files")),
         }
     }
+
+    // ===== Macros =====
+
+    /// Get decompressed VBA modules; empty if the workbook has no macros
+    pub fn vba_modules(&mut self) -> Result<Vec<VbaModule>> {
+        let Source::Excel(sheets) = &mut self.source else {
+            return Ok(Vec::new());
+        };
+        match sheets.vba_project().context("Failed to read VBA project")? {
+            Some(project) => macros::modules_from_project(&project),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // ===== Package inspection (Xlsx only) =====
+
+    /// Open the underlying zip package for parts calamine doesn't parse
+    fn package(&self, feature: &str) -> Result<Package> {
+        match &self.source {
+            Source::Excel(Sheets::Xlsx(_)) => Package::open(&self.path),
+            _ => Err(anyhow!("{feature} are only supported in .xlsx files")),
+        }
+    }
+
+    /// Get document properties such as author and modification dates (Xlsx only)
+    pub fn document_properties(&self) -> Result<DocumentProperties> {
+        let mut package = self.package("Document properties")?;
+        properties::load_document_properties(&mut package)
+            .context("Failed to read document properties")
+    }
+
+    /// Get pivot table definitions (Xlsx only)
+    pub fn pivot_tables(&self) -> Result<Vec<PivotTable>> {
+        let mut package = self.package("Pivot tables")?;
+        pivot::load_pivot_tables(&mut package).context("Failed to read pivot tables")
+    }
+
+    /// Get pictures embedded in worksheet drawings (Xlsx only)
+    pub fn images(&self) -> Result<Vec<EmbeddedMedia>> {
+        let mut package = self.package("Embedded images")?;
+        media::load_images(&mut package).context("Failed to read embedded images")
+    }
+
+    /// Get pictures, charts, and embedded OLE objects on worksheets (Xlsx only)
+    pub fn media(&self) -> Result<Vec<EmbeddedMedia>> {
+        let mut package = self.package("Embedded media")?;
+        media::load_media(&mut package).context("Failed to read embedded media")
+    }
+
+    /// Get the rows and columns a sheet hides (Xlsx only)
+    pub fn hidden_cells(&self, sheet_name: &str) -> Result<HiddenCells> {
+        let mut package = self.package("Hidden rows and columns")?;
+        hidden::load_hidden_cells(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read hidden rows for sheet '{sheet_name}'"))
+    }
+
+    /// Get a sheet's frozen rows and columns; None if nothing is frozen (Xlsx only)
+    pub fn frozen_panes(&self, sheet_name: &str) -> Result<Option<FrozenPanes>> {
+        let mut package = self.package("Frozen panes")?;
+        panes::load_frozen_panes(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read frozen panes for sheet '{sheet_name}'"))
+    }
+
+    /// Get the fill and font colors of a sheet's styled cells by absolute (row, col) (Xlsx only)
+    pub fn cell_styles(&self, sheet_name: &str) -> Result<HashMap<(u32, u32), CellStyle>> {
+        let mut package = self.package("Cell styles")?;
+        styles::load_cell_styles(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read cell styles for sheet '{sheet_name}'"))
+    }
+
+    /// Get the horizontal alignment of a sheet's aligned cells by absolute (row, col)
+    /// (Xlsx only)
+    pub fn cell_alignments(
+        &self,
+        sheet_name: &str,
+    ) -> Result<HashMap<(u32, u32), HorizontalAlign>> {
+        let mut package = self.package("Cell alignments")?;
+        styles::load_cell_alignments(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read cell styles for sheet '{sheet_name}'"))
+    }
+
+    /// Get the alignment most of each column's cells from `first_row` down share, by
+    /// absolute column (Xlsx only)
+    pub fn column_alignments(
+        &self,
+        sheet_name: &str,
+        first_row: u32,
+    ) -> Result<HashMap<u32, HorizontalAlign>> {
+        let mut package = self.package("Cell alignments")?;
+        styles::load_column_alignments(&mut package, sheet_name, first_row)
+            .with_context(|| format!("Failed to read cell styles for sheet '{sheet_name}'"))
+    }
+
+    /// Get the cells of a sheet with a currency number format by absolute (row, col)
+    /// (Xlsx only)
+    pub fn currency_cells(&self, sheet_name: &str) -> Result<HashSet<(u32, u32)>> {
+        let mut package = self.package("Number formats")?;
+        styles::load_currency_cells(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read cell styles for sheet '{sheet_name}'"))
+    }
+
+    /// Get the columns most of whose cells from `first_row` down have a currency number
+    /// format, by absolute column (Xlsx only)
+    pub fn currency_columns(&self, sheet_name: &str, first_row: u32) -> Result<HashSet<u32>> {
+        let mut package = self.package("Number formats")?;
+        styles::load_currency_columns(&mut package, sheet_name, first_row)
+            .with_context(|| format!("Failed to read cell styles for sheet '{sheet_name}'"))
+    }
+
+    /// Get a sheet's conditional formatting rules, by priority (Xlsx only)
+    pub fn conditional_formats(&self, sheet_name: &str) -> Result<Vec<ConditionalFormat>> {
+        let mut package = self.package("Conditional formats")?;
+        conditional::load_conditional_formats(&mut package, sheet_name).with_context(|| {
+            format!("Failed to read conditional formatting for sheet '{sheet_name}'")
+        })
+    }
+
+    /// Get a sheet's cell hyperlinks (Xlsx only)
+    pub fn hyperlinks(&self, sheet_name: &str) -> Result<Vec<Hyperlink>> {
+        let mut package = self.package("Hyperlinks")?;
+        hyperlinks::load_hyperlinks(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read hyperlinks for sheet '{sheet_name}'"))
+    }
+
+    /// Get external workbook links, data connections, and query tables (Xlsx only)
+    pub fn external_references(&self) -> Result<Vec<ExternalReference>> {
+        let mut package = self.package("External links")?;
+        links::load_external_references(&mut package).context("Failed to read external links")
+    }
+
+    /// Files of the workbooks formulas refer to as `[1]`, `[2]`, ... (Xlsx only)
+    pub fn external_workbooks(&self) -> Result<Vec<String>> {
+        let mut package = self.package("External links")?;
+        links::load_external_workbooks(&mut package).context("Failed to read external links")
+    }
+
+    /// Get the OLE objects on sheets that show files from outside the workbook (Xlsx only)
+    pub fn linked_objects(&self) -> Result<Vec<LinkedObject>> {
+        let mut package = self.package("Linked objects")?;
+        links::load_linked_objects(&mut package).context("Failed to read OLE objects")
+    }
+
+    /// Get workbook structure/window protection; None if unprotected (Xlsx only)
+    pub fn workbook_protection(&self) -> Result<Option<WorkbookProtection>> {
+        let mut package = self.package("Protection details")?;
+        protection::load_workbook_protection(&mut package)
+            .context("Failed to read workbook protection")
+    }
+
+    /// Get a sheet's protection flags and unlocked cells (Xlsx only)
+    pub fn sheet_protection(&self, sheet_name: &str) -> Result<SheetProtection> {
+        let mut package = self.package("Protection details")?;
+        protection::load_sheet_protection(&mut package, sheet_name)
+            .with_context(|| format!("Failed to read protection for sheet '{sheet_name}'"))
+    }
+
+    /// Visibility of each sheet as (name, "visible" | "hidden" | "very hidden")
+    pub fn sheet_visibility(&self) -> Vec<(String, &'static str)> {
+        let sheets = match &self.source {
+            Source::Excel(sheets) => sheets,
+            Source::Delimited(sheet) => return vec![(sheet.name.clone(), "visible")],
+        };
+        sheets
+            .sheets_metadata()
+            .iter()
+            .map(|sheet| {
+                let visibility = match sheet.visible {
+                    SheetVisible::Visible => "visible",
+                    SheetVisible::Hidden => "hidden",
+                    SheetVisible::VeryHidden => "very hidden",
+                };
+                (sheet.name.clone(), visibility)
+            })
+            .collect()
+    }
+}
+
+/// Convert a 0-based column index to Excel letters (0 -> "A", 26 -> "AA")
+pub fn column_letter(col: usize) -> String {
+    let mut result = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        n -= 1;
+        result.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    result.chars().rev().collect()
+}
+
+/// A1-style reference to a cell on a sheet, quoting the sheet name as Excel does when it
+/// isn't a plain word ("Data!B2", "'Q1 Sales'!B2")
+pub fn cell_reference(sheet: &str, row: usize, col: usize) -> String {
+    let plain = !sheet.is_empty() && sheet.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let sheet = if plain {
+        sheet.to_string()
+    } else {
+        format!("'{}'", sheet.replace('\'', "''"))
+    };
+    format!("{sheet}!{}{}", column_letter(col), row + 1)
+}
+
+/// Convert Excel column letters to a 0-based index ("A" -> 0, "aa" -> 26)
+pub fn column_index(letters: &str) -> Option<usize> {
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let n = letters.chars().try_fold(0usize, |acc, c| {
+        acc.checked_mul(26)?
+            .checked_add((c.to_ascii_uppercase() as u8 - b'A') as usize + 1)
+    })?;
+    Some(n - 1)
 }
 
 /// Eagerly-loaded sheet data (loads all rows immediately)
@@ -103,42 +1496,108 @@ pub struct SheetData {
     pub formulas: Vec<Vec<Option<String>>>, // Parallel structure to rows with formulas
     pub width: usize,
     pub height: usize,
+    /// Absolute 0-based sheet row of the first data row (`rows[0]`)
+    pub first_row: usize,
+    /// Absolute 0-based sheet column of the first column, so letters like "C" map correctly
+    pub first_col: usize,
 }
 
-/// Lazy-loaded sheet data (loads rows on demand)
+/// Error positions and empty-cell count for a sheet's data rows
+#[derive(Debug, Clone, Default)]
+pub struct CellStats {
+    /// (row, col) of error cells in row-major order, 0-indexed data rows
+    pub errors: Vec<(usize, usize)>,
+    pub empty: usize,
+}
+
+/// Lazy-loaded sheet data (loads rows on demand); clones share the sheet's cells, so a
+/// clone can be read from another thread cheaply
+#[derive(Clone)]
 pub struct LazySheetData {
-    range: Range<Data>,
-    formula_range: Option<Range<String>>,
+    cells: LazyCells,
+    /// Whether the range's top row is the header (else headers are column letters)
+    has_header: bool,
     pub headers: Vec<String>,
     pub width: usize,
     pub height: usize,
 }
 
+/// Where a lazy sheet's rows are read from
+#[derive(Clone)]
+enum LazyCells {
+    /// Every cell of the table, header row included
+    Grid {
+        range: Arc<Range<Data>>,
+        formulas: Option<Arc<Range<String>>>,
+    },
+    /// Only the filled data cells, for huge mostly empty sheets
+    Sparse(Arc<SparseCells>),
+}
+
+/// A sheet's filled data cells in compressed sparse row form: data row `r`'s cells are
+/// `values[row_starts[r]..row_starts[r + 1]]`, in column order
+struct SparseCells {
+    /// Absolute 0-based (row, col) of the first data cell
+    origin: (usize, usize),
+    row_starts: Vec<usize>,
+    /// (column within the table, value)
+    values: Vec<(usize, CellValue)>,
+    /// (data row, column within the table, formula), in row-major order
+    formulas: Vec<(usize, usize, String)>,
+}
+
+impl SparseCells {
+    fn row(&self, row: usize) -> &[(usize, CellValue)] {
+        &self.values[self.row_starts[row]..self.row_starts[row + 1]]
+    }
+}
+
 impl LazySheetData {
     /// Extracts headers only; defers row loading
     pub fn from_range_with_formulas(
         range: Range<Data>,
         formula_range: Option<Range<String>>,
+    ) -> Self {
+        Self::from_range(range, formula_range, true)
+    }
+
+    /// Like [`Self::from_range_with_formulas`]; without a header row every row is data
+    pub fn from_range(
+        range: Range<Data>,
+        formula_range: Option<Range<String>>,
+        has_header: bool,
     ) -> Self {
         let (height, width) = range.get_size();
 
         // Only extract headers (first row) - don't load all data yet
-        let headers = if height > 0 {
-            range
-                .rows()
-                .next()
-                .map(|row| row.iter().map(SheetData::cell_to_string).collect())
-                .unwrap_or_default()
-        } else {
-            vec![]
-        };
+        let headers = SheetData::headers_of(&range, has_header);
 
         Self {
-            range,
-            formula_range,
+            cells: LazyCells::Grid {
+                range: Arc::new(range),
+                formulas: formula_range.map(Arc::new),
+            },
+            has_header,
             headers,
             width,
-            height: height.saturating_sub(1), // Don't count header row
+            height: height.saturating_sub(has_header as usize), // Don't count header row
+        }
+    }
+
+    /// Whether only the filled cells are kept, as for huge mostly empty sheets; rows are
+    /// then filled in with empty cells as they are read
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.cells, LazyCells::Sparse(_))
+    }
+
+    /// Absolute 0-based (row, col) of the first data cell
+    pub fn origin(&self) -> (usize, usize) {
+        match &self.cells {
+            LazyCells::Grid { range, .. } => range
+                .start()
+                .map(|(row, col)| (row as usize + self.has_header as usize, col as usize))
+                .unwrap_or((0, 0)),
+            LazyCells::Sparse(sparse) => sparse.origin,
         }
     }
 
@@ -149,15 +1608,7 @@ impl LazySheetData {
         count: usize,
     ) -> (Vec<Vec<CellValue>>, Vec<Vec<Option<String>>>) {
         let end = (start + count).min(self.height);
-
-        // Extract requested rows (skip header + start rows, take count)
-        let rows: Vec<Vec<CellValue>> = self
-            .range
-            .rows()
-            .skip(1 + start) // Skip header + start offset
-            .take(end - start)
-            .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect())
-            .collect();
+        let rows = self.get_values(start, count);
 
         // Extract formulas for requested rows
         let formulas = self.get_formulas_for_range(start, end);
@@ -165,30 +1616,71 @@ impl LazySheetData {
         (rows, formulas)
     }
 
+    /// Zero-indexed row range without formulas (cheaper when only values are needed)
+    pub fn get_values(&self, start: usize, count: usize) -> Vec<Vec<CellValue>> {
+        let end = (start + count).min(self.height);
+        let range = match &self.cells {
+            LazyCells::Grid { range, .. } => range,
+            LazyCells::Sparse(sparse) => {
+                return (start.min(end)..end)
+                    .map(|row| {
+                        let mut values = vec![CellValue::Empty; self.width];
+                        for (col, value) in sparse.row(row) {
+                            values[*col] = value.clone();
+                        }
+                        values
+                    })
+                    .collect();
+            }
+        };
+
+        // Extract requested rows (skip header + start rows, take count)
+        range
+            .rows()
+            .skip(self.has_header as usize + start) // Skip header + start offset
+            .take(end.saturating_sub(start))
+            .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect())
+            .collect()
+    }
+
     fn get_formulas_for_range(&self, start: usize, end: usize) -> Vec<Vec<Option<String>>> {
-        if let Some(ref formula_range) = self.formula_range {
+        let formula_range = match &self.cells {
+            LazyCells::Grid { formulas, .. } => formulas.as_ref(),
+            LazyCells::Sparse(sparse) => {
+                let mut formula_grid = vec![vec![None; self.width]; end.saturating_sub(start)];
+                let first = sparse.formulas.partition_point(|(row, ..)| *row < start);
+                let last = sparse.formulas.partition_point(|(row, ..)| *row < end);
+                for (row, col, formula) in &sparse.formulas[first..last.max(first)] {
+                    formula_grid[row - start][*col] = Some(formula.clone());
+                }
+                return formula_grid;
+            }
+        };
+        if let Some(formula_range) = formula_range {
             let formula_start = formula_range.start().unwrap_or((0, 0));
-            let total_height = self.height + 1; // Include header in total
+            let (first_row, first_col) = self.origin();
 
             // Create formula grid only for requested rows
             let mut formula_grid: Vec<Vec<Option<String>>> =
-                vec![vec![None; self.width]; end - start];
+                vec![vec![None; self.width]; end.saturating_sub(start)];
 
-            // Populate formulas at their absolute positions
+            // Populate formulas at their positions relative to the data rows
             for (row_offset, formula_row) in formula_range.rows().enumerate() {
                 let absolute_row = formula_start.0 as usize + row_offset;
 
-                if absolute_row > 0 && absolute_row <= total_height {
-                    let data_row_idx = absolute_row - 1; // Convert to 0-based data row index
-
+                if let Some(data_row_idx) = absolute_row.checked_sub(first_row) {
                     // Only process if this row is in our requested range
                     if data_row_idx >= start && data_row_idx < end {
                         let result_idx = data_row_idx - start; // Index in result array
 
                         for (col_offset, formula_str) in formula_row.iter().enumerate() {
-                            let absolute_col = formula_start.1 as usize + col_offset;
-                            if absolute_col < self.width && !formula_str.is_empty() {
-                                formula_grid[result_idx][absolute_col] = Some(formula_str.clone());
+                            let grid_col =
+                                (formula_start.1 as usize + col_offset).checked_sub(first_col);
+                            if let Some(grid_col) = grid_col
+                                && grid_col < self.width
+                                && !formula_str.is_empty()
+                            {
+                                formula_grid[result_idx][grid_col] = Some(formula_str.clone());
                             }
                         }
                     }
@@ -198,39 +1690,133 @@ impl LazySheetData {
             formula_grid
         } else {
             // No formulas available
-            vec![vec![None; self.width]; end - start]
+            vec![vec![None; self.width]; end.saturating_sub(start)]
         }
     }
 
+    /// Counts error and empty cells without converting rows
+    pub fn cell_stats(&self) -> CellStats {
+        let mut stats = CellStats::default();
+        let range = match &self.cells {
+            LazyCells::Grid { range, .. } => range,
+            LazyCells::Sparse(sparse) => {
+                stats.empty = self.width * self.height - sparse.values.len();
+                for row_idx in 0..self.height {
+                    for (col_idx, cell) in sparse.row(row_idx) {
+                        match cell {
+                            CellValue::Error(_) => stats.errors.push((row_idx, *col_idx)),
+                            CellValue::String(s) if s.is_empty() => stats.empty += 1,
+                            _ => {}
+                        }
+                    }
+                }
+                return stats;
+            }
+        };
+        for (row_idx, row) in range.rows().skip(self.has_header as usize).enumerate() {
+            for (col_idx, cell) in row.iter().enumerate() {
+                match cell {
+                    Data::Error(_) => stats.errors.push((row_idx, col_idx)),
+                    Data::Empty => stats.empty += 1,
+                    Data::String(s) if s.is_empty() => stats.empty += 1,
+                    _ => {}
+                }
+            }
+        }
+        stats
+    }
+
     /// Consumes lazy data and loads all rows into memory
     #[allow(clippy::wrong_self_convention)]
     pub fn to_sheet_data(self) -> SheetData {
-        SheetData::from_range_with_formulas(self.range, self.formula_range)
+        match self.cells {
+            LazyCells::Grid { range, formulas } => SheetData::from_range(
+                Arc::unwrap_or_clone(range),
+                formulas.map(Arc::unwrap_or_clone),
+                self.has_header,
+            ),
+            LazyCells::Sparse(ref sparse) => {
+                let (first_row, first_col) = sparse.origin;
+                let (rows, formulas) = self.get_rows(0, self.height);
+                SheetData {
+                    rows,
+                    formulas,
+                    first_row,
+                    first_col,
+                    headers: self.headers,
+                    width: self.width,
+                    height: self.height,
+                }
+            }
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Excel's spelling of an error value, as the cell shows it
+pub fn error_name(error: &CellErrorType) -> &'static str {
+    match error {
+        CellErrorType::Div0 => "#DIV/0!",
+        CellErrorType::NA => "#N/A",
+        CellErrorType::Name => "#NAME?",
+        CellErrorType::Null => "#NULL!",
+        CellErrorType::Num => "#NUM!",
+        CellErrorType::Ref => "#REF!",
+        CellErrorType::Value => "#VALUE!",
+        CellErrorType::GettingData => "#GETTING_DATA",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum CellValue {
     Empty,
     String(String),
     Int(i64),
     Float(f64),
     Bool(bool),
-    Error(String),
+    Error(String), // Excel's spelling, e.g. "#DIV/0!"
     DateTime(f64), // Excel datetime as float
+    Time(f64),     // Time of day as a fraction of a day
+    Duration(f64), // Elapsed time in days, e.g. a [h]:mm:ss cell
 }
 
 impl CellValue {
-    #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         matches!(self, CellValue::Empty)
     }
 
-    #[allow(dead_code)]
     pub fn is_numeric(&self) -> bool {
         matches!(self, CellValue::Int(_) | CellValue::Float(_))
     }
 
+    /// The raw value, with date-times, floats, empty cells, and errors written as
+    /// `options` say
+    pub fn to_export_string(&self, options: &ExportOptions) -> String {
+        match self {
+            CellValue::Empty => options.empty.clone().unwrap_or_default(),
+            CellValue::Error(_) if options.errors_as_empty => {
+                options.empty.clone().unwrap_or_default()
+            }
+            CellValue::Float(val) => locale::export_float(*val, options.scientific),
+            CellValue::DateTime(serial) => options
+                .dates
+                .format_serial(*serial)
+                .unwrap_or_else(|| self.to_raw_string()),
+            _ => self.to_raw_string(),
+        }
+    }
+
+    /// The value as shown to people, with `locale`'s separators, precision, and date
+    /// pattern; `to_string()` uses the default US style
+    pub fn to_display_string(&self, locale: &NumberLocale) -> String {
+        struct Shown<'a>(&'a CellValue, &'a NumberLocale);
+        impl std::fmt::Display for Shown<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_with(f, self.1)
+            }
+        }
+        Shown(self, locale).to_string()
+    }
+
     /// Returns unformatted value (for export/clipboard)
     pub fn to_raw_string(&self) -> String {
         match self {
@@ -245,7 +1831,9 @@ impl CellValue {
                 }
             }
             CellValue::Bool(b) => b.to_string(),
-            CellValue::Error(e) => format!("#{e}"),
+            CellValue::Error(e) => e.clone(),
+            CellValue::Time(t) => clock(*t, false),
+            CellValue::Duration(d) => clock(*d, true),
             CellValue::DateTime(dt) => {
                 let days = dt.floor() as i64;
                 // Excel epoch: December 31, 1899 (Excel serial 0)
@@ -275,6 +1863,58 @@ impl CellValue {
     }
 }
 
+/// A fraction of a day as "HH:MM:SS"; with `elapsed`, hours go past 24 ("37:30:00") and
+/// negative values get a sign
+fn clock(days: f64, elapsed: bool) -> String {
+    let total_seconds = (days * 86400.0).round() as i64;
+    let sign = if elapsed && total_seconds < 0 {
+        "-"
+    } else {
+        ""
+    };
+    let seconds = if elapsed {
+        total_seconds.abs()
+    } else {
+        total_seconds.rem_euclid(86400)
+    };
+    format!(
+        "{sign}{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Days in an ISO 8601 duration such as "PT12H30M00S" or "P1DT2H" (as ODS writes them)
+fn parse_iso_duration(text: &str) -> Option<f64> {
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let (date_part, time_part) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut seconds = 0.0;
+    for (part, units) in [
+        (date_part, &[('W', 604_800.0), ('D', 86_400.0)][..]),
+        (time_part, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)][..]),
+    ] {
+        let mut number = String::new();
+        for ch in part.chars() {
+            if ch.is_ascii_digit() || ch == '.' {
+                number.push(ch);
+                continue;
+            }
+            let &(_, unit) = units.iter().find(|(letter, _)| *letter == ch)?;
+            seconds += number.parse::<f64>().ok()? * unit;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    Some(sign * seconds / 86_400.0)
+}
+
 /// Excel Table data
 #[derive(Debug, Clone)]
 pub struct TableData {
@@ -282,18 +1922,31 @@ pub struct TableData {
     pub sheet_name: String,
     pub headers: Vec<String>,
     pub rows: Vec<Vec<CellValue>>,
+    /// Absolute 0-based (row, col) of the first data cell, below the header; None when
+    /// the table has no data rows
+    pub data_start: Option<(u32, u32)>,
 }
 
 impl TableData {
     pub fn from_calamine_table(table: Table<Data>) -> Self {
+        Self::from_table_in(table, None)
+    }
+
+    /// Like [`Self::from_calamine_table`], reading date serials in a forced date system
+    fn from_table_in(table: Table<Data>, date_system: Option<DateSystem>) -> Self {
         let name = table.name().to_string();
         let sheet_name = table.sheet_name().to_string();
         let headers = table.columns().to_vec();
+        let data_start = table.data().start();
 
         let rows: Vec<Vec<CellValue>> = table
             .data()
             .rows()
-            .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect())
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell_value_in(cell, date_system))
+                    .collect()
+            })
             .collect();
 
         Self {
@@ -301,65 +1954,37 @@ impl TableData {
             sheet_name,
             headers,
             rows,
+            data_start,
         }
     }
 }
 
 impl std::fmt::Display for CellValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, locale::default_locale())
+    }
+}
+
+impl CellValue {
+    fn fmt_with(&self, f: &mut std::fmt::Formatter<'_>, locale: &NumberLocale) -> std::fmt::Result {
         match self {
             CellValue::Empty => write!(f, ""),
             CellValue::String(s) => write!(f, "{s}"),
             CellValue::Int(i) => {
                 // Format integers with thousand separators
-                let s = i.to_string();
-                let negative = s.starts_with('-');
-                let digits: String = s.trim_start_matches('-').chars().collect();
-                let mut result = String::new();
-                for (idx, ch) in digits.chars().rev().enumerate() {
-                    if idx > 0 && idx % 3 == 0 {
-                        result.push(',');
-                    }
-                    result.push(ch);
-                }
-                if negative {
-                    result.push('-');
-                }
-                write!(f, "{}", result.chars().rev().collect::<String>())
+                write!(f, "{}", locale.number(&i.to_string(), None))
             }
             CellValue::Float(val) => {
                 // Format floats with thousand separators
-                let formatted = if val.fract() == 0.0 {
-                    format!("{val:.0}")
-                } else {
-                    format!("{val:.2}")
-                };
-                let parts: Vec<&str> = formatted.split('.').collect();
-                let int_part = parts[0];
-                let negative = int_part.starts_with('-');
-                let digits: String = int_part.trim_start_matches('-').chars().collect();
-                let mut result = String::new();
-                for (idx, ch) in digits.chars().rev().enumerate() {
-                    if idx > 0 && idx % 3 == 0 {
-                        result.push(',');
-                    }
-                    result.push(ch);
-                }
-                if negative {
-                    result.push('-');
-                }
-                let int_formatted: String = result.chars().rev().collect();
-                if parts.len() > 1 {
-                    write!(f, "{}.{}", int_formatted, parts[1])
-                } else {
-                    write!(f, "{}", int_formatted)
-                }
+                write!(f, "{}", locale.float(*val))
             }
             CellValue::Bool(b) => {
                 // Use lowercase for booleans
                 write!(f, "{}", if *b { "true" } else { "false" })
             }
-            CellValue::Error(e) => write!(f, "ERROR: {e}"),
+            CellValue::Error(e) => write!(f, "{e}"),
+            CellValue::Time(t) => write!(f, "{}", clock(*t, false)),
+            CellValue::Duration(d) => write!(f, "{}", clock(*d, true)),
             CellValue::DateTime(d) => {
                 // Excel dates are days since December 31, 1899 (serial 0)
                 // Excel has a leap year bug where 1900 is incorrectly treated as a leap year
@@ -373,6 +1998,7 @@ impl std::fmt::Display for CellValue {
                 let adjusted_days = if days > 60 { days - 1 } else { days };
 
                 if let Some(date) = excel_epoch.checked_add_signed(Duration::days(adjusted_days)) {
+                    let date = locale.date(date);
                     // Check if there's a time component
                     let frac = d.fract();
                     if frac.abs() > 0.000001 {
@@ -398,24 +2024,27 @@ impl SheetData {
     pub fn from_range_with_formulas(
         range: Range<Data>,
         formula_range: Option<Range<String>>,
+    ) -> Self {
+        Self::from_range(range, formula_range, true)
+    }
+
+    /// Like [`Self::from_range_with_formulas`]; without a header row every row is data
+    /// and the columns are named by letter
+    pub fn from_range(
+        range: Range<Data>,
+        formula_range: Option<Range<String>>,
+        has_header: bool,
     ) -> Self {
         let (height, width) = range.get_size();
+        let data_start = range.start().unwrap_or((0, 0));
+        let header_rows = has_header as usize;
 
-        // Extract headers from first row if it exists
-        let headers = if height > 0 {
-            range
-                .rows()
-                .next()
-                .map(|row| row.iter().map(Self::cell_to_string).collect())
-                .unwrap_or_default()
-        } else {
-            vec![]
-        };
+        let headers = Self::headers_of(&range, has_header);
 
         // Extract data rows (skip first row as headers)
         let rows: Vec<Vec<CellValue>> = range
             .rows()
-            .skip(1)
+            .skip(header_rows)
             .map(|row| row.iter().map(Self::datatype_to_cellvalue).collect())
             .collect();
 
@@ -427,16 +2056,24 @@ impl SheetData {
             // Create empty formula structure matching data dimensions
             let mut formula_grid: Vec<Vec<Option<String>>> = vec![vec![None; width]; height];
 
-            // Populate formulas at their absolute positions
+            // Populate formulas at their positions relative to the data range
             for (row_offset, formula_row) in formula_range.rows().enumerate() {
-                let absolute_row = formula_start.0 as usize + row_offset;
-                if absolute_row > 0 && absolute_row <= height {
+                let Some(grid_row) =
+                    (formula_start.0 as usize + row_offset).checked_sub(data_start.0 as usize)
+                else {
+                    continue;
+                };
+                if grid_row >= header_rows && grid_row < height {
                     // Skip header row (row 0)
-                    let data_row_idx = absolute_row - 1; // Convert to 0-based data row index
+                    let data_row_idx = grid_row - header_rows; // Convert to 0-based data row index
                     for (col_offset, formula_str) in formula_row.iter().enumerate() {
-                        let absolute_col = formula_start.1 as usize + col_offset;
-                        if absolute_col < width && !formula_str.is_empty() {
-                            formula_grid[data_row_idx][absolute_col] = Some(formula_str.clone());
+                        let grid_col = (formula_start.1 as usize + col_offset)
+                            .checked_sub(data_start.1 as usize);
+                        if let Some(grid_col) = grid_col
+                            && grid_col < width
+                            && !formula_str.is_empty()
+                        {
+                            formula_grid[data_row_idx][grid_col] = Some(formula_str.clone());
                         }
                     }
                 }
@@ -446,11 +2083,11 @@ impl SheetData {
             // We already handled header row when populating, so just take the data rows
             formula_grid
                 .into_iter()
-                .take(height.saturating_sub(1))
+                .take(height.saturating_sub(header_rows))
                 .collect()
         } else {
             // No formulas available, create empty parallel structure
-            vec![vec![None; width]; height.saturating_sub(1)]
+            vec![vec![None; width]; height.saturating_sub(header_rows)]
         };
 
         Self {
@@ -458,8 +2095,25 @@ impl SheetData {
             rows,
             formulas,
             width,
-            height: height.saturating_sub(1), // Don't count header row
+            height: height.saturating_sub(header_rows), // Don't count header row
+            first_row: data_start.0 as usize + header_rows,
+            first_col: data_start.1 as usize,
+        }
+    }
+
+    /// The range's top row as text, or the columns' letters when there is no header row
+    fn headers_of(range: &Range<Data>, has_header: bool) -> Vec<String> {
+        if !has_header {
+            let first_col = range.start().map_or(0, |(_, col)| col as usize);
+            return (0..range.width())
+                .map(|col| column_letter(first_col + col))
+                .collect();
         }
+        range
+            .rows()
+            .next()
+            .map(|row| row.iter().map(Self::cell_to_string).collect())
+            .unwrap_or_default()
     }
 
     fn cell_to_string(cell: &Data) -> String {
@@ -475,7 +2129,7 @@ impl SheetData {
                 }
             }
             Data::Bool(b) => b.to_string(),
-            Data::Error(e) => format!("ERROR: {e:?}"),
+            Data::Error(e) => error_name(e).to_string(),
             Data::DateTime(d) => format!("Date({})", d.as_f64()),
             Data::DateTimeIso(s) => s.clone(),
             Data::DurationIso(s) => s.clone(),
@@ -489,10 +2143,548 @@ impl SheetData {
             Data::Int(i) => CellValue::Int(*i),
             Data::Float(f) => CellValue::Float(*f),
             Data::Bool(b) => CellValue::Bool(*b),
-            Data::Error(e) => CellValue::Error(format!("{e:?}")),
-            Data::DateTime(d) => CellValue::DateTime(d.as_f64()),
+            Data::Error(e) => CellValue::Error(error_name(e).to_string()),
+            Data::DateTime(d) if d.is_duration() => CellValue::Duration(d.as_f64()),
+            // Serials below 1 have no date part: a time-of-day cell
+            Data::DateTime(d) if (0.0..1.0).contains(&d.as_f64()) => CellValue::Time(d.as_f64()),
+            Data::DateTime(d) => CellValue::DateTime(date_serial(d)),
             Data::DateTimeIso(s) => CellValue::String(s.clone()),
-            Data::DurationIso(s) => CellValue::String(s.clone()),
+            Data::DurationIso(s) => parse_iso_duration(s)
+                .map(CellValue::Duration)
+                .unwrap_or_else(|| CellValue::String(s.clone())),
+        }
+    }
+
+    /// Counts error and empty cells
+    pub fn cell_stats(&self) -> CellStats {
+        let mut stats = CellStats::default();
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            for (col_idx, cell) in row.iter().enumerate() {
+                match cell {
+                    CellValue::Error(_) => stats.errors.push((row_idx, col_idx)),
+                    CellValue::Empty => stats.empty += 1,
+                    CellValue::String(s) if s.is_empty() => stats.empty += 1,
+                    _ => {}
+                }
+            }
+        }
+        stats
+    }
+
+    /// Replace linked cells' text with their link target, or both (see [`HyperlinkMode`]).
+    /// Must run before rows or columns are rearranged, as links are matched by position.
+    pub fn apply_hyperlinks(&mut self, links: &[Hyperlink], mode: HyperlinkMode) {
+        for link in links {
+            // Row 0 is the header row, the one above the data
+            let (Some(row), Some(col)) = (
+                (link.row as usize + 1).checked_sub(self.first_row),
+                (link.col as usize).checked_sub(self.first_col),
+            ) else {
+                continue;
+            };
+            if row == 0 {
+                if let Some(header) = self.headers.get_mut(col) {
+                    *header = mode.render(header, &link.target);
+                }
+            } else if let Some(cell) = self.rows.get_mut(row - 1).and_then(|r| r.get_mut(col)) {
+                *cell = CellValue::String(mode.render(&cell.to_raw_string(), &link.target));
+            }
+        }
+    }
+
+    /// Remove data rows the sheet hides; returns how many were removed.
+    /// Like `apply_hyperlinks`, this matches by position and must run before sorting.
+    pub fn remove_hidden_rows(&mut self, hidden: &HiddenCells) -> usize {
+        let keep: Vec<usize> = (0..self.rows.len())
+            .filter(|&r| !hidden.rows.contains(&((self.first_row + r) as u32)))
+            .collect();
+        let removed = self.rows.len() - keep.len();
+        if removed > 0 {
+            self.rows = keep.iter().map(|&i| self.rows[i].clone()).collect();
+            self.formulas = keep
+                .iter()
+                .map(|&i| self.formulas.get(i).cloned().unwrap_or_default())
+                .collect();
+            self.height = self.rows.len();
+        }
+        removed
+    }
+
+    /// Remove columns the sheet hides; returns how many were removed.
+    /// Column letters no longer line up afterwards, so this runs after `--sort`.
+    pub fn remove_hidden_columns(&mut self, hidden: &HiddenCells) -> usize {
+        let keep: Vec<usize> = (0..self.width)
+            .filter(|&c| !hidden.cols.contains(&((self.first_col + c) as u32)))
+            .collect();
+        let removed = self.width - keep.len();
+        if removed > 0 {
+            project_rows(&mut self.headers, &mut self.rows, &keep);
+            for formula_row in &mut self.formulas {
+                *formula_row = keep
+                    .iter()
+                    .map(|&i| formula_row.get(i).cloned().flatten())
+                    .collect();
+            }
+            self.width = keep.len();
+        }
+        removed
+    }
+
+    /// Mask, drop, or pseudonymize the named columns, including their formulas
+    pub fn redact_columns(&mut self, columns: &[String], mode: &RedactMode) -> Result<()> {
+        let indices = redaction_indices(&self.headers, columns)?;
+        redact_rows(&mut self.headers, &mut self.rows, &indices, mode);
+
+        let drop = *mode == RedactMode::Drop;
+        for formula_row in &mut self.formulas {
+            for &idx in indices.iter().rev() {
+                if idx < formula_row.len() {
+                    if drop {
+                        formula_row.remove(idx);
+                    } else {
+                        formula_row[idx] = None;
+                    }
+                }
+            }
+        }
+        if drop {
+            self.width = self.width.saturating_sub(indices.len());
+        }
+        Ok(())
+    }
+
+    /// Keep `n` evenly spaced rows (first row always included)
+    pub fn sample_rows(&mut self, n: usize) {
+        let keep = sample_indices(self.rows.len(), n);
+        self.rows = keep.iter().map(|&i| self.rows[i].clone()).collect();
+        self.formulas = keep
+            .iter()
+            .map(|&i| self.formulas.get(i).cloned().unwrap_or_default())
+            .collect();
+        self.height = self.rows.len();
+    }
+
+    /// Normalize values so exports are byte-stable (see `canonical_cell`)
+    pub fn canonicalize(&mut self) {
+        canonicalize_rows(&mut self.headers, &mut self.rows);
+    }
+
+    /// Keep only the selected columns, in selection order (see `column_selection`)
+    pub fn select_columns(&mut self, columns: &[String]) -> Result<()> {
+        let indices = column_selection(&self.headers, self.first_col, columns)?;
+        project_rows(&mut self.headers, &mut self.rows, &indices);
+        for formula_row in &mut self.formulas {
+            *formula_row = indices
+                .iter()
+                .map(|&i| formula_row.get(i).cloned().flatten())
+                .collect();
+        }
+        self.width = indices.len();
+        Ok(())
+    }
+
+    /// Sort rows (and their formulas) by `--sort` keys (see `sort_keys`)
+    pub fn sort_rows(&mut self, keys: &[String]) -> Result<()> {
+        let order = sorted_order(&self.headers, self.first_col, &self.rows, keys)?;
+        self.rows = order.iter().map(|&i| self.rows[i].clone()).collect();
+        self.formulas = order
+            .iter()
+            .map(|&i| self.formulas.get(i).cloned().unwrap_or_default())
+            .collect();
+        Ok(())
+    }
+
+    /// Replace the rows with one per group of `group_by` values and the aggregated
+    /// `values` columns (see `aggregate::pivot`)
+    pub fn pivot(&mut self, group_by: &[String], values: &[String]) -> Result<()> {
+        let summary =
+            aggregate::pivot(&self.headers, self.first_col, &self.rows, group_by, values)?;
+        self.show_summary(summary);
+        Ok(())
+    }
+
+    /// Replace the rows with a count of each value of `column` (see `aggregate::value_counts`)
+    pub fn count_values(&mut self, column: &str) -> Result<()> {
+        let summary = aggregate::value_counts(&self.headers, self.first_col, &self.rows, column)?;
+        self.show_summary(summary);
+        Ok(())
+    }
+
+    /// Add a column of bars after each of `columns`, for terminal display (see
+    /// `display::insert_bar_columns`), with the scale in `locale`
+    pub fn add_bars(&mut self, columns: &[String], locale: &NumberLocale) -> Result<()> {
+        let added = display::insert_bar_columns(
+            &mut self.headers,
+            &mut self.rows,
+            self.first_col,
+            columns,
+            locale,
+        )?;
+        for formula_row in &mut self.formulas {
+            for &col in &added {
+                if col <= formula_row.len() {
+                    formula_row.insert(col, None);
+                }
+            }
+        }
+        self.width = self.headers.len();
+        Ok(())
+    }
+
+    fn show_summary(&mut self, (headers, rows): (Vec<String>, Vec<Vec<CellValue>>)) {
+        self.formulas = vec![vec![None; headers.len()]; rows.len()];
+        self.width = headers.len();
+        self.height = rows.len();
+        self.headers = headers;
+        self.rows = rows;
+        // The result no longer lines up with the sheet's cells
+        self.first_row = 0;
+        self.first_col = 0;
+    }
+}
+
+impl TableData {
+    /// Mask, drop, or pseudonymize the named columns
+    pub fn redact_columns(&mut self, columns: &[String], mode: &RedactMode) -> Result<()> {
+        let indices = redaction_indices(&self.headers, columns)?;
+        redact_rows(&mut self.headers, &mut self.rows, &indices, mode);
+        Ok(())
+    }
+
+    /// Keep `n` evenly spaced rows (first row always included)
+    pub fn sample_rows(&mut self, n: usize) {
+        let keep = sample_indices(self.rows.len(), n);
+        self.rows = keep.iter().map(|&i| self.rows[i].clone()).collect();
+    }
+
+    /// Normalize values so exports are byte-stable (see `canonical_cell`)
+    pub fn canonicalize(&mut self) {
+        canonicalize_rows(&mut self.headers, &mut self.rows);
+    }
+
+    /// Keep only the selected columns, in selection order (see `column_selection`)
+    pub fn select_columns(&mut self, columns: &[String]) -> Result<()> {
+        let indices = column_selection(&self.headers, 0, columns)?;
+        project_rows(&mut self.headers, &mut self.rows, &indices);
+        Ok(())
+    }
+
+    /// Sort rows by `--sort` keys (see `sort_keys`)
+    pub fn sort_rows(&mut self, keys: &[String]) -> Result<()> {
+        let order = sorted_order(&self.headers, 0, &self.rows, keys)?;
+        self.rows = order.iter().map(|&i| self.rows[i].clone()).collect();
+        Ok(())
+    }
+
+    /// Replace the rows with one per group of `group_by` values and the aggregated
+    /// `values` columns (see `aggregate::pivot`)
+    pub fn pivot(&mut self, group_by: &[String], values: &[String]) -> Result<()> {
+        (self.headers, self.rows) =
+            aggregate::pivot(&self.headers, 0, &self.rows, group_by, values)?;
+        self.data_start = None;
+        Ok(())
+    }
+
+    /// Replace the rows with a count of each value of `column` (see `aggregate::value_counts`)
+    pub fn count_values(&mut self, column: &str) -> Result<()> {
+        (self.headers, self.rows) = aggregate::value_counts(&self.headers, 0, &self.rows, column)?;
+        self.data_start = None;
+        Ok(())
+    }
+
+    /// Add a column of bars after each of `columns`, for terminal display, with the scale
+    /// in `locale`
+    pub fn add_bars(&mut self, columns: &[String], locale: &NumberLocale) -> Result<()> {
+        display::insert_bar_columns(&mut self.headers, &mut self.rows, 0, columns, locale)?;
+        Ok(())
+    }
+}
+
+// ===== Column selection =====
+
+/// Resolves `--columns` entries to column indices, in the order given.
+/// Each entry is a header name (case-insensitive), a column letter, or a
+/// letter range like "E:G"; header names win over letters, so a column
+/// titled "ID" is picked by name rather than as column 238. Letters are
+/// sheet columns; `first_col` is the sheet column of `headers[0]`.
+pub fn column_selection(
+    headers: &[String],
+    first_col: usize,
+    columns: &[String],
+) -> Result<Vec<usize>> {
+    let letter = |spec: &str| -> Result<usize> {
+        column_index(spec.trim())
+            .and_then(|idx| idx.checked_sub(first_col))
+            .filter(|&idx| idx < headers.len())
+            .with_context(|| {
+                format!(
+                    "Column '{spec}' not found: not a header name or a column letter in {}-{}. Available columns: {}",
+                    column_letter(first_col),
+                    column_letter((first_col + headers.len()).saturating_sub(1)),
+                    headers.join(", ")
+                )
+            })
+    };
+
+    let mut indices = Vec::new();
+    for spec in columns {
+        let spec = spec.trim();
+        if let Some(idx) = headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(spec))
+        {
+            indices.push(idx);
+        } else if let Some((start, end)) = spec.split_once(':') {
+            let (start, end) = (letter(start)?, letter(end)?);
+            if start <= end {
+                indices.extend(start..=end);
+            } else {
+                indices.extend((end..=start).rev());
+            }
+        } else {
+            indices.push(letter(spec)?);
+        }
+    }
+    Ok(indices)
+}
+
+/// Resolves a single column, given like a `--columns` entry (no letter ranges)
+pub fn find_column(headers: &[String], first_col: usize, spec: &str) -> Result<usize> {
+    match column_selection(headers, first_col, &[spec.to_string()])?[..] {
+        [idx] => Ok(idx),
+        _ => anyhow::bail!("'{spec}' must name a single column, not a range"),
+    }
+}
+
+/// Rebuilds headers and rows from the given column indices (short rows pad with empty cells)
+fn project_rows(headers: &mut Vec<String>, rows: &mut [Vec<CellValue>], indices: &[usize]) {
+    *headers = indices
+        .iter()
+        .map(|&i| headers.get(i).cloned().unwrap_or_default())
+        .collect();
+    for row in rows.iter_mut() {
+        *row = indices
+            .iter()
+            .map(|&i| row.get(i).cloned().unwrap_or(CellValue::Empty))
+            .collect();
+    }
+}
+
+// ===== Sorting =====
+
+/// Parses `--sort` entries ("Name", "C:desc", "Total:asc") into (column, descending)
+fn sort_keys(headers: &[String], first_col: usize, keys: &[String]) -> Result<Vec<(usize, bool)>> {
+    keys.iter()
+        .map(|key| {
+            let (column, descending) = match key.trim().rsplit_once(':') {
+                Some((column, dir)) if dir.eq_ignore_ascii_case("asc") => (column, false),
+                Some((column, dir)) if dir.eq_ignore_ascii_case("desc") => (column, true),
+                _ => (key.trim(), false),
+            };
+            match column_selection(headers, first_col, &[column.to_string()])?[..] {
+                [idx] => Ok((idx, descending)),
+                _ => anyhow::bail!("Sort key '{key}' must name a single column"),
+            }
+        })
+        .collect()
+}
+
+/// Row indices in sorted order; stable, so ties keep their original order
+fn sorted_order(
+    headers: &[String],
+    first_col: usize,
+    rows: &[Vec<CellValue>],
+    keys: &[String],
+) -> Result<Vec<usize>> {
+    let keys = sort_keys(headers, first_col, keys)?;
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys.iter()
+            .map(|&(col, descending)| compare_cells(rows[a].get(col), rows[b].get(col), descending))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    Ok(order)
+}
+
+/// Excel's sort order: numbers and dates, then text, booleans, errors.
+/// Empty cells always go last, whichever the direction.
+pub(crate) fn compare_cells(
+    a: Option<&CellValue>,
+    b: Option<&CellValue>,
+    descending: bool,
+) -> Ordering {
+    fn rank(cell: Option<&CellValue>) -> u8 {
+        match cell {
+            Some(
+                CellValue::Int(_)
+                | CellValue::Float(_)
+                | CellValue::DateTime(_)
+                | CellValue::Time(_)
+                | CellValue::Duration(_),
+            ) => 0,
+            Some(CellValue::String(s)) if !s.is_empty() => 1,
+            Some(CellValue::Bool(_)) => 2,
+            Some(CellValue::Error(_)) => 3,
+            _ => 4,
+        }
+    }
+    fn number(cell: &CellValue) -> f64 {
+        match cell {
+            CellValue::Int(i) => *i as f64,
+            CellValue::Float(f)
+            | CellValue::DateTime(f)
+            | CellValue::Time(f)
+            | CellValue::Duration(f) => *f,
+            _ => 0.0,
+        }
+    }
+
+    let (rank_a, rank_b) = (rank(a), rank(b));
+    if rank_a == 4 || rank_b == 4 {
+        return rank_a.cmp(&rank_b);
+    }
+    let ordering = match (a, b) {
+        _ if rank_a != rank_b => rank_a.cmp(&rank_b),
+        (Some(CellValue::String(x)), Some(CellValue::String(y))) => x
+            .to_lowercase()
+            .cmp(&y.to_lowercase())
+            .then_with(|| x.cmp(y)),
+        (Some(CellValue::Bool(x)), Some(CellValue::Bool(y))) => x.cmp(y),
+        (Some(CellValue::Error(x)), Some(CellValue::Error(y))) => x.cmp(y),
+        (Some(x), Some(y)) => number(x).total_cmp(&number(y)),
+        _ => Ordering::Equal,
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+// ===== Redaction and sampling =====
+
+/// Replacement text for masked cells
+const REDACTED: &str = "[REDACTED]";
+
+/// How redacted columns are hidden
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactMode {
+    /// Replace values with a fixed marker
+    Mask,
+    /// Remove the columns entirely
+    Drop,
+    /// Replace values with a salted hash, so equal inputs map to equal tokens
+    Hash { salt: String },
+}
+
+/// Stable pseudonym for a value: equal inputs (and salt) always give the same token
+fn pseudonymize(value: &str, salt: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0]);
+    hasher.update(value.as_bytes());
+    let digest = hasher.finalize();
+    let hex: String = digest[..6].iter().map(|b| format!("{b:02x}")).collect();
+    format!("anon-{hex}")
+}
+
+/// Indices of `n` rows spread evenly over `len` rows, in order
+fn sample_indices(len: usize, n: usize) -> Vec<usize> {
+    if n >= len {
+        return (0..len).collect();
+    }
+    (0..n).map(|i| i * len / n).collect()
+}
+
+/// Sorted indices of the named columns (case-insensitive header match)
+fn redaction_indices(headers: &[String], columns: &[String]) -> Result<Vec<usize>> {
+    let mut indices = Vec::new();
+    for column in columns {
+        // Fail rather than silently export a column the user meant to hide
+        let idx = headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(column.trim()))
+            .with_context(|| {
+                format!(
+                    "Column '{column}' not found for redaction. Available columns: {}",
+                    headers.join(", ")
+                )
+            })?;
+        if !indices.contains(&idx) {
+            indices.push(idx);
+        }
+    }
+    indices.sort_unstable();
+    Ok(indices)
+}
+
+fn redact_rows(
+    headers: &mut Vec<String>,
+    rows: &mut [Vec<CellValue>],
+    indices: &[usize],
+    mode: &RedactMode,
+) {
+    for &idx in indices.iter().rev() {
+        if *mode == RedactMode::Drop {
+            headers.remove(idx);
+        }
+        for row in rows.iter_mut() {
+            if idx >= row.len() {
+                continue;
+            }
+            match mode {
+                RedactMode::Drop => {
+                    row.remove(idx);
+                }
+                // Empty cells stay empty so masking doesn't invent data
+                _ if row[idx].is_empty() => {}
+                RedactMode::Mask => row[idx] = CellValue::String(REDACTED.to_string()),
+                RedactMode::Hash { salt } => {
+                    row[idx] = CellValue::String(pseudonymize(&row[idx].to_raw_string(), salt))
+                }
+            }
+        }
+    }
+}
+
+// ===== Canonical output =====
+
+/// Significant digits kept for floats (Excel's own precision)
+const CANONICAL_DIGITS: usize = 15;
+
+/// Round floats to Excel precision (dropping binary noise like 0.30000000000000004),
+/// fold -0 into 0, and use LF line endings in text
+fn canonical_cell(cell: &CellValue) -> CellValue {
+    let round = |f: f64| {
+        let rounded: f64 = format!("{:.*e}", CANONICAL_DIGITS - 1, f)
+            .parse()
+            .unwrap_or(f);
+        if rounded == 0.0 { 0.0 } else { rounded }
+    };
+    match cell {
+        CellValue::Float(f) => CellValue::Float(round(*f)),
+        CellValue::DateTime(f) => CellValue::DateTime(round(*f)),
+        CellValue::Time(f) => CellValue::Time(round(*f)),
+        CellValue::Duration(f) => CellValue::Duration(round(*f)),
+        CellValue::String(s) => CellValue::String(canonical_text(s)),
+        other => other.clone(),
+    }
+}
+
+fn canonical_text(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+fn canonicalize_rows(headers: &mut [String], rows: &mut [Vec<CellValue>]) {
+    for header in headers.iter_mut() {
+        *header = canonical_text(header);
+    }
+    for row in rows.iter_mut() {
+        for cell in row.iter_mut() {
+            *cell = canonical_cell(cell);
         }
     }
 }
@@ -545,8 +2737,11 @@ mod tests {
 
     #[test]
     fn test_cellvalue_display_error() {
-        let val = CellValue::Error("DIV/0!".to_string());
-        assert_eq!(val.to_string(), "ERROR: DIV/0!");
+        let val = CellValue::Error("#DIV/0!".to_string());
+        assert_eq!(val.to_string(), "#DIV/0!");
+        assert_eq!(val.to_raw_string(), "#DIV/0!");
+        assert_eq!(error_name(&CellErrorType::NA), "#N/A");
+        assert_eq!(error_name(&CellErrorType::Name), "#NAME?");
     }
 
     #[test]
@@ -623,6 +2818,485 @@ mod tests {
         // If file doesn't exist, test passes (integration test needs real file)
     }
 
+    #[test]
+    fn test_column_letter() {
+        assert_eq!(column_letter(0), "A");
+        assert_eq!(column_letter(25), "Z");
+        assert_eq!(column_letter(26), "AA");
+        assert_eq!(column_letter(701), "ZZ");
+        assert_eq!(column_index("A"), Some(0));
+        assert_eq!(column_index("zz"), Some(701));
+        assert_eq!(column_index("A1"), None);
+        assert_eq!(cell_reference("Data", 1, 1), "Data!B2");
+        assert_eq!(cell_reference("Q1 Sales", 0, 27), "'Q1 Sales'!AB1");
+        assert_eq!(cell_reference("Bob's", 4, 0), "'Bob''s'!A5");
+    }
+
+    #[test]
+    fn test_rows_iter() {
+        let path =
+            std::env::temp_dir().join(format!("xleak-test-{}-stream.xlsx", std::process::id()));
+        let headers = vec!["Item".to_string(), "Qty".to_string()];
+        let rows = vec![
+            vec![CellValue::String("Pens".to_string()), CellValue::Float(3.0)],
+            vec![CellValue::Empty, CellValue::Empty],
+            vec![CellValue::Empty, CellValue::Float(2.5)],
+            vec![
+                CellValue::String("Total".to_string()),
+                CellValue::Float(5.5),
+            ],
+        ];
+        let sheets = [crate::save::XlsxSheet {
+            name: "Stock",
+            headers: &headers,
+            rows: &rows,
+        }];
+        crate::save::write_sheets(&path, &sheets).unwrap();
+        let mut workbook = Workbook::open(&path).unwrap();
+
+        workbook.set_skip_footer(1);
+        let streamed = workbook.rows_iter("Stock").unwrap();
+        assert_eq!(streamed.headers, headers);
+        let streamed: Vec<_> = streamed.map(Result::unwrap).collect();
+        assert_eq!(streamed, rows[..3]);
+        assert_eq!(streamed, workbook.load_sheet("Stock").unwrap().rows);
+
+        workbook.set_header_row(HeaderRow::None);
+        let streamed = workbook.rows_iter("Stock").unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(streamed.headers, vec!["A", "B"]);
+        assert_eq!(streamed.count(), 4);
+    }
+
+    #[test]
+    fn test_sheet_window_matches_full_load() {
+        let stem = std::env::temp_dir().join(format!("xleak-test-{}-window", std::process::id()));
+        let xlsx = stem.with_extension("xlsx");
+        let csv = stem.with_extension("csv");
+        let headers = vec!["Quarterly report".to_string()];
+        let text = |s: &str| CellValue::String(s.to_string());
+        let rows = vec![
+            vec![text("Item"), text("Qty"), text("Price")],
+            vec![text("Pens"), CellValue::Int(3), CellValue::Float(1.5)],
+            vec![text("Paper"), CellValue::Int(10), CellValue::Float(4.25)],
+            vec![text("Ink"), CellValue::Int(2), CellValue::Float(12.5)],
+            vec![text("Total"), CellValue::Int(15)],
+        ];
+        let sheets = [crate::save::XlsxSheet {
+            name: "Stock",
+            headers: &headers,
+            rows: &rows,
+        }];
+        crate::save::write_sheets(&xlsx, &sheets).unwrap();
+        std::fs::write(
+            &csv,
+            "Quarterly report\nItem,Qty,Price\nPens,3,1.5\nPaper,10,4.25\nInk,2,12.5\nTotal,15,\n",
+        )
+        .unwrap();
+
+        for path in [&xlsx, &csv] {
+            let mut workbook = Workbook::open(path).unwrap();
+            let name = workbook.sheet_names()[0].clone();
+            workbook.set_header_row(HeaderRow::Skip(1));
+            workbook.set_skip_footer(1);
+            let full = workbook.load_sheet(&name).unwrap();
+            assert_eq!(full.headers, vec!["Item", "Qty", "Price"]);
+            assert_eq!(full.height, 3);
+
+            // A window over the whole sheet leaves out the skipped rows and the footer too
+            let window = workbook.load_sheet_window(&name, (0, 0), (99, 25)).unwrap();
+            assert_eq!(window.headers, full.headers);
+            assert_eq!(window.rows, full.rows);
+            assert_eq!((window.first_row, window.first_col), (2, 0));
+
+            // B2:C4 is the header and first two data rows of the Qty and Price columns
+            let window = workbook.load_sheet_window(&name, (1, 1), (3, 2)).unwrap();
+            assert_eq!(window.headers, full.headers[1..]);
+            let slice: Vec<_> = full.rows[..2].iter().map(|row| row[1..].to_vec()).collect();
+            assert_eq!(window.rows, slice);
+            assert_eq!((window.first_row, window.first_col), (2, 1));
+
+            // The footer is left out even when the window reaches into it
+            let window = workbook.load_sheet_window(&name, (2, 0), (5, 1)).unwrap();
+            assert_eq!(window.headers, vec!["Pens", "3"]);
+            assert_eq!(window.rows.len(), 2);
+            assert!(workbook.load_sheet_window(&name, (5, 0), (5, 2)).is_err());
+        }
+        std::fs::remove_file(&xlsx).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+    }
+
+    #[test]
+    fn test_trim_blank_edges() {
+        let path = std::env::temp_dir().join(format!("xleak-test-{}-trim.csv", std::process::id()));
+        std::fs::write(&path, "Item,Qty,,\nPens,3,,\nTotal,3,,\n,,,\n , ,,\n").unwrap();
+        let mut workbook = Workbook::open(&path).unwrap();
+        let name = workbook.sheet_names()[0].clone();
+
+        let sheet = workbook.load_sheet(&name).unwrap();
+        assert_eq!(sheet.headers, vec!["Item", "Qty"]);
+        assert_eq!(sheet.height, 2);
+        assert_eq!(workbook.load_sheet_lazy(&name).unwrap().width, 2);
+        // The footer is counted from the last row with data
+        workbook.set_skip_footer(1);
+        assert_eq!(workbook.load_sheet(&name).unwrap().height, 1);
+        workbook.set_skip_footer(0);
+
+        workbook.set_trim(false);
+        let sheet = workbook.load_sheet(&name).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sheet.width, 4);
+        assert_eq!(sheet.height, 4);
+    }
+
+    #[test]
+    fn test_sparse_sheet_matches_grid() {
+        let path =
+            std::env::temp_dir().join(format!("xleak-test-{}-sparse.csv", std::process::id()));
+        std::fs::write(&path, "x\n").unwrap();
+        let mut workbook = Workbook::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let text = |s: &str| Data::String(s.to_string());
+        let cells = vec![
+            Cell::new((2, 1), text("Item")),
+            Cell::new((2, 3), text("Qty")),
+            Cell::new((3, 3), Data::Int(3)),
+            Cell::new((3, 1), text("Pens")),
+            Cell::new((5, 2), Data::Error(CellErrorType::Div0)),
+            Cell::new((6, 1), text("")),
+            Cell::new((7, 4), text(" ")),
+        ];
+        let formulas = vec![
+            Cell::new((0, 0), "NOW()".to_string()),
+            Cell::new((3, 3), "B4*3".to_string()),
+        ];
+        assert!(!is_mostly_empty(&cells));
+        let far = [
+            Cell::new((0, 0), Data::Int(1)),
+            Cell::new((1999, 999), Data::Int(2)),
+        ];
+        assert!(is_mostly_empty(&far));
+
+        let settings = [
+            (HeaderRow::First, 0, true),
+            (HeaderRow::First, 0, false),
+            (HeaderRow::None, 1, true),
+            (HeaderRow::Skip(1), 2, true),
+            (HeaderRow::Skip(9), 0, true),
+        ];
+        for (header, skip_footer, trim) in settings {
+            workbook.set_header_row(header);
+            workbook.set_skip_footer(skip_footer);
+            workbook.set_trim(trim);
+            let sparse = workbook.sparse_sheet(cells.clone(), formulas.clone());
+            let (range, has_header) = workbook.table_range(Range::from_sparse(cells.clone()));
+            let grid = LazySheetData::from_range(
+                range,
+                Some(Range::from_sparse(formulas.clone())),
+                has_header,
+            );
+            assert!(sparse.is_sparse() || sparse.height == 0);
+            assert_eq!(sparse.headers, grid.headers);
+            assert_eq!((sparse.width, sparse.height), (grid.width, grid.height));
+            assert_eq!(sparse.origin(), grid.origin());
+            assert_eq!(sparse.get_rows(0, 100), grid.get_rows(0, 100));
+            assert_eq!(sparse.get_rows(2, 1), grid.get_rows(2, 1));
+            let (stats, expected) = (sparse.cell_stats(), grid.cell_stats());
+            assert_eq!(
+                (stats.errors, stats.empty),
+                (expected.errors, expected.empty)
+            );
+            assert_eq!(sparse.to_sheet_data().rows, grid.to_sheet_data().rows);
+        }
+    }
+
+    #[test]
+    fn test_header_row_modes() {
+        let path =
+            std::env::temp_dir().join(format!("xleak-test-{}-banner.csv", std::process::id()));
+        std::fs::write(&path, "Quarterly report\n\nItem,Qty\nPens,3\nPaper,10\n").unwrap();
+        let mut workbook = Workbook::open(&path).unwrap();
+        let name = workbook.sheet_names()[0].clone();
+
+        workbook.set_header_row(HeaderRow::Skip(2));
+        let sheet = workbook.load_sheet(&name).unwrap();
+        assert_eq!(sheet.headers, vec!["Item", "Qty"]);
+        assert_eq!(sheet.height, 2);
+        assert_eq!(sheet.first_row, 3);
+        assert_eq!(workbook.load_sheet_lazy(&name).unwrap().origin(), (3, 0));
+
+        workbook.set_skip_footer(1);
+        let sheet = workbook.load_sheet(&name).unwrap();
+        assert_eq!(
+            sheet.rows,
+            vec![vec![
+                CellValue::String("Pens".to_string()),
+                CellValue::Int(3)
+            ]]
+        );
+        workbook.set_skip_footer(5);
+        assert_eq!(workbook.load_sheet(&name).unwrap().height, 0);
+        workbook.set_skip_footer(0);
+
+        workbook.set_header_row(HeaderRow::None);
+        let lazy = workbook.load_sheet_lazy(&name).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lazy.headers, vec!["A", "B"]);
+        assert_eq!(lazy.height, 5);
+        assert_eq!(lazy.origin(), (0, 0));
+        assert_eq!(
+            lazy.get_values(0, 1)[0],
+            vec![
+                CellValue::String("Quarterly report".to_string()),
+                CellValue::Empty
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_and_duration() {
+        assert_eq!(CellValue::Time(0.5 + 1.0 / 86400.0).to_string(), "12:00:01");
+        assert_eq!(CellValue::Duration(1.5625).to_raw_string(), "37:30:00");
+        assert_eq!(CellValue::Duration(-0.25).to_string(), "-06:00:00");
+        assert_eq!(parse_iso_duration("PT12H30M00S"), Some(0.5208333333333334));
+        assert_eq!(parse_iso_duration("-P1DT12H"), Some(-1.5));
+        assert_eq!(parse_iso_duration("PT1.5X"), None);
+        assert_eq!(parse_iso_duration("12:30"), None);
+    }
+
+    #[test]
+    fn test_date_system() {
+        // 2024-01-15 is 45306 in the 1900 system and 43844 in the 1904 one
+        let mac = ExcelDateTime::new(43844.0, ExcelDateTimeType::DateTime, true);
+        let windows = ExcelDateTime::new(45306.0, ExcelDateTimeType::DateTime, false);
+        assert_eq!(date_serial(&mac), 45306.0);
+        assert_eq!(date_serial(&windows), 45306.0);
+        assert_eq!(
+            SheetData::datatype_to_cellvalue(&Data::DateTime(mac)).to_string(),
+            "2024-01-15"
+        );
+        // --date-system overrides what the workbook records
+        let forced = |date, system| cell_value_in(&Data::DateTime(date), Some(system)).to_string();
+        assert_eq!(forced(windows, DateSystem::D1904), "2028-01-16");
+        assert_eq!(forced(mac, DateSystem::D1900), "2020-01-14");
+        assert_eq!(
+            cell_value_in(&Data::DateTime(mac), None).to_string(),
+            "2024-01-15"
+        );
+    }
+
+    fn redaction_sheet() -> SheetData {
+        SheetData {
+            headers: vec!["Name".to_string(), "SSN".to_string(), "Email".to_string()],
+            rows: vec![vec![
+                CellValue::String("Alice".to_string()),
+                CellValue::String("123-45-6789".to_string()),
+                CellValue::Empty,
+            ]],
+            formulas: vec![vec![None, Some("=A1".to_string()), None]],
+            width: 3,
+            height: 1,
+            first_row: 0,
+            first_col: 0,
+        }
+    }
+
+    #[test]
+    fn test_redact_columns_mask() {
+        let mut sheet = redaction_sheet();
+        sheet
+            .redact_columns(&["ssn".to_string(), "Email".to_string()], &RedactMode::Mask)
+            .unwrap();
+        assert_eq!(sheet.headers.len(), 3);
+        assert_eq!(sheet.rows[0][1], CellValue::String(REDACTED.to_string()));
+        assert_eq!(sheet.rows[0][2], CellValue::Empty);
+        assert_eq!(sheet.formulas[0][1], None);
+    }
+
+    #[test]
+    fn test_redact_columns_drop() {
+        let mut sheet = redaction_sheet();
+        sheet
+            .redact_columns(&["SSN".to_string()], &RedactMode::Drop)
+            .unwrap();
+        assert_eq!(sheet.headers, vec!["Name", "Email"]);
+        assert_eq!(sheet.rows[0].len(), 2);
+        assert_eq!(sheet.formulas[0].len(), 2);
+        assert_eq!(sheet.width, 2);
+    }
+
+    #[test]
+    fn test_redact_unknown_column_errors() {
+        let mut sheet = redaction_sheet();
+        assert!(
+            sheet
+                .redact_columns(&["Phone".to_string()], &RedactMode::Mask)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_hyperlinks_offsets_by_origin() {
+        let mut sheet = redaction_sheet();
+        sheet.first_row = 5;
+        sheet.first_col = 1;
+        let link = |row, col| Hyperlink {
+            row,
+            col,
+            target: "mailto:alice@example.com".to_string(),
+            tooltip: None,
+        };
+        // D6 is the first data row's Email cell; A6 is left of the data
+        sheet.apply_hyperlinks(&[link(5, 3), link(5, 0)], HyperlinkMode::Url);
+        assert_eq!(
+            sheet.rows[0][2],
+            CellValue::String("mailto:alice@example.com".to_string())
+        );
+        assert_eq!(sheet.rows[0][0], CellValue::String("Alice".to_string()));
+    }
+
+    #[test]
+    fn test_remove_hidden_rows_and_columns() {
+        let mut sheet = redaction_sheet();
+        sheet.rows.push(vec![CellValue::String("Bob".to_string())]);
+        sheet.formulas.push(vec![]);
+        sheet.height = 2;
+        sheet.first_row = 2;
+        let hidden = HiddenCells {
+            // Sheet row 3 is the first data row (header on row 2); column B is SSN
+            rows: [2].into(),
+            cols: [1, 40].into(),
+        };
+
+        assert_eq!(sheet.remove_hidden_rows(&hidden), 1);
+        assert_eq!(sheet.remove_hidden_columns(&hidden), 1);
+        assert_eq!(sheet.headers, vec!["Name", "Email"]);
+        assert_eq!(sheet.rows.len(), 1);
+        assert_eq!(sheet.rows[0][0], CellValue::String("Bob".to_string()));
+        assert_eq!((sheet.width, sheet.height), (2, 1));
+    }
+
+    #[test]
+    fn test_select_columns() {
+        let mut sheet = redaction_sheet();
+        sheet
+            .select_columns(&["email".to_string(), "A:B".to_string()])
+            .unwrap();
+        assert_eq!(sheet.headers, vec!["Email", "Name", "SSN"]);
+        assert_eq!(sheet.rows[0][0], CellValue::Empty);
+        assert_eq!(sheet.rows[0][1], CellValue::String("Alice".to_string()));
+        assert_eq!(sheet.formulas[0], vec![None, None, Some("=A1".to_string())]);
+        assert_eq!(sheet.width, 3);
+
+        let mut sheet = redaction_sheet();
+        sheet.select_columns(&["C:B".to_string()]).unwrap();
+        assert_eq!(sheet.headers, vec!["Email", "SSN"]);
+
+        // Letters are sheet columns, even when the data starts further right
+        let mut sheet = redaction_sheet();
+        sheet.first_col = 1;
+        sheet.select_columns(&["B".to_string()]).unwrap();
+        assert_eq!(sheet.headers, vec!["Name"]);
+
+        assert!(
+            redaction_sheet()
+                .select_columns(&["D".to_string()])
+                .is_err()
+        );
+        assert!(
+            redaction_sheet()
+                .select_columns(&["Phone".to_string()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut sheet = SheetData {
+            headers: vec!["Name".to_string(), "Score".to_string()],
+            rows: vec![
+                vec![CellValue::String("bob".to_string()), CellValue::Int(10)],
+                vec![CellValue::String("Alice".to_string()), CellValue::Empty],
+                vec![
+                    CellValue::String("carol".to_string()),
+                    CellValue::Float(9.5),
+                ],
+                vec![CellValue::String("dave".to_string()), CellValue::Int(10)],
+                vec![CellValue::Empty, CellValue::String("n/a".to_string())],
+            ],
+            formulas: Vec::new(),
+            width: 2,
+            height: 5,
+            first_row: 0,
+            first_col: 0,
+        };
+        let names = |sheet: &SheetData| -> Vec<String> {
+            sheet.rows.iter().map(|r| r[0].to_raw_string()).collect()
+        };
+
+        sheet.sort_rows(&["Name".to_string()]).unwrap();
+        assert_eq!(names(&sheet), vec!["Alice", "bob", "carol", "dave", ""]);
+
+        // Descending puts text before numbers but keeps empty cells last; ties use the next key
+        sheet
+            .sort_rows(&["b:desc".to_string(), "Name:DESC".to_string()])
+            .unwrap();
+        assert_eq!(names(&sheet), vec!["", "dave", "bob", "carol", "Alice"]);
+        assert_eq!(sheet.formulas.len(), 5);
+
+        assert!(sheet.sort_rows(&["A:B".to_string()]).is_err());
+        assert!(sheet.sort_rows(&["Missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_redact_columns_hash_is_stable() {
+        let mode = RedactMode::Hash {
+            salt: "s3cret".to_string(),
+        };
+        let mut a = redaction_sheet();
+        let mut b = redaction_sheet();
+        a.redact_columns(&["Name".to_string()], &mode).unwrap();
+        b.redact_columns(&["Name".to_string()], &mode).unwrap();
+
+        let CellValue::String(token) = &a.rows[0][0] else {
+            panic!("expected pseudonym string");
+        };
+        assert!(token.starts_with("anon-"));
+        assert_eq!(a.rows[0][0], b.rows[0][0]);
+        assert_ne!(
+            pseudonymize("Alice", "s3cret"),
+            pseudonymize("Alice", "other")
+        );
+    }
+
+    #[test]
+    fn test_cell_stats() {
+        let sheet = SheetData {
+            headers: vec!["A".to_string(), "B".to_string()],
+            rows: vec![
+                vec![CellValue::Int(1), CellValue::Error("#DIV/0!".to_string())],
+                vec![CellValue::Empty, CellValue::String(String::new())],
+                vec![CellValue::Error("#N/A".to_string()), CellValue::Int(2)],
+            ],
+            formulas: vec![vec![None, None]; 3],
+            width: 2,
+            height: 3,
+            first_row: 0,
+            first_col: 0,
+        };
+        let stats = sheet.cell_stats();
+        assert_eq!(stats.errors, vec![(0, 1), (2, 0)]);
+        assert_eq!(stats.empty, 2);
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        assert_eq!(sample_indices(10, 3), vec![0, 3, 6]);
+        assert_eq!(sample_indices(3, 5), vec![0, 1, 2]);
+        assert!(sample_indices(5, 0).is_empty());
+    }
+
     #[test]
     fn test_sheet_data_structure() {
         // Test SheetData structure can be created
@@ -635,6 +3309,8 @@ mod tests {
             formulas: vec![vec![None, None], vec![None, None]],
             width: 2,
             height: 2,
+            first_row: 0,
+            first_col: 0,
         };
 
         assert_eq!(sheet.width, 2);
@@ -642,4 +3318,21 @@ mod tests {
         assert_eq!(sheet.headers.len(), 2);
         assert_eq!(sheet.rows.len(), 2);
     }
+
+    #[test]
+    fn test_canonical_cell() {
+        assert_eq!(
+            canonical_cell(&CellValue::Float(0.1 + 0.2)),
+            CellValue::Float(0.3)
+        );
+        assert_eq!(canonical_cell(&CellValue::Float(-0.0)).to_raw_string(), "0");
+        assert_eq!(
+            canonical_cell(&CellValue::Float(1234.5678)),
+            CellValue::Float(1234.5678)
+        );
+        assert_eq!(
+            canonical_cell(&CellValue::String("a\r\nb\rc".to_string())),
+            CellValue::String("a\nb\nc".to_string())
+        );
+    }
 }